@@ -0,0 +1,208 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use fbximport::fbx::{import_fbx_from_bytes, encode_polygon_vertex_indices, ImportOptions, PropertyRecordType, ArrayProperty, FbxString};
+use fbximport::fbx::writer::{FbxNode, FbxWriter};
+use fbximport::fbx::bench;
+use fbximport::mesh_processor::MeshProcessor;
+use fbximport::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
+use fbximport::mesh_processor::normals_processor::GenerateNormalsProcessor;
+use fbximport::mesh_processor::weld_processor::WeldVerticesProcessor;
+use fbximport::scene::Scene;
+use fbximport::scene::cache::CacheSource;
+use fbximport::scene::mesh::{Face, Mesh};
+use byteorder::{LittleEndian, WriteBytesExt};
+
+/* Flat grid of quads with grid_size * grid_size faces, as a Geometry node */
+fn grid_geometry(id: i64, grid_size: usize) -> FbxNode {
+    let row = grid_size + 1;
+    let mut coordinates = Vec::with_capacity(row * row * 3);
+    for y in 0..row {
+        for x in 0..row {
+            coordinates.extend_from_slice(&[x as f64, y as f64, 0.0]);
+        }
+    }
+
+    let mut quads = Vec::with_capacity(grid_size * grid_size);
+    for y in 0..grid_size {
+        for x in 0..grid_size {
+            let corner = (y * row + x) as u32;
+            quads.push([corner, corner + 1, corner + 1 + row as u32, corner + row as u32]);
+        }
+    }
+    let indices = encode_polygon_vertex_indices(&quads.iter().map(|q| q.as_slice()).collect::<Vec<_>>());
+
+    FbxNode::new("Geometry")
+        .with_property(PropertyRecordType::SignedInt64(id))
+        .with_property(PropertyRecordType::String(FbxString::new(&format!("grid{}", id)).with_class("Geometry")))
+        .with_property(PropertyRecordType::String(FbxString::new("Mesh")))
+        .with_child(FbxNode::new("Vertices")
+            .with_property(PropertyRecordType::DoubleArray(ArrayProperty::from_values(coordinates))))
+        .with_child(FbxNode::new("PolygonVertexIndex")
+            .with_property(PropertyRecordType::SignedInt32Array(ArrayProperty::from_values(indices))))
+}
+
+/* Valid binary FBX holding mesh_count grids, sized by how many quads each grid has */
+fn generate_fixture(mesh_count: usize, grid_size: usize, compress_arrays: bool) -> Vec<u8> {
+    let mut objects = FbxNode::new("Objects");
+    for id in 0..mesh_count {
+        objects = objects.with_child(grid_geometry(id as i64, grid_size));
+    }
+
+    FbxWriter::new(7400)
+        .with_compressed_arrays(compress_arrays)
+        .with_node(FbxNode::new("Creator").with_property(PropertyRecordType::String(FbxString::new("fbximport benches"))))
+        .with_node(objects)
+        .write()
+}
+
+/* Animation curve nodes with a Properties70 block each, mostly small nodes whose names repeat over and over */
+fn generate_animation_fixture(curve_node_count: usize) -> Vec<u8> {
+    let mut objects = FbxNode::new("Objects");
+    for id in 0..curve_node_count {
+        let mut properties = FbxNode::new("Properties70");
+        for axis in &["d|X", "d|Y", "d|Z"] {
+            properties = properties.with_child(FbxNode::new("P")
+                .with_property(PropertyRecordType::String(FbxString::new(axis)))
+                .with_property(PropertyRecordType::String(FbxString::new("Number")))
+                .with_property(PropertyRecordType::String(FbxString::new("")))
+                .with_property(PropertyRecordType::String(FbxString::new("A")))
+                .with_property(PropertyRecordType::Double(id as f64)));
+        }
+        objects = objects.with_child(FbxNode::new("AnimationCurveNode")
+            .with_property(PropertyRecordType::SignedInt64(id as i64))
+            .with_property(PropertyRecordType::String(FbxString::new("T").with_class("AnimCurveNode")))
+            .with_property(PropertyRecordType::String(FbxString::new("")))
+            .with_child(properties));
+    }
+
+    let mut connections = FbxNode::new("Connections");
+    for id in 0..curve_node_count {
+        connections = connections.with_child(FbxNode::new("C")
+            .with_property(PropertyRecordType::String(FbxString::new("OO")))
+            .with_property(PropertyRecordType::SignedInt64(id as i64))
+            .with_property(PropertyRecordType::SignedInt64(0)));
+    }
+
+    FbxWriter::new(7400)
+        .with_node(objects)
+        .with_node(connections)
+        .write()
+}
+
+fn ngon(vertex_count: usize) -> Mesh {
+    // Star shaped so the triangulator has reflex vertices to deal with
+    let vertices = (0..vertex_count)
+        .map(|i| {
+            let angle = 2.0 * std::f32::consts::PI * i as f32 / vertex_count as f32;
+            let radius = if i % 2 == 0 { 1.0 } else { 0.6 };
+            glm::vec3(radius * angle.cos(), radius * angle.sin(), 0.0)
+        })
+        .collect();
+    Mesh::new("ngon".to_string(), vertices, vec![Face::new((0..vertex_count as i32).collect())])
+}
+
+/* Circle with a slight wobble, so there are a few reflex vertices spread all around a large polygon */
+fn wobbly_circle(vertex_count: usize) -> Mesh {
+    let vertices = (0..vertex_count)
+        .map(|i| {
+            let angle = 2.0 * std::f32::consts::PI * i as f32 / vertex_count as f32;
+            let radius = 100.0 + (angle * 50.0).sin() * 0.5;
+            glm::vec3(radius * angle.cos(), radius * angle.sin(), 0.0)
+        })
+        .collect();
+    Mesh::new("circle".to_string(), vertices, vec![Face::new((0..vertex_count as i32).collect())])
+}
+
+fn parse_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_nodes");
+    for grid_size in &[64, 256] {
+        let fixture = generate_fixture(8, *grid_size, true);
+        group.throughput(Throughput::Bytes(fixture.len() as u64));
+        group.bench_with_input(BenchmarkId::new("tree", grid_size), &fixture, |b, fixture| {
+            b.iter(|| bench::parse_nodes(fixture))
+        });
+        group.bench_with_input(BenchmarkId::new("arena", grid_size), &fixture, |b, fixture| {
+            b.iter(|| bench::parse_arena_document(fixture))
+        });
+    }
+
+    let fixture = generate_animation_fixture(10_000);
+    group.throughput(Throughput::Bytes(fixture.len() as u64));
+    group.bench_with_input(BenchmarkId::new("tree", "animation"), &fixture, |b, fixture| {
+        b.iter(|| bench::parse_nodes(fixture))
+    });
+    group.finish();
+}
+
+fn array_benchmarks(c: &mut Criterion) {
+    let values: Vec<u8> = (0..1_000_000).flat_map(|i| (i as f64).to_le_bytes().to_vec()).collect();
+    let compressed = deflate::deflate_bytes_zlib(&values);
+    let mut property = vec![b'd'];
+    property.write_u32::<LittleEndian>(1_000_000).unwrap();
+    property.write_u32::<LittleEndian>(1).unwrap();
+    property.write_u32::<LittleEndian>(compressed.len() as u32).unwrap();
+    property.extend_from_slice(&compressed);
+
+    c.bench_function("decode_compressed_f64_array", |b| {
+        b.iter(|| bench::decode_f64_array_property(&property))
+    });
+}
+
+fn face_benchmarks(c: &mut Criterion) {
+    let indices: Vec<i32> = (0..1_000_000).map(|i| if i % 4 == 3 { i ^ -1 } else { i }).collect();
+
+    c.bench_function("get_faces", |b| b.iter(|| bench::count_faces(&indices)));
+}
+
+fn triangulate_group(c: &mut Criterion, name: &str, vertex_counts: &[usize], shape: fn(usize) -> Mesh) {
+    let mut group = c.benchmark_group(name);
+    for vertex_count in vertex_counts {
+        let mesh = shape(*vertex_count);
+        group.bench_with_input(BenchmarkId::from_parameter(vertex_count), &mesh, |b, mesh| {
+            let processor = TriangulateMeshProcessor::new();
+            b.iter(|| {
+                let mut mesh = mesh.clone();
+                processor.process(&mut mesh);
+                mesh
+            })
+        });
+    }
+    group.finish();
+}
+
+fn triangulation_benchmarks(c: &mut Criterion) {
+    triangulate_group(c, "triangulate_ngon", &[8, 64, 512, 4096], ngon);
+    triangulate_group(c, "triangulate_wobbly_circle", &[1000, 10000], wobbly_circle);
+}
+
+fn import_benchmarks(c: &mut Criterion) {
+    let fixture = generate_fixture(16, 128, true);
+
+    c.bench_function("import_fbx_from_bytes", |b| {
+        b.iter(|| {
+            let processors: Vec<Box<dyn MeshProcessor>> = vec![Box::new(TriangulateMeshProcessor::new())];
+            import_fbx_from_bytes(&fixture, processors, ImportOptions::new())
+        })
+    });
+}
+
+/* Loading a cached scene against importing the file it came from with the processors an editor would typically
+   run, the cache should win by at least an order of magnitude */
+fn cache_benchmarks(c: &mut Criterion) {
+    let fixture = generate_fixture(16, 128, true);
+    let processors = || -> Vec<Box<dyn MeshProcessor>> {
+        vec![Box::new(WeldVerticesProcessor::new()), Box::new(TriangulateMeshProcessor::new()), Box::new(GenerateNormalsProcessor::new())]
+    };
+    let scene = import_fbx_from_bytes(&fixture, processors(), ImportOptions::new()).unwrap();
+    let source = CacheSource { size: fixture.len() as u64, modified: 0, hash: 0, settings: 0 };
+    let mut cache = Vec::new();
+    scene.write_cache(&mut cache, &source).unwrap();
+
+    let mut group = c.benchmark_group("scene_cache");
+    group.bench_function("import", |b| b.iter(|| import_fbx_from_bytes(&fixture, processors(), ImportOptions::new())));
+    group.bench_function("read_cache", |b| b.iter(|| Scene::read_cache(cache.as_slice()).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, parse_benchmarks, array_benchmarks, face_benchmarks, triangulation_benchmarks, import_benchmarks,
+                 cache_benchmarks);
+criterion_main!(benches);
\ No newline at end of file
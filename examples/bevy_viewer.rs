@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+use fbximport::fbx::import_fbx;
+use fbximport::mesh_processor::MeshProcessor;
+use fbximport::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
+use fbximport::mesh_processor::normals_processor::GenerateNormalsProcessor;
+
+/* Imports the FBX file given on the command line and shows its meshes:
+
+   cargo run --example bevy_viewer --features bevy -- path/to/file.fbx */
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let path = std::env::args().nth(1).expect("usage: bevy_viewer <file.fbx>");
+
+    let mut processors = Vec::<Box<dyn MeshProcessor>>::new();
+    processors.push(Box::new(TriangulateMeshProcessor::new()));
+    processors.push(Box::new(GenerateNormalsProcessor::new()));
+    let scene = import_fbx(&path, processors).expect("could not import file");
+
+    let material = materials.add(StandardMaterial::default());
+    for mesh in scene.meshes() {
+        commands.spawn(PbrBundle {
+            mesh: meshes.add(mesh.to_bevy_mesh()),
+            material: material.clone(),
+            ..default()
+        });
+    }
+
+    commands.spawn(PointLightBundle {
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..default()
+    });
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use fbximport::fbx::{import_fbx_from_bytes, ImportOptions};
+use fbximport::mesh_processor::MeshProcessor;
+use fbximport::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
+
+// Whole files, seeded with the cube in corpus/import_fbx
+fuzz_target!(|data: &[u8]| {
+    let processors: Vec<Box<dyn MeshProcessor>> = vec![Box::new(TriangulateMeshProcessor::new())];
+    let _ = import_fbx_from_bytes(data, processors, ImportOptions::new());
+});
\ No newline at end of file
@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+// Header followed by the node tree
+fuzz_target!(|data: &[u8]| {
+    fbximport::fbx::fuzz::parse_nodes(data);
+});
\ No newline at end of file
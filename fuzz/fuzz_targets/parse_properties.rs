@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+// First two bytes are the declared property count, the rest is the property stream
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 2 {
+        return;
+    }
+
+    let num_properties = u16::from_le_bytes([data[0], data[1]]) as usize;
+    fbximport::fbx::fuzz::parse_properties(&data[2..], num_properties);
+});
\ No newline at end of file
@@ -1,27 +1,163 @@
 use std::str::Utf8Error;
 use std::string::FromUtf8Error;
-use std::io::{Error, BufReader, Seek};
+use std::io::{Error, BufRead, BufReader, Cursor, Read, Seek};
 use std::fs::File;
-use crate::fbx::property::PropertyRecordType;
-use crate::fbx::node::{NodeRecord, parse_nodes};
+use std::path::Path;
+use crate::fbx::node_reader::DEFAULT_MAX_DEPTH;
 use crate::fbx::header::parse_header;
-use multimap::MultiMap;
-use crate::fbx::node_collection::NodeCollection;
+use crate::fbx::gzip::{is_gzip, gunzip, read_to_memory};
+use crate::fbx::progress::Progress;
 use crate::fbx::importer::import;
-use crate::mesh_processor::MeshProcessor;
+use crate::mesh_processor::{MeshProcessor, process_meshes};
 use crate::scene::Scene;
+use crate::scene::timings::ImportTimings;
+use crate::scene::mesh::content_hash::Fnv1a;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
 mod property;
+mod array_view;
 mod node;
+mod node_reader;
+mod name_interner;
 mod header;
 mod importer;
 mod node_collection;
+mod arena_document;
+mod polygon_vertex_index;
+mod metadata;
+mod gzip;
+mod progress;
+mod batch;
+mod cached;
+mod info;
+#[cfg(feature = "async")]
+mod async_import;
+mod hierarchy;
+mod properties70;
+mod materials;
+mod textures;
+mod animation;
+mod global_settings;
+mod skin;
+mod blend_shapes;
+mod cameras;
+mod lights;
+mod documents;
+mod definitions;
+#[cfg(test)]
+mod fixture;
+pub mod writer;
+pub mod debug;
+
+pub use crate::fbx::property::{PropertyRecordType, ArrayProperty, FbxString};
+pub use crate::fbx::array_view::ArrayView;
+pub use crate::fbx::arena_document::{ArenaDocument, DocumentError, NodeList, NodeRef, PropertyError};
+pub use crate::fbx::header::Header;
+pub use crate::fbx::metadata::{ImportMetadata, ImportResult, ImportError};
+pub use crate::fbx::importer::ImportError as ConversionError;
+pub use crate::fbx::node_collection::Error as NodeError;
+pub use crate::fbx::progress::ProgressEvent;
+pub use crate::fbx::cached::{import_fbx_cached, CachedImport};
+pub use crate::fbx::info::{read_file_info, FileInfo, MeshInfo, AnimationStackInfo};
+pub use crate::fbx::batch::{import_fbx_batch, import_fbx_batch_with_progress, BatchResult, BatchSummary};
+#[cfg(feature = "async")]
+pub use crate::fbx::async_import::import_fbx_async;
+pub use crate::fbx::polygon_vertex_index::{FaceIterator, encode_faces, decode_polygon_vertex_indices, encode_polygon_vertex_indices};
 
 #[derive(Debug)]
 enum ParseError {
     ValidationError(String),
     FormatError,
+    // Type code that is not part of the format, offset is where the code was read in the stream
+    UnknownPropertyType { code: u8, offset: u64 },
     IOError(Error),
+    // Error detected at offset while reading the node at path, such as Objects/Geometry[12]/Vertices
+    InNode { offset: u64, path: String, error: Box<ParseError> },
+    // The file ends before expected_at. Context says what needed the missing bytes, phrased to go before the offset,
+    // like "node 'Objects/Geometry' claims to end".
+    Truncated { expected_at: u64, file_length: u64, context: String },
+    // The import was cancelled while the nodes were read
+    Cancelled,
+}
+
+/* Number with its thousands separated by commas, for offsets in messages meant for people */
+fn with_thousands_separators(value: u64) -> String {
+    let digits = value.to_string();
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(digit);
+    }
+    result
+}
+
+impl ParseError {
+    /* The error itself, without where it was detected */
+    #[cfg(test)]
+    fn into_cause(self) -> ParseError {
+        match self {
+            ParseError::InNode { error, .. } => error.into_cause(),
+            error => error
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::ValidationError(message) => write!(f, "{}", message),
+            ParseError::FormatError => write!(f, "text is not valid UTF-8"),
+            ParseError::UnknownPropertyType { code, offset } =>
+                write!(f, "unknown property type {:?} at offset {}", *code as char, offset),
+            ParseError::IOError(e) => write!(f, "{}", e),
+            ParseError::InNode { offset, path, error } if path.is_empty() => write!(f, "at byte {:#X}: {}", offset, error),
+            ParseError::InNode { offset, path, error } => write!(f, "at byte {:#X} in {}: {}", offset, path, error),
+            ParseError::Truncated { expected_at, file_length, context } =>
+                write!(f, "file appears truncated: {} at byte {} but the file is {} bytes", context,
+                       with_thousands_separators(*expected_at), with_thousands_separators(*file_length)),
+            ParseError::Cancelled => write!(f, "import was cancelled"),
+        }
+    }
+}
+
+/* io::Error is neither, the copy keeps its kind and message */
+impl Clone for ParseError {
+    fn clone(&self) -> Self {
+        match self {
+            ParseError::ValidationError(message) => ParseError::ValidationError(message.clone()),
+            ParseError::FormatError => ParseError::FormatError,
+            ParseError::UnknownPropertyType { code, offset } => ParseError::UnknownPropertyType { code: *code, offset: *offset },
+            ParseError::IOError(e) => ParseError::IOError(Error::new(e.kind(), e.to_string())),
+            ParseError::InNode { offset, path, error } => ParseError::InNode { offset: *offset, path: path.clone(), error: error.clone() },
+            ParseError::Truncated { expected_at, file_length, context } =>
+                ParseError::Truncated { expected_at: *expected_at, file_length: *file_length, context: context.clone() },
+            ParseError::Cancelled => ParseError::Cancelled,
+        }
+    }
+}
+
+/* I/O errors compare by their kind */
+impl PartialEq for ParseError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ParseError::ValidationError(a), ParseError::ValidationError(b)) => a == b,
+            (ParseError::FormatError, ParseError::FormatError) => true,
+            (ParseError::UnknownPropertyType { code: a, offset: a_offset }, ParseError::UnknownPropertyType { code: b, offset: b_offset }) =>
+                a == b && a_offset == b_offset,
+            (ParseError::IOError(a), ParseError::IOError(b)) => a.kind() == b.kind(),
+            (ParseError::InNode { offset: a_offset, path: a_path, error: a }, ParseError::InNode { offset: b_offset, path: b_path, error: b }) =>
+                a_offset == b_offset && a_path == b_path && a == b,
+            (ParseError::Truncated { expected_at: a_at, file_length: a_length, context: a },
+             ParseError::Truncated { expected_at: b_at, file_length: b_length, context: b }) =>
+                a_at == b_at && a_length == b_length && a == b,
+            (ParseError::Cancelled, ParseError::Cancelled) => true,
+            _ => false,
+        }
+    }
 }
 
 impl From<std::io::Error> for ParseError {
@@ -44,61 +180,605 @@ impl From<FromUtf8Error> for ParseError {
 
 type ParseResult<T> = Result<T, ParseError>;
 
-fn parse_fbx(path: &str) -> NodeCollection {
-    let file = File::open(path)
-        .expect("Could not open file");
+// Top level nodes the importer never looks at. FBXHeaderExtension can hold a large embedded thumbnail.
+const UNUSED_TOP_LEVEL_NODES: [&str; 3] = ["FBXHeaderExtension", "References", "Takes"];
 
-    let mut reader = BufReader::new(file);
-    let length = reader.stream_len().unwrap() as usize;
-    let _header = parse_header(&mut reader).unwrap();
+// Largest file read into memory, gzip compressed files are inflated to memory before parsing
+const DEFAULT_MAX_IN_MEMORY_SIZE: u64 = 4 << 30;
 
-    parse_nodes(
-        &mut reader,
-        length).unwrap()
+/* What the importer does with vertices whose position is NaN or infinite, which otherwise spread into normals,
+   projections and bounds computed from them */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NanPolicy {
+    // Fail the import
+    Error,
+    // Remove the vertices along with every face using them, the remaining faces are renumbered
+    RemoveAffectedFaces,
+    // Move the vertices to the origin and keep the faces
+    ReplaceWithZero,
 }
 
-pub fn import_fbx(path: &str, mesh_processors: Vec<Box<dyn MeshProcessor>>) -> Option<Scene> {
-    let nodes = parse_fbx(path);
+pub struct ImportOptions {
+    pub(crate) skip_unused_nodes: bool,
+    pub(crate) max_node_depth: usize,
+    pub(crate) strict: bool,
+    pub(crate) keep_degenerate_faces: bool,
+    pub(crate) rename_duplicate_meshes: bool,
+    pub(crate) nan_policy: NanPolicy,
+    pub(crate) include_orphans: bool,
+    pub(crate) include_hidden: bool,
+    pub(crate) include_animation: bool,
+    pub(crate) max_in_memory_size: u64,
+    pub(crate) max_parallel_files: usize,
+    // Raised by import_fbx_async when its future is dropped, checked for every node read and every mesh processed
+    pub(crate) cancel: Option<Arc<AtomicBool>>,
+}
 
-    if let Some(mut scene) = import(nodes) {
-        for mesh in &mut scene.meshes {
-            for processor in &mesh_processors {
-                processor.process(mesh);
-            }
+impl ImportOptions {
+    pub fn new() -> Self {
+        ImportOptions {
+            skip_unused_nodes: true,
+            max_node_depth: DEFAULT_MAX_DEPTH,
+            strict: false,
+            keep_degenerate_faces: false,
+            rename_duplicate_meshes: true,
+            nan_policy: NanPolicy::RemoveAffectedFaces,
+            include_orphans: false,
+            include_hidden: true,
+            include_animation: true,
+            max_in_memory_size: DEFAULT_MAX_IN_MEMORY_SIZE,
+            max_parallel_files: 0,
+            cancel: None,
         }
+    }
+
+    /* Fail the import on corrupt geometry, instead of leaving out the broken parts and reporting them */
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /* Keep faces with fewer than 3 indices in Mesh::degenerate_faces instead of dropping them */
+    pub fn with_keep_degenerate_faces(mut self, keep_degenerate_faces: bool) -> Self {
+        self.keep_degenerate_faces = keep_degenerate_faces;
+        self
+    }
+
+    /* Meshes sharing a name get numbered suffixes in file order, like Cube, Cube.001 and Cube.002. Turn off for
+       pipelines that identify meshes some other way and want the names as written. */
+    pub fn with_rename_duplicate_meshes(mut self, rename_duplicate_meshes: bool) -> Self {
+        self.rename_duplicate_meshes = rename_duplicate_meshes;
+        self
+    }
+
+    pub fn with_nan_policy(mut self, nan_policy: NanPolicy) -> Self {
+        self.nan_policy = nan_policy;
+        self
+    }
+
+    /* Import the objects that are in the file but not part of the scene, left behind by editing sessions, instead
+       of leaving them out. Their nodes are grouped under a node called "orphans". */
+    pub fn with_include_orphans(mut self, include_orphans: bool) -> Self {
+        self.include_orphans = include_orphans;
+        self
+    }
+
+    /* Turn off to leave out the nodes hidden in the authoring tool along with their children. Hidden nodes are
+       imported by default, see Node::is_hidden. */
+    pub fn with_include_hidden(mut self, include_hidden: bool) -> Self {
+        self.include_hidden = include_hidden;
+        self
+    }
+
+    /* Turn off to import the scene without its animation layers, for when only the meshes are of interest. The
+       animation curves are then never decoded, which is most of the work for files with a lot of animation. */
+    pub fn with_include_animation(mut self, include_animation: bool) -> Self {
+        self.include_animation = include_animation;
+        self
+    }
+
+    /* Files nesting nodes deeper than this fail to import instead of exhausting memory */
+    pub fn with_max_node_depth(mut self, max_node_depth: usize) -> Self {
+        self.max_node_depth = max_node_depth;
+        self
+    }
+
+    /* Limit on how large input read into memory may be, which is gzip compressed input once inflated and anything
+       passed to import_fbx_from_reader. A corrupt or hostile stream fails instead of exhausting memory. */
+    pub fn with_max_in_memory_size(mut self, max_in_memory_size: u64) -> Self {
+        self.max_in_memory_size = max_in_memory_size;
+        self
+    }
+
+    /* How many files import_fbx_batch imports at the same time, 0 for one per core */
+    pub fn with_max_parallel_files(mut self, max_parallel_files: usize) -> Self {
+        self.max_parallel_files = max_parallel_files;
+        self
+    }
+
+    /* Hash of the options changing what an import gives, and of the names and fingerprints of the processors, to
+       tell whether a cached scene was imported the same way */
+    pub(crate) fn fingerprint(&self, mesh_processors: &[Box<dyn MeshProcessor>]) -> u64 {
+        let nan_policy = match self.nan_policy {
+            NanPolicy::Error => 0,
+            NanPolicy::RemoveAffectedFaces => 1,
+            NanPolicy::ReplaceWithZero => 2,
+        };
+        let flags = [self.skip_unused_nodes, self.strict, self.keep_degenerate_faces, self.rename_duplicate_meshes,
+            self.include_orphans, self.include_hidden, self.include_animation];
+
+        let mut hasher = Fnv1a::new();
+        hasher.write(&(self.max_node_depth as u64).to_le_bytes());
+        hasher.write(&[nan_policy]);
+        hasher.write(&flags.iter().map(|flag| *flag as u8).collect::<Vec<u8>>());
+        for processor in mesh_processors {
+            hasher.write(processor.name().as_bytes());
+            hasher.write(&[0]);
+            hasher.write(&processor.fingerprint().to_le_bytes());
+        }
+        hasher.0
+    }
+
+    /* Turn off to parse every top level node, including the ones the importer does not use */
+    pub fn with_skip_unused_nodes(mut self, skip_unused_nodes: bool) -> Self {
+        self.skip_unused_nodes = skip_unused_nodes;
+        self
+    }
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        ImportOptions::new()
+    }
+}
+
+fn parse_fbx<R: Read + Seek>(reader: &mut R, options: &ImportOptions) -> ParseResult<(Header, ArenaDocument)> {
+    let skipped: &[&str] = if options.skip_unused_nodes { &UNUSED_TOP_LEVEL_NODES } else { &[] };
+    let header = parse_header(reader, options.strict)?;
+    let document = ArenaDocument::parse_cancellable(reader, header.version, skipped, options.max_node_depth, options.strict,
+                                                    options.cancel.as_deref())?;
+    Ok((header, document))
+}
+
+pub(crate) fn no_progress(_: ProgressEvent) -> bool {
+    true
+}
+
+/* Prints why the import failed, for the entry points that only tell whether it did */
+pub(crate) fn report<T>(result: Result<T, ImportError>) -> Option<T> {
+    result.map_err(|e| eprintln!("{}, aborting import.", e)).ok()
+}
+
+/* Converts the parsed nodes and runs the processors. start is when parsing began. */
+fn import_nodes((header, document): (Header, ArenaDocument), mesh_processors: &[Box<dyn MeshProcessor>], options: &ImportOptions,
+                start: Instant, file_size: u64, progress: Progress) -> Result<ImportResult, ImportError> {
+    let parse_time = start.elapsed();
+    if !progress(ProgressEvent::Converting) {
+        return Err(ImportError::Cancelled);
+    }
+    let conversion_start = Instant::now();
+    let scene = import(&document, options)?;
+    let conversion_time = conversion_start.elapsed();
+
+    let mut scene = scene.ok_or(ImportError::NoObjects)?;
+    if !progress(ProgressEvent::Processing { meshes: scene.meshes.len() }) {
+        return Err(ImportError::Cancelled);
+    }
+    let processors = process_meshes(&mut scene.meshes, mesh_processors, options.cancel.as_deref())
+        .map_err(|processor| ImportError::ProcessorFailed { processor: processor.name().to_string() })?;
+    if options.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+        return Err(ImportError::Cancelled);
+    }
+    scene.timings = ImportTimings {
+        parse: parse_time,
+        conversion: conversion_time,
+        processors,
+    };
+    scene.header = Some(header);
+
+    let mut metadata = ImportMetadata {
+        file_size,
+        timings: scene.timings.clone(),
+        ..Default::default()
+    };
+    metadata.add_nodes(document.nodes());
+    metadata.add_scene(&scene);
+    metadata.wall_time = start.elapsed();
+    progress(ProgressEvent::Finished);
+    Ok(ImportResult { scene, metadata })
+}
+
+pub fn import_fbx(path: &str, mesh_processors: Vec<Box<dyn MeshProcessor>>) -> Option<Scene> {
+    import_fbx_with_options(path, mesh_processors, ImportOptions::new())
+}
+
+pub fn import_fbx_with_options(path: &str, mesh_processors: Vec<Box<dyn MeshProcessor>>, options: ImportOptions) -> Option<Scene> {
+    import_fbx_with_metadata(path, mesh_processors, options).map(|result| result.scene)
+}
+
+/* Same as import_fbx_with_options, along with numbers about the import like its duration and the size of the file */
+pub fn import_fbx_with_metadata(path: &str, mesh_processors: Vec<Box<dyn MeshProcessor>>, options: ImportOptions) -> Option<ImportResult> {
+    report(import_path(Path::new(path), &mesh_processors, &options, &mut no_progress))
+}
+
+/* Imports the file at path, reporting the phases to progress. Fails with ImportError::Cancelled if progress asked for
+   it to stop. */
+pub(crate) fn import_path(path: &Path, mesh_processors: &[Box<dyn MeshProcessor>], options: &ImportOptions, progress: Progress) -> Result<ImportResult, ImportError> {
+    let file = File::open(path)?;
+    let file_size = file.metadata().map_or(0, |metadata| metadata.len());
+    if !progress(ProgressEvent::Parsing { file_size }) {
+        return Err(ImportError::Cancelled);
+    }
+
+    let start = Instant::now();
+    let parsed = parse_file(file, options)?;
+    let mut result = import_nodes(parsed, mesh_processors, options, start, file_size, progress)?;
+    // Relative texture paths are relative to the file
+    let directory = path.parent().map(|p| p.to_path_buf());
+    for texture in &mut result.scene.textures {
+        texture.source_directory = directory.clone();
+    }
+    Ok(result)
+}
+
+/* Plain files are parsed as they are read, gzip compressed ones need to be inflated first to be able to seek */
+fn parse_file(file: File, options: &ImportOptions) -> ParseResult<(Header, ArenaDocument)> {
+    let mut reader = BufReader::new(file);
+    if reader.fill_buf().is_ok_and(is_gzip) {
+        let bytes = read_to_memory(reader, options.max_in_memory_size)?;
+        parse_fbx(&mut Cursor::new(bytes), options)
+    } else {
+        parse_fbx(&mut reader, options)
+    }
+}
+
+/* Imports a file already loaded into memory, plain or gzip compressed */
+pub fn import_fbx_from_bytes(bytes: &[u8], mesh_processors: Vec<Box<dyn MeshProcessor>>, options: ImportOptions) -> Option<Scene> {
+    import_fbx_from_bytes_with_metadata(bytes, mesh_processors, options).map(|result| result.scene)
+}
+
+pub fn import_fbx_from_bytes_with_metadata(bytes: &[u8], mesh_processors: Vec<Box<dyn MeshProcessor>>, options: ImportOptions) -> Option<ImportResult> {
+    report(import_bytes(bytes, &mesh_processors, &options))
+}
+
+fn import_bytes(bytes: &[u8], mesh_processors: &[Box<dyn MeshProcessor>], options: &ImportOptions) -> Result<ImportResult, ImportError> {
+    let start = Instant::now();
+    let parsed = if is_gzip(bytes) {
+        parse_fbx(&mut Cursor::new(gunzip(bytes, options.max_in_memory_size)?), options)?
+    } else {
+        parse_fbx(&mut Cursor::new(bytes), options)?
+    };
+    import_nodes(parsed, mesh_processors, options, start, bytes.len() as u64, &mut no_progress)
+}
+
+/* Imports from a stream that cannot seek, like a network download, plain or gzip compressed. The whole stream is
+   read into memory first, up to ImportOptions::with_max_in_memory_size. */
+pub fn import_fbx_from_reader<R: Read>(reader: R, mesh_processors: Vec<Box<dyn MeshProcessor>>, options: ImportOptions) -> Option<Scene> {
+    import_fbx_from_reader_with_metadata(reader, mesh_processors, options).map(|result| result.scene)
+}
+
+/* Same as import_fbx_from_reader. The file size in the metadata is the size after inflating. */
+pub fn import_fbx_from_reader_with_metadata<R: Read>(reader: R, mesh_processors: Vec<Box<dyn MeshProcessor>>, options: ImportOptions) -> Option<ImportResult> {
+    report(import_reader(reader, &mesh_processors, &options))
+}
+
+fn import_reader<R: Read>(reader: R, mesh_processors: &[Box<dyn MeshProcessor>], options: &ImportOptions) -> Result<ImportResult, ImportError> {
+    let start = Instant::now();
+    let bytes = read_to_memory(reader, options.max_in_memory_size)?;
+    let parsed = parse_fbx(&mut Cursor::new(&bytes), options)?;
+    import_nodes(parsed, mesh_processors, options, start, bytes.len() as u64, &mut no_progress)
+}
+
+/* Entry points into internals for the benchmarks in benches/ and the allocation tests in tests/, not part of the API */
+#[doc(hidden)]
+pub mod bench {
+    use super::{ArrayProperty, PropertyRecordType};
+    use super::writer::{FbxNode, FbxWriter};
+    use std::io::Cursor;
+
+    pub fn parse_nodes(bytes: &[u8]) -> usize {
+        let mut reader = Cursor::new(bytes);
+        let header = super::header::parse_header(&mut reader, true).unwrap();
+        super::node::parse_nodes_skipping(&mut reader, header.version, &[], super::node_reader::DEFAULT_MAX_DEPTH, true).unwrap().len()
+    }
+
+    pub fn parse_arena_document(bytes: &[u8]) -> usize {
+        super::ArenaDocument::from_bytes(bytes).unwrap().node_count()
+    }
+
+    /* Parses a single array property and decodes its values */
+    pub fn decode_f64_array_property(bytes: &[u8]) -> usize {
+        let property = super::property::parse_property(&mut Cursor::new(bytes), bytes.len() as u64).unwrap();
+        property.as_f64_array().unwrap().len()
+    }
 
-        return Some(scene);
+    pub fn count_faces(indices: &[i32]) -> usize {
+        super::FaceIterator::from(indices).count()
     }
 
-    None
+    /* Geometry node holding the given arrays, read the way the importer reads it */
+    pub struct Geometry(super::ArenaDocument);
+
+    impl Geometry {
+        pub fn new(coordinates: Vec<f64>, indices: Vec<i32>) -> Self {
+            let bytes = FbxWriter::new(7400)
+                .with_node(FbxNode::new("Geometry")
+                    .with_child(FbxNode::new("Vertices").with_property(PropertyRecordType::DoubleArray(ArrayProperty::from_values(coordinates))))
+                    .with_child(FbxNode::new("PolygonVertexIndex").with_property(PropertyRecordType::SignedInt32Array(ArrayProperty::from_values(indices)))))
+                .write();
+            Geometry(super::ArenaDocument::from_bytes(&bytes).unwrap())
+        }
+
+        pub fn read_faces(&self) -> usize {
+            super::importer::get_faces(self.0.get("Geometry").unwrap(), "bench", true).unwrap().len()
+        }
+
+        pub fn read_vertices(&self) -> usize {
+            super::importer::get_vertices(self.0.get("Geometry").unwrap(), "bench", true).unwrap().map_or(0, |vertices| vertices.len())
+        }
+    }
 }
 
-fn print_property(prop: &PropertyRecordType, indent: usize) {
-    print!("{}", String::from_utf8(vec![' ' as u8; indent]).unwrap());
-    match prop {
-        PropertyRecordType::SignedInt16(x) => { println!("i16 {}", x); }
-        PropertyRecordType::Boolean(x) => { println!("bool {}", x); }
-        PropertyRecordType::SignedInt32(x) => { println!("i32 {}", x); }
-        PropertyRecordType::Float(x) => { println!("println {}", x); }
-        PropertyRecordType::Double(x) => { println!("f64 {}", x); }
-        PropertyRecordType::SignedInt64(x) => { println!("i64 {}", x); }
-        PropertyRecordType::FloatArray(_) => { println!("[f32]"); }
-        PropertyRecordType::DoubleArray(_) => { println!("[f64]"); }
-        PropertyRecordType::SignedInt64Array(_) => { println!("[i64]"); }
-        PropertyRecordType::SignedInt32Array(_) => { println!("[i32]"); }
-        PropertyRecordType::BooleanArray(_) => { println!("[bool]"); }
-        PropertyRecordType::String(x) => { println!("str {}", x); }
-        PropertyRecordType::BinaryData(_) => { println!("raw"); }
+/* Entry points for the fuzz targets in fuzz/, not part of the API. Errors are the expected outcome, panics are bugs. */
+#[doc(hidden)]
+pub mod fuzz {
+    use std::io::Cursor;
+
+    pub fn parse_nodes(bytes: &[u8]) {
+        let mut reader = Cursor::new(bytes);
+        if let Ok(header) = super::header::parse_header(&mut reader, false) {
+            let _ = super::ArenaDocument::parse(&mut reader, header.version, &[], super::node_reader::DEFAULT_MAX_DEPTH, false);
+        }
+    }
+
+    pub fn parse_properties(bytes: &[u8], num_properties: usize) {
+        let properties = match super::property::parse_properties(&mut Cursor::new(bytes), num_properties, bytes.len() as u64) {
+            Ok(properties) => properties,
+            Err(_) => return
+        };
+
+        // Arrays are only decoded on first access
+        for property in &properties {
+            let _ = property.as_f32_array();
+            let _ = property.as_f64_array();
+            let _ = property.as_i64_array();
+            let _ = property.as_i32_array();
+            let _ = property.as_bool_array();
+        }
     }
 }
 
-fn print_node(node: &NodeRecord, indent: usize) {
-    println!("{}{}", String::from_utf8(vec!['-' as u8; indent]).unwrap(), &node.name);
-    for prop in &node.properties {
-        print_property(prop, indent);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::FbxFixture;
+    use crate::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
+    use crate::mesh_processor::normals_processor::GenerateNormalsProcessor;
+    use std::time::Duration;
+
+    #[test]
+    fn import_bytes_should_stop_parsing_once_cancelled() {
+        // Arrange
+        let bytes = FbxFixture::new(7400)
+            .node("Objects", |o| o)
+            .bytes();
+        let mut options = ImportOptions::new();
+        options.cancel = Some(Arc::new(AtomicBool::new(true)));
+
+        // Act
+        let result = import_bytes(&bytes, &[], &options);
+
+        // Assert
+        assert!(matches!(result, Err(ImportError::Cancelled)));
     }
 
-    /*for child in &node.nested_list {
-        print_node(child, indent + 1);
-    }*/
-}
\ No newline at end of file
+    #[test]
+    fn import_fbx_from_bytes_should_return_none_for_truncated_header() {
+        // Arrange
+        let bytes = b"Kaydara FBX Binary  \0\x1a\x00\xe8";
+
+        // Act
+        let scene = import_fbx_from_bytes(bytes, Vec::new(), ImportOptions::new());
+
+        // Assert
+        assert!(scene.is_none());
+    }
+
+    #[test]
+    fn import_fbx_from_bytes_should_return_none_for_node_running_past_the_end() {
+        // Arrange
+        // Version 7400 header and a node claiming to end at byte 0xffff
+        let mut bytes = b"Kaydara FBX Binary  \0\x1a\x00\xe8\x1c\x00\x00".to_vec();
+        bytes.extend_from_slice(&[0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7]);
+        bytes.extend_from_slice(b"Objects");
+
+        // Act
+        let scene = import_fbx_from_bytes(&bytes, Vec::new(), ImportOptions::new());
+
+        // Assert
+        assert!(scene.is_none());
+    }
+
+    #[test]
+    fn parse_fbx_should_read_a_node_body_of_null_records() {
+        // Arrange
+        // Version 7400 header and an Objects node holding 4 MB of zeros, which read as one null record after another
+        let body_length = 4 * 1024 * 1024 + 13;
+        let mut bytes = b"Kaydara FBX Binary  \0\x1a\x00\xe8\x1c\x00\x00".to_vec();
+        let end_offset = (bytes.len() + 20 + body_length) as u32;
+        bytes.extend_from_slice(&end_offset.to_le_bytes());
+        bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 7]);
+        bytes.extend_from_slice(b"Objects");
+        bytes.resize(end_offset as usize + 13, 0);
+
+        // Act
+        let result = parse_fbx(&mut Cursor::new(&bytes), &ImportOptions::new());
+
+        // Assert
+        let (_, nodes) = result.unwrap();
+        assert!(nodes.get("Objects").unwrap().children().is_empty());
+    }
+
+    #[test]
+    fn import_fbx_from_bytes_should_check_the_bytes_after_the_magic_string_in_strict_mode() {
+        // Arrange
+        let mut bytes = FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("Geometry", vec![1i64.into(), FbxString::new("mesh").with_class("Geometry").into(), "Mesh".into()], |g| g
+                    .double_array("Vertices", vec![0.0; 9])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3])))
+            .bytes();
+        bytes[21] = 0x00;
+
+        // Act
+        let strict = import_fbx_from_bytes(&bytes, Vec::new(), ImportOptions::new().with_strict(true));
+        let lenient = import_fbx_from_bytes(&bytes, Vec::new(), ImportOptions::new()).unwrap();
+
+        // Assert
+        assert!(strict.is_none());
+        let header = lenient.header().unwrap();
+        assert_eq!(header.version(), 7400);
+        assert_eq!(header.unknown_bytes(), [0x00, 0x00]);
+        assert_eq!(lenient.meshes.len(), 1);
+    }
+
+    /* Three meshes, the second of which has a property with a type code that does not exist */
+    fn create_file_with_corrupt_second_mesh() -> Vec<u8> {
+        let mut bytes = FbxFixture::new(7400)
+            .node("Objects", |o| ["first", "second", "third"].iter().fold(o, |o, name| o
+                .node_with_props("Geometry", vec![1i64.into(), FbxString::new(name).with_class("Geometry").into(), "Mesh".into()], |g| g
+                    .double_array("Vertices", vec![0.0; 9])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3]))))
+            .bytes();
+        let second_name = bytes.windows(6).position(|window| window == b"second").unwrap();
+        // Type code of the name property, before its length
+        bytes[second_name - 5] = b'X';
+        bytes
+    }
+
+    #[test]
+    fn import_fbx_from_bytes_should_skip_malformed_node_and_import_the_rest() {
+        // Arrange
+        let bytes = create_file_with_corrupt_second_mesh();
+
+        // Act
+        let scene = import_fbx_from_bytes(&bytes, Vec::new(), ImportOptions::new()).unwrap();
+
+        // Assert
+        let names: Vec<&str> = scene.meshes.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["first", "third"]);
+    }
+
+    #[test]
+    fn import_fbx_from_bytes_should_fail_on_malformed_node_in_strict_mode() {
+        // Arrange
+        let bytes = create_file_with_corrupt_second_mesh();
+
+        // Act
+        let scene = import_fbx_from_bytes(&bytes, Vec::new(), ImportOptions::new().with_strict(true));
+
+        // Assert
+        assert!(scene.is_none());
+    }
+
+    #[test]
+    fn import_fbx_from_bytes_should_record_timings_of_every_phase() {
+        // Arrange
+        let bytes = create_file_with_corrupt_second_mesh();
+        let processors: Vec<Box<dyn MeshProcessor>> = vec![
+            Box::new(TriangulateMeshProcessor::new()),
+            Box::new(GenerateNormalsProcessor::new()),
+        ];
+
+        // Act
+        let scene = import_fbx_from_bytes(&bytes, processors, ImportOptions::new()).unwrap();
+
+        // Assert
+        let timings = scene.timings();
+        assert!(timings.parse > Duration::from_secs(0));
+        let names: Vec<&str> = timings.processors.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["TriangulateMeshProcessor", "GenerateNormalsProcessor"]);
+        assert_eq!(timings.processors[0].meshes, 2);
+        assert_eq!(timings.processors[0].faces_in, 2);
+        assert_eq!(timings.processors[0].vertices_out, 6);
+    }
+
+    #[test]
+    fn import_fbx_from_bytes_with_metadata_should_count_what_was_imported() {
+        // Arrange
+        let bytes = FbxFixture::new(7400).compressed()
+            .node("Objects", |o| o
+                .node_with_props("Geometry", vec![1i64.into(), FbxString::new("quad").with_class("Geometry").into(), "Mesh".into()], |g| g
+                    .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, 2, -4])))
+            .bytes();
+        let processors: Vec<Box<dyn MeshProcessor>> = vec![Box::new(TriangulateMeshProcessor::new())];
+
+        // Act
+        let result = import_fbx_from_bytes_with_metadata(&bytes, processors, ImportOptions::new()).unwrap();
+
+        // Assert
+        let metadata = &result.metadata;
+        assert_eq!(metadata.file_size, bytes.len() as u64);
+        assert!(metadata.nodes > 0);
+        assert!(metadata.inflated_arrays > 0);
+        assert!(metadata.inflated_bytes > 0);
+        assert_eq!(metadata.meshes, 1);
+        assert!(metadata.vertices > 0);
+        assert_eq!(metadata.faces, 2);
+        assert!(metadata.wall_time > Duration::from_secs(0));
+        assert!(metadata.wall_time >= metadata.timings.parse);
+        let line = metadata.to_string();
+        assert!(!line.contains('\n') && line.contains("nodes=") && line.contains("faces=2"), "{}", line);
+    }
+
+    fn create_file_with_two_meshes() -> Vec<u8> {
+        FbxFixture::new(7400).compressed()
+            .node("Objects", |o| ["first", "second"].iter().fold(o, |o, name| o
+                .node_with_props("Geometry", vec![1i64.into(), FbxString::new(name).with_class("Geometry").into(), "Mesh".into()], |g| g
+                    .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, 2, -4]))))
+            .bytes()
+    }
+
+    #[test]
+    fn import_fbx_from_reader_should_import_gzip_compressed_input_like_plain_input() {
+        // Arrange
+        let plain = create_file_with_two_meshes();
+        let compressed = crate::fbx::gzip::gzip(&plain);
+
+        // Act
+        let from_plain = import_fbx_from_reader(plain.as_slice(), Vec::new(), ImportOptions::new()).unwrap();
+        let from_compressed = import_fbx_from_reader(compressed.as_slice(), Vec::new(), ImportOptions::new()).unwrap();
+        let from_compressed_bytes = import_fbx_from_bytes(&compressed, Vec::new(), ImportOptions::new()).unwrap();
+
+        // Assert
+        assert!(compressed.len() < plain.len());
+        for scene in &[&from_compressed, &from_compressed_bytes] {
+            assert_eq!(scene.meshes.len(), from_plain.meshes.len());
+            for (mesh, expected) in scene.meshes.iter().zip(&from_plain.meshes) {
+                assert_eq!(mesh.name, expected.name);
+                assert_eq!(mesh.vertices, expected.vertices);
+                let indices = |faces: &[crate::scene::mesh::Face]| faces.iter().map(|f| f.indices.clone()).collect::<Vec<_>>();
+                assert_eq!(indices(&mesh.faces), indices(&expected.faces));
+            }
+        }
+        assert_eq!(from_compressed.header(), from_plain.header());
+    }
+
+    #[test]
+    fn import_fbx_from_reader_should_stop_at_the_in_memory_size_limit() {
+        // Arrange
+        let plain = create_file_with_two_meshes();
+        let compressed = crate::fbx::gzip::gzip(&plain);
+        let options = || ImportOptions::new().with_max_in_memory_size(plain.len() as u64 - 1);
+
+        // Act
+        let from_plain = import_fbx_from_reader(plain.as_slice(), Vec::new(), options());
+        let from_compressed = import_fbx_from_reader(compressed.as_slice(), Vec::new(), options());
+
+        // Assert
+        assert!(from_plain.is_none());
+        assert!(from_compressed.is_none());
+    }
+}
@@ -0,0 +1,304 @@
+use crate::warnings::warning;
+use crate::fbx::arena_document::NodeRef;
+use crate::fbx::properties70::Properties70;
+use crate::scene::time::KTime;
+use crate::scene::connections::{ConnectionKind, Connections};
+use crate::scene::animation::{AnimCurve, AnimCurveNode, AnimationLayer, KeyTangent, NodeAnimation, PropertyAnimation};
+use std::collections::HashMap;
+
+const CHANNELS: [&str; 3] = ["d|X", "d|Y", "d|Z"];
+
+fn read_curve(node: NodeRef, id: i64) -> Option<AnimCurve> {
+    let times = node.children().get_first("KeyTime").ok()?.properties().first()?.as_i64_array()?;
+    let values = node.children().get_first("KeyValueFloat").ok()?.properties().first()?.as_f32_array()?;
+    if times.len() != values.len() {
+        warning!("Curve {} has {} key times but {} values, skipping it.", id, times.len(), values.len());
+        return None;
+    }
+
+    let tangents = read_tangents(node, id, times.len());
+    Some(AnimCurve::with_tangents(id, times.iter().map(|t| KTime(*t)).collect(), values.to_vec(), tangents))
+}
+
+/* KeyAttrFlags and KeyAttrDataFloat hold one entry per run of keys sharing their attributes, KeyAttrRefCount the
+   length of each run. Keys are linear when the arrays are missing or do not add up to the number of keys. */
+fn read_tangents(node: NodeRef, id: i64, key_count: usize) -> Vec<KeyTangent> {
+    let array = |name: &str| node.children().get_first(name).ok().and_then(|c| c.properties().first());
+    let flags = array("KeyAttrFlags").and_then(|p| p.as_i32_array()).unwrap_or(&[]);
+    let ref_counts = array("KeyAttrRefCount").and_then(|p| p.as_i32_array()).unwrap_or(&[]);
+    let data = array("KeyAttrDataFloat").and_then(|p| p.as_f32_array()).unwrap_or(&[]);
+
+    let mut tangents = Vec::with_capacity(key_count);
+    for (i, (flags, count)) in flags.iter().zip(ref_counts).enumerate() {
+        let mut tangent = KeyTangent { flags: *flags, data: [0.0; 4] };
+        for (target, value) in tangent.data.iter_mut().zip(data.iter().skip(i * 4)) {
+            *target = *value;
+        }
+        tangents.extend(std::iter::repeat_n(tangent, (*count).max(0) as usize));
+    }
+
+    if tangents.len() != key_count {
+        if !flags.is_empty() {
+            warning!("Key attributes of curve {} cover {} keys but it has {}, making every key linear.", id, tangents.len(), key_count);
+        }
+        return vec![KeyTangent::linear(); key_count];
+    }
+    tangents
+}
+
+/* Reads the AnimationCurve objects whose id is wanted. The key arrays of the others are left undecoded. */
+pub(super) fn read_curves<F: Fn(i64) -> bool>(objects: NodeRef, wanted: F) -> HashMap<i64, AnimCurve> {
+    let mut curves = HashMap::new();
+    for node in objects.children().get_multiple("AnimationCurve") {
+        if let Some(id) = node.properties().first().and_then(|p| p.as_i64()).filter(|id| wanted(*id)) {
+            if let Some(curve) = read_curve(node, id) {
+                curves.insert(id, curve);
+            }
+        }
+    }
+    curves
+}
+
+/* Reads the AnimationCurveNode objects along with the curves connected to their d|X, d|Y and d|Z channels */
+fn read_curve_nodes(objects: NodeRef, connections: &Connections) -> Vec<AnimCurveNode> {
+    let mut curves = read_curves(objects, |_| true);
+    let mut curve_nodes = Vec::new();
+    let mut index_of = HashMap::new();
+    for node in objects.children().get_multiple("AnimationCurveNode") {
+        let id = match node.properties().first().and_then(|p| p.as_i64()) {
+            Some(id) => id,
+            None => continue
+        };
+        let name = node.properties().get(1).and_then(|p| p.as_string()).map_or(String::new(), |s| s.name().to_string());
+        let properties = Properties70::from(node);
+
+        let mut curve_node = AnimCurveNode::new(id, name);
+        for (i, channel) in CHANNELS.iter().enumerate() {
+            if let Some(default) = properties.number(channel) {
+                curve_node.default[i] = default as f32;
+            }
+        }
+
+        index_of.insert(id, curve_nodes.len());
+        curve_nodes.push(curve_node);
+    }
+
+    for connection in connections.of_kind(ConnectionKind::ObjectProperty) {
+        let curve_node = index_of.get(&connection.parent);
+        let channel = CHANNELS.iter().position(|c| *c == connection.property());
+        if let (Some(&curve_node), Some(channel)) = (curve_node, channel) {
+            if let Some(curve) = curves.remove(&connection.child) {
+                curve_nodes[curve_node].curves[channel] = Some(curve);
+            }
+        }
+    }
+
+    curve_nodes
+}
+
+/* Reads the AnimationLayer objects with the curve nodes connected to them. Curve nodes on the Lcl Translation, Lcl
+   Rotation and Lcl Scaling properties become the NodeAnimation of their model, those on any other property are kept
+   as PropertyAnimation. */
+pub(super) fn read_animation_layers(objects: NodeRef, connections: &Connections) -> Vec<AnimationLayer> {
+    let mut layers = Vec::new();
+    let mut layer_index = HashMap::new();
+    for node in objects.children().get_multiple("AnimationLayer") {
+        if let Some(id) = node.properties().first().and_then(|p| p.as_i64()) {
+            let name = node.properties().get(1).and_then(|p| p.as_string()).map_or(String::new(), |s| s.name().to_string());
+            layer_index.insert(id, layers.len());
+            layers.push(AnimationLayer::new(id, name));
+        }
+    }
+
+    let curve_nodes = read_curve_nodes(objects, connections);
+    let is_curve_node = |id: i64| curve_nodes.iter().any(|n| n.id == id);
+    let mut layer_of = HashMap::new();
+    for connection in connections.of_kind(ConnectionKind::ObjectObject) {
+        if let Some(layer) = layer_index.get(&connection.parent) {
+            layer_of.entry(connection.child).or_insert(*layer);
+        }
+    }
+    let mut target_of = HashMap::new();
+    for connection in connections.of_kind(ConnectionKind::ObjectProperty) {
+        let (child, parent, property) = (connection.child, connection.parent, connection.property());
+        // Curves are connected to curve nodes with OP connections as well
+        if is_curve_node(child) && !is_curve_node(parent) {
+            target_of.entry(child).or_insert((parent, property));
+        }
+    }
+
+    for curve_node in curve_nodes {
+        let layer = match layer_of.get(&curve_node.id) {
+            Some(layer) => &mut layers[*layer],
+            None => {
+                warning!("Curve node {} is not part of any animation layer, skipping it.", curve_node.id);
+                continue;
+            }
+        };
+
+        let (target, property) = match target_of.get(&curve_node.id) {
+            Some((target, property)) => (Some(*target), *property),
+            None => (None, ""),
+        };
+        match (target, property) {
+            (Some(target), "Lcl Translation") | (Some(target), "Lcl Rotation") | (Some(target), "Lcl Scaling") => {
+                let animations = &mut layer.node_animations;
+                let index = match animations.iter().position(|a| a.target_node_id == target) {
+                    Some(index) => index,
+                    None => {
+                        animations.push(NodeAnimation::new(target));
+                        animations.len() - 1
+                    }
+                };
+                let animation = &mut animations[index];
+                match property {
+                    "Lcl Translation" => animation.translation = Some(curve_node),
+                    "Lcl Rotation" => animation.rotation = Some(curve_node),
+                    _ => animation.scaling = Some(curve_node),
+                }
+            }
+            _ => layer.property_animations.push(PropertyAnimation {
+                target_id: target,
+                property: property.to_string(),
+                curve_node,
+            }),
+        }
+    }
+
+    layers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::hierarchy::read_connections;
+    use crate::fbx::fixture::{FbxFixture, FixtureNode};
+    use crate::fbx::property::{FbxString, PropertyRecordType};
+    use crate::scene::animation::{KeyInterpolation, TangentMode};
+
+    fn p(name: &str, value: f64) -> Vec<PropertyRecordType> {
+        vec![name.into(), "Number".into(), "".into(), "A".into(), value.into()]
+    }
+
+    fn curve(o: FixtureNode, id: i64, times: Vec<i64>, values: Vec<f32>) -> FixtureNode {
+        let properties = vec![id.into(), FbxString::new("").with_class("AnimCurve").into(), "".into()];
+        o.node_with_props("AnimationCurve", properties, |c| c
+            .i64_array("KeyTime", times)
+            .float_array("KeyValueFloat", values))
+    }
+
+    #[test]
+    fn read_curve_nodes_should_attach_curves_to_their_channels() {
+        // Arrange
+        let second = crate::scene::time::TICKS_PER_SECOND;
+        let curve_node = vec![10i64.into(), FbxString::new("T").with_class("AnimCurveNode").into(), "".into()];
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| {
+                let o = o.node_with_props("AnimationCurveNode", curve_node, |n| n
+                    .node("Properties70", |p70| p70
+                        .node_with_props("P", p("d|X", 1.0), |n| n)
+                        .node_with_props("P", p("d|Y", 2.0), |n| n)
+                        .node_with_props("P", p("d|Z", 3.0), |n| n)));
+                curve(o, 20, vec![0, second], vec![0.0, 4.0])
+            })
+            .node("Connections", |c| c
+                .node_with_props("C", vec!["OP".into(), 20i64.into(), 10i64.into(), "d|Y".into()], |n| n))
+            .parse();
+
+        // Act
+        let curve_nodes = read_curve_nodes(nodes.get("Objects").ok().unwrap(), &read_connections(None, nodes.get("Connections").ok(), None));
+
+        // Assert
+        assert_eq!(curve_nodes.len(), 1);
+        assert!(curve_nodes[0].curves()[0].is_none());
+        assert_eq!(curve_nodes[0].curves()[1].as_ref().map(|c| c.id()), Some(20));
+        assert_eq!(curve_nodes[0].evaluate_vec3(0.5), glm::vec3(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn read_curve_should_expand_key_attribute_runs() {
+        // Arrange
+        let properties = vec![20i64.into(), FbxString::new("").with_class("AnimCurve").into(), "".into()];
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o.node_with_props("AnimationCurve", properties, |c| c
+                .i64_array("KeyTime", vec![0, 10, 20])
+                .float_array("KeyValueFloat", vec![1.0, 2.0, 3.0])
+                .i32_array("KeyAttrFlags", vec![0x8 | 0x200, 0x2])
+                .float_array("KeyAttrDataFloat", vec![0.5, 0.25, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0])
+                .i32_array("KeyAttrRefCount", vec![2, 1])))
+            .parse();
+
+        // Act
+        let curves = read_curves(nodes.get("Objects").ok().unwrap(), |_| true);
+
+        // Assert
+        let curve = &curves[&20];
+        assert_eq!(curve.interpolations(), &[KeyInterpolation::Cubic, KeyInterpolation::Cubic, KeyInterpolation::Constant]);
+        assert_eq!(curve.tangents()[1].mode(), Some(TangentMode::Tcb));
+        assert_eq!(curve.tangents()[1].data, [0.5, 0.25, 0.0, 0.0]);
+        assert_eq!(curve.tangents()[2].mode(), None);
+    }
+
+    #[test]
+    fn read_curve_should_make_keys_linear_without_key_attributes() {
+        // Arrange
+        let properties = vec![20i64.into(), FbxString::new("").with_class("AnimCurve").into(), "".into()];
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o.node_with_props("AnimationCurve", properties, |c| c
+                .i64_array("KeyTime", vec![0, 10])
+                .float_array("KeyValueFloat", vec![1.0, 2.0])))
+            .parse();
+
+        // Act
+        let curves = read_curves(nodes.get("Objects").ok().unwrap(), |_| true);
+
+        // Assert
+        assert_eq!(curves[&20].interpolations(), &[KeyInterpolation::Linear; 2]);
+    }
+
+    #[test]
+    fn read_animation_layers_should_resolve_translation_of_a_model() {
+        // Arrange
+        let second = crate::scene::time::TICKS_PER_SECOND;
+        let layer = vec![300i64.into(), FbxString::new("BaseLayer").with_class("AnimLayer").into(), "".into()];
+        let translation = vec![200i64.into(), FbxString::new("T").with_class("AnimCurveNode").into(), "".into()];
+        let color = vec![210i64.into(), FbxString::new("DiffuseColor").with_class("AnimCurveNode").into(), "".into()];
+        let connection = |kind: &str, child: i64, parent: i64, property: &str| {
+            let mut properties: Vec<PropertyRecordType> = vec![kind.into(), child.into(), parent.into()];
+            if !property.is_empty() {
+                properties.push(property.into());
+            }
+            properties
+        };
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| {
+                let o = o
+                    .node_with_props("AnimationLayer", layer, |n| n)
+                    .node_with_props("AnimationCurveNode", translation, |n| n)
+                    .node_with_props("AnimationCurveNode", color, |n| n);
+                curve(o, 201, vec![0, second], vec![0.0, 10.0])
+            })
+            .node("Connections", |c| c
+                .node_with_props("C", connection("OO", 200, 300, ""), |n| n)
+                .node_with_props("C", connection("OO", 210, 300, ""), |n| n)
+                .node_with_props("C", connection("OP", 200, 100, "Lcl Translation"), |n| n)
+                .node_with_props("C", connection("OP", 210, 400, "DiffuseColor"), |n| n)
+                .node_with_props("C", connection("OP", 201, 200, "d|X"), |n| n))
+            .parse();
+
+        // Act
+        let layers = read_animation_layers(nodes.get("Objects").ok().unwrap(), &read_connections(None, nodes.get("Connections").ok(), None));
+
+        // Assert
+        assert_eq!(layers.len(), 1);
+        let animations = layers[0].node_animations();
+        assert_eq!(animations.len(), 1);
+        assert_eq!(animations[0].target_node_id(), 100);
+        assert!(animations[0].rotation().is_none());
+        assert!(animations[0].scaling().is_none());
+        assert_eq!(animations[0].translation().unwrap().evaluate_vec3(0.5), glm::vec3(5.0, 0.0, 0.0));
+        assert_eq!(layers[0].property_animations().len(), 1);
+        assert_eq!(layers[0].property_animations()[0].target_id(), Some(400));
+        assert_eq!(layers[0].property_animations()[0].property(), "DiffuseColor");
+    }
+}
\ No newline at end of file
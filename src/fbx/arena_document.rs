@@ -0,0 +1,515 @@
+use crate::fbx::{ParseError, ParseResult};
+use crate::fbx::property::{FbxString, PropertyRecordType};
+use crate::fbx::array_view::ArrayView;
+use crate::fbx::node_reader::{NodeReader, NodeEvent};
+use crate::fbx::node_collection::Error;
+use crate::fbx::header::parse_header;
+use crate::fbx::node_reader::DEFAULT_MAX_DEPTH;
+use std::io::{Cursor, Read, Seek};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+struct NodeData {
+    name: Range<usize>,
+    properties: Range<usize>,
+    // Range into ArenaDocument::children, which holds node indices
+    children: Range<usize>,
+}
+
+/* Node tree stored in a handful of flat buffers instead of one allocation per name, property list and child list.
+   This is what the importer reads files into. Nodes are accessed through NodeRef handles. */
+pub struct ArenaDocument {
+    nodes: Vec<NodeData>,
+    names: String,
+    properties: Vec<PropertyRecordType>,
+    children: Vec<usize>,
+    roots: Vec<usize>,
+}
+
+#[derive(Clone, Copy)]
+pub struct NodeRef<'doc> {
+    document: &'doc ArenaDocument,
+    index: usize,
+}
+
+/* Top level nodes or the children of a node, in file order */
+#[derive(Clone, Copy)]
+pub struct NodeList<'doc> {
+    document: &'doc ArenaDocument,
+    indices: &'doc [usize],
+}
+
+/* Why a file could not be parsed into a document, or an array property could not be decoded */
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentError(pub(super) ParseError);
+
+impl std::fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for DocumentError {}
+
+/* A property of a node that is missing or holds another type than the one asked for */
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertyError {
+    pub node: String,
+    pub index: usize,
+    pub expected: &'static str,
+    // None when the node has no property at the index
+    pub found: Option<&'static str>,
+    // Why an array property of the expected type could not be decoded, such as compressed data that does not inflate
+    pub cause: Option<Box<DocumentError>>,
+}
+
+impl std::fmt::Display for PropertyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.found, &self.cause) {
+            (None, _) => write!(f, "{} node has no property {}, expected {}", self.node, self.index, self.expected),
+            (Some(found), Some(cause)) => write!(f, "Property {} of {} node is a {} that could not be decoded: {}", self.index, self.node, found, cause),
+            (Some(found), None) => write!(f, "Property {} of {} node is {} where {} was expected", self.index, self.node, found, self.expected),
+        }
+    }
+}
+
+impl std::error::Error for PropertyError {}
+
+impl ArenaDocument {
+    /* Parses every node after the header, the reader must be positioned right after it. Top level nodes named in
+       skipped are seeked past without reading them. Outside of strict mode, malformed nodes are left out along with
+       their children. */
+    pub(super) fn parse<R: Read + Seek>(reader: &mut R, version: u32, skipped: &[&str], max_depth: usize, strict: bool) -> ParseResult<Self> {
+        ArenaDocument::parse_cancellable(reader, version, skipped, max_depth, strict, None)
+    }
+
+    /* Same as parse, failing with ParseError::Cancelled at the next node once cancel is raised */
+    pub(super) fn parse_cancellable<R: Read + Seek>(reader: &mut R, version: u32, skipped: &[&str], max_depth: usize, strict: bool,
+                                                    cancel: Option<&AtomicBool>) -> ParseResult<Self> {
+        let mut node_reader = NodeReader::new(reader)?.with_max_depth(max_depth).with_version(version).with_strict(strict);
+        let mut document = ArenaDocument {
+            nodes: Vec::new(),
+            names: String::new(),
+            properties: Vec::new(),
+            children: Vec::new(),
+            roots: Vec::new(),
+        };
+
+        // Children of the nodes still open, only moved into the shared pool once a node ends so they stay contiguous.
+        // Along with them the size of the pool when the node began, to drop what was read of a skipped node.
+        let mut open_nodes: Vec<(usize, Vec<usize>, usize)> = Vec::new();
+
+        while let Some(event) = node_reader.next_event()? {
+            match event {
+                NodeEvent::BeginNode { .. } if cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) => {
+                    return Err(ParseError::Cancelled);
+                }
+                NodeEvent::BeginNode { name, .. } if open_nodes.is_empty() && skipped.contains(&&*name) => {
+                    node_reader.skip_current_subtree()?;
+                }
+                NodeEvent::BeginNode { name, .. } => {
+                    let name_start = document.names.len();
+                    document.names.push_str(&name);
+                    let properties_start = document.properties.len();
+
+                    let index = document.nodes.len();
+                    document.nodes.push(NodeData {
+                        name: name_start..document.names.len(),
+                        properties: properties_start..properties_start,
+                        children: 0..0,
+                    });
+
+                    match open_nodes.last_mut() {
+                        Some((_, siblings, _)) => siblings.push(index),
+                        None => document.roots.push(index),
+                    }
+                    open_nodes.push((index, Vec::new(), document.children.len()));
+                }
+                NodeEvent::Property(property) => {
+                    let (index, _, _) = open_nodes.last()
+                        .ok_or_else(|| ParseError::ValidationError("property outside of node".to_string()))?;
+                    document.properties.push(property);
+                    document.nodes[*index].properties.end = document.properties.len();
+                }
+                NodeEvent::EndNode => {
+                    let (index, children, _) = open_nodes.pop()
+                        .ok_or_else(|| ParseError::ValidationError("end of node outside of node".to_string()))?;
+                    let children_start = document.children.len();
+                    document.children.extend(children);
+                    document.nodes[index].children = children_start..document.children.len();
+                }
+                // Everything read of the node since it began comes after the rest in every buffer
+                NodeEvent::SkippedNode => {
+                    let (index, _, children_start) = open_nodes.pop()
+                        .ok_or_else(|| ParseError::ValidationError("skipped node outside of node".to_string()))?;
+                    match open_nodes.last_mut() {
+                        Some((_, siblings, _)) => siblings.pop(),
+                        None => document.roots.pop(),
+                    };
+                    let node = &document.nodes[index];
+                    document.names.truncate(node.name.start);
+                    document.properties.truncate(node.properties.start);
+                    document.children.truncate(children_start);
+                    document.nodes.truncate(index);
+                }
+            }
+        }
+
+        if !open_nodes.is_empty() {
+            return Err(ParseError::ValidationError("unexpected end of file inside node".to_string()));
+        }
+
+        Ok(document)
+    }
+
+    /* Parses a whole file already loaded into memory */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DocumentError> {
+        let mut reader = Cursor::new(bytes);
+        let header = parse_header(&mut reader, false).map_err(DocumentError)?;
+        ArenaDocument::parse(&mut reader, header.version, &[], DEFAULT_MAX_DEPTH, true).map_err(DocumentError)
+    }
+
+    /* The top level nodes */
+    pub fn nodes(&self) -> NodeList<'_> {
+        NodeList { document: self, indices: &self.roots }
+    }
+
+    /* The only top level node with the given name */
+    pub fn get(&self, name: &str) -> Result<NodeRef<'_>, Error> {
+        self.nodes().get(name)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl<'doc> NodeList<'doc> {
+    /* The only node with the given name */
+    pub fn get(&self, name: &str) -> Result<NodeRef<'doc>, Error> {
+        let mut matching = self.get_multiple(name);
+        match (matching.next(), matching.count()) {
+            (Some(node), 0) => Ok(node),
+            (Some(_), others) => Err(Error::MultipleValuesExist { name: name.to_string(), count: others + 1 }),
+            (None, _) => Err(Error::NoSuchNode { name: name.to_string() }),
+        }
+    }
+
+    /* The first node with the given name, for nodes where later duplicates are harmless */
+    pub fn get_first(&self, name: &str) -> Result<NodeRef<'doc>, Error> {
+        self.get_multiple(name).next().ok_or_else(|| Error::NoSuchNode { name: name.to_string() })
+    }
+
+    /* Every node with the given name, in file order */
+    pub fn get_multiple<'a>(&self, name: &'a str) -> impl Iterator<Item = NodeRef<'doc>> + 'a where 'doc: 'a {
+        self.iter().filter(move |node| node.name() == name)
+    }
+
+    /* How many nodes have the given name */
+    pub fn count(&self, name: &str) -> usize {
+        self.get_multiple(name).count()
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /* Every node in file order */
+    pub fn iter(&self) -> impl Iterator<Item = NodeRef<'doc>> {
+        let document = self.document;
+        self.indices.iter().map(move |index| NodeRef { document, index: *index })
+    }
+}
+
+impl<'doc> NodeRef<'doc> {
+    fn data(&self) -> &'doc NodeData {
+        &self.document.nodes[self.index]
+    }
+
+    pub fn name(&self) -> &'doc str {
+        &self.document.names[self.data().name.clone()]
+    }
+
+    pub fn properties(&self) -> &'doc [PropertyRecordType] {
+        &self.document.properties[self.data().properties.clone()]
+    }
+
+    pub fn children(&self) -> NodeList<'doc> {
+        NodeList { document: self.document, indices: &self.document.children[self.data().children.clone()] }
+    }
+
+    /* First child with the given name */
+    pub fn get(&self, name: &str) -> Result<NodeRef<'doc>, Error> {
+        self.children().get_first(name)
+    }
+
+    /* All children with the given name, like NodeList::get_multiple */
+    pub fn get_multiple(&self, name: &'doc str) -> impl Iterator<Item = NodeRef<'doc>> {
+        self.children().get_multiple(name)
+    }
+
+    pub fn property(&self, index: usize) -> Result<&'doc PropertyRecordType, PropertyError> {
+        self.properties().get(index).ok_or_else(|| self.property_error(index, "a value", None))
+    }
+
+    /* Strings hold a name and, for object names, a class */
+    pub fn property_str(&self, index: usize) -> Result<&'doc FbxString, PropertyError> {
+        self.typed_property(index, "string", |p| p.as_string().map(Ok))
+    }
+
+    /* Accepts 32 bit integers as well, which some exporters write for ids */
+    pub fn property_i64(&self, index: usize) -> Result<i64, PropertyError> {
+        self.typed_property(index, "i64", |p| p.as_i64().map(Ok))
+    }
+
+    pub fn property_f64_array(&self, index: usize) -> Result<&'doc [f64], PropertyError> {
+        self.typed_property(index, "f64 array", |p| match p {
+            PropertyRecordType::DoubleArray(a) => Some(a.try_values()),
+            _ => None
+        })
+    }
+
+    pub fn property_f32_array(&self, index: usize) -> Result<&'doc [f32], PropertyError> {
+        self.typed_property(index, "f32 array", |p| match p {
+            PropertyRecordType::FloatArray(a) => Some(a.try_values()),
+            _ => None
+        })
+    }
+
+    /* f32 or f64 array, without converting it */
+    pub fn property_array_view(&self, index: usize) -> Result<ArrayView<'doc>, PropertyError> {
+        self.typed_property(index, "f32 or f64 array", ArrayView::try_of)
+    }
+
+    pub fn property_i32_array(&self, index: usize) -> Result<&'doc [i32], PropertyError> {
+        self.typed_property(index, "i32 array", |p| match p {
+            PropertyRecordType::SignedInt32Array(a) => Some(a.try_values()),
+            _ => None
+        })
+    }
+
+    /* get gives None for properties of another type, and the error for arrays of the type that could not be decoded */
+    fn typed_property<T, F>(&self, index: usize, expected: &'static str, get: F) -> Result<T, PropertyError>
+        where F: FnOnce(&'doc PropertyRecordType) -> Option<ParseResult<T>>
+    {
+        let property = self.properties().get(index).ok_or_else(|| self.property_error(index, expected, None))?;
+        match get(property) {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(cause)) => {
+                let error = self.property_error(index, expected, Some(property.type_name()));
+                Err(PropertyError { cause: Some(Box::new(DocumentError(cause))), ..error })
+            }
+            None => Err(self.property_error(index, expected, Some(property.type_name()))),
+        }
+    }
+
+    fn property_error(&self, index: usize, expected: &'static str, found: Option<&'static str>) -> PropertyError {
+        PropertyError { node: self.name().to_string(), index, expected, found, cause: None }
+    }
+}
+
+/* Only the node itself, its children would print the whole subtree */
+impl std::fmt::Debug for NodeRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeRef").field("name", &self.name()).field("properties", &self.properties()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::property::{ArrayProperty, FbxString};
+    use crate::fbx::writer::{FbxNode, FbxWriter};
+
+    fn create_fixture() -> Vec<u8> {
+        let mut objects = FbxNode::new("Objects");
+        for i in 0..3 {
+            objects = objects.with_child(FbxNode::new("Geometry")
+                .with_property(PropertyRecordType::SignedInt64(i))
+                .with_property(PropertyRecordType::String(FbxString::new(&format!("mesh{}", i))))
+                .with_child(FbxNode::new("Vertices")
+                    .with_property(PropertyRecordType::DoubleArray(ArrayProperty::from_values(vec![i as f64; 3])))));
+        }
+
+        FbxWriter::new(7400)
+            .with_node(FbxNode::new("Creator").with_property(PropertyRecordType::String(FbxString::new("arena"))))
+            .with_node(objects)
+            .write()
+    }
+
+    /* Document with a top level node per name, the first one holding the given properties */
+    fn create_document(names: &[&str], properties: Vec<PropertyRecordType>) -> ArenaDocument {
+        let mut first = FbxNode::new(names[0]);
+        for property in properties {
+            first = first.with_property(property);
+        }
+        let mut writer = FbxWriter::new(7400).with_node(first);
+        for name in &names[1..] {
+            writer = writer.with_node(FbxNode::new(name));
+        }
+        ArenaDocument::from_bytes(&writer.write()).unwrap()
+    }
+
+    #[test]
+    fn parse_should_keep_tree_structure() {
+        // Arrange
+        let fixture = create_fixture();
+
+        // Act
+        let document = ArenaDocument::from_bytes(&fixture).unwrap();
+
+        // Assert
+        assert_eq!(document.node_count(), 8);
+        assert_eq!(document.nodes().iter().map(|n| n.name()).collect::<Vec<_>>(), vec!["Creator", "Objects"]);
+        assert_eq!(document.get("Creator").ok().unwrap().properties(), &[PropertyRecordType::String(FbxString::new("arena"))]);
+
+        let geometries: Vec<NodeRef> = document.get("Objects").ok().unwrap().get_multiple("Geometry").collect();
+        assert_eq!(geometries.len(), 3);
+        for (i, geometry) in geometries.iter().enumerate() {
+            assert_eq!(geometry.properties()[0], PropertyRecordType::SignedInt64(i as i64));
+            let vertices = geometry.get("Vertices").ok().unwrap();
+            assert_eq!(vertices.properties()[0].as_f64_array().unwrap(), &[i as f64; 3]);
+            assert_eq!(vertices.children().len(), 0);
+        }
+    }
+
+    #[test]
+    fn from_bytes_should_return_why_the_file_could_not_be_parsed() {
+        // Arrange
+        let mut fixture = create_fixture();
+        fixture.truncate(fixture.len() - 40);
+
+        // Act
+        let result = ArenaDocument::from_bytes(&fixture);
+
+        // Assert
+        assert!(result.err().unwrap().to_string().starts_with("file appears truncated"));
+    }
+
+    #[test]
+    fn get_should_fail_without_a_node_of_the_name() {
+        // Arrange
+        let document = create_document(&["Objects"], Vec::new());
+
+        // Act
+        let result = document.get("GlobalSettings");
+
+        // Assert
+        assert_eq!(result.unwrap_err(), Error::NoSuchNode { name: "GlobalSettings".to_string() });
+    }
+
+    #[test]
+    fn get_should_return_the_only_node_of_the_name() {
+        // Arrange
+        let document = create_document(&["Objects", "GlobalSettings"], Vec::new());
+
+        // Act
+        let result = document.get("GlobalSettings");
+
+        // Assert
+        assert_eq!(result.unwrap().name(), "GlobalSettings");
+    }
+
+    #[test]
+    fn get_should_fail_when_several_nodes_have_the_name() {
+        // Arrange
+        let document = create_document(&["GlobalSettings", "Objects", "GlobalSettings"], Vec::new());
+
+        // Act
+        let result = document.get("GlobalSettings");
+        let first = document.nodes().get_first("GlobalSettings");
+
+        // Assert
+        let error = result.unwrap_err();
+        assert_eq!(error, Error::MultipleValuesExist { name: "GlobalSettings".to_string(), count: 2 });
+        assert_eq!(error.to_string(), "Found 2 nodes named 'GlobalSettings' where one was expected");
+        assert!(first.is_ok());
+    }
+
+    #[test]
+    fn get_multiple_should_keep_the_file_order() {
+        // Arrange
+        let mut properties = FbxNode::new("Properties70");
+        for (index, name) in ["P", "Other", "P", "P"].iter().enumerate() {
+            properties = properties.with_child(FbxNode::new(name).with_property(PropertyRecordType::SignedInt32(index as i32)));
+        }
+        let document = ArenaDocument::from_bytes(&FbxWriter::new(7400).with_node(properties).write()).unwrap();
+        let children = document.get("Properties70").unwrap().children();
+
+        // Act
+        let p: Vec<i64> = children.get_multiple("P").map(|n| n.properties()[0].as_i64().unwrap()).collect();
+        let all: Vec<&str> = children.iter().map(|n| n.name()).collect();
+
+        // Assert
+        assert_eq!(p, vec![0, 2, 3]);
+        assert_eq!(all, vec!["P", "Other", "P", "P"]);
+        assert_eq!(children.count("P"), 3);
+    }
+
+    #[test]
+    fn property_i64_should_read_32_bit_integers() {
+        // Arrange
+        let document = create_document(&["Geometry"], vec![PropertyRecordType::SignedInt32(7)]);
+
+        // Act
+        let value = document.get("Geometry").unwrap().property_i64(0);
+
+        // Assert
+        assert_eq!(value, Ok(7));
+    }
+
+    #[test]
+    fn property_str_should_name_the_node_index_and_types_on_mismatch() {
+        // Arrange
+        let document = create_document(&["Geometry"], vec![PropertyRecordType::SignedInt64(1), PropertyRecordType::Double(2.0)]);
+
+        // Act
+        let error = document.get("Geometry").unwrap().property_str(1).unwrap_err();
+
+        // Assert
+        assert_eq!(error, PropertyError { node: "Geometry".to_string(), index: 1, expected: "string", found: Some("f64"), cause: None });
+        assert_eq!(error.to_string(), "Property 1 of Geometry node is f64 where string was expected");
+    }
+
+    #[test]
+    fn property_array_view_should_tell_why_a_compressed_array_could_not_be_decoded() {
+        // Arrange
+        let values = vec![1.0f64; 64];
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let payload = deflate::deflate_bytes_zlib(&bytes);
+        let mut file = FbxWriter::new(7400).with_compressed_arrays(true)
+            .with_node(FbxNode::new("Vertices").with_property(PropertyRecordType::DoubleArray(ArrayProperty::from_values(values))))
+            .write();
+        let start = file.windows(payload.len()).position(|window| window == payload).unwrap();
+        for byte in &mut file[start + 2..start + payload.len()] {
+            *byte ^= 0x5a;
+        }
+        let document = ArenaDocument::from_bytes(&file).unwrap();
+
+        // Act
+        let error = document.get("Vertices").unwrap().property_array_view(0).unwrap_err();
+
+        // Assert
+        assert!(error.cause.is_some());
+        assert!(error.to_string().starts_with("Property 0 of Vertices node is a f64 array that could not be decoded: could not inflate data"));
+    }
+
+    #[test]
+    fn property_f64_array_should_fail_on_missing_property() {
+        // Arrange
+        let document = create_document(&["Geometry"], vec![PropertyRecordType::DoubleArray(ArrayProperty::from_values(vec![1.0]))]);
+        let node = document.get("Geometry").unwrap();
+
+        // Act
+        let present = node.property_f64_array(0).map(|values| values.to_vec());
+        let missing = node.property_f64_array(1);
+
+        // Assert
+        assert_eq!(present, Ok(vec![1.0]));
+        assert_eq!(missing.unwrap_err().found, None);
+    }
+}
@@ -0,0 +1,172 @@
+use std::borrow::Cow;
+use crate::fbx::ParseResult;
+use crate::fbx::property::PropertyRecordType;
+
+/* Floating point array property as it is stored, exporters write the same data as either f32 or f64 arrays. The
+   values are borrowed from the property, converting only happens when a view of the other type is asked for. */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArrayView<'a> {
+    F32(&'a [f32]),
+    F64(&'a [f64]),
+}
+
+impl<'a> ArrayView<'a> {
+    /* None for properties that are not f32 or f64 arrays, or whose compressed data could not be decoded */
+    pub fn of(property: &'a PropertyRecordType) -> Option<Self> {
+        ArrayView::try_of(property)?.ok()
+    }
+
+    /* None for properties that are not f32 or f64 arrays, the error for the ones that could not be decoded */
+    pub(super) fn try_of(property: &'a PropertyRecordType) -> Option<ParseResult<Self>> {
+        match property {
+            PropertyRecordType::FloatArray(a) => Some(a.try_values().map(ArrayView::F32)),
+            PropertyRecordType::DoubleArray(a) => Some(a.try_values().map(ArrayView::F64)),
+            _ => None
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            ArrayView::F32(values) => values.len(),
+            ArrayView::F64(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /* The values without a copy, only for f64 arrays */
+    pub fn as_f64_slice(&self) -> Option<&'a [f64]> {
+        match self {
+            ArrayView::F64(values) => Some(values),
+            ArrayView::F32(_) => None,
+        }
+    }
+
+    /* The values without a copy, only for f32 arrays */
+    pub fn as_f32_slice(&self) -> Option<&'a [f32]> {
+        match self {
+            ArrayView::F32(values) => Some(values),
+            ArrayView::F64(_) => None,
+        }
+    }
+
+    /* Borrowed for f64 arrays, widened into a copy for f32 arrays */
+    pub fn to_f64(&self) -> Cow<'a, [f64]> {
+        match self {
+            ArrayView::F64(values) => Cow::Borrowed(values),
+            ArrayView::F32(_) => Cow::Owned(self.iter_f64().collect()),
+        }
+    }
+
+    /* Copy of the values as f32, rounding f64 values to the nearest f32 */
+    pub fn as_f32_vec_lossy(&self) -> Vec<f32> {
+        self.iter_f32_lossy().collect()
+    }
+
+    pub fn iter_f64(&self) -> impl ExactSizeIterator<Item = f64> + 'a {
+        let (f32s, f64s) = self.split();
+        f32s.iter().map(|v| *v as f64).chain(f64s.iter().copied()).exact()
+    }
+
+    /* Converts while iterating, rounding f64 values to the nearest f32 */
+    pub fn iter_f32_lossy(&self) -> impl ExactSizeIterator<Item = f32> + 'a {
+        let (f32s, f64s) = self.split();
+        f32s.iter().copied().chain(f64s.iter().map(|v| *v as f32)).exact()
+    }
+
+    /* One of the two is always empty, which lets the iterators be a single type */
+    fn split(&self) -> (&'a [f32], &'a [f64]) {
+        match self {
+            ArrayView::F32(values) => (values, &[]),
+            ArrayView::F64(values) => (&[], values),
+        }
+    }
+}
+
+/* Chain does not implement ExactSizeIterator since the sum of two lengths could overflow, which cannot happen for the
+   halves of ArrayView::split */
+struct Exact<I> {
+    inner: I,
+    remaining: usize,
+}
+
+trait IntoExact: Iterator + Sized {
+    fn exact(self) -> Exact<Self> {
+        let remaining = self.size_hint().0;
+        Exact { inner: self, remaining }
+    }
+}
+
+impl<I: Iterator> IntoExact for I {}
+
+impl<I: Iterator> Iterator for Exact<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I: Iterator> ExactSizeIterator for Exact<I> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::ArrayProperty;
+
+    #[test]
+    fn as_f64_slice_should_borrow_the_values_of_the_property() {
+        // Arrange
+        let property = PropertyRecordType::DoubleArray(ArrayProperty::from_values(vec![1.0, 2.5, 1e-300]));
+
+        // Act
+        let view = ArrayView::of(&property).unwrap();
+
+        // Assert
+        let stored = property.as_f64_array().unwrap();
+        assert_eq!(view.as_f64_slice().unwrap().as_ptr(), stored.as_ptr());
+        assert!(matches!(view.to_f64(), Cow::Borrowed(values) if values.as_ptr() == stored.as_ptr()));
+        assert_eq!(view.as_f32_slice(), None);
+        assert_eq!(view.len(), 3);
+    }
+
+    #[test]
+    fn as_f32_vec_lossy_should_round_like_a_cast() {
+        // Arrange
+        let values = vec![0.1, 1.0 / 3.0, 16777217.0, -2.0, 1e300];
+        let property = PropertyRecordType::DoubleArray(ArrayProperty::from_values(values.clone()));
+
+        // Act
+        let lossy = ArrayView::of(&property).unwrap().as_f32_vec_lossy();
+
+        // Assert
+        assert_eq!(lossy, values.iter().map(|v| *v as f32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn views_of_f32_arrays_should_widen_on_request() {
+        // Arrange
+        let property = PropertyRecordType::FloatArray(ArrayProperty::from_values(vec![0.1f32, 2.0]));
+        let view = ArrayView::of(&property).unwrap();
+
+        // Act
+        let widened = view.to_f64();
+        let iterated = view.iter_f64();
+
+        // Assert
+        assert_eq!(view.as_f32_slice().unwrap().as_ptr(), property.as_f32_array().unwrap().as_ptr());
+        assert_eq!(view.as_f64_slice(), None);
+        assert_eq!(iterated.len(), 2);
+        assert_eq!(widened.as_ref(), &[0.1f32 as f64, 2.0]);
+        assert_eq!(iterated.collect::<Vec<_>>(), widened.into_owned());
+        assert_eq!(ArrayView::of(&PropertyRecordType::SignedInt32Array(ArrayProperty::from_values(vec![1]))), None);
+    }
+}
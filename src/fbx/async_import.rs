@@ -0,0 +1,137 @@
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::mpsc;
+use tokio_stream::Stream;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use crate::fbx::{import_path, ImportError, ImportOptions, ImportResult, ProgressEvent};
+use crate::mesh_processor::MeshProcessor;
+
+/* Raises the flag when the future holding it is dropped */
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/* Imports the file at path on the blocking thread pool of the tokio runtime polling the future, which starts the
+   import when first polled. The stream reports each phase as the import reaches it and ends along with the import.
+   Dropping the future cancels the import, it stops at the next node read or mesh processed. */
+pub fn import_fbx_async(path: &str, mesh_processors: Vec<Box<dyn MeshProcessor>>, mut options: ImportOptions)
+    -> (impl Future<Output = Result<ImportResult, ImportError>>, impl Stream<Item = ProgressEvent>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let path = path.to_string();
+
+    let import = async move {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _cancel_on_drop = CancelOnDrop(cancelled.clone());
+        options.cancel = Some(cancelled.clone());
+        let task = tokio::task::spawn_blocking(move || {
+            let mut progress = |event| {
+                // Nobody might be listening, progress is optional
+                let _ = sender.send(event);
+                !cancelled.load(Ordering::Relaxed)
+            };
+            import_path(Path::new(&path), &mesh_processors, &options, &mut progress)
+        });
+
+        match task.await {
+            Ok(result) => result,
+            Err(_) => Err(ImportError::Panicked),
+        }
+    };
+
+    (import, UnboundedReceiverStream::new(receiver))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::FbxFixture;
+    use crate::fbx::FbxString;
+    use tokio_stream::StreamExt;
+    use crate::scene::mesh::Mesh;
+    use std::sync::atomic::AtomicUsize;
+
+    /* Counts the meshes it is given and holds on to each of them until released */
+    struct BlockingProcessor {
+        processed: Arc<AtomicUsize>,
+        released: Arc<AtomicBool>,
+    }
+
+    impl MeshProcessor for BlockingProcessor {
+        fn process(&self, _: &mut Mesh) {
+            self.processed.fetch_add(1, Ordering::SeqCst);
+            while !self.released.load(Ordering::SeqCst) {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn import_fbx_async_should_import_the_file_and_report_its_progress() {
+        // Arrange
+        let bytes = FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("Geometry", vec![1i64.into(), FbxString::new("triangle").with_class("Geometry").into(), "Mesh".into()], |g| g
+                    .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3])))
+            .bytes();
+        let path = std::env::temp_dir().join(format!("fbximport_async_{}.fbx", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+
+        // Act
+        let (import, progress) = import_fbx_async(path.to_str().unwrap(), Vec::new(), ImportOptions::new());
+        let result = import.await;
+        let events: Vec<ProgressEvent> = progress.collect().await;
+        std::fs::remove_file(&path).unwrap();
+
+        // Assert
+        let result = result.unwrap();
+        assert_eq!(result.scene.meshes.len(), 1);
+        assert_eq!(events, vec![
+            ProgressEvent::Parsing { file_size: bytes.len() as u64 },
+            ProgressEvent::Converting,
+            ProgressEvent::Processing { meshes: 1 },
+            ProgressEvent::Finished,
+        ]);
+    }
+
+    #[tokio::test]
+    async fn import_fbx_async_should_stop_processing_meshes_once_the_future_is_dropped() {
+        // Arrange
+        let meshes = 64;
+        let bytes = FbxFixture::new(7400)
+            .node("Objects", |o| (0..meshes).fold(o, |o, i| o
+                .node_with_props("Geometry", vec![(i as i64).into(), FbxString::new("triangle").with_class("Geometry").into(), "Mesh".into()], |g| g
+                    .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3]))))
+            .bytes();
+        let path = std::env::temp_dir().join(format!("fbximport_async_cancel_{}.fbx", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+        let processed = Arc::new(AtomicUsize::new(0));
+        let released = Arc::new(AtomicBool::new(false));
+        let processor = BlockingProcessor { processed: processed.clone(), released: released.clone() };
+
+        // Act
+        let (import, progress) = import_fbx_async(path.to_str().unwrap(), vec![Box::new(processor)], ImportOptions::new());
+        let task = tokio::spawn(import);
+        // Dropped while the first mesh is being processed
+        while processed.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        task.abort();
+        assert!(task.await.is_err_and(|e| e.is_cancelled()));
+        released.store(true, Ordering::SeqCst);
+        // Ends once the import has returned
+        let events: Vec<ProgressEvent> = progress.collect().await;
+        std::fs::remove_file(&path).unwrap();
+
+        // Assert
+        assert!(processed.load(Ordering::SeqCst) < meshes);
+        assert!(!events.contains(&ProgressEvent::Finished));
+    }
+}
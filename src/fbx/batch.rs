@@ -0,0 +1,188 @@
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use crate::fbx::{import_path, ImportError, ImportOptions, ImportResult, ProgressEvent};
+use crate::mesh_processor::MeshProcessor;
+use crate::scene::timings::millis;
+
+// Files listed in BatchSummary::slowest
+const SLOWEST_FILES: usize = 5;
+
+pub type BatchResult = Vec<(PathBuf, Result<ImportResult, ImportError>)>;
+
+/* Imports every file, several at a time, see ImportOptions::with_max_parallel_files. A file failing to import, even by
+   panicking, leaves the others alone. The results are in the order of paths. */
+pub fn import_fbx_batch(paths: &[PathBuf], mesh_processors: &[Box<dyn MeshProcessor>], options: &ImportOptions) -> BatchResult {
+    import_fbx_batch_with_progress(paths, mesh_processors, options, &|_, _, _| {})
+}
+
+/* Same as import_fbx_batch, reporting the phases of every file to progress along with the index and path of the file.
+   Called from the threads importing the files. */
+pub fn import_fbx_batch_with_progress(paths: &[PathBuf], mesh_processors: &[Box<dyn MeshProcessor>], options: &ImportOptions,
+                                      progress: &(dyn Fn(usize, &Path, ProgressEvent) + Sync)) -> BatchResult {
+    let threads = match options.max_parallel_files {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }.min(paths.len()).max(1);
+
+    let next = AtomicUsize::new(0);
+    let import_next = || {
+        let mut imported = Vec::new();
+        loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            let path = match paths.get(index) {
+                Some(path) => path,
+                None => return imported,
+            };
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                import_path(path, mesh_processors, options, &mut |event| {
+                    progress(index, path, event);
+                    true
+                })
+            }));
+            imported.push((index, match result {
+                Ok(result) => result,
+                Err(_) => Err(ImportError::Panicked),
+            }));
+        }
+    };
+
+    let mut imported: Vec<(usize, Result<ImportResult, ImportError>)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads).map(|_| scope.spawn(import_next)).collect();
+        workers.into_iter().flat_map(|worker| worker.join().expect("batch worker panicked outside an import")).collect()
+    });
+    imported.sort_by_key(|(index, _)| *index);
+    imported.into_iter().map(|(index, result)| (paths[index].clone(), result)).collect()
+}
+
+/* Totals over the results of import_fbx_batch */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BatchSummary {
+    pub files: usize,
+    pub imported: usize,
+    // Files that could not be imported, along with why
+    pub failed: Vec<(PathBuf, String)>,
+    // Summed over the imported files
+    pub file_size: u64,
+    pub meshes: usize,
+    pub vertices: usize,
+    pub faces: usize,
+    pub wall_time: Duration,
+    // Imported files that took the longest, slowest first
+    pub slowest: Vec<(PathBuf, Duration)>,
+}
+
+impl BatchSummary {
+    pub fn new(results: &[(PathBuf, Result<ImportResult, ImportError>)]) -> Self {
+        let mut summary = BatchSummary { files: results.len(), ..Default::default() };
+        for (path, result) in results {
+            match result {
+                Ok(result) => {
+                    let metadata = &result.metadata;
+                    summary.imported += 1;
+                    summary.file_size += metadata.file_size;
+                    summary.meshes += metadata.meshes;
+                    summary.vertices += metadata.vertices;
+                    summary.faces += metadata.faces;
+                    summary.wall_time += metadata.wall_time;
+                    summary.slowest.push((path.clone(), metadata.wall_time));
+                }
+                Err(error) => summary.failed.push((path.clone(), error.to_string())),
+            }
+        }
+        summary.slowest.sort_by_key(|(_, wall_time)| std::cmp::Reverse(*wall_time));
+        summary.slowest.truncate(SLOWEST_FILES);
+        summary
+    }
+}
+
+impl fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of {} files imported, {} meshes, {} vertices, {} faces in {:.2} ms",
+               self.imported, self.files, self.meshes, self.vertices, self.faces, millis(self.wall_time))?;
+        for (path, wall_time) in &self.slowest {
+            write!(f, "\n  {:>10.2} ms  {}", millis(*wall_time), path.display())?;
+        }
+        for (path, error) in &self.failed {
+            write!(f, "\n  failed: {}: {}", path.display(), error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::FbxFixture;
+    use crate::fbx::FbxString;
+    use std::sync::Mutex;
+
+    fn create_file(name: &str) -> Vec<u8> {
+        FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("Geometry", vec![1i64.into(), FbxString::new(name).with_class("Geometry").into(), "Mesh".into()], |g| g
+                    .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3])))
+            .bytes()
+    }
+
+    #[test]
+    fn import_fbx_batch_should_import_every_file_and_report_the_corrupt_one() {
+        // Arrange
+        let directory = std::env::temp_dir().join(format!("fbximport_batch_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut corrupt = create_file("corrupt");
+        corrupt[0] = b'X';
+        let files = [("first.fbx", create_file("first")), ("corrupt.fbx", corrupt), ("second.fbx", create_file("second"))];
+        let paths: Vec<PathBuf> = files.iter()
+            .map(|(name, bytes)| {
+                let path = directory.join(name);
+                std::fs::write(&path, bytes).unwrap();
+                path
+            })
+            .collect();
+        let events = Mutex::new(Vec::new());
+
+        // Act
+        let results = import_fbx_batch_with_progress(&paths, &[], &ImportOptions::new().with_max_parallel_files(2),
+                                                     &|index, _, event| events.lock().unwrap().push((index, event)));
+        let summary = BatchSummary::new(&results);
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        // Assert
+        let order: Vec<&PathBuf> = results.iter().map(|(path, _)| path).collect();
+        assert_eq!(order, paths.iter().collect::<Vec<_>>());
+        assert!(matches!(results[1].1, Err(ImportError::Parse(_))));
+        assert_eq!(results[2].1.as_ref().unwrap().scene.meshes[0].name, "second");
+        assert_eq!(summary.files, 3);
+        assert_eq!(summary.imported, 2);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.failed[0].0, paths[1]);
+        assert_eq!(summary.failed[0].1, "Could not parse file: file header magic string is incorrect");
+        assert_eq!(summary.meshes, 2);
+        assert_eq!(summary.faces, 2);
+        assert_eq!(summary.slowest.len(), 2);
+        assert!(summary.to_string().starts_with("2 of 3 files imported"));
+        assert!(summary.to_string().ends_with("corrupt.fbx: Could not parse file: file header magic string is incorrect"));
+        let events = events.into_inner().unwrap();
+        assert!(events.contains(&(1, ProgressEvent::Parsing { file_size: files[1].1.len() as u64 })));
+        assert!(events.contains(&(2, ProgressEvent::Finished)));
+        assert!(!events.contains(&(1, ProgressEvent::Finished)));
+    }
+
+    #[test]
+    fn import_fbx_batch_should_fail_missing_files_without_panicking() {
+        // Arrange
+        let paths = vec![std::env::temp_dir().join(format!("fbximport_batch_missing_{}.fbx", std::process::id()))];
+
+        // Act
+        let results = import_fbx_batch(&paths, &[], &ImportOptions::new());
+
+        // Assert
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0].1, Err(ImportError::Io(_))));
+        assert!(BatchSummary::new(&results).failed[0].1.starts_with("Could not read file: "));
+    }
+}
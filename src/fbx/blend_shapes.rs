@@ -0,0 +1,170 @@
+use crate::warnings::warning;
+use crate::fbx::animation::read_curves;
+use crate::fbx::arena_document::NodeRef;
+use crate::fbx::properties70::Properties70;
+use crate::scene::blend_shape::{BlendShapeChannel, BlendShapeTarget};
+use crate::scene::connections::{ConnectionKind, Connections};
+use std::collections::{HashMap, HashSet};
+
+fn objects_of<'a>(objects: NodeRef<'a>, node: &'a str, kind: &'a str) -> impl Iterator<Item=(i64, NodeRef<'a>)> + 'a {
+    objects.children().get_multiple(node)
+        .filter(move |o| o.properties().get(2).and_then(|p| p.as_string()).map(|s| s.name()) == Some(kind))
+        .filter_map(|o| Some((o.properties().first()?.as_i64()?, o)))
+}
+
+fn name_of(object: NodeRef) -> String {
+    object.properties().get(1).and_then(|p| p.as_string()).map_or(String::new(), |s| s.name().to_string())
+}
+
+fn read_target(shape: NodeRef, id: i64, full_weight: f32) -> Option<BlendShapeTarget> {
+    let indices = shape.children().get_first("Indexes").ok()?.properties().first()?.as_i32_array()?;
+    let vertices = shape.children().get_first("Vertices").ok()?.property_array_view(0).ok()?;
+    if vertices.len() != indices.len() * 3 {
+        warning!("Shape {} has {} indices but {} offset coordinates, leaving it out.", id, indices.len(), vertices.len());
+        return None;
+    }
+
+    let deltas = vertices.as_f32_vec_lossy().chunks(3).map(|d| glm::vec3(d[0], d[1], d[2])).collect();
+    Some(BlendShapeTarget::new(id, name_of(shape), full_weight, indices.to_vec(), deltas))
+}
+
+/* Full weight of each of count targets in percent. Files leaving out FullWeights space the targets evenly. */
+fn full_weights(channel: NodeRef, count: usize) -> Vec<f64> {
+    match channel.children().get_first("FullWeights").ok().and_then(|c| c.properties().first()).and_then(|p| p.as_f64_array()) {
+        Some(weights) if weights.len() == count => weights.to_vec(),
+        _ => (1..=count).map(|i| 100.0 * i as f64 / count as f64).collect(),
+    }
+}
+
+/* Reads the BlendShape deformers by the id of the geometry they deform. Shapes are connected to their channel,
+   channels to their blend shape and blend shapes to the geometry with OO connections. An animated DeformPercent is
+   connected as a curve node on the channel's DeformPercent property. */
+pub(super) fn read_blend_shapes(objects: NodeRef, connections: &Connections) -> HashMap<i64, Vec<BlendShapeChannel>> {
+    let shapes: HashMap<i64, NodeRef> = objects_of(objects, "Geometry", "Shape").collect();
+    let channels: HashMap<i64, NodeRef> = objects_of(objects, "Deformer", "BlendShapeChannel").collect();
+    let blend_shapes: HashMap<i64, NodeRef> = objects_of(objects, "Deformer", "BlendShape").collect();
+
+    let connections: Vec<(ConnectionKind, i64, i64, &str)> = connections.iter()
+        .map(|c| (c.kind, c.child, c.parent, c.property()))
+        .collect();
+    let children_of = |parents: &HashMap<i64, NodeRef>, children: &HashMap<i64, NodeRef>| {
+        let mut result: HashMap<i64, Vec<i64>> = HashMap::new();
+        for (kind, child, parent, _) in &connections {
+            if *kind == ConnectionKind::ObjectObject && parents.contains_key(parent) && children.contains_key(child) {
+                result.entry(*parent).or_default().push(*child);
+            }
+        }
+        result
+    };
+    let shapes_of = children_of(&channels, &shapes);
+    let channels_of = children_of(&blend_shapes, &channels);
+
+    // Curve on the DeformPercent of each channel, through the curve node in between
+    let mut curve_node_of = HashMap::new();
+    for (kind, child, parent, property) in &connections {
+        if *kind == ConnectionKind::ObjectProperty && *property == "DeformPercent" && channels.contains_key(parent) {
+            curve_node_of.entry(*child).or_insert(*parent);
+        }
+    }
+    let weight_curve_ids: HashSet<i64> = connections.iter()
+        .filter(|(kind, _, parent, property)| *kind == ConnectionKind::ObjectProperty && *property == "d|DeformPercent" && curve_node_of.contains_key(parent))
+        .map(|(_, child, _, _)| *child)
+        .collect();
+    let mut curves = read_curves(objects, |id| weight_curve_ids.contains(&id));
+    let mut weight_curves = HashMap::new();
+    for (kind, child, parent, property) in &connections {
+        if let (true, Some(channel)) = (*kind == ConnectionKind::ObjectProperty && *property == "d|DeformPercent", curve_node_of.get(parent)) {
+            if let Some(curve) = curves.remove(child) {
+                weight_curves.entry(*channel).or_insert(curve);
+            }
+        }
+    }
+
+    let mut result = HashMap::new();
+    for (kind, child, parent, _) in &connections {
+        if *kind != ConnectionKind::ObjectObject || !blend_shapes.contains_key(child) || result.contains_key(parent) {
+            continue;
+        }
+
+        let mut geometry_channels = Vec::new();
+        for channel_id in channels_of.get(child).into_iter().flatten() {
+            let node = channels[channel_id];
+            let shape_ids = shapes_of.get(channel_id).map_or(&[][..], |s| &s[..]);
+            let targets = shape_ids.iter().zip(full_weights(node, shape_ids.len()))
+                .filter_map(|(id, full_weight)| read_target(shapes[id], *id, (full_weight / 100.0) as f32))
+                .collect();
+
+            let mut channel = BlendShapeChannel::new(*channel_id, name_of(node), targets);
+            let deform_percent = node.children().get_first("DeformPercent").ok()
+                .and_then(|d| d.properties().first())
+                .and_then(|p| p.as_f64())
+                .or_else(|| Properties70::from(node).number("DeformPercent"));
+            channel.deform_percent = deform_percent.unwrap_or(0.0) as f32;
+            channel.weight_curve = weight_curves.remove(channel_id);
+            geometry_channels.push(channel);
+        }
+        result.insert(*parent, geometry_channels);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::hierarchy::read_connections;
+    use crate::fbx::fixture::FbxFixture;
+    use crate::fbx::property::{FbxString, PropertyRecordType};
+
+    fn object(id: i64, class: &str, kind: &str) -> Vec<PropertyRecordType> {
+        vec![id.into(), FbxString::new("Smile").with_class(class).into(), kind.into()]
+    }
+
+    fn connection(kind: &str, child: i64, parent: i64, property: &str) -> Vec<PropertyRecordType> {
+        let mut properties: Vec<PropertyRecordType> = vec![kind.into(), child.into(), parent.into()];
+        if !property.is_empty() {
+            properties.push(property.into());
+        }
+        properties
+    }
+
+    #[test]
+    fn read_blend_shapes_should_read_in_between_targets_and_weight_curve() {
+        // Arrange
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("Deformer", object(10, "Deformer", "BlendShape"), |n| n)
+                .node_with_props("Deformer", object(20, "SubDeformer", "BlendShapeChannel"), |n| n
+                    .leaf("DeformPercent", 25.0)
+                    .double_array("FullWeights", vec![50.0, 100.0]))
+                .node_with_props("Geometry", object(31, "Geometry", "Shape"), |n| n
+                    .i32_array("Indexes", vec![4])
+                    .double_array("Vertices", vec![0.0, 1.0, 0.0]))
+                .node_with_props("Geometry", object(32, "Geometry", "Shape"), |n| n
+                    .i32_array("Indexes", vec![4])
+                    .double_array("Vertices", vec![0.0, 3.0, 0.0]))
+                .node_with_props("AnimationCurveNode", object(40, "AnimCurveNode", ""), |n| n)
+                .node_with_props("AnimationCurve", object(41, "AnimCurve", ""), |n| n
+                    .i64_array("KeyTime", vec![0, 100])
+                    .float_array("KeyValueFloat", vec![0.0, 100.0])))
+            .node("Connections", |c| c
+                .node_with_props("C", connection("OO", 10, 1, ""), |n| n)
+                .node_with_props("C", connection("OO", 20, 10, ""), |n| n)
+                .node_with_props("C", connection("OO", 31, 20, ""), |n| n)
+                .node_with_props("C", connection("OO", 32, 20, ""), |n| n)
+                .node_with_props("C", connection("OP", 40, 20, "DeformPercent"), |n| n)
+                .node_with_props("C", connection("OP", 41, 40, "d|DeformPercent"), |n| n))
+            .parse();
+
+        // Act
+        let blend_shapes = read_blend_shapes(nodes.get("Objects").ok().unwrap(), &read_connections(None, nodes.get("Connections").ok(), None));
+
+        // Assert
+        let channel = &blend_shapes[&1][0];
+        assert_eq!(channel.deform_percent(), 25.0);
+        assert_eq!(channel.targets().iter().map(|t| t.full_weight()).collect::<Vec<f32>>(), vec![0.5, 1.0]);
+        assert_eq!(channel.weight_curve().map(|c| c.id()), Some(41));
+        let deltas: Vec<(usize, glm::Vec3)> = channel.evaluate_deltas(0.75).collect();
+        assert_eq!(deltas, vec![(4, glm::vec3(0.0, 2.0, 0.0))]);
+    }
+}
\ No newline at end of file
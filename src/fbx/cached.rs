@@ -0,0 +1,246 @@
+use crate::warnings::warning;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use crate::fbx::{import_path, no_progress, report, ImportOptions};
+use crate::mesh_processor::MeshProcessor;
+use crate::scene::Scene;
+use crate::scene::cache::{read_scene, CacheSource};
+use crate::scene::mesh::content_hash::Fnv1a;
+
+/* Scene from import_fbx_cached */
+pub struct CachedImport {
+    pub scene: Scene,
+    // Whether the scene was loaded from the cache instead of imported
+    pub from_cache: bool,
+}
+
+/* Cache of the file at path, named after the file and a hash of its full path so files with the same name in
+   different directories get a cache each */
+fn cache_path(path: &Path, cache_dir: &Path) -> PathBuf {
+    let full_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mut hasher = Fnv1a::new();
+    hasher.write(full_path.to_string_lossy().as_bytes());
+    let name = path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+    cache_dir.join(format!("{}-{:016x}.scene", name, hasher.0))
+}
+
+/* The cached scene if the cache was made by this version from the same contents and settings. Files that were
+   touched without changing are recognized by their hash, and their new modification time is stored so the next
+   import does not hash them again. */
+fn load_cache(cache: &Path, path: &Path, current: &CacheSource) -> Option<Scene> {
+    let mut reader = BufReader::new(File::open(cache).ok()?);
+    let cached = CacheSource::read(&mut reader).ok()?;
+    if cached.size != current.size || cached.settings != current.settings {
+        return None;
+    }
+    let touched = cached.modified != current.modified;
+    if touched && CacheSource::of_file(path, current.settings).ok()?.hash != cached.hash {
+        return None;
+    }
+
+    match read_scene(&mut reader) {
+        Ok(scene) => {
+            if touched {
+                let source = CacheSource { modified: current.modified, ..cached };
+                if let Err(e) = File::options().write(true).open(cache).and_then(|mut file| source.write(&mut file)) {
+                    warning!("Could not update scene cache {}: {}", cache.display(), e);
+                }
+            }
+            Some(scene)
+        }
+        Err(e) => {
+            warning!("Could not read scene cache {}: {}, importing again.", cache.display(), e);
+            None
+        }
+    }
+}
+
+/* Written next to the cache first, so an interrupted write never leaves a cache that looks valid */
+fn write_cache(cache: &Path, scene: &Scene, source: &CacheSource) -> std::io::Result<()> {
+    if let Some(directory) = cache.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    let partial = cache.with_extension("partial");
+    scene.write_cache(BufWriter::new(File::create(&partial)?), source)?;
+    std::fs::rename(&partial, cache)
+}
+
+/* Loads the scene from cache_dir if the file was imported before with the same options and processors, otherwise
+   imports it and stores it there for next time. Caches that are out of date, damaged or from another version of the
+   format are replaced, as are caches made with other processors or processor settings, see MeshProcessor::fingerprint. */
+pub fn import_fbx_cached(path: &str, cache_dir: &Path, mesh_processors: Vec<Box<dyn MeshProcessor>>, options: ImportOptions) -> Option<CachedImport> {
+    let path = Path::new(path);
+    let settings = options.fingerprint(&mesh_processors);
+    let cache = cache_path(path, cache_dir);
+
+    let current = match CacheSource::stat(path, settings) {
+        Ok(current) => current,
+        Err(e) => {
+            eprintln!("Could not open file {}: {}, aborting import.", path.display(), e);
+            return None;
+        }
+    };
+    if let Some(scene) = load_cache(&cache, path, &current) {
+        return Some(CachedImport { scene, from_cache: true });
+    }
+
+    let result = report(import_path(path, &mesh_processors, &options, &mut no_progress))?;
+    if let Err(e) = CacheSource::of_file(path, settings).and_then(|source| write_cache(&cache, &result.scene, &source)) {
+        warning!("Could not write scene cache {}: {}", cache.display(), e);
+    }
+    Some(CachedImport { scene: result.scene, from_cache: false })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::FbxFixture;
+    use crate::fbx::{import_fbx_from_bytes, FbxString};
+    use crate::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
+    use crate::scene::cache::CACHE_VERSION;
+    use crate::scene::snapshot::to_snapshot;
+    use std::time::{Duration, SystemTime};
+
+    fn create_file(x: f64) -> Vec<u8> {
+        FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("Geometry", vec![1i64.into(), FbxString::new("quad").with_class("Geometry").into(), "Mesh".into()], |g| g
+                    .double_array("Vertices", vec![0.0, 0.0, 0.0, x, 0.0, 0.0, x, 1.0, 0.0, 0.0, 1.0, 0.0])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, 2, -4])))
+            .bytes()
+    }
+
+    fn processors() -> Vec<Box<dyn MeshProcessor>> {
+        vec![Box::new(TriangulateMeshProcessor::new())]
+    }
+
+    fn directory(test: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("fbximport_cache_{}_{}", test, std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn read_cache_should_give_back_the_scene_that_was_written() {
+        // Arrange
+        let scene = import_fbx_from_bytes(&create_file(2.0), processors(), ImportOptions::new()).unwrap();
+        let source = CacheSource { size: 1, modified: 2, hash: 3, settings: 4 };
+        let mut bytes = Vec::new();
+
+        // Act
+        scene.write_cache(&mut bytes, &source).unwrap();
+        let (read_source, read) = Scene::read_cache(bytes.as_slice()).unwrap();
+
+        // Assert
+        assert_eq!(read_source, source);
+        assert_eq!(to_snapshot(&read), to_snapshot(&scene));
+        assert_eq!(read.timings(), scene.timings());
+        assert_eq!(read.header(), scene.header());
+        assert_eq!(read.meshes[0].faces.iter().map(|f| f.polygon).collect::<Vec<_>>(), vec![0, 0]);
+    }
+
+    #[test]
+    fn import_fbx_cached_should_import_again_once_the_file_changed() {
+        // Arrange
+        let directory = directory("stale");
+        let path = directory.join("quad.fbx");
+        let cache_dir = directory.join("cache");
+        std::fs::write(&path, create_file(2.0)).unwrap();
+        let import = || import_fbx_cached(path.to_str().unwrap(), &cache_dir, processors(), ImportOptions::new()).unwrap();
+
+        // Act
+        let first = import();
+        let second = import();
+        // Touched without changing the contents
+        File::options().write(true).open(&path).unwrap().set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        let touched = import();
+        std::fs::write(&path, create_file(3.0)).unwrap();
+        let changed = import();
+        let with_other_options = import_fbx_cached(path.to_str().unwrap(), &cache_dir, Vec::new(), ImportOptions::new()).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        // Assert
+        assert!(!first.from_cache);
+        assert!(second.from_cache);
+        assert!(touched.from_cache);
+        assert!(!changed.from_cache);
+        assert_eq!(changed.scene.meshes[0].vertices[1], glm::vec3(3.0, 0.0, 0.0));
+        assert!(!with_other_options.from_cache);
+        assert_eq!(with_other_options.scene.meshes[0].faces.len(), 1);
+    }
+
+    #[test]
+    fn import_fbx_cached_should_store_the_modification_time_of_a_touched_file() {
+        // Arrange
+        let directory = directory("touched");
+        let path = directory.join("quad.fbx");
+        std::fs::write(&path, create_file(2.0)).unwrap();
+        let import = || import_fbx_cached(path.to_str().unwrap(), &directory, processors(), ImportOptions::new()).unwrap();
+        import();
+        File::options().write(true).open(&path).unwrap().set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        let cache = cache_path(&path, &directory);
+        let before = CacheSource::read(&mut File::open(&cache).unwrap()).unwrap();
+
+        // Act
+        let touched = import();
+        let after = CacheSource::read(&mut File::open(&cache).unwrap()).unwrap();
+        let current = CacheSource::stat(&path, after.settings).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        // Assert
+        assert!(touched.from_cache);
+        assert_ne!(before.modified, current.modified);
+        assert_eq!(after, CacheSource { modified: current.modified, ..before });
+    }
+
+    #[test]
+    fn import_fbx_cached_should_import_again_once_processor_settings_changed() {
+        // Arrange
+        let directory = directory("settings");
+        let path = directory.join("quad.fbx");
+        std::fs::write(&path, create_file(2.0)).unwrap();
+        let import = |max_polygon_size: usize| import_fbx_cached(
+            path.to_str().unwrap(),
+            &directory,
+            vec![Box::new(TriangulateMeshProcessor::new().with_max_polygon_size(max_polygon_size))],
+            ImportOptions::new()).unwrap();
+
+        // Act
+        let triangles = import(3);
+        let quads = import(4);
+        let quads_again = import(4);
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        // Assert
+        assert!(!triangles.from_cache);
+        assert_eq!(triangles.scene.meshes[0].faces.len(), 2);
+        assert!(!quads.from_cache);
+        assert_eq!(quads.scene.meshes[0].faces.len(), 1);
+        assert!(quads_again.from_cache);
+    }
+
+    #[test]
+    fn import_fbx_cached_should_import_again_for_a_cache_of_another_version() {
+        // Arrange
+        let directory = directory("version");
+        let path = directory.join("quad.fbx");
+        std::fs::write(&path, create_file(2.0)).unwrap();
+        let import = || import_fbx_cached(path.to_str().unwrap(), &directory, processors(), ImportOptions::new()).unwrap();
+        import();
+        let cache = cache_path(&path, &directory);
+        let mut bytes = std::fs::read(&cache).unwrap();
+        bytes[8..12].copy_from_slice(&(CACHE_VERSION + 1).to_le_bytes());
+        std::fs::write(&cache, bytes).unwrap();
+
+        // Act
+        let outdated = import();
+        let rewritten = import();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        // Assert
+        assert!(!outdated.from_cache);
+        assert_eq!(outdated.scene.meshes[0].faces.len(), 2);
+        assert!(rewritten.from_cache);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::fbx::arena_document::NodeRef;
+use crate::scene::connections::{ConnectionKind, Connections};
+use crate::fbx::properties70::Properties70;
+use crate::scene::camera::{ApertureMode, Camera, Projection};
+
+/* Reads the camera NodeAttribute objects along with the node each one is attached to */
+pub(super) fn read_cameras(objects: NodeRef, connections: &Connections) -> Vec<Camera> {
+    let mut cameras = Vec::new();
+    for node in objects.children().get_multiple("NodeAttribute") {
+        if node.properties().get(2).and_then(|p| p.as_string()).map(|s| s.name()) != Some("Camera") {
+            continue;
+        }
+        let id = match node.properties().first().and_then(|p| p.as_i64()) {
+            Some(id) => id,
+            None => continue
+        };
+        let name = node.properties().get(1).and_then(|p| p.as_string()).map_or(String::new(), |s| s.name().to_string());
+        let properties = Properties70::from(node);
+
+        let mut camera = Camera::new(id, name);
+        camera.node_id = connections.parent_of(id, ConnectionKind::ObjectObject);
+        if let Some(mode) = properties.integer("ApertureMode") {
+            camera.aperture_mode = ApertureMode::from_fbx(mode);
+        }
+        if properties.integer("CameraProjectionType") == Some(1) {
+            camera.projection = Projection::Orthographic;
+        }
+        // Fixed ratio keeps the ratio in AspectWidth
+        camera.fixed_ratio = properties.integer("AspectRatioMode") == Some(1);
+
+        let number = |name: &str, target: &mut f32| {
+            if let Some(value) = properties.number(name) {
+                *target = value as f32;
+            }
+        };
+        number("FieldOfView", &mut camera.field_of_view);
+        number("FieldOfViewX", &mut camera.field_of_view_x);
+        number("FieldOfViewY", &mut camera.field_of_view_y);
+        number("FocalLength", &mut camera.focal_length);
+        number("FilmWidth", &mut camera.film_width);
+        number("FilmHeight", &mut camera.film_height);
+        number("AspectWidth", &mut camera.aspect_width);
+        number("AspectHeight", &mut camera.aspect_height);
+        number("NearPlane", &mut camera.near_plane);
+        number("FarPlane", &mut camera.far_plane);
+        number("OrthoZoom", &mut camera.ortho_zoom);
+
+        cameras.push(camera);
+    }
+
+    cameras
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::hierarchy::read_connections;
+    use crate::fbx::fixture::FbxFixture;
+    use crate::fbx::property::{FbxString, PropertyRecordType};
+
+    fn p(name: &str, kind: &str, value: PropertyRecordType) -> Vec<PropertyRecordType> {
+        vec![name.into(), kind.into(), "".into(), "A".into(), value]
+    }
+
+    #[test]
+    fn read_cameras_should_compute_fov_of_maya_camera() {
+        // Arrange
+        // Properties of the default perspective camera as Maya exports it, 35mm lens on a 35mm full aperture back
+        let attribute = vec![5i64.into(), FbxString::new("perspShape").with_class("NodeAttribute").into(), "Camera".into()];
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("NodeAttribute", attribute, |n| n
+                    .node("Properties70", |p70| p70
+                        .node_with_props("P", p("ApertureMode", "enum", 3i32.into()), |n| n)
+                        .node_with_props("P", p("FocalLength", "Number", 35.0.into()), |n| n)
+                        .node_with_props("P", p("FilmWidth", "double", 1.417.into()), |n| n)
+                        .node_with_props("P", p("FilmHeight", "double", 0.945.into()), |n| n)
+                        .node_with_props("P", p("AspectWidth", "double", 640.0.into()), |n| n)
+                        .node_with_props("P", p("AspectHeight", "double", 480.0.into()), |n| n)
+                        .node_with_props("P", p("NearPlane", "double", 0.1.into()), |n| n))))
+            .node("Connections", |c| c
+                .node_with_props("C", vec!["OO".into(), 5i64.into(), 50i64.into()], |n| n))
+            .parse();
+
+        // Act
+        let cameras = read_cameras(nodes.get("Objects").ok().unwrap(), &read_connections(None, nodes.get("Connections").ok(), None));
+
+        // Assert
+        let camera = &cameras[0];
+        assert_eq!(camera.node_id(), Some(50));
+        assert!((camera.vertical_fov_radians().to_degrees() - 37.8).abs() < 0.1);
+        assert!((camera.near_plane() - 0.1).abs() < 1e-6);
+        assert_eq!(camera.far_plane(), 4000.0);
+        let projection = camera.projection_matrix(None);
+        assert!((projection[0][0] * camera.aspect_ratio() - projection[1][1]).abs() < 1e-5);
+    }
+}
\ No newline at end of file
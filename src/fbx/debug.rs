@@ -0,0 +1,128 @@
+use crate::fbx::arena_document::ArenaDocument;
+use std::collections::{BTreeSet, HashSet};
+use std::io::Write;
+
+// Object id Connections use for the scene root, which has no entry under Objects
+const ROOT_ID: i64 = 0;
+
+fn fill_color(class: &str) -> &'static str {
+    match class {
+        "Model" => "lightblue",
+        "Geometry" => "palegreen",
+        "Material" => "gold",
+        "Texture" | "Video" => "orange",
+        "Deformer" | "SubDeformer" => "pink",
+        "AnimationStack" | "AnimationLayer" | "AnimationCurveNode" | "AnimationCurve" => "lavender",
+        "NodeAttribute" => "lightgrey",
+        _ => "white",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/* Writes the objects and connections of a document as a Graphviz digraph, for looking at files by hand:
+
+   fbx::debug::connections_to_dot(&document, &mut File::create("scene.dot")?)?;
+   dot -Tsvg scene.dot -o scene.svg
+
+   Edges point from child to parent, the way Connections lists them. Object-property connections are labeled with
+   the property name. Works on the raw document, so it also helps with files the importer cannot make sense of. */
+pub fn connections_to_dot<W: Write>(document: &ArenaDocument, writer: &mut W) -> std::io::Result<()> {
+    writeln!(writer, "digraph fbx {{")?;
+    writeln!(writer, "    node [shape=box, style=filled];")?;
+
+    let mut ids = HashSet::new();
+    if let Ok(objects) = document.get("Objects") {
+        for object in objects.children().iter() {
+            let id = match object.properties().first().and_then(|p| p.as_i64()) {
+                Some(id) => id,
+                None => continue
+            };
+            let name = object.properties().get(1).and_then(|p| p.as_string()).map_or("", |s| s.name());
+
+            ids.insert(id);
+            writeln!(writer, "    \"{}\" [label=\"{}: {} ({})\", fillcolor={}];",
+                     id, escape(object.name()), escape(name), id, fill_color(object.name()))?;
+        }
+    }
+
+    let connections: Vec<_> = document.get("Connections").into_iter()
+        .flat_map(|connections| connections.get_multiple("C"))
+        .collect();
+
+    let missing: BTreeSet<i64> = connections.iter()
+        .flat_map(|c| c.properties().iter().skip(1).take(2))
+        .filter_map(|p| p.as_i64())
+        .filter(|id| !ids.contains(id))
+        .collect();
+    for id in missing {
+        if id == ROOT_ID {
+            writeln!(writer, "    \"{}\" [label=\"Root\", shape=ellipse];", id)?;
+        } else {
+            // Connected to an object that does not exist, usually what is being debugged
+            writeln!(writer, "    \"{}\" [label=\"Missing ({})\", fillcolor=white, style=dashed];", id, id)?;
+        }
+    }
+
+    for connection in connections {
+        let properties = connection.properties();
+        let kind = properties.first().and_then(|p| p.as_string()).map(|s| s.name());
+        let child = properties.get(1).and_then(|p| p.as_i64());
+        let parent = properties.get(2).and_then(|p| p.as_i64());
+        match (kind, child, parent) {
+            (Some("OO"), Some(child), Some(parent)) =>
+                writeln!(writer, "    \"{}\" -> \"{}\";", child, parent)?,
+            (Some("OP"), Some(child), Some(parent)) => {
+                let property = properties.get(3).and_then(|p| p.as_string()).map_or("", |s| s.name());
+                writeln!(writer, "    \"{}\" -> \"{}\" [label=\"{}\", style=dashed];", child, parent, escape(property))?
+            }
+            _ => {}
+        }
+    }
+
+    writeln!(writer, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::FbxFixture;
+    use crate::fbx::property::FbxString;
+
+    #[test]
+    fn connections_to_dot_should_write_objects_and_connections() {
+        // Arrange
+        let bytes = FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("Model", vec![10i64.into(), FbxString::new("Cube").with_class("Model").into(), "Mesh".into()], |m| m)
+                .node_with_props("Geometry", vec![20i64.into(), FbxString::new("Cube").with_class("Geometry").into(), "Mesh".into()], |g| g)
+                .node_with_props("Texture", vec![30i64.into(), FbxString::new("Wood \"oak\"").with_class("Texture").into(), "".into()], |t| t))
+            .node("Connections", |c| c
+                .node_with_props("C", vec!["OO".into(), 10i64.into(), 0i64.into()], |n| n)
+                .node_with_props("C", vec!["OO".into(), 20i64.into(), 10i64.into()], |n| n)
+                .node_with_props("C", vec!["OP".into(), 30i64.into(), 40i64.into(), "DiffuseColor".into()], |n| n))
+            .bytes();
+        let document = ArenaDocument::from_bytes(&bytes).unwrap();
+        let mut out = Vec::new();
+
+        // Act
+        connections_to_dot(&document, &mut out).unwrap();
+
+        // Assert
+        let expected = r#"digraph fbx {
+    node [shape=box, style=filled];
+    "10" [label="Model: Cube (10)", fillcolor=lightblue];
+    "20" [label="Geometry: Cube (20)", fillcolor=palegreen];
+    "30" [label="Texture: Wood \"oak\" (30)", fillcolor=orange];
+    "0" [label="Root", shape=ellipse];
+    "40" [label="Missing (40)", fillcolor=white, style=dashed];
+    "10" -> "0";
+    "20" -> "10";
+    "30" -> "40" [label="DiffuseColor", style=dashed];
+}
+"#;
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,111 @@
+use crate::fbx::arena_document::NodeRef;
+
+/* Number of objects of every type the Definitions node declares, such as ObjectType: "Geometry" { Count: 12 } */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct Definitions {
+    // In file order
+    counts: Vec<(String, usize)>,
+}
+
+/* Object type whose declared count does not match the objects in the file */
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct CountMismatch {
+    pub(super) kind: String,
+    pub(super) declared: usize,
+    pub(super) found: usize,
+}
+
+// Declared along with the objects but stored as a top level node
+const TOP_LEVEL_TYPES: [&str; 1] = ["GlobalSettings"];
+
+impl Definitions {
+    /* Declared count of every type, in file order */
+    pub(super) fn counts(&self) -> &[(String, usize)] {
+        &self.counts
+    }
+
+    pub(super) fn count(&self, kind: &str) -> Option<usize> {
+        self.counts.iter().find(|(k, _)| k == kind).map(|(_, count)| *count)
+    }
+
+    /* Number of objects of all types, None for files without definitions */
+    pub(super) fn total(&self) -> Option<usize> {
+        if self.counts.is_empty() {
+            return None;
+        }
+        Some(self.counts.iter().map(|(_, count)| *count).sum())
+    }
+
+    /* Declared count of a type, for preallocating. Capped at how many there can be, so a corrupt count does not
+       allocate more than the file holds. */
+    pub(super) fn capacity(&self, kind: &str, available: usize) -> usize {
+        self.count(kind).map_or(available, |count| count.min(available))
+    }
+
+    /* Compares the declared counts with the objects in the Objects node. Mismatches usually mean the file was
+       truncated or the exporter has a bug. */
+    pub(super) fn check_counts(&self, objects: NodeRef) -> Vec<CountMismatch> {
+        self.counts.iter()
+            .filter(|(kind, _)| !TOP_LEVEL_TYPES.contains(&kind.as_str()))
+            .filter_map(|(kind, declared)| {
+                let found = objects.children().count(kind);
+                if found == *declared {
+                    return None;
+                }
+                Some(CountMismatch { kind: kind.clone(), declared: *declared, found })
+            })
+            .collect()
+    }
+}
+
+/* Reads the declared object counts. Types without a usable count are left out. */
+pub(super) fn read_definitions(definitions: Option<NodeRef>) -> Definitions {
+    let mut counts = Vec::new();
+    for object_type in definitions.into_iter().flat_map(|d| d.children().get_multiple("ObjectType")) {
+        let kind = match object_type.properties().first().and_then(|p| p.as_string()) {
+            Some(kind) => kind.name().to_string(),
+            None => continue
+        };
+        let count = object_type.children().get_first("Count").ok()
+            .and_then(|n| n.properties().first())
+            .and_then(|p| p.as_i64());
+        if let Some(count) = count.filter(|c| *c >= 0) {
+            counts.push((kind, count as usize));
+        }
+    }
+
+    Definitions { counts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::FbxFixture;
+
+    #[test]
+    fn check_counts_should_report_types_with_missing_objects() {
+        // Arrange
+        let nodes = FbxFixture::new(7400)
+            .node("Definitions", |d| d
+                .node_with_props("ObjectType", vec!["GlobalSettings".into()], |t| t
+                    .node_with_props("Count", vec![1i32.into()], |n| n))
+                .node_with_props("ObjectType", vec!["Geometry".into()], |t| t
+                    .node_with_props("Count", vec![3i32.into()], |n| n))
+                .node_with_props("ObjectType", vec!["SomethingNew".into()], |t| t
+                    .node_with_props("Count", vec![0i32.into()], |n| n))
+                .node_with_props("ObjectType", vec!["NoCount".into()], |t| t))
+            .node("Objects", |o| o
+                .node_with_props("Geometry", vec![1i64.into()], |g| g)
+                .node_with_props("Geometry", vec![2i64.into()], |g| g))
+            .parse();
+        let definitions = read_definitions(nodes.get("Definitions").ok());
+
+        // Act
+        let mismatches = definitions.check_counts(nodes.get("Objects").ok().unwrap());
+
+        // Assert
+        assert_eq!(mismatches, vec![CountMismatch { kind: "Geometry".to_string(), declared: 3, found: 2 }]);
+        assert_eq!(definitions.count("NoCount"), None);
+        assert_eq!(definitions.capacity("Geometry", 2), 2);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,51 @@
+use crate::fbx::arena_document::NodeRef;
+
+/* A Document entry of the Documents node. Objects belong to the scene when Connections lead from them to the root
+   node, the root node itself is not an object in the file and is usually 0. */
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Document {
+    pub(super) id: i64,
+    pub(super) name: String,
+    pub(super) root_node: i64,
+}
+
+/* Reads the documents in file order. Files hold a single one in practice, which is the active document. */
+pub(super) fn read_documents(documents: Option<NodeRef>) -> Vec<Document> {
+    let mut result = Vec::new();
+    for document in documents.into_iter().flat_map(|d| d.children().get_multiple("Document")) {
+        let id = document.properties().first().and_then(|p| p.as_i64()).unwrap_or(0);
+        let name = document.properties().get(1)
+            .and_then(|p| p.as_string())
+            .map_or(String::new(), |s| s.name().to_string());
+        let root_node = document.children().get_first("RootNode").ok()
+            .and_then(|n| n.properties().first())
+            .and_then(|p| p.as_i64())
+            .unwrap_or(0);
+
+        result.push(Document { id, name, root_node });
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::FbxFixture;
+
+    #[test]
+    fn read_documents_should_read_the_root_node() {
+        // Arrange
+        let nodes = FbxFixture::new(7400)
+            .node("Documents", |d| d
+                .node_with_props("Count", vec![1i32.into()], |n| n)
+                .node_with_props("Document", vec![99i64.into(), "Scene".into(), "Scene".into()], |n| n
+                    .node_with_props("RootNode", vec![7i64.into()], |n| n)))
+            .parse();
+
+        // Act
+        let documents = read_documents(nodes.get("Documents").ok());
+
+        // Assert
+        assert_eq!(documents, vec![Document { id: 99, name: "Scene".to_string(), root_node: 7 }]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,205 @@
+use crate::fbx::writer::{FbxNode, FbxWriter};
+use crate::fbx::property::{PropertyRecordType, ArrayProperty, FbxString};
+use crate::fbx::arena_document::ArenaDocument;
+use crate::fbx::header::parse_header;
+use crate::fbx::node_reader::DEFAULT_MAX_DEPTH;
+use std::io::Cursor;
+
+/* Builds binary FBX files in memory for tests, so they do not depend on files from some exporter:
+
+   FbxFixture::new(7400).node("Objects", |o| o
+       .node_with_props("Geometry", vec![1i64.into(), "Cube".into(), "Mesh".into()], |g| g
+           .double_array("Vertices", vec![...])))
+
+   Offsets and null records are written by FbxWriter. */
+pub(crate) struct FbxFixture {
+    writer: FbxWriter,
+}
+
+impl FbxFixture {
+    pub(crate) fn new(version: u32) -> Self {
+        FbxFixture {
+            writer: FbxWriter::new(version),
+        }
+    }
+
+    pub(crate) fn compressed(mut self) -> Self {
+        self.writer = self.writer.with_compressed_arrays(true);
+        self
+    }
+
+    pub(crate) fn node<F>(self, name: &str, build: F) -> Self
+        where F: FnOnce(FixtureNode) -> FixtureNode
+    {
+        self.node_with_props(name, Vec::new(), build)
+    }
+
+    pub(crate) fn node_with_props<F>(mut self, name: &str, properties: Vec<PropertyRecordType>, build: F) -> Self
+        where F: FnOnce(FixtureNode) -> FixtureNode
+    {
+        self.writer = self.writer.with_node(build(FixtureNode::new(name, properties)).node);
+        self
+    }
+
+    pub(crate) fn bytes(&self) -> Vec<u8> {
+        self.writer.write()
+    }
+
+    /* Runs the file through the header and node parsers, the way parse_fbx does */
+    pub(crate) fn parse(&self) -> ArenaDocument {
+        let bytes = self.bytes();
+        let mut reader = Cursor::new(&bytes[..]);
+        let header = parse_header(&mut reader, true).unwrap();
+        ArenaDocument::parse(&mut reader, header.version, &[], DEFAULT_MAX_DEPTH, true).unwrap()
+    }
+}
+
+pub(crate) struct FixtureNode {
+    node: FbxNode,
+}
+
+impl FixtureNode {
+    fn new(name: &str, properties: Vec<PropertyRecordType>) -> Self {
+        let mut node = FbxNode::new(name);
+        for property in properties {
+            node = node.with_property(property);
+        }
+
+        FixtureNode {
+            node
+        }
+    }
+
+    pub(crate) fn property<P: Into<PropertyRecordType>>(mut self, property: P) -> Self {
+        self.node = self.node.with_property(property.into());
+        self
+    }
+
+    pub(crate) fn node<F>(self, name: &str, build: F) -> Self
+        where F: FnOnce(FixtureNode) -> FixtureNode
+    {
+        self.node_with_props(name, Vec::new(), build)
+    }
+
+    pub(crate) fn node_with_props<F>(mut self, name: &str, properties: Vec<PropertyRecordType>, build: F) -> Self
+        where F: FnOnce(FixtureNode) -> FixtureNode
+    {
+        self.node = self.node.with_child(build(FixtureNode::new(name, properties)).node);
+        self
+    }
+
+    /* Child node holding a single property, the shape most leaf nodes have */
+    pub(crate) fn leaf<P: Into<PropertyRecordType>>(self, name: &str, property: P) -> Self {
+        self.node_with_props(name, vec![property.into()], |n| n)
+    }
+
+    pub(crate) fn double_array(self, name: &str, values: Vec<f64>) -> Self {
+        self.leaf(name, PropertyRecordType::DoubleArray(ArrayProperty::from_values(values)))
+    }
+
+    pub(crate) fn float_array(self, name: &str, values: Vec<f32>) -> Self {
+        self.leaf(name, PropertyRecordType::FloatArray(ArrayProperty::from_values(values)))
+    }
+
+    pub(crate) fn i32_array(self, name: &str, values: Vec<i32>) -> Self {
+        self.leaf(name, PropertyRecordType::SignedInt32Array(ArrayProperty::from_values(values)))
+    }
+
+    pub(crate) fn i64_array(self, name: &str, values: Vec<i64>) -> Self {
+        self.leaf(name, PropertyRecordType::SignedInt64Array(ArrayProperty::from_values(values)))
+    }
+}
+
+// Conversions that let property lists mix types, as in vec![1i64.into(), "Mesh".into()]
+
+impl From<i32> for PropertyRecordType {
+    fn from(value: i32) -> Self {
+        PropertyRecordType::SignedInt32(value)
+    }
+}
+
+impl From<i64> for PropertyRecordType {
+    fn from(value: i64) -> Self {
+        PropertyRecordType::SignedInt64(value)
+    }
+}
+
+impl From<f64> for PropertyRecordType {
+    fn from(value: f64) -> Self {
+        PropertyRecordType::Double(value)
+    }
+}
+
+impl From<&str> for PropertyRecordType {
+    fn from(value: &str) -> Self {
+        PropertyRecordType::String(FbxString::new(value))
+    }
+}
+
+impl From<FbxString> for PropertyRecordType {
+    fn from(value: FbxString) -> Self {
+        PropertyRecordType::String(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::arena_document::{NodeList, NodeRef};
+
+    fn create_fixture(version: u32) -> FbxFixture {
+        FbxFixture::new(version)
+            .node("Creator", |n| n.property("tests"))
+            .node("Objects", |o| o
+                .node_with_props("Model", vec![1i64.into(), FbxString::new("Root").with_class("Model").into()], |m| m
+                    .node("Properties70", |p| p
+                        .node_with_props("P", vec!["Visibility".into(), 1.0.into()], |n| n)))
+                .node_with_props("Geometry", vec![2i64.into()], |g| g
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3])))
+    }
+
+    fn get<'a>(nodes: NodeList<'a>, name: &str) -> NodeRef<'a> {
+        nodes.get(name).expect(name)
+    }
+
+    fn assert_fixture_tree(nodes: NodeList) {
+        let creator = get(nodes, "Creator");
+        assert_eq!(creator.properties()[0].as_string().unwrap().name(), "tests");
+
+        let objects = get(nodes, "Objects");
+        let model = get(objects.children(), "Model");
+        assert_eq!(model.properties()[1].as_string().unwrap().class(), Some("Model"));
+        let p = get(get(model.children(), "Properties70").children(), "P");
+        assert_eq!(p.properties()[1], PropertyRecordType::Double(1.0));
+
+        let geometry = get(objects.children(), "Geometry");
+        let indices = get(geometry.children(), "PolygonVertexIndex");
+        assert_eq!(indices.properties()[0].as_i32_array(), Some(&[0, 1, -3][..]));
+    }
+
+    #[test]
+    fn parse_should_read_back_nested_nodes_with_32_bit_offsets() {
+        // Arrange
+        let fixture = create_fixture(7400);
+
+        // Act
+        let document = fixture.parse();
+
+        // Assert
+        assert_eq!(document.nodes().len(), 2);
+        assert_fixture_tree(document.nodes());
+    }
+
+    #[test]
+    fn parse_should_read_back_nested_nodes_with_64_bit_offsets() {
+        // Arrange
+        let fixture = create_fixture(7500).compressed();
+
+        // Act
+        let document = fixture.parse();
+
+        // Assert
+        assert_eq!(document.nodes().len(), 2);
+        assert_fixture_tree(document.nodes());
+    }
+}
\ No newline at end of file
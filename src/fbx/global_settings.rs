@@ -0,0 +1,59 @@
+use crate::fbx::arena_document::NodeRef;
+use crate::fbx::properties70::Properties70;
+use crate::scene::time::{KTime, TimeMode, TimeSettings};
+
+/* Reads the frame rate and time span from the GlobalSettings node, missing entries keep their defaults */
+pub(super) fn read_time_settings(global_settings: Option<NodeRef>) -> TimeSettings {
+    let mut settings = TimeSettings::default();
+    let properties = match global_settings {
+        Some(node) => Properties70::from(node),
+        None => return settings
+    };
+
+    if let Some(mode) = properties.integer("TimeMode") {
+        // Exporters leave CustomFrameRate at -1 when it is not used
+        let custom_rate = properties.number("CustomFrameRate").filter(|r| *r > 0.0).unwrap_or(30.0);
+        settings.mode = TimeMode::from_fbx(mode, custom_rate);
+    }
+    if let Some(start) = properties.integer("TimeSpanStart") {
+        settings.start = KTime(start);
+    }
+    if let Some(stop) = properties.integer("TimeSpanStop") {
+        settings.stop = KTime(stop);
+    }
+
+    settings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::FbxFixture;
+    use crate::fbx::property::PropertyRecordType;
+
+    fn p(name: &str, kind: &str, value: PropertyRecordType) -> Vec<PropertyRecordType> {
+        vec![name.into(), kind.into(), "".into(), "".into(), value]
+    }
+
+    #[test]
+    fn read_time_settings_should_read_mode_and_span() {
+        // Arrange
+        let nodes = FbxFixture::new(7400)
+            .node("GlobalSettings", |g| g
+                .node("Properties70", |p70| p70
+                    .node_with_props("P", p("TimeMode", "enum", 14i32.into()), |n| n)
+                    .node_with_props("P", p("CustomFrameRate", "double", 12.5.into()), |n| n)
+                    .node_with_props("P", p("TimeSpanStart", "KTime", 0i64.into()), |n| n)
+                    .node_with_props("P", p("TimeSpanStop", "KTime", 46_186_158_000i64.into()), |n| n)))
+            .parse();
+
+        // Act
+        let settings = read_time_settings(nodes.get("GlobalSettings").ok());
+
+        // Assert
+        assert_eq!(settings.mode, TimeMode::Custom(12.5));
+        assert_eq!(settings.start, KTime(0));
+        assert_eq!(settings.stop.seconds(), 1.0);
+        assert_eq!(settings.stop.frames(settings.mode), 12.5);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,227 @@
+use std::io::Read;
+use crate::fbx::{ParseResult, ParseError};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Compression method, the only one defined is deflate
+const DEFLATE: u8 = 8;
+
+// Flags announcing optional header fields
+const FLAG_HEADER_CRC: u8 = 0x02;
+const FLAG_EXTRA: u8 = 0x04;
+const FLAG_NAME: u8 = 0x08;
+const FLAG_COMMENT: u8 = 0x10;
+
+// Magic, method, flags, modification time, extra flags and operating system
+const HEADER_LENGTH: usize = 10;
+
+// CRC-32 and length of the inflated data
+const TRAILER_LENGTH: usize = 8;
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { 0xedb88320 ^ (crc >> 1) } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, b| CRC_TABLE[((crc ^ *b as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+pub(crate) fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC)
+}
+
+fn error(message: &str) -> ParseError {
+    ParseError::ValidationError(format!("gzip stream {}", message))
+}
+
+/* Length of the member header, which has a variable length when it holds the original file name or the like */
+fn header_length(data: &[u8]) -> ParseResult<usize> {
+    if data.len() < HEADER_LENGTH + TRAILER_LENGTH {
+        return Err(error("is too short"));
+    }
+    if data[2] != DEFLATE {
+        return Err(error(&format!("uses unknown compression method {}", data[2])));
+    }
+
+    let flags = data[3];
+    let mut length = HEADER_LENGTH;
+    if flags & FLAG_EXTRA != 0 {
+        let extra = data.get(length..length + 2).ok_or_else(|| error("header is cut off"))?;
+        length += 2 + u16::from_le_bytes([extra[0], extra[1]]) as usize;
+    }
+    // Zero terminated strings
+    for flag in &[FLAG_NAME, FLAG_COMMENT] {
+        if flags & flag != 0 {
+            let end = data.get(length..).and_then(|rest| rest.iter().position(|b| *b == 0))
+                .ok_or_else(|| error("header is cut off"))?;
+            length += end + 1;
+        }
+    }
+    if flags & FLAG_HEADER_CRC != 0 {
+        length += 2;
+    }
+
+    if length + TRAILER_LENGTH > data.len() {
+        return Err(error("header is cut off"));
+    }
+    Ok(length)
+}
+
+/* Inflates a gzip stream held in memory, checking it against the length and CRC-32 stored after it. Only the first
+   member is read, files written by gzip and the usual libraries have just the one. Fails once the output would grow
+   past max_size, as a small stream can inflate to far more memory than it takes up. */
+pub(crate) fn gunzip(data: &[u8], max_size: u64) -> ParseResult<Vec<u8>> {
+    let start = header_length(data)?;
+    let end = data.len() - TRAILER_LENGTH;
+
+    let mut output = Vec::new();
+    let mut stream = inflate::InflateStream::new();
+    let mut position = start;
+    loop {
+        let (consumed, chunk) = stream.update(&data[position..end])
+            .map_err(|e| error(&format!("is corrupt: {}", e)))?;
+        if chunk.is_empty() {
+            break;
+        }
+        if (output.len() + chunk.len()) as u64 > max_size {
+            return Err(error(&format!("inflates to more than the limit of {} bytes", max_size)));
+        }
+        output.extend_from_slice(chunk);
+        position += consumed;
+    }
+
+    let trailer = &data[end..];
+    let expected_crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    // Length modulo 2^32
+    let expected_length = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    if output.len() as u32 != expected_length || crc32(&output) != expected_crc {
+        return Err(error("does not match the length and checksum stored after it"));
+    }
+    Ok(output)
+}
+
+/* Everything left in reader, inflated if it starts like a gzip stream. Neither the input nor the inflated data may
+   be larger than max_size. */
+pub(crate) fn read_to_memory<R: Read>(reader: R, max_size: u64) -> ParseResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(max_size.saturating_add(1)).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > max_size {
+        return Err(ParseError::ValidationError(format!("input is larger than the limit of {} bytes", max_size)));
+    }
+
+    if is_gzip(&bytes) {
+        gunzip(&bytes, max_size)
+    } else {
+        Ok(bytes)
+    }
+}
+
+/* Wraps data in a gzip member, for the tests */
+#[cfg(test)]
+pub(crate) fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![GZIP_MAGIC[0], GZIP_MAGIC[1], DEFLATE, 0, 0, 0, 0, 0, 0, 255];
+    bytes.extend(deflate::deflate_bytes(data));
+    bytes.extend_from_slice(&crc32(data).to_le_bytes());
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn crc32_should_match_the_check_value() {
+        // Arrange
+        let bytes = b"123456789";
+
+        // Act
+        let crc = crc32(bytes);
+
+        // Assert
+        assert_eq!(crc, 0xcbf43926);
+    }
+
+    #[test]
+    fn gunzip_should_skip_the_optional_header_fields() {
+        // Arrange
+        let data = b"Kaydara FBX Binary  \0".repeat(100);
+        let plain = gzip(&data);
+        let mut bytes = plain[..HEADER_LENGTH].to_vec();
+        bytes[3] = FLAG_EXTRA | FLAG_NAME | FLAG_COMMENT | FLAG_HEADER_CRC;
+        bytes.extend_from_slice(&[3, 0, 1, 2, 3]);
+        bytes.extend_from_slice(b"scene.fbx\0exported\0");
+        bytes.extend_from_slice(&[0, 0]);
+        bytes.extend_from_slice(&plain[HEADER_LENGTH..]);
+
+        // Act
+        let inflated = gunzip(&bytes, u64::MAX).unwrap();
+
+        // Assert
+        assert_eq!(inflated, data);
+    }
+
+    #[test]
+    fn gunzip_should_reject_a_corrupt_trailer() {
+        // Arrange
+        let mut bytes = gzip(b"some data");
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+
+        // Act
+        let result = gunzip(&bytes, u64::MAX);
+
+        // Assert
+        match result {
+            Err(ParseError::ValidationError(message)) => assert!(message.contains("checksum"), "{}", message),
+            other => panic!("expected a validation error, got {:?}", other.map(|b| b.len())),
+        }
+    }
+
+    #[test]
+    fn read_to_memory_should_apply_the_size_limit_to_the_inflated_data() {
+        // Arrange
+        let bytes = gzip(&vec![0u8; 100_000]);
+
+        // Act
+        let limited = read_to_memory(Cursor::new(&bytes), 50_000);
+        let unlimited = read_to_memory(Cursor::new(&bytes), 100_000);
+
+        // Assert
+        assert!(bytes.len() < 50_000);
+        match limited {
+            Err(ParseError::ValidationError(message)) => assert!(message.contains("limit of 50000"), "{}", message),
+            other => panic!("expected a validation error, got {:?}", other.map(|b| b.len())),
+        }
+        assert_eq!(unlimited.unwrap().len(), 100_000);
+    }
+
+    #[test]
+    fn read_to_memory_should_pass_plain_input_through() {
+        // Arrange
+        let bytes = b"Kaydara FBX Binary  \0".to_vec();
+
+        // Act
+        let read = read_to_memory(Cursor::new(&bytes), 1024).unwrap();
+        let too_large = read_to_memory(Cursor::new(&bytes), 8);
+
+        // Assert
+        assert_eq!(read, bytes);
+        assert!(too_large.is_err());
+    }
+}
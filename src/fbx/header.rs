@@ -1,25 +1,146 @@
-use std::io::{Read, Seek, SeekFrom};
+use crate::warnings::warning;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
 use crate::fbx::{ParseResult, ParseError};
 use byteorder::{ReadBytesExt, LittleEndian};
 
+pub(crate) const MAGIC: &[u8; 21] = b"Kaydara FBX Binary  \0";
+
+// The two bytes between the magic string and the version, always these in files written by the FBX SDK
+pub(crate) const EXPECTED_UNKNOWN_BYTES: [u8; 2] = [0x1a, 0x00];
+
+// Magic string, the two bytes and the version
+const HEADER_LENGTH: u64 = 27;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Header {
-    version: u32,
+    pub(crate) version: u32,
+    pub(crate) unknown_bytes: [u8; 2],
+}
+
+impl Header {
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /* The two bytes following the magic string. Not documented, but some tools tell variants of the format apart by
+       them. */
+    pub fn unknown_bytes(&self) -> [u8; 2] {
+        self.unknown_bytes
+    }
+}
+
+/* A file that ends within the header after a correct magic string was cut off, rather than not an FBX file */
+fn truncated_header<R: Seek>(reader: &mut R, error: std::io::Error) -> ParseError {
+    if error.kind() != ErrorKind::UnexpectedEof {
+        return error.into();
+    }
+
+    match reader.seek(SeekFrom::End(0)) {
+        Ok(file_length) => ParseError::Truncated { expected_at: HEADER_LENGTH, file_length, context: "the file header ends".to_string() },
+        Err(e) => e.into(),
+    }
 }
 
-pub(super) fn parse_header<R>(reader: &mut R) -> ParseResult<Header>
+/* Reads the magic string, the two bytes after it and the version. In strict mode unexpected values for the two bytes
+   fail the parse, otherwise they are reported and parsing goes on. */
+pub(super) fn parse_header<R>(reader: &mut R, strict: bool) -> ParseResult<Header>
     where
         R: Read + Seek
 {
-    let mut magic_string_bytes = vec![0u8; 21];
+    let mut magic_string_bytes = [0u8; 21];
     reader.read_exact(&mut magic_string_bytes)?;
-    if std::str::from_utf8(&magic_string_bytes)? != "Kaydara FBX Binary  \0" {
+    // Compared as bytes, garbage that is not UTF-8 is just as much a wrong magic string
+    if &magic_string_bytes != MAGIC {
         return Err(ParseError::ValidationError("file header magic string is incorrect".to_string()))
     }
-    // Skip past unknown bytes
-    reader.seek(SeekFrom::Current(2))?;
-    // reader.seek_relative(2)?;
 
-    let version = reader.read_u32::<LittleEndian>()?;
+    let mut unknown_bytes = [0u8; 2];
+    reader.read_exact(&mut unknown_bytes).map_err(|e| truncated_header(reader, e))?;
+    if unknown_bytes != EXPECTED_UNKNOWN_BYTES {
+        let message = format!("file header bytes after the magic string are {:02X?}, expected {:02X?}", unknown_bytes, EXPECTED_UNKNOWN_BYTES);
+        if strict {
+            return Err(ParseError::ValidationError(message));
+        }
+        warning!("{}, the file may be corrupt.", message);
+    }
+
+    let version = reader.read_u32::<LittleEndian>().map_err(|e| truncated_header(reader, e))?;
+
+    Ok(Header { version, unknown_bytes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn create_header(magic: &[u8], unknown_bytes: [u8; 2]) -> Vec<u8> {
+        let mut bytes = magic.to_vec();
+        bytes.extend_from_slice(&unknown_bytes);
+        bytes.extend_from_slice(&7500u32.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn parse_header_should_read_the_version_and_unknown_bytes() {
+        // Arrange
+        let bytes = create_header(MAGIC, EXPECTED_UNKNOWN_BYTES);
+
+        // Act
+        let header = parse_header(&mut Cursor::new(bytes), true).unwrap();
+
+        // Assert
+        assert_eq!(header.version(), 7500);
+        assert_eq!(header.unknown_bytes(), [0x1a, 0x00]);
+    }
+
+    #[test]
+    fn parse_header_should_reject_wrong_unknown_bytes_only_in_strict_mode() {
+        // Arrange
+        let bytes = create_header(MAGIC, [0x0d, 0x0a]);
+
+        // Act
+        let strict = parse_header(&mut Cursor::new(&bytes), true);
+        let lenient = parse_header(&mut Cursor::new(&bytes), false);
+
+        // Assert
+        match strict {
+            Err(ParseError::ValidationError(message)) => assert!(message.contains("[0D, 0A]"), "{}", message),
+            other => panic!("expected a validation error, got {:?}", other.map(|h| h.version)),
+        }
+        let lenient = lenient.unwrap();
+        assert_eq!(lenient.unknown_bytes(), [0x0d, 0x0a]);
+        assert_eq!(lenient.version(), 7500);
+    }
+
+    #[test]
+    fn parse_header_should_report_bad_magic_for_garbage_that_is_not_utf8() {
+        // Arrange
+        let bytes = create_header(&[0xff; 21], EXPECTED_UNKNOWN_BYTES);
+
+        // Act
+        let result = parse_header(&mut Cursor::new(bytes), false);
+
+        // Assert
+        match result {
+            Err(ParseError::ValidationError(message)) => assert!(message.contains("magic string"), "{}", message),
+            other => panic!("expected a validation error, got {:?}", other.map(|h| h.version)),
+        }
+    }
 
-    Ok(Header { version })
-}
\ No newline at end of file
+    #[test]
+    fn parse_header_should_report_a_header_cut_off_after_the_magic_string_as_truncated() {
+        // Arrange
+        let bytes = create_header(MAGIC, EXPECTED_UNKNOWN_BYTES)[..24].to_vec();
+
+        // Act
+        let result = parse_header(&mut Cursor::new(bytes), false);
+
+        // Assert
+        match result {
+            Err(error @ ParseError::Truncated { .. }) =>
+                assert_eq!(error.to_string(), "file appears truncated: the file header ends at byte 27 but the file is 24 bytes"),
+            other => panic!("expected a truncated error, got {:?}", other.map(|h| h.version)),
+        }
+    }
+}
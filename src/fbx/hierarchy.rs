@@ -0,0 +1,413 @@
+use crate::warnings::warning;
+use crate::fbx::arena_document::NodeRef;
+use crate::fbx::properties70::Properties70;
+use crate::scene::node::{CullingMode, InheritType, Node};
+use crate::scene::transform::Transform;
+use crate::scene::connections::{Connection, ConnectionKind, Connections};
+use std::collections::{HashMap, HashSet};
+
+// Objects whose class is recorded for the typed lookups of Connections
+const OBJECT_CLASSES: [&str; 12] = ["Model", "Geometry", "Material", "Texture", "Video", "NodeAttribute", "Deformer",
+    "AnimationStack", "AnimationLayer", "AnimationCurveNode", "AnimationCurve", "Pose"];
+
+/* Reads the Connections node, along with the class of every object in Objects. Capacity is how many connections the
+   file declares, room is made for all of them up front unless the file holds fewer. */
+pub(super) fn read_connections(objects: Option<NodeRef>, connections: Option<NodeRef>, capacity: Option<usize>) -> Connections {
+    let available = connections.map_or(0, |c| c.children().count("C"));
+    let mut result = Connections::with_capacity(capacity.map_or(available, |c| c.min(available)));
+    for entry in connections.into_iter().flat_map(|c| c.children().get_multiple("C")) {
+        let kind = entry.properties().first().and_then(|p| p.as_string()).and_then(|s| ConnectionKind::from_fbx(s.name()));
+        let child = entry.properties().get(1).and_then(|p| p.as_i64());
+        let parent = entry.properties().get(2).and_then(|p| p.as_i64());
+        if let (Some(kind), Some(child), Some(parent)) = (kind, child, parent) {
+            let mut connection = Connection::new(kind, child, parent);
+            if let Some(property) = entry.properties().get(3).and_then(|p| p.as_string()) {
+                connection = connection.with_property(property.name());
+            }
+            result.push(connection);
+        }
+    }
+
+    for class in OBJECT_CLASSES.iter() {
+        for object in objects.into_iter().flat_map(|o| o.children().get_multiple(class)) {
+            if let Some(id) = object.properties().first().and_then(|p| p.as_i64()) {
+                result.set_class(id, class);
+            }
+        }
+    }
+
+    result
+}
+
+fn read_transform(properties: &Properties70, translation: &str, rotation: &str, scaling: &str) -> Transform {
+    Transform::from_fbx(
+        properties.vec3(translation).unwrap_or(glm::vec3(0.0, 0.0, 0.0)),
+        properties.vec3(rotation).unwrap_or(glm::vec3(0.0, 0.0, 0.0)),
+        properties.vec3(scaling).unwrap_or(glm::vec3(1.0, 1.0, 1.0)))
+}
+
+/* Reads the local transform and display flags of a model, entries the file leaves out keep their defaults */
+fn read_node(model: NodeRef, id: i64, name: String) -> Node {
+    let mut node = Node::new(id, name);
+    let properties = Properties70::from(model);
+    node.transform = read_transform(&properties, "Lcl Translation", "Lcl Rotation", "Lcl Scaling");
+    node.geometric_transform = read_transform(&properties, "GeometricTranslation", "GeometricRotation", "GeometricScaling");
+    if let Some(inherit_type) = properties.integer("InheritType") {
+        node.inherit_type = InheritType::from_fbx(inherit_type);
+    }
+    if let Some(show) = properties.integer("Show") {
+        node.visible = show != 0;
+    }
+    if let Some(visibility) = properties.number("Visibility") {
+        node.visibility = visibility;
+    }
+    // A child node in files written by the SDK, a property in some others
+    let culling = model.children().get_first("Culling").ok()
+        .and_then(|c| c.properties().first())
+        .and_then(|p| p.as_string())
+        .map(|s| s.name())
+        .or_else(|| properties.string("Culling"));
+    if let Some(culling) = culling {
+        node.culling = CullingMode::from_fbx(culling);
+    }
+    node
+}
+
+/* Builds the node hierarchy from the Model objects and the object-object ("OO") connections between them. Models
+   without a parent model are attached to the scene root. */
+pub(super) fn build_nodes(objects: NodeRef, connections: &Connections) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let mut index_of: HashMap<i64, usize> = HashMap::new();
+    for model in objects.children().get_multiple("Model") {
+        let id = match model.properties().first().and_then(|p| p.as_i64()) {
+            Some(id) => id,
+            None => continue
+        };
+        let name = model.properties().get(1)
+            .and_then(|p| p.as_string())
+            .map_or(String::new(), |s| s.name().to_string());
+
+        index_of.insert(id, nodes.len());
+        nodes.push(read_node(model, id, name));
+    }
+
+    for connection in connections.of_kind(ConnectionKind::ObjectObject) {
+        let child = index_of.get(&connection.child);
+        let parent = index_of.get(&connection.parent);
+        if let (Some(&child), Some(&parent)) = (child, parent) {
+            // A node has a single parent, later connections for the same child are ignored
+            if nodes[child].parent.is_none() {
+                nodes[child].parent = Some(parent);
+            }
+        }
+    }
+
+    for cycle in break_cycles(&mut nodes) {
+        let ids: Vec<String> = cycle.iter().map(|id| id.to_string()).collect();
+        warning!("Connections between objects {} form a cycle, attaching object {} to the scene root.",
+                  ids.join(", "), cycle[cycle.len() - 1]);
+    }
+
+    for index in 0..nodes.len() {
+        if let Some(parent) = nodes[index].parent {
+            nodes[parent].children.push(index);
+        }
+    }
+
+    nodes
+}
+
+/* Ids of every object that "OO" connections lead from to the given root, the objects that belong to the scene */
+pub(super) fn reachable_from(root: i64, connections: &Connections) -> HashSet<i64> {
+    let mut reachable = HashSet::new();
+    let mut pending = vec![root];
+    while let Some(id) = pending.pop() {
+        if reachable.insert(id) {
+            pending.extend(connections.children_of(id).filter(|c| c.kind == ConnectionKind::ObjectObject).map(|c| c.child));
+        }
+    }
+    reachable
+}
+
+// Id of the group holding orphaned nodes, FBX object ids are never negative
+pub(super) const ORPHANS_NODE_ID: i64 = -1;
+
+/* Handles the nodes not reachable from the document root. They are removed, or with include_orphans moved under a
+   node called "orphans" that is added to the scene root. Returns how many nodes were orphaned. */
+pub(super) fn detach_orphans(nodes: &mut Vec<Node>, reachable: &HashSet<i64>, include_orphans: bool) -> usize {
+    let orphaned = nodes.iter().filter(|n| !reachable.contains(&n.id)).count();
+    if orphaned == 0 {
+        return 0;
+    }
+
+    if include_orphans {
+        let group = nodes.len();
+        let mut orphans = Node::new(ORPHANS_NODE_ID, "orphans".to_string());
+        for (index, node) in nodes.iter_mut().enumerate() {
+            if node.parent.is_none() && !reachable.contains(&node.id) {
+                node.parent = Some(group);
+                orphans.children.push(index);
+            }
+        }
+        nodes.push(orphans);
+        return orphaned;
+    }
+
+    // The parents of reachable nodes are reachable as well, so only links between kept nodes need renumbering
+    let keep: Vec<bool> = nodes.iter().map(|n| reachable.contains(&n.id)).collect();
+    retain_nodes(nodes, &keep);
+    orphaned
+}
+
+/* Removes the hidden nodes together with everything below them, since hiding a node hides its children in the
+   authoring tool as well. Returns how many nodes were removed. */
+pub(super) fn remove_hidden_nodes(nodes: &mut Vec<Node>) -> usize {
+    let mut keep = vec![true; nodes.len()];
+    let mut pending: Vec<usize> = (0..nodes.len()).filter(|i| nodes[*i].is_hidden()).collect();
+    while let Some(index) = pending.pop() {
+        if keep[index] {
+            keep[index] = false;
+            pending.extend(&nodes[index].children);
+        }
+    }
+
+    let removed = keep.iter().filter(|k| !**k).count();
+    if removed > 0 {
+        retain_nodes(nodes, &keep);
+    }
+    removed
+}
+
+/* Points every node at the mesh attached to it, given the id of the model of every mesh */
+pub(super) fn attach_meshes(nodes: &mut [Node], mesh_models: &[Option<i64>]) {
+    let index_of: HashMap<i64, usize> = nodes.iter().enumerate().map(|(index, node)| (node.id, index)).collect();
+    for (mesh, model) in mesh_models.iter().enumerate() {
+        if let Some(node) = model.and_then(|model| index_of.get(&model)) {
+            nodes[*node].mesh = Some(mesh);
+        }
+    }
+}
+
+/* Keeps the nodes marked in keep and renumbers the links between them. The parent of a kept node must be kept. */
+fn retain_nodes(nodes: &mut Vec<Node>, keep: &[bool]) {
+    let mut renumbered = Vec::with_capacity(nodes.len());
+    let mut kept = 0;
+    for k in keep {
+        renumbered.push(if *k { kept += 1; Some(kept - 1) } else { None });
+    }
+    let mut index = 0;
+    nodes.retain(|_| { index += 1; keep[index - 1] });
+    for node in nodes.iter_mut() {
+        node.parent = node.parent.and_then(|p| renumbered[p]);
+        node.children = node.children().iter().filter_map(|c| renumbered[*c]).collect();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    New,
+    OnPath,
+    Done,
+}
+
+/* Follows the parent links from every node. Reaching a node that is already on the current path means the last
+   link taken closes a cycle, so that link is removed. Returns the ids of each cycle, ending with the node that
+   was moved to the root. */
+fn break_cycles(nodes: &mut [Node]) -> Vec<Vec<i64>> {
+    let mut state = vec![Visit::New; nodes.len()];
+    let mut cycles = Vec::new();
+
+    for start in 0..nodes.len() {
+        let mut path = Vec::new();
+        let mut current = Some(start);
+        while let Some(index) = current {
+            match state[index] {
+                Visit::New => {
+                    state[index] = Visit::OnPath;
+                    path.push(index);
+                    current = nodes[index].parent;
+                }
+                Visit::OnPath => {
+                    let start_of_cycle = path.iter().position(|i| *i == index).unwrap();
+                    let last = path[path.len() - 1];
+                    nodes[last].parent = None;
+                    cycles.push(path[start_of_cycle..].iter().map(|i| nodes[*i].id).collect());
+                    break;
+                }
+                Visit::Done => break
+            }
+        }
+
+        for index in path {
+            state[index] = Visit::Done;
+        }
+    }
+
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::{FbxFixture, FixtureNode};
+    use crate::fbx::property::{FbxString, PropertyRecordType};
+
+    fn model(o: FixtureNode, id: i64, name: &str) -> FixtureNode {
+        o.node_with_props("Model", vec![id.into(), FbxString::new(name).with_class("Model").into(), "Null".into()], |m| m)
+    }
+
+    fn parse_models(connections: &[(i64, i64)]) -> Vec<Node> {
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| model(model(model(o, 10, "a"), 20, "b"), 30, "c"))
+            .node("Connections", |c| connections.iter().fold(c, |c, (child, parent)| c
+                .node_with_props("C", vec!["OO".into(), (*child).into(), (*parent).into()], |n| n)))
+            .parse();
+
+        let objects = nodes.get("Objects").ok().unwrap();
+        build_nodes(objects, &read_connections(Some(objects), nodes.get("Connections").ok(), None))
+    }
+
+    #[test]
+    fn build_nodes_should_link_children_to_parents() {
+        // Arrange
+        let connections = [(20, 10), (30, 20), (10, 0)];
+
+        // Act
+        let nodes = parse_models(&connections);
+
+        // Assert
+        assert_eq!(nodes[0].parent, None);
+        assert_eq!(nodes[1].parent, Some(0));
+        assert_eq!(nodes[2].parent, Some(1));
+        assert_eq!(nodes[0].children, vec![1]);
+        assert_eq!(nodes[1].children, vec![2]);
+    }
+
+    #[test]
+    fn break_cycles_should_attach_the_node_closing_a_cycle_to_the_root() {
+        // Arrange
+        let mut nodes = vec![Node::new(10, "a".to_string()), Node::new(20, "b".to_string())];
+        nodes[0].parent = Some(1);
+        nodes[1].parent = Some(0);
+
+        // Act
+        let cycles = break_cycles(&mut nodes);
+
+        // Assert
+        assert_eq!(cycles, vec![vec![10, 20]]);
+        assert_eq!(nodes[0].parent, Some(1));
+        assert_eq!(nodes[1].parent, None);
+    }
+
+    #[test]
+    fn detach_orphans_should_remove_nodes_not_reachable_from_the_root() {
+        // Arrange
+        let connections = [(20, 10), (10, 0), (30, 40)];
+        let mut nodes = parse_models(&connections);
+        let reachable: HashSet<i64> = [0, 10, 20].iter().copied().collect();
+
+        // Act
+        let orphaned = detach_orphans(&mut nodes, &reachable, false);
+
+        // Assert
+        assert_eq!(orphaned, 1);
+        let names: Vec<&str> = nodes.iter().map(|n| n.name()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(nodes[0].children, vec![1]);
+    }
+
+    #[test]
+    fn detach_orphans_should_group_orphans_when_included() {
+        // Arrange
+        let connections = [(20, 10), (10, 0)];
+        let mut nodes = parse_models(&connections);
+        let reachable: HashSet<i64> = [0, 10, 20].iter().copied().collect();
+
+        // Act
+        detach_orphans(&mut nodes, &reachable, true);
+
+        // Assert
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(nodes[3].id, ORPHANS_NODE_ID);
+        assert_eq!(nodes[3].children, vec![2]);
+        assert_eq!(nodes[2].parent, Some(3));
+    }
+
+    #[test]
+    fn reachable_from_should_follow_connections_down_from_the_root() {
+        // Arrange
+        let nodes = FbxFixture::new(7400)
+            .node("Connections", |c| [(10i64, 0i64), (20, 10), (30, 40)].iter().fold(c, |c, (child, parent)| c
+                .node_with_props("C", vec!["OO".into(), (*child).into(), (*parent).into()], |n| n)))
+            .parse();
+
+        // Act
+        let reachable = reachable_from(0, &read_connections(None, nodes.get("Connections").ok(), None));
+
+        // Assert
+        let mut ids: Vec<i64> = reachable.into_iter().collect();
+        ids.sort();
+        assert_eq!(ids, vec![0, 10, 20]);
+    }
+
+    #[test]
+    fn build_nodes_should_keep_every_node_of_a_cycle() {
+        // Arrange
+        let connections = [(10, 20), (20, 10), (30, 10)];
+
+        // Act
+        let nodes = parse_models(&connections);
+
+        // Assert
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes.iter().filter(|n| n.parent.is_none()).count(), 1);
+        assert_eq!(nodes[1].parent, None);
+        assert_eq!(nodes[1].children, vec![0]);
+        assert_eq!(nodes[0].children, vec![2]);
+    }
+
+    #[test]
+    fn build_nodes_should_read_display_flags_and_inherit_type() {
+        // Arrange
+        fn p(name: &str, kind: &str, value: PropertyRecordType) -> Vec<PropertyRecordType> {
+            vec![name.into(), kind.into(), "".into(), "A".into(), value]
+        }
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("Model", vec![10i64.into(), FbxString::new("a").with_class("Model").into(), "Null".into()], |m| m
+                    .node_with_props("Culling", vec!["CullingOnCW".into()], |n| n)
+                    .node("Properties70", |p70| p70
+                        .node_with_props("P", p("InheritType", "enum", 1i32.into()), |n| n)
+                        .node_with_props("P", p("Show", "bool", 0i32.into()), |n| n)
+                        .node_with_props("P", p("Visibility", "Visibility", 0.5.into()), |n| n))))
+            .parse();
+        let objects = nodes.get("Objects").ok().unwrap();
+
+        // Act
+        let nodes = build_nodes(objects, &read_connections(Some(objects), None, None));
+
+        // Assert
+        assert_eq!(nodes[0].inherit_type, InheritType::RSrs);
+        assert_eq!(nodes[0].culling, CullingMode::Clockwise);
+        assert!(!nodes[0].visible);
+        assert_eq!(nodes[0].visibility, 0.5);
+        assert!(nodes[0].is_hidden());
+    }
+
+    #[test]
+    fn remove_hidden_nodes_should_remove_the_children_of_hidden_nodes() {
+        // Arrange
+        let connections = [(20, 10), (30, 0)];
+        let mut nodes = parse_models(&connections);
+        nodes[0].visibility = 0.0;
+
+        // Act
+        let removed = remove_hidden_nodes(&mut nodes);
+
+        // Assert
+        assert_eq!(removed, 2);
+        let names: Vec<&str> = nodes.iter().map(|n| n.name()).collect();
+        assert_eq!(names, vec!["c"]);
+        assert_eq!(nodes[0].parent, None);
+    }
+}
@@ -1,52 +1,69 @@
-use crate::fbx::node::NodeRecord;
-use crate::scene::Scene;
-use crate::fbx::node_collection::{NodeCollection, Error};
-use crate::fbx::property::PropertyRecordType;
-use crate::scene::mesh::{Mesh, Face};
-use num::abs;
-use std::fs::File;
-use std::path::Path;
-use std::io::{Write, Cursor};
-use std::slice::Iter;
+use crate::warnings::warning;
+use crate::fbx::arena_document::{ArenaDocument, NodeRef, NodeList, PropertyError};
+use crate::scene::{Scene, rename_duplicate_meshes};
+use crate::fbx::node_collection::Error;
+use crate::fbx::property::FbxString;
+use crate::scene::mesh::{Mesh, Face, Smoothing, Layer, LayerElement};
+use crate::scene::connections::ConnectionKind;
+use crate::fbx::polygon_vertex_index::FaceIterator;
+use crate::fbx::hierarchy::{build_nodes, detach_orphans, reachable_from, read_connections, remove_hidden_nodes, attach_meshes};
+use crate::fbx::documents::read_documents;
+use crate::fbx::definitions::read_definitions;
+use crate::fbx::materials::read_materials;
+use crate::fbx::textures::read_textures;
+use crate::fbx::animation::read_animation_layers;
+use crate::fbx::global_settings::read_time_settings;
+use crate::fbx::skin::read_skins;
+use crate::fbx::blend_shapes::read_blend_shapes;
+use crate::fbx::cameras::read_cameras;
+use crate::fbx::lights::read_lights;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use crate::fbx::{ImportOptions, NanPolicy};
 
-struct FaceIterator<'a, I>
-where
-    I: Iterator<Item = &'a i32>
-{
-    indices: &'a mut I,
+/* Why the nodes of a file could not be converted into a scene, see fbx::ImportError::Conversion */
+#[derive(Debug, PartialEq)]
+pub enum ImportError {
+    // Face refers to a vertex that does not exist, face is its position in PolygonVertexIndex
+    IndexOutOfRange { mesh: String, face: usize, index: i32 },
+    // Vertices does not hold whole xyz triplets
+    IncompleteVertex { mesh: String, coordinates: usize },
+    // Faces with fewer than 3 indices, usually stray edges written into the polygon buffer
+    DegenerateFaces { mesh: String, count: usize },
+    // Geometry without one of the child nodes every mesh needs
+    MissingChild { mesh: String, child: &'static str },
+    // Child node whose first property is not of a type it can hold, or could not be decoded
+    UnexpectedChildData { mesh: String, child: &'static str, error: PropertyError },
+    // Vertices with a NaN or infinite coordinate
+    NonFiniteVertices { mesh: String, count: usize },
+    // Top level node the file should hold one of at most, such as Objects
+    RepeatedNode(Error),
+    // Object without the properties every object of its kind has
+    MalformedNode(PropertyError),
 }
 
-impl<'a, I: Iterator<Item = &'a i32>> FaceIterator<'a, I> {
-    pub fn from(indices: &'a mut I) -> Self {
-        FaceIterator {
-            indices
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::IndexOutOfRange { mesh, face, index } =>
+                write!(f, "Face {} of mesh '{}' uses vertex {} which does not exist", face, mesh, index),
+            ImportError::IncompleteVertex { mesh, coordinates } =>
+                write!(f, "Mesh '{}' has {} vertex coordinates, which is not a multiple of 3", mesh, coordinates),
+            ImportError::DegenerateFaces { mesh, count } =>
+                write!(f, "Mesh '{}' has {} faces with fewer than 3 indices", mesh, count),
+            ImportError::MissingChild { mesh, child } =>
+                write!(f, "Mesh '{}' has no {} node", mesh, child),
+            ImportError::UnexpectedChildData { mesh, child, error } =>
+                write!(f, "Mesh '{}' has a {} node that could not be read: {}", mesh, child, error),
+            ImportError::NonFiniteVertices { mesh, count } =>
+                write!(f, "Mesh '{}' has {} vertices with NaN or infinite positions", mesh, count),
+            ImportError::RepeatedNode(e) => write!(f, "{}", e),
+            ImportError::MalformedNode(e) => write!(f, "{}", e),
         }
     }
 }
 
-impl<'a, I: Iterator<Item = &'a i32>> Iterator for FaceIterator<'a, I> {
-    type Item = Face;
-
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut indices = Vec::<i32>::new();
-
-        while let Some(index) = self.indices.next() {
-            if *index < 0 {
-                indices.push(*index ^ -1);
-                break;
-            }
-
-            indices.push(*index);
-        }
-
-        if indices.is_empty() {
-            return None;
-        }
-
-        Some(Face::new(indices))
-    }
-}
+impl std::error::Error for ImportError {}
 
 struct Tuples3<I> {
     original: I,
@@ -65,13 +82,13 @@ impl<I> Iterator for Tuples3<I> where I: Iterator {
             }
         }
 
-        return None;
+        None
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
         match self.original.size_hint() {
-            (lower, Some(upper)) => (lower, Some(upper / 3)),
+            (lower, Some(upper)) => (lower / 3, Some(upper / 3)),
             h @ (_, _) => h
         }
     }
@@ -81,80 +98,1470 @@ fn tuples3<I: Iterator>(iterator: I) -> Tuples3<I> {
     Tuples3 { original: iterator }
 }
 
-fn get_faces(geometry: &NodeRecord) -> Vec<Face> {
-    let indices_node = match geometry.children.get("PolygonVertexIndex") {
-        Ok(v) => v,
-        Err(e) => panic!("sssss")
+/* Reads Vertices, which exporters write as either double or float arrays. Returns None for geometry without usable
+   vertices, which is an error in strict mode and skipped otherwise. Left over coordinates that do not make up a whole
+   vertex are an error in strict mode, and are dropped otherwise. */
+pub(super) fn get_vertices(geometry: NodeRef, mesh: &str, strict: bool) -> Result<Option<Vec<glm::Vec3>>, ImportError> {
+    let node = geometry.children().get_first("Vertices").ok().filter(|node| node.property(0).is_ok());
+    let result = match node {
+        None => Err(ImportError::MissingChild { mesh: mesh.to_string(), child: "Vertices" }),
+        Some(node) => {
+            match node.property_array_view(0) {
+                Ok(coordinates) => to_vertices(coordinates.iter_f32_lossy(), mesh, strict),
+                Err(error) => Err(ImportError::UnexpectedChildData { mesh: mesh.to_string(), child: "Vertices", error }),
+            }
+        }
     };
 
-    let mut indices = match &indices_node.properties[0] {
-        PropertyRecordType::SignedInt32Array(v) => v.clone(),
-        _ => panic!("Unexpected data in indices node")
+    match result {
+        Ok(vertices) => Ok(Some(vertices)),
+        Err(e @ ImportError::IncompleteVertex { .. }) => Err(e),
+        Err(e) if strict => Err(e),
+        Err(e) => {
+            warning!("{}, skipping it.", e);
+            Ok(None)
+        }
+    }
+}
+
+fn to_vertices<I: ExactSizeIterator<Item = f32>>(coordinates: I, mesh: &str, strict: bool) -> Result<Vec<glm::Vec3>, ImportError> {
+    if !coordinates.len().is_multiple_of(3) {
+        let error = ImportError::IncompleteVertex { mesh: mesh.to_string(), coordinates: coordinates.len() };
+        if strict {
+            return Err(error);
+        }
+        warning!("{}, ignoring the remainder.", error);
+    }
+
+    let mut vertices = Vec::with_capacity(coordinates.len() / 3);
+    vertices.extend(tuples3(coordinates).map(|x| glm::vec3(x.0, x.1, x.2)));
+    Ok(vertices)
+}
+
+/* Faces using vertices that do not exist are an error in strict mode, and are left out otherwise */
+fn remove_invalid_faces(faces: &mut Vec<Face>, vertex_count: usize, mesh: &str, strict: bool) -> Result<(), ImportError> {
+    let mut face_index = 0;
+    let mut error = None;
+    faces.retain(|face| {
+        let invalid = face.indices.iter().find(|i| **i < 0 || **i as usize >= vertex_count);
+        if let Some(index) = invalid {
+            let e = ImportError::IndexOutOfRange { mesh: mesh.to_string(), face: face_index, index: *index };
+            if !strict {
+                warning!("{}, leaving the face out.", e);
+            }
+            if error.is_none() {
+                error = Some(e);
+            }
+        }
+        face_index += 1;
+        invalid.is_none()
+    });
+
+    match error {
+        Some(error) if strict => Err(error),
+        _ => Ok(())
+    }
+}
+
+fn is_finite(v: &glm::Vec3) -> bool {
+    v.x.is_finite() && v.y.is_finite() && v.z.is_finite()
+}
+
+/* Deals with NaN and infinite vertex positions as the policy says. Runs once the faces only use existing vertices,
+   as removing vertices renumbers the faces, hard edges and degenerate faces referring to them. */
+fn apply_nan_policy(mesh: &mut Mesh, degenerate_faces: &mut Vec<Face>, policy: NanPolicy) -> Result<(), ImportError> {
+    let count = mesh.vertices.iter().filter(|v| !is_finite(v)).count();
+    if count == 0 {
+        return Ok(());
+    }
+
+    let e = ImportError::NonFiniteVertices { mesh: mesh.name.clone(), count };
+    match policy {
+        NanPolicy::Error => Err(e),
+        NanPolicy::ReplaceWithZero => {
+            for vertex in mesh.vertices.iter_mut().filter(|v| !is_finite(v)) {
+                *vertex = glm::vec3(0.0, 0.0, 0.0);
+            }
+            warning!("{}, moved them to the origin.", e);
+            Ok(())
+        }
+        NanPolicy::RemoveAffectedFaces => {
+            let mut remap = Vec::with_capacity(mesh.vertices.len());
+            let mut kept: i32 = 0;
+            for vertex in &mesh.vertices {
+                remap.push(if is_finite(vertex) { kept += 1; Some(kept - 1) } else { None });
+            }
+            // Degenerate faces have not been range checked, their out of range indices are left alone
+            let renumber = |faces: &mut Vec<Face>| {
+                let before = faces.len();
+                faces.retain(|face| face.indices.iter().all(|i| remap.get(*i as usize) != Some(&None)));
+                for face in faces.iter_mut() {
+                    for index in face.indices.iter_mut() {
+                        if let Some(Some(renumbered)) = remap.get(*index as usize) {
+                            *index = *renumbered;
+                        }
+                    }
+                }
+                before - faces.len()
+            };
+
+            let removed_faces = renumber(&mut mesh.faces);
+            renumber(degenerate_faces);
+            if let Smoothing::ByEdge(hard_edges) = &mesh.smoothing {
+                let renumbered = hard_edges.iter()
+                    .filter_map(|(a, b)| Some(((*remap.get(*a as usize)?)?, (*remap.get(*b as usize)?)?)))
+                    .collect();
+                mesh.smoothing = Smoothing::ByEdge(renumbered);
+            }
+            let kept: Vec<usize> = (0..mesh.vertices.len()).filter(|i| is_finite(&mesh.vertices[*i])).collect();
+            mesh.remap_vertex_attributes(&kept);
+            mesh.vertices.retain(is_finite);
+
+            warning!("{}, removed them and the {} faces using them.", e, removed_faces);
+            Ok(())
+        }
+    }
+}
+
+/* Moves faces with fewer than 3 indices out of the face list and returns them. They are an error in strict mode. */
+fn take_degenerate_faces(faces: &mut Vec<Face>, mesh: &str, strict: bool) -> Result<Vec<Face>, ImportError> {
+    let mut degenerate = Vec::new();
+    faces.retain(|face| {
+        if face.indices.len() >= 3 {
+            return true;
+        }
+        degenerate.push(face.clone());
+        false
+    });
+
+    if degenerate.is_empty() {
+        return Ok(degenerate);
+    }
+
+    let e = ImportError::DegenerateFaces { mesh: mesh.to_string(), count: degenerate.len() };
+    if strict {
+        return Err(e);
+    }
+    warning!("{}, leaving them out.", e);
+    Ok(degenerate)
+}
+
+/* Reads PolygonVertexIndex. Geometry without it is imported as a point cloud without faces, unless in strict mode. */
+pub(super) fn get_faces(geometry: NodeRef, mesh: &str, strict: bool) -> Result<Vec<Face>, ImportError> {
+    let node = geometry.children().get_first("PolygonVertexIndex").ok().filter(|node| node.property(0).is_ok());
+    let indices = match node.map(|node| node.property_i32_array(0)) {
+        Some(Ok(v)) => v,
+        unusable => {
+            let e = match unusable {
+                Some(Err(error)) => ImportError::UnexpectedChildData { mesh: mesh.to_string(), child: "PolygonVertexIndex", error },
+                _ => ImportError::MissingChild { mesh: mesh.to_string(), child: "PolygonVertexIndex" },
+            };
+            if strict {
+                return Err(e);
+            }
+            warning!("{}, importing it as points.", e);
+            return Ok(Vec::new());
+        }
     };
 
-    let mut faces = Vec::new();
-    for face in FaceIterator::from(&mut indices.iter()) {
+    let face_count = indices.iter().filter(|i| **i < 0).count();
+    let mut faces = Vec::with_capacity(face_count);
+    // Short runs are kept for now so the faces line up with per polygon layers, see take_degenerate_faces
+    let mut iterator = FaceIterator::from(indices).with_short_runs(true);
+    for (polygon, mut face) in iterator.by_ref().enumerate() {
+        face.polygon = polygon;
         faces.push(face);
     }
 
-    faces
+    if iterator.missing_terminator() {
+        warning!("PolygonVertexIndex of '{}' does not end with a negated index.", mesh);
+    }
+
+    Ok(faces)
+}
+
+fn get_i32_array_child<'a>(node: NodeRef<'a>, name: &str) -> Option<&'a [i32]> {
+    node.children().get_first(name).ok()?.property_i32_array(0).ok()
+}
+
+fn get_string_child<'a>(node: NodeRef<'a>, name: &str) -> Option<&'a str> {
+    Some(node.children().get_first(name).ok()?.property_str(0).ok()?.name())
+}
+
+/* Reads the Layer nodes, sorted by layer index */
+fn get_layers(geometry: NodeRef) -> Vec<Layer> {
+    let mut layers: Vec<Layer> = geometry.children().get_multiple("Layer")
+        .enumerate()
+        .map(|(position, layer)| {
+            let index = layer.property_i64(0).map_or(position as i32, |i| i as i32);
+            let elements = layer.children().get_multiple("LayerElement")
+                .filter_map(|element| Some(LayerElement {
+                    kind: get_string_child(element, "Type")?.to_string(),
+                    typed_index: element.children().get_first("TypedIndex").ok()?.property_i64(0).ok()? as i32,
+                }))
+                .collect();
+            Layer { index, elements }
+        })
+        .collect();
+    layers.sort_by_key(|layer| layer.index);
+    layers
+}
+
+/* Elements of a kind in the order the layers list them. Elements no layer mentions, or all of them when the file has
+   no Layer nodes, follow in file order. */
+fn in_layer_order<'a>(geometry: NodeRef<'a>, kind: &str, layers: &[Layer]) -> Vec<NodeRef<'a>> {
+    let elements: Vec<NodeRef> = geometry.children().get_multiple(kind).collect();
+    let typed_index = |element: NodeRef| element.property_i64(0).ok();
+
+    let mut order: Vec<usize> = Vec::with_capacity(elements.len());
+    for layer_element in layers.iter().flat_map(|l| l.elements.iter()).filter(|e| e.kind == kind) {
+        let position = elements.iter().position(|e| typed_index(*e) == Some(layer_element.typed_index as i64));
+        if let Some(position) = position.filter(|p| !order.contains(p)) {
+            order.push(position);
+        }
+    }
+    for position in 0..elements.len() {
+        if !order.contains(&position) {
+            order.push(position);
+        }
+    }
+
+    order.into_iter().map(|position| elements[position]).collect()
 }
 
-pub(super) fn import(nodes: NodeCollection) -> Option<Scene> {
-    let objects_node = match nodes.get("Objects") {
-        Ok(node) => node,
-        Err(_) => panic!("woop")
+/* Layer element holding a per corner attribute, with the names of its children holding the values and their indices */
+struct Channel<T> {
+    kind: &'static str,
+    values: &'static str,
+    index: &'static str,
+    // Numbers making up one value
+    components: usize,
+    convert: fn(&[f64]) -> T,
+}
+
+const UV_CHANNEL: Channel<glm::Vec2> = Channel {
+    kind: "LayerElementUV",
+    values: "UV",
+    index: "UVIndex",
+    components: 2,
+    convert: |v| glm::vec2(v[0] as f32, v[1] as f32),
+};
+
+const COLOR_CHANNEL: Channel<glm::Vec4> = Channel {
+    kind: "LayerElementColor",
+    values: "Colors",
+    index: "ColorIndex",
+    components: 4,
+    convert: |v| glm::vec4(v[0] as f32, v[1] as f32, v[2] as f32, v[3] as f32),
+};
+
+impl<T> Channel<T> {
+    fn zero(&self) -> T {
+        (self.convert)(&[0.0; 4])
+    }
+}
+
+/* Reads a layer element such as LayerElementUV as one value per face corner, in the order of the faces. Faces know
+   which polygon they were read from, corner_offsets holds where the corners of every polygon start in
+   PolygonVertexIndex. Returns None for elements without values or with a mapping this importer does not know. */
+fn get_corner_values<T: Copy>(element: NodeRef, channel: &Channel<T>, faces: &[Face], corner_offsets: &[usize], mesh: &str) -> Option<Vec<T>> {
+    let node = element.children().get_first(channel.values).ok()?;
+    let values: Vec<T> = node.property_array_view(0).ok()?.to_f64().chunks_exact(channel.components).map(channel.convert).collect();
+
+    let indices = match get_string_child(element, "ReferenceInformationType") {
+        Some("IndexToDirect") | Some("Index") => Some(get_i32_array_child(element, channel.index)?),
+        _ => None
     };
 
-    let geometry = objects_node.children.get_multiple("Geometry");
+    let mapping = get_string_child(element, "MappingInformationType").unwrap_or("");
+    let key = |face: &Face, corner: usize| match mapping {
+        "ByPolygonVertex" => Some(corner_offsets[face.polygon] + corner),
+        "ByVertice" | "ByVertex" | "ByControlPoint" => Some(face.indices[corner] as usize),
+        "ByPolygon" => Some(face.polygon),
+        "AllSame" => Some(0),
+        _ => None
+    };
 
-    if geometry.is_none() {
-        // No meshes to import
-        return None;
+    let mut missing = 0;
+    let mut result = Vec::with_capacity(faces.iter().map(|f| f.indices.len()).sum());
+    for face in faces {
+        for corner in 0..face.indices.len() {
+            let key = key(face, corner)?;
+            let index = match indices {
+                Some(indices) => indices.get(key).and_then(|i| usize::try_from(*i).ok()),
+                None => Some(key)
+            };
+            match index.and_then(|i| values.get(i)) {
+                Some(value) => result.push(*value),
+                None => {
+                    missing += 1;
+                    result.push(channel.zero());
+                }
+            }
+        }
     }
 
-    let mut meshes = Vec::new();
-    for geom in geometry.unwrap() {
-        // 3rd property should be "Mesh"
-        if geom.properties.len() < 3 {
-            continue;
+    if missing > 0 {
+        warning!("{} of mesh '{}' has no value for {} face corners, using zero for them.", element.name(), mesh, missing);
+    }
+    Some(result)
+}
+
+/* Reads every element of a channel in layer order as one value per face corner, along with their names */
+fn read_corner_channels<T: Copy>(geometry: NodeRef, mesh: &Mesh, channel: &Channel<T>, corner_offsets: &[usize]) -> (Vec<String>, Vec<Vec<T>>) {
+    let mut names = Vec::new();
+    let mut corner_values = Vec::new();
+    for element in in_layer_order(geometry, channel.kind, &mesh.layers) {
+        match get_corner_values(element, channel, &mesh.faces, corner_offsets, &mesh.name) {
+            Some(values) => {
+                names.push(get_string_child(element, "Name").unwrap_or("").to_string());
+                corner_values.push(values);
+            }
+            None => warning!("Mesh '{}' has a {} that could not be read, leaving it out.", mesh.name, channel.kind),
+        }
+    }
+    (names, corner_values)
+}
+
+/* Reads every uv and color set in layer order and stores them per vertex. Vertices whose corners have different values
+   in some set, like along a uv seam, are copied so every copy has a single value in every set. */
+fn read_vertex_channels(geometry: NodeRef, mesh: &mut Mesh, corner_offsets: &[usize]) {
+    let (uv_names, corner_uvs) = read_corner_channels(geometry, mesh, &UV_CHANNEL, corner_offsets);
+    let (color_names, corner_colors) = read_corner_channels(geometry, mesh, &COLOR_CHANNEL, corner_offsets);
+
+    if corner_uvs.is_empty() && corner_colors.is_empty() {
+        return;
+    }
+
+    let original_count = mesh.vertices.len();
+    let mut uv_sets: Vec<Vec<glm::Vec2>> = vec![vec![glm::vec2(0.0, 0.0); original_count]; corner_uvs.len()];
+    let mut color_sets: Vec<Vec<glm::Vec4>> = vec![vec![glm::vec4(0.0, 0.0, 0.0, 0.0); original_count]; corner_colors.len()];
+    let mut assigned = vec![false; original_count];
+    let mut copies: Vec<Vec<usize>> = vec![Vec::new(); original_count];
+
+    let mut corner = 0;
+    for face in mesh.faces.iter_mut() {
+        for index in face.indices.iter_mut() {
+            let original = *index as usize;
+            let matches = |vertex: usize| !assigned[vertex]
+                || (uv_sets.iter().zip(&corner_uvs).all(|(set, uvs)| set[vertex] == uvs[corner])
+                    && color_sets.iter().zip(&corner_colors).all(|(set, colors)| set[vertex] == colors[corner]));
+
+            let target = match std::iter::once(original).chain(copies[original].iter().copied()).find(|v| matches(*v)) {
+                Some(target) => target,
+                None => {
+                    mesh.vertices.push(mesh.vertices[original]);
+                    assigned.push(false);
+                    uv_sets.iter_mut().for_each(|set| set.push(glm::vec2(0.0, 0.0)));
+                    color_sets.iter_mut().for_each(|set| set.push(glm::vec4(0.0, 0.0, 0.0, 0.0)));
+                    copies[original].push(mesh.vertices.len() - 1);
+                    mesh.vertices.len() - 1
+                }
+            };
+
+            assigned[target] = true;
+            for (set, uvs) in uv_sets.iter_mut().zip(&corner_uvs) {
+                set[target] = uvs[corner];
+            }
+            for (set, colors) in color_sets.iter_mut().zip(&corner_colors) {
+                set[target] = colors[corner];
+            }
+            *index = target as i32;
+            corner += 1;
+        }
+    }
+    mesh.copy_hard_edges(&copies);
+    // The channels are built here, the copies only need the other vertex attributes
+    let mut source: Vec<usize> = (0..original_count).collect();
+    source.resize(mesh.vertices.len(), 0);
+    for (original, copies) in copies.iter().enumerate() {
+        for copy in copies {
+            source[*copy] = original;
         }
+    }
+    mesh.remap_vertex_attributes(&source);
+
+    // Sets are looked up by name, so a repeated one is numbered like a repeated mesh name
+    for (name, uvs) in uv_names.iter().zip(uv_sets) {
+        mesh.add_uv_set(name, uvs);
+    }
+    for (name, colors) in color_names.iter().zip(color_sets) {
+        mesh.add_color_set(name, colors);
+    }
+    if let Some(main) = mesh.uv_sets.first() {
+        mesh.uvs = main.uvs.clone();
+    }
+}
+
+/* Reads the material of every face from LayerElementMaterial, faces keep material 0 when there is none */
+fn read_face_materials(geometry: NodeRef, faces: &mut [Face], mesh: &str) {
+    let element = match geometry.children().get_first("LayerElementMaterial") {
+        Ok(element) => element,
+        Err(_) => return
+    };
 
-        let name = match &geom.properties[1] {
-            PropertyRecordType::String(str) => Some(str),
-            _ => None
-        }.unwrap().clone();
+    let values = match get_i32_array_child(element, "Materials") {
+        Some(values) => values,
+        None => return
+    };
+
+    let material = |value: Option<&i32>| value.and_then(|m| usize::try_from(*m).ok()).unwrap_or(0);
+    match get_string_child(element, "MappingInformationType") {
+        Some("AllSame") => {
+            let material = material(values.first());
+            faces.iter_mut().for_each(|face| face.material = material);
+        }
+        Some("ByPolygon") => {
+            for face in faces.iter_mut() {
+                face.material = material(values.get(face.polygon));
+            }
+        }
+        mapping => warning!("Materials of mesh '{}' have mapping {:?} which is not supported, using the first material for every face.", mesh, mapping),
+    }
+}
+
+/* Reads LayerElementSmoothing. ByPolygon groups are written to the faces, ByEdge flags are resolved to hard vertex pairs.
+   Runs before faces using vertices that do not exist are left out, so edges between such vertices are skipped here. */
+fn get_smoothing(geometry: NodeRef, faces: &mut [Face], vertex_count: usize) -> Smoothing {
+    let layer = match geometry.children().get_first("LayerElementSmoothing") {
+        Ok(layer) => layer,
+        Err(_) => return Smoothing::None
+    };
+
+    let values = match get_i32_array_child(layer, "Smoothing") {
+        Some(v) => v,
+        None => return Smoothing::None
+    };
+
+    match get_string_child(layer, "MappingInformationType") {
+        Some("ByPolygon") => {
+            for (face, group) in faces.iter_mut().zip(values) {
+                face.smoothing_group = *group;
+            }
+
+            Smoothing::ByPolygon
+        }
+        Some("ByEdge") => {
+            let edges = match get_i32_array_child(geometry, "Edges") {
+                Some(v) => v,
+                None => return Smoothing::None
+            };
+
+            // Edges are indices into PolygonVertexIndex, each one starting an edge that ends at the next
+            // vertex of the same polygon.
+            let mut polygon_vertex_edges = Vec::new();
+            for face in faces.iter() {
+                for i in 0..face.indices.len() {
+                    polygon_vertex_edges.push((face.indices[i], face.indices[(i + 1) % face.indices.len()]));
+                }
+            }
+
+            let mut hard_edges = HashSet::new();
+            for (edge, smooth) in edges.iter().zip(values) {
+                if *smooth != 0 {
+                    continue;
+                }
+
+                let exists = |index: &i32| (0..vertex_count as i32).contains(index);
+                if let Some((a, b)) = polygon_vertex_edges.get(*edge as usize).filter(|(a, b)| exists(a) && exists(b)) {
+                    hard_edges.insert((*a.min(b), *a.max(b)));
+                }
+            }
+
+            Smoothing::ByEdge(hard_edges)
+        }
+        _ => Smoothing::None
+    }
+}
+
+/* Id, name and type of an object, its first three properties */
+fn read_object_header(object: NodeRef<'_>) -> Result<(i64, &FbxString, &str), PropertyError> {
+    Ok((object.property_i64(0)?, object.property_str(1)?, object.property_str(2)?.name()))
+}
 
-        let object_type = match &geom.properties[2] {
-            PropertyRecordType::String(str) => Some(str),
-            _ => None
+/* Top level nodes the file should hold at most one of. Strict imports fail on repeated ones, others report them and
+   use the first. */
+fn get_section<'a>(nodes: NodeList<'a>, name: &str, strict: bool) -> Result<Option<NodeRef<'a>>, ImportError> {
+    match nodes.get(name) {
+        Ok(node) => Ok(Some(node)),
+        Err(Error::NoSuchNode { .. }) => Ok(None),
+        Err(e) if strict => Err(ImportError::RepeatedNode(e)),
+        Err(e) => {
+            warning!("{}, using the first one.", e);
+            Ok(nodes.get_first(name).ok())
+        }
+    }
+}
+
+/* Converts the nodes into a scene, None if the file has no Objects node */
+pub(super) fn import(document: &ArenaDocument, options: &ImportOptions) -> Result<Option<Scene>, ImportError> {
+    let nodes = document.nodes();
+    let strict = options.strict;
+
+    let objects_node = match get_section(nodes, "Objects", strict)? {
+        Some(node) => node,
+        None => return Ok(None),
+    };
+
+    let definitions = read_definitions(get_section(nodes, "Definitions", strict)?);
+    for mismatch in definitions.check_counts(objects_node) {
+        warning!("Definitions declare {} {} objects but the file holds {}, it may be truncated.",
+                  mismatch.declared, mismatch.kind, mismatch.found);
+    }
+
+    let geometry: Vec<NodeRef> = objects_node.children().get_multiple("Geometry").collect();
+
+    if geometry.is_empty() {
+        // No meshes to import
+        return Ok(None);
+    }
+
+    // Objects are usually connected to a single parent, so there are about as many connections as objects
+    let connections = read_connections(Some(objects_node), get_section(nodes, "Connections", strict)?, definitions.total());
+    // Files without documents, which are rare, keep every object and treat all models without a parent as roots
+    let reachable = read_documents(get_section(nodes, "Documents", strict)?).first()
+        .map(|document| reachable_from(document.root_node, &connections));
+    let is_orphan = |id: Option<i64>| match (&reachable, id) {
+        (Some(reachable), Some(id)) => !reachable.contains(&id),
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    let mut orphaned_meshes = 0;
+
+    let mut skins = read_skins(objects_node, &connections);
+    let mut blend_shapes = read_blend_shapes(objects_node, &connections);
+    let mut meshes = Vec::with_capacity(definitions.capacity("Geometry", geometry.len()));
+    // Id of the model each mesh is attached to
+    let mut mesh_models = Vec::with_capacity(meshes.capacity());
+    for geom in geometry {
+        let (id, name, object_type) = match read_object_header(geom) {
+            Ok(header) => header,
+            Err(e) if strict => return Err(ImportError::MalformedNode(e)),
+            Err(e) => {
+                warning!("{}, skipping it.", e);
+                continue;
+            }
         };
 
-        if object_type.is_none() || object_type.unwrap() != "Mesh" {
+        // Older exporters leave out the class, so only reject names that say they are something else
+        if name.class().is_some_and(|class| class != "Geometry") {
             continue;
         }
 
-        let vertices_node = match geom.children.get("Vertices") {
-            Ok(v) => v,
-            Err(e) => panic!("Errorrrrr!")
-        };
+        // Geometry also holds blend shape targets and curves
+        if object_type != "Mesh" {
+            continue;
+        }
 
-        let coordinates = match &vertices_node.properties[0] {
-            PropertyRecordType::DoubleArray(arr) => arr,
-            _ => panic!("Unexpected data in vertex node")
+        if is_orphan(Some(id)) {
+            orphaned_meshes += 1;
+            if !options.include_orphans {
+                continue;
+            }
+        }
+
+        let vertices = match get_vertices(geom, name.name(), strict)? {
+            Some(vertices) => vertices,
+            None => continue
         };
 
-        let vertices: Vec<glm::Vec3> = tuples3(coordinates.iter()
-            .map(|x| *x as f32)).map(|x| glm::vec3(x.0, x.1, x.2)).collect();
+        let mut faces = get_faces(geom, name.name(), strict)?;
+        // Per corner layers are looked up by the position of the polygon, which stays valid as faces get left out
+        let corner_offsets: Vec<usize> = faces.iter()
+            .scan(0, |offset, face| {
+                let start = *offset;
+                *offset += face.indices.len();
+                Some(start)
+            })
+            .collect();
+        let smoothing = get_smoothing(geom, &mut faces, vertices.len());
+        read_face_materials(geom, &mut faces, name.name());
+        // Only after the smoothing, which is mapped to the faces by their position in the file
+        let mut degenerate_faces = take_degenerate_faces(&mut faces, name.name(), strict)?;
+        remove_invalid_faces(&mut faces, vertices.len(), name.name(), strict)?;
+
+        let mut mesh = Mesh::new(
+            name.name().to_string(),
+            vertices,
+            faces
+        );
+        mesh.smoothing = smoothing;
+        // Cluster and shape indices refer to the vertices as read, so these go on before any get copied or removed
+        mesh.skin = skins.remove(&id);
+        mesh.blend_shapes = blend_shapes.remove(&id).unwrap_or_default();
+        mesh.layers = get_layers(geom);
+        read_vertex_channels(geom, &mut mesh, &corner_offsets);
+        apply_nan_policy(&mut mesh, &mut degenerate_faces, options.nan_policy)?;
+        if options.keep_degenerate_faces {
+            mesh.degenerate_faces = degenerate_faces;
+        }
+
+        mesh_models.push(connections.parent_of(id, ConnectionKind::ObjectObject));
+        meshes.push(mesh);
+    }
+
+    if options.rename_duplicate_meshes {
+        rename_duplicate_meshes(&mut meshes);
+    }
+
+    let mut hierarchy = build_nodes(objects_node, &connections);
+    if let Some(reachable) = &reachable {
+        let orphaned_nodes = detach_orphans(&mut hierarchy, reachable, options.include_orphans);
+        if orphaned_nodes + orphaned_meshes > 0 && !options.include_orphans {
+            warning!("Left out {} meshes and {} nodes not connected to the document root.", orphaned_meshes, orphaned_nodes);
+        }
+    }
+    if !options.include_hidden {
+        remove_hidden_nodes(&mut hierarchy);
+    }
+    attach_meshes(&mut hierarchy, &mesh_models);
+    let animation_layers = if options.include_animation { read_animation_layers(objects_node, &connections) } else { Vec::new() };
+    let scene = Scene::new(meshes)
+        .with_nodes(hierarchy)
+        .with_materials(read_materials(objects_node))
+        .with_textures(read_textures(objects_node, &connections))
+        .with_animation_layers(animation_layers)
+        .with_time_settings(read_time_settings(get_section(nodes, "GlobalSettings", strict)?))
+        .with_cameras(read_cameras(objects_node, &connections))
+        .with_lights(read_lights(objects_node, &connections))
+        .with_connections(connections);
+    Ok(Some(scene))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::property::{FbxString, PropertyRecordType};
+    use crate::fbx::fixture::{FbxFixture, FixtureNode};
+    use crate::scene::snapshot::assert_snapshot;
+    use crate::scene::mesh::diff::{mesh_diff, DiffTolerances};
+    use crate::fbx::polygon_vertex_index::encode_faces;
+    use crate::mesh_processor::MeshProcessor;
+    use crate::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
 
+    fn create_geometry_with_vertices(coordinates: Vec<f64>, indices: Vec<i32>) -> ArenaDocument {
+        FbxFixture::new(7400)
+            .node("Geometry", |g| g
+                .double_array("Vertices", coordinates)
+                .i32_array("PolygonVertexIndex", indices))
+            .parse()
+    }
 
-        meshes.push(
-            Mesh::new(
-                name,
-                vertices,
-                get_faces(geom)
-            ));
+    fn geometry_properties(name: &str) -> Vec<PropertyRecordType> {
+        vec![1i64.into(), FbxString::new(name).with_class("Geometry").into(), "Mesh".into()]
     }
 
-    Some(Scene::new(meshes))
+    /* Parses a file holding a single mesh called "broken", whose Geometry node is filled in by build */
+    fn parse_mesh<F>(build: F) -> ArenaDocument
+        where F: FnOnce(FixtureNode) -> FixtureNode
+    {
+        FbxFixture::new(7400)
+            .node("Objects", |o| o.node_with_props("Geometry", geometry_properties("broken"), build))
+            .parse()
+    }
+
+    fn parse_mesh_with_index_equal_to_vertex_count() -> ArenaDocument {
+        // Three vertices, so the second face using index 3 is out of range
+        parse_mesh(|g| g
+            .double_array("Vertices", vec![0.0; 9])
+            .i32_array("PolygonVertexIndex", vec![0, 1, -3, 1, 2, -4]))
+    }
+
+    #[test]
+    fn import_should_fail_on_index_equal_to_vertex_count_in_strict_mode() {
+        // Arrange
+        let nodes = parse_mesh_with_index_equal_to_vertex_count();
+
+        // Act
+        let result = import(&nodes, &ImportOptions::new().with_strict(true));
+
+        // Assert
+        assert_eq!(result.err(), Some(ImportError::IndexOutOfRange { mesh: "broken".to_string(), face: 1, index: 3 }));
+    }
+
+    #[test]
+    fn import_should_drop_faces_with_index_equal_to_vertex_count_in_lenient_mode() {
+        // Arrange
+        let nodes = parse_mesh_with_index_equal_to_vertex_count();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(scene.meshes[0].faces.len(), 1);
+        assert_eq!(scene.meshes[0].faces[0].indices, vec![0, 1, 2]);
+    }
+
+    fn parse_geometry_with_only_an_id() -> ArenaDocument {
+        FbxFixture::new(7400)
+            .node("Objects", |o| o.node_with_props("Geometry", vec![1i64.into()], |g| g
+                .double_array("Vertices", vec![0.0; 9])
+                .i32_array("PolygonVertexIndex", vec![0, 1, -3])))
+            .parse()
+    }
+
+    #[test]
+    fn import_should_fail_on_geometry_without_a_name_in_strict_mode() {
+        // Arrange
+        let nodes = parse_geometry_with_only_an_id();
+
+        // Act
+        let error = import(&nodes, &ImportOptions::new().with_strict(true)).err().unwrap();
+
+        // Assert
+        assert_eq!(error, ImportError::MalformedNode(PropertyError { node: "Geometry".to_string(), index: 1, expected: "string", found: None, cause: None }));
+        assert_eq!(error.to_string(), "Geometry node has no property 1, expected string");
+    }
+
+    #[test]
+    fn import_should_skip_geometry_without_a_name_in_lenient_mode() {
+        // Arrange
+        let nodes = parse_geometry_with_only_an_id();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        assert!(scene.meshes.is_empty());
+    }
+
+    fn parse_mesh_with_two_global_settings() -> ArenaDocument {
+        FbxFixture::new(7400)
+            .node("GlobalSettings", |g| g)
+            .node("Objects", |o| o.node_with_props("Geometry", geometry_properties("mesh"), |g| g
+                .double_array("Vertices", vec![0.0; 9])
+                .i32_array("PolygonVertexIndex", vec![0, 1, -3])))
+            .node("GlobalSettings", |g| g)
+            .parse()
+    }
+
+    #[test]
+    fn import_should_fail_on_repeated_top_level_nodes_in_strict_mode() {
+        // Arrange
+        let nodes = parse_mesh_with_two_global_settings();
+
+        // Act
+        let result = import(&nodes, &ImportOptions::new().with_strict(true));
+
+        // Assert
+        assert_eq!(result.err(), Some(ImportError::RepeatedNode(Error::MultipleValuesExist { name: "GlobalSettings".to_string(), count: 2 })));
+    }
+
+    #[test]
+    fn import_should_use_the_first_of_repeated_top_level_nodes_in_lenient_mode() {
+        // Arrange
+        let nodes = parse_mesh_with_two_global_settings();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(scene.meshes.len(), 1);
+    }
+
+    fn parse_mesh_with_stray_edge() -> ArenaDocument {
+        // Two quads with an isolated 2-index run between them
+        parse_mesh(|g| g
+            .double_array("Vertices", vec![0.0; 24])
+            .i32_array("PolygonVertexIndex", vec![0, 1, 2, -4, 3, -5, 4, 5, 6, -8]))
+    }
+
+    #[test]
+    fn import_should_drop_faces_with_fewer_than_three_indices() {
+        // Arrange
+        let nodes = parse_mesh_with_stray_edge();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        let mesh = &scene.meshes[0];
+        let faces: Vec<&Vec<i32>> = mesh.faces.iter().map(|f| &f.indices).collect();
+        assert_eq!(faces, vec![&vec![0, 1, 2, 3], &vec![4, 5, 6, 7]]);
+        assert!(mesh.degenerate_faces.is_empty());
+    }
+
+    #[test]
+    fn import_should_keep_faces_with_fewer_than_three_indices_when_asked() {
+        // Arrange
+        let nodes = parse_mesh_with_stray_edge();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new().with_keep_degenerate_faces(true)).unwrap().unwrap();
+
+        // Assert
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.faces.len(), 2);
+        assert_eq!(mesh.degenerate_faces.len(), 1);
+        assert_eq!(mesh.degenerate_faces[0].indices, vec![3, 4]);
+    }
+
+    #[test]
+    fn import_should_fail_on_faces_with_fewer_than_three_indices_in_strict_mode() {
+        // Arrange
+        let nodes = parse_mesh_with_stray_edge();
+
+        // Act
+        let result = import(&nodes, &ImportOptions::new().with_strict(true));
+
+        // Assert
+        assert_eq!(result.err(), Some(ImportError::DegenerateFaces { mesh: "broken".to_string(), count: 1 }));
+    }
+
+    #[test]
+    fn get_vertices_should_handle_coordinates_that_are_not_whole_vertices() {
+        // Arrange
+        let geometry = create_geometry_with_vertices(vec![1.0; 7], vec![0, 1, -3]);
+
+        // Act
+        let strict = get_vertices(geometry.get("Geometry").unwrap(), "broken", true);
+        let lenient = get_vertices(geometry.get("Geometry").unwrap(), "broken", false);
+
+        // Assert
+        assert_eq!(strict.err(), Some(ImportError::IncompleteVertex { mesh: "broken".to_string(), coordinates: 7 }));
+        assert_eq!(lenient.unwrap().unwrap(), vec![glm::vec3(1.0, 1.0, 1.0); 2]);
+    }
+
+    #[test]
+    fn get_vertices_should_tell_why_compressed_vertices_could_not_be_decoded() {
+        // Arrange
+        let coordinates = vec![0.5f64; 30];
+        let payload = deflate::deflate_bytes_zlib(&coordinates.iter().flat_map(|c| c.to_le_bytes()).collect::<Vec<u8>>());
+        let mut bytes = FbxFixture::new(7400).compressed()
+            .node_with_props("Geometry", vec![1i64.into(), FbxString::new("broken").with_class("Geometry").into(), "Mesh".into()], |g| g
+                .double_array("Vertices", coordinates))
+            .bytes();
+        let start = bytes.windows(payload.len()).position(|window| window == payload).unwrap();
+        for byte in &mut bytes[start + 2..start + payload.len()] {
+            *byte ^= 0x5a;
+        }
+        let document = ArenaDocument::from_bytes(&bytes).unwrap();
+
+        // Act
+        let result = get_vertices(document.get("Geometry").unwrap(), "broken", true);
+
+        // Assert
+        match result {
+            Err(ImportError::UnexpectedChildData { child: "Vertices", error, .. }) => assert!(error.cause.is_some()),
+            other => panic!("Expected the decode failure of Vertices, got {:?}", other),
+        }
+    }
+
+    fn parse_point_cloud() -> ArenaDocument {
+        parse_mesh(|g| g.double_array("Vertices", vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]))
+    }
+
+    #[test]
+    fn import_should_import_geometry_without_polygon_indices_as_points() {
+        // Arrange
+        let nodes = parse_point_cloud();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(scene.meshes[0].vertices, vec![glm::vec3(0.0, 1.0, 2.0), glm::vec3(3.0, 4.0, 5.0)]);
+        assert!(scene.meshes[0].faces.is_empty());
+    }
+
+    #[test]
+    fn import_should_fail_on_geometry_without_polygon_indices_in_strict_mode() {
+        // Arrange
+        let nodes = parse_point_cloud();
+
+        // Act
+        let result = import(&nodes, &ImportOptions::new().with_strict(true));
+
+        // Assert
+        assert_eq!(result.err(), Some(ImportError::MissingChild { mesh: "broken".to_string(), child: "PolygonVertexIndex" }));
+    }
+
+    #[test]
+    fn import_should_skip_geometry_without_vertices_or_polygon_indices() {
+        // Arrange
+        let nodes = parse_mesh(|g| g);
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        assert!(scene.meshes.is_empty());
+    }
+
+    #[test]
+    fn import_should_fail_on_geometry_without_vertices_in_strict_mode() {
+        // Arrange
+        let nodes = parse_mesh(|g| g);
+
+        // Act
+        let result = import(&nodes, &ImportOptions::new().with_strict(true));
+
+        // Assert
+        assert_eq!(result.err(), Some(ImportError::MissingChild { mesh: "broken".to_string(), child: "Vertices" }));
+    }
+
+    #[test]
+    fn import_should_accept_vertices_stored_as_floats() {
+        // Arrange
+        let nodes = parse_mesh(|g| g
+            .float_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
+            .i32_array("PolygonVertexIndex", vec![0, 1, -3]));
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new().with_strict(true)).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(scene.meshes[0].vertices[1], glm::vec3(1.0, 0.0, 0.0));
+        assert_eq!(scene.meshes[0].faces[0].indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn import_should_skip_geometry_with_vertices_of_unexpected_type() {
+        // Arrange
+        let nodes = parse_mesh(|g| g
+            .i32_array("Vertices", vec![0, 0, 0])
+            .i32_array("PolygonVertexIndex", vec![0, 0, -1]));
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        assert!(scene.meshes.is_empty());
+    }
+
+    #[test]
+    fn import_should_return_no_scene_without_objects_node() {
+        // Arrange
+        let nodes = FbxFixture::new(7400).node_with_props("Creator", vec!["tests".into()], |n| n).parse();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new());
+
+        // Assert
+        assert!(scene.unwrap().is_none());
+    }
+
+    #[test]
+    fn import_should_import_every_mesh_in_file_order() {
+        // Arrange
+        let nodes = FbxFixture::new(7500).compressed()
+            .node("Objects", |o| o
+                .node_with_props("Geometry", geometry_properties("first"), |g| g
+                    .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3]))
+                .node_with_props("Model", vec![2i64.into(), FbxString::new("first").with_class("Model").into(), "Mesh".into()], |m| m)
+                .node_with_props("Geometry", geometry_properties("second"), |g| g
+                    .double_array("Vertices", vec![0.0; 12])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, 2, -4])))
+            .parse();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new().with_strict(true)).unwrap().unwrap();
+
+        // Assert
+        let names: Vec<&str> = scene.meshes.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["first", "second"]);
+        assert_eq!(scene.meshes[1].faces[0].indices, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn import_should_keep_both_models_of_a_connection_cycle() {
+        // Arrange
+        let model = |id: i64, name: &str| vec![id.into(), FbxString::new(name).with_class("Model").into(), "Mesh".into()];
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("Geometry", geometry_properties("mesh"), |g| g
+                    .double_array("Vertices", vec![0.0; 9])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3]))
+                .node_with_props("Model", model(2, "first"), |m| m)
+                .node_with_props("Model", model(3, "second"), |m| m))
+            .node("Connections", |c| c
+                .node_with_props("C", vec!["OO".into(), 2i64.into(), 3i64.into()], |n| n)
+                .node_with_props("C", vec!["OO".into(), 3i64.into(), 2i64.into()], |n| n))
+            .parse();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        let names: Vec<&str> = scene.nodes.iter().map(|n| n.name()).collect();
+        assert_eq!(names, vec!["first", "second"]);
+        assert_eq!(scene.nodes[0].parent(), Some(1));
+        assert_eq!(scene.nodes[1].parent(), None);
+        assert_eq!(scene.ancestors(0).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn import_should_import_files_declaring_more_geometry_than_they_hold() {
+        // Arrange
+        let nodes = FbxFixture::new(7400)
+            .node("Definitions", |d| d
+                .node_with_props("ObjectType", vec!["Geometry".into()], |t| t
+                    .node_with_props("Count", vec![3i32.into()], |n| n)))
+            .node("Objects", |o| o
+                .node_with_props("Geometry", geometry_properties("first"), |g| g
+                    .double_array("Vertices", vec![0.0; 9])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3]))
+                .node_with_props("Geometry", geometry_properties("second"), |g| g
+                    .double_array("Vertices", vec![0.0; 9])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3])))
+            .parse();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new().with_strict(true)).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(scene.meshes.len(), 2);
+    }
+
+    /* Two meshes, the second left behind by an editing session with nothing connecting it to the document root */
+    fn parse_scene_with_orphaned_mesh() -> ArenaDocument {
+        let geometry = |id: i64, name: &str| vec![id.into(), FbxString::new(name).with_class("Geometry").into(), "Mesh".into()];
+        let model = |id: i64, name: &str| vec![id.into(), FbxString::new(name).with_class("Model").into(), "Mesh".into()];
+        FbxFixture::new(7400)
+            .node("Documents", |d| d
+                .node_with_props("Document", vec![100i64.into(), "Scene".into(), "Scene".into()], |n| n
+                    .node_with_props("RootNode", vec![0i64.into()], |n| n)))
+            .node("Objects", |o| o
+                .node_with_props("Geometry", geometry(1, "kept"), |g| g
+                    .double_array("Vertices", vec![0.0; 9])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3]))
+                .node_with_props("Geometry", geometry(2, "orphan"), |g| g
+                    .double_array("Vertices", vec![0.0; 9])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3]))
+                .node_with_props("Model", model(10, "kept"), |m| m)
+                .node_with_props("Model", model(20, "orphan"), |m| m))
+            .node("Connections", |c| c
+                .node_with_props("C", vec!["OO".into(), 10i64.into(), 0i64.into()], |n| n)
+                .node_with_props("C", vec!["OO".into(), 1i64.into(), 10i64.into()], |n| n)
+                .node_with_props("C", vec!["OO".into(), 2i64.into(), 20i64.into()], |n| n))
+            .parse()
+    }
+
+    #[test]
+    fn import_should_leave_out_objects_not_connected_to_the_document_root() {
+        // Arrange
+        let nodes = parse_scene_with_orphaned_mesh();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(scene.meshes[0].name, "kept");
+        let names: Vec<&str> = scene.nodes.iter().map(|n| n.name()).collect();
+        assert_eq!(names, vec!["kept"]);
+    }
+
+    #[test]
+    fn import_should_group_orphans_when_included() {
+        // Arrange
+        let nodes = parse_scene_with_orphaned_mesh();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new().with_include_orphans(true)).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(scene.meshes.len(), 2);
+        let names: Vec<&str> = scene.nodes.iter().map(|n| n.name()).collect();
+        assert_eq!(names, vec!["kept", "orphan", "orphans"]);
+        assert_eq!(scene.nodes[1].parent(), Some(2));
+        assert_eq!(scene.nodes[0].parent(), None);
+    }
+
+    #[test]
+    fn import_should_attach_meshes_to_their_models() {
+        // Arrange
+        let nodes = parse_scene_with_orphaned_mesh();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new().with_include_orphans(true)).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(scene.nodes[0].mesh(), Some(0));
+        assert_eq!(scene.nodes[1].mesh(), Some(1));
+        assert_eq!(scene.nodes[2].mesh(), None);
+    }
+
+    /* A quad whose third vertex has a NaN y, next to a triangle that only uses finite vertices */
+    fn parse_quad_with_nan_vertex() -> ArenaDocument {
+        parse_mesh(|g| g
+            .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, f64::NAN, 0.0, 0.0, 1.0, 0.0, 2.0, 0.0, 0.0])
+            .i32_array("PolygonVertexIndex", vec![0, 1, 2, -4, 1, 4, -4]))
+    }
+
+    #[test]
+    fn import_should_fail_on_nan_vertices_with_error_policy() {
+        // Arrange
+        let nodes = parse_quad_with_nan_vertex();
+
+        // Act
+        let result = import(&nodes, &ImportOptions::new().with_nan_policy(NanPolicy::Error));
+
+        // Assert
+        assert_eq!(result.err(), Some(ImportError::NonFiniteVertices { mesh: "broken".to_string(), count: 1 }));
+    }
+
+    #[test]
+    fn import_should_remove_nan_vertices_and_their_faces() {
+        // Arrange
+        let nodes = parse_quad_with_nan_vertex();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new().with_nan_policy(NanPolicy::RemoveAffectedFaces)).unwrap().unwrap();
+
+        // Assert
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.vertices.len(), 4);
+        assert!(mesh.vertices.iter().all(is_finite));
+        assert_eq!(mesh.faces.len(), 1);
+        assert_eq!(mesh.faces[0].indices, vec![1, 3, 2]);
+    }
+
+    #[test]
+    fn import_should_move_nan_vertices_to_the_origin() {
+        // Arrange
+        let nodes = parse_quad_with_nan_vertex();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new().with_nan_policy(NanPolicy::ReplaceWithZero)).unwrap().unwrap();
+
+        // Assert
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.vertices.len(), 5);
+        assert_eq!(mesh.vertices[2], glm::vec3(0.0, 0.0, 0.0));
+        assert_eq!(mesh.faces.len(), 2);
+        assert_eq!(mesh.faces[0].indices, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn import_should_match_snapshot_for_quad() {
+        // Arrange
+        let nodes = parse_mesh(|g| g
+            .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0])
+            .i32_array("PolygonVertexIndex", vec![0, 1, 2, -4]));
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        assert_snapshot("quad", &scene);
+    }
+
+    #[test]
+    fn import_should_match_snapshot_for_triangulated_concave_polygon() {
+        // Arrange
+        // L shaped hexagon, the corner at (1, 1) is reflex
+        let nodes = parse_mesh(|g| g
+            .double_array("Vertices", vec![0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 2.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 2.0, 0.0, 0.0, 2.0, 0.0])
+            .i32_array("PolygonVertexIndex", vec![0, 1, 2, 3, 4, -6]));
+
+        // Act
+        let mut scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+        TriangulateMeshProcessor::new().process(&mut scene.meshes[0]);
+
+        // Assert
+        assert_snapshot("concave_polygon", &scene);
+    }
+
+    #[test]
+    fn import_should_match_snapshot_for_two_meshes() {
+        // Arrange
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("Geometry", geometry_properties("Tetrahedron"), |g| g
+                    .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0])
+                    .i32_array("PolygonVertexIndex", vec![0, 2, -2, 0, 1, -4, 0, 3, -3, 1, 2, -4]))
+                .node_with_props("Geometry", geometry_properties("Plane"), |g| g
+                    .double_array("Vertices", vec![-1.0, 0.0, -1.0, 1.0, 0.0, -1.0, 1.0, 0.0, 1.0, -1.0, 0.0, 1.0])
+                    .i32_array("PolygonVertexIndex", vec![0, 3, 2, -2]))
+                .node_with_props("Model", vec![5i64.into(), FbxString::new("Tetrahedron").with_class("Model").into(), "Mesh".into()], |m| m)
+                .node_with_props("Model", vec![6i64.into(), FbxString::new("Plane").with_class("Model").into(), "Mesh".into()], |m| m))
+            .node("Connections", |c| c
+                .node_with_props("C", vec!["OO".into(), 5i64.into(), 0i64.into()], |n| n)
+                .node_with_props("C", vec!["OO".into(), 6i64.into(), 5i64.into()], |n| n))
+            .parse();
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        assert_snapshot("two_meshes", &scene);
+    }
+
+    /* Writes the mesh back out as Geometry, the way an exporter would */
+    fn export_mesh(mesh: &Mesh) -> ArenaDocument {
+        let coordinates = mesh.vertices.iter().flat_map(|v| vec![v.x as f64, v.y as f64, v.z as f64]).collect();
+        FbxFixture::new(7500).compressed()
+            .node("Objects", |o| o.node_with_props("Geometry", geometry_properties(&mesh.name), |g| g
+                .double_array("Vertices", coordinates)
+                .i32_array("PolygonVertexIndex", encode_faces(&mesh.faces))))
+            .parse()
+    }
+
+    #[test]
+    fn import_should_round_trip_meshes_through_export() {
+        // Arrange
+        let nodes = parse_quad_with_nan_vertex();
+        let first = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Act
+        let second = import(&export_mesh(&first.meshes[0]), &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        let diff = mesh_diff(&first.meshes[0], &second.meshes[0], &DiffTolerances::new());
+        assert!(diff.is_equivalent(), "{:?}", diff);
+    }
+
+    fn uv_element(g: FixtureNode, typed_index: i32, name: &str, uvs: Vec<f64>) -> FixtureNode {
+        g.node_with_props("LayerElementUV", vec![typed_index.into()], |e| e
+            .leaf("Name", name)
+            .leaf("MappingInformationType", "ByPolygonVertex")
+            .leaf("ReferenceInformationType", "Direct")
+            .double_array("UV", uvs))
+    }
+
+    fn uv_layer(g: FixtureNode, index: i32, typed_index: i32) -> FixtureNode {
+        g.node_with_props("Layer", vec![index.into()], |l| l
+            .node("LayerElement", |e| e
+                .leaf("Type", "LayerElementUV")
+                .leaf("TypedIndex", typed_index)))
+    }
+
+    /* Triangle with a lightmap set written before the main set, Layer nodes say which one comes first */
+    fn parse_mesh_with_two_uv_sets(with_layers: bool) -> ArenaDocument {
+        parse_mesh(|g| {
+            let g = g
+                .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
+                .i32_array("PolygonVertexIndex", vec![0, 1, -3]);
+            let g = uv_element(g, 1, "lightmap", vec![0.5, 0.5, 0.75, 0.5, 0.5, 0.75]);
+            let g = uv_element(g, 0, "map1", vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+            if with_layers { uv_layer(uv_layer(g, 1, 1), 0, 0) } else { g }
+        })
+    }
+
+    #[test]
+    fn import_should_order_uv_sets_by_layer() {
+        // Arrange
+        let nodes = parse_mesh_with_two_uv_sets(true);
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        let mesh = &scene.meshes[0];
+        let names: Vec<&str> = mesh.uv_sets().iter().map(|s| s.name()).collect();
+        assert_eq!(names, vec!["map1", "lightmap"]);
+        assert_eq!(mesh.uvs, vec![glm::vec2(0.0, 0.0), glm::vec2(1.0, 0.0), glm::vec2(0.0, 1.0)]);
+        let layers: Vec<(i32, i32)> = mesh.layers().iter().map(|l| (l.index(), l.elements()[0].typed_index())).collect();
+        assert_eq!(layers, vec![(0, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn import_should_keep_uv_sets_in_file_order_without_layers() {
+        // Arrange
+        let nodes = parse_mesh_with_two_uv_sets(false);
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        let mesh = &scene.meshes[0];
+        let names: Vec<&str> = mesh.uv_sets().iter().map(|s| s.name()).collect();
+        assert_eq!(names, vec!["lightmap", "map1"]);
+        assert!(mesh.layers().is_empty());
+    }
+
+    #[test]
+    fn import_should_read_color_sets_and_number_repeated_set_names() {
+        // Arrange
+        let nodes = parse_mesh(|g| {
+            let g = g
+                .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
+                .i32_array("PolygonVertexIndex", vec![0, 1, -3]);
+            let g = uv_element(g, 0, "map1", vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+            let g = uv_element(g, 1, "map1", vec![0.5, 0.5, 0.75, 0.5, 0.5, 0.75]);
+            g.node_with_props("LayerElementColor", vec![0i32.into()], |e| e
+                .leaf("Name", "colorSet1")
+                .leaf("MappingInformationType", "AllSame")
+                .leaf("ReferenceInformationType", "Direct")
+                .double_array("Colors", vec![1.0, 0.5, 0.25, 1.0]))
+        });
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.uv_set_names().collect::<Vec<_>>(), vec!["map1", "map1.001"]);
+        assert_eq!(mesh.uv_set("map1.001").map(|s| s.uvs()[0]), Some(glm::vec2(0.5, 0.5)));
+        assert_eq!(mesh.color_set("colorSet1").map(|s| s.colors().to_vec()), Some(vec![glm::vec4(1.0, 0.5, 0.25, 1.0); 3]));
+    }
+
+    fn parse_mesh_with_materials(mapping: &str, materials: Vec<i32>) -> ArenaDocument {
+        parse_mesh(|g| g
+            .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0])
+            // The middle polygon is left out as degenerate
+            .i32_array("PolygonVertexIndex", vec![0, 1, -3, 1, -3, 1, 3, -3])
+            .node_with_props("LayerElementMaterial", vec![0i32.into()], |e| e
+                .leaf("MappingInformationType", mapping)
+                .leaf("ReferenceInformationType", "IndexToDirect")
+                .i32_array("Materials", materials)))
+    }
+
+    #[test]
+    fn import_should_read_the_material_of_every_face() {
+        // Arrange
+        let by_polygon = parse_mesh_with_materials("ByPolygon", vec![1, 0, 2]);
+        let all_same = parse_mesh_with_materials("AllSame", vec![3]);
+
+        // Act
+        let by_polygon = import(&by_polygon, &ImportOptions::new()).unwrap().unwrap();
+        let all_same = import(&all_same, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        let materials = |scene: &Scene| scene.meshes[0].faces.iter().map(|f| f.material()).collect::<Vec<_>>();
+        assert_eq!(materials(&by_polygon), vec![1, 2]);
+        assert_eq!(materials(&all_same), vec![3, 3]);
+    }
+
+    #[test]
+    fn import_should_copy_vertices_on_uv_seams() {
+        // Arrange
+        // Two triangles sharing the edge 1-2, the second one uses another part of the texture
+        let nodes = parse_mesh(|g| g
+            .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0])
+            .i32_array("PolygonVertexIndex", vec![0, 1, -3, 1, 3, -3])
+            .node_with_props("LayerElementUV", vec![0i32.into()], |e| e
+                .leaf("Name", "map1")
+                .leaf("MappingInformationType", "ByPolygonVertex")
+                .leaf("ReferenceInformationType", "IndexToDirect")
+                .double_array("UV", vec![0.0, 0.0, 0.5, 0.0, 0.0, 0.5, 0.5, 0.5, 1.0, 0.5, 1.0, 1.0])
+                .i32_array("UVIndex", vec![0, 1, 2, 3, 4, 5])));
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.vertices.len(), 6);
+        assert_eq!(mesh.faces[1].indices, vec![4, 3, 5]);
+        assert_eq!(mesh.vertices[4], mesh.vertices[1]);
+        assert_eq!(mesh.uvs[4], glm::vec2(0.5, 0.5));
+        assert_eq!(mesh.uvs[3], glm::vec2(1.0, 0.5));
+    }
+
+    #[test]
+    fn import_should_skip_hard_edges_of_faces_using_vertices_that_do_not_exist() {
+        // Arrange
+        // The second triangle uses vertex 5 of 3, the uvs make the importer copy vertices along the hard edges
+        let nodes = parse_mesh(|g| {
+            let g = g
+                .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
+                .i32_array("PolygonVertexIndex", vec![0, 1, -3, 0, 1, !5])
+                .i32_array("Edges", vec![0, 1, 2, 3, 4, 5])
+                .node_with_props("LayerElementSmoothing", vec![0i32.into()], |e| e
+                    .leaf("MappingInformationType", "ByEdge")
+                    .leaf("ReferenceInformationType", "Direct")
+                    .i32_array("Smoothing", vec![0, 0, 0, 0, 0, 0]));
+            uv_element(g, 0, "map1", vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5])
+        });
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        let mesh = &scene.meshes[0];
+        assert_eq!(mesh.faces.len(), 1);
+        match &mesh.smoothing {
+            Smoothing::ByEdge(hard_edges) => {
+                assert_eq!(hard_edges.len(), 3);
+                assert!(hard_edges.iter().all(|(a, b)| (*a as usize) < mesh.vertices.len() && (*b as usize) < mesh.vertices.len()));
+            }
+            smoothing => panic!("expected hard edges, got {:?}", smoothing),
+        }
+    }
+
+    fn parse_meshes_named(names: &[&str]) -> ArenaDocument {
+        FbxFixture::new(7400)
+            .node("Objects", |o| names.iter().fold(o, |o, name| o
+                .node_with_props("Geometry", geometry_properties(name), |g| g
+                    .double_array("Vertices", vec![0.0; 9])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3]))))
+            .parse()
+    }
+
+    #[test]
+    fn import_should_number_meshes_sharing_a_name_in_file_order() {
+        // Arrange
+        let nodes = parse_meshes_named(&["Cube", "Cube", "Sphere", "Cube"]);
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        let names: Vec<&str> = scene.meshes.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["Cube", "Cube.001", "Sphere", "Cube.002"]);
+        assert!(scene.meshes.iter().filter(|m| m.name.starts_with("Cube")).all(|m| m.original_name == "Cube"));
+    }
+
+    #[test]
+    fn import_should_skip_suffixes_already_used_by_other_meshes() {
+        // Arrange
+        let nodes = parse_meshes_named(&["Cube", "Cube", "Cube.001"]);
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+
+        // Assert
+        let names: Vec<&str> = scene.meshes.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["Cube", "Cube.002", "Cube.001"]);
+    }
+
+    #[test]
+    fn import_should_keep_duplicate_names_when_renaming_is_off() {
+        // Arrange
+        let nodes = parse_meshes_named(&["Cube", "Cube"]);
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new().with_rename_duplicate_meshes(false)).unwrap().unwrap();
+
+        // Assert
+        let names: Vec<&str> = scene.meshes.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["Cube", "Cube"]);
+    }
+
+    #[test]
+    fn import_without_animation_should_leave_the_animation_curves_undecoded() {
+        // Arrange
+        let nodes = FbxFixture::new(7400).compressed()
+            .node("Objects", |o| (0..50i64).fold(o
+                .node_with_props("Geometry", geometry_properties("mesh"), |g| g
+                    .double_array("Vertices", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
+                    .i32_array("PolygonVertexIndex", vec![0, 1, -3])),
+                |o, id| o.node_with_props("AnimationCurve", vec![(100 + id).into(), "AnimCurve::".into(), "".into()], |c| c
+                    .i64_array("KeyTime", (0..1000).collect())
+                    .float_array("KeyValueFloat", (0..1000).map(|v| v as f32).collect()))))
+            .parse();
+        let curve_arrays = || nodes.get("Objects").unwrap().children().get_multiple("AnimationCurve")
+            .flat_map(|c| c.children().iter().flat_map(|k| k.properties().iter()))
+            .collect::<Vec<_>>();
+        let is_decoded = |property: &PropertyRecordType| match property {
+            PropertyRecordType::SignedInt64Array(a) => a.is_decoded(),
+            PropertyRecordType::FloatArray(a) => a.is_decoded(),
+            _ => panic!("Expected an animation curve array")
+        };
+
+        // Act
+        let scene = import(&nodes, &ImportOptions::new().with_include_animation(false)).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(curve_arrays().len(), 100);
+        assert!(curve_arrays().into_iter().all(|p| !is_decoded(p)));
+        import(&nodes, &ImportOptions::new()).unwrap().unwrap();
+        assert!(curve_arrays().into_iter().all(is_decoded));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,423 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Write;
+use std::fs::File;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use crate::fbx::{parse_file, Header, ImportOptions};
+use crate::fbx::definitions::read_definitions;
+use crate::fbx::importer::import;
+use crate::fbx::arena_document::NodeRef;
+use crate::fbx::arena_document::{ArenaDocument, NodeList};
+use crate::fbx::properties70::Properties70;
+use crate::scene::Scene;
+use crate::scene::time::KTime;
+
+/* Numbers about one mesh of FileInfo, as written in the file before any processing */
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshInfo {
+    pub name: String,
+    pub vertices: usize,
+    pub faces: usize,
+    // Number of faces with each number of corners
+    pub polygons: BTreeMap<usize, usize>,
+    // Smallest and largest corner, None for meshes without vertices
+    pub bounds: Option<(glm::Vec3, glm::Vec3)>,
+}
+
+/* Take of a file, with its start and stop in seconds */
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationStackInfo {
+    pub name: String,
+    pub start: f64,
+    pub stop: f64,
+}
+
+impl AnimationStackInfo {
+    pub fn duration(&self) -> f64 {
+        self.stop - self.start
+    }
+}
+
+/* What is in a file, see read_file_info. The parts read from the scene are empty when it could not be imported, the
+   node tree statistics are there for every file that parses. */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileInfo {
+    pub version: u32,
+    pub file_size: u64,
+    pub creator: Option<String>,
+    // From the header extension, as YYYY-MM-DD hh:mm:ss
+    pub created: Option<String>,
+    // Name and number of children of every top level node, in file order
+    pub top_level_nodes: Vec<(String, usize)>,
+    // Number of objects of each type as declared in Definitions
+    pub object_counts: Vec<(String, usize)>,
+    pub animation_stacks: Vec<AnimationStackInfo>,
+    // Whether the scene could be imported, the meshes, materials and textures are empty otherwise
+    pub scene_imported: bool,
+    pub meshes: Vec<MeshInfo>,
+    pub materials: Vec<String>,
+    pub textures: Vec<String>,
+    // Problems found in the file, which did not keep it from being read
+    pub diagnostics: Vec<String>,
+}
+
+fn object_name(object: NodeRef) -> String {
+    object.properties().get(1).and_then(|p| p.as_string()).map_or(String::new(), |s| s.name().to_string())
+}
+
+fn read_creator(nodes: NodeList) -> Option<String> {
+    let extension = nodes.get("FBXHeaderExtension").ok().and_then(|e| e.children().get_first("Creator").ok());
+    let creator = extension.or_else(|| nodes.get_first("Creator").ok())?;
+    Some(creator.property_str(0).ok()?.name().to_string())
+}
+
+fn read_creation_time(nodes: NodeList) -> Option<String> {
+    let stamp = nodes.get("FBXHeaderExtension").ok()?.children().get_first("CreationTimeStamp").ok()?;
+    let field = |name: &str| stamp.children().get_first(name).ok().and_then(|n| n.property_i64(0).ok());
+    Some(format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", field("Year")?, field("Month")?, field("Day")?,
+                 field("Hour")?, field("Minute")?, field("Second")?))
+}
+
+fn read_animation_stacks(objects: Option<NodeRef>) -> Vec<AnimationStackInfo> {
+    objects.into_iter()
+        .flat_map(|o| o.children().get_multiple("AnimationStack"))
+        .map(|stack| {
+            let properties = Properties70::from(stack);
+            let time = |local: &str, reference: &str| {
+                let ticks = properties.integer(local).or_else(|| properties.integer(reference)).unwrap_or(0);
+                KTime(ticks).seconds()
+            };
+            AnimationStackInfo {
+                name: object_name(stack),
+                start: time("LocalStart", "ReferenceStart"),
+                stop: time("LocalStop", "ReferenceStop"),
+            }
+        })
+        .collect()
+}
+
+fn add_scene(info: &mut FileInfo, scene: &Scene) {
+    info.scene_imported = true;
+    info.meshes = scene.meshes().iter()
+        .map(|mesh| MeshInfo {
+            name: mesh.name().to_string(),
+            vertices: mesh.vertices.len(),
+            faces: mesh.faces.len(),
+            polygons: mesh.polygon_histogram(),
+            bounds: mesh.bounds(),
+        })
+        .collect();
+    info.materials = scene.materials().iter().map(|m| m.name().to_string()).collect();
+    info.textures = scene.textures().iter().map(|t| t.name().to_string()).collect();
+}
+
+/* Everything FileInfo holds about the parsed file. Files whose scene cannot be imported still get the numbers read
+   from the node tree, with the reason in the diagnostics. */
+pub(crate) fn file_info((header, document): (Header, ArenaDocument), file_size: u64, options: &ImportOptions) -> FileInfo {
+    let nodes = document.nodes();
+    let mut info = FileInfo {
+        version: header.version(),
+        file_size,
+        creator: read_creator(nodes),
+        created: read_creation_time(nodes),
+        top_level_nodes: nodes.iter().map(|n| (n.name().to_string(), n.children().len())).collect(),
+        ..Default::default()
+    };
+
+    // First of them for files the importer turns down for having several
+    let objects = nodes.get_first("Objects").ok();
+    let definitions = read_definitions(nodes.get("Definitions").ok());
+    info.object_counts = definitions.counts().to_vec();
+    if let Some(objects) = objects {
+        for mismatch in definitions.check_counts(objects) {
+            info.diagnostics.push(format!("Definitions declare {} {} objects but the file has {}",
+                                          mismatch.declared, mismatch.kind, mismatch.found));
+        }
+    }
+    info.animation_stacks = read_animation_stacks(objects);
+
+    // Files too broken for the importer are what this is most often used on
+    match catch_unwind(AssertUnwindSafe(|| import(&document, options))) {
+        Ok(Ok(Some(scene))) => add_scene(&mut info, &scene),
+        Ok(Ok(None)) => info.diagnostics.push("The file holds no scene to import, showing the node tree only".to_string()),
+        Ok(Err(e)) => info.diagnostics.push(format!("The scene could not be imported: {}, showing the node tree only", e)),
+        Err(_) => info.diagnostics.push("The importer failed on the scene, showing the node tree only".to_string()),
+    }
+    for mesh in info.meshes.iter().filter(|m| m.faces == 0) {
+        info.diagnostics.push(format!("Mesh '{}' has no faces", mesh.name));
+    }
+    info
+}
+
+/* Reads what is in the file at path, for tools and logs, see FileInfo. Every top level node is parsed, including the
+   ones the importer skips. None if the file could not be parsed at all. */
+pub fn read_file_info(path: &Path) -> Option<FileInfo> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Could not open file {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    let file_size = file.metadata().map_or(0, |metadata| metadata.len());
+    let options = ImportOptions::new().with_skip_unused_nodes(false);
+    let parsed = match parse_file(file, &options) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("Could not parse file {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    Some(file_info(parsed, file_size, &options))
+}
+
+impl fmt::Display for FileInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "FBX version {}, {} bytes", self.version, self.file_size)?;
+        if let Some(creator) = &self.creator {
+            writeln!(f, "Creator: {}", creator)?;
+        }
+        if let Some(created) = &self.created {
+            writeln!(f, "Created: {}", created)?;
+        }
+
+        writeln!(f, "Top level nodes:")?;
+        for (name, children) in &self.top_level_nodes {
+            writeln!(f, "  {:<24} {} children", name, children)?;
+        }
+        if !self.object_counts.is_empty() {
+            writeln!(f, "Objects:")?;
+            for (kind, count) in &self.object_counts {
+                writeln!(f, "  {:<24} {}", kind, count)?;
+            }
+        }
+
+        if self.scene_imported {
+            writeln!(f, "Meshes: {}", self.meshes.len())?;
+            for mesh in &self.meshes {
+                let polygons: Vec<String> = mesh.polygons.iter().map(|(arity, count)| format!("{}x{}", count, arity)).collect();
+                write!(f, "  {}: {} vertices, {} faces ({})", mesh.name, mesh.vertices, mesh.faces, polygons.join(" "))?;
+                match mesh.bounds {
+                    Some((min, max)) => writeln!(f, ", bounds ({}, {}, {}) to ({}, {}, {})", min.x, min.y, min.z, max.x, max.y, max.z)?,
+                    None => writeln!(f)?,
+                }
+            }
+            writeln!(f, "Materials: {}", self.materials.join(", "))?;
+            writeln!(f, "Textures: {}", self.textures.join(", "))?;
+        }
+        if !self.animation_stacks.is_empty() {
+            writeln!(f, "Animation stacks:")?;
+            for stack in &self.animation_stacks {
+                writeln!(f, "  {}: {:.3} s to {:.3} s ({:.3} s)", stack.name, stack.start, stack.stop, stack.duration())?;
+            }
+        }
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "warning: {}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+fn json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => { let _ = write!(out, "\\u{:04x}", c as u32); }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/* JSON has no NaN or infinity */
+fn json_number(out: &mut String, value: f64) {
+    if value.is_finite() {
+        let _ = write!(out, "{}", value);
+    } else {
+        out.push_str("null");
+    }
+}
+
+fn json_list<T>(out: &mut String, values: &[T], write_value: impl Fn(&mut String, &T)) {
+    out.push('[');
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_value(out, value);
+    }
+    out.push(']');
+}
+
+fn json_counts(out: &mut String, counts: &[(String, usize)], key: &str) {
+    json_list(out, counts, |out, (name, count)| {
+        out.push_str("{\"name\":");
+        json_string(out, name);
+        let _ = write!(out, ",\"{}\":{}}}", key, count);
+    });
+}
+
+fn json_vec3(out: &mut String, v: glm::Vec3) {
+    json_list(out, &[v.x, v.y, v.z], |out, c| json_number(out, *c as f64));
+}
+
+impl FileInfo {
+    /* The same as a single JSON object, for scripts */
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(out, "{{\"version\":{},\"file_size\":{},\"creator\":", self.version, self.file_size);
+        match &self.creator {
+            Some(creator) => json_string(&mut out, creator),
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"created\":");
+        match &self.created {
+            Some(created) => json_string(&mut out, created),
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"top_level_nodes\":");
+        json_counts(&mut out, &self.top_level_nodes, "children");
+        out.push_str(",\"object_counts\":");
+        json_counts(&mut out, &self.object_counts, "count");
+        let _ = write!(out, ",\"scene_imported\":{},\"meshes\":", self.scene_imported);
+        json_list(&mut out, &self.meshes, |out, mesh| {
+            out.push_str("{\"name\":");
+            json_string(out, &mesh.name);
+            let _ = write!(out, ",\"vertices\":{},\"faces\":{},\"polygons\":{{", mesh.vertices, mesh.faces);
+            let polygons: Vec<String> = mesh.polygons.iter().map(|(arity, count)| format!("\"{}\":{}", arity, count)).collect();
+            out.push_str(&polygons.join(","));
+            out.push_str("},\"bounds\":");
+            match mesh.bounds {
+                Some((min, max)) => {
+                    out.push_str("{\"min\":");
+                    json_vec3(out, min);
+                    out.push_str(",\"max\":");
+                    json_vec3(out, max);
+                    out.push('}');
+                }
+                None => out.push_str("null"),
+            }
+            out.push('}');
+        });
+        out.push_str(",\"materials\":");
+        json_list(&mut out, &self.materials, |out, name| json_string(out, name));
+        out.push_str(",\"textures\":");
+        json_list(&mut out, &self.textures, |out, name| json_string(out, name));
+        out.push_str(",\"animation_stacks\":");
+        json_list(&mut out, &self.animation_stacks, |out, stack| {
+            out.push_str("{\"name\":");
+            json_string(out, &stack.name);
+            out.push_str(",\"start\":");
+            json_number(out, stack.start);
+            out.push_str(",\"stop\":");
+            json_number(out, stack.stop);
+            out.push('}');
+        });
+        out.push_str(",\"diagnostics\":");
+        json_list(&mut out, &self.diagnostics, |out, diagnostic| json_string(out, diagnostic));
+        out.push('}');
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::FbxFixture;
+    use crate::fbx::{parse_fbx, FbxString};
+    use std::io::Cursor;
+
+    fn create_file(with_geometry: bool) -> FbxFixture {
+        FbxFixture::new(7400)
+            .node("FBXHeaderExtension", |e| e
+                .leaf("Creator", "tests")
+                .node("CreationTimeStamp", |t| t
+                    .leaf("Year", 2024).leaf("Month", 3).leaf("Day", 9)
+                    .leaf("Hour", 14).leaf("Minute", 5).leaf("Second", 0)))
+            .node("Definitions", |d| d
+                .node_with_props("ObjectType", vec!["Geometry".into()], |t| t.leaf("Count", 2))
+                .node_with_props("ObjectType", vec!["AnimationStack".into()], |t| t.leaf("Count", 1)))
+            .node("Objects", |o| {
+                let o = if !with_geometry { o } else {
+                    o.node_with_props("Geometry", vec![1i64.into(), FbxString::new("quad").with_class("Geometry").into(), "Mesh".into()], |g| g
+                        .double_array("Vertices", vec![0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 2.0, 1.0, 0.0, 0.0, 1.0, -1.0])
+                        .i32_array("PolygonVertexIndex", vec![0, 1, 2, -4]))
+                };
+                o.node_with_props("AnimationStack", vec![2i64.into(), FbxString::new("Take 001").with_class("AnimStack").into(), "".into()], |s| s
+                    .node("Properties70", |p| p
+                        .node_with_props("P", vec!["LocalStart".into(), "KTime".into(), "Time".into(), "".into(), 0i64.into()], |n| n)
+                        .node_with_props("P", vec!["LocalStop".into(), "KTime".into(), "Time".into(), "".into(), KTime::from_seconds(2.5).0.into()], |n| n)))
+            })
+    }
+
+    fn info_of(fixture: FbxFixture) -> FileInfo {
+        let bytes = fixture.bytes();
+        let options = ImportOptions::new().with_skip_unused_nodes(false);
+        let parsed = parse_fbx(&mut Cursor::new(&bytes), &options).unwrap();
+        file_info(parsed, bytes.len() as u64, &options)
+    }
+
+    #[test]
+    fn file_info_should_describe_the_file_and_its_meshes() {
+        // Arrange
+        let fixture = create_file(true);
+
+        // Act
+        let info = info_of(fixture);
+
+        // Assert
+        assert_eq!(info.version, 7400);
+        assert_eq!(info.creator.as_deref(), Some("tests"));
+        assert_eq!(info.created.as_deref(), Some("2024-03-09 14:05:00"));
+        let top_level: Vec<&str> = info.top_level_nodes.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(top_level, vec!["FBXHeaderExtension", "Definitions", "Objects"]);
+        assert_eq!(info.top_level_nodes[2].1, 2);
+        assert_eq!(info.object_counts, vec![("Geometry".to_string(), 2), ("AnimationStack".to_string(), 1)]);
+        assert!(info.scene_imported);
+        assert_eq!(info.meshes.len(), 1);
+        assert_eq!(info.meshes[0].name, "quad");
+        assert_eq!(info.meshes[0].polygons, BTreeMap::from([(4, 1)]));
+        assert_eq!(info.meshes[0].bounds, Some((glm::vec3(0.0, 0.0, -1.0), glm::vec3(2.0, 1.0, 0.0))));
+        assert_eq!(info.animation_stacks, vec![AnimationStackInfo { name: "Take 001".to_string(), start: 0.0, stop: 2.5 }]);
+        assert_eq!(info.diagnostics, vec!["Definitions declare 2 Geometry objects but the file has 1".to_string()]);
+        assert!(info.to_string().contains("  quad: 4 vertices, 1 faces (1x4)"));
+        assert!(info.to_json().contains("\"meshes\":[{\"name\":\"quad\",\"vertices\":4,\"faces\":1,\"polygons\":{\"4\":1},\
+                                         \"bounds\":{\"min\":[0,0,-1],\"max\":[2,1,0]}}]"));
+    }
+
+    #[test]
+    fn file_info_should_fall_back_to_the_node_tree_without_a_scene() {
+        // Arrange
+        let fixture = create_file(false);
+
+        // Act
+        let info = info_of(fixture);
+
+        // Assert
+        assert!(!info.scene_imported);
+        assert!(info.meshes.is_empty());
+        assert_eq!(info.top_level_nodes.len(), 3);
+        assert_eq!(info.animation_stacks.len(), 1);
+        assert!(info.diagnostics.last().unwrap().starts_with("The file holds no scene"));
+        assert!(info.to_json().contains("\"scene_imported\":false"));
+    }
+
+    #[test]
+    fn to_json_should_escape_strings() {
+        // Arrange
+        let info = FileInfo { creator: Some("\"quoted\" \\ \n".to_string()), ..Default::default() };
+
+        // Act
+        let json = info.to_json();
+
+        // Assert
+        assert!(json.contains("\"creator\":\"\\\"quoted\\\" \\\\ \\n\""));
+        assert!(json.starts_with("{\"version\":0,"));
+        assert!(json.ends_with("\"diagnostics\":[]}"));
+    }
+}
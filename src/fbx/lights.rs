@@ -0,0 +1,113 @@
+use crate::fbx::arena_document::NodeRef;
+use crate::scene::connections::{ConnectionKind, Connections};
+use crate::fbx::properties70::Properties70;
+use crate::scene::light::{DecayType, Light, LightType};
+
+/* Reads the light NodeAttribute objects along with the node each one is attached to */
+pub(super) fn read_lights(objects: NodeRef, connections: &Connections) -> Vec<Light> {
+    let mut lights = Vec::new();
+    for node in objects.children().get_multiple("NodeAttribute") {
+        if node.properties().get(2).and_then(|p| p.as_string()).map(|s| s.name()) != Some("Light") {
+            continue;
+        }
+        let id = match node.properties().first().and_then(|p| p.as_i64()) {
+            Some(id) => id,
+            None => continue
+        };
+        let name = node.properties().get(1).and_then(|p| p.as_string()).map_or(String::new(), |s| s.name().to_string());
+        let properties = Properties70::from(node);
+
+        let mut light = Light::new(id, name);
+        light.node_id = connections.parent_of(id, ConnectionKind::ObjectObject);
+        if let Some(light_type) = properties.integer("LightType") {
+            light.light_type = LightType::from_fbx(light_type);
+        }
+        if let Some(decay) = properties.integer("DecayType") {
+            light.decay = DecayType::from_fbx(decay);
+        }
+        if let Some(color) = properties.vec3("Color") {
+            light.color = color;
+        }
+        if let Some(cast_shadows) = properties.integer("CastShadows") {
+            light.cast_shadows = cast_shadows != 0;
+        }
+
+        let number = |name: &str, target: &mut f32| {
+            if let Some(value) = properties.number(name) {
+                *target = value as f32;
+            }
+        };
+        number("Intensity", &mut light.intensity);
+        // Older files call the inner angle the cone angle
+        number("Coneangle", &mut light.inner_angle);
+        number("InnerAngle", &mut light.inner_angle);
+        number("OuterAngle", &mut light.outer_angle);
+
+        lights.push(light);
+    }
+
+    lights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::FbxFixture;
+    use crate::fbx::property::{FbxString, PropertyRecordType};
+
+    fn p(name: &str, kind: &str, values: Vec<PropertyRecordType>) -> Vec<PropertyRecordType> {
+        let mut properties: Vec<PropertyRecordType> = vec![name.into(), kind.into(), "".into(), "A".into()];
+        properties.extend(values);
+        properties
+    }
+
+    #[test]
+    fn read_lights_should_read_spot_light() {
+        // Arrange
+        let attribute = vec![5i64.into(), FbxString::new("Spot").with_class("NodeAttribute").into(), "Light".into()];
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("NodeAttribute", attribute, |n| n
+                    .node("Properties70", |p70| p70
+                        .node_with_props("P", p("LightType", "enum", vec![2i32.into()]), |n| n)
+                        .node_with_props("P", p("Color", "Color", vec![1.0.into(), 0.5.into(), 0.25.into()]), |n| n)
+                        .node_with_props("P", p("Intensity", "Number", vec![250.0.into()]), |n| n)
+                        .node_with_props("P", p("DecayType", "enum", vec![2i32.into()]), |n| n)
+                        .node_with_props("P", p("InnerAngle", "Number", vec![30.0.into()]), |n| n)
+                        .node_with_props("P", p("OuterAngle", "Number", vec![45.0.into()]), |n| n)
+                        .node_with_props("P", p("CastShadows", "bool", vec![1i32.into()]), |n| n))))
+            .parse();
+
+        // Act
+        let lights = read_lights(nodes.get("Objects").ok().unwrap(), &Connections::new());
+
+        // Assert
+        let light = &lights[0];
+        assert_eq!(light.light_type, LightType::Spot);
+        assert_eq!(light.color, glm::vec3(1.0, 0.5, 0.25));
+        assert_eq!(light.intensity_normalized(), 2.5);
+        assert_eq!(light.decay, DecayType::Quadratic);
+        assert!(light.cast_shadows);
+        let (inner, outer) = light.cone_angles_radians().unwrap();
+        assert!((inner - std::f32::consts::PI / 6.0).abs() < 1e-6);
+        assert!((outer - std::f32::consts::PI / 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn read_lights_should_use_sdk_defaults() {
+        // Arrange
+        let attribute = vec![5i64.into(), FbxString::new("Point").with_class("NodeAttribute").into(), "Light".into()];
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o.node_with_props("NodeAttribute", attribute, |n| n))
+            .parse();
+
+        // Act
+        let lights = read_lights(nodes.get("Objects").ok().unwrap(), &Connections::new());
+
+        // Assert
+        assert_eq!(lights[0].light_type, LightType::Point);
+        assert_eq!(lights[0].intensity_normalized(), 1.0);
+        assert_eq!(lights[0].cone_angles_radians(), None);
+        assert!(!lights[0].cast_shadows);
+    }
+}
\ No newline at end of file
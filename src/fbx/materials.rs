@@ -0,0 +1,116 @@
+use crate::fbx::arena_document::NodeRef;
+use crate::fbx::properties70::Properties70;
+use crate::scene::material::{Material, ShadingModel};
+
+/* Reads the Material objects. The shading model comes from the ShadingModel child, or the property of the same name
+   some exporters write instead. */
+pub(super) fn read_materials(objects: NodeRef) -> Vec<Material> {
+    let mut materials = Vec::new();
+    for node in objects.children().get_multiple("Material") {
+        let id = match node.properties().first().and_then(|p| p.as_i64()) {
+            Some(id) => id,
+            None => continue
+        };
+        let name = node.properties().get(1).and_then(|p| p.as_string()).map_or(String::new(), |s| s.name().to_string());
+        let properties = Properties70::from(node);
+
+        let shading_model = node.children().get_first("ShadingModel").ok()
+            .and_then(|s| s.properties().first())
+            .and_then(|p| p.as_string())
+            .map(|s| s.name())
+            .or_else(|| properties.string("ShadingModel"))
+            .map_or(ShadingModel::Lambert, ShadingModel::from_name);
+
+        let mut material = Material::new(id, name, shading_model);
+        let number = |name: &str, target: &mut f32| {
+            if let Some(value) = properties.number(name) {
+                *target = value as f32;
+            }
+        };
+        let color = |name: &str, target: &mut glm::Vec3| {
+            if let Some(value) = properties.vec3(name) {
+                *target = value;
+            }
+        };
+
+        let lambert = &mut material.lambert;
+        color("AmbientColor", &mut lambert.ambient_color);
+        number("AmbientFactor", &mut lambert.ambient_factor);
+        color("DiffuseColor", &mut lambert.diffuse_color);
+        number("DiffuseFactor", &mut lambert.diffuse_factor);
+        color("EmissiveColor", &mut lambert.emissive_color);
+        number("EmissiveFactor", &mut lambert.emissive_factor);
+
+        if let Some(phong) = &mut material.phong {
+            color("SpecularColor", &mut phong.specular_color);
+            number("SpecularFactor", &mut phong.specular_factor);
+            number("Shininess", &mut phong.shininess);
+        }
+
+        materials.push(material);
+    }
+
+    materials
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::{FbxFixture, FixtureNode};
+    use crate::fbx::property::{FbxString, PropertyRecordType};
+
+    fn p(name: &str, values: Vec<PropertyRecordType>) -> Vec<PropertyRecordType> {
+        let mut properties: Vec<PropertyRecordType> = vec![name.into(), "".into(), "".into(), "A".into()];
+        properties.extend(values);
+        properties
+    }
+
+    fn read_material<F>(shading_model: &str, build: F) -> Material
+        where F: FnOnce(FixtureNode) -> FixtureNode
+    {
+        let properties = vec![7i64.into(), FbxString::new("Paint").with_class("Material").into(), "".into()];
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o.node_with_props("Material", properties, |m| m
+                .leaf("ShadingModel", shading_model)
+                .node("Properties70", build)))
+            .parse();
+        read_materials(nodes.get("Objects").ok().unwrap()).remove(0)
+    }
+
+    #[test]
+    fn read_materials_should_use_the_default_specular_factor_for_phong() {
+        // Arrange
+        let build = |p70: FixtureNode| p70
+            .node_with_props("P", p("Shininess", vec![20.0.into()]), |n| n)
+            .node_with_props("P", p("DiffuseColor", vec![1.0.into(), 0.0.into(), 0.0.into()]), |n| n);
+
+        // Act
+        let material = read_material("Phong", build);
+
+        // Assert
+        assert_eq!(material.name(), "Paint");
+        assert_eq!(material.shading_model(), &ShadingModel::Phong);
+        assert_eq!(material.lambert().diffuse_color, glm::vec3(1.0, 0.0, 0.0));
+        assert_eq!(material.lambert().ambient_color, glm::vec3(0.2, 0.2, 0.2));
+        let phong = material.phong().unwrap();
+        assert_eq!(phong.shininess, 20.0);
+        assert_eq!(phong.specular_factor, 1.0);
+    }
+
+    #[test]
+    fn read_materials_should_match_shading_models_ignoring_case() {
+        // Arrange
+        let build = |p70: FixtureNode| p70.node_with_props("P", p("SpecularFactor", vec![0.5.into()]), |n| n);
+
+        // Act
+        let phong = read_material("phong", build);
+        let lambert = read_material("LAMBERT", |p70| p70);
+        let unknown = read_material("toon", |p70| p70);
+
+        // Assert
+        assert_eq!(phong.phong().unwrap().specular_factor, 0.5);
+        assert_eq!(lambert.shading_model(), &ShadingModel::Lambert);
+        assert!(lambert.phong().is_none());
+        assert_eq!(unknown.shading_model(), &ShadingModel::Unknown("toon".to_string()));
+    }
+}
\ No newline at end of file
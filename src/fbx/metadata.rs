@@ -0,0 +1,125 @@
+use crate::fbx::ParseError;
+use crate::fbx::arena_document::{DocumentError, NodeList};
+use crate::fbx::importer::ImportError as ConversionError;
+use crate::scene::Scene;
+use crate::scene::timings::{millis, ImportTimings};
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/* Numbers about an import for logging, see import_fbx_with_metadata */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportMetadata {
+    // From the start of parsing until the last processor was done
+    pub wall_time: Duration,
+    // Same as Scene::timings
+    pub timings: ImportTimings,
+    pub file_size: u64,
+    // Node records read, not counting the top level nodes skipped without reading them
+    pub nodes: usize,
+    // Compressed array properties inflated while converting, and their size once inflated
+    pub inflated_arrays: usize,
+    pub inflated_bytes: u64,
+    pub meshes: usize,
+    pub vertices: usize,
+    pub faces: usize,
+}
+
+impl ImportMetadata {
+    /* Counts the nodes and inflated arrays, after the scene has been converted from them */
+    pub(crate) fn add_nodes(&mut self, nodes: NodeList) {
+        let mut pending = vec![nodes];
+        while let Some(collection) = pending.pop() {
+            for node in collection.iter() {
+                self.nodes += 1;
+                for size in node.properties().iter().filter_map(|p| p.inflated_size()) {
+                    self.inflated_arrays += 1;
+                    self.inflated_bytes += size as u64;
+                }
+                pending.push(node.children());
+            }
+        }
+    }
+
+    /* Counts the meshes of the scene, once the processors are done with them */
+    pub(crate) fn add_scene(&mut self, scene: &Scene) {
+        self.meshes += scene.meshes.len();
+        self.vertices += scene.meshes.iter().map(|m| m.vertices.len()).sum::<usize>();
+        self.faces += scene.meshes.iter().map(|m| m.faces.len()).sum::<usize>();
+    }
+}
+
+/* A single line of key=value pairs, for structured logs */
+impl fmt::Display for ImportMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let processing = self.timings.processors.iter().map(|p| p.duration).sum::<Duration>();
+        write!(f, "file_size={} nodes={} inflated_arrays={} inflated_bytes={} meshes={} vertices={} faces={} \
+                   wall_ms={:.3} parse_ms={:.3} conversion_ms={:.3} processing_ms={:.3}",
+               self.file_size, self.nodes, self.inflated_arrays, self.inflated_bytes, self.meshes, self.vertices, self.faces,
+               millis(self.wall_time), millis(self.timings.parse), millis(self.timings.conversion), millis(processing))
+    }
+}
+
+/* Why an import gave no scene */
+#[derive(Debug)]
+pub enum ImportError {
+    // The file could not be opened or read
+    Io(io::Error),
+    // The file is not a valid FBX file, or a gzip compressed one that could not be inflated
+    Parse(DocumentError),
+    // The nodes were read but do not make up a scene, such as a face using a vertex that does not exist
+    Conversion(ConversionError),
+    // The file holds no Objects node, so there is nothing to import
+    NoObjects,
+    // A mesh processor asked for the import to be aborted, processor is its name
+    ProcessorFailed { processor: String },
+    // The progress callback asked for the import to stop, or the future importing the file was dropped
+    Cancelled,
+    // The import panicked, or the runtime running it shut down before it was done
+    Panicked,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(e) => write!(f, "Could not read file: {}", e),
+            ImportError::Parse(e) => write!(f, "Could not parse file: {}", e),
+            ImportError::Conversion(e) => write!(f, "{}", e),
+            ImportError::NoObjects => write!(f, "File has no Objects node, nothing to import"),
+            ImportError::ProcessorFailed { processor } => write!(f, "Mesh processing failed in {}", processor),
+            ImportError::Cancelled => write!(f, "Import was cancelled"),
+            ImportError::Panicked => write!(f, "Import panicked"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<io::Error> for ImportError {
+    fn from(error: io::Error) -> Self {
+        ImportError::Io(error)
+    }
+}
+
+/* Errors reading the input are told apart from the ones in what was read */
+impl From<ParseError> for ImportError {
+    fn from(error: ParseError) -> Self {
+        match error {
+            ParseError::IOError(e) => ImportError::Io(e),
+            ParseError::Cancelled => ImportError::Cancelled,
+            error => ImportError::Parse(DocumentError(error)),
+        }
+    }
+}
+
+impl From<ConversionError> for ImportError {
+    fn from(error: ConversionError) -> Self {
+        ImportError::Conversion(error)
+    }
+}
+
+/* An imported scene along with the numbers about its import */
+pub struct ImportResult {
+    pub scene: Scene,
+    pub metadata: ImportMetadata,
+}
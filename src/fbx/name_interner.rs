@@ -0,0 +1,27 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/* Hands out one shared allocation per distinct node name. Files repeat a small set of names ("P", "Properties70",
+   "Model"...) many thousands of times, so this saves an allocation per node and lets equal names be compared by
+   pointer. */
+pub(crate) struct NameInterner {
+    names: HashSet<Arc<str>>,
+}
+
+impl NameInterner {
+    pub(crate) fn new() -> Self {
+        NameInterner {
+            names: HashSet::new(),
+        }
+    }
+
+    pub(crate) fn intern(&mut self, name: &str) -> Arc<str> {
+        if let Some(existing) = self.names.get(name) {
+            return existing.clone();
+        }
+
+        let interned: Arc<str> = Arc::from(name);
+        self.names.insert(interned.clone());
+        interned
+    }
+}
\ No newline at end of file
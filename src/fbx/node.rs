@@ -1,102 +1,87 @@
 use crate::fbx::{ParseError, ParseResult};
 use std::io::{Read, Seek};
-use byteorder::{ReadBytesExt, LittleEndian};
-use crate::fbx::property::{PropertyRecordType, parse_properties};
-use multimap::MultiMap;
+use std::sync::Arc;
+use crate::fbx::property::PropertyRecordType;
+use crate::fbx::node_reader::{NodeReader, NodeEvent};
 use crate::fbx::node_collection::NodeCollection;
 
+/* Node tree with an allocation per name, property list and child list, the way files were read before
+   ArenaDocument. Only kept for the benchmarks to compare against. */
 #[derive(Debug)]
 pub struct NodeRecord {
-    pub(crate) name: String,
+    pub(crate) name: Arc<str>,
     pub(crate) properties: Vec<PropertyRecordType>,
     pub(crate) children: NodeCollection,
 }
 
-fn parse_string(reader: &mut dyn Read) -> ParseResult<String> {
-    let length = reader.read_u8()? as usize;
-    let mut string_bytes = vec![0u8; length];
-    reader.read_exact(&mut string_bytes)?;
-
-    Ok(String::from_utf8(string_bytes)?)
+fn begin_record<R>(node_reader: &mut NodeReader<R>, name: Arc<str>, num_properties: usize) -> ParseResult<NodeRecord>
+    where
+        R: Read + Seek
+{
+    // Every property takes at least two bytes, so a corrupt count can not make us allocate more than the file holds
+    let capacity = num_properties.min(node_reader.remaining_bytes()? / 2);
+
+    Ok(NodeRecord {
+        properties: Vec::with_capacity(capacity),
+        children: NodeCollection::new(),
+        name,
+    })
 }
 
-fn parse_node<R>(reader: &mut R, file_length: usize) -> ParseResult<Option<NodeRecord>>
+/* Reads the node just begun along with all of its children. Nodes still being read are kept on an explicit stack
+   rather than the call stack, NodeReader limits how deep it may grow. Returns None if the node itself was skipped
+   as malformed. */
+fn read_node<R>(node_reader: &mut NodeReader<R>, name: Arc<str>, num_properties: usize) -> ParseResult<Option<NodeRecord>>
     where
-        R: Read + Seek{
-    let end_offset = reader.read_u32::<LittleEndian>()? as usize;
-    if end_offset == 0 {
-        // End of file
-        return Ok(None);
-    }
-
-    if end_offset >= file_length {
-        return Err(ParseError::ValidationError("end offset is outside bounds".to_string()));
-    }
-
-    let num_properties = reader.read_u32::<LittleEndian>()?;
-    let property_length_bytes = reader.read_u32::<LittleEndian>()?;
-    let name = parse_string(reader)?;
-
-    if name == "Vertices" {
-        let sdsds = 22;
-    }
-
-    let property_start_offset = reader.stream_position()? as usize;
-    if property_start_offset + property_length_bytes as usize > file_length {
-        return Err(ParseError::ValidationError("property length out of bounds".to_string()));
-    }
-    let properties = parse_properties(reader,num_properties as usize)?;
-
-    if property_length_bytes as usize != reader.stream_position()? as usize - property_start_offset {
-        return Err(ParseError::ValidationError("did not read correct amount of bytes when parsing properties".to_string()));
-    }
-
-    let mut child_nodes = NodeCollection::new();
-    if (reader.stream_position()? as usize) < end_offset {
-        let remaining_byte_count = end_offset - reader.stream_position()? as usize;
-        let sentinel_block_length = std::mem::size_of::<u32>() * 3 + 1;
-        if remaining_byte_count < sentinel_block_length {
-            return Err(ParseError::ValidationError("insufficient amount of bytes at end of node".to_string()))
-        }
-
-        while (reader.stream_position()? as usize) < end_offset - sentinel_block_length {
-            if let Some(node) = parse_node(reader, file_length)? {
-                child_nodes.insert(node);
+        R: Read + Seek
+{
+    let mut open_records = vec![begin_record(node_reader, name, num_properties)?];
+
+    loop {
+        match node_reader.next_event()? {
+            Some(NodeEvent::Property(property)) => open_records.last_mut().unwrap().properties.push(property),
+            Some(NodeEvent::BeginNode { name, num_properties }) => {
+                let record = begin_record(node_reader, name, num_properties)?;
+                open_records.push(record);
             }
-        }
-
-        let mut sentinel_block = vec![0u8; sentinel_block_length];
-        reader.read_exact(&mut sentinel_block)?;
-        for i in 0..sentinel_block_length {
-            if sentinel_block[i] != 0 {
-                return Err(ParseError::ValidationError("sentinel block contains non-zero values".to_string()));
+            Some(NodeEvent::EndNode) => {
+                let record = open_records.pop().unwrap();
+                match open_records.last_mut() {
+                    Some(parent) => parent.children.insert(record),
+                    None => return Ok(Some(record))
+                }
             }
+            Some(NodeEvent::SkippedNode) => {
+                open_records.pop();
+                if open_records.is_empty() {
+                    return Ok(None);
+                }
+            }
+            None => return Err(ParseError::ValidationError("unexpected end of file inside node".to_string()))
         }
     }
-
-    if reader.stream_position()? as usize != end_offset {
-        return Err(ParseError::ValidationError("end offset not reached.".to_string()));
-    }
-
-    Ok(Some(NodeRecord {
-        properties,
-        children: child_nodes,
-        name: name.to_string(),
-    }))
 }
 
-pub(super) fn parse_nodes<R>(reader: &mut R, file_length: usize) -> ParseResult<NodeCollection>
+/* Parses the node tree of a file with the given version, seeking past any top level node named in skipped without
+   reading it. Outside of strict mode, records that are slightly off are read anyway where possible. */
+pub(super) fn parse_nodes_skipping<R>(reader: &mut R, version: u32, skipped: &[&str], max_depth: usize, strict: bool) -> ParseResult<NodeCollection>
     where
         R: Read + Seek
 {
+    let mut node_reader = NodeReader::new(reader)?.with_max_depth(max_depth).with_version(version).with_strict(strict);
     let mut result = NodeCollection::new();
 
-    while (reader.stream_position()? as usize) < file_length {
-        match parse_node(reader, file_length)? {
-            Some(node) => result.insert(node),
-            None => break
+    while let Some(event) = node_reader.next_event()? {
+        match event {
+            NodeEvent::BeginNode { name, .. } if skipped.contains(&&*name) => node_reader.skip_current_subtree()?,
+            NodeEvent::BeginNode { name, num_properties } => {
+                if let Some(record) = read_node(&mut node_reader, name, num_properties)? {
+                    result.insert(record);
+                }
+            }
+            _ => return Err(ParseError::ValidationError("unexpected event outside of node".to_string()))
         }
     }
 
     Ok(result)
-}
\ No newline at end of file
+}
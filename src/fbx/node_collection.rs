@@ -1,36 +1,48 @@
-use multimap::MultiMap;
+use std::collections::HashMap;
+use std::sync::Arc;
 use crate::fbx::node::NodeRecord;
-use crate::fbx::node_collection::Error::{NoSuchNode, MultipleValuesExist};
 
+/* Child nodes of a NodeRecord in file order, along with the index by name the importer looked them up in */
 #[derive(Debug)]
 pub struct NodeCollection {
-    nodes: MultiMap<String, NodeRecord>,
+    nodes: Vec<NodeRecord>,
+    // Positions in nodes of every name, keyed on the interned name so inserting does not copy it
+    by_name: HashMap<Arc<str>, Vec<usize>>,
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum Error {
-    MultipleValuesExist,
-    NoSuchNode
+    // Several nodes have the name where one was asked for
+    MultipleValuesExist { name: String, count: usize },
+    NoSuchNode { name: String },
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::MultipleValuesExist { name, count } => write!(f, "Found {} nodes named '{}' where one was expected", count, name),
+            Error::NoSuchNode { name } => write!(f, "Found no node named '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl NodeCollection {
     pub fn new() -> Self {
         NodeCollection {
-            nodes: MultiMap::new()
+            nodes: Vec::new(),
+            by_name: HashMap::new(),
         }
     }
 
+    /* Adds a node after the ones already in the collection */
     pub fn insert(&mut self, node: NodeRecord) {
-        self.nodes.insert(node.name.clone(), node);
+        self.by_name.entry(node.name.clone()).or_default().push(self.nodes.len());
+        self.nodes.push(node);
     }
 
-    pub fn get(&self, name: &str) -> Result<&NodeRecord, Error> {
-        match self.nodes.get(name) {
-            Some(x) => Ok(x),
-            None => Err(NoSuchNode)
-        }
-    }
-
-    pub fn get_multiple(&self, name: &str) -> Option<&Vec<NodeRecord>> {
-        self.nodes.get_vec(name)
+    pub fn len(&self) -> usize {
+        self.nodes.len()
     }
-}
\ No newline at end of file
+}
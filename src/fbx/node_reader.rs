@@ -0,0 +1,1115 @@
+use crate::warnings::warning;
+use crate::fbx::{ParseError, ParseResult, with_thousands_separators};
+use crate::fbx::property::{PropertyRecordType, parse_property};
+use crate::fbx::name_interner::NameInterner;
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::sync::Arc;
+use std::convert::TryFrom;
+use byteorder::{ReadBytesExt, LittleEndian};
+
+const SENTINEL_BLOCK_LENGTH: usize = std::mem::size_of::<u32>() * 3 + 1;
+// From 7.5 on the offsets and counts in node records are 64 bit, which makes the sentinel block longer as well
+const WIDE_SENTINEL_BLOCK_LENGTH: usize = std::mem::size_of::<u64>() * 3 + 1;
+pub(crate) const FIRST_WIDE_VERSION: u32 = 7500;
+// Real files nest around 10 levels deep
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 256;
+
+#[derive(Debug, PartialEq)]
+pub enum NodeEvent {
+    BeginNode { name: Arc<str>, num_properties: usize },
+    Property(PropertyRecordType),
+    EndNode,
+    // The node last begun turned out to be malformed and was skipped outside of strict mode. No EndNode follows.
+    SkippedNode,
+}
+
+struct OpenNode {
+    start_offset: u64,
+    name: Arc<str>,
+    // How many siblings with the same name came before this node
+    index: usize,
+    // Children begun so far per name, for the index of the next one
+    child_counts: Vec<(Arc<str>, usize)>,
+    end_offset: u64,
+    property_end_offset: u64,
+    remaining_properties: usize,
+    properties_verified: bool,
+}
+
+// Node record whose header is being read, so errors while doing so know which bytes to skip
+struct PendingNode {
+    start_offset: u64,
+    // Set once the end offset has been read and found to be within the file
+    end_offset: Option<u64>,
+}
+
+/* Counts another node called name and returns how many came before it. Names are interned, so comparing pointers is
+   enough. */
+fn count_name(counts: &mut Vec<(Arc<str>, usize)>, name: &Arc<str>) -> usize {
+    match counts.iter_mut().find(|(counted, _)| Arc::ptr_eq(counted, name)) {
+        Some((_, count)) => {
+            *count += 1;
+            *count - 1
+        }
+        None => {
+            counts.push((name.clone(), 1));
+            0
+        }
+    }
+}
+
+/* Pull parser over the node records of a file, positioned right after the header. Every node is reported as a
+   BeginNode, followed by its properties, its children and finally an EndNode. Nodes that are of no interest can be
+   skipped with skip_current_subtree without reading any of their contents. */
+pub struct NodeReader<R> {
+    reader: R,
+    file_length: u64,
+    // Whether node records use 64 bit offsets and counts
+    wide: bool,
+    open_nodes: Vec<OpenNode>,
+    max_depth: usize,
+    names: NameInterner,
+    // Reused for every name read, so names seen before cost no allocation
+    name_buffer: Vec<u8>,
+    // Top level nodes begun so far per name
+    top_level_counts: Vec<(Arc<str>, usize)>,
+    // Off to let small inconsistencies through with a diagnostic, where the rest of the record still makes sense
+    strict: bool,
+    pending: Option<PendingNode>,
+    // Furthest offset skipped to after an error. Skipping only ever moves forward, so recovering always ends.
+    recovered_to: u64,
+    finished: bool,
+}
+
+impl<R: Read + Seek> NodeReader<R> {
+    pub fn new(mut reader: R) -> ParseResult<Self> {
+        let position = reader.stream_position()?;
+        let file_length = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(position))?;
+
+        Ok(NodeReader {
+            reader,
+            file_length,
+            wide: false,
+            open_nodes: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            names: NameInterner::new(),
+            name_buffer: Vec::new(),
+            top_level_counts: Vec::new(),
+            strict: true,
+            pending: None,
+            recovered_to: 0,
+            finished: false,
+        })
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /* Version from the file header, which decides the size of the offsets in node records */
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.wide = version >= FIRST_WIDE_VERSION;
+        self
+    }
+
+    /* Bytes left in the file after the current position. Used to bound counts read from the file before allocating. */
+    pub fn remaining_bytes(&mut self) -> ParseResult<usize> {
+        let remaining = self.file_length.saturating_sub(self.position()?);
+        Ok(usize::try_from(remaining).unwrap_or(usize::MAX))
+    }
+
+    fn sentinel_block_length(&self) -> u64 {
+        if self.wide { WIDE_SENTINEL_BLOCK_LENGTH as u64 } else { SENTINEL_BLOCK_LENGTH as u64 }
+    }
+
+    fn read_offset(&mut self) -> ParseResult<u64> {
+        if self.wide {
+            Ok(self.reader.read_u64::<LittleEndian>()?)
+        } else {
+            Ok(self.reader.read_u32::<LittleEndian>()? as u64)
+        }
+    }
+
+    /* Skips whatever is left of the node last begun, including its children. No EndNode is reported for it. */
+    pub fn skip_current_subtree(&mut self) -> ParseResult<()> {
+        if let Some(error) = self.truncated_node(None) {
+            return Err(error);
+        }
+
+        match self.open_nodes.pop() {
+            Some(node) => {
+                self.reader.seek(SeekFrom::Start(node.end_offset))?;
+                Ok(())
+            }
+            None => Err(ParseError::ValidationError("no node to skip".to_string()))
+        }
+    }
+
+    fn position(&mut self) -> ParseResult<u64> {
+        Ok(self.reader.stream_position()?)
+    }
+
+    fn parse_name(&mut self, start_offset: u64) -> ParseResult<Arc<str>> {
+        let length = self.reader.read_u8()? as usize;
+        let name_end = self.position()? + length as u64;
+        self.require_header(name_end, start_offset)?;
+        self.name_buffer.resize(length, 0);
+        self.reader.read_exact(&mut self.name_buffer)?;
+
+        let name = std::str::from_utf8(&self.name_buffer)?;
+        Ok(self.names.intern(name))
+    }
+
+    /* Reads a node record header. Returns None for the null record terminating a list of nodes. */
+    fn begin_node(&mut self) -> ParseResult<Option<NodeEvent>> {
+        if !self.wide && self.file_length > u32::MAX as u64 {
+            return Err(ParseError::ValidationError("file is too large for the 32 bit offsets of its version".to_string()));
+        }
+
+        let start_offset = self.position()?;
+        self.pending = Some(PendingNode { start_offset, end_offset: None });
+
+        let offset_size = if self.wide { 8 } else { 4 };
+        self.require_header(start_offset + offset_size, start_offset)?;
+        let end_offset = self.read_offset()?;
+        if end_offset == 0 {
+            self.pending = None;
+            return Ok(None);
+        }
+
+        // Offsets only go up to 0xFFFFFFFE here, anything claiming the maximum has most likely overflowed
+        if !self.wide && end_offset == u32::MAX as u64 {
+            return Err(ParseError::ValidationError("end offset overflows the 32 bit offsets of its version".to_string()));
+        }
+
+        // Nodes can only run past the end of the file, or up to it, when their parent does as well. Then the file was
+        // cut off, and the node is read for as long as the file goes on so the error names the innermost node cut.
+        let parent_runs_past_end = self.open_nodes.last().is_none_or(|parent| parent.end_offset > self.file_length);
+        if end_offset >= self.file_length && !parent_runs_past_end {
+            return Err(ParseError::ValidationError("end offset is outside bounds".to_string()));
+        }
+        let runs_past_end = end_offset > self.file_length;
+
+        if !runs_past_end {
+            self.pending = Some(PendingNode { start_offset, end_offset: Some(end_offset) });
+        }
+
+        if self.open_nodes.len() >= self.max_depth {
+            return Err(ParseError::ValidationError("max node depth exceeded".to_string()));
+        }
+
+        self.require_header(start_offset + 3 * offset_size + 1, start_offset)?;
+        let num_properties = self.read_offset()?;
+        let property_length_bytes = self.read_offset()?;
+        let name = self.parse_name(start_offset)?;
+
+        let property_start_offset = self.position()?;
+        let property_end_offset = match property_start_offset.checked_add(property_length_bytes) {
+            Some(offset) if offset <= self.file_length || runs_past_end => offset,
+            _ => return Err(ParseError::ValidationError("property length out of bounds".to_string()))
+        };
+        // Can only be too large for usize on 32 bit targets, where so many properties would not fit in memory anyway
+        let num_properties = usize::try_from(num_properties)
+            .map_err(|_| ParseError::ValidationError("too many properties".to_string()))?;
+
+        let counts = match self.open_nodes.last_mut() {
+            Some(parent) => &mut parent.child_counts,
+            None => &mut self.top_level_counts
+        };
+        let index = count_name(counts, &name);
+
+        self.pending = None;
+        self.open_nodes.push(OpenNode {
+            start_offset,
+            name: name.clone(),
+            index,
+            child_counts: Vec::new(),
+            end_offset,
+            property_end_offset,
+            remaining_properties: num_properties,
+            properties_verified: false,
+        });
+
+        Ok(Some(NodeEvent::BeginNode { name, num_properties }))
+    }
+
+    fn end_node(&mut self) -> ParseResult<NodeEvent> {
+        let end_offset = self.open_nodes.last().unwrap().end_offset;
+        if self.position()? != end_offset {
+            return Err(ParseError::ValidationError("end offset not reached.".to_string()));
+        }
+
+        self.open_nodes.pop();
+        Ok(NodeEvent::EndNode)
+    }
+
+    /* Path of the nodes currently open, like Objects/Geometry[12]/Vertices. Nodes after the first of their name
+       among their siblings get its index. */
+    fn path(&self) -> String {
+        let mut path = String::new();
+        for node in &self.open_nodes {
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(&node.name);
+            if node.index > 0 {
+                path.push_str(&format!("[{}]", node.index));
+            }
+        }
+        path
+    }
+
+    /* Truncated error blaming the innermost open node when it runs past the end of the file, None if it does not.
+       property is the type code of the property that was cut off, if any. */
+    fn truncated_node(&self, property: Option<u8>) -> Option<ParseError> {
+        let node = self.open_nodes.last().filter(|node| node.end_offset > self.file_length)?;
+        let context = match property {
+            Some(code) => format!("node '{}' with its '{}' property cut off claims to end", self.path(), code as char),
+            None => format!("node '{}' claims to end", self.path()),
+        };
+        Some(ParseError::Truncated { expected_at: node.end_offset, file_length: self.file_length, context })
+    }
+
+    /* Fails unless the file goes on up to expected_at, for the header of the node record starting at start_offset */
+    fn require_header(&self, expected_at: u64, start_offset: u64) -> ParseResult<()> {
+        if expected_at <= self.file_length {
+            return Ok(());
+        }
+
+        Err(self.truncated_node(None).unwrap_or_else(|| ParseError::Truncated {
+            expected_at,
+            file_length: self.file_length,
+            context: format!("the header of the node at byte {} ends", with_thousands_separators(start_offset)),
+        }))
+    }
+
+    /* Errors carry where in the file and in the node tree they were detected. Outside of strict mode a malformed
+       node is skipped using the end offsets, and reading goes on with whatever follows it. */
+    pub fn next_event(&mut self) -> ParseResult<Option<NodeEvent>> {
+        loop {
+            let error = match self.read_event() {
+                Ok(event) => return Ok(event),
+                // There is nothing after the end of the file to recover with
+                Err(error @ ParseError::Truncated { .. }) => return Err(error),
+                Err(ParseError::IOError(error)) if error.kind() == ErrorKind::UnexpectedEof =>
+                    return Err(self.truncated_node(None).unwrap_or_else(|| self.locate(ParseError::IOError(error)))),
+                Err(error) => self.locate(error)
+            };
+
+            if self.strict {
+                return Err(error);
+            }
+
+            if let Some(event) = self.recover(error)? {
+                return Ok(Some(event));
+            }
+        }
+    }
+
+    fn locate(&mut self, error: ParseError) -> ParseError {
+        match error {
+            ParseError::InNode { .. } => error,
+            error => ParseError::InNode {
+                offset: self.reader.stream_position().unwrap_or(self.file_length),
+                path: self.path(),
+                error: Box::new(error),
+            }
+        }
+    }
+
+    /* Skips the node the error happened in. A node whose header could not be read is skipped along with the rest of
+       its parent's children, unless its end offset could be read. Returns SkippedNode when an open node was dropped,
+       None when reading can simply go on, and the error when there is nothing to skip to. */
+    fn recover(&mut self, error: ParseError) -> ParseResult<Option<NodeEvent>> {
+        let parent_end_offset = |open_nodes: &[OpenNode]| open_nodes.last().map(|parent| parent.end_offset);
+
+        let (start_offset, end_offset, skipped_node) = match self.pending.take() {
+            Some(pending) => {
+                // Only the parent is open, a top level node without a readable header leaves nothing to go on
+                let parent_end = match parent_end_offset(&self.open_nodes) {
+                    Some(end) => end,
+                    None => return Err(error)
+                };
+                let end = pending.end_offset.filter(|end| *end > pending.start_offset && *end <= parent_end);
+                (pending.start_offset, end.unwrap_or(parent_end), false)
+            }
+            None => {
+                let node = match self.open_nodes.pop() {
+                    Some(node) => node,
+                    None => return Err(error)
+                };
+                let parent_end = parent_end_offset(&self.open_nodes).unwrap_or(self.file_length);
+                let end = if node.end_offset > node.start_offset && node.end_offset <= parent_end {
+                    node.end_offset
+                } else if !self.open_nodes.is_empty() {
+                    parent_end
+                } else {
+                    return Err(error);
+                };
+                (node.start_offset, end, true)
+            }
+        };
+
+        if end_offset <= self.recovered_to {
+            return Err(error);
+        }
+
+        warning!("Skipped malformed node at byte {:#X}, {}", start_offset, error);
+        self.recovered_to = end_offset;
+        self.reader.seek(SeekFrom::Start(end_offset))?;
+        Ok(if skipped_node { Some(NodeEvent::SkippedNode) } else { None })
+    }
+
+    fn read_event(&mut self) -> ParseResult<Option<NodeEvent>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        loop {
+            let position = self.position()?;
+            let (end_offset, property_end_offset, remaining_properties, properties_verified) = match self.open_nodes.last() {
+                Some(node) => (node.end_offset, node.property_end_offset, node.remaining_properties, node.properties_verified),
+                None => {
+                    if position >= self.file_length {
+                        self.finished = true;
+                        return Ok(None);
+                    }
+
+                    let event = self.begin_node()?;
+                    if event.is_none() {
+                        self.finished = true;
+                    }
+                    return Ok(event);
+                }
+            };
+
+            if remaining_properties > 0 {
+                // The properties of a node that was cut off are read as far as the file goes
+                let property = match parse_property(&mut self.reader, property_end_offset.min(self.file_length)) {
+                    Ok(property) => property,
+                    Err(_) if property_end_offset > self.file_length => {
+                        self.reader.seek(SeekFrom::Start(position))?;
+                        let code = self.reader.read_u8().ok();
+                        return Err(self.truncated_node(code).unwrap());
+                    }
+                    Err(error) => return Err(error),
+                };
+                self.open_nodes.last_mut().unwrap().remaining_properties -= 1;
+                return Ok(Some(NodeEvent::Property(property)));
+            }
+
+            if !properties_verified {
+                self.open_nodes.last_mut().unwrap().properties_verified = true;
+                if position != property_end_offset {
+                    if self.strict {
+                        return Err(ParseError::ValidationError("did not read correct amount of bytes when parsing properties".to_string()));
+                    }
+
+                    // Every property was read, so the length is what is off. Skip to where it says the properties end,
+                    // unless that means going back over them.
+                    warning!("Properties of {} end at byte {:#X} instead of the declared {:#X}, continuing after them.",
+                              self.path(), position, property_end_offset);
+                    if property_end_offset > position {
+                        self.reader.seek(SeekFrom::Start(property_end_offset))?;
+                    }
+                    continue;
+                }
+            }
+
+            if position >= end_offset {
+                return Ok(Some(self.end_node()?));
+            }
+
+            let sentinel_block_length = self.sentinel_block_length();
+            if end_offset - position < sentinel_block_length {
+                return Err(ParseError::ValidationError("insufficient amount of bytes at end of node".to_string()));
+            }
+
+            if position == end_offset - sentinel_block_length {
+                let mut sentinel_block = [0u8; WIDE_SENTINEL_BLOCK_LENGTH];
+                let sentinel_block = &mut sentinel_block[..sentinel_block_length as usize];
+                self.reader.read_exact(sentinel_block)?;
+                if sentinel_block.iter().any(|b| *b != 0) {
+                    return Err(ParseError::ValidationError("sentinel block contains non-zero values".to_string()));
+                }
+                return Ok(Some(self.end_node()?));
+            }
+
+            // Null records between children are ignored, there can be any number of them
+            if let Some(event) = self.begin_node()? {
+                return Ok(Some(event));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::header::parse_header;
+    use crate::fbx::property::FbxString;
+    use crate::fbx::arena_document::{ArenaDocument, NodeRef};
+    use crate::fbx::fixture::FbxFixture;
+    use byteorder::WriteBytesExt;
+    use std::io::Cursor;
+
+    /* Parses nodes as in a 7.4 file, the last version with 32 bit offsets, without having read a header */
+    fn parse_nodes<R: Read + Seek>(reader: &mut R) -> ParseResult<ArenaDocument> {
+        ArenaDocument::parse(reader, 7400, &[], DEFAULT_MAX_DEPTH, true)
+    }
+
+    struct CountingReader<R> {
+        inner: R,
+        bytes_read: usize,
+    }
+
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let count = self.inner.read(buf)?;
+            self.bytes_read += count;
+            Ok(count)
+        }
+    }
+
+    impl<R: Seek> Seek for CountingReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    /* Writes a node record, patching in the end offset once the children have been written */
+    fn write_node(out: &mut Vec<u8>, name: &str, num_properties: u32, properties: &[u8], children: &dyn Fn(&mut Vec<u8>)) {
+        let start = out.len();
+        out.write_u32::<LittleEndian>(0).unwrap();
+        out.write_u32::<LittleEndian>(num_properties).unwrap();
+        out.write_u32::<LittleEndian>(properties.len() as u32).unwrap();
+        out.write_u8(name.len() as u8).unwrap();
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(properties);
+
+        let children_start = out.len();
+        children(out);
+        if out.len() != children_start {
+            out.extend_from_slice(&[0u8; SENTINEL_BLOCK_LENGTH]);
+        }
+
+        let end = out.len() as u32;
+        out[start..start + 4].copy_from_slice(&end.to_le_bytes());
+    }
+
+    fn string_property(value: &str) -> Vec<u8> {
+        let mut bytes = vec![b'S'];
+        bytes.write_u32::<LittleEndian>(value.len() as u32).unwrap();
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    fn create_fixture() -> Vec<u8> {
+        let mut out = b"Kaydara FBX Binary  \0".to_vec();
+        out.extend_from_slice(&[0x1a, 0]);
+        out.write_u32::<LittleEndian>(7400).unwrap();
+
+        write_node(&mut out, "FBXHeaderExtension", 0, &[], &|out| {
+            let mut version = vec![b'I'];
+            version.write_i32::<LittleEndian>(1003).unwrap();
+            write_node(out, "FBXHeaderVersion", 1, &version, &|_| {});
+        });
+        write_node(&mut out, "Objects", 0, &[], &|out| {
+            for _ in 0..100 {
+                let mut array = vec![b'd'];
+                array.write_u32::<LittleEndian>(1000).unwrap();
+                array.write_u32::<LittleEndian>(0).unwrap();
+                array.write_u32::<LittleEndian>(0).unwrap();
+                for i in 0..1000 {
+                    array.write_f64::<LittleEndian>(i as f64).unwrap();
+                }
+                write_node(out, "Vertices", 1, &array, &|_| {});
+            }
+        });
+        write_node(&mut out, "Creator", 1, &string_property("fbximport test"), &|_| {});
+        out.extend_from_slice(&[0u8; SENTINEL_BLOCK_LENGTH]);
+        out
+    }
+
+    #[test]
+    fn skip_current_subtree_should_avoid_reading_skipped_nodes() {
+        // Arrange
+        let fixture = create_fixture();
+        let total_bytes = fixture.len();
+        let mut reader = CountingReader { inner: Cursor::new(fixture), bytes_read: 0 };
+
+        // Act
+        let header = parse_header(&mut reader, false).unwrap();
+        let mut node_reader = NodeReader::new(&mut reader).unwrap();
+        let mut creator = None;
+        while let Some(event) = node_reader.next_event().unwrap() {
+            match event {
+                NodeEvent::BeginNode { name, .. } if &*name == "Creator" => {
+                    if let Some(NodeEvent::Property(PropertyRecordType::String(value))) = node_reader.next_event().unwrap() {
+                        creator = Some(value);
+                    }
+                    node_reader.skip_current_subtree().unwrap();
+                }
+                NodeEvent::BeginNode { .. } => node_reader.skip_current_subtree().unwrap(),
+                _ => {}
+            }
+        }
+
+        // Assert
+        assert_eq!(header.version, 7400);
+        assert_eq!(creator, Some(FbxString::new("fbximport test")));
+        assert!(reader.bytes_read * 100 < total_bytes, "read {} of {} bytes", reader.bytes_read, total_bytes);
+    }
+
+    #[test]
+    fn next_event_should_report_nodes_in_order() {
+        // Arrange
+        let mut out = Vec::new();
+        write_node(&mut out, "Parent", 1, &string_property("a"), &|out| {
+            write_node(out, "Child", 0, &[], &|_| {});
+        });
+        out.extend_from_slice(&[0u8; SENTINEL_BLOCK_LENGTH]);
+        let mut node_reader = NodeReader::new(Cursor::new(out)).unwrap();
+
+        // Act
+        let mut events = Vec::new();
+        while let Some(event) = node_reader.next_event().unwrap() {
+            events.push(event);
+        }
+
+        // Assert
+        assert_eq!(events, vec![
+            NodeEvent::BeginNode { name: "Parent".into(), num_properties: 1 },
+            NodeEvent::Property(PropertyRecordType::String(FbxString::new("a"))),
+            NodeEvent::BeginNode { name: "Child".into(), num_properties: 0 },
+            NodeEvent::EndNode,
+            NodeEvent::EndNode,
+        ]);
+    }
+
+    #[test]
+    fn parse_nodes_should_build_tree_from_events() {
+        // Arrange
+        let mut reader = Cursor::new(create_fixture());
+        parse_header(&mut reader, false).unwrap();
+
+        // Act
+        let nodes = parse_nodes(&mut reader).unwrap();
+
+        // Assert
+        assert!(nodes.get("Creator").is_ok());
+        assert_eq!(nodes.get("Objects").ok().unwrap().children().count("Vertices"), 100);
+        let header_extension = nodes.get("FBXHeaderExtension").ok().unwrap();
+        assert_eq!(header_extension.children().get("FBXHeaderVersion").ok().unwrap().properties()[0], PropertyRecordType::SignedInt32(1003));
+    }
+
+    #[test]
+    fn parse_nodes_should_share_names_between_nodes() {
+        // Arrange
+        let mut reader = Cursor::new(create_fixture());
+        parse_header(&mut reader, false).unwrap();
+
+        // Act
+        let mut node_reader = NodeReader::new(&mut reader).unwrap().with_version(7400);
+        let mut vertices = Vec::new();
+        while let Some(event) = node_reader.next_event().unwrap() {
+            match event {
+                NodeEvent::BeginNode { name, .. } if &*name == "Vertices" => vertices.push(name),
+                _ => {}
+            }
+        }
+
+        // Assert
+        assert_eq!(vertices.len(), 100);
+        assert!(vertices.iter().all(|name| Arc::ptr_eq(name, &vertices[0])));
+    }
+
+    #[test]
+    fn parse_nodes_skipping_should_not_read_skipped_top_level_nodes() {
+        // Arrange
+        let mut out = Vec::new();
+        let mut thumbnail = vec![b'R'];
+        thumbnail.write_u32::<LittleEndian>(5 * 1024 * 1024).unwrap();
+        thumbnail.resize(thumbnail.len() + 5 * 1024 * 1024, 0xff);
+        write_node(&mut out, "FBXHeaderExtension", 0, &[], &|out| {
+            write_node(out, "Thumbnail", 1, &thumbnail, &|_| {});
+        });
+        write_node(&mut out, "Objects", 0, &[], &|out| {
+            write_node(out, "Geometry", 1, &string_property("mesh"), &|_| {});
+        });
+        out.extend_from_slice(&[0u8; SENTINEL_BLOCK_LENGTH]);
+        let total_bytes = out.len();
+        let mut skipping_reader = CountingReader { inner: Cursor::new(out.clone()), bytes_read: 0 };
+        let mut full_reader = Cursor::new(out);
+
+        // Act
+        let skipped = ArenaDocument::parse(&mut skipping_reader, 7400, &["FBXHeaderExtension"], DEFAULT_MAX_DEPTH, true).unwrap();
+        let full = parse_nodes(&mut full_reader).unwrap();
+
+        // Assert
+        assert!(skipped.get("FBXHeaderExtension").is_err());
+        assert!(skipped.get("Objects").is_ok());
+        assert!(skipping_reader.bytes_read < 1024, "read {} of {} bytes", skipping_reader.bytes_read, total_bytes);
+        assert!(full.get("FBXHeaderExtension").is_ok());
+    }
+
+    /* Nodes without properties, each one the only child of the previous */
+    fn create_nested_nodes(depth: usize) -> Vec<u8> {
+        let header_length = 13 + 1;
+        let mut out = Vec::new();
+        for level in 0..depth {
+            // Every level ends after the headers and sentinel blocks of all deeper levels, plus its own sentinel block.
+            // The deepest node has no children and so no sentinel block.
+            let deeper = depth - level - 1;
+            let children_length = deeper * header_length + deeper.saturating_sub(1) * SENTINEL_BLOCK_LENGTH;
+            let sentinel_length = if deeper > 0 { SENTINEL_BLOCK_LENGTH } else { 0 };
+            let end = out.len() + header_length + children_length + sentinel_length;
+            out.write_u32::<LittleEndian>(end as u32).unwrap();
+            out.write_u32::<LittleEndian>(0).unwrap();
+            out.write_u32::<LittleEndian>(0).unwrap();
+            out.write_u8(1).unwrap();
+            out.push(b'N');
+        }
+        for _ in 1..depth {
+            out.extend_from_slice(&[0u8; SENTINEL_BLOCK_LENGTH]);
+        }
+        out.extend_from_slice(&[0u8; SENTINEL_BLOCK_LENGTH]);
+        out
+    }
+
+    #[test]
+    fn parse_nodes_should_accept_nesting_below_max_depth() {
+        // Arrange
+        let mut reader = Cursor::new(create_nested_nodes(20));
+
+        // Act
+        let result = parse_nodes(&mut reader);
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn parse_nodes_should_fail_cleanly_on_deeply_nested_nodes() {
+        // Arrange
+        let mut reader = Cursor::new(create_nested_nodes(10000));
+
+        // Act
+        let result = parse_nodes(&mut reader);
+
+        // Assert
+        match result.map_err(ParseError::into_cause) {
+            Err(ParseError::ValidationError(message)) => assert_eq!(message, "max node depth exceeded"),
+            _ => panic!("Expected a validation error")
+        }
+    }
+
+    #[test]
+    fn parse_nodes_should_skip_a_large_body_of_null_records() {
+        // Arrange
+        let mut out = Vec::new();
+        write_node(&mut out, "Objects", 0, &[], &|out| out.resize(out.len() + 4 * 1024 * 1024, 0));
+        out.extend_from_slice(&[0u8; SENTINEL_BLOCK_LENGTH]);
+
+        // Act
+        let result = parse_nodes(&mut Cursor::new(out));
+
+        // Assert
+        let nodes = result.unwrap();
+        assert_eq!(nodes.nodes().len(), 1);
+        assert!(nodes.get("Objects").ok().unwrap().children().is_empty());
+    }
+
+    #[test]
+    fn parse_nodes_should_reject_property_length_larger_than_file() {
+        // Arrange
+        let mut string = vec![b'S'];
+        string.write_u32::<LittleEndian>(u32::MAX).unwrap();
+        let mut out = Vec::new();
+        write_node(&mut out, "Creator", 1, &string, &|_| {});
+        out.extend_from_slice(&[0u8; SENTINEL_BLOCK_LENGTH]);
+
+        // Act
+        let result = parse_nodes(&mut Cursor::new(out));
+
+        // Assert
+        assert!(matches!(result.map_err(ParseError::into_cause), Err(ParseError::ValidationError(_))));
+    }
+
+    /* Reports a stream of length bytes holding head at the start and tail at tail_offset, with zeros everywhere else,
+       so large files can be simulated without keeping them in memory */
+    struct SparseReader {
+        head: Vec<u8>,
+        tail: Vec<u8>,
+        tail_offset: u64,
+        length: u64,
+        position: u64,
+    }
+
+    impl Read for SparseReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let (segment, segment_start, segment_end) = if self.position < self.head.len() as u64 {
+                (Some(&self.head), 0, self.head.len() as u64)
+            } else if self.position >= self.tail_offset && self.position < self.tail_offset + self.tail.len() as u64 {
+                (Some(&self.tail), self.tail_offset, self.tail_offset + self.tail.len() as u64)
+            } else if self.position < self.tail_offset {
+                (None, self.position, self.tail_offset)
+            } else {
+                (None, self.position, self.length)
+            };
+
+            let count = (segment_end.saturating_sub(self.position) as usize).min(buf.len());
+            match segment {
+                Some(bytes) => {
+                    let start = (self.position - segment_start) as usize;
+                    buf[..count].copy_from_slice(&bytes[start..start + count]);
+                }
+                None => buf[..count].iter_mut().for_each(|b| *b = 0),
+            }
+            self.position += count as u64;
+            Ok(count)
+        }
+    }
+
+    impl Seek for SparseReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.position = match pos {
+                SeekFrom::Start(offset) => offset,
+                SeekFrom::End(offset) => (self.length as i64 + offset) as u64,
+                SeekFrom::Current(offset) => (self.position as i64 + offset) as u64,
+            };
+            Ok(self.position)
+        }
+    }
+
+    /* Node record with 64 bit offsets and no properties */
+    fn write_wide_node(out: &mut Vec<u8>, name: &str, end_offset: u64) {
+        out.write_u64::<LittleEndian>(end_offset).unwrap();
+        out.write_u64::<LittleEndian>(0).unwrap();
+        out.write_u64::<LittleEndian>(0).unwrap();
+        out.write_u8(name.len() as u8).unwrap();
+        out.extend_from_slice(name.as_bytes());
+    }
+
+    #[test]
+    fn parse_nodes_should_read_wide_offsets_beyond_4_gb() {
+        // Arrange
+        let tail_offset = 5 * 1024 * 1024 * 1024u64;
+        let header_length = 3 * 8 + 1u64;
+        // Objects with a single Geometry child, followed by the null record ending the top level
+        let geometry_end = tail_offset + (header_length + 7) + (header_length + 8);
+        let objects_end = geometry_end + WIDE_SENTINEL_BLOCK_LENGTH as u64;
+        let mut tail = Vec::new();
+        write_wide_node(&mut tail, "Objects", objects_end);
+        write_wide_node(&mut tail, "Geometry", geometry_end);
+        tail.extend_from_slice(&[0u8; WIDE_SENTINEL_BLOCK_LENGTH]);
+        tail.extend_from_slice(&[0u8; WIDE_SENTINEL_BLOCK_LENGTH]);
+
+        // A node spanning everything up to the tail, which is skipped rather than read
+        let mut head = Vec::new();
+        write_wide_node(&mut head, "Takes", tail_offset);
+        let length = tail_offset + tail.len() as u64;
+        let mut reader = SparseReader { head, tail, tail_offset, length, position: 0 };
+
+        // Act
+        let nodes = ArenaDocument::parse(&mut reader, 7500, &["Takes"], DEFAULT_MAX_DEPTH, true).unwrap();
+
+        // Assert
+        assert!(nodes.get("Takes").is_err());
+        assert!(nodes.get("Objects").ok().unwrap().children().get("Geometry").is_ok());
+        assert!(reader.position > objects_end);
+    }
+
+    #[test]
+    fn parse_nodes_should_reject_files_too_large_for_32_bit_offsets() {
+        // Arrange
+        let mut head = Vec::new();
+        write_node(&mut head, "Objects", 0, &[], &|_| {});
+        let mut reader = SparseReader { head, tail: Vec::new(), tail_offset: 0, length: 5 * 1024 * 1024 * 1024, position: 0 };
+
+        // Act
+        let result = ArenaDocument::parse(&mut reader, 7400, &[], DEFAULT_MAX_DEPTH, true);
+
+        // Assert
+        match result.map_err(ParseError::into_cause) {
+            Err(ParseError::ValidationError(message)) => assert_eq!(message, "file is too large for the 32 bit offsets of its version"),
+            _ => panic!("Expected a validation error")
+        }
+    }
+
+    #[test]
+    fn parse_nodes_should_reject_maximum_32_bit_end_offset() {
+        // Arrange
+        let mut head = Vec::new();
+        head.write_u32::<LittleEndian>(u32::MAX).unwrap();
+        head.extend_from_slice(&[0u8; 9]);
+        let mut reader = SparseReader { head, tail: Vec::new(), tail_offset: 0, length: u32::MAX as u64, position: 0 };
+
+        // Act
+        let result = ArenaDocument::parse(&mut reader, 7400, &[], DEFAULT_MAX_DEPTH, true);
+
+        // Assert
+        match result.map_err(ParseError::into_cause) {
+            Err(ParseError::ValidationError(message)) => assert_eq!(message, "end offset overflows the 32 bit offsets of its version"),
+            _ => panic!("Expected a validation error")
+        }
+    }
+
+    #[test]
+    fn parse_nodes_should_report_offset_and_path_of_corrupt_property() {
+        // Arrange
+        let mut fixture = FbxFixture::new(7400).node("Objects", |o| (0..3).fold(o, |o, id| o
+            .node_with_props("Geometry", vec![(id as i64).into()], |g| g
+                .double_array("Vertices", vec![0.0; 9]))))
+            .bytes();
+        let second_vertices = fixture.windows(8).enumerate()
+            .filter(|(_, window)| *window == b"Vertices")
+            .nth(1).unwrap().0;
+        // Encoding of the array, after the name, the type code and the length
+        let encoding = second_vertices + 8 + 1 + 4;
+        fixture[encoding] = 5;
+        let mut reader = Cursor::new(fixture);
+        let header = parse_header(&mut reader, false).unwrap();
+
+        // Act
+        let result = ArenaDocument::parse(&mut reader, header.version, &[], DEFAULT_MAX_DEPTH, true);
+
+        // Assert
+        let expected_offset = (encoding + 8) as u64;
+        match result {
+            Err(ParseError::InNode { offset, path, error }) => {
+                assert_eq!(offset, expected_offset);
+                assert_eq!(path, "Objects/Geometry[1]/Vertices");
+                assert!(matches!(*error, ParseError::ValidationError(_)));
+            }
+            _ => panic!("Expected an error with its location")
+        }
+    }
+
+    #[test]
+    fn parse_error_should_display_offset_and_path() {
+        // Arrange
+        let error = ParseError::InNode {
+            offset: 0x1A2B3C,
+            path: "Objects/Geometry[12]/Vertices".to_string(),
+            error: Box::new(ParseError::ValidationError("property length out of bounds".to_string())),
+        };
+
+        // Act
+        let message = error.to_string();
+
+        // Assert
+        assert_eq!(message, "at byte 0x1A2B3C in Objects/Geometry[12]/Vertices: property length out of bounds");
+    }
+
+    /* Creator node declaring 2 more bytes of properties than its one property takes, followed by an Objects node */
+    fn create_nodes_with_padded_properties() -> Vec<u8> {
+        let mut properties = string_property("exporter");
+        properties.extend_from_slice(&[0, 0]);
+        let mut out = Vec::new();
+        write_node(&mut out, "Creator", 1, &properties, &|_| {});
+        write_node(&mut out, "Objects", 0, &[], &|_| {});
+        out.extend_from_slice(&[0u8; SENTINEL_BLOCK_LENGTH]);
+        out
+    }
+
+    #[test]
+    fn parse_nodes_should_trust_declared_property_length_in_lenient_mode() {
+        // Arrange
+        let mut reader = Cursor::new(create_nodes_with_padded_properties());
+
+        // Act
+        let nodes = ArenaDocument::parse(&mut reader, 7400, &[], DEFAULT_MAX_DEPTH, false).unwrap();
+
+        // Assert
+        assert_eq!(nodes.nodes().len(), 2);
+        let creator = nodes.get("Creator").ok().unwrap();
+        assert_eq!(creator.properties().len(), 1);
+        assert_eq!(creator.properties()[0].as_string().unwrap().name(), "exporter");
+        assert!(nodes.get("Objects").is_ok());
+    }
+
+    #[test]
+    fn parse_nodes_should_reject_wrong_property_length_in_strict_mode() {
+        // Arrange
+        let mut reader = Cursor::new(create_nodes_with_padded_properties());
+
+        // Act
+        let result = ArenaDocument::parse(&mut reader, 7400, &[], DEFAULT_MAX_DEPTH, true);
+
+        // Assert
+        match result.map_err(ParseError::into_cause) {
+            Err(ParseError::ValidationError(message)) => assert_eq!(message, "did not read correct amount of bytes when parsing properties"),
+            _ => panic!("Expected a validation error")
+        }
+    }
+
+    #[test]
+    fn parse_nodes_should_skip_rest_of_parent_when_child_header_is_unreadable_in_lenient_mode() {
+        // Arrange
+        // Second child claims to end past the end of the file
+        let mut out = Vec::new();
+        write_node(&mut out, "Objects", 0, &[], &|out| {
+            write_node(out, "Model", 1, &string_property("first"), &|_| {});
+            let broken = out.len();
+            write_node(out, "Model", 1, &string_property("second"), &|_| {});
+            out[broken..broken + 4].copy_from_slice(&u32::MAX.wrapping_sub(1).to_le_bytes());
+            write_node(out, "Model", 1, &string_property("third"), &|_| {});
+        });
+        write_node(&mut out, "Connections", 0, &[], &|_| {});
+        out.extend_from_slice(&[0u8; SENTINEL_BLOCK_LENGTH]);
+
+        // Act
+        let lenient = ArenaDocument::parse(&mut Cursor::new(out.clone()), 7400, &[], DEFAULT_MAX_DEPTH, false).unwrap();
+        let strict = ArenaDocument::parse(&mut Cursor::new(out), 7400, &[], DEFAULT_MAX_DEPTH, true);
+
+        // Assert
+        let objects = lenient.get("Objects").ok().unwrap();
+        let models: Vec<NodeRef> = objects.children().get_multiple("Model").collect();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].properties()[0].as_string().unwrap().name(), "first");
+        assert!(lenient.get("Connections").is_ok());
+        assert!(strict.is_err());
+    }
+
+    #[test]
+    fn parse_nodes_should_drop_child_with_corrupt_properties_in_lenient_mode() {
+        // Arrange
+        let mut out = Vec::new();
+        write_node(&mut out, "Objects", 0, &[], &|out| {
+            write_node(out, "Model", 1, &string_property("first"), &|_| {});
+            write_node(out, "Model", 1, &[b'X', 0, 0, 0, 0], &|_| {});
+            write_node(out, "Model", 1, &string_property("third"), &|_| {});
+        });
+        out.extend_from_slice(&[0u8; SENTINEL_BLOCK_LENGTH]);
+
+        // Act
+        let nodes = ArenaDocument::parse(&mut Cursor::new(out), 7400, &[], DEFAULT_MAX_DEPTH, false).unwrap();
+
+        // Assert
+        let models: Vec<NodeRef> = nodes.get("Objects").ok().unwrap().children().get_multiple("Model").collect();
+        let names: Vec<&str> = models.iter().map(|m| m.properties()[0].as_string().unwrap().name()).collect();
+        assert_eq!(names, vec!["first", "third"]);
+    }
+
+    /* Three Geometry nodes with 1000 vertices each, along with where every Geometry and Vertices node starts */
+    fn create_file_to_truncate() -> (Vec<u8>, Vec<usize>, Vec<usize>) {
+        let fixture = FbxFixture::new(7400).node("Objects", |o| (0..3).fold(o, |o, id| o
+            .node_with_props("Geometry", vec![(id as i64).into()], |g| g
+                .double_array("Vertices", vec![0.0; 3000]))))
+            .bytes();
+        // Names follow the three offsets and the name length
+        let starts = |name: &[u8]| fixture.windows(name.len()).enumerate()
+            .filter(|(_, window)| *window == name)
+            .map(|(i, _)| i - 13)
+            .collect::<Vec<_>>();
+        let (geometries, vertices) = (starts(b"Geometry"), starts(b"Vertices"));
+        (fixture, geometries, vertices)
+    }
+
+    fn parse_truncated(bytes: &[u8], length: usize, skipped: &[&str], strict: bool) -> ParseResult<ArenaDocument> {
+        let mut reader = Cursor::new(&bytes[..length]);
+        let header = parse_header(&mut reader, strict)?;
+        ArenaDocument::parse(&mut reader, header.version, skipped, DEFAULT_MAX_DEPTH, strict)
+    }
+
+    fn end_offset(bytes: &[u8], start: usize) -> u32 {
+        u32::from_le_bytes([bytes[start], bytes[start + 1], bytes[start + 2], bytes[start + 3]])
+    }
+
+    #[test]
+    fn parse_nodes_should_report_a_file_truncated_between_nodes() {
+        // Arrange
+        let (bytes, geometries, _) = create_file_to_truncate();
+        let objects_end = end_offset(&bytes, 27);
+
+        for strict in [true, false].iter() {
+            // Act
+            let result = parse_truncated(&bytes, geometries[2], &[], *strict);
+
+            // Assert
+            match result {
+                Err(ParseError::Truncated { expected_at, file_length, context }) => {
+                    assert_eq!(expected_at, objects_end as u64);
+                    assert_eq!(file_length, geometries[2] as u64);
+                    assert_eq!(context, "node 'Objects' claims to end");
+                }
+                other => panic!("expected a truncated error, got {:?}", other.err()),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_nodes_should_name_the_node_and_property_cut_off_in_the_middle() {
+        // Arrange
+        let (bytes, _, vertices) = create_file_to_truncate();
+        let length = vertices[1] + 13 + 8 + 100;
+        let vertices_end = end_offset(&bytes, vertices[1]);
+
+        // Act
+        let error = parse_truncated(&bytes, length, &[], false).err().unwrap();
+
+        // Assert
+        assert_eq!(error.to_string(), format!(
+            "file appears truncated: node 'Objects/Geometry[1]/Vertices' with its 'd' property cut off claims to end at byte {} but the file is {} bytes",
+            with_thousands_separators(vertices_end as u64), with_thousands_separators(length as u64)));
+    }
+
+    #[test]
+    fn parse_nodes_should_report_a_truncated_node_that_is_skipped() {
+        // Arrange
+        let (bytes, geometries, _) = create_file_to_truncate();
+
+        // Act
+        let result = parse_truncated(&bytes, geometries[1] + 5, &["Objects"], false);
+
+        // Assert
+        match result {
+            Err(ParseError::Truncated { context, .. }) => assert_eq!(context, "node 'Objects' claims to end"),
+            other => panic!("expected a truncated error, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn parse_nodes_should_report_a_top_level_header_cut_off() {
+        // Arrange
+        let (bytes, _, _) = create_file_to_truncate();
+        let objects_end = end_offset(&bytes, 27) as usize;
+        let mut bytes = bytes[..objects_end].to_vec();
+        // Start of another top level node, cut off after its end offset
+        bytes.extend_from_slice(&[0xff, 0xff, 0, 0, 1, 0]);
+        let length = bytes.len();
+
+        // Act
+        let error = parse_truncated(&bytes, length, &[], true).err().unwrap();
+
+        // Assert
+        match error {
+            ParseError::Truncated { expected_at, file_length, context } => {
+                assert_eq!(expected_at, (objects_end + 13) as u64);
+                assert_eq!(file_length, length as u64);
+                assert_eq!(context, format!("the header of the node at byte {} ends", with_thousands_separators(objects_end as u64)));
+            }
+            other => panic!("expected a truncated error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn with_thousands_separators_should_group_digits_by_three() {
+        // Arrange
+        let values = [0, 999, 1000, 12_345_678, 100_000];
+
+        // Act
+        let formatted: Vec<String> = values.iter().map(|v| with_thousands_separators(*v)).collect();
+
+        // Assert
+        assert_eq!(formatted, vec!["0", "999", "1,000", "12,345,678", "100,000"]);
+    }
+}
@@ -0,0 +1,384 @@
+use crate::scene::mesh::Face;
+use std::convert::TryFrom;
+
+// Fewest indices a run can have and still describe a polygon
+const MIN_FACE_INDICES: usize = 3;
+
+/* The last index of every polygon in a PolygonVertexIndex array is stored as its bitwise negation, -(index + 1). These
+   are the only places that convention lives. */
+fn encode_last_index(index: i32) -> i32 {
+    index ^ -1
+}
+
+fn decode_index(index: i32) -> i32 {
+    if index < 0 { index ^ -1 } else { index }
+}
+
+/* Splits the first run off indices, up to and including the first negated index. The run is the rest of the array
+   when there is none, in which case the flag is false. */
+fn split_run(indices: &[i32]) -> (&[i32], &[i32], bool) {
+    match indices.iter().position(|i| *i < 0) {
+        Some(last) => {
+            let (run, rest) = indices.split_at(last + 1);
+            (run, rest, true)
+        }
+        None => (indices, &[], false),
+    }
+}
+
+fn encode_run<I: ExactSizeIterator<Item = i32>>(run: I, out: &mut Vec<i32>) {
+    let last = run.len().saturating_sub(1);
+    out.extend(run.enumerate().map(|(i, index)| if i == last { encode_last_index(index) } else { index }));
+}
+
+/* Splits a PolygonVertexIndex array into the vertex indices of its polygons. Unlike FaceIterator every run is kept,
+   also those shorter than a triangle and a final one that does not end with a negated index. */
+pub fn decode_polygon_vertex_indices(indices: &[i32]) -> Vec<Vec<u32>> {
+    let mut polygons = Vec::with_capacity(indices.iter().filter(|i| **i < 0).count() + 1);
+    let mut rest = indices;
+    while !rest.is_empty() {
+        let (run, next, _) = split_run(rest);
+        polygons.push(run.iter().map(|i| decode_index(*i) as u32).collect());
+        rest = next;
+    }
+
+    polygons
+}
+
+/* Inverse of decode_polygon_vertex_indices. Empty polygons can not be written and are left out.
+   Panics for indices above i32::MAX, which the format has no room for. */
+pub fn encode_polygon_vertex_indices(polygons: &[&[u32]]) -> Vec<i32> {
+    let mut indices = Vec::with_capacity(polygons.iter().map(|p| p.len()).sum());
+    for polygon in polygons {
+        let run = polygon.iter().map(|i| i32::try_from(*i).expect("vertex index does not fit in a PolygonVertexIndex array"));
+        encode_run(run, &mut indices);
+    }
+
+    indices
+}
+
+/* Splits a PolygonVertexIndex array into faces. The last index of every face is stored as its bitwise negation.
+   Runs too short to be a polygon are left out and counted, as is a final run missing its negated index. */
+pub struct FaceIterator<'a> {
+    indices: &'a [i32],
+    skipped_runs: usize,
+    missing_terminator: bool,
+    yield_short_runs: bool,
+}
+
+impl<'a> FaceIterator<'a> {
+    pub fn from(indices: &'a [i32]) -> Self {
+        FaceIterator {
+            indices,
+            skipped_runs: 0,
+            missing_terminator: false,
+            yield_short_runs: false,
+        }
+    }
+
+    /* Return runs with fewer than three indices as faces instead of leaving them out, so every face keeps the
+       position it has in the file */
+    pub(crate) fn with_short_runs(mut self, yield_short_runs: bool) -> Self {
+        self.yield_short_runs = yield_short_runs;
+        self
+    }
+
+    /* Number of runs with fewer than three indices left out so far */
+    pub fn skipped_runs(&self) -> usize {
+        self.skipped_runs
+    }
+
+    /* True once the iterator has reached a final run that was not ended by a negated index */
+    pub fn missing_terminator(&self) -> bool {
+        self.missing_terminator
+    }
+}
+
+impl<'a> Iterator for FaceIterator<'a> {
+    type Item = Face;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.indices.is_empty() {
+            let (face, rest, terminated) = split_run(self.indices);
+            self.indices = rest;
+            self.missing_terminator |= !terminated;
+
+            if face.len() < MIN_FACE_INDICES && !self.yield_short_runs {
+                self.skipped_runs += 1;
+                continue;
+            }
+
+            let indices = face.iter().map(|index| decode_index(*index)).collect();
+
+            return Some(Face::new_unchecked(indices));
+        }
+
+        None
+    }
+}
+
+/* Inverse of FaceIterator, writes the faces as a PolygonVertexIndex array with the last index of each face negated */
+pub fn encode_faces(faces: &[Face]) -> Vec<i32> {
+    let mut indices = Vec::with_capacity(faces.iter().map(|f| f.indices.len()).sum());
+    for face in faces {
+        encode_run(face.indices.iter().copied(), &mut indices);
+    }
+
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(indices: &[i32]) -> Vec<Vec<i32>> {
+        FaceIterator::from(indices).map(|f| f.indices).collect()
+    }
+
+    #[test]
+    fn face_iterator_should_split_on_negative_indices() {
+        // Arrange
+        let indices = vec![0, 1, -3, 2, 3, 4, -6];
+
+        // Act
+        let faces = decode(&indices);
+
+        // Assert
+        assert_eq!(faces, vec![vec![0, 1, 2], vec![2, 3, 4, 5]]);
+    }
+
+    #[test]
+    fn face_iterator_should_return_nothing_for_empty_buffer() {
+        // Arrange
+        let mut iterator = FaceIterator::from(&[]);
+
+        // Act
+        let face = iterator.next();
+
+        // Assert
+        assert!(face.is_none());
+        assert_eq!(iterator.skipped_runs(), 0);
+        assert!(!iterator.missing_terminator());
+    }
+
+    #[test]
+    fn face_iterator_should_not_add_a_face_after_the_final_terminator() {
+        // Arrange
+        let indices = vec![0, 1, 2, -4];
+        let mut iterator = FaceIterator::from(&indices);
+
+        // Act
+        let faces: Vec<Vec<i32>> = iterator.by_ref().map(|f| f.indices).collect();
+
+        // Assert
+        assert_eq!(faces, vec![vec![0, 1, 2, 3]]);
+        assert!(!iterator.missing_terminator());
+    }
+
+    #[test]
+    fn face_iterator_should_keep_unterminated_trailing_polygon() {
+        // Arrange
+        let indices = vec![0, 1, -3, 3, 4, 5];
+        let mut iterator = FaceIterator::from(&indices);
+
+        // Act
+        let faces: Vec<Vec<i32>> = iterator.by_ref().map(|f| f.indices).collect();
+
+        // Assert
+        assert_eq!(faces, vec![vec![0, 1, 2], vec![3, 4, 5]]);
+        assert!(iterator.missing_terminator());
+        assert_eq!(iterator.skipped_runs(), 0);
+    }
+
+    #[test]
+    fn face_iterator_should_skip_unterminated_trailing_run_shorter_than_a_triangle() {
+        // Arrange
+        let indices = vec![0, 1, -3, 3, 4];
+        let mut iterator = FaceIterator::from(&indices);
+
+        // Act
+        let faces: Vec<Vec<i32>> = iterator.by_ref().map(|f| f.indices).collect();
+
+        // Assert
+        assert_eq!(faces, vec![vec![0, 1, 2]]);
+        assert!(iterator.missing_terminator());
+        assert_eq!(iterator.skipped_runs(), 1);
+    }
+
+    #[test]
+    fn face_iterator_should_reject_single_negative_index() {
+        // Arrange
+        let indices = vec![-1];
+        let mut iterator = FaceIterator::from(&indices);
+
+        // Act
+        let face = iterator.next();
+
+        // Assert
+        assert!(face.is_none());
+        assert_eq!(iterator.skipped_runs(), 1);
+        assert!(!iterator.missing_terminator());
+    }
+
+    #[test]
+    fn face_iterator_should_skip_short_runs_between_faces() {
+        // Arrange
+        let indices = vec![-1, 0, 1, -3, 4, -6, 6, 7, -9];
+        let mut iterator = FaceIterator::from(&indices);
+
+        // Act
+        let faces: Vec<Vec<i32>> = iterator.by_ref().map(|f| f.indices).collect();
+
+        // Assert
+        assert_eq!(faces, vec![vec![0, 1, 2], vec![6, 7, 8]]);
+        assert_eq!(iterator.skipped_runs(), 2);
+    }
+
+    #[test]
+    fn face_iterator_should_return_short_runs_when_asked() {
+        // Arrange
+        let indices = vec![-1, 0, 1, -3, 4, -6];
+        let mut iterator = FaceIterator::from(&indices).with_short_runs(true);
+
+        // Act
+        let faces: Vec<Vec<i32>> = iterator.by_ref().map(|f| f.indices).collect();
+
+        // Assert
+        assert_eq!(faces, vec![vec![0], vec![0, 1, 2], vec![4, 5]]);
+        assert_eq!(iterator.skipped_runs(), 0);
+    }
+
+    #[test]
+    fn face_iterator_should_decode_index_zero_as_terminator() {
+        // Arrange
+        // The last index, 0, stored as 0 ^ -1
+        let indices = vec![2, 1, -1];
+
+        // Act
+        let faces = decode(&indices);
+
+        // Assert
+        assert_eq!(faces, vec![vec![2, 1, 0]]);
+    }
+
+    #[test]
+    fn encode_faces_should_negate_last_index_of_each_face() {
+        // Arrange
+        let faces = vec![Face::new(vec![0, 1, 2]), Face::new(vec![2, 3, 4, 0])];
+
+        // Act
+        let indices = encode_faces(&faces);
+
+        // Assert
+        assert_eq!(indices, vec![0, 1, -3, 2, 3, 4, -1]);
+    }
+
+    #[test]
+    fn encode_faces_should_round_trip_through_face_iterator() {
+        // Arrange
+        let indices = vec![0, 1, -3, 2, 3, 4, -6, 7, 8, 9, 10, -12];
+
+        // Act
+        let faces: Vec<Face> = FaceIterator::from(&indices).collect();
+        let encoded = encode_faces(&faces);
+
+        // Assert
+        assert_eq!(encoded, indices);
+    }
+
+    #[test]
+    fn decode_polygon_vertex_indices_should_return_nothing_for_empty_input() {
+        // Arrange
+        let indices: Vec<i32> = Vec::new();
+
+        // Act
+        let polygons = decode_polygon_vertex_indices(&indices);
+
+        // Assert
+        assert!(polygons.is_empty());
+        assert!(encode_polygon_vertex_indices(&[]).is_empty());
+    }
+
+    #[test]
+    fn decode_polygon_vertex_indices_should_decode_a_single_polygon() {
+        // Arrange
+        let indices = vec![3, 0, 1, -3];
+
+        // Act
+        let polygons = decode_polygon_vertex_indices(&indices);
+
+        // Assert
+        assert_eq!(polygons, vec![vec![3, 0, 1, 2]]);
+    }
+
+    #[test]
+    fn decode_polygon_vertex_indices_should_keep_a_trailing_run_without_terminator() {
+        // Arrange
+        let indices = vec![0, 1, -3, 4, 5];
+
+        // Act
+        let polygons = decode_polygon_vertex_indices(&indices);
+
+        // Assert
+        assert_eq!(polygons, vec![vec![0, 1, 2], vec![4, 5]]);
+    }
+
+    #[test]
+    fn decode_polygon_vertex_indices_should_keep_runs_shorter_than_a_triangle() {
+        // Arrange
+        let indices = vec![-1, 0, 1, -3, 4, -6];
+
+        // Act
+        let polygons = decode_polygon_vertex_indices(&indices);
+
+        // Assert
+        assert_eq!(polygons, vec![vec![0], vec![0, 1, 2], vec![4, 5]]);
+    }
+
+    #[test]
+    fn encode_polygon_vertex_indices_should_negate_the_last_index_and_skip_empty_polygons() {
+        // Arrange
+        let polygons: Vec<&[u32]> = vec![&[0, 1, 2], &[], &[0], &[2, 3, 4, 0]];
+
+        // Act
+        let indices = encode_polygon_vertex_indices(&polygons);
+
+        // Assert
+        assert_eq!(indices, vec![0, 1, -3, -1, 2, 3, 4, -1]);
+    }
+
+    #[test]
+    fn polygon_vertex_indices_should_round_trip() {
+        // Arrange
+        let polygons: Vec<Vec<u32>> = vec![vec![0, 1, 2], vec![2, 3, 4, 5], vec![7], vec![8, 9], vec![i32::MAX as u32, 0, 1]];
+
+        // Act
+        let encoded = encode_polygon_vertex_indices(&polygons.iter().map(|p| p.as_slice()).collect::<Vec<_>>());
+        let decoded = decode_polygon_vertex_indices(&encoded);
+
+        // Assert
+        assert_eq!(decoded, polygons);
+        assert_eq!(encode_polygon_vertex_indices(&decoded.iter().map(|p| p.as_slice()).collect::<Vec<_>>()), encoded);
+    }
+
+    #[test]
+    fn polygon_vertex_indices_should_agree_with_face_iterator() {
+        // Arrange
+        let indices = vec![0, 1, -3, 2, 3, 4, -6, 7, 8, 9, 10, -12];
+
+        // Act
+        let polygons = decode_polygon_vertex_indices(&indices);
+        let faces = decode(&indices);
+
+        // Assert
+        let faces: Vec<Vec<u32>> = faces.iter().map(|f| f.iter().map(|i| *i as u32).collect()).collect();
+        assert_eq!(polygons, faces);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn encode_polygon_vertex_indices_should_panic_on_indices_the_format_can_not_store() {
+        encode_polygon_vertex_indices(&[&[0, 1, i32::MAX as u32 + 1]]);
+    }
+}
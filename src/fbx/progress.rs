@@ -0,0 +1,15 @@
+/* Phase an import has reached, reported as it starts */
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProgressEvent {
+    // Reading the header and node records, file_size is the size of the input as stored
+    Parsing { file_size: u64 },
+    // Turning the nodes into the scene
+    Converting,
+    // Running the mesh processors on the meshes of the scene
+    Processing { meshes: usize },
+    Finished,
+}
+
+/* Receives the progress of an import and returns whether it should go on. Called between the phases only, never from
+   within the parsing loops. */
+pub(crate) type Progress<'a> = &'a mut dyn FnMut(ProgressEvent) -> bool;
@@ -0,0 +1,78 @@
+use crate::fbx::arena_document::{NodeList, NodeRef};
+use crate::fbx::property::PropertyRecordType;
+
+/* The Properties70 child of an object. Every entry is a P node holding the name, type, label and flags of the
+   property followed by its value:
+
+   P: "DiffuseColor", "Color", "", "A", 0.8, 0.8, 0.8
+
+   Missing entries mean the property has its default value, so the getters return None for the caller to fill in. */
+pub(crate) struct Properties70<'a> {
+    entries: Option<NodeList<'a>>,
+}
+
+// Name, type, label and flags come before the value
+const VALUE_OFFSET: usize = 4;
+
+impl<'a> Properties70<'a> {
+    pub(crate) fn from(object: NodeRef<'a>) -> Self {
+        Properties70 {
+            entries: object.children().get_first("Properties70").ok().map(|p| p.children()),
+        }
+    }
+
+    /* Values of the named entry, without the name, type, label and flags */
+    pub(crate) fn get(&self, name: &str) -> Option<&'a [PropertyRecordType]> {
+        let entry = self.entries?.get_multiple("P")
+            .find(|p| p.properties().first().and_then(|n| n.as_string()).map(|n| n.name()) == Some(name))?;
+        entry.properties().get(VALUE_OFFSET..)
+    }
+
+    pub(crate) fn number(&self, name: &str) -> Option<f64> {
+        self.get(name)?.first()?.as_f64()
+    }
+
+    /* Integers, enums and KTime values */
+    pub(crate) fn integer(&self, name: &str) -> Option<i64> {
+        self.get(name)?.first()?.as_i64()
+    }
+
+    pub(crate) fn vec3(&self, name: &str) -> Option<glm::Vec3> {
+        match self.get(name)? {
+            [x, y, z, ..] => Some(glm::vec3(x.as_f64()? as f32, y.as_f64()? as f32, z.as_f64()? as f32)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn string(&self, name: &str) -> Option<&'a str> {
+        Some(self.get(name)?.first()?.as_string()?.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::fixture::FbxFixture;
+
+    #[test]
+    fn get_should_read_values_after_the_entry_header() {
+        // Arrange
+        let nodes = FbxFixture::new(7400)
+            .node("Material", |m| m
+                .node("Properties70", |p| p
+                    .node_with_props("P", vec!["DiffuseColor".into(), "Color".into(), "".into(), "A".into(), 0.5.into(), 0.25.into(), 1.0.into()], |n| n)
+                    .node_with_props("P", vec!["Shininess".into(), "double".into(), "Number".into(), "".into(), 20i32.into()], |n| n)
+                    .node_with_props("P", vec!["ShadingModel".into(), "KString".into(), "".into(), "".into(), "phong".into()], |n| n)))
+            .parse();
+        let material = nodes.get("Material").ok().unwrap();
+
+        // Act
+        let properties = Properties70::from(material);
+
+        // Assert
+        assert_eq!(properties.vec3("DiffuseColor"), Some(glm::vec3(0.5, 0.25, 1.0)));
+        assert_eq!(properties.number("Shininess"), Some(20.0));
+        assert_eq!(properties.string("ShadingModel"), Some("phong"));
+        assert_eq!(properties.number("Missing"), None);
+    }
+}
\ No newline at end of file
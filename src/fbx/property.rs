@@ -1,6 +1,296 @@
-use crate::fbx::ParseResult;
-use std::io::{Read, Cursor, Seek};
-use byteorder::{LittleEndian, ReadBytesExt};
+use crate::warnings::warning;
+use crate::fbx::{ParseResult, ParseError};
+use std::io::{Read, Seek};
+use std::sync::OnceLock;
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
+
+pub trait ArrayElement: Sized {
+    // Size of one element in the file, which is not necessarily the size of the Rust type
+    const SIZE: usize;
+    const TYPE_CODE: u8;
+
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+    fn write_le_bytes(&self, out: &mut Vec<u8>);
+
+    // Values that decode fine but are worth reporting, to learn which exporters write them
+    fn is_unusual(_bytes: &[u8]) -> bool { false }
+}
+
+impl ArrayElement for f32 {
+    const SIZE: usize = 4;
+    const TYPE_CODE: u8 = b'f';
+    fn from_le_bytes(bytes: &[u8]) -> Self { LittleEndian::read_f32(bytes) }
+    fn write_le_bytes(&self, out: &mut Vec<u8>) { out.extend_from_slice(&self.to_le_bytes()) }
+}
+
+impl ArrayElement for f64 {
+    const SIZE: usize = 8;
+    const TYPE_CODE: u8 = b'd';
+    fn from_le_bytes(bytes: &[u8]) -> Self { LittleEndian::read_f64(bytes) }
+    fn write_le_bytes(&self, out: &mut Vec<u8>) { out.extend_from_slice(&self.to_le_bytes()) }
+}
+
+impl ArrayElement for i64 {
+    const SIZE: usize = 8;
+    const TYPE_CODE: u8 = b'l';
+    fn from_le_bytes(bytes: &[u8]) -> Self { LittleEndian::read_i64(bytes) }
+    fn write_le_bytes(&self, out: &mut Vec<u8>) { out.extend_from_slice(&self.to_le_bytes()) }
+}
+
+impl ArrayElement for i32 {
+    const SIZE: usize = 4;
+    const TYPE_CODE: u8 = b'i';
+    fn from_le_bytes(bytes: &[u8]) -> Self { LittleEndian::read_i32(bytes) }
+    fn write_le_bytes(&self, out: &mut Vec<u8>) { out.extend_from_slice(&self.to_le_bytes()) }
+}
+
+impl ArrayElement for bool {
+    const SIZE: usize = 1;
+    const TYPE_CODE: u8 = b'b';
+    fn from_le_bytes(bytes: &[u8]) -> Self { bytes[0] != 0 }
+    fn write_le_bytes(&self, out: &mut Vec<u8>) { out.push(*self as u8) }
+    fn is_unusual(bytes: &[u8]) -> bool { is_unusual_bool(bytes[0]) }
+}
+
+/* Booleans are 0 or 1 in most files, but the SDK writes 'Y' and 'T' in some versions and other exporters write any
+   nonzero value. All of those are true. */
+fn is_unusual_bool(byte: u8) -> bool {
+    !matches!(byte, 0 | 1 | b'Y' | b'T')
+}
+
+// Deflate cannot compress better than this, which bounds how large an inflated array can be
+const MAX_DEFLATE_RATIO: usize = 1032;
+
+/* Array property kept as the bytes from the file. Inflating and converting only happens the first time the values
+   are asked for, after which they are cached. Most arrays in a file are never looked at by the importer. */
+pub struct ArrayProperty<T> {
+    length: usize,
+    encoding: u32,
+    bytes: Vec<u8>,
+    // Position of the property in the file, only used for error messages
+    offset: u64,
+    values: OnceLock<Vec<T>>,
+}
+
+impl<T: ArrayElement> ArrayProperty<T> {
+    fn from_bytes(length: usize, encoding: u32, bytes: Vec<u8>, offset: u64) -> Self {
+        ArrayProperty {
+            length,
+            encoding,
+            bytes,
+            offset,
+            values: OnceLock::new(),
+        }
+    }
+
+    pub fn from_values(values: Vec<T>) -> Self {
+        ArrayProperty {
+            length: values.len(),
+            encoding: 0,
+            bytes: Vec::new(),
+            offset: 0,
+            values: OnceLock::from(values),
+        }
+    }
+
+    /* Reports the first unusual element in bytes, returns whether there was one */
+    fn report_unusual(&self, bytes: &[u8]) -> bool {
+        match bytes.chunks_exact(T::SIZE).find(|element| T::is_unusual(element)) {
+            Some(element) => {
+                warning!("'{}' array at offset {} contains unusual value {:02x?}", T::TYPE_CODE as char, self.offset, element);
+                true
+            }
+            None => false
+        }
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError::ValidationError(format!("{} for '{}' array at offset {}", message, T::TYPE_CODE as char, self.offset))
+    }
+
+    /* The declared length comes from the file, so it is not trusted further than the compressed data can reach */
+    fn inflated_capacity(&self) -> usize {
+        self.length.min(self.bytes.len().saturating_mul(MAX_DEFLATE_RATIO) / T::SIZE)
+    }
+
+    /* Inflates straight into the typed values, without holding on to the whole inflated byte stream */
+    fn inflate_values(&self) -> ParseResult<Vec<T>> {
+        let mut values = Vec::with_capacity(self.inflated_capacity());
+        // Elements can be split between two chunks of inflated output
+        let mut partial = Vec::with_capacity(T::SIZE);
+        let mut too_long = false;
+        let mut reported = false;
+
+        inflate_array_bytes(&self.bytes, &mut |mut chunk| {
+            // Stop as soon as the stream inflates past the declared length, rather than inflating all of it
+            if (values.len() * T::SIZE + partial.len() + chunk.len()) > self.length * T::SIZE {
+                too_long = true;
+                return false;
+            }
+            // Elements split between chunks are not checked, this is only a diagnostic
+            reported = reported || self.report_unusual(chunk);
+
+            if !partial.is_empty() {
+                let missing = (T::SIZE - partial.len()).min(chunk.len());
+                partial.extend_from_slice(&chunk[..missing]);
+                chunk = &chunk[missing..];
+                if partial.len() == T::SIZE {
+                    values.push(T::from_le_bytes(&partial));
+                    partial.clear();
+                }
+            }
+
+            let mut elements = chunk.chunks_exact(T::SIZE);
+            for element in &mut elements {
+                values.push(T::from_le_bytes(element));
+            }
+            partial.extend_from_slice(elements.remainder());
+            true
+        }).map_err(|_| self.error("could not inflate data"))?;
+
+        if too_long || values.len() != self.length || !partial.is_empty() {
+            return Err(self.error("inflated size does not match declared length"));
+        }
+
+        Ok(values)
+    }
+
+    /* Same as values, with the reason the stored data could not be decoded */
+    pub(super) fn try_values(&self) -> ParseResult<&[T]> {
+        if let Some(values) = self.values.get() {
+            return Ok(values);
+        }
+
+        let values = if self.encoding == 0 {
+            // Straight from the stored bytes, no copy or cursor in between
+            self.report_unusual(&self.bytes);
+            self.bytes.chunks_exact(T::SIZE).map(T::from_le_bytes).collect()
+        } else {
+            self.inflate_values()?
+        };
+        let _ = self.values.set(values);
+        Ok(self.values.get().unwrap())
+    }
+
+    /* The values of the array, decoded on the first call. None if the stored data does not inflate to the declared
+       length, see try_values for why. */
+    pub fn values(&self) -> Option<&[T]> {
+        self.try_values().ok()
+    }
+
+    pub fn is_decoded(&self) -> bool {
+        self.values.get().is_some()
+    }
+
+    /* Size of the values in bytes, if the array was stored compressed and has been inflated */
+    pub(crate) fn inflated_size(&self) -> Option<usize> {
+        if self.encoding == 0 {
+            return None;
+        }
+        self.values.get().map(|values| values.len() * T::SIZE)
+    }
+}
+
+impl<T: ArrayElement + PartialEq> PartialEq for ArrayProperty<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.values(), other.values()) {
+            (Some(a), Some(b)) => a == b,
+            _ => false
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ArrayProperty<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArrayProperty")
+            .field("encoding", &self.encoding)
+            .field("bytes", &self.bytes.len())
+            .field("decoded", &self.values.get().is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    static INFLATE_CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/* Feeds the inflated output of a zlib stream to sink chunk by chunk, until the stream ends or sink returns false.
+   Corrupt streams are reported as errors. */
+fn inflate_array_bytes(data: &[u8], sink: &mut dyn FnMut(&[u8]) -> bool) -> ParseResult<()> {
+    #[cfg(test)]
+    INFLATE_CALLS.with(|c| c.set(c.get() + 1));
+
+    let mut stream = inflate::InflateStream::from_zlib();
+    let mut position = 0;
+    loop {
+        let (consumed, output) = stream.update(&data[position..])
+            .map_err(|_| ParseError::FormatError)?;
+        if output.is_empty() {
+            break;
+        }
+        position += consumed;
+        if !sink(output) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/* Separates the name of an object from its class in string properties, e.g. "Cube\x00\x01Geometry" */
+const CLASS_SEPARATOR: &[u8] = &[0x00, 0x01];
+
+/* String property value. Object names are stored together with their class ("Geometry", "Model"...), strings without
+   the separator have no class. */
+#[derive(Clone, Debug, PartialEq)]
+pub struct FbxString {
+    value: String,
+    class: Option<String>,
+}
+
+impl FbxString {
+    pub fn new(value: &str) -> Self {
+        FbxString {
+            value: value.to_string(),
+            class: None,
+        }
+    }
+
+    pub fn with_class(mut self, class: &str) -> Self {
+        self.class = Some(class.to_string());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.value
+    }
+
+    pub fn class(&self) -> Option<&str> {
+        self.class.as_deref()
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> ParseResult<Self> {
+        match bytes.windows(CLASS_SEPARATOR.len()).position(|w| w == CLASS_SEPARATOR) {
+            Some(separator) => Ok(FbxString {
+                value: String::from_utf8(bytes[..separator].to_vec())?,
+                class: Some(String::from_utf8(bytes[separator + CLASS_SEPARATOR.len()..].to_vec())?),
+            }),
+            None => Ok(FbxString {
+                value: String::from_utf8(bytes)?,
+                class: None,
+            }),
+        }
+    }
+
+    pub(crate) fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.value.as_bytes());
+        if let Some(class) = &self.class {
+            out.extend_from_slice(CLASS_SEPARATOR);
+            out.extend_from_slice(class.as_bytes());
+        }
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub enum PropertyRecordType {
@@ -10,15 +300,119 @@ pub enum PropertyRecordType {
     Float(f32),
     Double(f64),
     SignedInt64(i64),
-    FloatArray(Vec<f32>),
-    DoubleArray(Vec<f64>),
-    SignedInt64Array(Vec<i64>),
-    SignedInt32Array(Vec<i32>),
-    BooleanArray(Vec<bool>),
-    String(String),
+    FloatArray(ArrayProperty<f32>),
+    DoubleArray(ArrayProperty<f64>),
+    SignedInt64Array(ArrayProperty<i64>),
+    SignedInt32Array(ArrayProperty<i32>),
+    BooleanArray(ArrayProperty<bool>),
+    String(FbxString),
     BinaryData(Vec<u8>),
 }
 
+impl PropertyRecordType {
+    /* See ArrayProperty::inflated_size, None for properties that are not arrays */
+    pub(crate) fn inflated_size(&self) -> Option<usize> {
+        match self {
+            PropertyRecordType::FloatArray(array) => array.inflated_size(),
+            PropertyRecordType::DoubleArray(array) => array.inflated_size(),
+            PropertyRecordType::SignedInt64Array(array) => array.inflated_size(),
+            PropertyRecordType::SignedInt32Array(array) => array.inflated_size(),
+            PropertyRecordType::BooleanArray(array) => array.inflated_size(),
+            _ => None
+        }
+    }
+
+    /* Name of the type for error messages */
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            PropertyRecordType::SignedInt16(_) => "i16",
+            PropertyRecordType::Boolean(_) => "bool",
+            PropertyRecordType::SignedInt32(_) => "i32",
+            PropertyRecordType::Float(_) => "f32",
+            PropertyRecordType::Double(_) => "f64",
+            PropertyRecordType::SignedInt64(_) => "i64",
+            PropertyRecordType::FloatArray(_) => "f32 array",
+            PropertyRecordType::DoubleArray(_) => "f64 array",
+            PropertyRecordType::SignedInt64Array(_) => "i64 array",
+            PropertyRecordType::SignedInt32Array(_) => "i32 array",
+            PropertyRecordType::BooleanArray(_) => "bool array",
+            PropertyRecordType::String(_) => "string",
+            PropertyRecordType::BinaryData(_) => "binary data",
+        }
+    }
+
+    pub fn as_string(&self) -> Option<&FbxString> {
+        match self {
+            PropertyRecordType::String(s) => Some(s),
+            _ => None
+        }
+    }
+
+    /* Object ids are 64 bit, but some exporters write smaller integers */
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            PropertyRecordType::SignedInt64(v) => Some(*v),
+            PropertyRecordType::SignedInt32(v) => Some(*v as i64),
+            _ => None
+        }
+    }
+
+    /* Any numeric value, Properties70 entries are written as float, double or integer depending on the exporter */
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            PropertyRecordType::Double(v) => Some(*v),
+            PropertyRecordType::Float(v) => Some(*v as f64),
+            PropertyRecordType::SignedInt16(v) => Some(*v as f64),
+            PropertyRecordType::SignedInt32(v) => Some(*v as f64),
+            PropertyRecordType::SignedInt64(v) => Some(*v as f64),
+            _ => None
+        }
+    }
+
+    pub fn as_binary(&self) -> Option<&[u8]> {
+        match self {
+            PropertyRecordType::BinaryData(data) => Some(data),
+            _ => None
+        }
+    }
+
+    /* The accessors below decode the array on first use. Corrupt arrays are treated as missing. */
+    pub fn as_f32_array(&self) -> Option<&[f32]> {
+        match self {
+            PropertyRecordType::FloatArray(a) => a.values(),
+            _ => None
+        }
+    }
+
+    pub fn as_f64_array(&self) -> Option<&[f64]> {
+        match self {
+            PropertyRecordType::DoubleArray(a) => a.values(),
+            _ => None
+        }
+    }
+
+    pub fn as_i64_array(&self) -> Option<&[i64]> {
+        match self {
+            PropertyRecordType::SignedInt64Array(a) => a.values(),
+            _ => None
+        }
+    }
+
+    pub fn as_i32_array(&self) -> Option<&[i32]> {
+        match self {
+            PropertyRecordType::SignedInt32Array(a) => a.values(),
+            _ => None
+        }
+    }
+
+    pub fn as_bool_array(&self) -> Option<&[bool]> {
+        match self {
+            PropertyRecordType::BooleanArray(a) => a.values(),
+            _ => None
+        }
+    }
+}
+
 fn parse_i16_property(reader: &mut dyn Read) -> ParseResult<PropertyRecordType>
 {
     let value = reader.read_i16::<LittleEndian>()?;
@@ -49,10 +443,13 @@ fn parse_f64_property(reader: &mut dyn Read) -> ParseResult<PropertyRecordType>
     Ok(PropertyRecordType::Double(value))
 }
 
-fn parse_bool_property(reader: &mut dyn Read) -> ParseResult<PropertyRecordType>
+fn parse_bool_property(reader: &mut dyn Read, offset: u64) -> ParseResult<PropertyRecordType>
 {
     let value = reader.read_u8()?;
-    Ok(PropertyRecordType::Boolean(value == 1))
+    if is_unusual_bool(value) {
+        warning!("Boolean property at offset {} has unusual value {:#04x}", offset, value);
+    }
+    Ok(PropertyRecordType::Boolean(value != 0))
 }
 
 struct ArrayMetaData {
@@ -73,129 +470,111 @@ fn parse_array_metadata(reader: &mut dyn Read) -> ParseResult<ArrayMetaData> {
     })
 }
 
-fn get_property_raw_byte_cursor<T>(reader: &mut dyn Read) -> ParseResult<Cursor<Vec<u8>>> {
-    let metadata = parse_array_metadata(reader)?;
-    if metadata.encoding == 0 {
-        let byte_count = std::mem::size_of::<T>() * metadata.length as usize;
-        let mut array = vec![0u8; byte_count];
-        reader.read_exact(&mut array)?;
-        Ok(Cursor::new(array))
-    } else {
-        let mut deflated_data = vec![0u8; metadata.compressed_length as usize];
-        reader.read_exact(&mut deflated_data)?;
-        let inflated_data = inflate::inflate_bytes_zlib(&deflated_data).unwrap();
-        Ok(Cursor::new(inflated_data))
-    }
+/* Lengths read from the file are checked against what is left of the node properties before anything is allocated
+   for them, so a corrupt or hostile length fails right away instead of attempting a huge allocation */
+fn fits_in_properties<R: Seek>(reader: &mut R, length: u64, property_end_offset: u64) -> ParseResult<bool> {
+    Ok(length <= property_end_offset.saturating_sub(reader.stream_position()?))
 }
 
-fn apply_transform_on_byte_stream<T>(input: &mut Cursor<Vec<u8>>, transform: &dyn Fn(&mut Cursor<Vec<u8>>) -> ParseResult<T>) -> ParseResult<Vec<T>> {
-    let elements = input.stream_len()? as usize / std::mem::size_of::<T>();
-    let mut array = Vec::with_capacity(elements);
-    for _ in 0..elements {
-        array.push(transform(input)?);
+/* Reads the metadata and stored bytes of an array. offset is where the property starts, property_end_offset where
+   the properties of the enclosing node end, which the array data must not run past. */
+fn read_array_property<T: ArrayElement, R: Read + Seek>(reader: &mut R, offset: u64, property_end_offset: u64) -> ParseResult<ArrayProperty<T>> {
+    let error = |message: &str| ParseError::ValidationError(format!("{} for '{}' array at offset {}", message, T::TYPE_CODE as char, offset));
+
+    let metadata = parse_array_metadata(reader)?;
+    let byte_count = match metadata.encoding {
+        0 => metadata.length as u64 * T::SIZE as u64,
+        1 => metadata.compressed_length as u64,
+        _ => return Err(error(&format!("unknown encoding {}", metadata.encoding)))
+    };
+
+    if !fits_in_properties(reader, byte_count, property_end_offset)? {
+        return Err(error("data runs past the end of the node properties"));
     }
 
-    Ok(array)
+    let mut bytes = vec![0u8; byte_count as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(ArrayProperty::from_bytes(metadata.length as usize, metadata.encoding, bytes, offset))
 }
 
-fn parse_f32_array_property(reader: &mut dyn Read) -> ParseResult<PropertyRecordType>
+fn parse_f32_array_property<R: Read + Seek>(reader: &mut R, offset: u64, property_end_offset: u64) -> ParseResult<PropertyRecordType>
 {
-    let mut cursor = get_property_raw_byte_cursor::<f32>(reader)?;
-    let array = apply_transform_on_byte_stream(
-        &mut cursor,
-        &|x| Ok(x.read_f32::<LittleEndian>()?))?;
-
-    Ok(PropertyRecordType::FloatArray(array))
+    Ok(PropertyRecordType::FloatArray(read_array_property(reader, offset, property_end_offset)?))
 }
 
-fn parse_f64_array_property(reader: &mut dyn Read) -> ParseResult<PropertyRecordType>
+fn parse_f64_array_property<R: Read + Seek>(reader: &mut R, offset: u64, property_end_offset: u64) -> ParseResult<PropertyRecordType>
 {
-    let mut cursor = get_property_raw_byte_cursor::<f64>(reader)?;
-    let array = apply_transform_on_byte_stream(
-        &mut cursor,
-        &|x| Ok(x.read_f64::<LittleEndian>()?))?;
-
-    Ok(PropertyRecordType::DoubleArray(array))
+    Ok(PropertyRecordType::DoubleArray(read_array_property(reader, offset, property_end_offset)?))
 }
 
-fn parse_i64_array_property(reader: &mut dyn Read) -> ParseResult<PropertyRecordType>
+fn parse_i64_array_property<R: Read + Seek>(reader: &mut R, offset: u64, property_end_offset: u64) -> ParseResult<PropertyRecordType>
 {
-    let mut cursor = get_property_raw_byte_cursor::<i64>(reader)?;
-    let array = apply_transform_on_byte_stream(
-        &mut cursor,
-        &|x| Ok(x.read_i64::<LittleEndian>()?))?;
-
-    Ok(PropertyRecordType::SignedInt64Array(array))
+    Ok(PropertyRecordType::SignedInt64Array(read_array_property(reader, offset, property_end_offset)?))
 }
 
-fn parse_i32_array_property(reader: &mut dyn Read) -> ParseResult<PropertyRecordType>
+fn parse_i32_array_property<R: Read + Seek>(reader: &mut R, offset: u64, property_end_offset: u64) -> ParseResult<PropertyRecordType>
 {
-    let mut cursor = get_property_raw_byte_cursor::<i32>(reader)?;
-    let array = apply_transform_on_byte_stream(
-        &mut cursor,
-        &|x| Ok(x.read_i32::<LittleEndian>()?))?;
-
-    Ok(PropertyRecordType::SignedInt32Array(array))
+    Ok(PropertyRecordType::SignedInt32Array(read_array_property(reader, offset, property_end_offset)?))
 }
 
-fn parse_bool_array_property(reader: &mut dyn Read) -> ParseResult<PropertyRecordType>
+fn parse_bool_array_property<R: Read + Seek>(reader: &mut R, offset: u64, property_end_offset: u64) -> ParseResult<PropertyRecordType>
 {
-    let mut cursor = get_property_raw_byte_cursor::<bool>(reader)?;
-    let array = apply_transform_on_byte_stream(
-        &mut cursor,
-        &|x| Ok(x.read_u8()? == 1))?;
-
-    Ok(PropertyRecordType::BooleanArray(array))
+    Ok(PropertyRecordType::BooleanArray(read_array_property(reader, offset, property_end_offset)?))
 }
 
-fn parse_string_property(reader: &mut dyn Read) -> ParseResult<PropertyRecordType> {
-    let length = reader.read_u32::<LittleEndian>()? as usize;
-    let mut bytes = vec![0u8; length];
-    reader.read_exact(&mut bytes)?;
-
-    // For some reason, the names of objects consists of [actual name][bytes 0 and 1][object type].
-    // For now I will just parse everything up to the null byte, to avoid problems downstream.
-    let actual_string_length = bytes.iter().position(|x| *x == 0).unwrap_or(bytes.len());
+fn read_length_prefixed_bytes<R: Read + Seek>(reader: &mut R, offset: u64, property_end_offset: u64) -> ParseResult<Vec<u8>> {
+    let length = reader.read_u32::<LittleEndian>()? as u64;
+    if !fits_in_properties(reader, length, property_end_offset)? {
+        return Err(ParseError::ValidationError(format!("length {} of property at offset {} runs past the end of the node properties", length, offset)));
+    }
 
-    let null_terminated_data = bytes[0..actual_string_length].to_vec();
+    let mut bytes = vec![0u8; length as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
 
-    Ok(PropertyRecordType::String(String::from_utf8(null_terminated_data).unwrap()))
+fn parse_string_property<R: Read + Seek>(reader: &mut R, offset: u64, property_end_offset: u64) -> ParseResult<PropertyRecordType> {
+    let bytes = read_length_prefixed_bytes(reader, offset, property_end_offset)?;
+    Ok(PropertyRecordType::String(FbxString::from_bytes(bytes)?))
 }
 
-fn parse_binary_data_property(reader: &mut dyn Read) -> ParseResult<PropertyRecordType> {
-    let length = reader.read_u32::<LittleEndian>()? as usize;
-    let mut bytes = vec![0u8; length];
-    reader.read_exact(&mut bytes)?;
+fn parse_binary_data_property<R: Read + Seek>(reader: &mut R, offset: u64, property_end_offset: u64) -> ParseResult<PropertyRecordType> {
+    let bytes = read_length_prefixed_bytes(reader, offset, property_end_offset)?;
     Ok(PropertyRecordType::BinaryData(bytes))
 }
 
-fn parse_property(reader: &mut dyn Read) -> ParseResult<PropertyRecordType>
+/* Parses the property at the current position. property_end_offset is where the properties of the node end. */
+pub(super) fn parse_property<R: Read + Seek>(reader: &mut R, property_end_offset: u64) -> ParseResult<PropertyRecordType>
 {
+    let offset = reader.stream_position()?;
     let type_code = reader.read_u8()?;
 
     match type_code as char {
         'Y' => parse_i16_property(reader),
-        'C' => parse_bool_property(reader),
+        'C' => parse_bool_property(reader, offset),
         'I' => parse_i32_property(reader),
         'F' => parse_f32_property(reader),
         'D' => parse_f64_property(reader),
         'L' => parse_i64_property(reader),
-        'f' => parse_f32_array_property(reader),
-        'd' => parse_f64_array_property(reader),
-        'l' => parse_i64_array_property(reader),
-        'i' => parse_i32_array_property(reader),
-        'b' => parse_bool_array_property(reader),
-        'S' => parse_string_property(reader),
-        'R' => parse_binary_data_property(reader),
-        other => panic!("Unexpected type_code: {}", other)
+        'f' => parse_f32_array_property(reader, offset, property_end_offset),
+        'd' => parse_f64_array_property(reader, offset, property_end_offset),
+        'l' => parse_i64_array_property(reader, offset, property_end_offset),
+        'i' => parse_i32_array_property(reader, offset, property_end_offset),
+        'b' => parse_bool_array_property(reader, offset, property_end_offset),
+        'S' => parse_string_property(reader, offset, property_end_offset),
+        'R' => parse_binary_data_property(reader, offset, property_end_offset),
+        _ => Err(ParseError::UnknownPropertyType { code: type_code, offset })
     }
 }
 
-pub(super) fn parse_properties(reader: &mut dyn Read, num_properties: usize) -> ParseResult<Vec<PropertyRecordType>>
+/* Reads the property list of a node, which ends at property_end_offset */
+pub(super) fn parse_properties<R: Read + Seek>(reader: &mut R, num_properties: usize, property_end_offset: u64) -> ParseResult<Vec<PropertyRecordType>>
 {
-    let mut result = Vec::new();
+    // Every property takes at least two bytes
+    let remaining = property_end_offset.saturating_sub(reader.stream_position()?) as usize;
+    let mut result = Vec::with_capacity(num_properties.min(remaining / 2));
     for _ in 0..num_properties {
-        let property = parse_property(reader)?;
+        let property = parse_property(reader, property_end_offset)?;
         result.push(property);
     }
 
@@ -304,7 +683,7 @@ mod tests {
 
         let value = parse_f32_property(&mut input).unwrap();
 
-        assert_eq!(value, PropertyRecordType::Float(0.00000000000000877510717));
+        assert_eq!(value, PropertyRecordType::Float(8.775107e-15));
     }
 
     #[test]
@@ -331,7 +710,7 @@ mod tests {
 
         let value = parse_f32_property(&mut input).unwrap();
 
-        assert_eq!(value, PropertyRecordType::Float(0.00000000000000877510717));
+        assert_eq!(value, PropertyRecordType::Float(8.775107e-15));
     }
 
     #[test]
@@ -347,7 +726,7 @@ mod tests {
     fn parse_bool_property_should_read_1_byte() {
         let mut input = Cursor::new(vec![1u8, 0]);
 
-        parse_bool_property(&mut input).unwrap();
+        parse_bool_property(&mut input, 0).unwrap();
 
         assert_eq!(input.position(), 1);
     }
@@ -356,7 +735,7 @@ mod tests {
     fn parse_bool_property_should_return_true_if_byte_is_1() {
         let mut input = Cursor::new(vec![1u8]);
 
-        let value = parse_bool_property(&mut input).unwrap();
+        let value = parse_bool_property(&mut input, 0).unwrap();
 
         assert_eq!(value, PropertyRecordType::Boolean(true));
     }
@@ -365,7 +744,7 @@ mod tests {
     fn parse_bool_property_should_return_true_if_byte_is_0() {
         let mut input = Cursor::new(vec![0u8]);
 
-        let value = parse_bool_property(&mut input).unwrap();
+        let value = parse_bool_property(&mut input, 0).unwrap();
 
         assert_eq!(value, PropertyRecordType::Boolean(false));
     }
@@ -381,122 +760,416 @@ mod tests {
     }
 
     fn fill_array_metadata(data: &mut Vec<u8>, length: u32, encoding: u32, compressed_length: u32) {
-        data.write_u32::<LittleEndian>(length);
-        data.write_u32::<LittleEndian>(encoding);
-        data.write_u32::<LittleEndian>(compressed_length);
+        data.write_u32::<LittleEndian>(length).unwrap();
+        data.write_u32::<LittleEndian>(encoding).unwrap();
+        data.write_u32::<LittleEndian>(compressed_length).unwrap();
     }
 
     #[test]
-    fn get_property_raw_byte_cursor_should_handle_uncompressed_data() {
+    fn read_array_property_should_handle_uncompressed_data() {
         // Arrange
-        let payload = vec![1u8, 2u8, 3u8, 4u8, 3u8, 2u8, 1u8, 0u8];
         let mut data = Vec::new();
         fill_array_metadata(&mut data, 2, 0, 0);
-        data.append(&mut payload.clone());
+        data.extend_from_slice(&[1u8, 2u8, 3u8, 4u8, 3u8, 2u8, 1u8, 0u8]);
 
         // Act
-        let result = get_property_raw_byte_cursor::<i32>(&mut Cursor::new(data));
+        let result = read_array_property::<i32, _>(&mut Cursor::new(data), 0, u64::MAX);
 
         // Assert
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap().into_inner(), payload);
+        assert_eq!(result.unwrap().values().unwrap(), &[0x04030201, 0x00010203]);
     }
 
     #[test]
-    fn get_property_raw_byte_cursor_should_handle_compressed_data() {
+    fn read_array_property_should_handle_compressed_data() {
         // Arrange
         // these are signed 32-bit values 0 1 2 deflated.
         let payload = vec![120, 156, 99, 0, 2, 70, 32, 102, 2, 98, 0, 0, 28, 0, 4];
         let mut data = Vec::new();
-        fill_array_metadata(&mut data, 0, 1, payload.len() as u32);
+        fill_array_metadata(&mut data, 3, 1, payload.len() as u32);
         data.append(&mut payload.clone());
 
         // Act
-        let result = get_property_raw_byte_cursor::<i32>(&mut Cursor::new(data));
+        let result = read_array_property::<i32, _>(&mut Cursor::new(data), 0, u64::MAX);
 
         // Assert
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap().into_inner(), vec![0, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(result.unwrap().values().unwrap(), &[0, 1, 2]);
     }
 
     #[test]
-    fn get_property_raw_byte_cursor_should_return_error_if_not_enough_bytes() {
+    fn read_array_property_should_return_error_if_not_enough_bytes() {
         // Arrange
         let mut data = Vec::new();
         fill_array_metadata(&mut data, 1, 0, 0);
 
         // Act
-        let result = get_property_raw_byte_cursor::<i32>(&mut Cursor::new(data));
+        let result = read_array_property::<i32, _>(&mut Cursor::new(data), 0, u64::MAX);
 
         // Assert
         assert!(result.is_err());
     }
 
     #[test]
-    fn apply_transform_on_byte_stream_should_apply_transform() {
+    fn parse_i32_array_property_should_handle_uncompressed_data() {
         // Arrange
-        let data = vec![9, 0, 0, 0, 4, 0, 0, 0, 7, 1, 0, 0];
+        let mut data = Vec::new();
+        fill_array_metadata(&mut data, 5, 0, 0);
+        for i in 0..5 {
+            data.write_i32::<LittleEndian>(i).unwrap();
+        }
+        let mut input = Cursor::new(data);
 
         // Act
-        let result = apply_transform_on_byte_stream(
-            &mut Cursor::new(data),
-            &|x| Ok(x.read_i32::<LittleEndian>().unwrap() + 1));
+        let result = parse_i32_array_property(&mut input, 0, u64::MAX);
 
         // Assert
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), vec![10i32, 5, 264])
+        assert_eq!(result.unwrap(), PropertyRecordType::SignedInt32Array(ArrayProperty::from_values(vec![0, 1, 2, 3, 4])));
     }
 
     #[test]
-    fn apply_transform_on_byte_stream_should_handle_empty_input() {
+    fn parse_properties_should_not_inflate_arrays_until_accessed() {
         // Arrange
-        let data = Vec::<u8>::new();
+        let payload = deflate_bytes_zlib(&[0u8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0]);
+        let mut data = Vec::new();
+        for _ in 0..2 {
+            data.push(b'i');
+            fill_array_metadata(&mut data, 3, 1, payload.len() as u32);
+            data.extend_from_slice(&payload);
+        }
+        INFLATE_CALLS.with(|c| c.set(0));
 
         // Act
-        let result = apply_transform_on_byte_stream(
-            &mut Cursor::new(data),
-            &|x| Ok(x.read_i32::<LittleEndian>().unwrap() + 1));
+        let properties = parse_properties(&mut Cursor::new(&data), 2, data.len() as u64).unwrap();
+        let calls_after_parse = INFLATE_CALLS.with(|c| c.get());
+        let first = properties[0].as_i32_array().unwrap().to_vec();
+        let first_again = properties[0].as_i32_array().unwrap().to_vec();
 
         // Assert
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap().len(), 0);
+        assert_eq!(calls_after_parse, 0);
+        assert_eq!(first, vec![0, 1, 2]);
+        assert_eq!(first_again, first);
+        assert_eq!(INFLATE_CALLS.with(|c| c.get()), 1);
+        match &properties[1] {
+            PropertyRecordType::SignedInt32Array(a) => assert!(!a.is_decoded()),
+            _ => panic!("Expected an i32 array")
+        }
     }
 
     #[test]
-    fn apply_transform_on_byte_stream_should_handle_incomplete_stream() {
+    fn parse_i32_array_property_should_handle_compressed_data() {
         // Arrange
-        // data only contains enough bytes for ONE i32, leaving 3 bytes
-        let data = vec![9, 0, 0, 0, 8, 0, 0];
-        let mut cursor = Cursor::new(data);
+        let values: Vec<i32> = (0..10000).collect();
+        let mut bytes = Vec::new();
+        for value in &values {
+            bytes.write_i32::<LittleEndian>(*value).unwrap();
+        }
+        let payload = deflate_bytes_zlib(&bytes);
+        let mut data = Vec::new();
+        fill_array_metadata(&mut data, values.len() as u32, 1, payload.len() as u32);
+        data.extend_from_slice(&payload);
 
         // Act
-        let result = apply_transform_on_byte_stream(
-            &mut cursor,
-            &|x| Ok(x.read_i32::<LittleEndian>().unwrap()));
+        let result = parse_i32_array_property(&mut Cursor::new(data), 0, u64::MAX).unwrap();
 
         // Assert
-        assert!(result.is_ok());
-        let unwrapped_result = result.unwrap();
-        assert_eq!(unwrapped_result.len(), 1);
-        assert_eq!(unwrapped_result[0], 9);
-        assert_eq!(cursor.position(), 4);
+        assert_eq!(result.as_i32_array().unwrap(), &values[..]);
     }
 
     #[test]
-    fn parse_i32_array_property_should_handle_uncompressed_data() {
+    fn array_values_should_return_error_for_corrupt_deflate_stream() {
         // Arrange
-        let mut data = Vec::new();
-        fill_array_metadata(&mut data, 5, 0, 0);
-        for i in 0..5 {
-            data.write_i32::<LittleEndian>(i).unwrap();
+        let mut payload = deflate_bytes_zlib(&[1u8, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0]);
+        for byte in payload.iter_mut().skip(2) {
+            *byte ^= 0x5a;
         }
+        let mut data = Vec::new();
+        fill_array_metadata(&mut data, 3, 1, payload.len() as u32);
+        data.extend_from_slice(&payload);
+        let property = parse_i32_array_property(&mut Cursor::new(data), 0, u64::MAX).unwrap();
+
+        // Act
+        let result = match &property {
+            PropertyRecordType::SignedInt32Array(a) => a.try_values().map(|v| v.to_vec()),
+            _ => panic!("Expected an i32 array")
+        };
+
+        // Assert
+        assert!(result.is_err());
+        assert!(property.as_i32_array().is_none());
+    }
+
+    #[test]
+    fn parse_bool_array_property_should_read_one_byte_per_element() {
+        // Arrange
+        let mut data = Vec::new();
+        fill_array_metadata(&mut data, 4, 0, 0);
+        data.extend_from_slice(&[1, 0, 0, 1]);
+        data.push(0xaa);
         let mut input = Cursor::new(data);
 
         // Act
-        let result = parse_i32_array_property(&mut input);
+        let result = parse_bool_array_property(&mut input, 0, u64::MAX).unwrap();
 
         // Assert
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), PropertyRecordType::SignedInt32Array(vec![0, 1, 2, 3, 4]));
+        assert_eq!(input.position(), 16);
+        assert_eq!(result.as_bool_array().unwrap(), &[true, false, false, true]);
     }
-}
+
+    #[test]
+    fn parse_properties_should_not_read_past_the_end_of_the_property_list() {
+        // Arrange
+        let mut data = vec![b'i'];
+        fill_array_metadata(&mut data, 2, 0, 8);
+        data.extend_from_slice(&[1, 0, 0, 0, 2, 0, 0, 0]);
+        // The list ends halfway through the array, the rest of the stream belongs to the next node
+        let end = data.len() as u64 - 4;
+
+        // Act
+        let result = parse_properties(&mut Cursor::new(&data), 1, end);
+
+        // Assert
+        assert!(result.is_err());
+        assert!(parse_properties(&mut Cursor::new(&data), 1, data.len() as u64).is_ok());
+    }
+
+    #[test]
+    fn parse_properties_should_return_error_for_unknown_type_code() {
+        // Arrange
+        let mut data = vec![b'I'];
+        data.write_i32::<LittleEndian>(7).unwrap();
+        data.extend_from_slice(&[b'X', 1, 2, 3, 4]);
+
+        // Act
+        let result = parse_properties(&mut Cursor::new(&data), 2, data.len() as u64);
+
+        // Assert
+        match result {
+            Err(ParseError::UnknownPropertyType { code, offset }) => {
+                assert_eq!(code, b'X');
+                assert_eq!(offset, 5);
+            }
+            _ => panic!("Expected an unknown property type error")
+        }
+    }
+
+    fn string_property_bytes(value: &[u8]) -> Vec<u8> {
+        let mut data = vec![b'S'];
+        data.write_u32::<LittleEndian>(value.len() as u32).unwrap();
+        data.extend_from_slice(value);
+        data
+    }
+
+    #[test]
+    fn parse_property_should_split_name_and_class() {
+        // Arrange
+        let mut input = Cursor::new(string_property_bytes(b"Cube\x00\x01Geometry"));
+
+        // Act
+        let result = parse_property(&mut input, u64::MAX).unwrap();
+
+        // Assert
+        let string = result.as_string().unwrap();
+        assert_eq!(string.name(), "Cube");
+        assert_eq!(string.class(), Some("Geometry"));
+    }
+
+    #[test]
+    fn parse_property_should_not_set_class_for_plain_string() {
+        // Arrange
+        let mut input = Cursor::new(string_property_bytes(b"ByPolygon"));
+
+        // Act
+        let result = parse_property(&mut input, u64::MAX).unwrap();
+
+        // Assert
+        assert_eq!(result, PropertyRecordType::String(FbxString::new("ByPolygon")));
+        assert_eq!(result.as_string().unwrap().class(), None);
+    }
+
+    #[test]
+    fn parse_property_should_keep_isolated_null_byte() {
+        // Arrange
+        let mut input = Cursor::new(string_property_bytes(b"a\x00b"));
+
+        // Act
+        let result = parse_property(&mut input, u64::MAX).unwrap();
+
+        // Assert
+        let string = result.as_string().unwrap();
+        assert_eq!(string.name(), "a\0b");
+        assert_eq!(string.class(), None);
+    }
+
+    fn compressed_i32_array(length: u32, payload: &[u8]) -> Vec<u8> {
+        let mut data = vec![b'i'];
+        fill_array_metadata(&mut data, length, 1, payload.len() as u32);
+        data.extend_from_slice(payload);
+        data
+    }
+
+    fn assert_validation_error(result: ParseResult<Vec<i32>>, expected: &str) {
+        match result {
+            Err(ParseError::ValidationError(message)) => assert!(message.contains(expected), "unexpected message: {}", message),
+            _ => panic!("Expected a validation error")
+        }
+    }
+
+    #[test]
+    fn array_values_should_return_error_for_truncated_deflate_stream() {
+        // Arrange
+        let payload = deflate_bytes_zlib(&(0..64u8).collect::<Vec<u8>>());
+        let data = compressed_i32_array(16, &payload[..payload.len() / 2]);
+        let end = data.len() as u64;
+        let property = parse_property(&mut Cursor::new(data), end).unwrap();
+
+        // Act
+        let result = match &property {
+            PropertyRecordType::SignedInt32Array(a) => a.try_values().map(|v| v.to_vec()),
+            _ => panic!("Expected an i32 array")
+        };
+
+        // Assert
+        assert_validation_error(result, "at offset 0");
+    }
+
+    #[test]
+    fn array_values_should_return_error_when_inflated_size_does_not_match_length() {
+        // Arrange
+        let payload = deflate_bytes_zlib(&[0u8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0]);
+        // An i32 in front, so the array starts at offset 5
+        let mut data = vec![b'I', 0, 0, 0, 0];
+        data.extend(compressed_i32_array(4, &payload));
+        let properties = parse_properties(&mut Cursor::new(&data), 2, data.len() as u64).unwrap();
+
+        // Act
+        let result = match &properties[1] {
+            PropertyRecordType::SignedInt32Array(a) => a.try_values().map(|v| v.to_vec()),
+            _ => panic!("Expected an i32 array")
+        };
+
+        // Assert
+        assert_validation_error(result, "inflated size does not match declared length for 'i' array at offset 5");
+    }
+
+    #[test]
+    fn parse_property_should_return_error_for_unknown_encoding() {
+        // Arrange
+        let mut data = vec![b'd'];
+        fill_array_metadata(&mut data, 1, 7, 8);
+        data.extend_from_slice(&[0u8; 8]);
+        let end = data.len() as u64;
+
+        // Act
+        let result = parse_property(&mut Cursor::new(data), end);
+
+        // Assert
+        match result {
+            Err(ParseError::ValidationError(message)) => assert_eq!(message, "unknown encoding 7 for 'd' array at offset 0"),
+            _ => panic!("Expected a validation error")
+        }
+    }
+
+    #[test]
+    fn parse_property_should_return_error_when_compressed_length_exceeds_properties() {
+        // Arrange
+        let payload = deflate_bytes_zlib(&[0u8; 12]);
+        let mut data = compressed_i32_array(3, &payload);
+        data.extend_from_slice(&[0u8; 64]);
+        let end = (data.len() - 64 - 1) as u64;
+
+        // Act
+        let result = parse_property(&mut Cursor::new(data), end);
+
+        // Assert
+        assert!(matches!(result, Err(ParseError::ValidationError(_))));
+    }
+
+    #[test]
+    fn parse_property_should_reject_absurd_lengths_without_allocating_them() {
+        for type_code in b"SRdflib" {
+            // Arrange
+            let mut data = vec![*type_code];
+            data.write_u32::<LittleEndian>(u32::MAX).unwrap();
+            if type_code.is_ascii_lowercase() {
+                data.write_u32::<LittleEndian>(0).unwrap();
+                data.write_u32::<LittleEndian>(0).unwrap();
+            }
+            data.extend_from_slice(&[0u8; 16]);
+            let end = data.len() as u64;
+
+            // Act
+            let result = parse_property(&mut Cursor::new(data), end);
+
+            // Assert
+            assert!(matches!(result, Err(ParseError::ValidationError(_))), "type code {}", *type_code as char);
+        }
+    }
+
+    #[test]
+    fn array_values_should_not_trust_declared_length_of_compressed_array() {
+        // Arrange
+        let payload = deflate_bytes_zlib(&[0u8; 16]);
+        let mut data = vec![b'd'];
+        fill_array_metadata(&mut data, u32::MAX, 1, payload.len() as u32);
+        data.extend_from_slice(&payload);
+        let end = data.len() as u64;
+        let property = parse_property(&mut Cursor::new(data), end).unwrap();
+
+        // Act
+        let values = property.as_f64_array();
+
+        // Assert
+        assert!(values.is_none());
+    }
+
+    #[test]
+    fn array_values_should_stop_inflating_past_declared_length() {
+        // Arrange
+        let payload = deflate_bytes_zlib(&vec![0u8; 1024 * 1024]);
+        let data = compressed_i32_array(1, &payload);
+        let end = data.len() as u64;
+        let property = parse_property(&mut Cursor::new(data), end).unwrap();
+
+        // Act
+        let result = match &property {
+            PropertyRecordType::SignedInt32Array(a) => a.try_values().map(|v| v.to_vec()),
+            _ => panic!("Expected an i32 array")
+        };
+
+        // Assert
+        assert_validation_error(result, "inflated size does not match declared length");
+    }
+
+    const BOOL_BYTES: [u8; 5] = [0, 1, 0x59, 0x54, 0xff];
+
+    #[test]
+    fn parse_bool_property_should_treat_any_nonzero_byte_as_true() {
+        for byte in &BOOL_BYTES {
+            // Arrange
+            let mut input = Cursor::new(vec![*byte]);
+
+            // Act
+            let value = parse_bool_property(&mut input, 0).unwrap();
+
+            // Assert
+            assert_eq!(value, PropertyRecordType::Boolean(*byte != 0), "byte {:#04x}", byte);
+        }
+    }
+
+    #[test]
+    fn parse_bool_array_property_should_treat_any_nonzero_byte_as_true() {
+        for encoding in &[0, 1] {
+            // Arrange
+            let payload = if *encoding == 0 { BOOL_BYTES.to_vec() } else { deflate_bytes_zlib(&BOOL_BYTES) };
+            let mut data = Vec::new();
+            fill_array_metadata(&mut data, BOOL_BYTES.len() as u32, *encoding, payload.len() as u32);
+            data.extend_from_slice(&payload);
+
+            // Act
+            let result = parse_bool_array_property(&mut Cursor::new(data), 0, u64::MAX).unwrap();
+
+            // Assert
+            assert_eq!(result.as_bool_array().unwrap(), &[false, true, true, true, true]);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,133 @@
+use crate::warnings::warning;
+use crate::fbx::arena_document::NodeRef;
+use crate::scene::skin::{Cluster, Skin};
+use crate::scene::connections::{ConnectionKind, Connections};
+use std::collections::HashMap;
+
+fn get_matrix(node: NodeRef, name: &str) -> Option<glm::Mat4> {
+    let m = node.children().get_first(name).ok()?.properties().first()?.as_f64_array()?;
+    if m.len() != 16 {
+        return None;
+    }
+
+    // Stored column by column, translation in the last four values
+    let m: Vec<f32> = m.iter().map(|v| *v as f32).collect();
+    Some(glm::mat4(
+        m[0], m[1], m[2], m[3],
+        m[4], m[5], m[6], m[7],
+        m[8], m[9], m[10], m[11],
+        m[12], m[13], m[14], m[15],
+    ))
+}
+
+fn deformers<'a>(objects: NodeRef<'a>, kind: &'a str) -> impl Iterator<Item=(i64, NodeRef<'a>)> + 'a {
+    objects.children().get_multiple("Deformer")
+        .filter(move |d| d.properties().get(2).and_then(|p| p.as_string()).map(|s| s.name()) == Some(kind))
+        .filter_map(|d| Some((d.properties().first()?.as_i64()?, d)))
+}
+
+fn read_cluster(node: NodeRef, id: i64, joint_id: i64) -> Cluster {
+    let mut cluster = Cluster::new(id, joint_id);
+    let indices = node.children().get_first("Indexes").ok().and_then(|c| c.properties().first()).and_then(|p| p.as_i32_array());
+    let weights = node.children().get_first("Weights").ok().and_then(|c| c.properties().first()).and_then(|p| p.as_f64_array());
+    if let (Some(indices), Some(weights)) = (indices, weights) {
+        if indices.len() == weights.len() {
+            cluster.indices = indices.to_vec();
+            cluster.weights = weights.iter().map(|w| *w as f32).collect();
+        } else {
+            warning!("Cluster {} has {} indices but {} weights, leaving it without influences.", id, indices.len(), weights.len());
+        }
+    }
+    if let Some(transform) = get_matrix(node, "Transform") {
+        cluster.transform = transform;
+    }
+    if let Some(transform_link) = get_matrix(node, "TransformLink") {
+        cluster.transform_link = transform_link;
+    }
+    cluster
+}
+
+/* Reads the Skin deformers by the id of the geometry they deform. Clusters are connected to their skin and their
+   joint model with OO connections, and keep the order of the cluster to skin connections. Clusters without a joint
+   are left out. */
+pub(super) fn read_skins(objects: NodeRef, connections: &Connections) -> HashMap<i64, Skin> {
+    let clusters: HashMap<i64, NodeRef> = deformers(objects, "Cluster").collect();
+    let mut skins: HashMap<i64, Skin> = deformers(objects, "Skin").map(|(id, _)| (id, Skin::new(id, Vec::new()))).collect();
+
+    let connections: Vec<(i64, i64)> = connections.of_kind(ConnectionKind::ObjectObject)
+        .map(|c| (c.child, c.parent))
+        .collect();
+
+    let mut joint_of = HashMap::new();
+    for (child, parent) in &connections {
+        if clusters.contains_key(parent) {
+            joint_of.entry(*parent).or_insert(*child);
+        }
+    }
+
+    let mut skin_of_geometry = HashMap::new();
+    for (child, parent) in &connections {
+        if skins.contains_key(child) {
+            skin_of_geometry.entry(*parent).or_insert(*child);
+        }
+        if let (Some(node), Some(skin)) = (clusters.get(child), skins.get_mut(parent)) {
+            match joint_of.get(child) {
+                Some(joint) => skin.clusters.push(read_cluster(*node, *child, *joint)),
+                None => warning!("Cluster {} is not connected to a joint, leaving it out.", child),
+            }
+        }
+    }
+
+    skin_of_geometry.into_iter()
+        .filter_map(|(geometry, skin)| Some((geometry, skins.get(&skin)?.clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::hierarchy::read_connections;
+    use crate::fbx::fixture::{FbxFixture, FixtureNode};
+    use crate::fbx::property::{FbxString, PropertyRecordType};
+
+    fn deformer(id: i64, kind: &str) -> Vec<PropertyRecordType> {
+        vec![id.into(), FbxString::new("").with_class("Deformer").into(), kind.into()]
+    }
+
+    fn translation(y: f64) -> Vec<f64> {
+        vec![1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, y, 0.0, 1.0]
+    }
+
+    #[test]
+    fn read_skins_should_read_clusters_in_connection_order() {
+        // Arrange
+        let cluster = |o: FixtureNode, id: i64, y: f64| o.node_with_props("Deformer", deformer(id, "Cluster"), |c| c
+            .i32_array("Indexes", vec![0, 1])
+            .double_array("Weights", vec![1.0, 0.5])
+            .double_array("Transform", translation(0.0))
+            .double_array("TransformLink", translation(y)));
+        let oo = |child: i64, parent: i64| vec!["OO".into(), child.into(), parent.into()];
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| {
+                let o = o.node_with_props("Deformer", deformer(10, "Skin"), |s| s);
+                let o = cluster(o, 11, 1.0);
+                cluster(o, 12, 2.0)
+            })
+            .node("Connections", |c| c
+                .node_with_props("C", oo(10, 1), |n| n)
+                .node_with_props("C", oo(12, 10), |n| n)
+                .node_with_props("C", oo(11, 10), |n| n)
+                .node_with_props("C", oo(200, 12), |n| n)
+                .node_with_props("C", oo(100, 11), |n| n))
+            .parse();
+
+        // Act
+        let skins = read_skins(nodes.get("Objects").ok().unwrap(), &read_connections(None, nodes.get("Connections").ok(), None));
+
+        // Assert
+        let skin = &skins[&1];
+        assert_eq!(skin.joint_ids(), vec![200, 100]);
+        assert_eq!(skin.clusters()[1].weights(), &[1.0, 0.5]);
+        assert_eq!(skin.inverse_bind_matrices()[0] * glm::vec4(0.0, 2.0, 0.0, 1.0), glm::vec4(0.0, 0.0, 0.0, 1.0));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,177 @@
+use crate::fbx::arena_document::NodeRef;
+use crate::fbx::properties70::Properties70;
+use crate::scene::texture::{Texture, WrapMode};
+use crate::scene::connections::{ConnectionKind, Connections};
+use std::collections::HashMap;
+
+fn get_string_child(node: NodeRef, name: &str) -> Option<String> {
+    Some(node.children().get_first(name).ok()?.properties().first()?.as_string()?.name().to_string())
+}
+
+/* ModelUVTranslation and ModelUVScaling hold two numbers each, older exporters write them instead of the
+   Properties70 entries */
+fn get_uv_pair(node: NodeRef, name: &str) -> Option<glm::Vec2> {
+    match node.children().get_first(name).ok()?.properties() {
+        [u, v, ..] => Some(glm::vec2(u.as_f64()? as f32, v.as_f64()? as f32)),
+        _ => None,
+    }
+}
+
+/* Image files embedded in Video objects, by the id of the texture they are connected to. Exporters write an empty
+   Content node when the image is not embedded. */
+fn get_embedded_content(objects: NodeRef, connections: &Connections) -> HashMap<i64, Vec<u8>> {
+    let mut videos = HashMap::new();
+    for video in objects.children().get_multiple("Video") {
+        let id = video.properties().first().and_then(|p| p.as_i64());
+        let content = video.children().get_first("Content").ok().and_then(|c| c.properties().first()).and_then(|p| p.as_binary());
+        if let (Some(id), Some(content)) = (id, content) {
+            if !content.is_empty() {
+                videos.insert(id, content);
+            }
+        }
+    }
+
+    let mut content = HashMap::new();
+    for connection in connections.of_kind(ConnectionKind::ObjectObject) {
+        if let Some(video) = videos.get(&connection.child) {
+            content.entry(connection.parent).or_insert_with(|| video.to_vec());
+        }
+    }
+    content
+}
+
+/* Reads the Texture objects along with their placement in uv space. Entries the file leaves out keep the defaults,
+   no transform and repeating in both directions. */
+pub(super) fn read_textures(objects: NodeRef, connections: &Connections) -> Vec<Texture> {
+    let mut content = get_embedded_content(objects, connections);
+    let mut textures = Vec::new();
+    for node in objects.children().get_multiple("Texture") {
+        let id = match node.properties().first().and_then(|p| p.as_i64()) {
+            Some(id) => id,
+            None => continue
+        };
+        let name = node.properties().get(1).and_then(|p| p.as_string()).map_or(String::new(), |s| s.name().to_string());
+        let properties = Properties70::from(node);
+
+        let mut texture = Texture::new(id, name);
+        texture.file_name = get_string_child(node, "FileName").unwrap_or_default();
+        texture.relative_file_name = get_string_child(node, "RelativeFilename").unwrap_or_default();
+        texture.uv_set = properties.string("UVSet").filter(|s| *s != "default").unwrap_or("").to_string();
+
+        let transform = &mut texture.uv_transform;
+        if let Some(translation) = properties.vec3("Translation").map(|t| glm::vec2(t.x, t.y)).or_else(|| get_uv_pair(node, "ModelUVTranslation")) {
+            transform.translation = translation;
+        }
+        if let Some(scale) = properties.vec3("Scaling").map(|s| glm::vec2(s.x, s.y)).or_else(|| get_uv_pair(node, "ModelUVScaling")) {
+            transform.scale = scale;
+        }
+        // Rotation around the w axis is the one that turns the texture in uv space
+        if let Some(rotation) = properties.vec3("Rotation") {
+            transform.rotation = rotation.z;
+        }
+
+        if let Some(wrap) = properties.number("WrapModeU") {
+            texture.wrap_u = WrapMode::from_fbx(wrap as i64);
+        }
+        if let Some(wrap) = properties.number("WrapModeV") {
+            texture.wrap_v = WrapMode::from_fbx(wrap as i64);
+        }
+
+        texture.content = content.remove(&id);
+        textures.push(texture);
+    }
+
+    textures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::hierarchy::read_connections;
+    use crate::fbx::fixture::{FbxFixture, FixtureNode};
+    use crate::fbx::property::{FbxString, PropertyRecordType};
+
+    fn p(name: &str, values: Vec<PropertyRecordType>) -> Vec<PropertyRecordType> {
+        let mut properties: Vec<PropertyRecordType> = vec![name.into(), "".into(), "".into(), "A".into()];
+        properties.extend(values);
+        properties
+    }
+
+    fn read_texture<F>(build: F) -> Texture
+        where F: FnOnce(FixtureNode) -> FixtureNode
+    {
+        let properties = vec![9i64.into(), FbxString::new("Decal").with_class("Texture").into(), "".into()];
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o.node_with_props("Texture", properties, build))
+            .parse();
+        read_textures(nodes.get("Objects").ok().unwrap(), &Connections::new()).remove(0)
+    }
+
+    #[test]
+    fn read_textures_should_read_scaling_and_wrap_modes() {
+        // Arrange
+        let build = |t: FixtureNode| t
+            .leaf("RelativeFilename", "textures\\decal.png")
+            .node("Properties70", |p70| p70
+                .node_with_props("P", p("Scaling", vec![2.0.into(), 1.0.into(), 1.0.into()]), |n| n)
+                .node_with_props("P", p("WrapModeV", vec![1i32.into()]), |n| n));
+
+        // Act
+        let texture = read_texture(build);
+
+        // Assert
+        assert_eq!(texture.name(), "Decal");
+        assert_eq!(texture.relative_file_name(), "textures\\decal.png");
+        assert_eq!(texture.uv_transform().scale, glm::vec2(2.0, 1.0));
+        assert_eq!(texture.uv_transform().translation, glm::vec2(0.0, 0.0));
+        assert_eq!(texture.wrap_u(), WrapMode::Repeat);
+        assert_eq!(texture.wrap_v(), WrapMode::Clamp);
+    }
+
+    #[test]
+    fn read_textures_should_fall_back_to_model_uv_nodes() {
+        // Arrange
+        let build = |t: FixtureNode| t
+            .node_with_props("ModelUVTranslation", vec![0.25.into(), 0.5.into()], |n| n)
+            .node_with_props("ModelUVScaling", vec![3.0.into(), 3.0.into()], |n| n);
+
+        // Act
+        let texture = read_texture(build);
+
+        // Assert
+        assert_eq!(texture.uv_transform().translation, glm::vec2(0.25, 0.5));
+        assert_eq!(texture.uv_transform().scale, glm::vec2(3.0, 3.0));
+    }
+
+    #[test]
+    fn read_textures_should_default_to_identity_and_repeat() {
+        // Act
+        let texture = read_texture(|t| t);
+
+        // Assert
+        assert_eq!(*texture.uv_transform(), Default::default());
+        assert_eq!(texture.wrap_u(), WrapMode::Repeat);
+        assert_eq!(texture.wrap_v(), WrapMode::Repeat);
+    }
+
+    #[test]
+    fn read_textures_should_take_content_from_connected_video() {
+        // Arrange
+        let texture_properties = vec![9i64.into(), FbxString::new("Decal").with_class("Texture").into(), "".into()];
+        let video_properties = vec![5i64.into(), FbxString::new("Decal").with_class("Video").into(), "Clip".into()];
+        let nodes = FbxFixture::new(7400)
+            .node("Objects", |o| o
+                .node_with_props("Texture", texture_properties, |t| t)
+                .node_with_props("Video", video_properties, |v| v
+                    .leaf("Content", PropertyRecordType::BinaryData(vec![1, 2, 3]))))
+            .node("Connections", |c| c
+                .node_with_props("C", vec!["OO".into(), 5i64.into(), 9i64.into()], |n| n))
+            .parse();
+
+        // Act
+        let textures = read_textures(nodes.get("Objects").ok().unwrap(), &read_connections(None, nodes.get("Connections").ok(), None));
+
+        // Assert
+        assert_eq!(textures[0].content, Some(vec![1, 2, 3]));
+    }
+}
\ No newline at end of file
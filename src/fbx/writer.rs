@@ -0,0 +1,209 @@
+use crate::fbx::property::{PropertyRecordType, ArrayElement};
+use crate::fbx::node_reader::FIRST_WIDE_VERSION;
+use crate::fbx::header::{MAGIC, EXPECTED_UNKNOWN_BYTES};
+use byteorder::{LittleEndian, WriteBytesExt};
+
+pub struct FbxNode {
+    name: String,
+    properties: Vec<PropertyRecordType>,
+    children: Vec<FbxNode>,
+}
+
+impl FbxNode {
+    pub fn new(name: &str) -> Self {
+        FbxNode {
+            name: name.to_string(),
+            properties: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_property(mut self, property: PropertyRecordType) -> Self {
+        self.properties.push(property);
+        self
+    }
+
+    pub fn with_child(mut self, child: FbxNode) -> Self {
+        self.children.push(child);
+        self
+    }
+}
+
+/* Writes binary FBX. Node records use 32 bit offsets below version 7500 and 64 bit offsets from then on. */
+pub struct FbxWriter {
+    version: u32,
+    nodes: Vec<FbxNode>,
+    compress_arrays: bool,
+}
+
+impl FbxWriter {
+    pub fn new(version: u32) -> Self {
+        FbxWriter {
+            version,
+            nodes: Vec::new(),
+            compress_arrays: false,
+        }
+    }
+
+    pub fn with_node(mut self, node: FbxNode) -> Self {
+        self.nodes.push(node);
+        self
+    }
+
+    /* Deflate array properties like most exporters do */
+    pub fn with_compressed_arrays(mut self, compress_arrays: bool) -> Self {
+        self.compress_arrays = compress_arrays;
+        self
+    }
+
+    pub fn write(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.extend_from_slice(&EXPECTED_UNKNOWN_BYTES);
+        out.write_u32::<LittleEndian>(self.version).unwrap();
+
+        for node in &self.nodes {
+            self.write_node(&mut out, node);
+        }
+
+        // Null record terminating the top level
+        out.resize(out.len() + self.sentinel_block_length(), 0);
+        out
+    }
+
+    fn offset_size(&self) -> usize {
+        if self.version >= FIRST_WIDE_VERSION { 8 } else { 4 }
+    }
+
+    fn sentinel_block_length(&self) -> usize {
+        self.offset_size() * 3 + 1
+    }
+
+    fn write_offset(&self, out: &mut Vec<u8>, value: u64) {
+        out.extend_from_slice(&value.to_le_bytes()[..self.offset_size()]);
+    }
+
+    fn patch_offset(&self, out: &mut [u8], at: usize, value: u64) {
+        let size = self.offset_size();
+        out[at..at + size].copy_from_slice(&value.to_le_bytes()[..size]);
+    }
+
+    fn write_node(&self, out: &mut Vec<u8>, node: &FbxNode) {
+        let start = out.len();
+        let size = self.offset_size();
+        self.write_offset(out, 0);
+        self.write_offset(out, node.properties.len() as u64);
+        self.write_offset(out, 0);
+        out.write_u8(node.name.len() as u8).unwrap();
+        out.extend_from_slice(node.name.as_bytes());
+
+        let properties_start = out.len();
+        for property in &node.properties {
+            self.write_property(out, property);
+        }
+        let property_length = (out.len() - properties_start) as u64;
+
+        for child in &node.children {
+            self.write_node(out, child);
+        }
+        if !node.children.is_empty() {
+            out.resize(out.len() + self.sentinel_block_length(), 0);
+        }
+
+        // Offsets are only known once everything below the node has been written
+        let end_offset = out.len() as u64;
+        self.patch_offset(out, start, end_offset);
+        self.patch_offset(out, start + 2 * size, property_length);
+    }
+
+    fn write_array<T: ArrayElement>(&self, out: &mut Vec<u8>, values: &[T]) {
+        let mut bytes = Vec::with_capacity(values.len() * T::SIZE);
+        for value in values {
+            value.write_le_bytes(&mut bytes);
+        }
+
+        out.push(T::TYPE_CODE);
+        out.write_u32::<LittleEndian>(values.len() as u32).unwrap();
+        if self.compress_arrays {
+            let compressed = deflate::deflate_bytes_zlib(&bytes);
+            out.write_u32::<LittleEndian>(1).unwrap();
+            out.write_u32::<LittleEndian>(compressed.len() as u32).unwrap();
+            out.extend_from_slice(&compressed);
+        } else {
+            out.write_u32::<LittleEndian>(0).unwrap();
+            out.write_u32::<LittleEndian>(bytes.len() as u32).unwrap();
+            out.extend_from_slice(&bytes);
+        }
+    }
+
+    fn write_property(&self, out: &mut Vec<u8>, property: &PropertyRecordType) {
+        match property {
+            PropertyRecordType::SignedInt16(x) => { out.push(b'Y'); out.write_i16::<LittleEndian>(*x).unwrap(); }
+            PropertyRecordType::Boolean(x) => { out.push(b'C'); out.push(*x as u8); }
+            PropertyRecordType::SignedInt32(x) => { out.push(b'I'); out.write_i32::<LittleEndian>(*x).unwrap(); }
+            PropertyRecordType::Float(x) => { out.push(b'F'); out.write_f32::<LittleEndian>(*x).unwrap(); }
+            PropertyRecordType::Double(x) => { out.push(b'D'); out.write_f64::<LittleEndian>(*x).unwrap(); }
+            PropertyRecordType::SignedInt64(x) => { out.push(b'L'); out.write_i64::<LittleEndian>(*x).unwrap(); }
+            PropertyRecordType::FloatArray(a) => self.write_array(out, a.values().unwrap_or(&[])),
+            PropertyRecordType::DoubleArray(a) => self.write_array(out, a.values().unwrap_or(&[])),
+            PropertyRecordType::SignedInt64Array(a) => self.write_array(out, a.values().unwrap_or(&[])),
+            PropertyRecordType::SignedInt32Array(a) => self.write_array(out, a.values().unwrap_or(&[])),
+            PropertyRecordType::BooleanArray(a) => self.write_array(out, a.values().unwrap_or(&[])),
+            PropertyRecordType::String(x) => {
+                let mut bytes = Vec::new();
+                x.write_bytes(&mut bytes);
+                out.push(b'S');
+                out.write_u32::<LittleEndian>(bytes.len() as u32).unwrap();
+                out.extend_from_slice(&bytes);
+            }
+            PropertyRecordType::BinaryData(x) => {
+                out.push(b'R');
+                out.write_u32::<LittleEndian>(x.len() as u32).unwrap();
+                out.extend_from_slice(x);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fbx::property::{ArrayProperty, FbxString};
+    use crate::fbx::header::parse_header;
+    use crate::fbx::arena_document::ArenaDocument;
+    use crate::fbx::node_reader::DEFAULT_MAX_DEPTH;
+    use std::io::Cursor;
+
+    fn create_writer(compress_arrays: bool, version: u32) -> FbxWriter {
+        FbxWriter::new(version)
+            .with_compressed_arrays(compress_arrays)
+            .with_node(FbxNode::new("Creator").with_property(PropertyRecordType::String(FbxString::new("writer"))))
+            .with_node(FbxNode::new("Objects")
+                .with_child(FbxNode::new("Geometry")
+                    .with_property(PropertyRecordType::SignedInt64(1))
+                    .with_child(FbxNode::new("Vertices")
+                        .with_property(PropertyRecordType::DoubleArray(ArrayProperty::from_values(vec![0.0, 1.0, 2.0]))))
+                    .with_child(FbxNode::new("PolygonVertexIndex")
+                        .with_property(PropertyRecordType::SignedInt32Array(ArrayProperty::from_values(vec![0, 1, -3]))))))
+    }
+
+    #[test]
+    fn write_should_produce_parseable_file() {
+        for (compress_arrays, version) in &[(false, 7400), (true, 7400), (false, 7500), (true, 7700)] {
+            // Arrange
+            let sut = create_writer(*compress_arrays, *version);
+
+            // Act
+            let mut reader = Cursor::new(sut.write());
+            let header = parse_header(&mut reader, false).unwrap();
+            let nodes = ArenaDocument::parse(&mut reader, header.version, &[], DEFAULT_MAX_DEPTH, true).unwrap();
+
+            // Assert
+            assert_eq!(header.version, *version);
+            assert_eq!(nodes.get("Creator").ok().unwrap().properties()[0], PropertyRecordType::String(FbxString::new("writer")));
+            let geometry = nodes.get("Objects").ok().unwrap().children().get("Geometry").ok().unwrap();
+            assert_eq!(geometry.properties()[0], PropertyRecordType::SignedInt64(1));
+            assert_eq!(geometry.children().get("Vertices").ok().unwrap().properties()[0].as_f64_array().unwrap(), &[0.0, 1.0, 2.0]);
+            assert_eq!(geometry.children().get("PolygonVertexIndex").ok().unwrap().properties()[0].as_i32_array().unwrap(), &[0, 1, -3]);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+pub mod fbx;
+pub mod scene;
+pub mod mesh_processor;
+pub mod polygon_utils;
+pub mod warnings;
\ No newline at end of file
@@ -1,21 +1,252 @@
-#![feature(seek_convenience)]
-#![feature(bufreader_seek_relative)]
-#![feature(array_methods)]
+use fbximport::fbx::{import_fbx, import_fbx_batch, import_fbx_with_options, read_file_info, BatchSummary, ImportOptions};
+use fbximport::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
+use fbximport::mesh_processor::weld_processor::WeldVerticesProcessor;
+use fbximport::mesh_processor::normals_processor::GenerateNormalsProcessor;
+use fbximport::mesh_processor::MeshProcessor;
+use fbximport::scene::Scene;
+use std::path::{Path, PathBuf};
 
-use crate::fbx::import_fbx;
-use crate::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
-use crate::mesh_processor::MeshProcessor;
+/* Whether name matches pattern, where * stands for any number of characters and ? for exactly one */
+fn matches_glob(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some('*'), _) => matches_glob(&pattern[1..], name) || (!name.is_empty() && matches_glob(pattern, &name[1..])),
+        (Some('?'), Some(_)) => matches_glob(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) => p == n && matches_glob(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
 
-mod fbx;
-mod scene;
-mod mesh_processor;
-mod polygon_utils;
+/* Files matching a pattern like "models/prop_?.fbx", sorted by name. Wildcards are only expanded in the file name. */
+fn expand_glob(pattern: &str) -> Vec<PathBuf> {
+    if !pattern.contains(['*', '?']) {
+        return vec![PathBuf::from(pattern)];
+    }
+
+    let pattern = Path::new(pattern);
+    let directory = match pattern.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_pattern: Vec<char> = pattern.file_name().map(|n| n.to_string_lossy().chars().collect()).unwrap_or_default();
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(directory)
+        .map(|entries| entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| matches_glob(&file_pattern, &entry.file_name().to_string_lossy().chars().collect::<Vec<_>>()))
+            .map(|entry| entry.path())
+            .collect())
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+fn print_stats(scene: &Scene) {
+    for mesh in scene.meshes() {
+        let faces: Vec<String> = mesh.polygon_histogram().iter()
+            .map(|(arity, count)| format!("{}x{}", count, arity))
+            .collect();
+        println!("{}: faces {}", mesh.name(), faces.join(" "));
+    }
+}
+
+/* fbximport info <file> [--json]: what is in the file, without importing it into a program. Exits with 1 only when
+   the file cannot be parsed, problems that still let it be read are listed as warnings. */
+fn info(args: &[String]) {
+    let json = args.iter().any(|a| a == "--json");
+    let path = match args.iter().find(|a| !a.starts_with("--")) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: fbximport info <file.fbx> [--json]");
+            std::process::exit(2);
+        }
+    };
+
+    match read_file_info(Path::new(path)) {
+        Some(info) if json => println!("{}", info.to_json()),
+        Some(info) => print!("{}", info),
+        None => std::process::exit(1),
+    }
+}
+
+const CONVERT_USAGE: &str = "Usage: fbximport convert <in.fbx>... -o <out.obj> [--format obj] [--no-triangulate] \
+[--weld <distance>] [--generate-normals] [--filter <mesh name pattern>] [--merge] [--quiet | --verbose]";
+
+// Formats convert knows of, only some of which can be written so far
+const OUTPUT_FORMATS: [&str; 6] = ["obj", "gltf", "glb", "ply", "stl", "fbx"];
+const WRITABLE_FORMATS: [&str; 1] = ["obj"];
+
+#[derive(Default)]
+struct ConvertArgs {
+    inputs: Vec<PathBuf>,
+    output: Option<PathBuf>,
+    format: Option<String>,
+    triangulate: bool,
+    weld: Option<f32>,
+    generate_normals: bool,
+    filter: Option<String>,
+    merge: bool,
+    quiet: bool,
+    verbose: bool,
+}
+
+fn parse_convert_args(args: &[String]) -> Result<ConvertArgs, String> {
+    let mut parsed = ConvertArgs { triangulate: true, ..Default::default() };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "-o" | "--output" => parsed.output = Some(PathBuf::from(value()?)),
+            "--format" => parsed.format = Some(value()?.to_lowercase()),
+            "--no-triangulate" => parsed.triangulate = false,
+            "--weld" => {
+                let distance = value()?;
+                parsed.weld = Some(distance.parse().map_err(|_| format!("--weld takes a distance, not '{}'", distance))?);
+            }
+            "--generate-normals" => parsed.generate_normals = true,
+            "--filter" => parsed.filter = Some(value()?.clone()),
+            "--merge" => parsed.merge = true,
+            "--quiet" => parsed.quiet = true,
+            "--verbose" => parsed.verbose = true,
+            "--scale-to-meters" | "--y-up" => return Err(format!("{} is not supported yet, the importer does not read the \
+                                                                   units and axes of files", arg)),
+            flag if flag.starts_with('-') => return Err(format!("unknown option {}", flag)),
+            input => parsed.inputs.extend(expand_glob(input)),
+        }
+    }
+
+    if parsed.inputs.is_empty() {
+        return Err("no input files".to_string());
+    }
+    if parsed.inputs.len() > 1 && !parsed.merge {
+        return Err(format!("{} input files given, pass --merge to convert them into one file", parsed.inputs.len()));
+    }
+    if parsed.output.is_none() {
+        return Err("no output file, pass it with -o".to_string());
+    }
+    if parsed.quiet && parsed.verbose {
+        return Err("--quiet and --verbose cannot be used together".to_string());
+    }
+    Ok(parsed)
+}
+
+/* Format from --format, otherwise from the extension of the output file */
+fn output_format(args: &ConvertArgs, output: &Path) -> Result<String, String> {
+    let format = match &args.format {
+        Some(format) => format.clone(),
+        None => output.extension().map(|e| e.to_string_lossy().to_lowercase())
+            .ok_or_else(|| format!("cannot tell the format of {} without an extension, pass --format", output.display()))?,
+    };
+    if !OUTPUT_FORMATS.contains(&format.as_str()) {
+        return Err(format!("unknown format '{}', expected one of {}", format, OUTPUT_FORMATS.join(", ")));
+    }
+    if !WRITABLE_FORMATS.contains(&format.as_str()) {
+        return Err(format!("writing {} is not supported yet, only {}", format, WRITABLE_FORMATS.join(", ")));
+    }
+    Ok(format)
+}
+
+fn convert(args: &ConvertArgs) -> Result<(), String> {
+    let output = args.output.as_deref().expect("checked by parse_convert_args");
+    output_format(args, output)?;
+    // Errors are still printed, only warnings are silenced
+    fbximport::warnings::set_enabled(!args.quiet);
+
+    let mut merged: Option<Scene> = None;
+    for input in &args.inputs {
+        let mut processors = Vec::<Box<dyn MeshProcessor>>::new();
+        if args.triangulate {
+            processors.push(Box::new(TriangulateMeshProcessor::new()));
+        }
+        if let Some(distance) = args.weld {
+            processors.push(Box::new(WeldVerticesProcessor::new().with_position_epsilon(distance)));
+        }
+        if args.generate_normals {
+            processors.push(Box::new(GenerateNormalsProcessor::new()));
+        }
+
+        // The reason has been printed by the importer
+        let scene = import_fbx_with_options(&input.to_string_lossy(), processors, ImportOptions::new())
+            .ok_or_else(|| format!("could not import {}", input.display()))?;
+        if args.verbose {
+            eprintln!("{}\n{}", input.display(), scene.timings());
+        }
+        match &mut merged {
+            Some(merged) => merged.merge_as(scene, &input.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned())),
+            None => merged = Some(scene),
+        }
+    }
+    let mut scene = merged.expect("checked by parse_convert_args");
+
+    if let Some(filter) = &args.filter {
+        let pattern: Vec<char> = filter.chars().collect();
+        scene.retain_meshes(|mesh| matches_glob(&pattern, &mesh.name().chars().collect::<Vec<_>>()));
+        if scene.meshes().is_empty() && !args.quiet {
+            eprintln!("warning: no mesh matches '{}'", filter);
+        }
+    }
+
+    let file = std::fs::File::create(output).map_err(|e| format!("could not create {}: {}", output.display(), e))?;
+    scene.write_obj(std::io::BufWriter::new(file)).map_err(|e| format!("could not write {}: {}", output.display(), e))?;
+
+    if !args.quiet {
+        let faces: usize = scene.meshes().iter().flat_map(|m| m.polygon_histogram().into_values()).sum();
+        println!("Wrote {} meshes with {} faces to {}", scene.meshes().len(), faces, output.display());
+    }
+    Ok(())
+}
 
 fn main() {
-    let path = "/Users/emil/Downloads/pig.fbx";
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("info") {
+        info(&args[1..]);
+        return;
+    }
+    if args.first().map(String::as_str) == Some("convert") {
+        let result = match parse_convert_args(&args[1..]) {
+            Ok(args) => convert(&args),
+            Err(e) => Err(format!("{}\n{}", e, CONVERT_USAGE)),
+        };
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let print_timings = args.iter().any(|a| a == "--timings");
+    let stats = args.iter().any(|a| a == "--stats");
+    let mut paths: Vec<PathBuf> = args.iter()
+        .filter(|a| !a.starts_with("--"))
+        .flat_map(|a| expand_glob(a))
+        .collect();
+    if paths.is_empty() {
+        paths.push(PathBuf::from("/Users/emil/Downloads/pig.fbx"));
+    }
+
+    let processors: Vec<Box<dyn MeshProcessor>> = vec![Box::new(TriangulateMeshProcessor::new())];
+
+    // Several files or a pattern are imported as a batch
+    if paths.len() > 1 || args.iter().any(|a| a.contains(['*', '?'])) {
+        let results = import_fbx_batch(&paths, &processors, &ImportOptions::new());
+        for (path, result) in &results {
+            if let (true, Ok(result)) = (stats, result) {
+                println!("{}", path.display());
+                print_stats(&result.scene);
+            }
+            if let (true, Ok(result)) = (print_timings, result) {
+                println!("{}\n{}", path.display(), result.scene.timings());
+            }
+        }
+        println!("{}", BatchSummary::new(&results));
+        return;
+    }
+
+    let model = import_fbx(&paths[0].to_string_lossy(), processors);
 
-    let mut processors = Vec::<Box<dyn MeshProcessor>>::new();
-    processors.push(Box::new(TriangulateMeshProcessor{}));
+    if let (true, Some(scene)) = (stats, &model) {
+        print_stats(scene);
+    }
 
-    let _model = import_fbx(path, processors);
-}
\ No newline at end of file
+    if let (true, Some(scene)) = (print_timings, &model) {
+        println!("{}", scene.timings());
+    }
+}
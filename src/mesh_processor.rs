@@ -1,7 +1,227 @@
 use crate::scene::mesh::Mesh;
+use crate::scene::mesh::content_hash::Fnv1a;
+use crate::scene::timings::ProcessorTiming;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 pub mod triangulate_processor;
+pub mod normals_processor;
+pub mod weld_processor;
+pub mod decimate_processor;
+pub mod uv_processor;
+pub mod validate_processor;
+pub mod skin_processor;
+pub mod coordinate_system_processor;
+pub mod winding_processor;
+pub mod snap_processor;
+pub mod material_sort_processor;
 
-pub trait MeshProcessor {
+/* Processors only ever see one mesh at a time and must not share mutable state between meshes, with the parallel
+   feature enabled the meshes of a scene are processed concurrently. */
+pub trait MeshProcessor: Send + Sync {
     fn process(&self, mesh: &mut Mesh);
-}
\ No newline at end of file
+
+    /* Processors can ask for the import to be aborted after they have run, e.g. when validation found errors */
+    fn has_failed(&self) -> bool {
+        false
+    }
+
+    /* Shown in the import timings, the type name without its module path by default */
+    fn name(&self) -> &str {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    /* Hash of the settings changing what the processor does to a mesh, so scenes cached by import_fbx_cached are
+       imported again once they change. Processors with such settings must override it, 0 by default. */
+    fn fingerprint(&self) -> u64 {
+        0
+    }
+}
+
+/* Fingerprint of a processor from its settings, floats passed by their bits */
+pub(crate) fn fingerprint_of(settings: &[u64]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    for setting in settings {
+        hasher.write(&setting.to_le_bytes());
+    }
+    hasher.0
+}
+
+fn empty_timings(processors: &[Box<dyn MeshProcessor>]) -> Vec<ProcessorTiming> {
+    processors.iter().map(|p| ProcessorTiming::new(p.name())).collect()
+}
+
+/* Runs the processors over a single mesh and adds what each of them did to timings. The processors after one that
+   asked for the import to be aborted are left out, they may not cope with what it found. Once cancel is raised the
+   mesh is left alone. */
+fn process_mesh(mesh: &mut Mesh, processors: &[Box<dyn MeshProcessor>], timings: &mut [ProcessorTiming], cancel: Option<&AtomicBool>) {
+    if cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+        return;
+    }
+    for (processor, timing) in processors.iter().zip(timings.iter_mut()) {
+        let faces_in = mesh.faces.len();
+        let vertices_in = mesh.vertices.len();
+        let start = Instant::now();
+        processor.process(mesh);
+        timing.duration += start.elapsed();
+        timing.meshes += 1;
+        timing.faces_in += faces_in;
+        timing.faces_out += mesh.faces.len();
+        timing.vertices_in += vertices_in;
+        timing.vertices_out += mesh.vertices.len();
+
+        if processor.has_failed() {
+            return;
+        }
+    }
+}
+
+#[cfg_attr(feature = "parallel", allow(dead_code))]
+fn process_meshes_sequential(meshes: &mut [Mesh], processors: &[Box<dyn MeshProcessor>], cancel: Option<&AtomicBool>) -> Vec<ProcessorTiming> {
+    let mut timings = empty_timings(processors);
+    for mesh in meshes.iter_mut() {
+        process_mesh(mesh, processors, &mut timings, cancel);
+    }
+    timings
+}
+
+#[cfg(feature = "parallel")]
+fn process_meshes_parallel(meshes: &mut [Mesh], processors: &[Box<dyn MeshProcessor>], cancel: Option<&AtomicBool>) -> Vec<ProcessorTiming> {
+    let results: Vec<Vec<ProcessorTiming>> = meshes.par_iter_mut()
+        .map(|mesh| {
+            let mut timings = empty_timings(processors);
+            process_mesh(mesh, processors, &mut timings, cancel);
+            timings
+        })
+        .collect();
+
+    let mut timings = empty_timings(processors);
+    for mesh_timings in results {
+        for (timing, mesh_timing) in timings.iter_mut().zip(mesh_timings.iter()) {
+            timing.add(mesh_timing);
+        }
+    }
+    timings
+}
+
+/* The first processor, in the order they run, that asked for the import to be aborted. Every mesh is processed
+   before checking, so with or without the parallel feature this is the same one. */
+fn first_failed(processors: &[Box<dyn MeshProcessor>]) -> Option<&dyn MeshProcessor> {
+    processors.iter().map(|p| p.as_ref()).find(|p| p.has_failed())
+}
+
+/* Applies the processors to every mesh, in parallel when the parallel feature is enabled. Returns how long each
+   processor took, or the first processor that asked for the import to be aborted. Meshes not yet begun when cancel
+   is raised are left as they are, the caller is expected to check it afterwards. */
+pub fn process_meshes<'a>(meshes: &mut [Mesh], processors: &'a [Box<dyn MeshProcessor>], cancel: Option<&AtomicBool>)
+    -> Result<Vec<ProcessorTiming>, &'a dyn MeshProcessor> {
+    #[cfg(feature = "parallel")]
+    let timings = process_meshes_parallel(meshes, processors, cancel);
+
+    #[cfg(not(feature = "parallel"))]
+    let timings = process_meshes_sequential(meshes, processors, cancel);
+
+    match first_failed(processors) {
+        Some(processor) => Err(processor),
+        None => Ok(timings),
+    }
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+    use crate::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
+    use crate::mesh_processor::normals_processor::GenerateNormalsProcessor;
+    use crate::mesh_processor::weld_processor::WeldVerticesProcessor;
+
+    /* Fails on the mesh with the given name */
+    struct FailOnMesh {
+        name: &'static str,
+        mesh: &'static str,
+        failed: AtomicBool,
+    }
+
+    impl MeshProcessor for FailOnMesh {
+        fn process(&self, mesh: &mut Mesh) {
+            if mesh.name == self.mesh {
+                self.failed.store(true, Ordering::SeqCst);
+            }
+        }
+
+        fn has_failed(&self) -> bool {
+            self.failed.load(Ordering::SeqCst)
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    fn create_meshes() -> Vec<Mesh> {
+        (0..16).map(|m| {
+            let count = 5 + m;
+            let vertices: Vec<glm::Vec3> = (0..count)
+                .map(|i| {
+                    let angle = 2.0 * std::f32::consts::PI * i as f32 / count as f32;
+                    // Every other vertex pulled inwards to get concave polygons
+                    let radius = if i % 2 == 0 { 1.0 } else { 0.5 };
+                    glm::vec3(radius * glm::cos(angle), radius * glm::sin(angle), m as f32)
+                })
+                .collect();
+            let faces = vec![Face::new((0..count).collect())];
+            Mesh::new(format!("mesh{}", m), vertices, faces)
+        }).collect()
+    }
+
+    fn processors() -> Vec<Box<dyn MeshProcessor>> {
+        vec![
+            Box::new(TriangulateMeshProcessor::new()),
+            Box::new(GenerateNormalsProcessor::new()),
+            Box::new(WeldVerticesProcessor::new()),
+        ]
+    }
+
+    #[test]
+    fn process_meshes_parallel_should_match_sequential_output() {
+        // Arrange
+        let mut sequential = create_meshes();
+        let mut parallel = create_meshes();
+
+        // Act
+        process_meshes_sequential(&mut sequential, &processors(), None);
+        process_meshes_parallel(&mut parallel, &processors(), None);
+
+        // Assert
+        for (a, b) in sequential.iter().zip(parallel.iter()) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.vertices, b.vertices);
+            assert_eq!(a.normals, b.normals);
+            let a_faces: Vec<&Vec<i32>> = a.faces.iter().map(|f| &f.indices).collect();
+            let b_faces: Vec<&Vec<i32>> = b.faces.iter().map(|f| &f.indices).collect();
+            assert_eq!(a_faces, b_faces);
+        }
+    }
+
+    #[test]
+    fn process_meshes_parallel_should_report_the_same_failure_as_sequential() {
+        // Arrange
+        // The second processor fails on an earlier mesh than the first one does
+        let failing = || -> Vec<Box<dyn MeshProcessor>> { vec![
+            Box::new(FailOnMesh { name: "first", mesh: "mesh12", failed: AtomicBool::new(false) }),
+            Box::new(FailOnMesh { name: "second", mesh: "mesh2", failed: AtomicBool::new(false) }),
+        ] };
+        let (sequential, parallel) = (failing(), failing());
+
+        // Act
+        process_meshes_sequential(&mut create_meshes(), &sequential, None);
+        process_meshes_parallel(&mut create_meshes(), &parallel, None);
+
+        // Assert
+        assert_eq!(first_failed(&sequential).map(|p| p.name()), Some("first"));
+        assert_eq!(first_failed(&parallel).map(|p| p.name()), Some("first"));
+    }
+}
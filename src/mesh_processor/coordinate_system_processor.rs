@@ -0,0 +1,298 @@
+use crate::mesh_processor::{fingerprint_of, MeshProcessor};
+use crate::scene::mesh::Mesh;
+use crate::scene::transform::{Quat, Transform};
+use crate::scene::Scene;
+use glm::GenSquareMat;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Axis {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl Axis {
+    fn direction(&self) -> glm::Vec3 {
+        match self {
+            Axis::PositiveX => glm::vec3(1.0, 0.0, 0.0),
+            Axis::NegativeX => glm::vec3(-1.0, 0.0, 0.0),
+            Axis::PositiveY => glm::vec3(0.0, 1.0, 0.0),
+            Axis::NegativeY => glm::vec3(0.0, -1.0, 0.0),
+            Axis::PositiveZ => glm::vec3(0.0, 0.0, 1.0),
+            Axis::NegativeZ => glm::vec3(0.0, 0.0, -1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Handedness {
+    Right,
+    Left,
+}
+
+/* Which axes point up and to the front of a model. The third axis completes the system, up × front when it is right
+   handed and front × up when it is left handed. */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisSystem {
+    pub(crate) up: Axis,
+    pub(crate) front: Axis,
+    pub(crate) handedness: Handedness,
+}
+
+impl AxisSystem {
+    // Maya and the FBX default
+    pub const Y_UP_RIGHT_HANDED: AxisSystem = AxisSystem { up: Axis::PositiveY, front: Axis::PositiveZ, handedness: Handedness::Right };
+    // 3ds Max and Blender
+    pub const Z_UP_RIGHT_HANDED: AxisSystem = AxisSystem { up: Axis::PositiveZ, front: Axis::NegativeY, handedness: Handedness::Right };
+    // Unity and DirectX
+    pub const Y_UP_LEFT_HANDED: AxisSystem = AxisSystem { up: Axis::PositiveY, front: Axis::PositiveZ, handedness: Handedness::Left };
+
+    /* None unless the up and front axes are perpendicular */
+    pub fn new(up: Axis, front: Axis, handedness: Handedness) -> Option<Self> {
+        if glm::dot(up.direction(), front.direction()) != 0.0 {
+            return None;
+        }
+        Some(AxisSystem { up, front, handedness })
+    }
+
+    pub fn up(&self) -> Axis {
+        self.up
+    }
+
+    pub fn front(&self) -> Axis {
+        self.front
+    }
+
+    pub fn handedness(&self) -> Handedness {
+        self.handedness
+    }
+
+    /* Columns are the side, up and front directions */
+    fn basis(&self) -> glm::Mat3 {
+        let up = self.up.direction();
+        let front = self.front.direction();
+        let side = match self.handedness {
+            Handedness::Right => glm::cross(up, front),
+            Handedness::Left => glm::cross(front, up),
+        };
+        glm::Mat3::new(side, up, front)
+    }
+}
+
+fn extend(m: &glm::Mat3) -> glm::Mat4 {
+    glm::mat4(
+        m[0].x, m[0].y, m[0].z, 0.0,
+        m[1].x, m[1].y, m[1].z, 0.0,
+        m[2].x, m[2].y, m[2].z, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    )
+}
+
+/* Moves meshes from one axis system to another. As a mesh processor it converts positions, normals, blend shape
+   offsets and skin bind matrices, node transforms are converted by convert_scene. When the handedness changes the
+   faces are reversed, so they keep facing the same way. */
+pub struct CoordinateSystemProcessor {
+    from: AxisSystem,
+    to: AxisSystem,
+    // Change of basis, from the side, up and front of one system to those of the other. Orthonormal, so it is its
+    // own inverse transpose and converts directions such as normals as well.
+    basis: glm::Mat3,
+    // Negative when the handedness changes
+    determinant: f32,
+}
+
+impl CoordinateSystemProcessor {
+    pub fn new(from: AxisSystem, to: AxisSystem) -> Self {
+        // The bases are orthonormal, so the transpose is the inverse
+        let basis = to.basis() * glm::transpose(&from.basis());
+        CoordinateSystemProcessor {
+            from,
+            to,
+            basis,
+            determinant: basis.determinant(),
+        }
+    }
+
+    pub fn from(&self) -> AxisSystem {
+        self.from
+    }
+
+    pub fn to(&self) -> AxisSystem {
+        self.to
+    }
+
+    pub fn convert_point(&self, point: glm::Vec3) -> glm::Vec3 {
+        self.basis * point
+    }
+
+    pub fn convert_normal(&self, normal: glm::Vec3) -> glm::Vec3 {
+        self.basis * normal
+    }
+
+    /* Converts a matrix taking one space of the source system to another, such as a world transform */
+    pub fn convert_matrix(&self, m: &glm::Mat4) -> glm::Mat4 {
+        extend(&self.basis) * *m * extend(&glm::transpose(&self.basis))
+    }
+
+    /* The same transform expressed in the target system. The rotation axis follows the basis, and is flipped along
+       with it when the handedness changes, as a mirror turns a rotation the other way. Scale stays positive and moves
+       to the axes the basis moves them to. */
+    pub fn convert_transform(&self, transform: &Transform) -> Transform {
+        let r = &transform.rotation;
+        let axis = self.basis * glm::vec3(r.x, r.y, r.z) * self.determinant;
+        let s = transform.scale;
+        let b = &self.basis;
+        let scale = glm::vec3(
+            b[0].x.abs() * s.x + b[1].x.abs() * s.y + b[2].x.abs() * s.z,
+            b[0].y.abs() * s.x + b[1].y.abs() * s.y + b[2].y.abs() * s.z,
+            b[0].z.abs() * s.x + b[1].z.abs() * s.y + b[2].z.abs() * s.z,
+        );
+
+        Transform::new(self.convert_point(transform.translation), Quat { x: axis.x, y: axis.y, z: axis.z, w: r.w }, scale)
+    }
+
+    /* Converts every mesh and the local transforms of every node */
+    pub fn convert_scene(&self, scene: &mut Scene) {
+        for mesh in &mut scene.meshes {
+            self.process(mesh);
+        }
+        for node in &mut scene.nodes {
+            node.transform = self.convert_transform(&node.transform);
+            node.geometric_transform = self.convert_transform(&node.geometric_transform);
+        }
+        scene.update_world_transforms();
+    }
+}
+
+impl MeshProcessor for CoordinateSystemProcessor {
+    fn process(&self, mesh: &mut Mesh) {
+        for vertex in &mut mesh.vertices {
+            *vertex = self.convert_point(*vertex);
+        }
+        for normal in &mut mesh.normals {
+            *normal = self.convert_normal(*normal);
+        }
+        for channel in &mut mesh.blend_shapes {
+            for target in &mut channel.targets {
+                for delta in &mut target.deltas {
+                    *delta = self.convert_point(*delta);
+                }
+            }
+        }
+        if let Some(skin) = &mut mesh.skin {
+            for cluster in &mut skin.clusters {
+                cluster.transform = self.convert_matrix(&cluster.transform);
+                cluster.transform_link = self.convert_matrix(&cluster.transform_link);
+            }
+        }
+
+        if self.determinant < 0.0 {
+            for face in mesh.faces.iter_mut().chain(mesh.degenerate_faces.iter_mut()) {
+                face.indices.reverse();
+            }
+        }
+    }
+
+    fn fingerprint(&self) -> u64 {
+        let systems = [self.from, self.to];
+        fingerprint_of(&systems.iter()
+            .flat_map(|system| vec![system.up as u64, system.front as u64, system.handedness as u64])
+            .collect::<Vec<u64>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::fixture;
+    use crate::polygon_utils::calculate_surface_normal;
+    use crate::mesh_processor::normals_processor::GenerateNormalsProcessor;
+
+    fn assert_near(a: &glm::Mat4, b: &glm::Mat4) {
+        for c in 0..4 {
+            assert!(glm::length(a[c] - b[c]) < 1e-5, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn new_should_reject_up_and_front_axes_that_are_not_perpendicular() {
+        // Act
+        let parallel = AxisSystem::new(Axis::PositiveY, Axis::NegativeY, Handedness::Right);
+        let same = AxisSystem::new(Axis::PositiveZ, Axis::PositiveZ, Handedness::Left);
+        let perpendicular = AxisSystem::new(Axis::PositiveZ, Axis::NegativeY, Handedness::Right);
+
+        // Assert
+        assert_eq!(parallel, None);
+        assert_eq!(same, None);
+        assert_eq!(perpendicular, Some(AxisSystem::Z_UP_RIGHT_HANDED));
+    }
+
+    #[test]
+    fn process_should_put_the_top_of_a_z_up_cube_on_positive_y() {
+        // Arrange
+        let mut cube = fixture::cube(-0.5, 0.5);
+        let processor = CoordinateSystemProcessor::new(AxisSystem::Z_UP_RIGHT_HANDED, AxisSystem::Y_UP_RIGHT_HANDED);
+
+        // Act
+        processor.process(&mut cube);
+
+        // Assert
+        let top = &cube.faces[0];
+        assert!(top.positions(&cube.vertices).all(|p| p.y == 0.5));
+        assert_eq!(calculate_surface_normal(top, &cube.vertices), Some(glm::vec3(0.0, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn process_should_restore_the_mesh_after_a_round_trip() {
+        // Arrange
+        let mut cube = fixture::cube(-0.5, 0.5);
+        GenerateNormalsProcessor::new().process(&mut cube);
+        let original = cube.clone();
+        let there = CoordinateSystemProcessor::new(AxisSystem::Z_UP_RIGHT_HANDED, AxisSystem::Y_UP_LEFT_HANDED);
+        let back = CoordinateSystemProcessor::new(AxisSystem::Y_UP_LEFT_HANDED, AxisSystem::Z_UP_RIGHT_HANDED);
+
+        // Act
+        there.process(&mut cube);
+        back.process(&mut cube);
+
+        // Assert
+        assert_eq!(cube.vertices, original.vertices);
+        assert_eq!(cube.normals, original.normals);
+        let indices = |mesh: &Mesh| mesh.faces.iter().map(|f| f.indices.clone()).collect::<Vec<_>>();
+        assert_eq!(indices(&cube), indices(&original));
+    }
+
+    #[test]
+    fn process_should_reverse_faces_when_the_handedness_changes() {
+        // Arrange
+        let mut cube = fixture::cube(-0.5, 0.5);
+        let processor = CoordinateSystemProcessor::new(AxisSystem::Y_UP_RIGHT_HANDED, AxisSystem::Y_UP_LEFT_HANDED);
+
+        // Act
+        processor.process(&mut cube);
+
+        // Assert
+        assert_eq!(cube.faces[0].indices, vec![6, 7, 5, 4]);
+        assert_eq!(cube.vertices[7], glm::vec3(-0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn convert_transform_should_match_the_converted_matrix() {
+        // Arrange
+        let transform = Transform::from_fbx(glm::vec3(1.0, 2.0, 3.0), glm::vec3(30.0, 45.0, 60.0), glm::vec3(1.0, 2.0, 3.0));
+        let systems = [AxisSystem::Y_UP_LEFT_HANDED, AxisSystem::Y_UP_RIGHT_HANDED, AxisSystem::new(Axis::NegativeX, Axis::PositiveY, Handedness::Left).unwrap()];
+
+        for to in &systems {
+            let processor = CoordinateSystemProcessor::new(AxisSystem::Z_UP_RIGHT_HANDED, *to);
+
+            // Act
+            let converted = processor.convert_transform(&transform);
+
+            // Assert
+            assert_near(&converted.to_mat4(), &processor.convert_matrix(&transform.to_mat4()));
+        }
+    }
+}
@@ -0,0 +1,568 @@
+use crate::warnings::warning;
+use crate::mesh_processor::{fingerprint_of, MeshProcessor};
+use crate::scene::mesh::{Mesh, Smoothing};
+use crate::scene::Scene;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, BinaryHeap};
+
+const MIN_TRIANGLE_COUNT: usize = 4;
+
+/* Symmetric 4x4 error quadric, stored as the upper triangle */
+#[derive(Clone, Copy)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn zero() -> Self {
+        Quadric([0.0; 10])
+    }
+
+    fn from_plane(a: f64, b: f64, c: f64, d: f64) -> Self {
+        Quadric([
+            a * a, a * b, a * c, a * d,
+            b * b, b * c, b * d,
+            c * c, c * d,
+            d * d,
+        ])
+    }
+
+    fn add(&self, other: &Quadric) -> Quadric {
+        let mut result = self.0;
+        for (sum, value) in result.iter_mut().zip(other.0.iter()) {
+            *sum += value;
+        }
+        Quadric(result)
+    }
+
+    fn error(&self, position: &glm::Vec3) -> f64 {
+        let q = &self.0;
+        let (x, y, z) = (position.x as f64, position.y as f64, position.z as f64);
+
+        q[0] * x * x + 2.0 * q[1] * x * y + 2.0 * q[2] * x * z + 2.0 * q[3] * x
+            + q[4] * y * y + 2.0 * q[5] * y * z + 2.0 * q[6] * y
+            + q[7] * z * z + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+/* Candidate edge collapse. removed is merged into kept, which moves to position. */
+struct Collapse {
+    cost: f64,
+    removed: usize,
+    kept: usize,
+    position: glm::Vec3,
+    removed_version: usize,
+    kept_version: usize,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    // Reversed so the BinaryHeap pops the cheapest collapse first. Ties are broken on the vertex indices to keep the
+    // output deterministic.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal)
+            .then_with(|| other.removed.cmp(&self.removed))
+            .then_with(|| other.kept.cmp(&self.kept))
+    }
+}
+
+/* Edge collapse decimation using the quadric error metric (Garland & Heckbert). Only works on triangulated meshes,
+   so run TriangulateMeshProcessor first. Boundary vertices are never moved or removed, and neither are vertices on
+   UV seams unless preserve_uv_seams is turned off. */
+pub struct DecimateProcessor {
+    target_ratio: f32,
+    preserve_uv_seams: bool,
+}
+
+impl DecimateProcessor {
+    pub fn new(target_ratio: f32) -> Self {
+        DecimateProcessor {
+            target_ratio,
+            preserve_uv_seams: true,
+        }
+    }
+
+    pub fn with_preserve_uv_seams(mut self, preserve_uv_seams: bool) -> Self {
+        self.preserve_uv_seams = preserve_uv_seams;
+        self
+    }
+
+    /* Returns a decimated copy of mesh with "_LOD1" appended to its name */
+    pub fn create_lod(&self, mesh: &Mesh) -> Mesh {
+        let mut lod = mesh.clone();
+        lod.name = format!("{}_LOD1", mesh.name);
+        self.process(&mut lod);
+        lod
+    }
+
+    /* Appends a decimated copy of every mesh in the scene */
+    pub fn append_lods(&self, scene: &mut Scene) {
+        let lods: Vec<Mesh> = scene.meshes.iter().map(|mesh| self.create_lod(mesh)).collect();
+        scene.meshes.extend(lods);
+    }
+
+    fn triangle_normal(a: &glm::Vec3, b: &glm::Vec3, c: &glm::Vec3) -> glm::Vec3 {
+        glm::cross(*b - *a, *c - *a)
+    }
+
+    fn find_locked_vertices(&self, mesh: &Mesh, triangles: &[[usize; 3]]) -> Vec<bool> {
+        let mut locked = vec![false; mesh.vertices.len()];
+
+        // Edges used by a single triangle are on the boundary
+        let mut edges = Vec::with_capacity(triangles.len() * 3);
+        for triangle in triangles {
+            for i in 0..3 {
+                let a = triangle[i];
+                let b = triangle[(i + 1) % 3];
+                edges.push((a.min(b), a.max(b)));
+            }
+        }
+        edges.sort();
+
+        let mut i = 0;
+        while i < edges.len() {
+            let mut j = i + 1;
+            while j < edges.len() && edges[j] == edges[i] {
+                j += 1;
+            }
+            if j - i == 1 {
+                locked[edges[i].0] = true;
+                locked[edges[i].1] = true;
+            }
+            i = j;
+        }
+
+        // Seams show up as separate vertices sharing a position
+        if self.preserve_uv_seams && !mesh.uvs.is_empty() {
+            let mut by_position: Vec<usize> = (0..mesh.vertices.len()).collect();
+            let key = |i: &usize| {
+                let v = mesh.vertices[*i];
+                (v.x.to_bits(), v.y.to_bits(), v.z.to_bits())
+            };
+            by_position.sort_by_key(key);
+            for pair in by_position.windows(2) {
+                if key(&pair[0]) == key(&pair[1]) {
+                    locked[pair[0]] = true;
+                    locked[pair[1]] = true;
+                }
+            }
+        }
+
+        locked
+    }
+}
+
+struct DecimationState {
+    positions: Vec<glm::Vec3>,
+    quadrics: Vec<Quadric>,
+    triangles: Vec<[usize; 3]>,
+    triangle_alive: Vec<bool>,
+    vertex_triangles: Vec<BTreeSet<usize>>,
+    vertex_alive: Vec<bool>,
+    versions: Vec<usize>,
+    locked: Vec<bool>,
+}
+
+impl DecimationState {
+    fn neighbours(&self, vertex: usize) -> BTreeSet<usize> {
+        let mut neighbours = BTreeSet::new();
+        for triangle in &self.vertex_triangles[vertex] {
+            for other in &self.triangles[*triangle] {
+                if *other != vertex {
+                    neighbours.insert(*other);
+                }
+            }
+        }
+        neighbours
+    }
+
+    fn candidate(&self, a: usize, b: usize) -> Option<Collapse> {
+        let quadric = self.quadrics[a].add(&self.quadrics[b]);
+
+        let mut options = Vec::with_capacity(3);
+        if !self.locked[a] {
+            options.push((a, b, self.positions[b]));
+        }
+        if !self.locked[b] {
+            options.push((b, a, self.positions[a]));
+        }
+        if !self.locked[a] && !self.locked[b] {
+            let midpoint = (self.positions[a] + self.positions[b]) * 0.5;
+            options.push((a, b, midpoint));
+        }
+
+        options.into_iter()
+            .map(|(removed, kept, position)| Collapse {
+                cost: quadric.error(&position),
+                removed,
+                kept,
+                position,
+                removed_version: self.versions[removed],
+                kept_version: self.versions[kept],
+            })
+            .min_by(|x, y| x.cost.partial_cmp(&y.cost).unwrap_or(Ordering::Equal))
+    }
+
+    fn push_candidates(&self, vertex: usize, heap: &mut BinaryHeap<Collapse>) {
+        for neighbour in self.neighbours(vertex) {
+            if let Some(collapse) = self.candidate(vertex.min(neighbour), vertex.max(neighbour)) {
+                heap.push(collapse);
+            }
+        }
+    }
+
+    fn is_valid(&self, collapse: &Collapse) -> bool {
+        let removed = collapse.removed;
+        let kept = collapse.kept;
+
+        // Link condition, an interior edge may only share two neighbours or the surface turns non-manifold
+        let common = self.neighbours(removed).intersection(&self.neighbours(kept)).count();
+        if common > 2 {
+            return false;
+        }
+
+        // Moving the vertices must not flip or collapse any of the remaining triangles
+        for vertex in &[removed, kept] {
+            for triangle in &self.vertex_triangles[*vertex] {
+                let corners = self.triangles[*triangle];
+                if corners.contains(&removed) && corners.contains(&kept) {
+                    continue;
+                }
+
+                let old_normal = DecimateProcessor::triangle_normal(
+                    &self.positions[corners[0]], &self.positions[corners[1]], &self.positions[corners[2]]);
+
+                let moved: Vec<glm::Vec3> = corners.iter()
+                    .map(|c| if *c == removed || *c == kept { collapse.position } else { self.positions[*c] })
+                    .collect();
+                let new_normal = DecimateProcessor::triangle_normal(&moved[0], &moved[1], &moved[2]);
+
+                if glm::dot(old_normal, new_normal) <= 0.0 || glm::length(new_normal) < 1e-12 {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /* Number of triangles apply would remove, the ones sharing the collapsed edge */
+    fn triangles_removed_by(&self, collapse: &Collapse) -> usize {
+        self.vertex_triangles[collapse.removed].iter()
+            .filter(|triangle| self.triangles[**triangle].contains(&collapse.kept))
+            .count()
+    }
+
+    fn apply(&mut self, collapse: &Collapse) -> usize {
+        let removed = collapse.removed;
+        let kept = collapse.kept;
+        let mut removed_triangles = 0;
+
+        let triangles: Vec<usize> = self.vertex_triangles[removed].iter().copied().collect();
+        for triangle in triangles {
+            if self.triangles[triangle].contains(&kept) {
+                self.triangle_alive[triangle] = false;
+                removed_triangles += 1;
+                for corner in self.triangles[triangle] {
+                    self.vertex_triangles[corner].remove(&triangle);
+                }
+            } else {
+                for corner in self.triangles[triangle].iter_mut() {
+                    if *corner == removed {
+                        *corner = kept;
+                    }
+                }
+                self.vertex_triangles[kept].insert(triangle);
+            }
+        }
+
+        self.vertex_triangles[removed].clear();
+        self.vertex_alive[removed] = false;
+        self.positions[kept] = collapse.position;
+        self.quadrics[kept] = self.quadrics[kept].add(&self.quadrics[removed]);
+        self.versions[kept] += 1;
+        self.versions[removed] += 1;
+
+        removed_triangles
+    }
+}
+
+impl MeshProcessor for DecimateProcessor {
+    fn process(&self, mesh: &mut Mesh) {
+        if !mesh.is_triangulated() {
+            warning!("Mesh '{}' is not triangulated, skipping decimation.", mesh.name);
+            return;
+        }
+
+        let triangles: Vec<[usize; 3]> = mesh.faces.iter()
+            .map(|f| [f.indices[0] as usize, f.indices[1] as usize, f.indices[2] as usize])
+            .collect();
+
+        let target = ((triangles.len() as f32 * self.target_ratio) as usize).max(MIN_TRIANGLE_COUNT);
+        if triangles.len() <= target {
+            return;
+        }
+
+        let mut state = DecimationState {
+            positions: mesh.vertices.clone(),
+            quadrics: vec![Quadric::zero(); mesh.vertices.len()],
+            triangle_alive: vec![true; triangles.len()],
+            vertex_triangles: vec![BTreeSet::new(); mesh.vertices.len()],
+            vertex_alive: vec![true; mesh.vertices.len()],
+            versions: vec![0; mesh.vertices.len()],
+            locked: self.find_locked_vertices(mesh, &triangles),
+            triangles,
+        };
+
+        for (index, triangle) in state.triangles.iter().enumerate() {
+            let (p0, p1, p2) = (state.positions[triangle[0]], state.positions[triangle[1]], state.positions[triangle[2]]);
+            let normal = Self::triangle_normal(&p0, &p1, &p2);
+            if glm::length(normal) > 0.0 {
+                let n = glm::normalize(normal);
+                let d = -glm::dot(n, p0);
+                let plane = Quadric::from_plane(n.x as f64, n.y as f64, n.z as f64, d as f64);
+                for corner in triangle {
+                    state.quadrics[*corner] = state.quadrics[*corner].add(&plane);
+                }
+            }
+
+            for corner in triangle {
+                state.vertex_triangles[*corner].insert(index);
+            }
+        }
+
+        let mut heap = BinaryHeap::new();
+        for vertex in 0..state.positions.len() {
+            for neighbour in state.neighbours(vertex) {
+                if vertex < neighbour {
+                    if let Some(collapse) = state.candidate(vertex, neighbour) {
+                        heap.push(collapse);
+                    }
+                }
+            }
+        }
+
+        let mut triangle_count = state.triangles.len();
+        while triangle_count > target {
+            let collapse = match heap.pop() {
+                Some(c) => c,
+                None => break
+            };
+
+            if !state.vertex_alive[collapse.removed] || !state.vertex_alive[collapse.kept]
+                || state.versions[collapse.removed] != collapse.removed_version
+                || state.versions[collapse.kept] != collapse.kept_version {
+                continue;
+            }
+
+            if !state.is_valid(&collapse) {
+                continue;
+            }
+
+            // Checked before applying, as a collapse cannot be undone
+            if triangle_count - state.triangles_removed_by(&collapse) < MIN_TRIANGLE_COUNT {
+                continue;
+            }
+            triangle_count -= state.apply(&collapse);
+
+            // Every vertex around the kept one has a changed edge cost
+            for neighbour in state.neighbours(collapse.kept) {
+                state.versions[neighbour] += 1;
+            }
+            for neighbour in state.neighbours(collapse.kept) {
+                state.push_candidates(neighbour, &mut heap);
+            }
+        }
+
+        // Compact the vertex arrays, dropping everything no longer referenced
+        let mut remap = vec![-1i32; state.positions.len()];
+        let mut kept = Vec::new();
+        let mut new_faces = Vec::with_capacity(triangle_count);
+        for (index, triangle) in state.triangles.iter().enumerate() {
+            if !state.triangle_alive[index] {
+                continue;
+            }
+
+            let mut indices = Vec::with_capacity(3);
+            for corner in triangle {
+                if remap[*corner] < 0 {
+                    remap[*corner] = kept.len() as i32;
+                    kept.push(*corner);
+                }
+                indices.push(remap[*corner]);
+            }
+            new_faces.push(mesh.faces[index].with_indices(indices));
+        }
+
+        mesh.vertices = kept.iter().map(|i| state.positions[*i]).collect();
+        if !mesh.normals.is_empty() {
+            mesh.normals = kept.iter().map(|i| mesh.normals[*i]).collect();
+        }
+        mesh.remap_vertex_attributes(&kept);
+        mesh.faces = new_faces;
+
+        if let Smoothing::ByEdge(hard_edges) = &mesh.smoothing {
+            let remapped = hard_edges.iter()
+                .filter(|(a, b)| remap[*a as usize] >= 0 && remap[*b as usize] >= 0)
+                .map(|(a, b)| {
+                    let a = remap[*a as usize];
+                    let b = remap[*b as usize];
+                    (a.min(b), a.max(b))
+                })
+                .collect();
+            mesh.smoothing = Smoothing::ByEdge(remapped);
+        }
+    }
+
+    fn fingerprint(&self) -> u64 {
+        fingerprint_of(&[self.target_ratio.to_bits() as u64, self.preserve_uv_seams as u64])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+    use crate::scene::mesh::fixture;
+
+    fn create_sphere(slices: usize, stacks: usize) -> Mesh {
+        let mut vertices = vec![glm::vec3(0.0, 1.0, 0.0)];
+        for stack in 1..stacks {
+            let phi = std::f32::consts::PI * stack as f32 / stacks as f32;
+            for slice in 0..slices {
+                let theta = 2.0 * std::f32::consts::PI * slice as f32 / slices as f32;
+                vertices.push(glm::vec3(glm::sin(phi) * glm::cos(theta), glm::cos(phi), glm::sin(phi) * glm::sin(theta)));
+            }
+        }
+        vertices.push(glm::vec3(0.0, -1.0, 0.0));
+        let bottom = vertices.len() as i32 - 1;
+
+        let ring = |stack: usize, slice: usize| (1 + (stack - 1) * slices + slice % slices) as i32;
+
+        let mut faces = Vec::new();
+        for slice in 0..slices {
+            faces.push(Face::new(vec![0, ring(1, slice + 1), ring(1, slice)]));
+            faces.push(Face::new(vec![bottom, ring(stacks - 1, slice), ring(stacks - 1, slice + 1)]));
+        }
+        for stack in 1..(stacks - 1) {
+            for slice in 0..slices {
+                faces.push(Face::new(vec![ring(stack, slice), ring(stack, slice + 1), ring(stack + 1, slice + 1)]));
+                faces.push(Face::new(vec![ring(stack, slice), ring(stack + 1, slice + 1), ring(stack + 1, slice)]));
+            }
+        }
+
+        Mesh::new("sphere".to_string(), vertices, faces)
+    }
+
+    #[test]
+    fn process_should_decimate_sphere_to_target_ratio() {
+        // Arrange
+        let mut mesh = create_sphere(50, 51);
+        assert_eq!(mesh.faces.len(), 5000);
+        let sut = DecimateProcessor::new(0.25);
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert!(mesh.faces.len() >= 1200 && mesh.faces.len() <= 1300, "got {} triangles", mesh.faces.len());
+        for face in &mesh.faces {
+            assert!(face.indices.iter().all(|i| *i >= 0 && (*i as usize) < mesh.vertices.len()));
+            assert_ne!(face.indices[0], face.indices[1]);
+            assert_ne!(face.indices[1], face.indices[2]);
+            assert_ne!(face.indices[0], face.indices[2]);
+        }
+    }
+
+    #[test]
+    fn process_should_be_deterministic() {
+        // Arrange
+        let mut first = create_sphere(20, 12);
+        let mut second = create_sphere(20, 12);
+        let sut = DecimateProcessor::new(0.5);
+
+        // Act
+        sut.process(&mut first);
+        sut.process(&mut second);
+
+        // Assert
+        assert_eq!(first.vertices, second.vertices);
+        let first_indices: Vec<Vec<i32>> = first.faces.iter().map(|f| f.indices.clone()).collect();
+        let second_indices: Vec<Vec<i32>> = second.faces.iter().map(|f| f.indices.clone()).collect();
+        assert_eq!(first_indices, second_indices);
+    }
+
+    #[test]
+    fn process_should_never_go_below_four_triangles() {
+        // Arrange
+        let mut mesh = create_sphere(8, 4);
+        let sut = DecimateProcessor::new(0.0);
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert!(mesh.faces.len() >= 4);
+    }
+
+    #[test]
+    fn process_should_keep_four_triangles_of_cube_decimated_to_nothing() {
+        // Arrange
+        let mut mesh = fixture::triangulated_cube(0.0, 1.0);
+        let sut = DecimateProcessor::new(0.0);
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.faces.len(), 4);
+    }
+
+    #[test]
+    fn process_should_not_collapse_below_four_triangles_when_a_collapse_removes_two() {
+        // Arrange
+        // Five triangles around an unlocked centre, collapsing it anywhere would leave three
+        let mut vertices = vec![glm::vec3(0.0, 0.0, 0.0)];
+        for i in 0..5 {
+            let angle = 2.0 * std::f32::consts::PI * i as f32 / 5.0;
+            vertices.push(glm::vec3(angle.cos(), angle.sin(), 0.0));
+        }
+        let faces = (0..5).map(|i| Face::new(vec![0, 1 + i, 1 + (i + 1) % 5])).collect();
+        let mut mesh = Mesh::new("fan".to_string(), vertices, faces);
+        let sut = DecimateProcessor::new(0.0);
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.faces.len(), 5);
+    }
+
+    #[test]
+    fn create_lod_should_append_suffix_and_keep_original() {
+        // Arrange
+        let mesh = create_sphere(20, 12);
+        let original_face_count = mesh.faces.len();
+        let sut = DecimateProcessor::new(0.5);
+
+        // Act
+        let lod = sut.create_lod(&mesh);
+
+        // Assert
+        assert_eq!(lod.name, "sphere_LOD1");
+        assert_eq!(mesh.faces.len(), original_face_count);
+        assert!(lod.faces.len() < original_face_count);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,93 @@
+use crate::mesh_processor::MeshProcessor;
+use crate::scene::mesh::Mesh;
+
+/* Groups the faces by material so every material can be drawn with one call, and records where each group is in
+   Mesh::material_ranges. The sort is stable, faces keep their order within a material. */
+pub struct SortFacesByMaterialProcessor {}
+
+impl SortFacesByMaterialProcessor {
+    pub fn new() -> Self {
+        SortFacesByMaterialProcessor {}
+    }
+}
+
+impl Default for SortFacesByMaterialProcessor {
+    fn default() -> Self {
+        SortFacesByMaterialProcessor::new()
+    }
+}
+
+impl MeshProcessor for SortFacesByMaterialProcessor {
+    fn process(&self, mesh: &mut Mesh) {
+        // Everything known about a face is stored on it, so it all moves along
+        mesh.faces.sort_by_key(|face| face.material);
+
+        mesh.material_ranges.clear();
+        let mut start = 0;
+        for end in 1..=mesh.faces.len() {
+            if end == mesh.faces.len() || mesh.faces[end].material != mesh.faces[start].material {
+                mesh.material_ranges.push((mesh.faces[start].material, start..end));
+                start = end;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+
+    fn create_strip(materials: &[usize]) -> Mesh {
+        let vertices = (0..materials.len() + 2).map(|i| glm::vec3(i as f32, (i % 2) as f32, 0.0)).collect();
+        let faces = materials.iter().enumerate()
+            .map(|(i, material)| {
+                let mut face = Face::new(vec![i as i32, i as i32 + 1, i as i32 + 2]);
+                face.material = *material;
+                face.polygon = i;
+                face
+            })
+            .collect();
+        Mesh::new("strip".to_string(), vertices, faces)
+    }
+
+    #[test]
+    fn process_should_group_interleaved_materials_in_order() {
+        // Arrange
+        let mut mesh = create_strip(&[0, 1, 0, 1]);
+
+        // Act
+        SortFacesByMaterialProcessor::new().process(&mut mesh);
+
+        // Assert
+        let faces: Vec<(usize, usize)> = mesh.faces.iter().map(|f| (f.material(), f.polygon)).collect();
+        assert_eq!(faces, vec![(0, 0), (0, 2), (1, 1), (1, 3)]);
+        assert_eq!(mesh.faces[1].indices, vec![2, 3, 4]);
+        assert_eq!(mesh.material_ranges(), &[(0, 0..2), (1, 2..4)]);
+    }
+
+    #[test]
+    fn process_should_give_a_single_range_for_one_material() {
+        // Arrange
+        let mut mesh = create_strip(&[2, 2, 2]);
+
+        // Act
+        SortFacesByMaterialProcessor::new().process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.material_ranges(), &[(2, 0..3)]);
+        assert_eq!(mesh.faces.iter().map(|f| f.polygon).collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn process_should_give_no_ranges_for_an_empty_mesh() {
+        // Arrange
+        let mut mesh = Mesh::new("empty".to_string(), Vec::new(), Vec::new());
+
+        // Act
+        SortFacesByMaterialProcessor::new().process(&mut mesh);
+
+        // Assert
+        assert!(mesh.material_ranges().is_empty());
+    }
+}
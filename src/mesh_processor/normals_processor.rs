@@ -0,0 +1,278 @@
+use crate::warnings::warning;
+use crate::mesh_processor::MeshProcessor;
+use crate::scene::mesh::{Mesh, Face, Smoothing};
+use crate::polygon_utils::{calculate_surface_normal, MIN_NORMAL_LENGTH};
+use num::Zero;
+
+/* Generates per vertex normals from the smoothing information of the mesh. Faces around a vertex are only averaged
+   together if they are smoothed together, otherwise the vertex is duplicated so every smoothing group gets its own
+   normal. Meshes without smoothing information are smoothed everywhere. */
+pub struct GenerateNormalsProcessor {}
+
+impl GenerateNormalsProcessor {
+    pub fn new() -> Self {
+        GenerateNormalsProcessor {}
+    }
+
+    fn contains_edge(face: &Face, a: i32, b: i32) -> bool {
+        let len = face.indices.len();
+        (0..len).any(|i| {
+            let current = face.indices[i];
+            let next = face.indices[(i + 1) % len];
+            (current == a && next == b) || (current == b && next == a)
+        })
+    }
+
+    /* True if faces f1 and f2, which both use vertex, should share a normal at that vertex */
+    fn is_smooth(smoothing: &Smoothing, f1: &Face, f2: &Face, vertex: i32) -> bool {
+        match smoothing {
+            Smoothing::None => true,
+            Smoothing::ByPolygon => f1.smoothing_group & f2.smoothing_group != 0,
+            Smoothing::ByEdge(hard_edges) => {
+                // The faces need to share a soft edge going out from the vertex
+                f1.indices.iter()
+                    .filter(|other| **other != vertex)
+                    .any(|other| {
+                        let edge = (vertex.min(*other), vertex.max(*other));
+                        Self::contains_edge(f1, vertex, *other)
+                            && Self::contains_edge(f2, vertex, *other)
+                            && !hard_edges.contains(&edge)
+                    })
+            }
+        }
+    }
+
+    /* Normal of every face. Faces without area borrow the average normal of the faces sharing a vertex with them,
+       or point along +Y when there is nothing to borrow from. */
+    fn face_normals(mesh: &Mesh, vertex_corners: &[Vec<(usize, usize)>]) -> Vec<glm::Vec3> {
+        let normals: Vec<Option<glm::Vec3>> = mesh.faces.iter()
+            .map(|face| calculate_surface_normal(face, &mesh.vertices))
+            .collect();
+
+        let mut degenerate_count = 0;
+        let result = mesh.faces.iter().enumerate()
+            .map(|(face_index, face)| {
+                if let Some(normal) = normals[face_index] {
+                    return normal;
+                }
+
+                degenerate_count += 1;
+                let sum = face.indices.iter()
+                    .flat_map(|index| vertex_corners[*index as usize].iter())
+                    .filter_map(|(neighbour, _)| normals[*neighbour])
+                    .fold(glm::Vec3::zero(), |acc, n| acc + n);
+
+                if glm::length(sum) >= MIN_NORMAL_LENGTH {
+                    glm::normalize(sum)
+                } else {
+                    glm::vec3(0.0, 1.0, 0.0)
+                }
+            })
+            .collect();
+
+        if degenerate_count > 0 {
+            warning!("Mesh '{}' has {} faces without area. Used the normals of their neighbours instead.", mesh.name, degenerate_count);
+        }
+
+        result
+    }
+
+    fn find_root(parents: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parents[root] != root {
+            root = parents[root];
+        }
+        parents[i] = root;
+        root
+    }
+}
+
+impl Default for GenerateNormalsProcessor {
+    fn default() -> Self {
+        GenerateNormalsProcessor::new()
+    }
+}
+
+impl MeshProcessor for GenerateNormalsProcessor {
+    fn process(&self, mesh: &mut Mesh) {
+        // (face, corner) pairs using each vertex
+        let mut vertex_corners = vec![Vec::new(); mesh.vertices.len()];
+        for (face_index, face) in mesh.faces.iter().enumerate() {
+            for (corner, index) in face.indices.iter().enumerate() {
+                vertex_corners[*index as usize].push((face_index, corner));
+            }
+        }
+
+        let face_normals = Self::face_normals(mesh, &vertex_corners);
+
+        // Vertices get duplicated while iterating, so the adjacency checks run on the untouched faces
+        let original_faces = mesh.faces.clone();
+
+        let mut normals = vec![glm::Vec3::zero(); mesh.vertices.len()];
+        let mut copied_from = Vec::new();
+        for (vertex, corners) in vertex_corners.iter().enumerate() {
+
+            // Group the faces around the vertex into clusters that are smoothed together
+            let mut parents: Vec<usize> = (0..corners.len()).collect();
+            for i in 0..corners.len() {
+                for j in (i + 1)..corners.len() {
+                    let f1 = &original_faces[corners[i].0];
+                    let f2 = &original_faces[corners[j].0];
+                    if Self::is_smooth(&mesh.smoothing, f1, f2, vertex as i32) {
+                        let root_i = Self::find_root(&mut parents, i);
+                        let root_j = Self::find_root(&mut parents, j);
+                        parents[root_j] = root_i;
+                    }
+                }
+            }
+
+            let mut cluster_vertices: Vec<(usize, usize)> = Vec::new();
+            for (i, (face_index, corner)) in corners.iter().enumerate() {
+                let root = Self::find_root(&mut parents, i);
+                let target = match cluster_vertices.iter().find(|(r, _)| *r == root) {
+                    Some((_, target)) => *target,
+                    None => {
+                        // The first cluster keeps the original vertex, the rest get a copy
+                        let target = if cluster_vertices.is_empty() {
+                            vertex
+                        } else {
+                            mesh.vertices.push(mesh.vertices[vertex]);
+                            copied_from.push(vertex);
+                            normals.push(glm::Vec3::zero());
+                            mesh.vertices.len() - 1
+                        };
+                        cluster_vertices.push((root, target));
+                        target
+                    }
+                };
+
+                mesh.faces[*face_index].indices[*corner] = target as i32;
+                normals[target] = normals[target] + face_normals[*face_index];
+            }
+        }
+
+        let source: Vec<usize> = (0..vertex_corners.len()).chain(copied_from).collect();
+        mesh.remap_vertex_attributes(&source);
+
+        mesh.normals = normals.into_iter()
+            .map(|n| if n == glm::Vec3::zero() { n } else { glm::normalize(n) })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::fixture;
+    use std::collections::HashSet;
+
+    fn create_cube(smoothing_groups: [i32; 6]) -> Mesh {
+        let mut mesh = fixture::cube(-1.0, 1.0);
+        for (face, smoothing_group) in mesh.faces.iter_mut().zip(smoothing_groups.iter()) {
+            face.smoothing_group = *smoothing_group;
+        }
+        mesh.smoothing = Smoothing::ByPolygon;
+        mesh
+    }
+
+    fn assert_face_normals(mesh: &Mesh) {
+        for face in &mesh.faces {
+            let face_normal = calculate_surface_normal(face, &mesh.vertices).unwrap();
+            for index in &face.indices {
+                assert!(glm::length(mesh.normals[*index as usize] - face_normal) < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn process_should_average_normals_when_all_faces_share_a_group() {
+        // Arrange
+        let mut mesh = create_cube([1; 6]);
+        let sut = GenerateNormalsProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.normals.len(), 8);
+        for i in 0..8 {
+            let expected = glm::normalize(mesh.vertices[i]);
+            assert!(glm::length(mesh.normals[i] - expected) < 0.0001);
+        }
+    }
+
+    #[test]
+    fn process_should_split_vertices_when_every_face_has_its_own_group() {
+        // Arrange
+        let mut mesh = create_cube([1, 2, 4, 8, 16, 32]);
+        let sut = GenerateNormalsProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.vertices.len(), 24);
+        assert_eq!(mesh.normals.len(), 24);
+        assert_face_normals(&mesh);
+    }
+
+    #[test]
+    fn process_should_split_vertices_along_hard_edges() {
+        // Arrange
+        let mut mesh = create_cube([0; 6]);
+        let mut hard_edges = HashSet::new();
+        for face in &mesh.faces {
+            for i in 0..face.indices.len() {
+                let a = face.indices[i];
+                let b = face.indices[(i + 1) % face.indices.len()];
+                hard_edges.insert((a.min(b), a.max(b)));
+            }
+        }
+        mesh.smoothing = Smoothing::ByEdge(hard_edges);
+        let sut = GenerateNormalsProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.vertices.len(), 24);
+        assert_face_normals(&mesh);
+    }
+
+    #[test]
+    fn process_should_give_faces_without_area_the_normal_of_their_neighbours() {
+        // Arrange
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+            glm::vec3(2.0, 0.0, 0.0),
+        ];
+        // Second face has all its points on a line
+        let faces = vec![Face::new(vec![0, 1, 2]), Face::new(vec![0, 1, 3])];
+        let mut mesh = Mesh::new("sliver".to_string(), vertices, faces);
+        let sut = GenerateNormalsProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.normals[3], glm::vec3(0.0, 0.0, 1.0));
+        assert!(mesh.normals.iter().all(|n| !n.x.is_nan() && !n.y.is_nan() && !n.z.is_nan()));
+    }
+
+    #[test]
+    fn process_should_point_isolated_faces_without_area_along_y() {
+        // Arrange
+        let vertices = vec![glm::vec3(1.0, 2.0, 3.0); 3];
+        let mut mesh = Mesh::new("point".to_string(), vertices, vec![Face::new(vec![0, 1, 2])]);
+        let sut = GenerateNormalsProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.normals, vec![glm::vec3(0.0, 1.0, 0.0); 3]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,168 @@
+use crate::warnings::warning;
+use crate::mesh_processor::{fingerprint_of, MeshProcessor};
+use crate::scene::mesh::Mesh;
+use std::cmp::Ordering;
+
+/* Weight thrown away by LimitSkinInfluencesProcessor, as a fraction of the total weight of each vertex */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DiscardedWeight {
+    // Vertices that had influences dropped
+    pub vertices: usize,
+    pub max: f32,
+    // Over the vertices that had influences dropped
+    pub average: f32,
+    // Vertices without any weight, bound to the fallback joint
+    pub unweighted: usize,
+}
+
+/* Keeps the largest max_influences weights of every vertex and scales them to sum to 1, as GPU skinning only takes a
+   fixed number of joints per vertex. Vertices without weight are bound fully to the fallback joint, the first joint
+   unless set, which is the root of the skeleton in files from common exporters. */
+pub struct LimitSkinInfluencesProcessor {
+    max_influences: usize,
+    fallback_joint: usize,
+}
+
+impl LimitSkinInfluencesProcessor {
+    pub fn new(max_influences: usize) -> Self {
+        LimitSkinInfluencesProcessor {
+            max_influences,
+            fallback_joint: 0,
+        }
+    }
+
+    /* Index into the joint list of the skin */
+    pub fn with_fallback_joint(mut self, fallback_joint: usize) -> Self {
+        self.fallback_joint = fallback_joint;
+        self
+    }
+
+    /* Limits the influences of the mesh's skin, None for meshes without one */
+    pub fn limit(&self, mesh: &mut Mesh) -> Option<DiscardedWeight> {
+        let vertex_count = mesh.vertices.len();
+        let skin = mesh.skin.as_mut()?;
+        let mut influences = skin.vertex_influences(vertex_count);
+
+        let mut discarded = DiscardedWeight { vertices: 0, max: 0.0, average: 0.0, unweighted: 0 };
+        for vertex in &mut influences {
+            vertex.retain(|(_, weight)| *weight > 0.0);
+            let total: f32 = vertex.iter().map(|(_, weight)| weight).sum();
+            // NaN weights leave a vertex as unweighted as no weights at all
+            if total.is_nan() || total <= 0.0 {
+                discarded.unweighted += 1;
+                *vertex = vec![(self.fallback_joint, 1.0)];
+                continue;
+            }
+
+            // Ties keep the lower joint index so the result does not depend on the order of the clusters in the file
+            vertex.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal).then(a.0.cmp(&b.0)));
+            if vertex.len() > self.max_influences {
+                vertex.truncate(self.max_influences);
+            }
+
+            let kept: f32 = vertex.iter().map(|(_, weight)| weight).sum();
+            let dropped = 1.0 - kept / total;
+            if dropped > 0.0 {
+                discarded.vertices += 1;
+                discarded.max = discarded.max.max(dropped);
+                discarded.average += dropped;
+            }
+            for (_, weight) in vertex.iter_mut() {
+                *weight /= kept;
+            }
+        }
+        if discarded.vertices > 0 {
+            discarded.average /= discarded.vertices as f32;
+        }
+
+        if discarded.unweighted > 0 && self.fallback_joint >= skin.clusters.len() {
+            warning!("Mesh '{}' has {} vertices without skin weights but no joint {} to bind them to, leaving the skin as it was.",
+                      mesh.name, discarded.unweighted, self.fallback_joint);
+            return None;
+        }
+        skin.set_vertex_influences(&influences);
+        Some(discarded)
+    }
+}
+
+impl MeshProcessor for LimitSkinInfluencesProcessor {
+    fn process(&self, mesh: &mut Mesh) {
+        let discarded = match self.limit(mesh) {
+            Some(discarded) => discarded,
+            None => return
+        };
+
+        if discarded.unweighted > 0 {
+            warning!("Mesh '{}' has {} vertices without skin weights, bound them to joint {}.", mesh.name, discarded.unweighted, self.fallback_joint);
+        }
+        if discarded.vertices > 0 {
+            warning!("Limited {} vertices of mesh '{}' to {} skin influences, discarding up to {:.1}% of their weight ({:.1}% on average).",
+                      discarded.vertices, mesh.name, self.max_influences, discarded.max * 100.0, discarded.average * 100.0);
+        }
+    }
+
+    fn fingerprint(&self) -> u64 {
+        fingerprint_of(&[self.max_influences as u64, self.fallback_joint as u64])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+    use crate::scene::skin::{Cluster, Skin};
+
+    fn create_skinned_triangle(weights: &[&[f32]]) -> Mesh {
+        let vertices = vec![glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0)];
+        let mut mesh = Mesh::new("skinned".to_string(), vertices, vec![Face::new(vec![0, 1, 2])]);
+        let joint_count = weights.iter().map(|w| w.len()).max().unwrap_or(0);
+        let clusters = (0..joint_count)
+            .map(|joint| {
+                let mut cluster = Cluster::new(joint as i64, 100 + joint as i64);
+                for (vertex, vertex_weights) in weights.iter().enumerate() {
+                    if let Some(weight) = vertex_weights.get(joint) {
+                        cluster.indices.push(vertex as i32);
+                        cluster.weights.push(*weight);
+                    }
+                }
+                cluster
+            })
+            .collect();
+        mesh.skin = Some(Skin::new(1, clusters));
+        mesh
+    }
+
+    #[test]
+    fn limit_should_keep_largest_weights_and_renormalize() {
+        // Arrange
+        let mut mesh = create_skinned_triangle(&[&[0.1, 0.2, 0.2, 0.1, 0.2, 0.2], &[0.5, 0.5], &[1.0]]);
+        let sut = LimitSkinInfluencesProcessor::new(4);
+
+        // Act
+        let discarded = sut.limit(&mut mesh).unwrap();
+
+        // Assert
+        let influences = mesh.skin().unwrap().vertex_influences(3);
+        assert_eq!(influences[0], vec![(1, 0.25), (2, 0.25), (4, 0.25), (5, 0.25)]);
+        assert_eq!(influences[1], vec![(0, 0.5), (1, 0.5)]);
+        assert_eq!(discarded.vertices, 1);
+        assert!((discarded.max - 0.2).abs() < 1e-6);
+        assert!((discarded.average - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn limit_should_bind_unweighted_vertices_to_the_fallback_joint() {
+        // Arrange
+        let mut mesh = create_skinned_triangle(&[&[0.0, 0.0], &[0.0, 0.4], &[]]);
+        let sut = LimitSkinInfluencesProcessor::new(4).with_fallback_joint(1);
+
+        // Act
+        let discarded = sut.limit(&mut mesh).unwrap();
+
+        // Assert
+        let influences = mesh.skin().unwrap().vertex_influences(3);
+        assert_eq!(influences, vec![vec![(1, 1.0)], vec![(1, 1.0)], vec![(1, 1.0)]]);
+        assert_eq!(discarded.unweighted, 2);
+        assert_eq!(discarded.vertices, 0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,142 @@
+use crate::warnings::warning;
+use crate::mesh_processor::{fingerprint_of, MeshProcessor};
+use crate::mesh_processor::weld_processor::WeldVerticesProcessor;
+use crate::scene::mesh::Mesh;
+
+/* What SnapToGridProcessor did to a mesh */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SnapResult {
+    // Vertices with at least one coordinate changed
+    pub moved: usize,
+    // Largest distance a vertex moved
+    pub max_displacement: f32,
+    // Vertices merged into another one after snapping, 0 unless welding is on
+    pub welded: usize,
+}
+
+/* Rounds every vertex coordinate to the nearest multiple of grid, to clean up coordinates like 1.0000000374 from CAD
+   exports that keep vertices from welding and make every export differ from the last. */
+pub struct SnapToGridProcessor {
+    grid: f64,
+    weld: bool,
+}
+
+impl SnapToGridProcessor {
+    /* None unless grid is a positive number */
+    pub fn new(grid: f32) -> Option<Self> {
+        if !grid.is_finite() || grid <= 0.0 {
+            return None;
+        }
+        Some(SnapToGridProcessor {
+            grid: grid as f64,
+            weld: false,
+        })
+    }
+
+    /* Afterwards merge vertices that snapped to the same position, as long as their other attributes agree */
+    pub fn with_weld(mut self, weld: bool) -> Self {
+        self.weld = weld;
+        self
+    }
+
+    // Rounded in double precision, so the multiple of grid is not off by the error of a float multiplication
+    fn snap_coordinate(&self, value: f32) -> f32 {
+        ((value as f64 / self.grid).round() * self.grid) as f32
+    }
+
+    pub fn snap(&self, mesh: &mut Mesh) -> SnapResult {
+        let mut result = SnapResult { moved: 0, max_displacement: 0.0, welded: 0 };
+        for vertex in &mut mesh.vertices {
+            let snapped = glm::vec3(self.snap_coordinate(vertex.x), self.snap_coordinate(vertex.y), self.snap_coordinate(vertex.z));
+            if snapped != *vertex {
+                result.moved += 1;
+                result.max_displacement = result.max_displacement.max(glm::length(snapped - *vertex));
+                *vertex = snapped;
+            }
+        }
+
+        if self.weld {
+            let before = mesh.vertices.len();
+            // Snapped positions are either equal or at least a grid step apart
+            WeldVerticesProcessor::new().with_position_epsilon((self.grid / 4.0) as f32).process(mesh);
+            result.welded = before - mesh.vertices.len();
+        }
+        result
+    }
+}
+
+impl MeshProcessor for SnapToGridProcessor {
+    fn process(&self, mesh: &mut Mesh) {
+        let result = self.snap(mesh);
+        if result.moved > 0 {
+            warning!("Snapped {} vertices of mesh '{}' to a grid of {}, moving them up to {}.", result.moved, mesh.name, self.grid, result.max_displacement);
+        }
+        if result.welded > 0 {
+            warning!("Welded {} vertices of mesh '{}' that snapped to the same position.", result.welded, mesh.name);
+        }
+    }
+
+    fn fingerprint(&self) -> u64 {
+        fingerprint_of(&[self.grid.to_bits(), self.weld as u64])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+
+    fn create_mesh() -> Mesh {
+        let vertices = vec![
+            glm::vec3(0.999999, 0.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.000001, 0.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+        ];
+        Mesh::new("mesh".to_string(), vertices, vec![Face::new(vec![0, 1, 2]), Face::new(vec![3, 4, 1])])
+    }
+
+    #[test]
+    fn new_should_reject_grids_that_are_not_positive() {
+        // Arrange
+        let grids = [0.0, -0.5, f32::NAN, f32::INFINITY];
+
+        // Act
+        let processors: Vec<bool> = grids.iter().map(|g| SnapToGridProcessor::new(*g).is_some()).collect();
+
+        // Assert
+        assert_eq!(processors, vec![false; 4]);
+    }
+
+    #[test]
+    fn snap_should_round_to_the_grid_and_weld_vertices_that_meet() {
+        // Arrange
+        let mut mesh = create_mesh();
+        let processor = SnapToGridProcessor::new(0.001).unwrap().with_weld(true);
+
+        // Act
+        let result = processor.snap(&mut mesh);
+
+        // Assert
+        assert_eq!(result.moved, 2);
+        assert!(result.max_displacement > 0.0 && result.max_displacement < 2e-6);
+        assert_eq!(result.welded, 1);
+        assert_eq!(mesh.vertices, vec![glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0), glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 1.0, 0.0)]);
+        assert_eq!(mesh.faces[1].indices, vec![0, 3, 1]);
+    }
+
+    #[test]
+    fn snap_should_keep_vertices_apart_without_welding() {
+        // Arrange
+        let mut mesh = create_mesh();
+
+        // Act
+        let result = SnapToGridProcessor::new(0.001).unwrap().snap(&mut mesh);
+
+        // Assert
+        assert_eq!(result.welded, 0);
+        assert_eq!(mesh.vertices[0], mesh.vertices[3]);
+        assert_eq!(mesh.vertices.len(), 5);
+    }
+}
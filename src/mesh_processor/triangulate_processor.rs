@@ -1,293 +1,102 @@
-use crate::mesh_processor::MeshProcessor;
-use crate::scene::mesh::{Mesh, Face};
-use crate::polygon_utils::{calculate_surface_normal, is_point_in_triangle_2d, tri_contains_other_verts_2d};
-use num::{Zero, Float};
-use image::{RgbImage, Rgb};
-use crate::scene::mesh::face_vertex_iterator::FaceVertexIterator;
+use crate::warnings::warning;
+use crate::mesh_processor::{fingerprint_of, MeshProcessor};
+use crate::scene::mesh::Mesh;
+use crate::mesh_processor::triangulate_processor::debug_sink::TriangulationDebugSink;
+use crate::mesh_processor::triangulate_processor::face_triangulator::FaceTriangulator;
+use crate::polygon_utils::polygon_normal;
 
 mod face_triangulator;
+pub mod debug_sink;
 
-pub struct TriangulateMeshProcessor {}
+pub struct TriangulateMeshProcessor {
+    debug_sink: Option<Box<dyn TriangulationDebugSink>>,
+    max_polygon_size: usize,
+}
 
 impl TriangulateMeshProcessor {
-    fn triangle_area_2d(v1: &glm::Vec2, v2: &glm::Vec2, v3: &glm::Vec2) -> f32 {
-        return (v1.x * (v3.y - v2.y)) + (v2.x * (v1.y - v3.y)) + (v3.x * (v2.y - v1.y));
+    pub fn new() -> Self {
+        TriangulateMeshProcessor {
+            debug_sink: None,
+            max_polygon_size: 3,
+        }
     }
 
-    fn is_point_on_left_side_of_line(line_v1: &glm::Vec2, line_v2: &glm::Vec2, point: &glm::Vec2) -> bool {
-        TriangulateMeshProcessor::triangle_area_2d(line_v1, point, line_v2) > 0.0
+    pub fn with_debug_sink(mut self, debug_sink: Box<dyn TriangulationDebugSink>) -> Self {
+        self.debug_sink = Some(debug_sink);
+        self
     }
 
-    pub fn new() -> Self {
-        TriangulateMeshProcessor {}
+    /* Faces with at most this many vertices are left as they are. Defaults to 3, i.e. only triangles are kept. */
+    pub fn with_max_polygon_size(mut self, max_polygon_size: usize) -> Self {
+        self.max_polygon_size = max_polygon_size;
+        self
     }
+}
 
-    // fn debug_face(face: &Face, vertices: &Vec<glm::Vec2>, name: &str) {
-    fn debug_face(vertex_indices: Option<&[usize]>, vertices: &Vec<glm::Vec2>, name: &str) {
-        let image_dimensions = glm::vec2(1024.0, 1024.0);
-
-        let mut img = RgbImage::new(image_dimensions.x as u32, image_dimensions.y as u32);
-        for y in 0..image_dimensions.y as u32 {
-            for x in 0..image_dimensions.x as u32 {
-                img.put_pixel(x, y, Rgb([255, 255, 255]));
-            }
-        }
-
-        fn range(count: usize) -> Vec<usize> {
-            let mut indices = vec![0usize; count];
-            for i in 0..indices.len() {
-                indices[i] = i;
-            }
-            indices
-        }
-
-        // Self::debug_face_inner(face, vertices, &mut img);
-        Self::debug_face_inner(vertex_indices.unwrap_or(range(vertices.len()).as_slice()), vertices, &mut img);
-
-        img.save(format!("/Users/emil/temp/{}.png", name)).unwrap();
+impl Default for TriangulateMeshProcessor {
+    fn default() -> Self {
+        TriangulateMeshProcessor::new()
     }
+}
 
-    // fn debug_face_inner(face: &Face, vertices: &Vec<glm::Vec2>, img: &mut RgbImage) {
-    fn debug_face_inner(vertex_indices: &[usize], vertices: &Vec<glm::Vec2>, img: &mut RgbImage) {
-        // let mut vertices = Vec::with_capacity(face.indices.len());
-        let mut smallest = glm::vec2(f32::max_value(), f32::max_value());
-        let mut largest = glm::vec2(f32::min_value(), f32::min_value());
-        // for index in &face.indices {
-
-        // Uncomment to render face at mesh-scale
-        /*for v in vertices {
-            // let v = vertices[*index as usize];
-            // vertices.push(v);
-
-            smallest = glm::min(smallest, *v);
-            largest = glm::max(largest, *v);
-        }*/
-        for i in vertex_indices {
-            let v = vertices[*i];
-            smallest = glm::min(smallest, v);
-            largest = glm::max(largest, v);
-        }
-
-        smallest = smallest - glm::vec2(10.0, 10.0);
-        largest = largest + glm::vec2(10.0, 10.0);
-
-
-        let polygon_size = largest - smallest;
-
-        let image_dimensions = glm::vec2(1024.0, 1024.0);
-
-        fn get_slope(start: &glm::Vec2, end: &glm::Vec2) -> Option<f32> {
-            if start.x == end.x {
-                return None;
-            }
-
-            let slope = (end.y - start.y) / (end.x - start.x);
-            if glm::abs(slope) > 100000.0 {
-                // slope is steep enough to handle as vertical
-                return None;
-            }
-            Some(slope)
-        }
-
-        fn get_intercept(start: &glm::Vec2, slope: Option<f32>) -> f32 {
-            match slope {
-                None => start.x,
-                Some(x) => start.y - x * start.x
-            }
-        }
+impl MeshProcessor for TriangulateMeshProcessor {
+    fn process(&self, mesh: &mut Mesh) {
+        let mut new_faces = Vec::with_capacity(mesh.faces.len());
+        let mut faces = mesh.faces_with_positions();
 
-        // for i in 0..face.indices.len() {
-        for i in 0..vertex_indices.len() {
-            let from_index = vertex_indices[i];
-            let to_index = vertex_indices[(i + 1) % vertex_indices.len()] as usize;
-            let from_vertex = &vertices[from_index];
-            let to_vertex = &vertices[to_index];
-
-            let start = glm::vec2(
-                ((from_vertex.x - smallest.x) / polygon_size.x) * (image_dimensions.x - 1.0),
-                ((from_vertex.y - smallest.y) / polygon_size.y) * (image_dimensions.y - 1.0),
-            );
-
-            let end = glm::vec2(
-                ((to_vertex.x - smallest.x) / polygon_size.x) * (image_dimensions.x - 1.0),
-                ((to_vertex.y - smallest.y) / polygon_size.y) * (image_dimensions.y - 1.0),
-            );
-
-            let slope = get_slope(&start, &end);
-            let intercept = get_intercept(&start, slope);
-
-            let mut previous_distance = f32::max_value();
-
-            let mut current_pos = glm::vec2(start.x, start.y);
-
-            while glm::length(current_pos - end) < previous_distance {
-                previous_distance = glm::length(current_pos - end);
-
-                let base_increment = 0.1f32;
-                match slope {
-                    None => {
-                        let mut step_increment = if start.y > end.y { -base_increment } else { base_increment };
-                        let diff = end.y - start.y;
-                        if glm::abs(diff) < glm::abs(step_increment) {
-                            step_increment = diff;
-                        }
-                        current_pos.y += step_increment;
-                    }
-                    Some(value) => {
-                        let mut step_increment = if start.x > end.x { -base_increment } else { base_increment };
-                        let diff = end.x - start.x;
-                        if glm::abs(diff) < glm::abs(step_increment) {
-                            step_increment = diff;
-                        }
-
-                        current_pos.x += step_increment;
-                        current_pos.y = value * current_pos.x + intercept;
-                    }
-                };
-
-                img.put_pixel(current_pos.x as u32, current_pos.y as u32, Rgb([0, 0, 0]));
+        for face_index in 0..mesh.faces.len() {
+            let (face, positions) = faces.next_face().unwrap();
+            if (3..=self.max_polygon_size).contains(&face.indices.len()) {
+                new_faces.push(face.clone());
+                continue;
             }
-        }
-    }
-
-    fn project_triangle_into_2d(face: &Face, vertices: &Vec<glm::Vec3>) -> Vec<glm::Vec2> {
-        let surface_normal = calculate_surface_normal(face, vertices);
-
-        let absolute_normal = glm::abs(surface_normal);
-
-        let mut project_axis_a = 0usize;
-        let mut project_axis_b = 1usize;
-        let mut inv = surface_normal.z;
 
-        if absolute_normal.x > absolute_normal.y {
-            if absolute_normal.x > absolute_normal.z {
-                project_axis_a = 1;
-                project_axis_b = 2;
-                inv = surface_normal.x;
+            // Without a plane there are no ears to find, so keep the indices and fan them
+            if polygon_normal(positions.iter().copied()).is_none() {
+                warning!("Face {} of mesh '{}' has no area. Fell back to fan triangulation.", face_index, mesh.name);
+                for i in 1..face.indices.len().saturating_sub(1) {
+                    new_faces.push(face.with_indices(vec![face.indices[0], face.indices[i], face.indices[i + 1]]));
+                }
+                continue;
             }
-        } else if absolute_normal.y > absolute_normal.z {
-            project_axis_a = 2;
-            project_axis_b = 0;
-            inv = surface_normal.y;
-        }
-
-        if inv < 0.0 {
-            std::mem::swap(&mut project_axis_a, &mut project_axis_b);
-        }
 
-        let mut plane_vertices = Vec::new();
-        for i in 0..face.indices.len() {
-            plane_vertices.push(glm::vec2(
-                vertices[face.indices[i] as usize][project_axis_a],
-                vertices[face.indices[i] as usize][project_axis_b],
-            ));
-        }
-        plane_vertices
-    }
-
-    /*fn tri_contains_other_verts_2d(v0: &glm::Vec2, v1: &glm::Vec2, v2: &glm::Vec2, face: &Face, vertices: &Vec<glm::Vec2>) -> bool {
-        for i in 0..face.indices.len() {
-            let vertex = &vertices[i];
+            let mut triangulator = FaceTriangulator::new(face, positions);
 
-            if vertex != v0 && vertex != v1 && vertex != v2 && is_point_in_triangle_2d(vertex, v0, v1, v2) {
-                return true;
+            if let Some(sink) = &self.debug_sink {
+                sink.on_face(&mesh.name, face_index, triangulator.plane_vertices());
             }
-        }
 
-        false
-    }*/
-}
+            while let Some(triangle) = triangulator.next() {
+                if let Some(sink) = &self.debug_sink {
+                    sink.on_triangle(&mesh.name, face_index, new_faces.len(), triangulator.plane_vertices(), triangulator.last_triangle().unwrap());
+                }
 
-impl MeshProcessor for TriangulateMeshProcessor {
-    fn process(&self, mesh: &mut Mesh) {
-        let mut new_faces = Vec::new();
-        let mut img = RgbImage::new(1024, 1024);
-        for y in 0..1024 {
-            for x in 0..1024 {
-                img.put_pixel(x, y, Rgb([255, 255, 255]));
+                new_faces.push(triangle);
             }
-        }
 
-        let mut face_counter = 0;
-        for face in &mesh.faces {
-            face_counter += 1;
-            if face.indices.len() == 3 {
-                println!("Skipping face {}. Already a triangle", face_counter);
-                continue;
-            }
-            println!("Triangulating face {} of {}", face_counter, mesh.faces.len());
-            let plane_vertices = Self::project_triangle_into_2d(face, &mesh.vertices);
-
-            // Self::debug_face(face, &plane_vertices, &*format!("{}_face{}_full", mesh.name, face_counter));
-            Self::debug_face(None, &plane_vertices, &*format!("{}_face{}_full", mesh.name, face_counter));
-
-            let mut clipped_vertices = vec![false; face.indices.len()];
-
-            let mut polygon_size = face.indices.len();
-            while polygon_size > 3 {
-                // FIND EAR
-                println!("Polygons remaining: {}", polygon_size);
-                for i in 0..face.indices.len() {
-                    if clipped_vertices[i] {
-                        continue;
-                    }
-
-                    let mut previous = if i == 0 { face.indices.len() - 1 } else { i - 1 };
-                    while clipped_vertices[previous] {
-                        previous = if previous == 0 { face.indices.len() - 1 } else { previous - 1 };
-                    }
-
-                    let mut next = (i + 1) % face.indices.len();
-                    while clipped_vertices[next] {
-                        next = (next + 1) % face.indices.len();
-                    }
-
-                    let v0 = plane_vertices[previous];
-                    let v1 = plane_vertices[i];
-                    let v2 = plane_vertices[next];
-
-                    if Self::is_point_on_left_side_of_line(&v0, &v2, &v1) {
-                        // Assuming CCW  winding, the point should be on the right side.
-                        // Move on to the next vertex in the polygon
-                        continue;
-                    }
-
-                    if tri_contains_other_verts_2d(&v0, &v1, &v2,
-                                                   &mut FaceVertexIterator::from(
-                                                       &mut face.indices.iter(),
-                                                       &plane_vertices)) {
-                        continue;
-                    }
-
-                    new_faces.push(Face::new(vec![face.indices[previous], face.indices[i], face.indices[next]]));
-
-                    // Self::debug_face(&new_faces[new_faces.len() - 1], &plane_vertices, &*format!("{}_face{}_triangle{}", mesh.name, face_counter, new_faces.len()));
-                    Self::debug_face(Some([previous, i, next].as_slice()), &plane_vertices, &*format!("{}_face{}_triangle{}", mesh.name, face_counter, new_faces.len()));
-                    // Self::debug_face_inner(&new_faces[new_faces.len() - 1], &plane_vertices, &mut img);
-                    Self::debug_face_inner([previous, i, next].as_slice(), &plane_vertices, &mut img);
-                    clipped_vertices[i] = true;
-                    polygon_size -= 1;
-                    /*while clipped_vertices[previous] && i != previous {
-
-                    }*/
-                    if polygon_size < 3 {
-                        break;
-                    }
-                }
+            if triangulator.is_self_intersecting() {
+                warning!("Face {} of mesh '{}' is self-intersecting. Triangulated its convex hull pieces instead.", face_index, mesh.name);
+            } else if triangulator.has_stalled() {
+                warning!("Triangulation stalled on face {} of mesh '{}'. Fell back to fan triangulation.", face_index, mesh.name);
             }
         }
 
-        img.save(format!("/Users/emil/temp/{}_result.png", mesh.name)).unwrap();
         mesh.faces = new_faces;
     }
+
+    fn fingerprint(&self) -> u64 {
+        fingerprint_of(&[self.max_polygon_size as u64])
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use glm::sin;
+    use crate::scene::mesh::Face;
     use std::f32::consts::PI;
-    use crate::fbx::import_fbx;
+    use crate::polygon_utils::calculate_surface_normal;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn process_should_handle_convex_quad() {
@@ -305,7 +114,7 @@ mod tests {
 
         // STAR
         let mut vertices = Vec::new();
-        let radians_step = 1.25663706 / 2.0;
+        let radians_step = PI / 5.0;
         let mut current_angle = 0.0f32;
         for i in 0..10 {
             let radius = if i % 2 == 0 { 6.0f32 } else { 2.0f32 };
@@ -323,23 +132,14 @@ mod tests {
 
         let mut mesh = Mesh::new("star".to_string(), vertices, faces);
 
-        let mut sut = TriangulateMeshProcessor::new();
+        let sut = TriangulateMeshProcessor::new();
 
         // Act
         sut.process(&mut mesh);
 
         // Assert
-        // assert_eq!(mesh.faces.len(), 2);
-
-        let face1 = &mesh.faces[0];
-        /*assert_eq!(face1.indices[0], 3);
-        assert_eq!(face1.indices[1], 0);
-        assert_eq!(face1.indices[2], 1);*/
-
-        let face2 = &mesh.faces[1];
-        /*assert_eq!(face2.indices[0], 3);
-        assert_eq!(face2.indices[1], 1);
-        assert_eq!(face2.indices[2], 2);*/
+        assert_eq!(mesh.faces.len(), 8);
+        assert!(mesh.faces.iter().all(|face| face.indices.len() == 3));
     }
 
     #[test]
@@ -364,16 +164,202 @@ mod tests {
         sut.process(&mut mesh);
 
         // Assert
-        // assert_eq!(mesh.faces.len(), 2);
+        assert_eq!(mesh.faces.len(), 2);
+        assert!(mesh.faces.iter().all(|face| face.indices.len() == 3));
+    }
+
+    #[test]
+    fn process_should_terminate_on_self_intersecting_bowtie() {
+        // Arrange
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(20.0, 20.0, 0.0),
+            glm::vec3(25.0, 10.0, 0.0),
+            glm::vec3(20.0, 0.0, 0.0),
+            glm::vec3(0.0, 5.0, 0.0),
+            glm::vec3(-2.0, 2.5, 0.0),
+        ];
+
+        let faces = vec![
+            Face::new(vec![0, 1, 2, 3, 4, 5])
+        ];
+
+        let mut mesh = Mesh::new("bowtie".to_string(), vertices, faces);
+
+        let sut = TriangulateMeshProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.faces.len(), 4);
+        for face in &mesh.faces {
+            assert_eq!(face.indices.len(), 3);
+            assert!(face.indices.iter().all(|i| *i >= 0 && *i < 6));
+        }
+    }
+
+    /* Counts the calls it receives */
+    struct CountingSink {
+        faces: Arc<AtomicUsize>,
+        triangles: Arc<AtomicUsize>,
+    }
+
+    impl TriangulationDebugSink for CountingSink {
+        fn on_face(&self, _: &str, _: usize, _: &[glm::Vec2]) {
+            self.faces.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_triangle(&self, _: &str, _: usize, _: usize, _: &[glm::Vec2], _: [usize; 3]) {
+            self.triangles.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn process_should_only_report_to_the_debug_sink_it_was_given() {
+        // Arrange
+        // Rendering debug images, the only thing writing files, happens in the sink
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(10.0, 0.0, 0.0),
+            glm::vec3(10.0, 10.0, 0.0),
+            glm::vec3(0.0, 10.0, 0.0),
+        ];
+        let mut mesh = Mesh::new("quad".to_string(), vertices, vec![Face::new(vec![0, 1, 2, 3])]);
+        let mut with_sink = mesh.clone();
+        let (faces, triangles) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let sink = CountingSink { faces: faces.clone(), triangles: triangles.clone() };
+
+        let sut = TriangulateMeshProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+        TriangulateMeshProcessor::new().with_debug_sink(Box::new(sink)).process(&mut with_sink);
+
+        // Assert
+        assert!(sut.debug_sink.is_none());
+        assert_eq!(mesh.faces.len(), 2);
+        assert_eq!((faces.load(Ordering::SeqCst), triangles.load(Ordering::SeqCst)), (1, 2));
+        assert_eq!(with_sink.faces.len(), 2);
+    }
+
+    fn assert_triangles_face_along(mesh: &Mesh, normal: glm::Vec3) {
+        for face in &mesh.faces {
+            let triangle_normal = calculate_surface_normal(face, &mesh.vertices).unwrap();
+            assert!(glm::dot(triangle_normal, normal) > 0.0);
+        }
+    }
+
+    #[test]
+    fn process_should_preserve_orientation_of_polygon_facing_negative_z() {
+        // Arrange
+        // L-shape, clockwise when seen from +Z
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(0.0, 2.0, 0.0),
+            glm::vec3(1.0, 2.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+            glm::vec3(2.0, 1.0, 0.0),
+            glm::vec3(2.0, 0.0, 0.0),
+        ];
+
+        let face = Face::new(vec![0, 1, 2, 3, 4, 5]);
+        let original_normal = calculate_surface_normal(&face, &vertices).unwrap();
+        let mut mesh = Mesh::new("negative_z".to_string(), vertices, vec![face]);
+
+        let sut = TriangulateMeshProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert!(original_normal.z < 0.0);
+        assert_eq!(mesh.faces.len(), 4);
+        assert_triangles_face_along(&mesh, original_normal);
+    }
+
+    #[test]
+    fn process_should_preserve_orientation_of_polygon_facing_positive_x() {
+        // Arrange
+        // L-shape in the YZ plane, counter-clockwise when seen from +X
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(0.0, 2.0, 0.0),
+            glm::vec3(0.0, 2.0, 1.0),
+            glm::vec3(0.0, 1.0, 1.0),
+            glm::vec3(0.0, 1.0, 2.0),
+            glm::vec3(0.0, 0.0, 2.0),
+        ];
+
+        let face = Face::new(vec![0, 1, 2, 3, 4, 5]);
+        let original_normal = calculate_surface_normal(&face, &vertices).unwrap();
+        let mut mesh = Mesh::new("positive_x".to_string(), vertices, vec![face]);
+
+        let sut = TriangulateMeshProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert!(original_normal.x > 0.0);
+        assert_eq!(mesh.faces.len(), 4);
+        assert_triangles_face_along(&mesh, original_normal);
+    }
+
+    #[test]
+    fn process_should_only_split_faces_above_max_polygon_size() {
+        // Arrange
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec3(2.0, 0.0, 0.0),
+            glm::vec3(3.0, 0.0, 0.0),
+            glm::vec3(4.0, 1.0, 0.0),
+            glm::vec3(3.0, 2.0, 0.0),
+            glm::vec3(2.0, 2.0, 0.0),
+            glm::vec3(1.5, 1.0, 0.0),
+        ];
+
+        let faces = vec![
+            Face::new(vec![0, 1, 2]),
+            Face::new(vec![0, 1, 2, 3]),
+            Face::new(vec![4, 5, 6, 7, 8, 9]),
+            Face::new(vec![1, 4, 9, 2]),
+        ];
+
+        let mut mesh = Mesh::new("mixed".to_string(), vertices, faces);
+
+        let sut = TriangulateMeshProcessor::new().with_max_polygon_size(4);
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.faces.len(), 7);
+        assert_eq!(mesh.faces[0].indices, vec![0, 1, 2]);
+        assert_eq!(mesh.faces[1].indices, vec![0, 1, 2, 3]);
+        for face in &mesh.faces[2..6] {
+            assert_eq!(face.indices.len(), 3);
+            assert!(face.indices.iter().all(|i| *i >= 4 && *i <= 9));
+        }
+        assert_eq!(mesh.faces[6].indices, vec![1, 4, 9, 2]);
+    }
+
+    #[test]
+    fn process_should_fan_faces_without_area() {
+        // Arrange
+        let vertices = (0..5).map(|i| glm::vec3(i as f32, 0.0, 0.0)).collect();
+        let face = Face::new(vec![0, 1, 2, 3, 4]);
+        let mut mesh = Mesh::new("line".to_string(), vertices, vec![face]);
+        let sut = TriangulateMeshProcessor::new();
 
-        let face1 = &mesh.faces[0];
-        /*assert_eq!(face1.indices[0], 0);
-        assert_eq!(face1.indices[1], 1);
-        assert_eq!(face1.indices[1], 2);*/
+        // Act
+        sut.process(&mut mesh);
 
-        let face2 = &mesh.faces[1];
-        /*assert_eq!(face2.indices[0], 0);
-        assert_eq!(face2.indices[1], 2);
-        assert_eq!(face2.indices[1], 3);*/
+        // Assert
+        let faces: Vec<Vec<i32>> = mesh.faces.iter().map(|f| f.indices.clone()).collect();
+        assert_eq!(faces, vec![vec![0, 1, 2], vec![0, 2, 3], vec![0, 3, 4]]);
     }
 }
\ No newline at end of file
@@ -0,0 +1,145 @@
+use crate::warnings::warning;
+use std::path::PathBuf;
+use image::{RgbImage, Rgb};
+use num::Float;
+
+/* Receives intermediate results from TriangulateMeshProcessor. Only used for debugging the ear clipping. */
+pub trait TriangulationDebugSink: Send + Sync {
+    /* Called once per polygon before it is triangulated. outline is the polygon projected onto its plane. */
+    fn on_face(&self, mesh: &str, face_idx: usize, outline: &[glm::Vec2]);
+
+    /* Called for every ear clipped from the polygon. triangle holds indices into outline. */
+    fn on_triangle(&self, mesh: &str, face_idx: usize, triangle_idx: usize, outline: &[glm::Vec2], triangle: [usize; 3]);
+}
+
+/* Renders every face and clipped triangle as a PNG image in output_directory */
+pub struct PngDebugSink {
+    output_directory: PathBuf,
+}
+
+impl PngDebugSink {
+    pub fn new<P: Into<PathBuf>>(output_directory: P) -> Self {
+        PngDebugSink {
+            output_directory: output_directory.into()
+        }
+    }
+
+    fn render(&self, vertex_indices: Option<&[usize]>, vertices: &[glm::Vec2], name: &str) {
+        let image_dimensions = glm::vec2(1024.0, 1024.0);
+
+        let mut img = RgbImage::new(image_dimensions.x as u32, image_dimensions.y as u32);
+        for y in 0..image_dimensions.y as u32 {
+            for x in 0..image_dimensions.x as u32 {
+                img.put_pixel(x, y, Rgb([255, 255, 255]));
+            }
+        }
+
+        let all: Vec<usize> = (0..vertices.len()).collect();
+        Self::render_outline(vertex_indices.unwrap_or(all.as_slice()), vertices, &mut img);
+
+        let path = self.output_directory.join(format!("{}.png", name));
+        if let Err(e) = img.save(&path) {
+            warning!("Could not write triangulation debug image {}: {}", path.display(), e);
+        }
+    }
+
+    fn render_outline(vertex_indices: &[usize], vertices: &[glm::Vec2], img: &mut RgbImage) {
+        let mut smallest = glm::vec2(f32::max_value(), f32::max_value());
+        let mut largest = glm::vec2(f32::min_value(), f32::min_value());
+        for i in vertex_indices {
+            let v = vertices[*i];
+            smallest = glm::min(smallest, v);
+            largest = glm::max(largest, v);
+        }
+
+        smallest = smallest - glm::vec2(10.0, 10.0);
+        largest = largest + glm::vec2(10.0, 10.0);
+
+
+        let polygon_size = largest - smallest;
+
+        let image_dimensions = glm::vec2(1024.0, 1024.0);
+
+        fn get_slope(start: &glm::Vec2, end: &glm::Vec2) -> Option<f32> {
+            if start.x == end.x {
+                return None;
+            }
+
+            let slope = (end.y - start.y) / (end.x - start.x);
+            if glm::abs(slope) > 100000.0 {
+                // slope is steep enough to handle as vertical
+                return None;
+            }
+            Some(slope)
+        }
+
+        fn get_intercept(start: &glm::Vec2, slope: Option<f32>) -> f32 {
+            match slope {
+                None => start.x,
+                Some(x) => start.y - x * start.x
+            }
+        }
+
+        for i in 0..vertex_indices.len() {
+            let from_index = vertex_indices[i];
+            let to_index = vertex_indices[(i + 1) % vertex_indices.len()];
+            let from_vertex = &vertices[from_index];
+            let to_vertex = &vertices[to_index];
+
+            let start = glm::vec2(
+                ((from_vertex.x - smallest.x) / polygon_size.x) * (image_dimensions.x - 1.0),
+                ((from_vertex.y - smallest.y) / polygon_size.y) * (image_dimensions.y - 1.0),
+            );
+
+            let end = glm::vec2(
+                ((to_vertex.x - smallest.x) / polygon_size.x) * (image_dimensions.x - 1.0),
+                ((to_vertex.y - smallest.y) / polygon_size.y) * (image_dimensions.y - 1.0),
+            );
+
+            let slope = get_slope(&start, &end);
+            let intercept = get_intercept(&start, slope);
+
+            let mut previous_distance = f32::max_value();
+
+            let mut current_pos = glm::vec2(start.x, start.y);
+
+            while glm::length(current_pos - end) < previous_distance {
+                previous_distance = glm::length(current_pos - end);
+
+                let base_increment = 0.1f32;
+                match slope {
+                    None => {
+                        let mut step_increment = if start.y > end.y { -base_increment } else { base_increment };
+                        let diff = end.y - start.y;
+                        if glm::abs(diff) < glm::abs(step_increment) {
+                            step_increment = diff;
+                        }
+                        current_pos.y += step_increment;
+                    }
+                    Some(value) => {
+                        let mut step_increment = if start.x > end.x { -base_increment } else { base_increment };
+                        let diff = end.x - start.x;
+                        if glm::abs(diff) < glm::abs(step_increment) {
+                            step_increment = diff;
+                        }
+
+                        current_pos.x += step_increment;
+                        current_pos.y = value * current_pos.x + intercept;
+                    }
+                };
+
+                img.put_pixel(current_pos.x as u32, current_pos.y as u32, Rgb([0, 0, 0]));
+            }
+        }
+    }
+}
+
+impl TriangulationDebugSink for PngDebugSink {
+    fn on_face(&self, mesh: &str, face_idx: usize, outline: &[glm::Vec2]) {
+        self.render(None, outline, &format!("{}_face{}_full", mesh, face_idx));
+    }
+
+    fn on_triangle(&self, mesh: &str, face_idx: usize, triangle_idx: usize, outline: &[glm::Vec2], triangle: [usize; 3]) {
+        self.render(Some(&triangle), outline, &format!("{}_face{}_triangle{}", mesh, face_idx, triangle_idx));
+    }
+}
\ No newline at end of file
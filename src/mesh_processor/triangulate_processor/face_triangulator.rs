@@ -1,22 +1,272 @@
-use crate::polygon_utils::project_triangle_into_2d;
+use crate::polygon_utils::{project_triangle_into_2d, polygon_area_2d, tri_contains_other_verts_2d_eps, segments_intersect_2d, convex_hull_2d,
+                           is_convex_2d, orientation_2d, orientation_2d_exact, bounding_size_2d, Orientation, RELATIVE_EPSILON};
 use crate::scene::mesh::Face;
 
-struct FaceTriangulator<'a> {
+/* Ear clipping triangulator. Every call to next() clips one ear off the polygon and returns it as a triangle.
+   Active vertices are kept in a doubly linked list and the ear status of a vertex is only recomputed when one of its
+   neighbours is clipped. Only reflex vertices can end up inside an ear, so those are the only ones tested. */
+pub struct FaceTriangulator<'a> {
     face: &'a Face,
     plane_vertices: Vec<glm::Vec2>,
-    clipped_vertices: Vec<bool>,
-    remaining_polygons: usize,
+    previous: Vec<usize>,
+    next: Vec<usize>,
+    is_reflex: Vec<bool>,
+    is_ear: Vec<bool>,
+    reflex_vertices: Vec<usize>,
+    remaining_vertices: usize,
+    is_ccw: bool,
+    // Points this close to a line count as on it, scaled to the size of the polygon
+    epsilon: f32,
+    // Index where the search for the next ear starts. Always an active vertex.
+    cursor: usize,
+    // Set when no ear could be found in the remaining polygon
+    stalled: bool,
+    // Set when the stalled polygon turned out to be self-intersecting. Its triangles are then queued up in pending, as
+    // are those of convex polygons.
+    self_intersecting: bool,
+    pending: Vec<[usize; 3]>,
+    last_triangle: Option<[usize; 3]>,
 }
 
 impl<'a> FaceTriangulator<'a> {
-    pub fn new(face: &'a Face, vertices: &'a Vec<glm::Vec3>) -> Self {
-        let plane_vertices = project_triangle_into_2d(face, vertices);
-        FaceTriangulator {
+    /* Positions holds the corners of the face, as given by Mesh::faces_with_positions */
+    pub fn new(face: &'a Face, positions: &[glm::Vec3]) -> Self {
+        let plane_vertices = project_triangle_into_2d(positions);
+        let vertex_count = face.indices.len();
+
+        // The projection does not guarantee a particular winding (degenerate or non-planar polygons),
+        // so check it here. The ear test flips for clockwise polygons, which keeps every clipped
+        // triangle in the same orientation as the source polygon.
+        let is_ccw = polygon_area_2d(&plane_vertices) >= 0.0;
+        let epsilon = bounding_size_2d(&plane_vertices) * RELATIVE_EPSILON;
+
+        let mut triangulator = FaceTriangulator {
             face,
             plane_vertices,
-            clipped_vertices: vec![false; face.indices.len()],
-            remaining_polygons: face.indices.len(),
+            previous: (0..vertex_count).map(|i| if i == 0 { vertex_count - 1 } else { i - 1 }).collect(),
+            next: (0..vertex_count).map(|i| (i + 1) % vertex_count).collect(),
+            is_reflex: vec![false; vertex_count],
+            is_ear: vec![false; vertex_count],
+            reflex_vertices: Vec::new(),
+            remaining_vertices: vertex_count,
+            is_ccw,
+            epsilon,
+            cursor: 0,
+            stalled: false,
+            self_intersecting: false,
+            pending: Vec::new(),
+            last_triangle: None,
+        };
+
+        // Any fan of a convex polygon is fine, no need to look for ears
+        if vertex_count > 3 && is_convex_2d(&triangulator.plane_vertices, epsilon) {
+            triangulator.pending = (1..vertex_count - 1).rev().map(|i| [0, i, i + 1]).collect();
+            triangulator.remaining_vertices = 0;
+        } else if vertex_count > 3 {
+            for i in 0..vertex_count {
+                if triangulator.compute_is_reflex(i) {
+                    triangulator.is_reflex[i] = true;
+                    triangulator.reflex_vertices.push(i);
+                }
+            }
+
+            triangulator.update_all_ears();
+        }
+
+        triangulator
+    }
+
+    /* The polygon projected onto its plane */
+    pub fn plane_vertices(&self) -> &[glm::Vec2] {
+        &self.plane_vertices
+    }
+
+    /* Indices into plane_vertices() of the triangle most recently returned by next() */
+    pub fn last_triangle(&self) -> Option<[usize; 3]> {
+        self.last_triangle
+    }
+
+    /* True if ear clipping got stuck and the remaining polygon was fan triangulated instead */
+    pub fn has_stalled(&self) -> bool {
+        self.stalled
+    }
+
+    /* True if the polygon crossed itself, in which case its convex hull pieces were triangulated instead */
+    pub fn is_self_intersecting(&self) -> bool {
+        self.self_intersecting
+    }
+
+    /* Which side of the line from its previous to its next neighbour vertex i is on, given by the orientation of the
+       path along the line to the vertex. Flipped for CW winding, so it is CounterClockwise for reflex vertices. */
+    fn corner_side(&self, i: usize, exact: bool) -> Orientation {
+        let v0 = &self.plane_vertices[self.previous[i]];
+        let v1 = &self.plane_vertices[i];
+        let v2 = &self.plane_vertices[self.next[i]];
+
+        let (line_start, line_end) = if self.is_ccw { (v0, v2) } else { (v2, v0) };
+        if exact {
+            orientation_2d_exact(line_start, line_end, v1)
+        } else {
+            orientation_2d(line_start, line_end, v1, self.epsilon)
+        }
+    }
+
+    fn compute_is_reflex(&self, i: usize) -> bool {
+        // For CCW winding the point should be on the right side (left side for CW), otherwise
+        // it is a reflex vertex. Points on the line are not reflex.
+        self.corner_side(i, false) == Orientation::CounterClockwise
+    }
+
+    fn compute_is_ear(&self, i: usize) -> bool {
+        if self.is_reflex[i] {
+            return false;
+        }
+
+        // A vertex within epsilon of the line is not reflex, but when it is on the wrong side of it at all its ear is
+        // wound backwards and overlaps the triangles next to it. Only the exact predicate can tell.
+        if self.corner_side(i, false) == Orientation::Collinear && self.corner_side(i, true) == Orientation::CounterClockwise {
+            return false;
+        }
+
+        let v0 = &self.plane_vertices[self.previous[i]];
+        let v1 = &self.plane_vertices[i];
+        let v2 = &self.plane_vertices[self.next[i]];
+
+        let mut reflex_vertices = self.reflex_vertices.iter().map(|r| &self.plane_vertices[*r]);
+        !tri_contains_other_verts_2d_eps(v0, v1, v2, &mut reflex_vertices, self.epsilon)
+    }
+
+    fn update_reflex(&mut self, i: usize) {
+        let is_reflex = self.compute_is_reflex(i);
+        if is_reflex == self.is_reflex[i] {
+            return;
+        }
+
+        self.is_reflex[i] = is_reflex;
+        if is_reflex {
+            self.reflex_vertices.push(i);
+        } else {
+            self.reflex_vertices.retain(|r| *r != i);
+        }
+    }
+
+    fn update_all_ears(&mut self) {
+        let mut i = self.cursor;
+        for _ in 0..self.remaining_vertices {
+            self.is_ear[i] = self.compute_is_ear(i);
+            i = self.next[i];
+        }
+    }
+
+    fn walk_to_ear(&self) -> Option<usize> {
+        let mut i = self.cursor;
+        for _ in 0..self.remaining_vertices {
+            if self.is_ear[i] {
+                return Some(i);
+            }
+            i = self.next[i];
+        }
+
+        None
+    }
+
+    fn find_ear(&mut self) -> Option<usize> {
+        if let Some(i) = self.walk_to_ear() {
+            return Some(i);
+        }
+
+        // Ear status is only refreshed for the neighbours of clipped vertices, so make sure nothing
+        // went stale before giving up.
+        self.update_all_ears();
+        self.walk_to_ear()
+    }
+
+    fn remaining_ring(&self) -> Vec<usize> {
+        let mut ring = Vec::with_capacity(self.remaining_vertices);
+        let mut i = self.cursor;
+        for _ in 0..self.remaining_vertices {
+            ring.push(i);
+            i = self.next[i];
+        }
+        ring
+    }
+
+    /* Finds the first pair of non-adjacent edges in ring that cross each other */
+    fn find_crossing(&self, ring: &[usize]) -> Option<(usize, usize)> {
+        let n = ring.len();
+        for a in 0..n {
+            for b in (a + 2)..n {
+                if a == 0 && b == n - 1 {
+                    // Adjacent through the wrap-around
+                    continue;
+                }
+
+                if segments_intersect_2d(
+                    &self.plane_vertices[ring[a]], &self.plane_vertices[ring[(a + 1) % n]],
+                    &self.plane_vertices[ring[b]], &self.plane_vertices[ring[(b + 1) % n]]) {
+                    return Some((a, b));
+                }
+            }
+        }
+
+        None
+    }
+
+    /* Splits ring into loops at its crossings and triangulates the convex hull of every loop */
+    fn triangulate_convex_pieces(&self, ring: Vec<usize>, triangles: &mut Vec<[usize; 3]>) {
+        if ring.len() < 3 {
+            return;
+        }
+
+        if let Some((a, b)) = self.find_crossing(&ring) {
+            let inner: Vec<usize> = ring[(a + 1)..=b].to_vec();
+            let outer: Vec<usize> = ring[(b + 1)..].iter().chain(&ring[..=a]).copied().collect();
+            if inner.len() >= 3 && outer.len() >= 3 {
+                self.triangulate_convex_pieces(inner, triangles);
+                self.triangulate_convex_pieces(outer, triangles);
+                return;
+            }
         }
+
+        // The hull is always CCW, flip the triangles for CW polygons to keep the original orientation
+        let hull = convex_hull_2d(&self.plane_vertices, &ring);
+        for i in 1..hull.len().saturating_sub(1) {
+            if self.is_ccw {
+                triangles.push([hull[0], hull[i], hull[i + 1]]);
+            } else {
+                triangles.push([hull[0], hull[i + 1], hull[i]]);
+            }
+        }
+    }
+
+    fn next_pending(&mut self) -> Option<Face> {
+        let triangle = self.pending.pop()?;
+        self.last_triangle = Some(triangle);
+        Some(self.face.with_indices(triangle.iter().map(|i| self.face.indices[*i]).collect()))
+    }
+
+    fn clip(&mut self, i: usize) -> Face {
+        let previous = self.previous[i];
+        let next = self.next[i];
+
+        self.next[previous] = next;
+        self.previous[next] = previous;
+        self.remaining_vertices -= 1;
+        self.last_triangle = Some([previous, i, next]);
+
+        if self.is_reflex[i] {
+            self.is_reflex[i] = false;
+            self.reflex_vertices.retain(|r| *r != i);
+        }
+
+        if self.remaining_vertices > 3 {
+            self.update_reflex(previous);
+            self.update_reflex(next);
+            self.is_ear[previous] = self.compute_is_ear(previous);
+            self.is_ear[next] = self.compute_is_ear(next);
+        }
+
+        self.face.with_indices(vec![self.face.indices[previous], self.face.indices[i], self.face.indices[next]])
     }
 }
 
@@ -24,12 +274,204 @@ impl Iterator for FaceTriangulator<'_> {
     type Item = Face;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.face.indices.len() == 3 {
-            return Some(self.face.clone());
+        if self.self_intersecting || !self.pending.is_empty() {
+            return self.next_pending();
         }
 
-        // if self.remaining_polygons
+        if self.remaining_vertices < 3 {
+            return None;
+        }
 
-        None
+        if self.remaining_vertices == 3 {
+            // Whatever is left is the final triangle
+            let face = self.clip(self.next[self.cursor]);
+            self.remaining_vertices = 0;
+            return Some(face);
+        }
+
+        if !self.stalled {
+            match self.find_ear() {
+                Some(i) => {
+                    self.cursor = self.next[i];
+                    return Some(self.clip(i));
+                }
+                None => {
+                    self.stalled = true;
+
+                    // Clipping through a crossing leaves behind loops wound the wrong way, so those count as
+                    // self-intersecting as well.
+                    let ring = self.remaining_ring();
+                    let ring_vertices: Vec<glm::Vec2> = ring.iter().map(|i| self.plane_vertices[*i]).collect();
+                    let is_flipped = (polygon_area_2d(&ring_vertices) >= 0.0) != self.is_ccw;
+                    if is_flipped || self.find_crossing(&ring).is_some() {
+                        let mut triangles = Vec::new();
+                        self.triangulate_convex_pieces(ring, &mut triangles);
+                        triangles.reverse();
+
+                        self.pending = triangles;
+                        self.self_intersecting = true;
+                        self.remaining_vertices = 0;
+                        return self.next_pending();
+                    }
+                }
+            }
+        }
+
+        // The polygon is degenerate without crossing itself (unexpected winding, numeric trouble etc).
+        // Fall back to a naive fan around the cursor.
+        Some(self.clip(self.next[self.cursor]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polygon_utils::is_point_in_triangle_2d;
+
+    #[test]
+    fn next_should_yield_four_triangles_for_concave_hexagon() {
+        // Arrange
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(4.0, 0.0, 0.0),
+            glm::vec3(4.0, 4.0, 0.0),
+            glm::vec3(2.0, 1.0, 0.0),
+            glm::vec3(0.0, 4.0, 0.0),
+            glm::vec3(-1.0, 2.0, 0.0),
+        ];
+        let face = Face::new(vec![0, 1, 2, 3, 4, 5]);
+
+        // Act
+        let triangles: Vec<Face> = FaceTriangulator::new(&face, &vertices).collect();
+
+        // Assert
+        assert_eq!(triangles.len(), 4);
+        for triangle in &triangles {
+            assert_eq!(triangle.indices.len(), 3);
+            assert!(triangle.indices.iter().all(|i| *i >= 0 && *i < 6));
+            assert_ne!(triangle.indices[0], triangle.indices[1]);
+            assert_ne!(triangle.indices[1], triangle.indices[2]);
+            assert_ne!(triangle.indices[0], triangle.indices[2]);
+        }
+    }
+
+    #[test]
+    fn next_should_yield_triangle_input_once() {
+        // Arrange
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+        ];
+        let face = Face::new(vec![0, 1, 2]);
+        let mut sut = FaceTriangulator::new(&face, &vertices);
+
+        // Act
+        let first = sut.next();
+        let second = sut.next();
+
+        // Assert
+        assert_eq!(first.unwrap().indices, vec![0, 1, 2]);
+        assert!(second.is_none());
+    }
+
+    #[test]
+    fn next_should_triangulate_large_polygon() {
+        // Arrange
+        let vertex_count = 1000;
+        let mut vertices = Vec::with_capacity(vertex_count);
+        for i in 0..vertex_count {
+            let angle = (i as f32 / vertex_count as f32) * std::f32::consts::PI * 2.0;
+            // Slightly wobbly circle to get a few reflex vertices in there
+            let radius = 100.0 + glm::sin(angle * 50.0) * 0.5;
+            vertices.push(glm::vec3(glm::cos(angle) * radius, glm::sin(angle) * radius, 0.0));
+        }
+        let face = Face::new((0..vertex_count as i32).collect());
+
+        // Act
+        let triangles: Vec<Face> = FaceTriangulator::new(&face, &vertices).collect();
+
+        // Assert
+        assert_eq!(triangles.len(), vertex_count - 2);
+        assert!(triangles.iter().all(|t| t.indices.iter().all(|i| *i >= 0 && (*i as usize) < vertex_count)));
+    }
+
+    #[test]
+    fn next_should_not_clip_a_vertex_that_is_reflex_by_less_than_epsilon() {
+        // Arrange
+        // The first vertex is on the line between its neighbours as far as floats can tell, but the last one is a few
+        // ulps below the line, which makes it reflex. There is a notch in the top to keep the polygon from being convex.
+        let half = 0.5f32.to_bits();
+        let vertices = vec![
+            glm::vec3(12.0, 12.0, 0.0),
+            glm::vec3(24.0, 24.0, 0.0),
+            glm::vec3(24.0, 40.0, 0.0),
+            glm::vec3(12.0, 30.0, 0.0),
+            glm::vec3(0.0, 40.0, 0.0),
+            glm::vec3(f32::from_bits(half + 48), f32::from_bits(half + 41), 0.0),
+        ];
+        let face = Face::new(vec![0, 1, 2, 3, 4, 5]);
+
+        // Act
+        let triangles: Vec<Face> = FaceTriangulator::new(&face, &vertices).collect();
+
+        // Assert
+        assert_eq!(triangles.len(), 4);
+        for triangle in &triangles {
+            let corner = |i: usize| glm::vec2(vertices[triangle.indices[i] as usize].x, vertices[triangle.indices[i] as usize].y);
+            assert_ne!(orientation_2d_exact(&corner(0), &corner(1), &corner(2)), Orientation::Clockwise, "{:?} is wound backwards", triangle.indices);
+        }
+    }
+
+    fn assert_covers(triangles: &[Face], vertices: &[glm::Vec3], point: glm::Vec2) {
+        let covered = triangles.iter().any(|t| {
+            let corner = |i: usize| glm::vec2(vertices[t.indices[i] as usize].x, vertices[t.indices[i] as usize].y);
+            is_point_in_triangle_2d(&point, &corner(0), &corner(1), &corner(2))
+        });
+        assert!(covered, "({}, {}) is not covered", point.x, point.y);
+    }
+
+    #[test]
+    fn next_should_cover_crossed_quad() {
+        // Arrange
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(10.0, 10.0, 0.0),
+            glm::vec3(10.0, 0.0, 0.0),
+            glm::vec3(0.0, 10.0, 0.0),
+        ];
+        let face = Face::new(vec![0, 1, 2, 3]);
+
+        // Act
+        let triangles: Vec<Face> = FaceTriangulator::new(&face, &vertices).collect();
+
+        // Assert
+        assert!(!triangles.is_empty());
+        assert_covers(&triangles, &vertices, glm::vec2(2.0, 5.0));
+        assert_covers(&triangles, &vertices, glm::vec2(8.0, 5.0));
+    }
+
+    #[test]
+    fn next_should_triangulate_both_lobes_of_figure_eight() {
+        // Arrange
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(10.0, 10.0, 0.0),
+            glm::vec3(12.0, 5.0, 0.0),
+            glm::vec3(10.0, 0.0, 0.0),
+            glm::vec3(0.0, 10.0, 0.0),
+            glm::vec3(-2.0, 5.0, 0.0),
+        ];
+        let face = Face::new(vec![0, 1, 2, 3, 4, 5]);
+        let mut sut = FaceTriangulator::new(&face, &vertices);
+
+        // Act
+        let triangles: Vec<Face> = sut.by_ref().collect();
+
+        // Assert
+        assert!(sut.is_self_intersecting());
+        assert_covers(&triangles, &vertices, glm::vec2(1.0, 5.0));
+        assert_covers(&triangles, &vertices, glm::vec2(9.0, 5.0));
+        assert!(triangles.iter().all(|t| t.indices.iter().all(|i| *i >= 0 && *i < 6)));
     }
 }
\ No newline at end of file
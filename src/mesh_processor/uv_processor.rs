@@ -0,0 +1,262 @@
+use crate::mesh_processor::{fingerprint_of, MeshProcessor};
+use crate::scene::mesh::Mesh;
+use crate::polygon_utils::calculate_surface_normal;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UvProjection {
+    // Every face is projected along the dominant axis of its normal. Faces pointing either way along an axis share
+    // one projection, so those on the far side come out mirrored.
+    Planar,
+    // Like Planar, with a projection of its own for each side of each axis so no face comes out mirrored
+    Box,
+}
+
+// Name of the uv set made for meshes that had none
+pub const GENERATED_UV_SET: &str = "generated";
+
+/* Generates texture coordinates for meshes that came without any. UVs are measured from the minimum corner of the
+   mesh bounds, so a mesh of units_per_tile size covers the 0-1 square. The uvs are per corner: vertices shared by
+   faces that project differently are split, the way the importer stores ByPolygonVertex layers. */
+pub struct GenerateUvsProcessor {
+    mode: UvProjection,
+    units_per_tile: f32,
+    force: bool,
+}
+
+impl GenerateUvsProcessor {
+    pub fn new(mode: UvProjection) -> Self {
+        GenerateUvsProcessor {
+            mode,
+            units_per_tile: 1.0,
+            force: false,
+        }
+    }
+
+    pub fn with_units_per_tile(mut self, units_per_tile: f32) -> Self {
+        self.units_per_tile = units_per_tile;
+        self
+    }
+
+    /* Replace existing UVs instead of skipping meshes that already have them */
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /* Index of the largest component of v, along with its sign */
+    fn dominant_axis(v: &glm::Vec3) -> (usize, bool) {
+        let abs = [v.x.abs(), v.y.abs(), v.z.abs()];
+        let axis = if abs[0] >= abs[1] && abs[0] >= abs[2] {
+            0
+        } else if abs[1] >= abs[2] {
+            1
+        } else {
+            2
+        };
+        (axis, v[axis] >= 0.0)
+    }
+
+    fn project(&self, position: &glm::Vec3, min: &glm::Vec3, max: &glm::Vec3, axis: usize, positive: bool) -> glm::Vec2 {
+        // The two remaining axes, ordered so the projection is not mirrored when looking at the face
+        let (u_axis, v_axis) = match axis {
+            0 => (2, 1),
+            1 => (0, 2),
+            _ => (0, 1),
+        };
+        let flip_u = match axis {
+            0 => positive,
+            1 => !positive,
+            _ => !positive,
+        };
+
+        let u = if flip_u { max[u_axis] - position[u_axis] } else { position[u_axis] - min[u_axis] };
+        let v = position[v_axis] - min[v_axis];
+        glm::vec2(u, v) / self.units_per_tile
+    }
+}
+
+impl MeshProcessor for GenerateUvsProcessor {
+    fn process(&self, mesh: &mut Mesh) {
+        if mesh.vertices.is_empty() || (!mesh.uvs.is_empty() && !self.force) {
+            return;
+        }
+
+        let mut min = mesh.vertices[0];
+        let mut max = mesh.vertices[0];
+        for vertex in &mesh.vertices {
+            min = glm::min(min, *vertex);
+            max = glm::max(max, *vertex);
+        }
+
+        let face_normals: Vec<glm::Vec3> = mesh.faces.iter()
+            // Faces without area do not favour any axis
+            .map(|face| calculate_surface_normal(face, &mesh.vertices).unwrap_or(glm::vec3(0.0, 0.0, 0.0)))
+            .collect();
+
+        // Every vertex starts out without a uv, the first face to use it decides its value
+        let original_count = mesh.vertices.len();
+        let mut uvs: Vec<Option<glm::Vec2>> = vec![None; original_count];
+        let mut copies: Vec<Vec<usize>> = vec![Vec::new(); original_count];
+        let mut copied_from = Vec::new();
+
+        for (face_index, face) in mesh.faces.iter_mut().enumerate() {
+            let (axis, positive) = Self::dominant_axis(&face_normals[face_index]);
+            let positive = positive || self.mode == UvProjection::Planar;
+
+            for index in face.indices.iter_mut() {
+                let original = *index as usize;
+                let uv = self.project(&mesh.vertices[original], &min, &max, axis, positive);
+
+                let existing = std::iter::once(original)
+                    .chain(copies[original].iter().copied())
+                    .find(|candidate| uvs[*candidate].is_none_or(|existing| existing == uv));
+
+                let target = match existing {
+                    Some(target) => target,
+                    None => {
+                        mesh.vertices.push(mesh.vertices[original]);
+                        if !mesh.normals.is_empty() {
+                            mesh.normals.push(mesh.normals[original]);
+                        }
+                        uvs.push(None);
+                        copied_from.push(original);
+                        copies[original].push(mesh.vertices.len() - 1);
+                        mesh.vertices.len() - 1
+                    }
+                };
+
+                uvs[target] = Some(uv);
+                *index = target as i32;
+            }
+        }
+
+        // Other uv sets have to follow the copies, the main one is replaced
+        let source: Vec<usize> = (0..original_count).chain(copied_from).collect();
+        mesh.remap_vertex_attributes(&source);
+        let generated: Vec<glm::Vec2> = uvs.into_iter().map(|uv| uv.unwrap_or(glm::vec2(0.0, 0.0))).collect();
+        match mesh.uv_sets.first_mut() {
+            Some(main) => main.uvs = generated.clone(),
+            None => {
+                mesh.add_uv_set(GENERATED_UV_SET, generated.clone());
+            }
+        }
+        mesh.uvs = generated;
+
+        mesh.copy_hard_edges(&copies);
+    }
+
+    fn fingerprint(&self) -> u64 {
+        fingerprint_of(&[self.mode as u64, self.units_per_tile.to_bits() as u64, self.force as u64])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+
+    fn create_unit_cube() -> Mesh {
+        let vertices = vec![
+            glm::vec3(-0.5, -0.5, -0.5),
+            glm::vec3(0.5, -0.5, -0.5),
+            glm::vec3(0.5, 0.5, -0.5),
+            glm::vec3(-0.5, 0.5, -0.5),
+            glm::vec3(-0.5, -0.5, 0.5),
+            glm::vec3(0.5, -0.5, 0.5),
+            glm::vec3(0.5, 0.5, 0.5),
+            glm::vec3(-0.5, 0.5, 0.5),
+        ];
+
+        let faces = vec![
+            Face::new(vec![0, 3, 2, 1]),
+            Face::new(vec![4, 5, 6, 7]),
+            Face::new(vec![0, 1, 5, 4]),
+            Face::new(vec![2, 3, 7, 6]),
+            Face::new(vec![1, 2, 6, 5]),
+            Face::new(vec![0, 4, 7, 3]),
+        ];
+
+        Mesh::new("cube".to_string(), vertices, faces)
+    }
+
+    #[test]
+    fn process_should_map_every_cube_face_to_unit_square_with_box_projection() {
+        // Arrange
+        let mut mesh = create_unit_cube();
+        let sut = GenerateUvsProcessor::new(UvProjection::Box);
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.uvs.len(), mesh.vertices.len());
+        for face in &mesh.faces {
+            let mut corners: Vec<(i32, i32)> = face.indices.iter()
+                .map(|i| {
+                    let uv = mesh.uvs[*i as usize];
+                    ((uv.x * 1000.0).round() as i32, (uv.y * 1000.0).round() as i32)
+                })
+                .collect();
+            corners.sort();
+            assert_eq!(corners, vec![(0, 0), (0, 1000), (1000, 0), (1000, 1000)]);
+        }
+    }
+
+    #[test]
+    fn process_should_project_every_face_along_its_own_axis_with_planar_projection() {
+        // Arrange
+        let mut mesh = create_unit_cube();
+        let sut = GenerateUvsProcessor::new(UvProjection::Planar);
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        // Every face covers the whole square, so corners shared by faces of different axes are split
+        assert!(mesh.vertices.len() > 8);
+        let uvs = |face: usize| mesh.faces[face].indices.iter().map(|i| mesh.uvs[*i as usize]).collect::<Vec<_>>();
+        assert_eq!(uvs(0), vec![glm::vec2(0.0, 0.0), glm::vec2(0.0, 1.0), glm::vec2(1.0, 1.0), glm::vec2(1.0, 0.0)]);
+        assert_eq!(uvs(1), vec![glm::vec2(0.0, 0.0), glm::vec2(1.0, 0.0), glm::vec2(1.0, 1.0), glm::vec2(0.0, 1.0)]);
+        for face in &mesh.faces {
+            let mut corners: Vec<(i32, i32)> = face.indices.iter()
+                .map(|i| (mesh.uvs[*i as usize].x.round() as i32, mesh.uvs[*i as usize].y.round() as i32))
+                .collect();
+            corners.sort();
+            assert_eq!(corners, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+        }
+    }
+
+    #[test]
+    fn process_should_scale_uvs_by_units_per_tile() {
+        // Arrange
+        let mut mesh = create_unit_cube();
+        let sut = GenerateUvsProcessor::new(UvProjection::Planar).with_units_per_tile(0.5);
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        let max_u = mesh.uvs.iter().map(|uv| uv.x).fold(0.0, f32::max);
+        let max_v = mesh.uvs.iter().map(|uv| uv.y).fold(0.0, f32::max);
+        assert!((max_u - 2.0).abs() < 0.0001);
+        assert!((max_v - 2.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn process_should_skip_meshes_with_uvs_unless_forced() {
+        // Arrange
+        let mut mesh = create_unit_cube();
+        mesh.uvs = vec![glm::vec2(0.25, 0.25); 8];
+        let mut forced_mesh = create_unit_cube();
+        forced_mesh.uvs = vec![glm::vec2(0.25, 0.25); 8];
+
+        // Act
+        GenerateUvsProcessor::new(UvProjection::Box).process(&mut mesh);
+        GenerateUvsProcessor::new(UvProjection::Box).with_force(true).process(&mut forced_mesh);
+
+        // Assert
+        assert_eq!(mesh.vertices.len(), 8);
+        assert!(mesh.uvs.iter().all(|uv| *uv == glm::vec2(0.25, 0.25)));
+        assert!(forced_mesh.uvs.iter().any(|uv| *uv != glm::vec2(0.25, 0.25)));
+    }
+}
\ No newline at end of file
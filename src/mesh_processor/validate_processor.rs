@@ -0,0 +1,290 @@
+use crate::mesh_processor::{fingerprint_of, MeshProcessor};
+use crate::scene::mesh::Mesh;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValidationIssueKind {
+    IndexOutOfRange,
+    NonFinitePosition,
+    ZeroAreaFace,
+    // Edge only used by a single face, the mesh is not closed
+    OpenEdge,
+    // Edge used by more than two faces
+    NonManifoldEdge,
+    // Edge used twice in the same direction, so the faces on either side disagree on winding
+    InconsistentWinding,
+    UnreasonableBounds,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationIssue {
+    pub kind: ValidationIssueKind,
+    pub severity: Severity,
+    pub mesh: String,
+    pub face: Option<usize>,
+    pub vertex: Option<usize>,
+}
+
+/* Shared list of issues found by a ValidateMeshProcessor. Keep a clone around before handing the processor to
+   import_fbx to read the findings afterwards. */
+#[derive(Clone, Default)]
+pub struct ValidationReport {
+    issues: Arc<Mutex<Vec<ValidationIssue>>>,
+}
+
+impl ValidationReport {
+    pub fn issues(&self) -> Vec<ValidationIssue> {
+        self.issues.lock().unwrap().clone()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.issues.lock().unwrap().iter().any(|i| i.severity == Severity::Error)
+    }
+
+    fn push(&self, issue: ValidationIssue) {
+        self.issues.lock().unwrap().push(issue);
+    }
+}
+
+/* Checks meshes for problems without modifying them: out of range indices, non finite positions, degenerate faces,
+   open or non-manifold edges, inconsistent winding and unreasonably sized bounds. */
+pub struct ValidateMeshProcessor {
+    report: ValidationReport,
+    // Number of zero area faces tolerated before they are reported
+    max_zero_area_faces: usize,
+    zero_area_epsilon: f32,
+    max_extent: f32,
+    fail_on_error: bool,
+}
+
+impl ValidateMeshProcessor {
+    pub fn new() -> Self {
+        ValidateMeshProcessor {
+            report: ValidationReport::default(),
+            max_zero_area_faces: 0,
+            zero_area_epsilon: 0.000001,
+            max_extent: 100000.0,
+            fail_on_error: false,
+        }
+    }
+
+    pub fn with_max_zero_area_faces(mut self, max_zero_area_faces: usize) -> Self {
+        self.max_zero_area_faces = max_zero_area_faces;
+        self
+    }
+
+    pub fn with_zero_area_epsilon(mut self, zero_area_epsilon: f32) -> Self {
+        self.zero_area_epsilon = zero_area_epsilon;
+        self
+    }
+
+    pub fn with_max_extent(mut self, max_extent: f32) -> Self {
+        self.max_extent = max_extent;
+        self
+    }
+
+    /* Makes import_fbx fail when any Error severity issue is found */
+    pub fn with_fail_on_error(mut self, fail_on_error: bool) -> Self {
+        self.fail_on_error = fail_on_error;
+        self
+    }
+
+    pub fn report(&self) -> ValidationReport {
+        self.report.clone()
+    }
+
+    fn add(&self, mesh: &Mesh, kind: ValidationIssueKind, severity: Severity, face: Option<usize>, vertex: Option<usize>) {
+        self.report.push(ValidationIssue {
+            kind,
+            severity,
+            mesh: mesh.name.clone(),
+            face,
+            vertex,
+        });
+    }
+
+    fn face_area(mesh: &Mesh, indices: &[i32]) -> f32 {
+        let first = mesh.vertices[indices[0] as usize];
+        let mut sum = glm::vec3(0.0, 0.0, 0.0);
+        for i in 1..(indices.len() - 1) {
+            let a = mesh.vertices[indices[i] as usize] - first;
+            let b = mesh.vertices[indices[i + 1] as usize] - first;
+            sum = sum + glm::cross(a, b);
+        }
+        glm::length(sum) * 0.5
+    }
+}
+
+impl Default for ValidateMeshProcessor {
+    fn default() -> Self {
+        ValidateMeshProcessor::new()
+    }
+}
+
+impl MeshProcessor for ValidateMeshProcessor {
+    fn process(&self, mesh: &mut Mesh) {
+        for (index, vertex) in mesh.vertices.iter().enumerate() {
+            if !(vertex.x.is_finite() && vertex.y.is_finite() && vertex.z.is_finite()) {
+                self.add(mesh, ValidationIssueKind::NonFinitePosition, Severity::Error, None, Some(index));
+            }
+        }
+
+        let mut valid_faces = Vec::with_capacity(mesh.faces.len());
+        for (face_index, face) in mesh.faces.iter().enumerate() {
+            match face.indices.iter().find(|i| **i < 0 || **i as usize >= mesh.vertices.len()) {
+                Some(index) => self.add(mesh, ValidationIssueKind::IndexOutOfRange, Severity::Error, Some(face_index), Some(*index as usize)),
+                None => valid_faces.push(face_index),
+            }
+        }
+
+        let zero_area_faces: Vec<usize> = valid_faces.iter()
+            .copied()
+            .filter(|f| {
+                let indices = &mesh.faces[*f].indices;
+                // Faces with a NaN area, from non finite positions, count as degenerate as well
+                indices.len() < 3 || Self::face_area(mesh, indices).partial_cmp(&self.zero_area_epsilon) != Some(Ordering::Greater)
+            })
+            .collect();
+        if zero_area_faces.len() > self.max_zero_area_faces {
+            for face in zero_area_faces {
+                self.add(mesh, ValidationIssueKind::ZeroAreaFace, Severity::Warning, Some(face), None);
+            }
+        }
+
+        // Count how often every directed edge is used, watertight meshes use every edge once in each direction
+        let mut edge_uses: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+        let mut directed_uses: HashMap<(i32, i32), usize> = HashMap::new();
+        for face_index in &valid_faces {
+            let indices = &mesh.faces[*face_index].indices;
+            for i in 0..indices.len() {
+                let a = indices[i];
+                let b = indices[(i + 1) % indices.len()];
+                edge_uses.entry((a.min(b), a.max(b))).or_default().push(*face_index);
+                *directed_uses.entry((a, b)).or_insert(0) += 1;
+            }
+        }
+
+        let mut edges: Vec<&(i32, i32)> = edge_uses.keys().collect();
+        edges.sort();
+        for edge in edges {
+            let faces = &edge_uses[edge];
+            let kind = match faces.len() {
+                1 => Some(ValidationIssueKind::OpenEdge),
+                2 => None,
+                _ => Some(ValidationIssueKind::NonManifoldEdge),
+            };
+            if let Some(kind) = kind {
+                self.add(mesh, kind, Severity::Warning, Some(faces[0]), Some(edge.0 as usize));
+            }
+
+            let forward = directed_uses.get(edge).copied().unwrap_or(0);
+            let backward = directed_uses.get(&(edge.1, edge.0)).copied().unwrap_or(0);
+            if faces.len() == 2 && (forward == 2 || backward == 2) {
+                self.add(mesh, ValidationIssueKind::InconsistentWinding, Severity::Warning, Some(faces[1]), Some(edge.0 as usize));
+            }
+        }
+
+        let finite: Vec<&glm::Vec3> = mesh.vertices.iter()
+            .filter(|v| v.x.is_finite() && v.y.is_finite() && v.z.is_finite())
+            .collect();
+        if let Some(first) = finite.first() {
+            let mut min = **first;
+            let mut max = **first;
+            for vertex in &finite {
+                min = glm::min(min, **vertex);
+                max = glm::max(max, **vertex);
+            }
+            let extent = glm::length(max - min);
+            if extent == 0.0 || extent > self.max_extent {
+                self.add(mesh, ValidationIssueKind::UnreasonableBounds, Severity::Warning, None, None);
+            }
+        }
+    }
+
+    fn fingerprint(&self) -> u64 {
+        fingerprint_of(&[self.max_zero_area_faces as u64, self.zero_area_epsilon.to_bits() as u64, self.max_extent.to_bits() as u64, self.fail_on_error as u64])
+    }
+
+    fn has_failed(&self) -> bool {
+        self.fail_on_error && self.report.has_errors()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::fixture;
+
+    #[test]
+    fn process_should_report_nothing_for_closed_cube() {
+        // Arrange
+        let mut mesh = fixture::cube(-1.0, 1.0);
+        let sut = ValidateMeshProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert!(sut.report().issues().is_empty());
+        assert!(!sut.has_failed());
+    }
+
+    #[test]
+    fn process_should_report_open_edges_when_a_face_is_missing() {
+        // Arrange
+        let mut mesh = fixture::cube(-1.0, 1.0);
+        mesh.faces.remove(1);
+        let sut = ValidateMeshProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        let issues = sut.report().issues();
+        assert_eq!(issues.len(), 4);
+        assert!(issues.iter().all(|i| i.kind == ValidationIssueKind::OpenEdge && i.severity == Severity::Warning));
+        assert_eq!(mesh.faces.len(), 5);
+    }
+
+    #[test]
+    fn process_should_fail_on_out_of_range_index_when_requested() {
+        // Arrange
+        let mut mesh = fixture::cube(-1.0, 1.0);
+        mesh.faces[0].indices[1] = 42;
+        let sut = ValidateMeshProcessor::new().with_fail_on_error(true);
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        let issues = sut.report().issues();
+        assert!(issues.iter().any(|i| i.kind == ValidationIssueKind::IndexOutOfRange && i.face == Some(0)));
+        assert!(sut.has_failed());
+    }
+
+    #[test]
+    fn process_should_report_faces_with_nan_positions_as_zero_area() {
+        // Arrange
+        let mut mesh = fixture::cube(-1.0, 1.0);
+        mesh.vertices[6].y = f32::NAN;
+        let sut = ValidateMeshProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        let zero_area: Vec<Option<usize>> = sut.report().issues().iter()
+            .filter(|i| i.kind == ValidationIssueKind::ZeroAreaFace)
+            .map(|i| i.face)
+            .collect();
+        assert_eq!(zero_area, vec![Some(0), Some(3), Some(4)]);
+    }
+}
\ No newline at end of file
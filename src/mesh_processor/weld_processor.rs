@@ -0,0 +1,240 @@
+use crate::mesh_processor::{fingerprint_of, MeshProcessor};
+use crate::scene::mesh::{Mesh, Smoothing};
+use std::collections::HashMap;
+
+/* Merges vertices that share position and every other attribute present on the mesh. Candidates are found through a
+   spatial hash keyed on position only, normals and uvs are compared afterwards so UV seams and hard edges survive. */
+pub struct WeldVerticesProcessor {
+    position_epsilon: f32,
+    // Maximum angle between normals in radians
+    normal_angle_epsilon: f32,
+    uv_epsilon: f32,
+}
+
+impl WeldVerticesProcessor {
+    pub fn new() -> Self {
+        WeldVerticesProcessor {
+            position_epsilon: 0.00001,
+            normal_angle_epsilon: 0.0175,
+            uv_epsilon: 0.00001,
+        }
+    }
+
+    pub fn with_position_epsilon(mut self, position_epsilon: f32) -> Self {
+        self.position_epsilon = position_epsilon;
+        self
+    }
+
+    pub fn with_normal_angle_epsilon(mut self, normal_angle_epsilon: f32) -> Self {
+        self.normal_angle_epsilon = normal_angle_epsilon;
+        self
+    }
+
+    pub fn with_uv_epsilon(mut self, uv_epsilon: f32) -> Self {
+        self.uv_epsilon = uv_epsilon;
+        self
+    }
+
+    fn cell(&self, position: &glm::Vec3) -> (i64, i64, i64) {
+        let cell_size = self.position_epsilon.max(f32::MIN_POSITIVE);
+        (
+            (position.x / cell_size).floor() as i64,
+            (position.y / cell_size).floor() as i64,
+            (position.z / cell_size).floor() as i64,
+        )
+    }
+
+    fn can_weld(&self, mesh: &Mesh, a: usize, b: usize) -> bool {
+        // NaN positions are never welded
+        let distance = glm::length(mesh.vertices[a] - mesh.vertices[b]);
+        if distance.is_nan() || distance > self.position_epsilon {
+            return false;
+        }
+
+        if !mesh.normals.is_empty() {
+            let cos_angle = glm::dot(mesh.normals[a], mesh.normals[b]).clamp(-1.0, 1.0);
+            if glm::acos(cos_angle) > self.normal_angle_epsilon {
+                return false;
+            }
+        }
+
+        if !mesh.uvs.is_empty() {
+            let uv_distance = glm::length(mesh.uvs[a] - mesh.uvs[b]);
+            if uv_distance.is_nan() || uv_distance > self.uv_epsilon {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl Default for WeldVerticesProcessor {
+    fn default() -> Self {
+        WeldVerticesProcessor::new()
+    }
+}
+
+impl MeshProcessor for WeldVerticesProcessor {
+    fn process(&self, mesh: &mut Mesh) {
+        let mut grid: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+        let mut remap = Vec::with_capacity(mesh.vertices.len());
+        let mut kept = Vec::new();
+
+        for vertex in 0..mesh.vertices.len() {
+            let (x, y, z) = self.cell(&mesh.vertices[vertex]);
+
+            // Vertices within epsilon can end up in a neighbouring cell
+            let mut target = None;
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        if let Some(candidates) = grid.get(&(x + dx, y + dy, z + dz)) {
+                            for candidate in candidates {
+                                if self.can_weld(mesh, kept[*candidate], vertex) {
+                                    target = Some(*candidate);
+                                    break 'search;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            match target {
+                Some(target) => remap.push(target),
+                None => {
+                    grid.entry((x, y, z)).or_default().push(kept.len());
+                    remap.push(kept.len());
+                    kept.push(vertex);
+                }
+            }
+        }
+
+        if kept.len() == mesh.vertices.len() {
+            return;
+        }
+
+        mesh.vertices = kept.iter().map(|i| mesh.vertices[*i]).collect();
+        if !mesh.normals.is_empty() {
+            mesh.normals = kept.iter().map(|i| mesh.normals[*i]).collect();
+        }
+        mesh.remap_vertex_attributes(&kept);
+
+        for face in &mut mesh.faces {
+            for index in &mut face.indices {
+                *index = remap[*index as usize] as i32;
+            }
+        }
+
+        if let Smoothing::ByEdge(hard_edges) = &mesh.smoothing {
+            let remapped = hard_edges.iter()
+                .map(|(a, b)| {
+                    let a = remap[*a as usize] as i32;
+                    let b = remap[*b as usize] as i32;
+                    (a.min(b), a.max(b))
+                })
+                .collect();
+            mesh.smoothing = Smoothing::ByEdge(remapped);
+        }
+    }
+
+    fn fingerprint(&self) -> u64 {
+        fingerprint_of(&[self.position_epsilon.to_bits() as u64, self.normal_angle_epsilon.to_bits() as u64, self.uv_epsilon.to_bits() as u64])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+
+    /* Cube with its own 4 vertices for every side, like it would come out of an exporter with UVs */
+    fn create_unwelded_cube() -> Mesh {
+        let corners = [
+            [glm::vec3(-1.0, -1.0, -1.0), glm::vec3(-1.0, 1.0, -1.0), glm::vec3(1.0, 1.0, -1.0), glm::vec3(1.0, -1.0, -1.0)],
+            [glm::vec3(-1.0, -1.0, 1.0), glm::vec3(1.0, -1.0, 1.0), glm::vec3(1.0, 1.0, 1.0), glm::vec3(-1.0, 1.0, 1.0)],
+            [glm::vec3(-1.0, -1.0, -1.0), glm::vec3(1.0, -1.0, -1.0), glm::vec3(1.0, -1.0, 1.0), glm::vec3(-1.0, -1.0, 1.0)],
+            [glm::vec3(1.0, 1.0, -1.0), glm::vec3(-1.0, 1.0, -1.0), glm::vec3(-1.0, 1.0, 1.0), glm::vec3(1.0, 1.0, 1.0)],
+            [glm::vec3(1.0, -1.0, -1.0), glm::vec3(1.0, 1.0, -1.0), glm::vec3(1.0, 1.0, 1.0), glm::vec3(1.0, -1.0, 1.0)],
+            [glm::vec3(-1.0, -1.0, -1.0), glm::vec3(-1.0, -1.0, 1.0), glm::vec3(-1.0, 1.0, 1.0), glm::vec3(-1.0, 1.0, -1.0)],
+        ];
+
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        for side in corners.iter() {
+            let start = vertices.len() as i32;
+            vertices.extend_from_slice(side);
+            faces.push(Face::new(vec![start, start + 1, start + 2, start + 3]));
+        }
+
+        Mesh::new("cube".to_string(), vertices, faces)
+    }
+
+    #[test]
+    fn process_should_weld_identical_colocated_vertices() {
+        // Arrange
+        let mut mesh = create_unwelded_cube();
+        let sut = WeldVerticesProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.vertices.len(), 8);
+        assert!(mesh.faces.iter().all(|f| f.indices.iter().all(|i| *i >= 0 && *i < 8)));
+    }
+
+    #[test]
+    fn process_should_not_weld_across_uv_seams() {
+        // Arrange
+        let mut mesh = create_unwelded_cube();
+        // Every side gets its own tile in the UV layout
+        let square = [glm::vec2(0.0, 0.0), glm::vec2(1.0, 0.0), glm::vec2(1.0, 1.0), glm::vec2(0.0, 1.0)];
+        mesh.uvs = (0..24).map(|i| (square[i % 4] + glm::vec2((i / 4) as f32, 0.0)) / 6.0).collect();
+        let sut = WeldVerticesProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.vertices.len(), 24);
+    }
+
+    #[test]
+    fn process_should_not_weld_vertices_with_diverging_normals() {
+        // Arrange
+        let mut mesh = create_unwelded_cube();
+        mesh.normals = mesh.faces.iter()
+            .flat_map(|f| {
+                let normal = crate::polygon_utils::calculate_surface_normal(f, &mesh.vertices).unwrap();
+                vec![normal; 4]
+            })
+            .collect();
+        let sut = WeldVerticesProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.vertices.len(), 24);
+    }
+    #[test]
+    fn process_should_not_weld_nan_vertices() {
+        // Arrange
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(f32::NAN, 0.0, 0.0),
+            glm::vec3(0.0, f32::NAN, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+        ];
+        let mut mesh = Mesh::new("nan".to_string(), vertices, vec![Face::new(vec![0, 1, 2]), Face::new(vec![1, 2, 3])]);
+        let sut = WeldVerticesProcessor::new();
+
+        // Act
+        sut.process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.vertices.len(), 4);
+    }
+}
\ No newline at end of file
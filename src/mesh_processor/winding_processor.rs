@@ -0,0 +1,204 @@
+use crate::warnings::warning;
+use crate::mesh_processor::{fingerprint_of, MeshProcessor};
+use crate::scene::mesh::{Face, Mesh};
+use std::collections::{HashMap, VecDeque};
+
+/* Directed edges of a face, from each corner to the next */
+fn directed_edges(face: &Face) -> impl Iterator<Item = (i32, i32)> + '_ {
+    let len = face.indices.len();
+    (0..len).map(move |i| (face.indices[i], face.indices[(i + 1) % len]))
+}
+
+/* Six times the volume enclosed by the faces, positive when they are wound counter clockwise seen from outside */
+fn signed_volume(mesh: &Mesh, faces: &[usize]) -> f32 {
+    let mut volume = 0.0;
+    for face in faces {
+        let positions: Vec<glm::Vec3> = mesh.faces[*face].positions(&mesh.vertices).collect();
+        for i in 1..positions.len().saturating_sub(1) {
+            volume += glm::dot(positions[0], glm::cross(positions[i], positions[i + 1]));
+        }
+    }
+    volume
+}
+
+/* Rewinds faces so that neighbours agree on their orientation, fixing faces that were written backwards such as those
+   left by a mirror modifier. Starting from the first face of every connected part of the mesh, a face sharing an edge
+   with one already visited is reversed when it runs along the edge in the same direction. Edges shared by more than two
+   faces have no consistent orientation and are not crossed. */
+pub struct MakeWindingConsistentProcessor {
+    orient_outward: bool,
+}
+
+impl MakeWindingConsistentProcessor {
+    pub fn new() -> Self {
+        MakeWindingConsistentProcessor {
+            orient_outward: false,
+        }
+    }
+
+    /* Afterwards reverse every part of the mesh enclosing a negative volume, so closed parts face outwards */
+    pub fn with_orient_outward(mut self, orient_outward: bool) -> Self {
+        self.orient_outward = orient_outward;
+        self
+    }
+}
+
+impl Default for MakeWindingConsistentProcessor {
+    fn default() -> Self {
+        MakeWindingConsistentProcessor::new()
+    }
+}
+
+impl MeshProcessor for MakeWindingConsistentProcessor {
+    fn process(&self, mesh: &mut Mesh) {
+        // Faces using every undirected edge, lowest index first, along with the direction they run along it
+        let mut edges: HashMap<(i32, i32), Vec<(usize, bool)>> = HashMap::new();
+        for (index, face) in mesh.faces.iter().enumerate() {
+            for (a, b) in directed_edges(face).filter(|(a, b)| a != b) {
+                edges.entry((a.min(b), a.max(b))).or_default().push((index, a < b));
+            }
+        }
+
+        let mut reported = Vec::new();
+        let mut flipped = vec![false; mesh.faces.len()];
+        let mut visited = vec![false; mesh.faces.len()];
+        let mut flip_count = 0;
+        for seed in 0..mesh.faces.len() {
+            if visited[seed] {
+                continue;
+            }
+
+            visited[seed] = true;
+            let mut component = vec![seed];
+            let mut pending = VecDeque::from(vec![seed]);
+            while let Some(face) = pending.pop_front() {
+                for (a, b) in directed_edges(&mesh.faces[face]).filter(|(a, b)| a != b) {
+                    let key = (a.min(b), a.max(b));
+                    let users = &edges[&key];
+                    if users.len() > 2 {
+                        if !reported.contains(&key) {
+                            warning!("Edge {}-{} of mesh '{}' is shared by {} faces, not making the winding consistent across it.",
+                                      key.0, key.1, mesh.name, users.len());
+                            reported.push(key);
+                        }
+                        continue;
+                    }
+
+                    // Direction the face runs along the edge once it has been flipped or not
+                    let forward = (a < b) != flipped[face];
+                    for (neighbour, neighbour_forward) in users.iter().filter(|(n, _)| *n != face) {
+                        if visited[*neighbour] {
+                            continue;
+                        }
+                        visited[*neighbour] = true;
+                        // Neighbours agree when they run along the shared edge in opposite directions
+                        flipped[*neighbour] = *neighbour_forward == forward;
+                        component.push(*neighbour);
+                        pending.push_back(*neighbour);
+                    }
+                }
+            }
+
+            if self.orient_outward {
+                // The faces have not been reversed yet, so the ones about to be count against the volume
+                let (kept, reversed): (Vec<usize>, Vec<usize>) = component.iter().partition(|f| !flipped[**f]);
+                if signed_volume(mesh, &kept) - signed_volume(mesh, &reversed) < 0.0 {
+                    for face in &component {
+                        flipped[*face] = !flipped[*face];
+                    }
+                }
+            }
+        }
+
+        for (face, flip) in mesh.faces.iter_mut().zip(flipped.iter()) {
+            if *flip {
+                face.indices.reverse();
+                flip_count += 1;
+            }
+        }
+        if flip_count > 0 {
+            warning!("Reversed {} faces of mesh '{}' to make its winding consistent.", flip_count, mesh.name);
+        }
+    }
+
+    fn fingerprint(&self) -> u64 {
+        fingerprint_of(&[self.orient_outward as u64])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::fixture;
+
+    /* True if every edge used by two faces is run along in both directions */
+    fn is_consistent(mesh: &Mesh) -> bool {
+        let mut directed = HashMap::new();
+        for face in &mesh.faces {
+            for edge in directed_edges(face) {
+                *directed.entry(edge).or_insert(0) += 1;
+            }
+        }
+        directed.iter().all(|(_, count)| *count == 1)
+    }
+
+    #[test]
+    fn process_should_make_a_cube_with_reversed_faces_consistent() {
+        // Arrange
+        let mut mesh = fixture::triangulated_cube(-0.5, 0.5);
+        for face in [1, 4, 9].iter() {
+            mesh.faces[*face].indices.reverse();
+        }
+
+        // Act
+        MakeWindingConsistentProcessor::new().process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.faces.len(), 12);
+        assert!(is_consistent(&mesh));
+        assert!(signed_volume(&mesh, &(0..12).collect::<Vec<_>>()) > 0.0);
+    }
+
+    #[test]
+    fn process_should_orient_every_component_outward() {
+        // Arrange
+        let first = fixture::triangulated_cube(-0.5, 0.5);
+        let second = fixture::triangulated_cube(1.5, 2.5);
+        let vertices = first.vertices.iter().chain(&second.vertices).copied().collect();
+        // The second cube inside out, seeded from a face that is wound the wrong way
+        let mut faces = first.faces;
+        faces.extend(second.faces.into_iter().map(|f| Face::new(f.indices.iter().rev().map(|i| i + 8).collect())));
+        faces[0].indices.reverse();
+        let mut mesh = Mesh::new("cubes".to_string(), vertices, faces);
+
+        // Act
+        MakeWindingConsistentProcessor::new().with_orient_outward(true).process(&mut mesh);
+
+        // Assert
+        assert!(is_consistent(&mesh));
+        assert!(signed_volume(&mesh, &(0..12).collect::<Vec<_>>()) > 0.0);
+        assert!(signed_volume(&mesh, &(12..24).collect::<Vec<_>>()) > 0.0);
+    }
+
+    #[test]
+    fn process_should_not_cross_edges_shared_by_more_than_two_faces() {
+        // Arrange
+        // Three triangles around the edge 0-1, the third one wound against both others
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(0.5, 1.0, 0.0),
+            glm::vec3(0.5, -1.0, 0.0),
+            glm::vec3(0.5, 0.0, 1.0),
+        ];
+        let faces = vec![Face::new(vec![0, 1, 2]), Face::new(vec![1, 0, 3]), Face::new(vec![0, 1, 4])];
+        let mut mesh = Mesh::new("fin".to_string(), vertices, faces);
+
+        // Act
+        MakeWindingConsistentProcessor::new().process(&mut mesh);
+
+        // Assert
+        let indices: Vec<Vec<i32>> = mesh.faces.iter().map(|f| f.indices.clone()).collect();
+        assert_eq!(indices, vec![vec![0, 1, 2], vec![1, 0, 3], vec![0, 1, 4]]);
+    }
+}
@@ -1,20 +1,36 @@
 use crate::scene::mesh::Face;
 use num::Zero;
 
-/* Calculate surface normal for arbitrary polygon using Newell's method */
-pub fn calculate_surface_normal(face: &Face, vertices: &Vec<glm::Vec3>) -> glm::Vec3 {
-    let mut vertex_normal = glm::Vec3::zero();
+// Newell sums shorter than this belong to polygons without area, whose normal can not be normalized
+pub const MIN_NORMAL_LENGTH: f32 = 1e-10;
+
+/* Calculate surface normal for arbitrary polygon using Newell's method. Returns None for polygons without area, such
+   as collinear or coincident points. */
+pub fn calculate_surface_normal(face: &Face, vertices: &[glm::Vec3]) -> Option<glm::Vec3> {
+    polygon_normal(face.positions(vertices))
+}
 
-    for i in 0..face.indices.len() {
-        let current = vertices[face.indices[i] as usize];
-        let next = vertices[face.indices[(i + 1) % face.indices.len()] as usize];
+/* Same as calculate_surface_normal, given the corner positions of the polygon */
+pub fn polygon_normal<I: IntoIterator<Item = glm::Vec3>>(positions: I) -> Option<glm::Vec3> {
+    let mut vertex_normal = glm::Vec3::zero();
+    let mut positions = positions.into_iter();
+    let first = positions.next()?;
+    let mut current = first;
 
+    for next in positions.chain(std::iter::once(first)) {
         vertex_normal.x += (current.y - next.y) * (current.z + next.z);
         vertex_normal.y += (current.z - next.z) * (current.x + next.x);
         vertex_normal.z += (current.x - next.x) * (current.y + next.y);
+        current = next;
     }
 
-    glm::normalize(vertex_normal)
+    // NaN coordinates give a NaN length, which counts as too short
+    let length = glm::length(vertex_normal);
+    if length.is_nan() || length < MIN_NORMAL_LENGTH {
+        return None;
+    }
+
+    Some(glm::normalize(vertex_normal))
 }
 
 /* Taken from https://stackoverflow.com/questions/2049582/how-to-determine-if-a-point-is-in-a-2d-triangle*/
@@ -33,6 +49,103 @@ pub fn is_point_in_triangle_2d(point: &glm::Vec2, v0: &glm::Vec2, v1: &glm::Vec2
     !(has_neg && has_pos)
 }
 
+// Tolerances of the triangulator are this fraction of the size of the polygon, so they work the same at any scale
+pub const RELATIVE_EPSILON: f32 = 1e-5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+    Collinear,
+}
+
+/* Which way the path a -> b -> c turns. Collinear when c is at most eps away from the line through a and b, or when a
+   and b are within eps of each other. */
+pub fn orientation_2d(a: &glm::Vec2, b: &glm::Vec2, c: &glm::Vec2, eps: f32) -> Orientation {
+    let ab = *b - *a;
+    let cross = ab.x * (c.y - a.y) - ab.y * (c.x - a.x);
+    let length_squared = ab.x * ab.x + ab.y * ab.y;
+    // The cross product is the distance from the line times the length of ab, compared squared to save a square root
+    if length_squared <= eps * eps || cross * cross <= eps * eps * length_squared {
+        Orientation::Collinear
+    } else if cross > 0.0 {
+        Orientation::CounterClockwise
+    } else {
+        Orientation::Clockwise
+    }
+}
+
+/* Sum of a and b as the rounded sum and its rounding error, which add up to it exactly */
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_virtual = sum - a;
+    let a_virtual = sum - b_virtual;
+    (sum, (a - a_virtual) + (b - b_virtual))
+}
+
+/* Adds value to an expansion, a sum of non-overlapping numbers from the smallest to the largest that is never rounded */
+fn grow_expansion(expansion: &mut Vec<f64>, value: f64) {
+    let mut sum = value;
+    for component in expansion.iter_mut() {
+        let (rounded, error) = two_sum(sum, *component);
+        *component = error;
+        sum = rounded;
+    }
+    expansion.push(sum);
+}
+
+/* Same as orientation_2d without a tolerance, but always right. Nearly collinear points can make the float calculation
+   come out with the wrong sign, or different signs for the same points in another order. Adaptive like Shewchuk's
+   orient2d: the determinant is calculated in f64 and only when that is within its error bound is it summed up
+   exactly. */
+pub fn orientation_2d_exact(a: &glm::Vec2, b: &glm::Vec2, c: &glm::Vec2) -> Orientation {
+    let (ax, ay, bx, by, cx, cy) = (a.x as f64, a.y as f64, b.x as f64, b.y as f64, c.x as f64, c.y as f64);
+    let left = (bx - ax) * (cy - ay);
+    let right = (by - ay) * (cx - ax);
+    let determinant = left - right;
+    let half_epsilon = f64::EPSILON / 2.0;
+    let error_bound = (3.0 + 16.0 * half_epsilon) * half_epsilon * (left.abs() + right.abs());
+
+    let sign = if determinant.abs() > error_bound {
+        determinant
+    } else {
+        // Products of two f32 fit in a f64, so the expanded determinant only has rounding errors in its sum
+        let mut expansion = Vec::with_capacity(6);
+        for term in [bx * cy, -(bx * ay), -(ax * cy), -(by * cx), by * ax, ay * cx].iter() {
+            grow_expansion(&mut expansion, *term);
+        }
+        // The largest non-zero component outweighs all others
+        expansion.iter().rev().copied().find(|component| *component != 0.0).unwrap_or(0.0)
+    };
+
+    if sign > 0.0 {
+        Orientation::CounterClockwise
+    } else if sign < 0.0 {
+        Orientation::Clockwise
+    } else {
+        Orientation::Collinear
+    }
+}
+
+/* Same as is_point_in_triangle_2d, with points at most eps away from an edge counting as inside */
+pub fn is_point_in_triangle_2d_eps(point: &glm::Vec2, v0: &glm::Vec2, v1: &glm::Vec2, v2: &glm::Vec2, eps: f32) -> bool {
+    let sides = [orientation_2d(v0, v1, point, eps), orientation_2d(v1, v2, point, eps), orientation_2d(v2, v0, point, eps)];
+    let has_cw = sides.contains(&Orientation::Clockwise);
+    let has_ccw = sides.contains(&Orientation::CounterClockwise);
+
+    !(has_cw && has_ccw)
+}
+
+/* Largest extent of the bounding box of the points, 0 for no points */
+pub fn bounding_size_2d(points: &[glm::Vec2]) -> f32 {
+    let first = match points.first() {
+        Some(first) => *first,
+        None => return 0.0
+    };
+    let (min, max) = points.iter().fold((first, first), |(min, max), p| (glm::min(min, *p), glm::max(max, *p)));
+    (max.x - min.x).max(max.y - min.y)
+}
+
 pub fn tri_contains_other_verts_2d<'a, I>(v0: &glm::Vec2, v1: &glm::Vec2, v2: &glm::Vec2, vertices: &'a mut I) -> bool
     where I: Iterator<Item = &'a glm::Vec2>
 {
@@ -44,8 +157,180 @@ pub fn tri_contains_other_verts_2d<'a, I>(v0: &glm::Vec2, v1: &glm::Vec2, v2: &g
     false
 }
 
-pub fn project_triangle_into_2d(face: &Face, vertices: &Vec<glm::Vec3>) -> Vec<glm::Vec2> {
-    let surface_normal = calculate_surface_normal(face, vertices);
+/* Same as tri_contains_other_verts_2d, with points at most eps away from an edge counting as inside */
+pub fn tri_contains_other_verts_2d_eps<'a, I>(v0: &glm::Vec2, v1: &glm::Vec2, v2: &glm::Vec2, vertices: &'a mut I, eps: f32) -> bool
+    where I: Iterator<Item = &'a glm::Vec2>
+{
+    vertices.any(|vertex| vertex != v0 && vertex != v1 && vertex != v2 && is_point_in_triangle_2d_eps(vertex, v0, v1, v2, eps))
+}
+
+/* Signed area of a 2D polygon using the shoelace formula. Positive for counter-clockwise winding, 0 for fewer than 3
+   points. */
+pub fn polygon_area_2d(vertices: &[glm::Vec2]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..vertices.len() {
+        let current = vertices[i];
+        let next = vertices[(i + 1) % vertices.len()];
+        area += current.x * next.y - next.x * current.y;
+    }
+
+    area * 0.5
+}
+
+/* Centre of mass of the area of a 2D polygon. Polygons without area have none, for them this is the average of their
+   points. None for no points. */
+pub fn polygon_centroid_2d(vertices: &[glm::Vec2]) -> Option<glm::Vec2> {
+    let first = *vertices.first()?;
+    let mut sum = glm::vec2(0.0, 0.0);
+    let mut twice_area = 0.0;
+    // Relative to the first point, which keeps the products small for polygons far from the origin
+    for i in 0..vertices.len() {
+        let current = vertices[i] - first;
+        let next = vertices[(i + 1) % vertices.len()] - first;
+        let cross = current.x * next.y - next.x * current.y;
+        sum = sum + (current + next) * cross;
+        twice_area += cross;
+    }
+
+    if twice_area.abs() <= f32::EPSILON * bounding_size_2d(vertices).powi(2) {
+        let total = vertices.iter().fold(glm::vec2(0.0, 0.0), |total, v| total + (*v - first));
+        return Some(first + total / vertices.len() as f32);
+    }
+    Some(first + sum / (3.0 * twice_area))
+}
+
+/* True if every corner of the polygon turns the same way and it goes around only once. Corners within eps of the line
+   through their neighbours do not count as a turn either way. Polygons with fewer than 3 points or without any turn,
+   like collinear ones, are not convex. */
+pub fn is_convex_2d(vertices: &[glm::Vec2], eps: f32) -> bool {
+    let n = vertices.len();
+    if n < 3 {
+        return false;
+    }
+
+    let mut turns = None;
+    let mut total_angle = 0.0;
+    for i in 0..n {
+        let previous = &vertices[(i + n - 1) % n];
+        let current = &vertices[i];
+        let next = &vertices[(i + 1) % n];
+        let turn = orientation_2d(previous, current, next, eps);
+        if turn == Orientation::Collinear {
+            continue;
+        }
+        if *turns.get_or_insert(turn) != turn {
+            return false;
+        }
+
+        let a = *current - *previous;
+        let b = *next - *current;
+        total_angle += (a.x * b.y - a.y * b.x).atan2(glm::dot(a, b));
+    }
+
+    // Star shaped polygons also turn one way throughout, but go around more than once
+    turns.is_some() && (total_angle.abs() - 2.0 * std::f32::consts::PI).abs() < 0.1
+}
+
+/* True if the segments a0-a1 and b0-b1 cross each other. Touching end points do not count. */
+pub fn segments_intersect_2d(a0: &glm::Vec2, a1: &glm::Vec2, b0: &glm::Vec2, b1: &glm::Vec2) -> bool {
+    fn cross(origin: &glm::Vec2, a: &glm::Vec2, b: &glm::Vec2) -> f32 {
+        (a.x - origin.x) * (b.y - origin.y) - (a.y - origin.y) * (b.x - origin.x)
+    }
+
+    let d1 = cross(b0, b1, a0);
+    let d2 = cross(b0, b1, a1);
+    let d3 = cross(a0, a1, b0);
+    let d4 = cross(a0, a1, b1);
+
+    ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0)) && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+}
+
+/* Convex hull of the given subset of vertices using Andrew's monotone chain. Returns the hull in counter-clockwise order. */
+pub fn convex_hull_2d(vertices: &[glm::Vec2], indices: &[usize]) -> Vec<usize> {
+    fn cross(origin: &glm::Vec2, a: &glm::Vec2, b: &glm::Vec2) -> f32 {
+        (a.x - origin.x) * (b.y - origin.y) - (a.y - origin.y) * (b.x - origin.x)
+    }
+
+    let mut sorted = indices.to_vec();
+    sorted.sort_by(|a, b| {
+        let va = &vertices[*a];
+        let vb = &vertices[*b];
+        va.x.partial_cmp(&vb.x).unwrap_or(std::cmp::Ordering::Equal)
+            .then(va.y.partial_cmp(&vb.y).unwrap_or(std::cmp::Ordering::Equal))
+    });
+
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let mut hull: Vec<usize> = Vec::with_capacity(sorted.len() * 2);
+    for pass in 0..2 {
+        let start = hull.len();
+        let candidates: Vec<usize> = if pass == 0 { sorted.clone() } else { sorted.iter().rev().copied().collect() };
+        for i in candidates {
+            while hull.len() >= start + 2
+                && cross(&vertices[hull[hull.len() - 2]], &vertices[hull[hull.len() - 1]], &vertices[i]) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(i);
+        }
+        // Last point of each chain is the first point of the other one
+        hull.pop();
+    }
+
+    hull
+}
+
+/* Area of a polygon in 3D from its Newell normal, 0 for fewer than 3 indices */
+pub fn polygon_area(indices: &[i32], vertices: &[glm::Vec3]) -> f32 {
+    let mut sum = glm::Vec3::zero();
+    let positions: Vec<glm::Vec3> = indices.iter().map(|i| vertices[*i as usize]).collect();
+    for i in 0..positions.len() {
+        let current = positions[i];
+        let next = positions[(i + 1) % positions.len()];
+        sum = sum + glm::cross(current, next);
+    }
+    glm::length(sum) / 2.0
+}
+
+/* Centre of mass of the area of a polygon in 3D, found in its projection and lifted back onto the polygon by fanning
+   from the first corner. None for no indices. */
+pub fn polygon_centroid(indices: &[i32], vertices: &[glm::Vec3]) -> Option<glm::Vec3> {
+    let positions: Vec<glm::Vec3> = indices.iter().map(|i| vertices[*i as usize]).collect();
+    let first = *positions.first()?;
+    let plane = project_triangle_into_2d(&positions);
+
+    // Triangles of the fan weighted by their area in the projection, which is in proportion to their area
+    let mut sum = glm::Vec3::zero();
+    let mut total = 0.0;
+    for i in 1..positions.len().saturating_sub(1) {
+        let area = polygon_area_2d(&[plane[0], plane[i], plane[i + 1]]);
+        sum = sum + (positions[0] + positions[i] + positions[i + 1]) * (area / 3.0);
+        total += area;
+    }
+
+    if total.abs() <= f32::EPSILON * bounding_size_2d(&plane).powi(2) {
+        let average = positions.iter().fold(glm::Vec3::zero(), |total, p| total + (*p - first)) / positions.len() as f32;
+        return Some(first + average);
+    }
+    Some(sum / total)
+}
+
+/* Same as is_convex_2d for a polygon in 3D, tested in its projection */
+pub fn is_convex(indices: &[i32], vertices: &[glm::Vec3], eps: f32) -> bool {
+    is_convex_2d(&project_polygon_into_2d(indices, vertices), eps)
+}
+
+/* Projects a polygon given by its indices into vertices, see project_triangle_into_2d */
+pub fn project_polygon_into_2d(indices: &[i32], vertices: &[glm::Vec3]) -> Vec<glm::Vec2> {
+    let positions: Vec<glm::Vec3> = indices.iter().map(|i| vertices[*i as usize]).collect();
+    project_triangle_into_2d(&positions)
+}
+
+/* Projects the corner positions of a polygon onto the axis plane closest to its own, keeping the winding */
+pub fn project_triangle_into_2d(positions: &[glm::Vec3]) -> Vec<glm::Vec2> {
+    // Polygons without area have no plane to project onto, any projection keeps them free of NaN
+    let surface_normal = polygon_normal(positions.iter().copied()).unwrap_or(glm::vec3(0.0, 0.0, 1.0));
 
     let absolute_normal = glm::abs(surface_normal);
 
@@ -69,20 +354,59 @@ pub fn project_triangle_into_2d(face: &Face, vertices: &Vec<glm::Vec3>) -> Vec<g
         std::mem::swap(&mut project_axis_a, &mut project_axis_b);
     }
 
-    let mut plane_vertices = Vec::new();
-    for i in 0..face.indices.len() {
-        plane_vertices.push(glm::vec2(
-            vertices[face.indices[i] as usize][project_axis_a],
-            vertices[face.indices[i] as usize][project_axis_b],
-        ));
-    }
-    plane_vertices
+    positions.iter()
+        .map(|position| glm::vec2(position[project_axis_a], position[project_axis_b]))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn calculate_surface_normal_should_return_none_for_zero_area_triangle() {
+        // Arrange
+        let vertices = vec![glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 1.0, 1.0), glm::vec3(2.0, 2.0, 2.0)];
+        let face = Face::new(vec![0, 1, 2]);
+
+        // Act
+        let normal = calculate_surface_normal(&face, &vertices);
+
+        // Assert
+        assert!(normal.is_none());
+    }
+
+    #[test]
+    fn calculate_surface_normal_should_return_none_for_identical_points() {
+        // Arrange
+        let vertices = vec![glm::vec3(3.0, -1.0, 2.0); 4];
+        let face = Face::new(vec![0, 1, 2, 3]);
+
+        // Act
+        let normal = calculate_surface_normal(&face, &vertices);
+
+        // Assert
+        assert!(normal.is_none());
+    }
+
+    #[test]
+    fn calculate_surface_normal_should_return_unit_normal_for_valid_polygon() {
+        // Arrange
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(2.0, 0.0, 0.0),
+            glm::vec3(2.0, 2.0, 0.0),
+            glm::vec3(0.0, 2.0, 0.0),
+        ];
+        let face = Face::new(vec![0, 1, 2, 3]);
+
+        // Act
+        let normal = calculate_surface_normal(&face, &vertices);
+
+        // Assert
+        assert_eq!(normal, Some(glm::vec3(0.0, 0.0, 1.0)));
+    }
+
     #[test]
     fn tri_contains_other_verts_2d_should_return_true_for_point_in_triangle() {
         // Arrange
@@ -90,7 +414,7 @@ mod tests {
         let v1 = glm::vec2(0.0, -10.0);
         let v2 = glm::vec2(10.0, 10.0);
 
-        let vertices = vec![glm::vec2(5.5, 5.5)];
+        let vertices = [glm::vec2(5.5, 5.5)];
 
         // Act
         let result = tri_contains_other_verts_2d(&v0, &v1, &v2, &mut vertices.iter());
@@ -106,7 +430,7 @@ mod tests {
         let v1 = glm::vec2(0.0, -10.0);
         let v2 = glm::vec2(10.0, -5.0);
 
-        let vertices = vec![glm::vec2(0.0, -5.0)];
+        let vertices = [glm::vec2(0.0, -5.0)];
 
         // Act
         let result = tri_contains_other_verts_2d(&v0, &v1, &v2, &mut vertices.iter());
@@ -122,13 +446,13 @@ mod tests {
         let v1 = glm::vec2(0.0, -10.0);
         let v2 = glm::vec2(10.0, -5.0);
 
-        let vertices = vec![glm::vec2(-0.5, -5.0)];
+        let vertices = [glm::vec2(-0.5, -5.0)];
 
         // Act
         let result = tri_contains_other_verts_2d(&v0, &v1, &v2, &mut vertices.iter());
 
         // Assert
-        assert_eq!(result, false);
+        assert!(!result);
     }
 
     #[test]
@@ -144,6 +468,353 @@ mod tests {
         let result = tri_contains_other_verts_2d(&v0, &v1, &v2, &mut vertices.iter());
 
         // Assert
-        assert_eq!(result, false);
+        assert!(!result);
+    }
+
+    #[test]
+    fn polygon_area_2d_should_be_positive_for_counter_clockwise_polygon() {
+        // Arrange
+        let vertices = vec![
+            glm::vec2(0.0, 0.0),
+            glm::vec2(2.0, 0.0),
+            glm::vec2(2.0, 2.0),
+            glm::vec2(0.0, 2.0),
+        ];
+
+        // Act
+        let result = polygon_area_2d(&vertices);
+
+        // Assert
+        assert_eq!(result, 4.0);
+    }
+
+    #[test]
+    fn polygon_area_2d_should_be_negative_for_clockwise_polygon() {
+        // Arrange
+        let vertices = vec![
+            glm::vec2(0.0, 0.0),
+            glm::vec2(0.0, 2.0),
+            glm::vec2(2.0, 2.0),
+            glm::vec2(2.0, 0.0),
+        ];
+
+        // Act
+        let result = polygon_area_2d(&vertices);
+
+        // Assert
+        assert_eq!(result, -4.0);
+    }
+
+    #[test]
+    fn segments_intersect_2d_should_return_true_for_crossing_segments() {
+        // Arrange
+        let a0 = glm::vec2(0.0, 0.0);
+        let a1 = glm::vec2(10.0, 10.0);
+        let b0 = glm::vec2(10.0, 0.0);
+        let b1 = glm::vec2(0.0, 10.0);
+
+        // Act
+        let result = segments_intersect_2d(&a0, &a1, &b0, &b1);
+
+        // Assert
+        assert!(result);
+    }
+
+    #[test]
+    fn segments_intersect_2d_should_return_false_for_segments_sharing_end_point() {
+        // Arrange
+        let a0 = glm::vec2(0.0, 0.0);
+        let a1 = glm::vec2(10.0, 10.0);
+        let b0 = glm::vec2(10.0, 10.0);
+        let b1 = glm::vec2(20.0, 0.0);
+
+        // Act
+        let result = segments_intersect_2d(&a0, &a1, &b0, &b1);
+
+        // Assert
+        assert!(!result);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn convex_hull_2d_should_skip_interior_vertices() {
+        // Arrange
+        let vertices = vec![
+            glm::vec2(0.0, 0.0),
+            glm::vec2(5.0, 5.0),
+            glm::vec2(10.0, 0.0),
+            glm::vec2(10.0, 10.0),
+            glm::vec2(0.0, 10.0),
+        ];
+
+        // Act
+        let result = convex_hull_2d(&vertices, &[0, 1, 2, 3, 4]);
+
+        // Assert
+        assert_eq!(result, vec![0, 2, 3, 4]);
+    }
+
+    /* A triangle with a slanted edge from (0, 0) to (3, 7), times scale */
+    fn slanted_triangle(scale: f32) -> [glm::Vec2; 3] {
+        [glm::vec2(0.0, 0.0), glm::vec2(3.0, 7.0) * scale, glm::vec2(3.0, 0.0) * scale]
+    }
+
+    /* Point on the slanted edge, pushed along its normal by offset times the tolerance, positive is outwards */
+    fn near_slanted_edge(scale: f32, offset: f32) -> glm::Vec2 {
+        let eps = scale * 10.0 * RELATIVE_EPSILON;
+        let on_edge = glm::vec2(3.0, 7.0) * (scale / 3.0);
+        let outward = glm::normalize(glm::vec2(-7.0, 3.0));
+        on_edge + outward * (offset * eps)
+    }
+
+    #[test]
+    fn is_point_in_triangle_2d_eps_should_count_points_on_and_near_edges_at_any_scale() {
+        for scale in [0.001, 1.0, 10_000.0].iter() {
+            // Arrange
+            let [v0, v1, v2] = slanted_triangle(*scale);
+            let eps = scale * 10.0 * RELATIVE_EPSILON;
+            let inside = |point: glm::Vec2| is_point_in_triangle_2d_eps(&point, &v0, &v1, &v2, eps);
+
+            // Act
+            let on_edge = inside(near_slanted_edge(*scale, 0.0));
+            let just_inside = inside(near_slanted_edge(*scale, -2.0));
+            let within_tolerance = inside(near_slanted_edge(*scale, 0.5));
+            let just_outside = inside(near_slanted_edge(*scale, 2.0));
+
+            // Assert
+            assert!(on_edge, "scale {}", scale);
+            assert!(just_inside, "scale {}", scale);
+            assert!(within_tolerance, "scale {}", scale);
+            assert!(!just_outside, "scale {}", scale);
+        }
+    }
+
+    #[test]
+    fn is_point_in_triangle_2d_eps_should_count_corners_and_axis_aligned_edges() {
+        for scale in [0.001, 10_000.0].iter() {
+            // Arrange
+            let [v0, v1, v2] = slanted_triangle(*scale);
+            let eps = scale * 10.0 * RELATIVE_EPSILON;
+
+            // Act
+            let corner = is_point_in_triangle_2d_eps(&v1, &v0, &v1, &v2, eps);
+            let bottom_edge = is_point_in_triangle_2d_eps(&glm::vec2(1.5 * scale, 0.0), &v0, &v1, &v2, eps);
+            let below = is_point_in_triangle_2d_eps(&glm::vec2(1.5 * scale, -2.0 * eps), &v0, &v1, &v2, eps);
+
+            // Assert
+            assert!(corner);
+            assert!(bottom_edge);
+            assert!(!below);
+        }
+    }
+
+    #[test]
+    fn orientation_2d_should_report_turns_and_collinear_points() {
+        // Arrange
+        let a = glm::vec2(0.0, 0.0);
+        let b = glm::vec2(1.0, 0.0);
+
+        // Act
+        let left = orientation_2d(&a, &b, &glm::vec2(2.0, 1.0), 1e-4);
+        let right = orientation_2d(&a, &b, &glm::vec2(2.0, -1.0), 1e-4);
+        let nearly_collinear = orientation_2d(&a, &b, &glm::vec2(2.0, 5e-5), 1e-4);
+        let same_point = orientation_2d(&a, &a, &b, 1e-4);
+
+        // Assert
+        assert_eq!(left, Orientation::CounterClockwise);
+        assert_eq!(right, Orientation::Clockwise);
+        assert_eq!(nearly_collinear, Orientation::Collinear);
+        assert_eq!(same_point, Orientation::Collinear);
+    }
+
+    /* The classic example of Kettner et al, (0.5, 0.5) moved a few ulps off the line through (12, 12) and (24, 24) */
+    fn kettner_points(x_ulps: u32, y_ulps: u32) -> [glm::Vec2; 3] {
+        let half = 0.5f32.to_bits();
+        [
+            glm::vec2(f32::from_bits(half + x_ulps), f32::from_bits(half + y_ulps)),
+            glm::vec2(12.0, 12.0),
+            glm::vec2(24.0, 24.0),
+        ]
+    }
+
+    #[test]
+    fn orientation_2d_exact_should_get_the_sign_right_where_floats_do_not() {
+        // Arrange
+        let [a, b, c] = kettner_points(41, 48);
+
+        // Act
+        let naive = [orientation_2d(&a, &b, &c, 0.0), orientation_2d(&b, &c, &a, 0.0), orientation_2d(&c, &a, &b, 0.0)];
+        let exact = [orientation_2d_exact(&a, &b, &c), orientation_2d_exact(&b, &c, &a), orientation_2d_exact(&c, &a, &b)];
+
+        // Assert
+        // a is above the line, but the same three points in turn come out as right, on and left of it
+        assert_eq!(naive, [Orientation::Clockwise, Orientation::Collinear, Orientation::CounterClockwise]);
+        assert_eq!(exact, [Orientation::CounterClockwise; 3]);
+    }
+
+    #[test]
+    fn orientation_2d_exact_should_be_consistent_under_every_ordering() {
+        for x_ulps in 0..64 {
+            for y_ulps in 0..64 {
+                // Arrange
+                let [a, b, c] = kettner_points(x_ulps, y_ulps);
+                let expected = match y_ulps.cmp(&x_ulps) {
+                    std::cmp::Ordering::Greater => Orientation::CounterClockwise,
+                    std::cmp::Ordering::Less => Orientation::Clockwise,
+                    std::cmp::Ordering::Equal => Orientation::Collinear,
+                };
+                let reversed = match expected {
+                    Orientation::CounterClockwise => Orientation::Clockwise,
+                    Orientation::Clockwise => Orientation::CounterClockwise,
+                    Orientation::Collinear => Orientation::Collinear,
+                };
+
+                // Act
+                let turns = [orientation_2d_exact(&a, &b, &c), orientation_2d_exact(&b, &c, &a), orientation_2d_exact(&c, &a, &b)];
+                let reversed_turns = [orientation_2d_exact(&c, &b, &a), orientation_2d_exact(&a, &c, &b), orientation_2d_exact(&b, &a, &c)];
+
+                // Assert
+                assert_eq!(turns, [expected; 3], "{} {}", x_ulps, y_ulps);
+                assert_eq!(reversed_turns, [reversed; 3], "{} {}", x_ulps, y_ulps);
+            }
+        }
+    }
+
+    #[test]
+    fn orientation_2d_exact_should_handle_far_apart_magnitudes() {
+        // Arrange
+        let a = glm::vec2(1e30, 1e30);
+        let b = glm::vec2(-1e30, -1e30);
+
+        // Act
+        let on_line = orientation_2d_exact(&a, &b, &glm::vec2(1e-30, 1e-30));
+        let just_above = orientation_2d_exact(&a, &b, &glm::vec2(0.0, 1e-38));
+        let clear_turn = orientation_2d_exact(&glm::vec2(0.0, 0.0), &glm::vec2(1.0, 0.0), &glm::vec2(0.0, 1.0));
+
+        // Assert
+        assert_eq!(on_line, Orientation::Collinear);
+        assert_eq!(just_above, Orientation::Clockwise);
+        assert_eq!(clear_turn, Orientation::CounterClockwise);
+    }
+
+    #[test]
+    fn bounding_size_2d_should_be_the_largest_extent() {
+        // Arrange
+        let points = vec![glm::vec2(-1.0, 2.0), glm::vec2(3.0, 2.5), glm::vec2(0.0, 1.0)];
+
+        // Act
+        let size = bounding_size_2d(&points);
+
+        // Assert
+        assert_eq!(size, 4.0);
+        assert_eq!(bounding_size_2d(&[]), 0.0);
+    }
+
+    fn square(size: f32, offset: f32) -> Vec<glm::Vec2> {
+        vec![
+            glm::vec2(offset, offset),
+            glm::vec2(offset + size, offset),
+            glm::vec2(offset + size, offset + size),
+            glm::vec2(offset, offset + size),
+        ]
+    }
+
+    #[test]
+    fn polygon_area_2d_should_be_zero_for_degenerate_polygons() {
+        // Arrange
+        let collinear = vec![glm::vec2(0.0, 0.0), glm::vec2(1.0, 1.0), glm::vec2(2.0, 2.0)];
+
+        // Act
+        let areas = [polygon_area_2d(&[]), polygon_area_2d(&collinear[..2]), polygon_area_2d(&collinear)];
+
+        // Assert
+        assert_eq!(areas, [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn polygon_centroid_2d_should_find_the_centre_of_an_l_shape() {
+        // Arrange
+        // Two unit squares side by side and one on top of the left one
+        let vertices = vec![
+            glm::vec2(0.0, 0.0),
+            glm::vec2(2.0, 0.0),
+            glm::vec2(2.0, 1.0),
+            glm::vec2(1.0, 1.0),
+            glm::vec2(1.0, 2.0),
+            glm::vec2(0.0, 2.0),
+        ];
+
+        // Act
+        let centroid = polygon_centroid_2d(&vertices).unwrap();
+
+        // Assert
+        assert!(glm::length(centroid - glm::vec2(5.0 / 6.0, 5.0 / 6.0)) < 1e-5, "{:?}", centroid);
+        assert_eq!(polygon_centroid_2d(&square(2.0, 1000.0)), Some(glm::vec2(1001.0, 1001.0)));
+    }
+
+    #[test]
+    fn polygon_centroid_2d_should_average_degenerate_polygons() {
+        // Arrange
+        let collinear = vec![glm::vec2(0.0, 0.0), glm::vec2(1.0, 1.0), glm::vec2(5.0, 5.0)];
+
+        // Act
+        let centroids = (polygon_centroid_2d(&collinear), polygon_centroid_2d(&collinear[..1]), polygon_centroid_2d(&[]));
+
+        // Assert
+        assert_eq!(centroids, (Some(glm::vec2(2.0, 2.0)), Some(glm::vec2(0.0, 0.0)), None));
+    }
+
+    #[test]
+    fn is_convex_2d_should_tell_convex_from_concave_and_degenerate_polygons() {
+        // Arrange
+        let mut clockwise = square(1.0, 0.0);
+        clockwise.reverse();
+        let mut with_straight_corner = square(2.0, 0.0);
+        with_straight_corner.insert(1, glm::vec2(1.0, 0.0));
+        let arrow = vec![glm::vec2(0.0, 0.0), glm::vec2(2.0, 1.0), glm::vec2(0.0, 2.0), glm::vec2(1.0, 1.0)];
+        let pentagram: Vec<glm::Vec2> = (0..5)
+            .map(|i| {
+                let angle = i as f32 * 4.0 * std::f32::consts::PI / 5.0;
+                glm::vec2(angle.cos(), angle.sin())
+            })
+            .collect();
+        let collinear = vec![glm::vec2(0.0, 0.0), glm::vec2(1.0, 1.0), glm::vec2(2.0, 2.0)];
+
+        // Act
+        let results = [
+            is_convex_2d(&square(1.0, 0.0), 1e-5),
+            is_convex_2d(&clockwise, 1e-5),
+            is_convex_2d(&with_straight_corner, 1e-5),
+            is_convex_2d(&arrow, 1e-5),
+            is_convex_2d(&pentagram, 1e-5),
+            is_convex_2d(&collinear, 1e-5),
+            is_convex_2d(&collinear[..2], 1e-5),
+        ];
+
+        // Assert
+        assert_eq!(results, [true, true, true, false, false, false, false]);
+    }
+
+    #[test]
+    fn polygon_area_and_centroid_should_work_on_tilted_polygons() {
+        // Arrange
+        // 2 by 1 rectangle standing in the plane x = y
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+            glm::vec3(1.0, 1.0, 2.0_f32.sqrt()),
+            glm::vec3(0.0, 0.0, 2.0_f32.sqrt()),
+        ];
+        let indices = [0, 1, 2, 3];
+
+        // Act
+        let area = polygon_area(&indices, &vertices);
+        let centroid = polygon_centroid(&indices, &vertices).unwrap();
+
+        // Assert
+        assert!((area - 2.0).abs() < 1e-5);
+        assert!(glm::length(centroid - glm::vec3(0.5, 0.5, 2.0_f32.sqrt() / 2.0)) < 1e-5);
+        assert!(is_convex(&indices, &vertices, 1e-5));
+        assert_eq!(polygon_area(&indices[..2], &vertices), 0.0);
+        assert_eq!(polygon_centroid(&[], &vertices), None);
+    }
+}
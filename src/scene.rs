@@ -1,16 +1,840 @@
+use crate::warnings::warning;
 use crate::scene::mesh::Mesh;
+use crate::scene::mesh::content_hash::MeshHasher;
+use crate::scene::mesh::diff::{mesh_diff, DiffTolerances};
+use crate::scene::node::Node;
+use crate::scene::material::Material;
+use crate::scene::texture::Texture;
+use crate::scene::animation::AnimationLayer;
+use crate::scene::time::TimeSettings;
+use crate::scene::camera::Camera;
+use crate::scene::light::Light;
+use crate::scene::connections::Connections;
+use crate::scene::traversal::{traverse_mut, Inherited, Traversal};
+use crate::scene::transform::Transform;
+use crate::scene::timings::ImportTimings;
+use crate::fbx::Header;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use image::DynamicImage;
+use std::collections::{HashMap, HashSet};
 
 pub mod mesh;
+pub mod node;
+pub mod material;
+pub mod texture;
+pub mod animation;
+pub mod time;
+pub mod skin;
+pub mod blend_shape;
+pub mod camera;
+pub mod light;
+pub mod connections;
+pub mod traversal;
+pub mod transform;
+pub mod timings;
+pub mod cache;
+pub mod obj;
+#[cfg(test)]
+pub(crate) mod snapshot;
 
+/* Plain owned data, so scenes are Send + Sync: import on a worker thread and hand the scene over, or share &Scene and
+   &Mesh between threads. Everything taking &mut self (merging, deduping meshes, moving nodes, resolving textures) needs
+   the scene to itself. Anything shared inside the scene later on has to go through Arc, and lazily computed values
+   through OnceLock or a lock, never Rc or RefCell. */
 pub struct Scene {
     pub(crate) meshes: Vec<Mesh>,
+    pub(crate) nodes: Vec<Node>,
+    pub(crate) materials: Vec<Material>,
+    pub(crate) textures: Vec<Texture>,
+    pub(crate) animation_layers: Vec<AnimationLayer>,
+    pub(crate) time_settings: TimeSettings,
+    pub(crate) cameras: Vec<Camera>,
+    pub(crate) lights: Vec<Light>,
+    pub(crate) connections: Connections,
+    pub(crate) timings: ImportTimings,
+    pub(crate) header: Option<Header>,
 }
 
 impl Scene {
     pub fn new(meshes: Vec<Mesh>) -> Self {
         Scene {
-            meshes
+            meshes,
+            nodes: Vec::new(),
+            materials: Vec::new(),
+            textures: Vec::new(),
+            animation_layers: Vec::new(),
+            time_settings: TimeSettings::default(),
+            cameras: Vec::new(),
+            lights: Vec::new(),
+            connections: Connections::new(),
+            timings: ImportTimings::default(),
+            header: None,
         }
     }
+
+    pub fn with_nodes(mut self, nodes: Vec<Node>) -> Self {
+        self.nodes = nodes;
+        self.update_world_transforms();
+        self
+    }
+
+    pub fn with_materials(mut self, materials: Vec<Material>) -> Self {
+        self.materials = materials;
+        self
+    }
+
+    pub fn materials(&self) -> &[Material] {
+        &self.materials
+    }
+
+    pub fn with_textures(mut self, textures: Vec<Texture>) -> Self {
+        self.textures = textures;
+        self
+    }
+
+    pub fn textures(&self) -> &[Texture] {
+        &self.textures
+    }
+
+    pub fn with_animation_layers(mut self, animation_layers: Vec<AnimationLayer>) -> Self {
+        self.animation_layers = animation_layers;
+        self
+    }
+
+    pub fn animation_layers(&self) -> &[AnimationLayer] {
+        &self.animation_layers
+    }
+
+    pub fn with_time_settings(mut self, time_settings: TimeSettings) -> Self {
+        self.time_settings = time_settings;
+        self
+    }
+
+    /* Frame rate and time span the file was authored with */
+    pub fn time_settings(&self) -> &TimeSettings {
+        &self.time_settings
+    }
+
+    pub fn with_cameras(mut self, cameras: Vec<Camera>) -> Self {
+        self.cameras = cameras;
+        self
+    }
+
+    pub fn cameras(&self) -> &[Camera] {
+        &self.cameras
+    }
+
+    pub fn with_lights(mut self, lights: Vec<Light>) -> Self {
+        self.lights = lights;
+        self
+    }
+
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    pub fn with_connections(mut self, connections: Connections) -> Self {
+        self.connections = connections;
+        self
+    }
+
+    /* Connections between the objects of the file the scene was imported from */
+    pub fn connections(&self) -> &Connections {
+        &self.connections
+    }
+
+    /* How long each phase of the import took, empty for scenes that were not imported */
+    pub fn timings(&self) -> &ImportTimings {
+        &self.timings
+    }
+
+    /* Header of the file the scene was imported from, None for scenes that were not imported */
+    pub fn header(&self) -> Option<&Header> {
+        self.header.as_ref()
+    }
+
+    /* Looks up the image file of every texture, see Texture::resolve_path. Returns the ids of the textures no file
+       was found for. */
+    pub fn resolve_all_textures(&mut self, search_dirs: &[PathBuf]) -> Vec<i64> {
+        let mut unresolved = Vec::new();
+        for texture in &mut self.textures {
+            texture.resolved_path = texture.resolve_path(search_dirs);
+            if texture.resolved_path.is_none() {
+                warning!("Could not find the file of texture '{}', looked for '{}' and '{}'.",
+                          texture.name, texture.relative_file_name, texture.file_name);
+                unresolved.push(texture.id);
+            }
+        }
+        unresolved
+    }
+
+    /* Decodes the image of every texture, external files at the paths found by resolve_all_textures. Textures
+       sharing a file share the decoded image. Textures that fail to load are reported and left out. */
+    pub fn load_all_textures(&self) -> HashMap<i64, Arc<DynamicImage>> {
+        let mut images = HashMap::new();
+        let mut by_path: HashMap<&Path, Option<Arc<DynamicImage>>> = HashMap::new();
+        for texture in &self.textures {
+            let loaded = match texture.resolved_path() {
+                Some(path) if !texture.has_embedded_content() => by_path.entry(path)
+                    .or_insert_with(|| load_texture(texture))
+                    .clone(),
+                _ => load_texture(texture),
+            };
+            if let Some(image) = loaded {
+                images.insert(texture.id, image);
+            }
+        }
+        images
+    }
+
+    pub fn meshes(&self) -> &[Mesh] {
+        &self.meshes
+    }
+
+    /* Keeps the first of every set of identical meshes, nodes of the removed meshes are pointed at the one that was
+       kept. Meshes are identical when their positions and faces match and every other channel holds the same values.
+       Returns the new index of every mesh, so references outside the scene can be updated. */
+    pub fn dedupe_meshes(&mut self) -> Vec<usize> {
+        let hasher = MeshHasher::new().with_normals(true).with_uvs(true);
+        // Indices into kept, a hash can be shared by meshes that turn out to differ
+        let mut kept_by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut remap = Vec::with_capacity(self.meshes.len());
+        let mut kept: Vec<Mesh> = Vec::with_capacity(self.meshes.len());
+
+        for mesh in self.meshes.drain(..) {
+            let candidates = kept_by_hash.entry(hasher.hash(&mesh)).or_default();
+            match candidates.iter().find(|index| is_duplicate(&kept[**index], &mesh)) {
+                Some(index) => remap.push(*index),
+                None => {
+                    candidates.push(kept.len());
+                    remap.push(kept.len());
+                    kept.push(mesh);
+                }
+            }
+        }
+
+        self.meshes = kept;
+        for node in &mut self.nodes {
+            node.mesh = node.mesh.map(|mesh| remap[mesh]);
+        }
+        remap
+    }
+
+    /* Removes the meshes keep returns false for, nodes using them are left without a mesh. Returns the new index of
+       every mesh, None for the removed ones. */
+    pub fn retain_meshes<F: FnMut(&Mesh) -> bool>(&mut self, mut keep: F) -> Vec<Option<usize>> {
+        let mut remap = Vec::with_capacity(self.meshes.len());
+        let mut kept = 0;
+        for mesh in &self.meshes {
+            if keep(mesh) {
+                remap.push(Some(kept));
+                kept += 1;
+            } else {
+                remap.push(None);
+            }
+        }
+
+        let mut index = 0;
+        self.meshes.retain(|_| {
+            index += 1;
+            remap[index - 1].is_some()
+        });
+        for node in &mut self.nodes {
+            node.mesh = node.mesh.and_then(|mesh| remap[mesh]);
+        }
+        remap
+    }
+
+    /* Bounds of all mesh vertices, None for a scene without any */
+    pub fn bounds(&self) -> Option<(glm::Vec3, glm::Vec3)> {
+        self.meshes.iter()
+            .filter_map(|mesh| mesh.bounds())
+            .fold(None, |bounds, (min, max)| match bounds {
+                Some((all_min, all_max)) => Some((glm::min(all_min, min), glm::max(all_max, max))),
+                None => Some((min, max)),
+            })
+    }
+
+    /* Moves the contents of other into this scene, see merge_as */
+    pub fn merge(&mut self, other: Scene) {
+        self.merge_as(other, "Merged");
+    }
+
+    /* Moves the meshes, materials, textures, animation and nodes of other into this scene, its connections are left
+       out. Meshes from other get numbered suffixes where their names are taken, materials shading the same as one
+       already in the scene are left out. The root nodes of other become children of a new group node with the given
+       name, such as the file other was imported from. Node ids are only unique within the file they came from, so the
+       group node gets an id above every id in both scenes. */
+    pub fn merge_as(&mut self, other: Scene, group_name: &str) {
+        let mesh_offset = self.meshes.len();
+        self.meshes.extend(other.meshes);
+        rename_duplicate_meshes(&mut self.meshes);
+
+        for material in other.materials {
+            if !self.materials.iter().any(|m| m.same_content(&material)) {
+                self.materials.push(material);
+            }
+        }
+        self.textures.extend(other.textures);
+        self.animation_layers.extend(other.animation_layers);
+        self.cameras.extend(other.cameras);
+        self.lights.extend(other.lights);
+        // Object ids are only unique within a file, so the connections of other would be ambiguous here
+
+        if other.nodes.is_empty() {
+            return;
+        }
+
+        let offset = self.nodes.len();
+        let group = offset + other.nodes.len();
+        let group_id = self.nodes.iter().chain(other.nodes.iter()).map(|n| n.id).max().unwrap_or(0) + 1;
+        let mut group_node = Node::new(group_id, group_name.to_string());
+
+        for (index, mut node) in other.nodes.into_iter().enumerate() {
+            node.children.iter_mut().for_each(|child| *child += offset);
+            node.mesh = node.mesh.map(|mesh| mesh + mesh_offset);
+            node.parent = match node.parent {
+                Some(parent) => Some(parent + offset),
+                None => {
+                    group_node.children.push(offset + index);
+                    Some(group)
+                }
+            };
+            self.nodes.push(node);
+        }
+        self.nodes.push(group_node);
+        self.update_world_transforms();
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /* Transform from the space of the node to world space, composed down from the root as the inherit type of every
+       node on the way says. Uses the cached transform of the node unless it is out of date. */
+    pub fn world_transform(&self, node: usize) -> glm::Mat4 {
+        if !self.nodes[node].world_dirty {
+            return self.nodes[node].world;
+        }
+
+        let mut path: Vec<usize> = self.ancestors(node).collect();
+        path.reverse();
+        path.push(node);
+        path.iter()
+            .fold(Inherited::root(), |inherited, index| inherited.child(&self.nodes[*index]))
+            .world
+    }
+
+    /* Recomputes the cached world transform of every node, see Node::world_transform */
+    pub fn update_world_transforms(&mut self) {
+        self.traverse_mut(|_, _, _| {});
+    }
+
+    /* Replaces the transform of the node relative to its parent. The cached world transforms of the node and
+       everything below it are out of date until the next update_world_transforms. */
+    pub fn set_local_transform(&mut self, node: usize, transform: Transform) {
+        self.nodes[node].transform = transform;
+        let mut pending = vec![node];
+        while let Some(index) = pending.pop() {
+            // Already marked nodes have their subtree marked, which also ends the walk on parent loops
+            if !self.nodes[index].world_dirty || index == node {
+                self.nodes[index].world_dirty = true;
+                pending.extend(&self.nodes[index].children);
+            }
+        }
+    }
+
+    /* Visits every node depth first, children in the order they are stored, with its depth below the scene root and
+       its world transform. The world transforms are composed on the way down instead of walking the ancestors of
+       every node. */
+    pub fn traverse(&self) -> Traversal<'_> {
+        Traversal::new(&self.nodes)
+    }
+
+    /* Like traverse, letting f change the nodes. Changes to the local transform of a node show in the world
+       transforms given for its children. */
+    pub fn traverse_mut<F: FnMut(usize, &mut Node, glm::Mat4)>(&mut self, f: F) {
+        traverse_mut(&mut self.nodes, f);
+    }
+
+    /* Like traverse, only visiting the nodes with a mesh and giving the mesh along with them. The transform includes
+       the geometric transform of the node, so it takes the vertices of the mesh to world space. */
+    pub fn traverse_meshes(&self) -> impl Iterator<Item = (usize, &Node, &Mesh, glm::Mat4)> + '_ {
+        self.traverse().filter_map(move |(depth, node, world)| node.mesh
+            .and_then(|mesh| self.meshes.get(mesh))
+            .map(|mesh| (depth, node, mesh, world * node.geometric_transform.to_mat4())))
+    }
+
+    /* Walks from the parent of node up to the root. Stops after visiting every node once, so a parent loop in a
+       hand-built scene ends the walk instead of hanging anything that accumulates transforms along it. */
+    pub fn ancestors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut current = self.nodes.get(node).and_then(|n| n.parent);
+        let mut remaining = self.nodes.len();
+        std::iter::from_fn(move || {
+            if remaining == 0 {
+                return None;
+            }
+            remaining -= 1;
+            let index = current?;
+            current = self.nodes.get(index).and_then(|n| n.parent);
+            Some(index)
+        })
+    }
+}
+
+/* Whether b can stand in for a, see Scene::dedupe_meshes. Channels other than the positions are compared exactly. */
+fn is_duplicate(a: &Mesh, b: &Mesh) -> bool {
+    let face_attributes = |mesh: &Mesh| mesh.faces.iter().map(|f| (f.material, f.smoothing_group)).collect::<Vec<_>>();
+    mesh_diff(a, b, &DiffTolerances::new()).is_equivalent()
+        && a.normals == b.normals
+        && a.uv_sets == b.uv_sets
+        && a.uvs == b.uvs
+        && a.color_sets == b.color_sets
+        && a.smoothing == b.smoothing
+        && a.skin == b.skin
+        && a.blend_shapes == b.blend_shapes
+        && face_attributes(a) == face_attributes(b)
+}
+
+/* Gives every mesh after the first with a given name the lowest free suffix, .001 and up. Meshes are in file order,
+   so importing the same file again gives the same names, and merged scenes keep the names of the scene merged into. */
+fn load_texture(texture: &Texture) -> Option<Arc<DynamicImage>> {
+    match texture.load_image(|t| t.resolved_path.clone()) {
+        Ok(image) => Some(Arc::new(image)),
+        Err(e) => {
+            warning!("{}.", e);
+            None
+        }
+    }
+}
+
+/* Gives every name used by an earlier one a numbered suffix, like Cube, Cube.001 and Cube.002, skipping suffixes that
+   are already taken. Returns the old and new name of everything renamed. */
+pub(crate) fn make_names_unique(names: Vec<&mut String>) -> Vec<(String, String)> {
+    let mut taken: HashSet<String> = names.iter().map(|n| n.to_string()).collect();
+    let mut seen = HashSet::new();
+    let mut next_suffix: HashMap<String, usize> = HashMap::new();
+    let mut renamed = Vec::new();
+
+    for current in names {
+        if seen.insert(current.clone()) {
+            continue;
+        }
+
+        let suffix = next_suffix.entry(current.clone()).or_insert(1);
+        let mut name = format!("{}.{:03}", current, suffix);
+        while taken.contains(&name) {
+            *suffix += 1;
+            name = format!("{}.{:03}", current, suffix);
+        }
+        *suffix += 1;
+
+        taken.insert(name.clone());
+        renamed.push((std::mem::replace(current, name.clone()), name));
+    }
+    renamed
 }
 
+pub(crate) fn rename_duplicate_meshes(meshes: &mut [Mesh]) {
+    for (old, new) in make_names_unique(meshes.iter_mut().map(|m| &mut m.name).collect()) {
+        warning!("Renamed mesh '{}' to '{}' as the name is used by another mesh.", old, new);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+    use crate::scene::material::ShadingModel;
+    use crate::scene::node::InheritType;
+    use crate::scene::transform::Quat;
+    use crate::fbx::{CachedImport, ImportResult};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn dedupe_meshes_should_keep_the_first_of_identical_meshes() {
+        // Arrange
+        let triangle = |name: &str, x: f32| Mesh::new(
+            name.to_string(),
+            vec![glm::vec3(x, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0)],
+            vec![Face::new(vec![0, 1, 2])]);
+        let mut scene = Scene::new(vec![triangle("a", 0.0), triangle("b", 0.5), triangle("a copy", 0.0)]);
+
+        // Act
+        let remap = scene.dedupe_meshes();
+
+        // Assert
+        let names: Vec<&str> = scene.meshes.iter().map(|m| m.name()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(remap, vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn dedupe_meshes_should_keep_meshes_differing_only_in_uvs() {
+        // Arrange
+        let triangle = |name: &str, u: f32| {
+            let mut mesh = Mesh::new(
+                name.to_string(),
+                vec![glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0)],
+                vec![Face::new(vec![0, 1, 2])]);
+            mesh.uvs = vec![glm::vec2(u, 0.0), glm::vec2(1.0, 0.0), glm::vec2(0.0, 1.0)];
+            mesh
+        };
+        let mut scene = Scene::new(vec![triangle("a", 0.0), triangle("b", 0.5)]);
+
+        // Act
+        let remap = scene.dedupe_meshes();
+
+        // Assert
+        let names: Vec<&str> = scene.meshes.iter().map(|m| m.name()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(remap, vec![0, 1]);
+    }
+
+    #[test]
+    fn is_duplicate_should_tell_apart_meshes_with_different_materials() {
+        // Arrange
+        let a = Mesh::new("a".to_string(),
+            vec![glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0)],
+            vec![Face::new(vec![0, 1, 2])]);
+        let mut b = a.clone();
+        b.faces[0].material = 1;
+
+        // Act
+        let duplicates = (is_duplicate(&a, &a.clone()), is_duplicate(&a, &b));
+
+        // Assert
+        assert_eq!(duplicates, (true, false));
+    }
+
+    #[test]
+    fn retain_meshes_should_detach_the_removed_meshes_from_their_nodes() {
+        // Arrange
+        let triangle = |name: &str| Mesh::new(
+            name.to_string(),
+            vec![glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0)],
+            vec![Face::new(vec![0, 1, 2])]);
+        let mut nodes: Vec<Node> = (0..3).map(|i| Node::new(i + 1, format!("node {}", i))).collect();
+        for (i, node) in nodes.iter_mut().enumerate() {
+            node.mesh = Some(i);
+        }
+        let mut scene = Scene::new(vec![triangle("keep"), triangle("drop"), triangle("keep too")]).with_nodes(nodes);
+
+        // Act
+        let remap = scene.retain_meshes(|mesh| mesh.name().starts_with("keep"));
+
+        // Assert
+        let names: Vec<&str> = scene.meshes.iter().map(|m| m.name()).collect();
+        assert_eq!(names, vec!["keep", "keep too"]);
+        assert_eq!(remap, vec![Some(0), None, Some(1)]);
+        assert_eq!(scene.nodes.iter().map(|n| n.mesh).collect::<Vec<_>>(), vec![Some(0), None, Some(1)]);
+    }
+
+    fn create_single_mesh_scene(name: &str, offset: f32) -> Scene {
+        let vertices = vec![glm::vec3(offset, 0.0, 0.0), glm::vec3(offset + 1.0, 0.0, 0.0), glm::vec3(offset, 1.0, 0.0)];
+        let mut model = Node::new(1, name.to_string());
+        let mut child = Node::new(2, "child".to_string());
+        child.parent = Some(0);
+        model.children.push(1);
+        Scene::new(vec![Mesh::new(name.to_string(), vertices, vec![Face::new(vec![0, 1, 2])])])
+            .with_nodes(vec![model, child])
+    }
+
+    #[test]
+    fn merge_should_append_meshes_with_unique_names() {
+        // Arrange
+        let mut scene = create_single_mesh_scene("Prop", 0.0);
+        let other = create_single_mesh_scene("Prop", 5.0);
+
+        // Act
+        scene.merge(other);
+
+        // Assert
+        let names: Vec<&str> = scene.meshes.iter().map(|m| m.name()).collect();
+        assert_eq!(names, vec!["Prop", "Prop.001"]);
+        let (min, max) = scene.bounds().unwrap();
+        assert_eq!(min, glm::vec3(0.0, 0.0, 0.0));
+        assert_eq!(max, glm::vec3(6.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn merge_should_leave_out_materials_with_the_same_content() {
+        // Arrange
+        let mut scene = Scene::new(Vec::new()).with_materials(vec![Material::new(1, "Red".to_string(), ShadingModel::Phong)]);
+        let other = Scene::new(Vec::new()).with_materials(vec![
+            Material::new(1, "Red".to_string(), ShadingModel::Phong),
+            Material::new(2, "Matte".to_string(), ShadingModel::Lambert),
+        ]);
+
+        // Act
+        scene.merge(other);
+
+        // Assert
+        let names: Vec<&str> = scene.materials().iter().map(|m| m.name()).collect();
+        assert_eq!(names, vec!["Red", "Matte"]);
+    }
+
+    #[test]
+    fn merge_as_should_put_the_merged_roots_under_a_group_node() {
+        // Arrange
+        let mut scene = create_single_mesh_scene("Table", 0.0);
+        let other = create_single_mesh_scene("Chair", 0.0);
+
+        // Act
+        scene.merge_as(other, "chair.fbx");
+
+        // Assert
+        assert_eq!(scene.nodes.len(), 5);
+        let group = &scene.nodes[4];
+        assert_eq!(group.name(), "chair.fbx");
+        assert_eq!(group.id(), 3);
+        assert_eq!(group.parent(), None);
+        assert_eq!(group.children(), &[2]);
+        assert_eq!(scene.nodes[2].parent(), Some(4));
+        assert_eq!(scene.nodes[2].children(), &[3]);
+        assert_eq!(scene.nodes[3].parent(), Some(2));
+        assert_eq!(scene.ancestors(3).collect::<Vec<_>>(), vec![2, 4]);
+    }
+
+    #[test]
+    fn ancestors_should_stop_on_parent_loops() {
+        // Arrange
+        let mut first = Node::new(1, "first".to_string());
+        let mut second = Node::new(2, "second".to_string());
+        first.parent = Some(1);
+        second.parent = Some(0);
+        let scene = Scene::new(Vec::new()).with_nodes(vec![first, second]);
+
+        // Act
+        let ancestors: Vec<usize> = scene.ancestors(0).collect();
+
+        // Assert
+        assert_eq!(ancestors, vec![1, 0]);
+    }
+
+    /* A root scaled along x, a child rotated a quarter turn around z and a grandchild, each one unit along x */
+    fn create_scaled_hierarchy(inherit_type: InheritType) -> Scene {
+        let mut root = Node::new(1, "root".to_string());
+        let mut child = Node::new(2, "child".to_string());
+        let mut grandchild = Node::new(3, "grandchild".to_string());
+        root.transform.scale = glm::vec3(2.0, 1.0, 1.0);
+        child.parent = Some(0);
+        child.transform = Transform::from_fbx(glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 0.0, 90.0), glm::vec3(1.0, 1.0, 1.0));
+        grandchild.parent = Some(1);
+        grandchild.transform.translation = glm::vec3(1.0, 0.0, 0.0);
+        for node in [&mut root, &mut child, &mut grandchild].iter_mut() {
+            node.inherit_type = inherit_type;
+        }
+        Scene::new(Vec::new()).with_nodes(vec![root, child, grandchild])
+    }
+
+    fn world_position(scene: &Scene, node: usize) -> glm::Vec3 {
+        let position = scene.world_transform(node) * glm::vec4(0.0, 0.0, 0.0, 1.0);
+        glm::vec3(position.x, position.y, position.z)
+    }
+
+    fn assert_near(actual: glm::Vec3, expected: glm::Vec3) {
+        assert!(glm::length(actual - expected) < 1e-5, "{:?} is not {:?}", actual, expected);
+    }
+
+    #[test]
+    fn world_transform_should_compose_matrices_for_rsrs() {
+        // Arrange
+        let scene = create_scaled_hierarchy(InheritType::RSrs);
+
+        // Act
+        let child = world_position(&scene, 1);
+        let grandchild = world_position(&scene, 2);
+
+        // Assert
+        // The child rotates the grandchild offset onto y, where the root scaling of x no longer reaches it
+        assert_near(child, glm::vec3(2.0, 0.0, 0.0));
+        assert_near(grandchild, glm::vec3(2.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn world_transform_should_scale_after_the_node_rotation_for_rrss() {
+        // Arrange
+        let scene = create_scaled_hierarchy(InheritType::RrSs);
+
+        // Act
+        let grandchild = world_position(&scene, 2);
+
+        // Assert
+        // The root scaling of x applies in the rotated space of the child, stretching its y
+        assert_near(grandchild, glm::vec3(2.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn world_transform_should_ignore_parent_scaling_for_rrs() {
+        // Arrange
+        let scene = create_scaled_hierarchy(InheritType::Rrs);
+
+        // Act
+        let y_axis = scene.world_transform(1) * glm::vec4(0.0, 1.0, 0.0, 0.0);
+        let grandchild = world_position(&scene, 2);
+
+        // Assert
+        // Rotated onto x without the root stretching it, though the child still moves with the scaled root
+        assert_near(glm::vec3(y_axis.x, y_axis.y, y_axis.z), glm::vec3(-1.0, 0.0, 0.0));
+        assert_near(grandchild, glm::vec3(2.0, 1.0, 0.0));
+    }
+
+    /* Two roots, the first with a child that has a child of its own and a mesh */
+    fn create_three_level_hierarchy() -> Scene {
+        let mut nodes: Vec<Node> = ["root", "child", "grandchild", "other root"].iter().enumerate()
+            .map(|(index, name)| Node::new(index as i64 + 1, name.to_string()))
+            .collect();
+        nodes[0].transform.translation = glm::vec3(1.0, 0.0, 0.0);
+        nodes[0].children = vec![1];
+        nodes[1].parent = Some(0);
+        nodes[1].transform = Transform::from_fbx(glm::vec3(0.0, 0.0, 0.0), glm::vec3(0.0, 0.0, 90.0), glm::vec3(2.0, 2.0, 2.0));
+        nodes[1].children = vec![2];
+        nodes[2].parent = Some(1);
+        nodes[2].transform.translation = glm::vec3(0.0, 3.0, 0.0);
+        nodes[2].mesh = Some(0);
+        let mesh = Mesh::new("mesh".to_string(), Vec::new(), Vec::new());
+        Scene::new(vec![mesh]).with_nodes(nodes)
+    }
+
+    #[test]
+    fn traverse_should_visit_nodes_depth_first_with_world_transforms() {
+        // Arrange
+        let scene = create_three_level_hierarchy();
+
+        // Act
+        let visited: Vec<(usize, &str, glm::Mat4)> = scene.traverse().map(|(depth, node, world)| (depth, node.name(), world)).collect();
+
+        // Assert
+        let order: Vec<(usize, &str)> = visited.iter().map(|(depth, name, _)| (*depth, *name)).collect();
+        assert_eq!(order, vec![(0, "root"), (1, "child"), (2, "grandchild"), (0, "other root")]);
+        let expected = scene.nodes[0].local_transform() * scene.nodes[1].local_transform() * scene.nodes[2].local_transform();
+        for column in 0..4 {
+            assert_near(
+                glm::vec3(visited[2].2[column].x, visited[2].2[column].y, visited[2].2[column].z),
+                glm::vec3(expected[column].x, expected[column].y, expected[column].z));
+        }
+        assert_near(world_position(&scene, 2), glm::vec3(-5.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn traverse_should_visit_every_node_of_a_parent_loop_once() {
+        // Arrange
+        let mut scene = create_three_level_hierarchy();
+        scene.nodes[2].children = vec![0];
+
+        // Act
+        let count = scene.traverse().count();
+
+        // Assert
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn traverse_mut_should_compose_children_from_the_changed_node() {
+        // Arrange
+        let mut scene = create_three_level_hierarchy();
+        let mut grandchild_position = glm::vec4(0.0, 0.0, 0.0, 0.0);
+
+        // Act
+        scene.traverse_mut(|_, node, world| {
+            if node.name() == "child" {
+                node.transform.scale = glm::vec3(1.0, 1.0, 1.0);
+            }
+            if node.name() == "grandchild" {
+                grandchild_position = world[3];
+            }
+        });
+
+        // Assert
+        assert_near(glm::vec3(grandchild_position.x, grandchild_position.y, grandchild_position.z), glm::vec3(-2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn traverse_meshes_should_only_visit_nodes_with_a_mesh() {
+        // Arrange
+        let scene = create_three_level_hierarchy();
+
+        // Act
+        let visited: Vec<(usize, &str, &str)> = scene.traverse_meshes().map(|(depth, node, mesh, _)| (depth, node.name(), mesh.name())).collect();
+
+        // Assert
+        assert_eq!(visited, vec![(2, "grandchild", "mesh")]);
+    }
+
+    #[test]
+    fn set_local_transform_should_only_outdate_the_subtree_of_the_node() {
+        // Arrange
+        let mut scene = create_three_level_hierarchy();
+        let grandchild_before = scene.nodes[2].world_transform();
+        let other_root_before = scene.nodes[3].world_transform();
+        let moved = Transform::new(glm::vec3(0.0, 0.0, 5.0), Quat::identity(), glm::vec3(1.0, 1.0, 1.0));
+
+        // Act
+        scene.set_local_transform(1, moved);
+        let stale: Vec<bool> = scene.nodes.iter().map(|n| n.is_world_transform_stale()).collect();
+        scene.update_world_transforms();
+
+        // Assert
+        assert_eq!(stale, vec![false, true, true, false]);
+        assert_near(world_position(&scene, 2), glm::vec3(1.0, 3.0, 5.0));
+        assert!(scene.nodes[2].world_transform() != grandchild_before);
+        assert!(scene.nodes[3].world_transform() == other_root_before);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "out of date")]
+    fn world_transform_should_panic_on_stale_reads_in_debug_builds() {
+        // Arrange
+        let mut scene = create_three_level_hierarchy();
+        scene.set_local_transform(0, Transform::identity());
+
+        // Act
+        scene.nodes[2].world_transform();
+    }
+
+    #[test]
+    fn resolve_all_textures_should_return_the_textures_without_a_file() {
+        // Arrange
+        let mut scene = Scene::new(Vec::new()).with_textures(vec![Texture::new(7, "Missing".to_string())]);
+
+        // Act
+        let unresolved = scene.resolve_all_textures(&[std::env::temp_dir()]);
+
+        // Assert
+        assert_eq!(unresolved, vec![7]);
+        assert_eq!(scene.textures()[0].resolved_path(), None);
+    }
+
+    #[test]
+    fn load_all_textures_should_load_shared_files_once() {
+        // Arrange
+        let path = std::env::temp_dir().join(format!("fbximport_scene_shared_texture_{}.png", std::process::id()));
+        DynamicImage::new_rgb8(2, 2).save_with_format(&path, image::ImageFormat::Png).unwrap();
+        let mut textures = vec![Texture::new(1, "A".to_string()), Texture::new(2, "B".to_string()), Texture::new(3, "C".to_string())];
+        textures[0].resolved_path = Some(path.clone());
+        textures[1].resolved_path = Some(path);
+        let scene = Scene::new(Vec::new()).with_textures(textures);
+
+        // Act
+        let images = scene.load_all_textures();
+
+        // Assert
+        assert_eq!(images.len(), 2);
+        assert!(Arc::ptr_eq(&images[&1], &images[&2]));
+    }
+
+    #[test]
+    fn scene_types_should_be_send_and_sync() {
+        assert_send_sync::<Scene>();
+        assert_send_sync::<Mesh>();
+        assert_send_sync::<Material>();
+        assert_send_sync::<Texture>();
+        assert_send_sync::<Node>();
+        assert_send_sync::<ImportResult>();
+        assert_send_sync::<CachedImport>();
+    }
+}
@@ -0,0 +1,366 @@
+use crate::warnings::warning;
+use crate::scene::time::KTime;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyInterpolation {
+    // Value holds until the next key
+    Constant,
+    Linear,
+    // Evaluated as linear for now
+    Cubic,
+}
+
+impl KeyInterpolation {
+    /* Interpolation bits of KeyAttrFlags, keys without any are linear */
+    pub fn from_flags(flags: i32) -> Self {
+        if flags & 0x2 != 0 {
+            KeyInterpolation::Constant
+        } else if flags & 0x8 != 0 {
+            KeyInterpolation::Cubic
+        } else {
+            KeyInterpolation::Linear
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TangentMode {
+    Auto,
+    // Tension, continuity and bias
+    Tcb,
+    User,
+    // User tangents with different slopes on either side of the key
+    Break,
+}
+
+/* Tangent data of a key as stored in KeyAttrFlags and KeyAttrDataFloat, kept as read so curves can be written back
+   unchanged */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyTangent {
+    pub flags: i32,
+    // Right slope, next left slope, packed weights and packed velocities. Tension, continuity and bias for TCB keys.
+    pub data: [f32; 4],
+}
+
+impl KeyTangent {
+    pub fn linear() -> Self {
+        KeyTangent {
+            flags: 0x4,
+            data: [0.0; 4],
+        }
+    }
+
+    /* None for keys that are not cubic */
+    pub fn mode(&self) -> Option<TangentMode> {
+        if KeyInterpolation::from_flags(self.flags) != KeyInterpolation::Cubic {
+            return None;
+        }
+
+        Some(if self.flags & 0x200 != 0 {
+            TangentMode::Tcb
+        } else if self.flags & 0x800 != 0 {
+            TangentMode::Break
+        } else if self.flags & 0x400 != 0 {
+            TangentMode::User
+        } else {
+            TangentMode::Auto
+        })
+    }
+
+    pub fn right_slope(&self) -> f32 {
+        self.data[0]
+    }
+
+    pub fn next_left_slope(&self) -> f32 {
+        self.data[1]
+    }
+}
+
+/* Keyframed values of a single channel. Each key's interpolation applies to the segment running from it to the next
+   key. */
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimCurve {
+    pub(crate) id: i64,
+    // Ascending
+    pub(crate) times: Vec<KTime>,
+    pub(crate) values: Vec<f32>,
+    pub(crate) interpolations: Vec<KeyInterpolation>,
+    // One per key, linear unless the file had tangent data
+    pub(crate) tangents: Vec<KeyTangent>,
+}
+
+impl AnimCurve {
+    pub fn new(id: i64, times: Vec<KTime>, values: Vec<f32>, interpolations: Vec<KeyInterpolation>) -> Self {
+        debug_assert!(times.len() == values.len() && times.len() == interpolations.len(), "every key needs a time, value and interpolation");
+        if interpolations.contains(&KeyInterpolation::Cubic) {
+            warning!("Curve {} has cubic keys, which are evaluated as linear.", id);
+        }
+
+        AnimCurve {
+            id,
+            tangents: vec![KeyTangent::linear(); times.len()],
+            times,
+            values,
+            interpolations,
+        }
+    }
+
+    /* Curve with the interpolation and tangents of every key taken from its KeyAttrFlags */
+    pub fn with_tangents(id: i64, times: Vec<KTime>, values: Vec<f32>, tangents: Vec<KeyTangent>) -> Self {
+        let interpolations = tangents.iter().map(|t| KeyInterpolation::from_flags(t.flags)).collect();
+        let mut curve = AnimCurve::new(id, times, values, interpolations);
+        curve.tangents = tangents;
+        curve
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn times(&self) -> &[KTime] {
+        &self.times
+    }
+
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    pub fn interpolations(&self) -> &[KeyInterpolation] {
+        &self.interpolations
+    }
+
+    pub fn tangents(&self) -> &[KeyTangent] {
+        &self.tangents
+    }
+
+    /* Value at the given time. Times outside the keys take the value of the nearest key, a curve without keys is 0. */
+    pub fn evaluate(&self, seconds: f64) -> f32 {
+        self.evaluate_at(KTime::from_seconds(seconds))
+    }
+
+    pub fn evaluate_at(&self, time: KTime) -> f32 {
+        let (first, last) = match (self.times.first(), self.times.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return 0.0,
+        };
+        if time <= first {
+            return self.values[0];
+        }
+        if time >= last {
+            return self.values[self.values.len() - 1];
+        }
+
+        // Last key at or before the time, there is always one after it as the time is before the last key
+        let key = self.times.partition_point(|t| *t <= time) - 1;
+        match self.interpolations[key] {
+            KeyInterpolation::Constant => self.values[key],
+            KeyInterpolation::Linear | KeyInterpolation::Cubic => {
+                let start = self.times[key].ticks();
+                let end = self.times[key + 1].ticks();
+                let t = ((time.ticks() - start) as f64 / (end - start) as f64) as f32;
+                self.values[key] + (self.values[key + 1] - self.values[key]) * t
+            }
+        }
+    }
+}
+
+/* Groups the X, Y and Z curves animating one property. Channels without a curve keep the default value. */
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimCurveNode {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    pub(crate) default: glm::Vec3,
+    pub(crate) curves: [Option<AnimCurve>; 3],
+}
+
+impl AnimCurveNode {
+    pub fn new(id: i64, name: String) -> Self {
+        AnimCurveNode {
+            id,
+            name,
+            default: glm::vec3(0.0, 0.0, 0.0),
+            curves: [None, None, None],
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn default(&self) -> glm::Vec3 {
+        self.default
+    }
+
+    /* Curves of the X, Y and Z channels */
+    pub fn curves(&self) -> &[Option<AnimCurve>; 3] {
+        &self.curves
+    }
+
+    pub fn evaluate_vec3(&self, seconds: f64) -> glm::Vec3 {
+        let channel = |i: usize| self.curves[i].as_ref().map_or(self.default[i], |c| c.evaluate(seconds));
+        glm::vec3(channel(0), channel(1), channel(2))
+    }
+}
+
+/* Transform curves of one node, channels the layer does not animate are None */
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeAnimation {
+    pub(crate) target_node_id: i64,
+    pub(crate) translation: Option<AnimCurveNode>,
+    pub(crate) rotation: Option<AnimCurveNode>,
+    pub(crate) scaling: Option<AnimCurveNode>,
+}
+
+impl NodeAnimation {
+    pub fn new(target_node_id: i64) -> Self {
+        NodeAnimation {
+            target_node_id,
+            translation: None,
+            rotation: None,
+            scaling: None,
+        }
+    }
+
+    pub fn target_node_id(&self) -> i64 {
+        self.target_node_id
+    }
+
+    pub fn translation(&self) -> Option<&AnimCurveNode> {
+        self.translation.as_ref()
+    }
+
+    /* Euler angles in degrees */
+    pub fn rotation(&self) -> Option<&AnimCurveNode> {
+        self.rotation.as_ref()
+    }
+
+    pub fn scaling(&self) -> Option<&AnimCurveNode> {
+        self.scaling.as_ref()
+    }
+}
+
+/* Curves animating a property that has no dedicated field yet, such as the color of a material */
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertyAnimation {
+    // Object owning the property, None if the curve node is not connected to one
+    pub(crate) target_id: Option<i64>,
+    pub(crate) property: String,
+    pub(crate) curve_node: AnimCurveNode,
+}
+
+impl PropertyAnimation {
+    pub fn target_id(&self) -> Option<i64> {
+        self.target_id
+    }
+
+    pub fn property(&self) -> &str {
+        &self.property
+    }
+
+    pub fn curve_node(&self) -> &AnimCurveNode {
+        &self.curve_node
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationLayer {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    pub(crate) node_animations: Vec<NodeAnimation>,
+    pub(crate) property_animations: Vec<PropertyAnimation>,
+}
+
+impl AnimationLayer {
+    pub fn new(id: i64, name: String) -> Self {
+        AnimationLayer {
+            id,
+            name,
+            node_animations: Vec::new(),
+            property_animations: Vec::new(),
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn node_animations(&self) -> &[NodeAnimation] {
+        &self.node_animations
+    }
+
+    pub fn property_animations(&self) -> &[PropertyAnimation] {
+        &self.property_animations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::time::TICKS_PER_SECOND;
+
+    fn create_curve(interpolation: KeyInterpolation) -> AnimCurve {
+        // Keys at 0, 1 and 3 seconds
+        let times = vec![KTime(0), KTime(TICKS_PER_SECOND), KTime(3 * TICKS_PER_SECOND)];
+        AnimCurve::new(1, times, vec![0.0, 10.0, 4.0], vec![interpolation; 3])
+    }
+
+    #[test]
+    fn evaluate_should_interpolate_linear_keys() {
+        // Arrange
+        let curve = create_curve(KeyInterpolation::Linear);
+
+        // Act
+        let values: Vec<f32> = [0.0, 0.5, 1.0, 1.25, 2.0, 3.0].iter().map(|t| curve.evaluate(*t)).collect();
+
+        // Assert
+        assert_eq!(values, vec![0.0, 5.0, 10.0, 9.25, 7.0, 4.0]);
+    }
+
+    #[test]
+    fn evaluate_should_clamp_outside_the_keys() {
+        // Arrange
+        let curve = create_curve(KeyInterpolation::Linear);
+
+        // Act
+        let before = curve.evaluate(-1.0);
+        let after = curve.evaluate(10.0);
+
+        // Assert
+        assert_eq!(before, 0.0);
+        assert_eq!(after, 4.0);
+    }
+
+    #[test]
+    fn evaluate_should_hold_constant_keys() {
+        // Arrange
+        let curve = create_curve(KeyInterpolation::Constant);
+
+        // Act
+        let value = curve.evaluate(2.5);
+
+        // Assert
+        assert_eq!(value, 10.0);
+    }
+
+    #[test]
+    fn evaluate_vec3_should_use_defaults_for_missing_channels() {
+        // Arrange
+        let mut node = AnimCurveNode::new(2, "T".to_string());
+        node.default = glm::vec3(1.0, 2.0, 3.0);
+        node.curves[1] = Some(create_curve(KeyInterpolation::Linear));
+
+        // Act
+        let value = node.evaluate_vec3(0.5);
+
+        // Assert
+        assert_eq!(value, glm::vec3(1.0, 5.0, 3.0));
+    }
+}
\ No newline at end of file
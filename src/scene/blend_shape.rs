@@ -0,0 +1,182 @@
+use crate::scene::animation::AnimCurve;
+use std::collections::BTreeMap;
+
+/* Offsets of the vertices a shape moves, vertices not listed stay in place */
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlendShapeTarget {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    // Channel weight at which the shape is fully applied, 0 to 1
+    pub(crate) full_weight: f32,
+    pub(crate) indices: Vec<i32>,
+    pub(crate) deltas: Vec<glm::Vec3>,
+}
+
+impl BlendShapeTarget {
+    pub fn new(id: i64, name: String, full_weight: f32, indices: Vec<i32>, deltas: Vec<glm::Vec3>) -> Self {
+        debug_assert_eq!(indices.len(), deltas.len(), "every moved vertex needs an offset");
+        BlendShapeTarget {
+            id,
+            name,
+            full_weight,
+            indices,
+            deltas,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn full_weight(&self) -> f32 {
+        self.full_weight
+    }
+
+    pub fn indices(&self) -> &[i32] {
+        &self.indices
+    }
+
+    pub fn deltas(&self) -> &[glm::Vec3] {
+        &self.deltas
+    }
+}
+
+/* Morph target channel. With more than one target the ones before the last are in-between shapes, applied in turn
+   as the weight passes their full weight. */
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlendShapeChannel {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    // Weight in the file, 0 to 100
+    pub(crate) deform_percent: f32,
+    // Ordered by full weight
+    pub(crate) targets: Vec<BlendShapeTarget>,
+    // Animation of the deform percent, 0 to 100
+    pub(crate) weight_curve: Option<AnimCurve>,
+}
+
+impl BlendShapeChannel {
+    pub fn new(id: i64, name: String, mut targets: Vec<BlendShapeTarget>) -> Self {
+        targets.sort_by(|a, b| a.full_weight.partial_cmp(&b.full_weight).unwrap_or(std::cmp::Ordering::Equal));
+        BlendShapeChannel {
+            id,
+            name,
+            deform_percent: 0.0,
+            targets,
+            weight_curve: None,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn deform_percent(&self) -> f32 {
+        self.deform_percent
+    }
+
+    pub fn targets(&self) -> &[BlendShapeTarget] {
+        &self.targets
+    }
+
+    pub fn weight_curve(&self) -> Option<&AnimCurve> {
+        self.weight_curve.as_ref()
+    }
+
+    /* Offset of every moved vertex at the given weight, 0 to 1, in vertex order. Between two targets the offsets are
+       blended linearly, below the first one it is scaled down towards zero and above the last one scaled up. */
+    pub fn evaluate_deltas(&self, weight: f32) -> impl Iterator<Item=(usize, glm::Vec3)> {
+        let mut deltas = BTreeMap::new();
+        let mut add = |target: &BlendShapeTarget, factor: f32| {
+            for (index, delta) in target.indices.iter().zip(&target.deltas) {
+                let sum = deltas.entry(*index as usize).or_insert(glm::vec3(0.0, 0.0, 0.0));
+                *sum = *sum + *delta * factor;
+            }
+        };
+
+        let next = self.targets.iter().position(|t| t.full_weight >= weight);
+        match next {
+            _ if weight == 0.0 || self.targets.is_empty() => {}
+            Some(0) => add(&self.targets[0], weight / self.targets[0].full_weight),
+            Some(next) => {
+                let (previous, next) = (&self.targets[next - 1], &self.targets[next]);
+                let t = (weight - previous.full_weight) / (next.full_weight - previous.full_weight);
+                add(previous, 1.0 - t);
+                add(next, t);
+            }
+            None => {
+                let last = &self.targets[self.targets.len() - 1];
+                add(last, weight / last.full_weight);
+            }
+        }
+
+        deltas.into_iter()
+    }
+
+    /* See Mesh::remap_vertex_attributes */
+    pub(crate) fn remap(&mut self, source: &[usize]) {
+        let old_count = source.iter().max().map_or(0, |m| m + 1);
+        let mut copies = vec![Vec::new(); old_count];
+        for (new, old) in source.iter().enumerate() {
+            copies[*old].push(new as i32);
+        }
+
+        for target in &mut self.targets {
+            let mut indices = Vec::with_capacity(target.indices.len());
+            let mut deltas = Vec::with_capacity(target.deltas.len());
+            for (index, delta) in target.indices.iter().zip(&target.deltas) {
+                for copy in copies.get(*index as usize).into_iter().flatten() {
+                    indices.push(*copy);
+                    deltas.push(*delta);
+                }
+            }
+            target.indices = indices;
+            target.deltas = deltas;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_channel() -> BlendShapeChannel {
+        let half = BlendShapeTarget::new(1, "Half".to_string(), 0.5, vec![0, 2], vec![glm::vec3(0.0, 1.0, 0.0), glm::vec3(2.0, 0.0, 0.0)]);
+        let full = BlendShapeTarget::new(2, "Full".to_string(), 1.0, vec![0], vec![glm::vec3(0.0, 3.0, 0.0)]);
+        BlendShapeChannel::new(3, "Smile".to_string(), vec![full, half])
+    }
+
+    #[test]
+    fn evaluate_deltas_should_blend_between_in_between_targets() {
+        // Arrange
+        let channel = create_channel();
+
+        // Act
+        let deltas: Vec<(usize, glm::Vec3)> = channel.evaluate_deltas(0.75).collect();
+
+        // Assert
+        assert_eq!(deltas, vec![(0, glm::vec3(0.0, 2.0, 0.0)), (2, glm::vec3(1.0, 0.0, 0.0))]);
+    }
+
+    #[test]
+    fn evaluate_deltas_should_scale_the_first_target_below_its_weight() {
+        // Arrange
+        let channel = create_channel();
+
+        // Act
+        let deltas: Vec<(usize, glm::Vec3)> = channel.evaluate_deltas(0.25).collect();
+        let none: Vec<(usize, glm::Vec3)> = channel.evaluate_deltas(0.0).collect();
+
+        // Assert
+        assert_eq!(deltas, vec![(0, glm::vec3(0.0, 0.5, 0.0)), (2, glm::vec3(1.0, 0.0, 0.0))]);
+        assert!(none.is_empty());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,649 @@
+use crate::fbx::Header;
+use crate::scene::Scene;
+use crate::scene::animation::{AnimCurve, AnimCurveNode, AnimationLayer, KeyInterpolation, KeyTangent, NodeAnimation, PropertyAnimation};
+use crate::scene::blend_shape::{BlendShapeChannel, BlendShapeTarget};
+use crate::scene::camera::{ApertureMode, Camera, Projection};
+use crate::scene::connections::{Connection, ConnectionKind, Connections};
+use crate::scene::light::{DecayType, Light, LightType};
+use crate::scene::material::{LambertParameters, Material, PhongParameters, ShadingModel};
+use crate::scene::mesh::{ColorSet, Face, Layer, LayerElement, Mesh, Smoothing, UvSet};
+use crate::scene::mesh::content_hash::Fnv1a;
+use crate::scene::node::{CullingMode, InheritType, Node};
+use crate::scene::skin::{Cluster, Skin};
+use crate::scene::texture::{Texture, UvTransform, WrapMode};
+use crate::scene::time::{KTime, TimeMode, TimeSettings};
+use crate::scene::timings::{ImportTimings, ProcessorTiming};
+use crate::scene::transform::{Quat, Transform};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::convert::TryInto;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+const CACHE_MAGIC: &[u8; 8] = b"FBXCACHE";
+
+// Bumped on any change to what is written, caches of other versions are ignored
+pub(crate) const CACHE_VERSION: u32 = 1;
+
+// Lists are read in steps of at most this many elements, so a corrupt length fails on the missing data instead of
+// allocating for it up front
+const MAX_PREALLOCATED: usize = 1 << 16;
+
+const SHORT_LIST: usize = 16;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("scene cache {}", message))
+}
+
+/* The file a cache was made from and how it was imported, stored at the start of the cache to tell whether it is
+   still valid */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CacheSource {
+    pub size: u64,
+    // Modification time in nanoseconds since the Unix epoch, 0 where the platform does not keep it
+    pub modified: u64,
+    // FNV-1a of the contents
+    pub hash: u64,
+    // Fingerprint of the import options and mesh processors, see ImportOptions::fingerprint
+    pub settings: u64,
+}
+
+impl CacheSource {
+    /* Size and modification time of the file, without reading it */
+    pub fn stat(path: &Path, settings: u64) -> io::Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata.modified().ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_nanos() as u64);
+        Ok(CacheSource { size: metadata.len(), modified, hash: 0, settings })
+    }
+
+    /* Same as stat, with the hash of the contents */
+    pub fn of_file(path: &Path, settings: u64) -> io::Result<Self> {
+        let mut source = CacheSource::stat(path, settings)?;
+        source.hash = hash_file(path)?;
+        Ok(source)
+    }
+
+    /* Reads the start of a cache, leaving the reader at the scene. Fails for caches of another version. */
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != CACHE_MAGIC {
+            return Err(invalid("magic is incorrect"));
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != CACHE_VERSION {
+            return Err(invalid(&format!("has version {}, expected {}", version, CACHE_VERSION)));
+        }
+        Ok(CacheSource {
+            size: u64::decode(reader)?,
+            modified: u64::decode(reader)?,
+            hash: u64::decode(reader)?,
+            settings: u64::decode(reader)?,
+        })
+    }
+
+    /* Always the same length, so the start of an existing cache can be written over */
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(CACHE_MAGIC)?;
+        writer.write_u32::<LittleEndian>(CACHE_VERSION)?;
+        for value in &[self.size, self.modified, self.hash, self.settings] {
+            value.encode(writer)?;
+        }
+        Ok(())
+    }
+}
+
+fn hash_file(path: &Path) -> io::Result<u64> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Fnv1a::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hasher.0);
+        }
+        hasher.write(&buffer[..read]);
+    }
+}
+
+impl Scene {
+    /* Writes the scene in a compact binary form that read_cache loads far faster than importing the file again.
+       Everything is kept, including the timings of the import it came from. */
+    pub fn write_cache<W: Write>(&self, mut writer: W, source: &CacheSource) -> io::Result<()> {
+        source.write(&mut writer)?;
+        self.encode(&mut writer)?;
+        writer.flush()
+    }
+
+    /* Reads back a scene written by write_cache along with where it came from. Caches written by another version fail
+       with InvalidData. */
+    pub fn read_cache<R: Read>(mut reader: R) -> io::Result<(CacheSource, Scene)> {
+        let source = CacheSource::read(&mut reader)?;
+        Ok((source, read_scene(&mut reader)?))
+    }
+}
+
+/* The scene of a cache whose start was already read with CacheSource::read */
+pub(crate) fn read_scene<R: Read>(reader: &mut R) -> io::Result<Scene> {
+    Scene::decode(reader)
+}
+
+/* Little endian and field by field in declaration order, lists and strings prefixed with their length */
+trait Encode: Sized {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self>;
+
+    /* Lists of numbers override these to be copied as one block instead of value by value, which is most of what
+       makes loading a cache fast */
+    fn encode_slice<W: Write>(values: &[Self], writer: &mut W) -> io::Result<()> {
+        for value in values {
+            value.encode(writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode_vec<R: Read>(reader: &mut R, length: usize) -> io::Result<Vec<Self>> {
+        let mut values = Vec::with_capacity(length.min(MAX_PREALLOCATED));
+        for _ in 0..length {
+            values.push(Self::decode(reader)?);
+        }
+        Ok(values)
+    }
+}
+
+macro_rules! encode_number {
+    ($type:ty) => {
+        impl Encode for $type {
+            fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                writer.write_all(&self.to_le_bytes())
+            }
+
+            fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+                let mut bytes = [0u8; std::mem::size_of::<$type>()];
+                reader.read_exact(&mut bytes)?;
+                Ok(<$type>::from_le_bytes(bytes))
+            }
+
+            fn encode_slice<W: Write>(values: &[Self], writer: &mut W) -> io::Result<()> {
+                let bytes: Vec<u8> = values.iter().flat_map(|value| value.to_le_bytes()).collect();
+                writer.write_all(&bytes)
+            }
+
+            fn decode_vec<R: Read>(reader: &mut R, length: usize) -> io::Result<Vec<Self>> {
+                const SIZE: usize = std::mem::size_of::<$type>();
+                let mut values = Vec::with_capacity(length.min(MAX_PREALLOCATED));
+                // Short lists such as the indices of a face are not worth a buffer
+                if length <= SHORT_LIST {
+                    for _ in 0..length {
+                        values.push(Self::decode(reader)?);
+                    }
+                    return Ok(values);
+                }
+                let mut bytes = Vec::new();
+                while values.len() < length {
+                    bytes.resize((length - values.len()).min(MAX_PREALLOCATED) * SIZE, 0);
+                    reader.read_exact(&mut bytes)?;
+                    values.extend(bytes.chunks_exact(SIZE).map(|chunk| <$type>::from_le_bytes(chunk.try_into().unwrap())));
+                }
+                Ok(values)
+            }
+        }
+    };
+}
+
+encode_number!(u8);
+encode_number!(i32);
+encode_number!(u32);
+encode_number!(i64);
+encode_number!(u64);
+encode_number!(f32);
+encode_number!(f64);
+
+impl Encode for bool {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (*self as u8).encode(writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match u8::decode(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid("has a bool that is neither 0 nor 1")),
+        }
+    }
+}
+
+impl Encode for usize {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (*self as u64).encode(writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        usize::try_from(u64::decode(reader)?).map_err(|_| invalid("has a length too large for this platform"))
+    }
+
+    fn encode_slice<W: Write>(values: &[Self], writer: &mut W) -> io::Result<()> {
+        u64::encode_slice(&values.iter().map(|value| *value as u64).collect::<Vec<u64>>(), writer)
+    }
+
+    fn decode_vec<R: Read>(reader: &mut R, length: usize) -> io::Result<Vec<Self>> {
+        u64::decode_vec(reader, length)?.into_iter()
+            .map(|value| usize::try_from(value).map_err(|_| invalid("has a length too large for this platform")))
+            .collect()
+    }
+}
+
+impl Encode for String {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.len().encode(writer)?;
+        writer.write_all(self.as_bytes())
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let bytes = Vec::<u8>::decode(reader)?;
+        String::from_utf8(bytes).map_err(|_| invalid("has a string that is not UTF-8"))
+    }
+}
+
+impl Encode for PathBuf {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.to_string_lossy().into_owned().encode(writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(PathBuf::from(String::decode(reader)?))
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.len().encode(writer)?;
+        T::encode_slice(self, writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let length = usize::decode(reader)?;
+        T::decode_vec(reader, length)
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Some(value) => {
+                true.encode(writer)?;
+                value.encode(writer)
+            }
+            None => false.encode(writer),
+        }
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(if bool::decode(reader)? { Some(T::decode(reader)?) } else { None })
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.encode(writer)?;
+        self.1.encode(writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok((A::decode(reader)?, B::decode(reader)?))
+    }
+}
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for value in self {
+            value.encode(writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let values = (0..N).map(|_| T::decode(reader)).collect::<io::Result<Vec<T>>>()?;
+        <[T; N]>::try_from(values).map_err(|_| invalid("has an array of the wrong length"))
+    }
+}
+
+impl Encode for Range<usize> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.start.encode(writer)?;
+        self.end.encode(writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(usize::decode(reader)?..usize::decode(reader)?)
+    }
+}
+
+impl Encode for Duration {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.as_secs().encode(writer)?;
+        self.subsec_nanos().encode(writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(Duration::new(u64::decode(reader)?, u32::decode(reader)?))
+    }
+}
+
+impl Encode for glm::Vec2 {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        [self.x, self.y].encode(writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let [x, y] = <[f32; 2]>::decode(reader)?;
+        Ok(glm::vec2(x, y))
+    }
+
+    fn encode_slice<W: Write>(values: &[Self], writer: &mut W) -> io::Result<()> {
+        f32::encode_slice(&values.iter().flat_map(|v| [v.x, v.y]).collect::<Vec<f32>>(), writer)
+    }
+
+    fn decode_vec<R: Read>(reader: &mut R, length: usize) -> io::Result<Vec<Self>> {
+        let coordinates = f32::decode_vec(reader, length.checked_mul(2).ok_or_else(|| invalid("has a length that overflows"))?)?;
+        Ok(coordinates.chunks_exact(2).map(|c| glm::vec2(c[0], c[1])).collect())
+    }
+}
+
+impl Encode for glm::Vec3 {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        [self.x, self.y, self.z].encode(writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let [x, y, z] = <[f32; 3]>::decode(reader)?;
+        Ok(glm::vec3(x, y, z))
+    }
+
+    fn encode_slice<W: Write>(values: &[Self], writer: &mut W) -> io::Result<()> {
+        f32::encode_slice(&values.iter().flat_map(|v| [v.x, v.y, v.z]).collect::<Vec<f32>>(), writer)
+    }
+
+    fn decode_vec<R: Read>(reader: &mut R, length: usize) -> io::Result<Vec<Self>> {
+        let coordinates = f32::decode_vec(reader, length.checked_mul(3).ok_or_else(|| invalid("has a length that overflows"))?)?;
+        Ok(coordinates.chunks_exact(3).map(|c| glm::vec3(c[0], c[1], c[2])).collect())
+    }
+}
+
+impl Encode for glm::Vec4 {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        [self.x, self.y, self.z, self.w].encode(writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let [x, y, z, w] = <[f32; 4]>::decode(reader)?;
+        Ok(glm::vec4(x, y, z, w))
+    }
+
+    fn encode_slice<W: Write>(values: &[Self], writer: &mut W) -> io::Result<()> {
+        f32::encode_slice(&values.iter().flat_map(|v| [v.x, v.y, v.z, v.w]).collect::<Vec<f32>>(), writer)
+    }
+
+    fn decode_vec<R: Read>(reader: &mut R, length: usize) -> io::Result<Vec<Self>> {
+        let coordinates = f32::decode_vec(reader, length.checked_mul(4).ok_or_else(|| invalid("has a length that overflows"))?)?;
+        Ok(coordinates.chunks_exact(4).map(|c| glm::vec4(c[0], c[1], c[2], c[3])).collect())
+    }
+}
+
+impl Encode for glm::Mat4 {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for column in 0..4 {
+            self[column].encode(writer)?;
+        }
+        Ok(())
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let [c0, c1, c2, c3] = <[glm::Vec4; 4]>::decode(reader)?;
+        Ok(glm::mat4(c0.x, c0.y, c0.z, c0.w, c1.x, c1.y, c1.z, c1.w, c2.x, c2.y, c2.z, c2.w, c3.x, c3.y, c3.z, c3.w))
+    }
+}
+
+impl Encode for KTime {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.encode(writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(KTime(i64::decode(reader)?))
+    }
+}
+
+/* Structs as their fields in the order listed */
+macro_rules! encode_struct {
+    ($type:ty { $($field:ident),* $(,)? }) => {
+        impl Encode for $type {
+            fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                $(self.$field.encode(writer)?;)*
+                Ok(())
+            }
+
+            fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+                Ok(Self { $($field: Encode::decode(reader)?,)* })
+            }
+        }
+    };
+}
+
+/* Enums without data as the position of the variant in the list */
+macro_rules! encode_enum {
+    ($type:ident { $($variant:ident),* $(,)? }) => {
+        impl Encode for $type {
+            fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                let position = [$($type::$variant),*].iter().position(|v| v == self).unwrap_or(0);
+                (position as u8).encode(writer)
+            }
+
+            fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+                let position = u8::decode(reader)? as usize;
+                [$($type::$variant),*].get(position).copied()
+                    .ok_or_else(|| invalid(concat!("has an unknown ", stringify!($type))))
+            }
+        }
+    };
+}
+
+encode_enum!(CullingMode { Off, CounterClockwise, Clockwise });
+encode_enum!(InheritType { RrSs, RSrs, Rrs });
+encode_enum!(WrapMode { Repeat, Clamp });
+encode_enum!(KeyInterpolation { Constant, Linear, Cubic });
+encode_enum!(ApertureMode { HorizontalAndVertical, Horizontal, Vertical, FocalLength });
+encode_enum!(Projection { Perspective, Orthographic });
+encode_enum!(LightType { Point, Directional, Spot, Area, Volume });
+encode_enum!(DecayType { None, Linear, Quadratic, Cubic });
+encode_enum!(ConnectionKind { ObjectObject, ObjectProperty, PropertyObject, PropertyProperty });
+
+// Tag of TimeMode::Custom, the other modes are numbered in order
+const CUSTOM_TIME_MODE: u8 = 255;
+
+const TIME_MODES: [TimeMode; 18] = [
+    TimeMode::Default, TimeMode::Frames120, TimeMode::Frames100, TimeMode::Frames60, TimeMode::Frames50,
+    TimeMode::Frames48, TimeMode::Frames30, TimeMode::Frames30Drop, TimeMode::NtscDropFrame, TimeMode::NtscFullFrame,
+    TimeMode::Pal, TimeMode::Frames24, TimeMode::Frames1000, TimeMode::FilmFullFrame, TimeMode::Frames96,
+    TimeMode::Frames72, TimeMode::Frames59_94, TimeMode::Frames119_88,
+];
+
+impl Encode for TimeMode {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            TimeMode::Custom(rate) => {
+                CUSTOM_TIME_MODE.encode(writer)?;
+                rate.encode(writer)
+            }
+            mode => (TIME_MODES.iter().position(|m| m == mode).unwrap_or(0) as u8).encode(writer),
+        }
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match u8::decode(reader)? {
+            CUSTOM_TIME_MODE => Ok(TimeMode::Custom(f64::decode(reader)?)),
+            tag => TIME_MODES.get(tag as usize).copied().ok_or_else(|| invalid("has an unknown TimeMode")),
+        }
+    }
+}
+
+impl Encode for Smoothing {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Smoothing::None => 0u8.encode(writer),
+            Smoothing::ByPolygon => 1u8.encode(writer),
+            Smoothing::ByEdge(edges) => {
+                2u8.encode(writer)?;
+                // Sorted so the same scene always gives the same bytes
+                let mut edges: Vec<(i32, i32)> = edges.iter().copied().collect();
+                edges.sort_unstable();
+                edges.encode(writer)
+            }
+        }
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match u8::decode(reader)? {
+            0 => Ok(Smoothing::None),
+            1 => Ok(Smoothing::ByPolygon),
+            2 => Ok(Smoothing::ByEdge(Vec::<(i32, i32)>::decode(reader)?.into_iter().collect::<HashSet<_>>())),
+            _ => Err(invalid("has an unknown Smoothing")),
+        }
+    }
+}
+
+impl Encode for ShadingModel {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            ShadingModel::Lambert => 0u8.encode(writer),
+            ShadingModel::Phong => 1u8.encode(writer),
+            ShadingModel::Unknown(name) => {
+                2u8.encode(writer)?;
+                name.encode(writer)
+            }
+        }
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match u8::decode(reader)? {
+            0 => Ok(ShadingModel::Lambert),
+            1 => Ok(ShadingModel::Phong),
+            2 => Ok(ShadingModel::Unknown(String::decode(reader)?)),
+            _ => Err(invalid("has an unknown ShadingModel")),
+        }
+    }
+}
+
+impl Encode for Connections {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.iter().cloned().collect::<Vec<Connection>>().encode(writer)?;
+        let mut classes: Vec<(i64, String)> = self.classes().map(|(id, class)| (id, class.to_string())).collect();
+        classes.sort_unstable();
+        classes.encode(writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let list = Vec::<Connection>::decode(reader)?;
+        let mut connections = Connections::with_capacity(list.len());
+        for connection in list {
+            connections.push(connection);
+        }
+        for (id, class) in Vec::<(i64, String)>::decode(reader)? {
+            connections.set_class(id, &class);
+        }
+        Ok(connections)
+    }
+}
+
+encode_struct!(Header { version, unknown_bytes });
+encode_struct!(Quat { x, y, z, w });
+encode_struct!(Transform { translation, rotation, scale });
+/* Lists of faces a field at a time, with the indices of all faces in one list, as meshes have so many of them */
+impl Encode for Face {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        Face::encode_slice(std::slice::from_ref(self), writer)
+    }
+
+    fn decode<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Face::decode_vec(reader, 1)?.pop().ok_or_else(|| invalid("has a face missing"))
+    }
+
+    fn encode_slice<W: Write>(faces: &[Self], writer: &mut W) -> io::Result<()> {
+        u32::encode_slice(&faces.iter().map(|f| f.indices.len() as u32).collect::<Vec<u32>>(), writer)?;
+        i32::encode_slice(&faces.iter().flat_map(|f| f.indices.iter().copied()).collect::<Vec<i32>>(), writer)?;
+        i32::encode_slice(&faces.iter().map(|f| f.smoothing_group).collect::<Vec<i32>>(), writer)?;
+        usize::encode_slice(&faces.iter().map(|f| f.polygon).collect::<Vec<usize>>(), writer)?;
+        usize::encode_slice(&faces.iter().map(|f| f.material).collect::<Vec<usize>>(), writer)
+    }
+
+    fn decode_vec<R: Read>(reader: &mut R, length: usize) -> io::Result<Vec<Self>> {
+        let counts = u32::decode_vec(reader, length)?;
+        let total = counts.iter().try_fold(0usize, |total, count| total.checked_add(*count as usize))
+            .ok_or_else(|| invalid("has a length that overflows"))?;
+        let indices = i32::decode_vec(reader, total)?;
+        let smoothing_groups = i32::decode_vec(reader, length)?;
+        let polygons = usize::decode_vec(reader, length)?;
+        let materials = usize::decode_vec(reader, length)?;
+
+        let mut start = 0;
+        Ok((0..length).map(|i| {
+            let end = start + counts[i] as usize;
+            let face = Face {
+                indices: indices[start..end].to_vec(),
+                smoothing_group: smoothing_groups[i],
+                polygon: polygons[i],
+                material: materials[i],
+            };
+            start = end;
+            face
+        }).collect())
+    }
+}
+encode_struct!(LayerElement { kind, typed_index });
+encode_struct!(Layer { index, elements });
+encode_struct!(UvSet { name, uvs });
+encode_struct!(ColorSet { name, colors });
+encode_struct!(Cluster { id, joint_id, indices, weights, transform, transform_link });
+encode_struct!(Skin { id, clusters });
+encode_struct!(BlendShapeTarget { id, name, full_weight, indices, deltas });
+encode_struct!(BlendShapeChannel { id, name, deform_percent, targets, weight_curve });
+encode_struct!(Mesh {
+    vertices, faces, name, original_name, normals, uvs, uv_sets, color_sets, layers, smoothing, degenerate_faces, skin,
+    blend_shapes, material_ranges,
+});
+encode_struct!(Node {
+    id, name, parent, children, mesh, transform, geometric_transform, inherit_type, visible, visibility, culling, world,
+    world_dirty,
+});
+encode_struct!(LambertParameters { ambient_color, ambient_factor, diffuse_color, diffuse_factor, emissive_color, emissive_factor });
+encode_struct!(PhongParameters { specular_color, specular_factor, shininess });
+encode_struct!(Material { id, name, shading_model, lambert, phong });
+encode_struct!(UvTransform { translation, scale, rotation });
+encode_struct!(Texture {
+    id, name, file_name, relative_file_name, uv_set, uv_transform, wrap_u, wrap_v, source_directory, resolved_path,
+    content,
+});
+encode_struct!(KeyTangent { flags, data });
+encode_struct!(AnimCurve { id, times, values, interpolations, tangents });
+encode_struct!(AnimCurveNode { id, name, default, curves });
+encode_struct!(NodeAnimation { target_node_id, translation, rotation, scaling });
+encode_struct!(PropertyAnimation { target_id, property, curve_node });
+encode_struct!(AnimationLayer { id, name, node_animations, property_animations });
+encode_struct!(TimeSettings { mode, start, stop });
+encode_struct!(Camera {
+    id, name, node_id, projection, aperture_mode, field_of_view, field_of_view_x, field_of_view_y, focal_length,
+    film_width, film_height, aspect_width, aspect_height, fixed_ratio, near_plane, far_plane, ortho_zoom,
+});
+encode_struct!(Light { id, name, node_id, light_type, color, intensity, decay, inner_angle, outer_angle, cast_shadows });
+encode_struct!(Connection { kind, child, parent, property });
+encode_struct!(ProcessorTiming { name, duration, meshes, faces_in, faces_out, vertices_in, vertices_out });
+encode_struct!(ImportTimings { parse, conversion, processors });
+encode_struct!(Scene {
+    meshes, nodes, materials, textures, animation_layers, time_settings, cameras, lights, connections, timings, header,
+});
@@ -0,0 +1,185 @@
+/* Which of the camera properties define its field of view, the ApertureMode entry */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ApertureMode {
+    // FieldOfViewX and FieldOfViewY
+    HorizontalAndVertical,
+    // FieldOfView is horizontal
+    Horizontal,
+    // FieldOfView is vertical
+    Vertical,
+    // FocalLength together with FilmHeight
+    FocalLength,
+}
+
+impl ApertureMode {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            0 => ApertureMode::HorizontalAndVertical,
+            1 => ApertureMode::Horizontal,
+            3 => ApertureMode::FocalLength,
+            _ => ApertureMode::Vertical,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+}
+
+/* Camera attribute of a node. Defaults are those of the FBX SDK, angles are in degrees and film sizes in inches as
+   in the file. */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Camera {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    // Node the camera is attached to
+    pub(crate) node_id: Option<i64>,
+    pub projection: Projection,
+    pub aperture_mode: ApertureMode,
+    pub field_of_view: f32,
+    pub field_of_view_x: f32,
+    pub field_of_view_y: f32,
+    // Millimeters
+    pub focal_length: f32,
+    pub film_width: f32,
+    pub film_height: f32,
+    // Render resolution, or the ratio in aspect_width when the aspect ratio mode is fixed ratio
+    pub aspect_width: f32,
+    pub aspect_height: f32,
+    pub fixed_ratio: bool,
+    pub near_plane: f32,
+    pub far_plane: f32,
+    pub ortho_zoom: f32,
+}
+
+// Millimeters per inch, focal lengths are given in millimeters and film sizes in inches
+const MM_PER_INCH: f32 = 25.4;
+
+impl Camera {
+    pub fn new(id: i64, name: String) -> Self {
+        Camera {
+            id,
+            name,
+            node_id: None,
+            projection: Projection::Perspective,
+            aperture_mode: ApertureMode::Vertical,
+            field_of_view: 25.115,
+            field_of_view_x: 40.0,
+            field_of_view_y: 40.0,
+            focal_length: 34.89,
+            film_width: 0.816,
+            film_height: 0.612,
+            aspect_width: 320.0,
+            aspect_height: 200.0,
+            fixed_ratio: false,
+            near_plane: 10.0,
+            far_plane: 4000.0,
+            ortho_zoom: 1.0,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn node_id(&self) -> Option<i64> {
+        self.node_id
+    }
+
+    pub fn near_plane(&self) -> f32 {
+        self.near_plane
+    }
+
+    pub fn far_plane(&self) -> f32 {
+        self.far_plane
+    }
+
+    /* Width over height of the rendered image */
+    pub fn aspect_ratio(&self) -> f32 {
+        if self.fixed_ratio || self.aspect_height <= 0.0 {
+            self.aspect_width
+        } else {
+            self.aspect_width / self.aspect_height
+        }
+    }
+
+    /* Vertical field of view, worked out from whichever properties the aperture mode says are in use */
+    pub fn vertical_fov_radians(&self) -> f32 {
+        match self.aperture_mode {
+            ApertureMode::Vertical => self.field_of_view.to_radians(),
+            ApertureMode::HorizontalAndVertical => self.field_of_view_y.to_radians(),
+            ApertureMode::Horizontal => {
+                let half_horizontal = self.field_of_view.to_radians() / 2.0;
+                2.0 * (half_horizontal.tan() / self.aspect_ratio()).atan()
+            }
+            ApertureMode::FocalLength => 2.0 * (self.film_height * MM_PER_INCH / (2.0 * self.focal_length)).atan(),
+        }
+    }
+
+    /* Right handed projection with depth from -1 to 1. Orthographic cameras see ortho_zoom units above and below the
+       view direction. */
+    pub fn projection_matrix(&self, aspect_override: Option<f32>) -> glm::Mat4 {
+        let aspect = aspect_override.unwrap_or_else(|| self.aspect_ratio());
+        let (near, far) = (self.near_plane, self.far_plane);
+        match self.projection {
+            Projection::Perspective => glm::ext::perspective(self.vertical_fov_radians(), aspect, near, far),
+            Projection::Orthographic => {
+                let top = self.ortho_zoom;
+                let right = top * aspect;
+                glm::mat4(
+                    1.0 / right, 0.0, 0.0, 0.0,
+                    0.0, 1.0 / top, 0.0, 0.0,
+                    0.0, 0.0, -2.0 / (far - near), 0.0,
+                    0.0, 0.0, -(far + near) / (far - near), 1.0,
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vertical_fov_radians_should_follow_the_aperture_mode() {
+        // Arrange
+        let mut camera = Camera::new(1, "Camera".to_string());
+        camera.field_of_view = 60.0;
+        camera.aspect_width = 2.0;
+        camera.aspect_height = 1.0;
+
+        // Act
+        camera.aperture_mode = ApertureMode::Vertical;
+        let vertical = camera.vertical_fov_radians();
+        camera.aperture_mode = ApertureMode::Horizontal;
+        let horizontal = camera.vertical_fov_radians();
+
+        // Assert
+        assert!((vertical - 60f32.to_radians()).abs() < 1e-6);
+        assert!((horizontal - 2.0 * (30f32.to_radians().tan() / 2.0).atan()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn projection_matrix_should_map_the_ortho_view_to_clip_space() {
+        // Arrange
+        let mut camera = Camera::new(1, "Camera".to_string());
+        camera.projection = Projection::Orthographic;
+        camera.ortho_zoom = 5.0;
+        camera.near_plane = 1.0;
+        camera.far_plane = 11.0;
+
+        // Act
+        let matrix = camera.projection_matrix(Some(2.0));
+
+        // Assert
+        assert_eq!(matrix * glm::vec4(10.0, 5.0, -1.0, 1.0), glm::vec4(1.0, 1.0, -1.0, 1.0));
+        assert_eq!(matrix * glm::vec4(-10.0, -5.0, -11.0, 1.0), glm::vec4(-1.0, -1.0, 1.0, 1.0));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,278 @@
+use crate::scene::animation::{AnimCurve, AnimCurveNode, AnimationLayer};
+use crate::scene::camera::Camera;
+use crate::scene::light::Light;
+use crate::scene::material::Material;
+use crate::scene::mesh::Mesh;
+use crate::scene::node::Node;
+use crate::scene::skin::Skin;
+use crate::scene::texture::Texture;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConnectionKind {
+    // "OO", such as a geometry belonging to a model
+    ObjectObject,
+    // "OP", an object connected to a property of another, such as a texture on the DiffuseColor of a material
+    ObjectProperty,
+    // "PO"
+    PropertyObject,
+    // "PP"
+    PropertyProperty,
+}
+
+impl ConnectionKind {
+    pub fn from_fbx(kind: &str) -> Option<Self> {
+        match kind {
+            "OO" => Some(ConnectionKind::ObjectObject),
+            "OP" => Some(ConnectionKind::ObjectProperty),
+            "PO" => Some(ConnectionKind::PropertyObject),
+            "PP" => Some(ConnectionKind::PropertyProperty),
+            _ => None
+        }
+    }
+}
+
+/* A C entry of the Connections node. The child is the source of the connection, the parent its destination. */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Connection {
+    pub kind: ConnectionKind,
+    pub child: i64,
+    pub parent: i64,
+    // Name of the destination property for OP connections, such as "DiffuseColor" or "d|X"
+    pub property: Option<String>,
+}
+
+impl Connection {
+    pub fn new(kind: ConnectionKind, child: i64, parent: i64) -> Self {
+        Connection {
+            kind,
+            child,
+            parent,
+            property: None,
+        }
+    }
+
+    pub fn with_property(mut self, property: &str) -> Self {
+        self.property = Some(property.to_string());
+        self
+    }
+
+    /* The property name, empty for connections without one */
+    pub fn property(&self) -> &str {
+        self.property.as_deref().unwrap_or("")
+    }
+}
+
+/* Object class in the file of the scene types, the name of its node in Objects */
+pub trait ObjectClass {
+    const CLASS: &'static str;
+}
+
+impl ObjectClass for Node {
+    const CLASS: &'static str = "Model";
+}
+
+impl ObjectClass for Mesh {
+    const CLASS: &'static str = "Geometry";
+}
+
+impl ObjectClass for Material {
+    const CLASS: &'static str = "Material";
+}
+
+impl ObjectClass for Texture {
+    const CLASS: &'static str = "Texture";
+}
+
+impl ObjectClass for Camera {
+    const CLASS: &'static str = "NodeAttribute";
+}
+
+impl ObjectClass for Light {
+    const CLASS: &'static str = "NodeAttribute";
+}
+
+impl ObjectClass for Skin {
+    const CLASS: &'static str = "Deformer";
+}
+
+impl ObjectClass for AnimationLayer {
+    const CLASS: &'static str = "AnimationLayer";
+}
+
+impl ObjectClass for AnimCurveNode {
+    const CLASS: &'static str = "AnimationCurveNode";
+}
+
+impl ObjectClass for AnimCurve {
+    const CLASS: &'static str = "AnimationCurve";
+}
+
+/* The connections between the objects of a file, indexed by both ends. Connections are kept in file order, repeats
+   of a connection that is already there are left out. */
+#[derive(Clone, Debug, Default)]
+pub struct Connections {
+    connections: Vec<Connection>,
+    seen: HashSet<Connection>,
+    by_child: HashMap<i64, Vec<usize>>,
+    by_parent: HashMap<i64, Vec<usize>>,
+    // Object class of every object id, for the typed lookups
+    classes: HashMap<i64, String>,
+}
+
+impl Connections {
+    pub fn new() -> Self {
+        Connections::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Connections {
+            connections: Vec::with_capacity(capacity),
+            seen: HashSet::with_capacity(capacity),
+            by_child: HashMap::with_capacity(capacity),
+            by_parent: HashMap::with_capacity(capacity),
+            classes: HashMap::new(),
+        }
+    }
+
+    /* Adds a connection, returns false if it was already there */
+    pub fn push(&mut self, connection: Connection) -> bool {
+        if self.seen.contains(&connection) {
+            return false;
+        }
+
+        let index = self.connections.len();
+        self.by_child.entry(connection.child).or_default().push(index);
+        self.by_parent.entry(connection.parent).or_default().push(index);
+        self.seen.insert(connection.clone());
+        self.connections.push(connection);
+        true
+    }
+
+    pub fn set_class(&mut self, id: i64, class: &str) {
+        self.classes.insert(id, class.to_string());
+    }
+
+    /* Every object id with its class, in no particular order */
+    pub(crate) fn classes(&self) -> impl Iterator<Item = (i64, &str)> {
+        self.classes.iter().map(|(id, class)| (*id, class.as_str()))
+    }
+
+    /* Object class of an object, None for ids that are not objects such as the scene root */
+    pub fn class_of(&self, id: i64) -> Option<&str> {
+        self.classes.get(&id).map(|c| c.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.connections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Connection> {
+        self.connections.iter()
+    }
+
+    pub fn of_kind(&self, kind: ConnectionKind) -> impl Iterator<Item = &Connection> {
+        self.connections.iter().filter(move |c| c.kind == kind)
+    }
+
+    /* Connections whose destination is the given object */
+    pub fn children_of(&self, id: i64) -> impl Iterator<Item = &Connection> {
+        self.by_parent.get(&id).into_iter().flatten().map(move |i| &self.connections[*i])
+    }
+
+    /* Connections whose source is the given object */
+    pub fn parents_of(&self, id: i64) -> impl Iterator<Item = &Connection> {
+        self.by_child.get(&id).into_iter().flatten().map(move |i| &self.connections[*i])
+    }
+
+    /* First object the given object is connected to with a connection of the kind, such as the model owning an
+       attribute */
+    pub fn parent_of(&self, id: i64, kind: ConnectionKind) -> Option<i64> {
+        self.parents_of(id).find(|c| c.kind == kind).map(|c| c.parent)
+    }
+
+    /* Object connected to a property of the given object, such as the texture on the DiffuseColor of a material */
+    pub fn child_on_property(&self, id: i64, property: &str) -> Option<i64> {
+        self.children_of(id)
+            .find(|c| c.kind == ConnectionKind::ObjectProperty && c.property() == property)
+            .map(|c| c.child)
+    }
+
+    /* Objects of type T connected to the given object in either direction, children first. Only finds objects whose
+       class was set. */
+    pub fn find_connected<T: ObjectClass>(&self, id: i64) -> impl Iterator<Item = i64> + '_ {
+        self.children_of(id).map(|c| c.child)
+            .chain(self.parents_of(id).map(|c| c.parent))
+            .filter(move |other| self.class_of(*other) == Some(T::CLASS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_should_leave_out_repeated_connections() {
+        // Arrange
+        let mut connections = Connections::new();
+
+        // Act
+        let first = connections.push(Connection::new(ConnectionKind::ObjectObject, 1, 2));
+        let repeated = connections.push(Connection::new(ConnectionKind::ObjectObject, 1, 2));
+        let on_property = connections.push(Connection::new(ConnectionKind::ObjectProperty, 1, 2).with_property("DiffuseColor"));
+
+        // Assert
+        assert!(first && !repeated && on_property);
+        assert_eq!(connections.len(), 2);
+        assert_eq!(connections.child_on_property(2, "DiffuseColor"), Some(1));
+        assert_eq!(connections.child_on_property(2, "SpecularColor"), None);
+    }
+
+    #[test]
+    fn find_connected_should_only_return_objects_of_the_type() {
+        // Arrange
+        let mut connections = Connections::new();
+        connections.set_class(10, "Model");
+        connections.set_class(20, "Material");
+        connections.set_class(30, "Geometry");
+        connections.push(Connection::new(ConnectionKind::ObjectObject, 10, 0));
+        connections.push(Connection::new(ConnectionKind::ObjectObject, 20, 10));
+        connections.push(Connection::new(ConnectionKind::ObjectObject, 30, 10));
+
+        // Act
+        let materials: Vec<i64> = connections.find_connected::<Material>(10).collect();
+        let geometry: Vec<i64> = connections.find_connected::<Mesh>(10).collect();
+        let models: Vec<i64> = connections.find_connected::<Node>(30).collect();
+
+        // Assert
+        assert_eq!(materials, vec![20]);
+        assert_eq!(geometry, vec![30]);
+        assert_eq!(models, vec![10]);
+    }
+
+    #[test]
+    fn connections_should_look_up_large_graphs_through_the_index() {
+        // Arrange
+        let count = 10_000;
+        let mut connections = Connections::with_capacity(count as usize);
+        for child in 1..=count {
+            // A tree where every object has ten children
+            connections.push(Connection::new(ConnectionKind::ObjectObject, child, child / 10));
+        }
+
+        // Act
+        let children: Vec<i64> = connections.children_of(42).map(|c| c.child).collect();
+        let total: usize = (0..=count).map(|id| connections.children_of(id).count()).sum();
+
+        // Assert
+        assert_eq!(children, (420..430).collect::<Vec<i64>>());
+        assert_eq!(connections.parent_of(4321, ConnectionKind::ObjectObject), Some(432));
+        assert_eq!(total, count as usize);
+        // Every query goes straight to its own entries, no query looks at more connections than it returns
+        assert!(connections.by_parent.values().all(|indices| indices.len() <= 10));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,132 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightType {
+    Point,
+    Directional,
+    Spot,
+    Area,
+    Volume,
+}
+
+impl LightType {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            1 => LightType::Directional,
+            2 => LightType::Spot,
+            3 => LightType::Area,
+            4 => LightType::Volume,
+            _ => LightType::Point,
+        }
+    }
+}
+
+/* How the intensity falls off with distance */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DecayType {
+    None,
+    Linear,
+    Quadratic,
+    Cubic,
+}
+
+impl DecayType {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            1 => DecayType::Linear,
+            2 => DecayType::Quadratic,
+            3 => DecayType::Cubic,
+            _ => DecayType::None,
+        }
+    }
+}
+
+/* Light attribute of a node, with the defaults of the FBX SDK. Lights shine along the negative Y axis of their
+   node, directional lights only have that direction and no position. */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Light {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    // Node the light is attached to
+    pub(crate) node_id: Option<i64>,
+    pub light_type: LightType,
+    // Linear, 0 to 1
+    pub color: glm::Vec3,
+    // 100 is the normal brightness
+    pub intensity: f32,
+    pub decay: DecayType,
+    // Spot light cone in degrees, full brightness within the inner angle fading out towards the outer
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    pub cast_shadows: bool,
+}
+
+impl Light {
+    pub fn new(id: i64, name: String) -> Self {
+        Light {
+            id,
+            name,
+            node_id: None,
+            light_type: LightType::Point,
+            color: glm::vec3(1.0, 1.0, 1.0),
+            intensity: 100.0,
+            decay: DecayType::None,
+            inner_angle: 0.0,
+            outer_angle: 45.0,
+            cast_shadows: false,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn node_id(&self) -> Option<i64> {
+        self.node_id
+    }
+
+    /* Intensity with 1 as the normal brightness, the scale engines usually take */
+    pub fn intensity_normalized(&self) -> f32 {
+        self.intensity / 100.0
+    }
+
+    /* Inner and outer cone angles of spot lights, None for other lights */
+    pub fn cone_angles_radians(&self) -> Option<(f32, f32)> {
+        match self.light_type {
+            LightType::Spot => Some((self.inner_angle.to_radians(), self.outer_angle.to_radians())),
+            _ => None
+        }
+    }
+
+    /* Direction the light shines in, given the world transform of its node */
+    pub fn direction(&self, world_transform: &glm::Mat4) -> glm::Vec3 {
+        let direction = *world_transform * glm::vec4(0.0, -1.0, 0.0, 0.0);
+        glm::normalize(glm::vec3(direction.x, direction.y, direction.z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direction_should_point_along_negative_y_of_the_node() {
+        // Arrange
+        let light = Light::new(1, "Sun".to_string());
+        // Quarter turn around Z, taking -Y to +X
+        let rotation = glm::mat4(
+            0.0, 1.0, 0.0, 0.0,
+            -1.0, 0.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            5.0, 5.0, 5.0, 1.0,
+        );
+
+        // Act
+        let direction = light.direction(&rotation);
+
+        // Assert
+        assert_eq!(direction, glm::vec3(1.0, 0.0, 0.0));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,115 @@
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShadingModel {
+    Lambert,
+    Phong,
+    // Anything else the file names, such as "unknown" or a renderer specific model
+    Unknown(String),
+}
+
+impl ShadingModel {
+    /* Exporters disagree on the case, "phong" and "Phong" both appear */
+    pub fn from_name(name: &str) -> Self {
+        if name.eq_ignore_ascii_case("lambert") {
+            ShadingModel::Lambert
+        } else if name.eq_ignore_ascii_case("phong") {
+            ShadingModel::Phong
+        } else {
+            ShadingModel::Unknown(name.to_string())
+        }
+    }
+}
+
+/* Diffuse shading parameters every material has. Defaults are the ones of the FBX SDK, used for entries the file
+   leaves out. */
+#[derive(Clone, Debug, PartialEq)]
+pub struct LambertParameters {
+    pub ambient_color: glm::Vec3,
+    pub ambient_factor: f32,
+    pub diffuse_color: glm::Vec3,
+    pub diffuse_factor: f32,
+    pub emissive_color: glm::Vec3,
+    pub emissive_factor: f32,
+}
+
+impl Default for LambertParameters {
+    fn default() -> Self {
+        LambertParameters {
+            ambient_color: glm::vec3(0.2, 0.2, 0.2),
+            ambient_factor: 1.0,
+            diffuse_color: glm::vec3(0.8, 0.8, 0.8),
+            diffuse_factor: 1.0,
+            emissive_color: glm::vec3(0.0, 0.0, 0.0),
+            emissive_factor: 1.0,
+        }
+    }
+}
+
+/* Specular parameters Phong materials add to the Lambert ones, with the FBX SDK defaults */
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhongParameters {
+    pub specular_color: glm::Vec3,
+    pub specular_factor: f32,
+    pub shininess: f32,
+}
+
+impl Default for PhongParameters {
+    fn default() -> Self {
+        PhongParameters {
+            specular_color: glm::vec3(0.2, 0.2, 0.2),
+            specular_factor: 1.0,
+            shininess: 20.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    pub(crate) shading_model: ShadingModel,
+    pub(crate) lambert: LambertParameters,
+    // Only for Phong materials
+    pub(crate) phong: Option<PhongParameters>,
+}
+
+impl Material {
+    pub fn new(id: i64, name: String, shading_model: ShadingModel) -> Self {
+        let phong = match shading_model {
+            ShadingModel::Phong => Some(PhongParameters::default()),
+            _ => None,
+        };
+
+        Material {
+            id,
+            name,
+            shading_model,
+            lambert: LambertParameters::default(),
+            phong,
+        }
+    }
+
+    /* Same shading, ignoring id and name */
+    pub fn same_content(&self, other: &Material) -> bool {
+        self.shading_model == other.shading_model && self.lambert == other.lambert && self.phong == other.phong
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn shading_model(&self) -> &ShadingModel {
+        &self.shading_model
+    }
+
+    pub fn lambert(&self) -> &LambertParameters {
+        &self.lambert
+    }
+
+    pub fn phong(&self) -> Option<&PhongParameters> {
+        self.phong.as_ref()
+    }
+}
\ No newline at end of file
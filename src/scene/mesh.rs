@@ -1,23 +1,188 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+use crate::scene::skin::Skin;
+use crate::scene::blend_shape::BlendShapeChannel;
+use crate::scene::mesh::corners::{Corner, FacePositions};
+use crate::scene::make_names_unique;
 
 pub mod face_vertex_iterator;
+pub mod content_hash;
+pub mod diff;
+pub mod skin_buffers;
+pub mod corners;
+pub mod topology;
+pub mod measure;
+#[cfg(test)]
+pub(crate) mod fixture;
+#[cfg(feature = "bevy")]
+mod bevy_conversion;
 
 #[derive(Clone)]
 pub struct Face {
-    pub(crate) indices: Vec<i32>
+    pub(crate) indices: Vec<i32>,
+    // 3ds Max style smoothing group bitmask. Faces sharing at least one bit are smoothed together.
+    pub(crate) smoothing_group: i32,
+    // Position of the polygon this face was read from in the file, triangles made from a polygon share it
+    pub(crate) polygon: usize,
+    // Index into the materials of the node using the mesh
+    pub(crate) material: usize,
 }
 
 impl Face {
     pub fn new(indices: Vec<i32>) -> Self {
+        debug_assert!(indices.len() >= 3, "a face needs at least 3 indices, got {}", indices.len());
+        Face::new_unchecked(indices)
+    }
+
+    /* For stray edges and points read from files, which are kept around until the importer has dealt with them */
+    pub(crate) fn new_unchecked(indices: Vec<i32>) -> Self {
         Face{
-            indices
+            indices,
+            smoothing_group: 0,
+            polygon: 0,
+            material: 0,
+        }
+    }
+
+    /* Index into the materials of the node using the mesh, 0 for files without per face materials */
+    pub fn material(&self) -> usize {
+        self.material
+    }
+
+    /* Positions of the corners in order */
+    pub fn positions<'a>(&'a self, vertices: &'a [glm::Vec3]) -> impl Iterator<Item = glm::Vec3> + 'a {
+        self.indices.iter().map(move |i| vertices[*i as usize])
+    }
+
+    /* Number of corners */
+    pub fn arity(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_triangle(&self) -> bool {
+        self.arity() == 3
+    }
+
+    pub fn is_quad(&self) -> bool {
+        self.arity() == 4
+    }
+
+    /* Creates a face with other indices but the same per-face attributes as this one */
+    pub fn with_indices(&self, indices: Vec<i32>) -> Self {
+        Face {
+            indices,
+            smoothing_group: self.smoothing_group,
+            polygon: self.polygon,
+            material: self.material,
         }
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum Smoothing {
+    // No smoothing information available
+    None,
+    // Smoothing groups are stored per face in Face::smoothing_group
+    ByPolygon,
+    // Edges between these vertex index pairs are hard, all other edges are smooth. Pairs are stored lowest index first.
+    ByEdge(HashSet<(i32, i32)>),
+}
+
+/* Element of a layer, kind is the node name of the element such as LayerElementUV, typed_index tells which of the
+   elements of that kind it is */
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerElement {
+    pub(crate) kind: String,
+    pub(crate) typed_index: i32,
+}
+
+impl LayerElement {
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub fn typed_index(&self) -> i32 {
+        self.typed_index
+    }
+}
+
+/* Which elements of a geometry belong together. Layer 0 holds the main channels, further layers the extra ones such
+   as a second uv set. */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layer {
+    pub(crate) index: i32,
+    pub(crate) elements: Vec<LayerElement>,
+}
+
+impl Layer {
+    pub fn index(&self) -> i32 {
+        self.index
+    }
+
+    pub fn elements(&self) -> &[LayerElement] {
+        &self.elements
+    }
+}
+
+/* Per vertex texture coordinates with the name the file gave them */
+#[derive(Clone, Debug, PartialEq)]
+pub struct UvSet {
+    pub(crate) name: String,
+    pub(crate) uvs: Vec<glm::Vec2>,
+}
+
+impl UvSet {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn uvs(&self) -> &[glm::Vec2] {
+        &self.uvs
+    }
+}
+
+/* Per vertex colors with the name the file gave them */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorSet {
+    pub(crate) name: String,
+    pub(crate) colors: Vec<glm::Vec4>,
+}
+
+impl ColorSet {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn colors(&self) -> &[glm::Vec4] {
+        &self.colors
+    }
+}
+
+/* Send + Sync like the rest of the scene, see Scene. Processors get &mut Mesh and so have it to themselves, one mesh
+   per thread with the parallel feature. */
+#[derive(Clone)]
 pub struct Mesh {
     pub(crate) vertices: Vec<glm::Vec3>,
     pub(crate) faces: Vec<Face>,
     pub(crate) name: String,
+    // Name as written in the file, differs from name when the importer had to make it unique
+    pub(crate) original_name: String,
+    // Per vertex normals, empty until generated
+    pub(crate) normals: Vec<glm::Vec3>,
+    // Per vertex texture coordinates, empty if the mesh has none. Same as the first uv set.
+    pub(crate) uvs: Vec<glm::Vec2>,
+    // Every uv set in layer order, the first one is the main set
+    pub(crate) uv_sets: Vec<UvSet>,
+    // Every color set in layer order
+    pub(crate) color_sets: Vec<ColorSet>,
+    pub(crate) layers: Vec<Layer>,
+    pub(crate) smoothing: Smoothing,
+    // Faces with fewer than 3 indices, only kept when the import options ask for them
+    pub(crate) degenerate_faces: Vec<Face>,
+    pub(crate) skin: Option<Skin>,
+    pub(crate) blend_shapes: Vec<BlendShapeChannel>,
+    // Faces using each material, empty until SortFacesByMaterialProcessor has run
+    pub(crate) material_ranges: Vec<(usize, Range<usize>)>,
     // pub(crate) indices: Vec<i32>,
 }
 
@@ -26,7 +191,264 @@ impl Mesh {
         Mesh {
             vertices,
             faces,
+            original_name: name.clone(),
             name,
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            uv_sets: Vec::new(),
+            color_sets: Vec::new(),
+            layers: Vec::new(),
+            smoothing: Smoothing::None,
+            degenerate_faces: Vec::new(),
+            skin: None,
+            blend_shapes: Vec::new(),
+            material_ranges: Vec::new(),
         }
     }
-}
\ No newline at end of file
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /* Smallest and largest coordinates of the vertices, None for a mesh without any */
+    pub fn bounds(&self) -> Option<(glm::Vec3, glm::Vec3)> {
+        let first = *self.vertices.first()?;
+        Some(self.vertices.iter().fold((first, first), |(min, max), v| (glm::min(min, *v), glm::max(max, *v))))
+    }
+
+    /* Corner positions of every triangle. Faces with more corners are skipped, run the TriangulateMeshProcessor first
+       to include them. */
+    pub fn triangles(&self) -> impl Iterator<Item = [glm::Vec3; 3]> + '_ {
+        self.faces.iter()
+            .filter(|face| face.is_triangle())
+            .map(move |face| {
+                let v = |i: usize| self.vertices[face.indices[i] as usize];
+                [v(0), v(1), v(2)]
+            })
+    }
+
+    /* True when every face is a triangle, which is what the exporters and GPU buffers need */
+    pub fn is_triangulated(&self) -> bool {
+        self.faces.iter().all(|face| face.is_triangle())
+    }
+
+    /* Number of faces by their number of corners, to tell whether the mesh needs triangulating at all and to spot
+       faces with far more corners than an asset should have */
+    pub fn polygon_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for face in &self.faces {
+            *histogram.entry(face.arity()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /* Corners of a face in order, with the attributes of their vertices */
+    pub fn face_corners(&self, face: usize) -> impl Iterator<Item = Corner> + '_ {
+        self.faces[face].indices.iter().map(move |i| Corner::of(self, *i as usize))
+    }
+
+    /* Every face along with the positions of its corners, without allocating per face */
+    pub fn faces_with_positions(&self) -> FacePositions<'_> {
+        FacePositions::new(&self.faces, &self.vertices)
+    }
+
+    /* Layer composition read from the file, empty when the file has no Layer nodes */
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    pub fn uv_sets(&self) -> &[UvSet] {
+        &self.uv_sets
+    }
+
+    /* Names of the uv sets in order. Names are unique within a mesh, repeated ones get numbered suffixes the same way
+       mesh names do. */
+    pub fn uv_set_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.uv_sets.iter().map(|set| set.name())
+    }
+
+    pub fn uv_set(&self, name: &str) -> Option<&UvSet> {
+        self.uv_sets.iter().find(|set| set.name == name)
+    }
+
+    pub fn uv_set_at(&self, index: usize) -> Option<&UvSet> {
+        self.uv_sets.get(index)
+    }
+
+    pub fn color_sets(&self) -> &[ColorSet] {
+        &self.color_sets
+    }
+
+    /* Names of the color sets in order, unique within the mesh like the uv set names */
+    pub fn color_set_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.color_sets.iter().map(|set| set.name())
+    }
+
+    pub fn color_set(&self, name: &str) -> Option<&ColorSet> {
+        self.color_sets.iter().find(|set| set.name == name)
+    }
+
+    pub fn color_set_at(&self, index: usize) -> Option<&ColorSet> {
+        self.color_sets.get(index)
+    }
+
+    /* Adds a uv set, the name gets a suffix if another set has it. Returns the name it got. */
+    pub(crate) fn add_uv_set(&mut self, name: &str, uvs: Vec<glm::Vec2>) -> &str {
+        self.uv_sets.push(UvSet { name: name.to_string(), uvs });
+        make_names_unique(self.uv_sets.iter_mut().map(|set| &mut set.name).collect());
+        &self.uv_sets.last().unwrap().name
+    }
+
+    /* Adds a color set, named the same way as by add_uv_set */
+    pub(crate) fn add_color_set(&mut self, name: &str, colors: Vec<glm::Vec4>) -> &str {
+        self.color_sets.push(ColorSet { name: name.to_string(), colors });
+        make_names_unique(self.color_sets.iter_mut().map(|set| &mut set.name).collect());
+        &self.color_sets.last().unwrap().name
+    }
+
+    /* For processors adding or removing vertices: afterwards vertex i takes its uvs, in every set, its skin weights
+       and its blend shape offsets from what was vertex source[i]. */
+    pub(crate) fn remap_vertex_attributes(&mut self, source: &[usize]) {
+        if !self.uvs.is_empty() {
+            self.uvs = source.iter().map(|i| self.uvs[*i]).collect();
+        }
+        for set in &mut self.uv_sets {
+            set.uvs = source.iter().map(|i| set.uvs[*i]).collect();
+        }
+        for set in &mut self.color_sets {
+            set.colors = source.iter().map(|i| set.colors[*i]).collect();
+        }
+        if let Some(skin) = &mut self.skin {
+            skin.remap(source);
+        }
+        for channel in &mut self.blend_shapes {
+            channel.remap(source);
+        }
+    }
+
+    /* For processors copying vertices: hard edges of a vertex also apply to its copies, copies[i] lists the copies
+       made of vertex i */
+    pub(crate) fn copy_hard_edges(&mut self, copies: &[Vec<usize>]) {
+        if let Smoothing::ByEdge(hard_edges) = &self.smoothing {
+            let mut expanded = HashSet::new();
+            for (a, b) in hard_edges {
+                let all_a: Vec<i32> = std::iter::once(*a).chain(copies[*a as usize].iter().map(|c| *c as i32)).collect();
+                let all_b: Vec<i32> = std::iter::once(*b).chain(copies[*b as usize].iter().map(|c| *c as i32)).collect();
+                for x in &all_a {
+                    for y in &all_b {
+                        expanded.insert((*x.min(y), *x.max(y)));
+                    }
+                }
+            }
+            self.smoothing = Smoothing::ByEdge(expanded);
+        }
+    }
+
+    pub fn skin(&self) -> Option<&Skin> {
+        self.skin.as_ref()
+    }
+
+    pub fn blend_shapes(&self) -> &[BlendShapeChannel] {
+        &self.blend_shapes
+    }
+
+    /* Name as written in the file, before the importer made it unique */
+    pub fn original_name(&self) -> &str {
+        &self.original_name
+    }
+
+    /* Material index along with the range of faces using it, one draw call each. Only filled in by
+       SortFacesByMaterialProcessor, processors changing the faces afterwards leave the ranges out of date. */
+    pub fn material_ranges(&self) -> &[(usize, Range<usize>)] {
+        &self.material_ranges
+    }
+
+    /* Faces with fewer than 3 indices, see ImportOptions::with_keep_degenerate_faces */
+    pub fn degenerate_faces(&self) -> &[Face] {
+        &self.degenerate_faces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polygon_histogram_should_count_faces_by_arity() {
+        // Arrange
+        let vertices = vec![glm::vec3(0.0, 0.0, 0.0); 5];
+        let faces = vec![
+            Face::new(vec![0, 1, 2]),
+            Face::new(vec![0, 1, 2, 3]),
+            Face::new(vec![1, 2, 3]),
+            Face::new(vec![0, 1, 2, 3, 4]),
+        ];
+        let mesh = Mesh::new("mixed".to_string(), vertices, faces);
+
+        // Act
+        let histogram = mesh.polygon_histogram();
+
+        // Assert
+        assert_eq!(histogram.into_iter().collect::<Vec<_>>(), vec![(3, 2), (4, 1), (5, 1)]);
+        assert!(!mesh.is_triangulated());
+        assert!(mesh.faces[1].is_quad());
+        assert!(!mesh.faces[1].is_triangle());
+    }
+
+    fn create_mesh_with_uv_sets(names: &[&str]) -> Mesh {
+        let vertices = vec![glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0)];
+        let mut mesh = Mesh::new("triangle".to_string(), vertices, vec![Face::new(vec![0, 1, 2])]);
+        for (i, name) in names.iter().enumerate() {
+            mesh.add_uv_set(name, vec![glm::vec2(i as f32, 0.0); 3]);
+        }
+        mesh
+    }
+
+    #[test]
+    fn uv_set_should_resolve_sets_by_name() {
+        // Arrange
+        let mut mesh = create_mesh_with_uv_sets(&["map1", "lightmap"]);
+        mesh.add_color_set("colorSet1", vec![glm::vec4(1.0, 0.0, 0.0, 1.0); 3]);
+
+        // Act
+        let map1 = mesh.uv_set("map1");
+        let lightmap = mesh.uv_set("lightmap");
+
+        // Assert
+        assert_eq!(map1.map(|s| s.uvs()[0]), Some(glm::vec2(0.0, 0.0)));
+        assert_eq!(lightmap.map(|s| s.uvs()[0]), Some(glm::vec2(1.0, 0.0)));
+        assert_eq!(mesh.uv_set_at(1), lightmap);
+        assert_eq!(mesh.color_set("colorSet1"), mesh.color_set_at(0));
+        assert!(mesh.uv_set("missing").is_none());
+        assert!(mesh.color_set("map1").is_none());
+        assert!(mesh.uv_set_at(2).is_none());
+    }
+
+    #[test]
+    fn add_uv_set_should_number_repeated_names() {
+        // Arrange
+        let mut mesh = create_mesh_with_uv_sets(&["map1", "map1.001"]);
+
+        // Act
+        let name = mesh.add_uv_set("map1", vec![glm::vec2(0.0, 0.0); 3]).to_string();
+
+        // Assert
+        assert_eq!(name, "map1.002");
+        assert_eq!(mesh.uv_set_names().collect::<Vec<_>>(), vec!["map1", "map1.001", "map1.002"]);
+    }
+
+    #[test]
+    fn is_triangulated_should_be_true_for_triangles_only_and_empty_meshes() {
+        // Arrange
+        let triangles = Mesh::new("triangles".to_string(), vec![glm::vec3(0.0, 0.0, 0.0); 4],
+                                  vec![Face::new(vec![0, 1, 2]), Face::new(vec![1, 2, 3])]);
+        let empty = Mesh::new("empty".to_string(), Vec::new(), Vec::new());
+
+        // Act
+        let result = (triangles.is_triangulated(), empty.is_triangulated());
+
+        // Assert
+        assert_eq!(result, (true, true));
+        assert!(empty.polygon_histogram().is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::warnings::warning;
+use crate::scene::mesh::Mesh;
+use crate::mesh_processor::MeshProcessor;
+use crate::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
+use bevy::render::mesh::{Indices, Mesh as BevyMesh};
+use bevy::render::render_resource::PrimitiveTopology;
+use std::borrow::Cow;
+
+/* Bevy only draws triangle lists, so meshes with larger faces are triangulated on a copy first. Faces that are still
+   not triangles after that are left out with a warning. Normals and uvs are only added when there is one per
+   vertex. */
+impl From<&Mesh> for BevyMesh {
+    fn from(mesh: &Mesh) -> Self {
+        let mesh = if mesh.is_triangulated() {
+            Cow::Borrowed(mesh)
+        } else {
+            let mut triangulated = mesh.clone();
+            TriangulateMeshProcessor::new().process(&mut triangulated);
+            Cow::Owned(triangulated)
+        };
+
+        let positions: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| [v.x, v.y, v.z]).collect();
+        let indices: Vec<u32> = mesh.faces.iter()
+            .filter(|face| face.is_triangle())
+            .flat_map(|face| face.indices.iter().map(|i| *i as u32))
+            .collect();
+        let left_out = mesh.faces.len() - indices.len() / 3;
+        if left_out > 0 {
+            warning!("Left out {} faces of mesh '{}' that could not be triangulated.", left_out, mesh.name);
+        }
+
+        let mut result = BevyMesh::new(PrimitiveTopology::TriangleList);
+        result.insert_attribute(BevyMesh::ATTRIBUTE_POSITION, positions);
+        if mesh.normals.len() == mesh.vertices.len() {
+            let normals: Vec<[f32; 3]> = mesh.normals.iter().map(|n| [n.x, n.y, n.z]).collect();
+            result.insert_attribute(BevyMesh::ATTRIBUTE_NORMAL, normals);
+        }
+        if mesh.uvs.len() == mesh.vertices.len() {
+            let uvs: Vec<[f32; 2]> = mesh.uvs.iter().map(|uv| [uv.x, uv.y]).collect();
+            result.insert_attribute(BevyMesh::ATTRIBUTE_UV_0, uvs);
+        }
+        result.set_indices(Some(Indices::U32(indices)));
+        result
+    }
+}
+
+impl Mesh {
+    pub fn to_bevy_mesh(&self) -> BevyMesh {
+        BevyMesh::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::{fixture, Face};
+    use bevy::render::mesh::VertexAttributeValues;
+
+    #[test]
+    fn from_should_triangulate_the_faces_into_a_u32_index_buffer() {
+        // Arrange
+        let mut mesh = fixture::cube(0.0, 1.0);
+        mesh.normals = mesh.vertices.iter().map(|v| glm::normalize(*v - glm::vec3(0.5, 0.5, 0.5))).collect();
+
+        // Act
+        let result = BevyMesh::from(&mesh);
+
+        // Assert
+        assert_eq!(result.count_vertices(), 8);
+        match result.indices() {
+            Some(Indices::U32(indices)) => {
+                assert_eq!(indices.len(), 36);
+                assert!(indices.iter().all(|i| *i < 8));
+            }
+            _ => panic!("expected u32 indices"),
+        }
+        match result.attribute(BevyMesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float32x3(normals)) => assert_eq!(normals.len(), 8),
+            _ => panic!("expected normals"),
+        }
+        assert!(result.attribute(BevyMesh::ATTRIBUTE_UV_0).is_none());
+    }
+
+    #[test]
+    fn from_should_leave_out_faces_it_cannot_triangulate() {
+        // Arrange
+        let vertices = vec![glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0)];
+        let mesh = Mesh::new("edge".to_string(), vertices, vec![Face::new(vec![0, 1, 2]), Face::new_unchecked(vec![1, 2])]);
+
+        // Act
+        let result = mesh.to_bevy_mesh();
+
+        // Assert
+        assert_eq!(result.indices().map(|indices| indices.len()), Some(3));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::scene::mesh::{Mesh, Face};
+
+// Positions closer together than this hash the same, enough to absorb the noise of a float round trip
+pub const DEFAULT_HASH_EPSILON: f32 = 1e-5;
+
+/* FNV-1a, written out so the hashes stay the same across Rust versions and can be stored by asset pipelines */
+pub(crate) struct Fnv1a(pub(crate) u64);
+
+impl Fnv1a {
+    pub(crate) fn new() -> Self {
+        Fnv1a(0xcbf29ce484222325)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_i64(&mut self, value: i64) {
+        self.write(&value.to_le_bytes());
+    }
+}
+
+/* Face indices starting at the lowest index, keeping the winding, so the same polygon written from another corner
+   compares equal */
+pub(crate) fn canonical_face(indices: &[i32]) -> Vec<i32> {
+    let start = (0..indices.len()).min_by_key(|i| indices[*i]).unwrap_or(0);
+    let mut canonical = indices.to_vec();
+    canonical.rotate_left(start);
+    canonical
+}
+
+/* Hashes the geometry of a mesh, leaving out its name, so identical meshes from different files hash the same.
+   Positions are quantized to a grid of epsilon sized cells and faces are put in a canonical order first. Normals and
+   uvs are only included when asked for. */
+pub struct MeshHasher {
+    epsilon: f32,
+    include_normals: bool,
+    include_uvs: bool,
+}
+
+impl MeshHasher {
+    pub fn new() -> Self {
+        MeshHasher {
+            epsilon: DEFAULT_HASH_EPSILON,
+            include_normals: false,
+            include_uvs: false,
+        }
+    }
+
+    pub fn with_epsilon(mut self, epsilon: f32) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn with_normals(mut self, include_normals: bool) -> Self {
+        self.include_normals = include_normals;
+        self
+    }
+
+    pub fn with_uvs(mut self, include_uvs: bool) -> Self {
+        self.include_uvs = include_uvs;
+        self
+    }
+
+    fn write_quantized(&self, hasher: &mut Fnv1a, values: &[f32]) {
+        let cell = self.epsilon.max(f32::MIN_POSITIVE);
+        for value in values {
+            hasher.write_i64((*value / cell).round() as i64);
+        }
+    }
+
+    /* Sorted, so exporters writing the same polygons in another order give the same list */
+    fn canonical_faces(faces: &[Face]) -> Vec<Vec<i32>> {
+        let mut canonical: Vec<Vec<i32>> = faces.iter().map(|face| canonical_face(&face.indices)).collect();
+        canonical.sort();
+        canonical
+    }
+
+    pub fn hash(&self, mesh: &Mesh) -> u64 {
+        let mut hasher = Fnv1a::new();
+
+        hasher.write_i64(mesh.vertices.len() as i64);
+        for vertex in &mesh.vertices {
+            self.write_quantized(&mut hasher, &[vertex.x, vertex.y, vertex.z]);
+        }
+
+        let faces = Self::canonical_faces(&mesh.faces);
+        hasher.write_i64(faces.len() as i64);
+        for face in faces {
+            hasher.write_i64(face.len() as i64);
+            for index in face {
+                hasher.write_i64(index as i64);
+            }
+        }
+
+        if self.include_normals {
+            hasher.write_i64(mesh.normals.len() as i64);
+            for normal in &mesh.normals {
+                self.write_quantized(&mut hasher, &[normal.x, normal.y, normal.z]);
+            }
+        }
+
+        if self.include_uvs {
+            hasher.write_i64(mesh.uvs.len() as i64);
+            for uv in &mesh.uvs {
+                self.write_quantized(&mut hasher, &[uv.x, uv.y]);
+            }
+        }
+
+        hasher.0
+    }
+}
+
+impl Default for MeshHasher {
+    fn default() -> Self {
+        MeshHasher::new()
+    }
+}
+
+impl Mesh {
+    /* Hash of the positions and faces, see MeshHasher for hashing other channels or with another epsilon */
+    pub fn content_hash(&self) -> u64 {
+        MeshHasher::new().hash(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_quad(name: &str) -> Mesh {
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+        ];
+        Mesh::new(name.to_string(), vertices, vec![Face::new(vec![0, 1, 2]), Face::new(vec![0, 2, 3])])
+    }
+
+    #[test]
+    fn content_hash_should_ignore_names_float_noise_and_face_order() {
+        // Arrange
+        let a = create_quad("Quad");
+        let mut b = create_quad("Quad_exported");
+        b.vertices[2].x += 1e-7;
+        b.faces = vec![Face::new(vec![3, 0, 2]), Face::new(vec![1, 2, 0])];
+
+        // Act
+        let hashes = (a.content_hash(), b.content_hash());
+
+        // Assert
+        assert_eq!(hashes.0, hashes.1);
+    }
+
+    #[test]
+    fn content_hash_should_change_when_a_vertex_moves_further_than_epsilon() {
+        // Arrange
+        let a = create_quad("Quad");
+        let mut b = create_quad("Quad");
+        b.vertices[2].y += 0.001;
+
+        // Act
+        let hashes = (a.content_hash(), b.content_hash());
+
+        // Assert
+        assert_ne!(hashes.0, hashes.1);
+    }
+
+    #[test]
+    fn content_hash_should_change_when_winding_flips() {
+        // Arrange
+        let a = create_quad("Quad");
+        let mut b = create_quad("Quad");
+        b.faces[0] = Face::new(vec![0, 2, 1]);
+
+        // Act
+        let hashes = (a.content_hash(), b.content_hash());
+
+        // Assert
+        assert_ne!(hashes.0, hashes.1);
+    }
+
+    #[test]
+    fn hash_should_only_include_uvs_when_asked() {
+        // Arrange
+        let a = create_quad("Quad");
+        let mut b = create_quad("Quad");
+        b.uvs = vec![glm::vec2(0.0, 0.0); 4];
+        let with_uvs = MeshHasher::new().with_uvs(true);
+
+        // Act
+        let without = (a.content_hash(), b.content_hash());
+        let with = (with_uvs.hash(&a), with_uvs.hash(&b));
+
+        // Assert
+        assert_eq!(without.0, without.1);
+        assert_ne!(with.0, with.1);
+    }
+}
\ No newline at end of file
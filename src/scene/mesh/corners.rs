@@ -0,0 +1,130 @@
+use crate::scene::mesh::{Face, Mesh};
+
+/* One corner of a face with the vertex attributes the mesh has */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Corner {
+    pub vertex: usize,
+    pub position: glm::Vec3,
+    // None until normals are generated
+    pub normal: Option<glm::Vec3>,
+    // From the main uv set, None for meshes without one
+    pub uv: Option<glm::Vec2>,
+}
+
+impl Corner {
+    pub(crate) fn of(mesh: &Mesh, vertex: usize) -> Self {
+        Corner {
+            vertex,
+            position: mesh.vertices[vertex],
+            normal: mesh.normals.get(vertex).copied(),
+            uv: mesh.uvs.get(vertex).copied(),
+        }
+    }
+}
+
+/* Walks the faces of a mesh along with the positions of their corners, see Mesh::faces_with_positions. The positions
+   are gathered into one buffer that is reused for every face, so this is not an Iterator. */
+pub struct FacePositions<'a> {
+    faces: std::slice::Iter<'a, Face>,
+    vertices: &'a [glm::Vec3],
+    positions: Vec<glm::Vec3>,
+}
+
+impl<'a> FacePositions<'a> {
+    pub(crate) fn new(faces: &'a [Face], vertices: &'a [glm::Vec3]) -> Self {
+        FacePositions {
+            faces: faces.iter(),
+            vertices,
+            positions: Vec::new(),
+        }
+    }
+
+    pub fn next_face(&mut self) -> Option<(&'a Face, &[glm::Vec3])> {
+        let face = self.faces.next()?;
+        self.positions.clear();
+        self.positions.extend(face.positions(self.vertices));
+        Some((face, &self.positions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh_processor::MeshProcessor;
+    use crate::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
+
+    /* A quad and a triangle next to it, with a uv per vertex */
+    fn create_mixed_mesh() -> Mesh {
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec3(2.0, 0.0, 0.0),
+        ];
+        let mut mesh = Mesh::new("mixed".to_string(), vertices, vec![Face::new(vec![0, 1, 2, 3]), Face::new(vec![1, 4, 2])]);
+        mesh.uvs = mesh.vertices.iter().map(|v| glm::vec2(v.x * 0.5, v.y)).collect();
+        mesh
+    }
+
+    #[test]
+    fn triangles_should_yield_nothing_for_an_empty_mesh() {
+        // Arrange
+        let mesh = Mesh::new("empty".to_string(), Vec::new(), Vec::new());
+
+        // Act
+        let count = mesh.triangles().count();
+        let mut faces = mesh.faces_with_positions();
+
+        // Assert
+        assert_eq!(count, 0);
+        assert!(faces.next_face().is_none());
+    }
+
+    #[test]
+    fn triangles_should_skip_faces_that_are_not_triangles() {
+        // Arrange
+        let mesh = create_mixed_mesh();
+
+        // Act
+        let triangles: Vec<[glm::Vec3; 3]> = mesh.triangles().collect();
+
+        // Assert
+        assert_eq!(triangles, vec![[glm::vec3(1.0, 0.0, 0.0), glm::vec3(2.0, 0.0, 0.0), glm::vec3(1.0, 1.0, 0.0)]]);
+    }
+
+    #[test]
+    fn faces_with_positions_should_give_the_corners_of_every_face() {
+        // Arrange
+        let mesh = create_mixed_mesh();
+        let mut faces = mesh.faces_with_positions();
+
+        // Act
+        let quad = faces.next_face().map(|(face, positions)| (face.indices.len(), positions.to_vec()));
+        let triangle = faces.next_face().map(|(face, positions)| (face.indices.len(), positions.to_vec()));
+
+        // Assert
+        assert_eq!(quad.unwrap(), (4, mesh.vertices[0..4].to_vec()));
+        assert_eq!(triangle.unwrap(), (3, vec![mesh.vertices[1], mesh.vertices[4], mesh.vertices[2]]));
+        assert!(faces.next_face().is_none());
+    }
+
+    #[test]
+    fn face_corners_should_stay_consistent_after_triangulation() {
+        // Arrange
+        let mut mesh = create_mixed_mesh();
+
+        // Act
+        TriangulateMeshProcessor::new().process(&mut mesh);
+
+        // Assert
+        assert_eq!(mesh.triangles().count(), 3);
+        for face in 0..mesh.faces.len() {
+            for corner in mesh.face_corners(face) {
+                assert_eq!(corner.position, mesh.vertices[corner.vertex]);
+                assert_eq!(corner.uv, Some(glm::vec2(corner.position.x * 0.5, corner.position.y)));
+                assert_eq!(corner.normal, None);
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::scene::mesh::Mesh;
+use crate::scene::mesh::content_hash::canonical_face;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VertexMatching {
+    // Vertex i of one mesh is compared with vertex i of the other
+    SameOrder,
+    // Every vertex is compared with the closest vertex of the other mesh, for exporters that reorder vertices
+    Nearest,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Channel {
+    Normals,
+    Uvs,
+}
+
+pub struct DiffTolerances {
+    position: f32,
+    matching: VertexMatching,
+}
+
+impl DiffTolerances {
+    pub fn new() -> Self {
+        DiffTolerances {
+            position: 1e-5,
+            matching: VertexMatching::SameOrder,
+        }
+    }
+
+    /* Largest distance between matched vertices that still counts as the same position */
+    pub fn with_position(mut self, position: f32) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_matching(mut self, matching: VertexMatching) -> Self {
+        self.matching = matching;
+        self
+    }
+}
+
+impl Default for DiffTolerances {
+    fn default() -> Self {
+        DiffTolerances::new()
+    }
+}
+
+/* Differences between two meshes, a to b. Counts are how many more b has than a. */
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshDiff {
+    pub vertex_count_delta: isize,
+    pub face_count_delta: isize,
+    // Largest distance between a vertex and its match in the other mesh, in either direction
+    pub max_position_deviation: f32,
+    // Faces of b that a does not have, and the other way around, compared after vertex matching
+    pub faces_added: usize,
+    pub faces_removed: usize,
+    // Channels only one of the meshes has
+    pub channel_differences: Vec<Channel>,
+    position_tolerance: f32,
+}
+
+impl MeshDiff {
+    pub fn is_equivalent(&self) -> bool {
+        self.vertex_count_delta == 0
+            && self.face_count_delta == 0
+            && self.max_position_deviation <= self.position_tolerance
+            && self.faces_added == 0
+            && self.faces_removed == 0
+            && self.channel_differences.is_empty()
+    }
+}
+
+/* Index of the closest vertex in to for every vertex in from, along with the largest of those distances */
+fn nearest_vertices(from: &[glm::Vec3], to: &[glm::Vec3]) -> (Vec<Option<usize>>, f32) {
+    let mut deviation: f32 = 0.0;
+    let matches = from.iter()
+        .map(|v| {
+            let nearest = to.iter().enumerate()
+                .map(|(index, other)| (index, glm::distance(*v, *other)))
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+            if let Some((_, distance)) = nearest {
+                deviation = deviation.max(distance);
+            }
+            nearest.map(|(index, _)| index)
+        })
+        .collect();
+    (matches, deviation)
+}
+
+pub fn mesh_diff(a: &Mesh, b: &Mesh, tolerances: &DiffTolerances) -> MeshDiff {
+    let (remap, max_position_deviation) = match tolerances.matching {
+        VertexMatching::SameOrder => {
+            let deviation = a.vertices.iter().zip(b.vertices.iter())
+                .map(|(a, b)| glm::distance(*a, *b))
+                .fold(0.0, f32::max);
+            ((0..a.vertices.len()).map(Some).collect(), deviation)
+        }
+        VertexMatching::Nearest => {
+            let (remap, forward) = nearest_vertices(&a.vertices, &b.vertices);
+            let (_, backward) = nearest_vertices(&b.vertices, &a.vertices);
+            (remap, forward.max(backward))
+        }
+    };
+
+    // Faces of a are written with the indices of their matching b vertices, then both are counted as multisets
+    let mut face_counts: HashMap<Vec<i32>, isize> = HashMap::new();
+    for face in &a.faces {
+        let indices: Vec<i32> = face.indices.iter()
+            .map(|i| remap.get(*i as usize).copied().flatten().map_or(-1, |m| m as i32))
+            .collect();
+        *face_counts.entry(canonical_face(&indices)).or_insert(0) -= 1;
+    }
+    for face in &b.faces {
+        *face_counts.entry(canonical_face(&face.indices)).or_insert(0) += 1;
+    }
+
+    let mut channel_differences = Vec::new();
+    if a.normals.is_empty() != b.normals.is_empty() {
+        channel_differences.push(Channel::Normals);
+    }
+    if a.uvs.is_empty() != b.uvs.is_empty() {
+        channel_differences.push(Channel::Uvs);
+    }
+
+    MeshDiff {
+        vertex_count_delta: b.vertices.len() as isize - a.vertices.len() as isize,
+        face_count_delta: b.faces.len() as isize - a.faces.len() as isize,
+        max_position_deviation,
+        faces_added: face_counts.values().filter(|c| **c > 0).map(|c| *c as usize).sum(),
+        faces_removed: face_counts.values().filter(|c| **c < 0).map(|c| -*c as usize).sum(),
+        channel_differences,
+        position_tolerance: tolerances.position,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+
+    fn create_quad() -> Mesh {
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+        ];
+        Mesh::new("quad".to_string(), vertices, vec![Face::new(vec![0, 1, 2]), Face::new(vec![0, 2, 3])])
+    }
+
+    #[test]
+    fn mesh_diff_should_find_identical_meshes_equivalent() {
+        // Arrange
+        let a = create_quad();
+        let b = create_quad();
+
+        // Act
+        let diff = mesh_diff(&a, &b, &DiffTolerances::new());
+
+        // Assert
+        assert!(diff.is_equivalent());
+    }
+
+    #[test]
+    fn mesh_diff_should_report_moved_vertices_and_changed_faces() {
+        // Arrange
+        let a = create_quad();
+        let mut b = create_quad();
+        b.vertices[2].z = 0.5;
+        b.faces[1] = Face::new(vec![0, 3, 2]);
+        b.uvs = vec![glm::vec2(0.0, 0.0); 4];
+
+        // Act
+        let diff = mesh_diff(&a, &b, &DiffTolerances::new());
+
+        // Assert
+        assert!(!diff.is_equivalent());
+        assert_eq!(diff.vertex_count_delta, 0);
+        assert!((diff.max_position_deviation - 0.5).abs() < 1e-6);
+        assert_eq!(diff.faces_added, 1);
+        assert_eq!(diff.faces_removed, 1);
+        assert_eq!(diff.channel_differences, vec![Channel::Uvs]);
+    }
+
+    #[test]
+    fn mesh_diff_should_match_reordered_vertices_when_asked() {
+        // Arrange
+        let a = create_quad();
+        let mut b = create_quad();
+        b.vertices.reverse();
+        b.faces = vec![Face::new(vec![3, 2, 1]), Face::new(vec![3, 1, 0])];
+
+        // Act
+        let same_order = mesh_diff(&a, &b, &DiffTolerances::new());
+        let nearest = mesh_diff(&a, &b, &DiffTolerances::new().with_matching(VertexMatching::Nearest));
+
+        // Assert
+        assert!(!same_order.is_equivalent());
+        assert!(nearest.is_equivalent());
+    }
+}
\ No newline at end of file
@@ -35,7 +35,14 @@ mod tests {
 
     #[test]
     fn face_vertex_iterator_should_handle_empty_input() {
+        // Arrange
         let indices = Vec::<i32>::new();
         let vertices = Vec::<glm::Vec2>::new();
+
+        // Act
+        let count = FaceVertexIterator::from(&mut indices.iter(), &vertices).count();
+
+        // Assert
+        assert_eq!(count, 0);
     }
 }
\ No newline at end of file
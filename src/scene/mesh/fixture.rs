@@ -0,0 +1,26 @@
+use crate::scene::mesh::{Face, Mesh};
+
+/* Faces of a cube wound counter clockwise seen from outside, +Z first. Corner i is at the low end of x, y and z when
+   bit 0, 1 and 2 of i are clear and at the high end when they are set. */
+pub(crate) const CUBE_QUADS: [[i32; 4]; 6] = [[4, 5, 7, 6], [0, 2, 3, 1], [1, 3, 7, 5], [0, 4, 6, 2], [2, 6, 7, 3], [0, 1, 5, 4]];
+
+/* Corner i of the cube from min to max along every axis, numbered as in CUBE_QUADS */
+pub(crate) fn cube_corner(i: usize, min: f32, max: f32) -> glm::Vec3 {
+    let along = |bit: usize| if i & bit == 0 { min } else { max };
+    glm::vec3(along(1), along(2), along(4))
+}
+
+/* Cube from min to max along every axis with the quads of CUBE_QUADS as its faces */
+pub(crate) fn cube(min: f32, max: f32) -> Mesh {
+    let vertices = (0..8).map(|i| cube_corner(i, min, max)).collect();
+    Mesh::new("cube".to_string(), vertices, CUBE_QUADS.iter().map(|q| Face::new(q.to_vec())).collect())
+}
+
+/* The cube with each quad split into two triangles along the diagonal from its first corner */
+pub(crate) fn triangulated_cube(min: f32, max: f32) -> Mesh {
+    let mut mesh = cube(min, max);
+    mesh.faces = CUBE_QUADS.iter()
+        .flat_map(|q| vec![Face::new(vec![q[0], q[1], q[2]]), Face::new(vec![q[0], q[2], q[3]])])
+        .collect();
+    mesh
+}
@@ -0,0 +1,157 @@
+use crate::scene::mesh::Mesh;
+
+fn to_f64(v: glm::Vec3) -> [f64; 3] {
+    [v.x as f64, v.y as f64, v.z as f64]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/* Weighted sum of points divided by the total weight, None when the weights add up to nothing */
+fn weighted_mean(sum: [f64; 3], weight: f64) -> Option<glm::Vec3> {
+    if weight.abs() < 1e-12 {
+        return None;
+    }
+    Some(glm::vec3((sum[0] / weight) as f32, (sum[1] / weight) as f32, (sum[2] / weight) as f32))
+}
+
+impl Mesh {
+    /* Every face as triangles fanned out from its first corner, in double precision. The mesh is left as it is. */
+    fn fan_triangles(&self) -> impl Iterator<Item = [[f64; 3]; 3]> + '_ {
+        self.faces.iter().flat_map(move |face| {
+            let v = move |i: usize| to_f64(self.vertices[face.indices[i] as usize]);
+            (1..face.indices.len().saturating_sub(1)).map(move |i| [v(0), v(i), v(i + 1)])
+        })
+    }
+
+    /* Summed area of the faces, faces that are not flat count as the fan of triangles from their first corner */
+    pub fn surface_area(&self) -> f32 {
+        self.fan_triangles()
+            .map(|[a, b, c]| dot(cross(sub(b, a), sub(c, a)), cross(sub(b, a), sub(c, a))).sqrt() / 2.0)
+            .sum::<f64>() as f32
+    }
+
+    /* Volume enclosed by the faces through the divergence theorem, positive when they are wound counter clockwise seen
+       from outside. Not checked for holes, so for an open mesh this depends on where the origin is, see volume. */
+    pub fn signed_volume(&self) -> f32 {
+        self.fan_triangles().map(|[a, b, c]| dot(a, cross(b, c)) / 6.0).sum::<f64>() as f32
+    }
+
+    /* Signed volume of a closed mesh, None if it has holes or edges shared by more than two faces */
+    pub fn volume(&self) -> Option<f32> {
+        let report = self.topology_report();
+        if !report.open_edges.is_empty() || !report.non_manifold_edges.is_empty() {
+            return None;
+        }
+        Some(self.signed_volume())
+    }
+
+    /* Centre of the surface, every face weighted by its area. None for a mesh without area. */
+    pub fn surface_centroid(&self) -> Option<glm::Vec3> {
+        let mut sum = [0.0; 3];
+        let mut total = 0.0;
+        for [a, b, c] in self.fan_triangles() {
+            let n = cross(sub(b, a), sub(c, a));
+            let area = dot(n, n).sqrt() / 2.0;
+            for i in 0..3 {
+                sum[i] += area * (a[i] + b[i] + c[i]) / 3.0;
+            }
+            total += area;
+        }
+        weighted_mean(sum, total)
+    }
+
+    /* Centre of mass of the enclosed volume at uniform density. None for meshes without a volume, see volume. */
+    pub fn volume_centroid(&self) -> Option<glm::Vec3> {
+        self.volume()?;
+        let mut sum = [0.0; 3];
+        let mut total = 0.0;
+        // Tetrahedra from the origin to every triangle, their centres weighted by their signed volume
+        for [a, b, c] in self.fan_triangles() {
+            let volume = dot(a, cross(b, c)) / 6.0;
+            for i in 0..3 {
+                sum[i] += volume * (a[i] + b[i] + c[i]) / 4.0;
+            }
+            total += volume;
+        }
+        weighted_mean(sum, total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+    use crate::scene::mesh::fixture;
+
+    fn create_tetrahedron() -> Mesh {
+        let vertices = vec![
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec3(0.0, 0.0, 1.0),
+        ];
+        let faces = vec![Face::new(vec![0, 2, 1]), Face::new(vec![0, 1, 3]), Face::new(vec![0, 3, 2]), Face::new(vec![1, 2, 3])];
+        Mesh::new("tetrahedron".to_string(), vertices, faces)
+    }
+
+    fn assert_near(a: glm::Vec3, b: glm::Vec3) {
+        assert!(glm::length(a - b) < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn measurements_should_match_a_unit_cube() {
+        // Arrange
+        let cube = fixture::cube(0.0, 1.0);
+
+        // Act
+        let area = cube.surface_area();
+        let volume = cube.volume().unwrap();
+
+        // Assert
+        assert!((area - 6.0).abs() < 1e-5);
+        assert!((volume - 1.0).abs() < 1e-5);
+        assert_near(cube.surface_centroid().unwrap(), glm::vec3(0.5, 0.5, 0.5));
+        assert_near(cube.volume_centroid().unwrap(), glm::vec3(0.5, 0.5, 0.5));
+        assert_eq!(cube.faces.len(), 6);
+    }
+
+    #[test]
+    fn measurements_should_match_a_tetrahedron() {
+        // Arrange
+        let tetrahedron = create_tetrahedron();
+
+        // Act
+        let area = tetrahedron.surface_area();
+        let volume = tetrahedron.volume().unwrap();
+
+        // Assert
+        assert!((area - (1.5 + 3f32.sqrt() / 2.0)).abs() < 1e-5);
+        assert!((volume - 1.0 / 6.0).abs() < 1e-5);
+        assert_near(tetrahedron.volume_centroid().unwrap(), glm::vec3(0.25, 0.25, 0.25));
+    }
+
+    #[test]
+    fn volume_should_be_none_for_an_open_mesh() {
+        // Arrange
+        let mut cube = fixture::cube(0.0, 1.0);
+        cube.faces.pop();
+
+        // Act
+        let volume = cube.volume();
+
+        // Assert
+        assert_eq!(volume, None);
+        assert_eq!(cube.volume_centroid(), None);
+        assert!((cube.surface_area() - 5.0).abs() < 1e-5);
+    }
+}
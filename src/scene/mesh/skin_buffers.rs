@@ -0,0 +1,120 @@
+use crate::scene::mesh::Mesh;
+use std::cmp::Ordering;
+
+// Influences per vertex in the buffers renderers take
+const BUFFER_INFLUENCES: usize = 4;
+
+/* Joint indices and weights per vertex, see Mesh::skin_buffers */
+pub type SkinBuffers = (Vec<[u16; 4]>, Vec<[f32; 4]>);
+
+impl Mesh {
+    /* Joint indices and weights of every vertex as the fixed size arrays GPU skinning takes. Joint indices refer to
+       Skin::joint_ids. Vertices keep their max_influences largest weights, at most 4, scaled to sum to 1, unused
+       slots are joint 0 with weight 0. None for meshes without a skin. */
+    pub fn skin_buffers(&self, max_influences: usize) -> Option<SkinBuffers> {
+        let skin = self.skin.as_ref()?;
+        let slots = max_influences.min(BUFFER_INFLUENCES);
+
+        let mut joints = Vec::with_capacity(self.vertices.len());
+        let mut weights = Vec::with_capacity(self.vertices.len());
+        for mut influences in skin.vertex_influences(self.vertices.len()) {
+            influences.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal).then(a.0.cmp(&b.0)));
+            influences.truncate(slots);
+            let total: f32 = influences.iter().map(|(_, weight)| weight).sum();
+
+            let mut vertex_joints = [0u16; 4];
+            let mut vertex_weights = [0.0f32; 4];
+            for (slot, (joint, weight)) in influences.iter().enumerate() {
+                vertex_joints[slot] = *joint as u16;
+                vertex_weights[slot] = if total > 0.0 { weight / total } else { 0.0 };
+            }
+            joints.push(vertex_joints);
+            weights.push(vertex_weights);
+        }
+
+        Some((joints, weights))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh_processor::MeshProcessor;
+    use crate::mesh_processor::weld_processor::WeldVerticesProcessor;
+    use crate::scene::mesh::{Face, Mesh};
+    use crate::scene::skin::{Cluster, Skin};
+
+    const SIDES: usize = 6;
+
+    /* Open cylinder from y = 0 to 2 with every quad having its own vertices, bone 0 at the bottom and bone 1 at the
+       top. The middle ring is shared half and half. */
+    fn create_two_bone_cylinder() -> Mesh {
+        let ring = |i: usize, y: f32| {
+            let angle = 2.0 * std::f32::consts::PI * (i % SIDES) as f32 / SIDES as f32;
+            glm::vec3(glm::cos(angle), y, glm::sin(angle))
+        };
+
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        for row in 0..2 {
+            for side in 0..SIDES {
+                let start = vertices.len() as i32;
+                let (bottom, top) = (row as f32, row as f32 + 1.0);
+                vertices.extend_from_slice(&[ring(side, bottom), ring(side + 1, bottom), ring(side + 1, top), ring(side, top)]);
+                faces.push(Face::new(vec![start, start + 1, start + 2, start + 3]));
+            }
+        }
+
+        let mut bones = [Cluster::new(1, 10), Cluster::new(2, 20)];
+        for (index, vertex) in vertices.iter().enumerate() {
+            let top_weight = vertex.y / 2.0;
+            for (bone, weight) in bones.iter_mut().zip(&[1.0 - top_weight, top_weight]) {
+                if *weight > 0.0 {
+                    bone.indices.push(index as i32);
+                    bone.weights.push(*weight);
+                }
+            }
+        }
+
+        let mut mesh = Mesh::new("cylinder".to_string(), vertices, faces);
+        mesh.skin = Some(Skin::new(3, bones.to_vec()));
+        mesh
+    }
+
+    #[test]
+    fn skin_buffers_should_follow_welded_vertices() {
+        // Arrange
+        let mut mesh = create_two_bone_cylinder();
+        WeldVerticesProcessor::new().process(&mut mesh);
+
+        // Act
+        let (joints, weights) = mesh.skin_buffers(4).unwrap();
+
+        // Assert
+        assert_eq!(mesh.vertices.len(), 3 * SIDES);
+        assert_eq!(joints.len(), mesh.vertices.len());
+        for ((vertex, joints), weights) in mesh.vertices.iter().zip(&joints).zip(&weights) {
+            let expected = match vertex.y as i32 {
+                0 => ([0, 0, 0, 0], [1.0, 0.0, 0.0, 0.0]),
+                1 => ([0, 1, 0, 0], [0.5, 0.5, 0.0, 0.0]),
+                _ => ([1, 0, 0, 0], [1.0, 0.0, 0.0, 0.0]),
+            };
+            assert_eq!((*joints, *weights), expected);
+        }
+    }
+
+    #[test]
+    fn skin_buffers_should_renormalize_limited_influences() {
+        // Arrange
+        let mut mesh = create_two_bone_cylinder();
+
+        // Act
+        let (joints, weights) = mesh.skin_buffers(1).unwrap();
+        mesh.skin = None;
+
+        // Assert
+        let middle = mesh.vertices.iter().position(|v| v.y == 1.0).unwrap();
+        assert_eq!(joints[middle], [0, 0, 0, 0]);
+        assert_eq!(weights[middle], [1.0, 0.0, 0.0, 0.0]);
+        assert!(mesh.skin_buffers(4).is_none());
+    }
+}
\ No newline at end of file
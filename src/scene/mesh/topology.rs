@@ -0,0 +1,246 @@
+use crate::scene::mesh::Mesh;
+use std::collections::{BTreeMap, HashMap};
+
+/* Problems keeping a mesh from being a closed surface, see Mesh::topology_report. Edges are vertex index pairs with the
+   lowest index first, everything is sorted. */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TopologyReport {
+    // Edges used by a single face, on the border of a hole
+    pub open_edges: Vec<(i32, i32)>,
+    // Edges used by more than two faces
+    pub non_manifold_edges: Vec<(i32, i32)>,
+    // Vertices where the faces around them do not form a single fan, like the tips of two cones touching
+    pub non_manifold_vertices: Vec<i32>,
+    // Groups of faces connected through shared vertices
+    pub components: usize,
+}
+
+impl TopologyReport {
+    /* No holes and no edges or vertices shared in ways a surface cannot */
+    pub fn is_closed_manifold(&self) -> bool {
+        self.open_edges.is_empty() && self.non_manifold_edges.is_empty() && self.non_manifold_vertices.is_empty()
+    }
+}
+
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    parents[a] = b;
+}
+
+impl Mesh {
+    /* Faces using each edge, with the lowest index of the edge first. Edges between a vertex and itself are left out. */
+    fn edge_faces(&self) -> BTreeMap<(i32, i32), Vec<usize>> {
+        let mut edges: BTreeMap<(i32, i32), Vec<usize>> = BTreeMap::new();
+        for (index, face) in self.faces.iter().enumerate() {
+            let len = face.indices.len();
+            for i in 0..len {
+                let (a, b) = (face.indices[i], face.indices[(i + 1) % len]);
+                if a != b {
+                    edges.entry((a.min(b), a.max(b))).or_default().push(index);
+                }
+            }
+        }
+        edges
+    }
+
+    /* Every edge of the faces once, lowest index first and sorted, as a line list for wireframes. Edges of n-gons are
+       their outline, triangulating adds the diagonals. Computed on every call, keep the result around while the mesh
+       does not change. */
+    pub fn edge_indices(&self) -> Vec<[u32; 2]> {
+        self.edge_faces().into_keys().map(|(a, b)| [a as u32, b as u32]).collect()
+    }
+
+    /* Edges used by a single face, the borders of holes, in the same form as edge_indices. Computed on every call. */
+    pub fn boundary_edge_indices(&self) -> Vec<[u32; 2]> {
+        self.edge_faces().into_iter()
+            .filter(|(_, faces)| faces.len() == 1)
+            .map(|((a, b), _)| [a as u32, b as u32])
+            .collect()
+    }
+
+    /* Looks for open and non-manifold edges, non-manifold vertices and counts the connected parts of the mesh, for
+       meshes that have to be watertight. Does not change the mesh. */
+    pub fn topology_report(&self) -> TopologyReport {
+        let edges = self.edge_faces();
+        let mut vertex_faces: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+        for (index, face) in self.faces.iter().enumerate() {
+            for vertex in &face.indices {
+                vertex_faces.entry(*vertex).or_default().push(index);
+            }
+        }
+
+        let mut report = TopologyReport::default();
+        for (edge, faces) in &edges {
+            match faces.len() {
+                1 => report.open_edges.push(*edge),
+                2 => {}
+                _ => report.non_manifold_edges.push(*edge),
+            }
+        }
+
+        // Faces around a vertex form a fan when they are all joined through manifold edges ending in the vertex
+        for (vertex, faces) in &vertex_faces {
+            let mut parents: Vec<usize> = (0..faces.len()).collect();
+            let local: HashMap<usize, usize> = faces.iter().enumerate().map(|(i, f)| (*f, i)).collect();
+            for face in faces {
+                let indices = &self.faces[*face].indices;
+                for other in indices.iter().filter(|i| *i != vertex) {
+                    let key = ((*vertex).min(*other), (*vertex).max(*other));
+                    if let Some(users) = edges.get(&key).filter(|users| users.len() == 2) {
+                        union(&mut parents, local[&users[0]], local[&users[1]]);
+                    }
+                }
+            }
+            let fans = (0..faces.len()).filter(|i| find(&mut parents, *i) == *i).count();
+            if fans > 1 {
+                report.non_manifold_vertices.push(*vertex);
+            }
+        }
+
+        let mut parents: Vec<usize> = (0..self.faces.len()).collect();
+        for faces in vertex_faces.values() {
+            for face in &faces[1..] {
+                union(&mut parents, faces[0], *face);
+            }
+        }
+        report.components = (0..self.faces.len()).filter(|i| find(&mut parents, *i) == *i).count();
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+    use crate::scene::mesh::fixture::{cube_corner, CUBE_QUADS};
+    use crate::mesh_processor::MeshProcessor;
+    use crate::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
+
+    /* Adds the faces of a unit cube at offset, sharing vertices with those already there at the same position */
+    fn add_cube(vertices: &mut Vec<glm::Vec3>, faces: &mut Vec<Face>, offset: glm::Vec3, skip_face: Option<usize>) {
+        let corners: Vec<i32> = (0..8)
+            .map(|i| {
+                let position = offset + cube_corner(i, 0.0, 1.0);
+                match vertices.iter().position(|v| *v == position) {
+                    Some(existing) => existing as i32,
+                    None => {
+                        vertices.push(position);
+                        vertices.len() as i32 - 1
+                    }
+                }
+            })
+            .collect();
+        for (i, quad) in CUBE_QUADS.iter().enumerate() {
+            if Some(i) != skip_face {
+                faces.push(Face::new(quad.iter().map(|c| corners[*c as usize]).collect()));
+            }
+        }
+    }
+
+    fn create_cubes(offsets: &[glm::Vec3], skip_face: Option<usize>) -> Mesh {
+        let mut vertices = Vec::new();
+        let mut faces = Vec::new();
+        for offset in offsets {
+            add_cube(&mut vertices, &mut faces, *offset, skip_face);
+        }
+        Mesh::new("cubes".to_string(), vertices, faces)
+    }
+
+    #[test]
+    fn topology_report_should_find_no_issues_in_a_closed_cube() {
+        // Arrange
+        let mesh = create_cubes(&[glm::vec3(0.0, 0.0, 0.0)], None);
+
+        // Act
+        let report = mesh.topology_report();
+
+        // Assert
+        assert!(report.is_closed_manifold());
+        assert_eq!(report.components, 1);
+    }
+
+    #[test]
+    fn topology_report_should_find_the_open_edges_of_a_missing_face() {
+        // Arrange
+        let mesh = create_cubes(&[glm::vec3(0.0, 0.0, 0.0)], Some(0));
+
+        // Act
+        let report = mesh.topology_report();
+
+        // Assert
+        assert_eq!(report.open_edges, vec![(4, 5), (4, 6), (5, 7), (6, 7)]);
+        assert!(report.non_manifold_edges.is_empty());
+        assert!(report.non_manifold_vertices.is_empty());
+    }
+
+    #[test]
+    fn topology_report_should_find_an_edge_shared_by_two_cubes() {
+        // Arrange
+        let mesh = create_cubes(&[glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 1.0, 0.0)], None);
+
+        // Act
+        let report = mesh.topology_report();
+
+        // Assert
+        // Corners 3 and 7 of the first cube are corners 0 and 4 of the second
+        assert_eq!(report.non_manifold_edges, vec![(3, 7)]);
+        assert_eq!(report.non_manifold_vertices, vec![3, 7]);
+        assert!(report.open_edges.is_empty());
+        assert_eq!(report.components, 1);
+    }
+
+    #[test]
+    fn topology_report_should_count_separate_parts() {
+        // Arrange
+        let mesh = create_cubes(&[glm::vec3(0.0, 0.0, 0.0), glm::vec3(3.0, 0.0, 0.0)], None);
+
+        // Act
+        let report = mesh.topology_report();
+
+        // Assert
+        assert_eq!(report.components, 2);
+        assert!(report.is_closed_manifold());
+    }
+
+    #[test]
+    fn edge_indices_should_give_each_edge_of_a_cube_once() {
+        // Arrange
+        let mut mesh = create_cubes(&[glm::vec3(0.0, 0.0, 0.0)], None);
+
+        // Act
+        let quad_edges = mesh.edge_indices();
+        TriangulateMeshProcessor::new().process(&mut mesh);
+        let triangle_edges = mesh.edge_indices();
+
+        // Assert
+        assert_eq!(quad_edges.len(), 12);
+        assert_eq!(quad_edges[..3], [[0, 1], [0, 2], [0, 4]]);
+        assert!(quad_edges.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(quad_edges.iter().all(|[a, b]| a < b));
+        // 12 edges and a diagonal per side
+        assert_eq!(triangle_edges.len(), 18);
+        assert!(quad_edges.iter().all(|edge| triangle_edges.contains(edge)));
+    }
+
+    #[test]
+    fn boundary_edge_indices_should_give_the_edges_around_a_hole() {
+        // Arrange
+        let open = create_cubes(&[glm::vec3(0.0, 0.0, 0.0)], Some(0));
+        let closed = create_cubes(&[glm::vec3(0.0, 0.0, 0.0)], None);
+
+        // Act
+        let boundary = open.boundary_edge_indices();
+
+        // Assert
+        assert_eq!(boundary, vec![[4, 5], [4, 6], [5, 7], [6, 7]]);
+        assert!(closed.boundary_edge_indices().is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+use crate::scene::transform::Transform;
+
+/* Which faces a renderer should skip drawing */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CullingMode {
+    Off,
+    // Cull faces wound counter clockwise
+    CounterClockwise,
+    Clockwise,
+}
+
+impl CullingMode {
+    pub fn from_fbx(value: &str) -> Self {
+        match value {
+            "CullingOnCCW" => CullingMode::CounterClockwise,
+            "CullingOnCW" => CullingMode::Clockwise,
+            _ => CullingMode::Off,
+        }
+    }
+}
+
+/* How the rotation and scaling of the parent combine with those of the node. Lower case letters are the node, upper
+   case the parent. */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InheritType {
+    // Parent scaling is applied after the rotation of the node, so a non-uniform parent scale does not shear it
+    RrSs,
+    // Plain matrix composition, parent scaling is applied before the rotation of the node
+    RSrs,
+    // The scaling of the parent is ignored, like the scale compensated joints of Maya
+    Rrs,
+}
+
+impl InheritType {
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            1 => InheritType::RSrs,
+            2 => InheritType::Rrs,
+            _ => InheritType::RrSs,
+        }
+    }
+}
+
+/* A Model object from the file. Nodes are stored in a flat list on the scene and refer to each other by index. */
+#[derive(Clone, Debug)]
+pub struct Node {
+    // Object id from the file, used by Connections to refer to the node
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    // None for nodes attached to the scene root
+    pub(crate) parent: Option<usize>,
+    pub(crate) children: Vec<usize>,
+    // Index of the mesh of the model in the meshes of the scene
+    pub(crate) mesh: Option<usize>,
+    // Transform from the space of the node to that of its parent
+    pub(crate) transform: Transform,
+    // Applied to the mesh of the node on top of the node transform, without passing on to the children
+    pub(crate) geometric_transform: Transform,
+    pub(crate) inherit_type: InheritType,
+    // Show in the file, false for nodes hidden in the authoring tool
+    pub(crate) visible: bool,
+    // Opacity like visibility from 0 to 1, which can be animated
+    pub(crate) visibility: f64,
+    pub(crate) culling: CullingMode,
+    // Cached transform to world space, see Scene::update_world_transforms
+    pub(crate) world: glm::Mat4,
+    // Set when the transform of the node or one of its ancestors changed after world was computed
+    pub(crate) world_dirty: bool,
+}
+
+impl Node {
+    pub fn new(id: i64, name: String) -> Self {
+        Node {
+            id,
+            name,
+            parent: None,
+            children: Vec::new(),
+            mesh: None,
+            transform: Transform::identity(),
+            geometric_transform: Transform::identity(),
+            inherit_type: InheritType::RrSs,
+            visible: true,
+            visibility: 1.0,
+            culling: CullingMode::Off,
+            world: Transform::identity().to_mat4(),
+            world_dirty: true,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    pub fn mesh(&self) -> Option<usize> {
+        self.mesh
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn geometric_transform(&self) -> &Transform {
+        &self.geometric_transform
+    }
+
+    pub fn inherit_type(&self) -> InheritType {
+        self.inherit_type
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn visibility(&self) -> f64 {
+        self.visibility
+    }
+
+    pub fn culling(&self) -> CullingMode {
+        self.culling
+    }
+
+    /* Hidden in the authoring tool, either switched off or fully transparent */
+    pub fn is_hidden(&self) -> bool {
+        !self.visible || self.visibility <= 0.0
+    }
+
+    /* Transform from the space of the node to world space as of the last Scene::update_world_transforms. Reading it
+       after a transform above the node changed panics in debug builds and returns the old transform otherwise. */
+    pub fn world_transform(&self) -> glm::Mat4 {
+        debug_assert!(!self.world_dirty, "World transform of node '{}' is out of date, call Scene::update_world_transforms first.", self.name);
+        self.world
+    }
+
+    /* A transform above the node changed since its world transform was computed */
+    pub fn is_world_transform_stale(&self) -> bool {
+        self.world_dirty
+    }
+
+    /* Transform from the space of the node to that of its parent */
+    pub fn local_transform(&self) -> glm::Mat4 {
+        self.transform.to_mat4()
+    }
+}
@@ -0,0 +1,80 @@
+use std::io::{self, Write};
+use crate::scene::Scene;
+use crate::scene::mesh::Mesh;
+
+fn write_mesh<W: Write>(writer: &mut W, mesh: &Mesh, first_vertex: usize) -> io::Result<()> {
+    writeln!(writer, "o {}", mesh.name)?;
+    for v in &mesh.vertices {
+        writeln!(writer, "v {} {} {}", v.x, v.y, v.z)?;
+    }
+    let has_uvs = !mesh.uvs.is_empty() && mesh.uvs.len() == mesh.vertices.len();
+    for uv in mesh.uvs.iter().filter(|_| has_uvs) {
+        writeln!(writer, "vt {} {}", uv.x, uv.y)?;
+    }
+    let has_normals = !mesh.normals.is_empty() && mesh.normals.len() == mesh.vertices.len();
+    for n in mesh.normals.iter().filter(|_| has_normals) {
+        writeln!(writer, "vn {} {} {}", n.x, n.y, n.z)?;
+    }
+
+    for face in &mesh.faces {
+        write!(writer, "f")?;
+        for index in &face.indices {
+            // Vertices, uvs and normals share their numbering since there is one of each per vertex
+            let i = first_vertex + *index as usize + 1;
+            match (has_uvs, has_normals) {
+                (false, false) => write!(writer, " {}", i)?,
+                (true, false) => write!(writer, " {}/{}", i, i)?,
+                (false, true) => write!(writer, " {}//{}", i, i)?,
+                (true, true) => write!(writer, " {}/{}/{}", i, i, i)?,
+            }
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+impl Scene {
+    /* Wavefront OBJ text with an object per mesh, positions in the space of the mesh. Uvs and normals are written for
+       meshes with one per vertex, materials are left out. */
+    pub fn write_obj<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "# fbximport")?;
+        let mut first_vertex = 0;
+        for mesh in &self.meshes {
+            write_mesh(&mut writer, mesh, first_vertex)?;
+            first_vertex += mesh.vertices.len();
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+
+    #[test]
+    fn write_obj_should_number_the_vertices_of_later_meshes_after_the_earlier_ones() {
+        // Arrange
+        let triangle = |name: &str| Mesh::new(
+            name.to_string(),
+            vec![glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 1.5, 0.0)],
+            vec![Face::new(vec![0, 1, 2])]);
+        let mut second = triangle("second");
+        second.normals = vec![glm::vec3(0.0, 0.0, 1.0); 3];
+        let scene = Scene::new(vec![triangle("first"), second]);
+        let mut out = Vec::new();
+
+        // Act
+        scene.write_obj(&mut out).unwrap();
+
+        // Assert
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.iter().filter(|l| l.starts_with("v ")).count(), 6);
+        assert!(lines.contains(&"v 0 1.5 0"));
+        assert!(lines.contains(&"o first"));
+        assert!(lines.contains(&"f 1 2 3"));
+        assert!(lines.contains(&"vn 0 0 1"));
+        assert!(lines.contains(&"f 4//4 5//5 6//6"));
+    }
+}
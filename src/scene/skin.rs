@@ -0,0 +1,205 @@
+use crate::warnings::warning;
+use glm::GenSquareMat;
+
+/* Vertices influenced by one joint, with the matrices relating the joint to the mesh at bind time */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cluster {
+    pub(crate) id: i64,
+    // Id of the node acting as the joint
+    pub(crate) joint_id: i64,
+    pub(crate) indices: Vec<i32>,
+    pub(crate) weights: Vec<f32>,
+    // World transform of the mesh at bind time
+    pub(crate) transform: glm::Mat4,
+    // World transform of the joint at bind time
+    pub(crate) transform_link: glm::Mat4,
+}
+
+impl Cluster {
+    pub fn new(id: i64, joint_id: i64) -> Self {
+        let identity = glm::mat4(
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        );
+        Cluster {
+            id,
+            joint_id,
+            indices: Vec::new(),
+            weights: Vec::new(),
+            transform: identity,
+            transform_link: identity,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn joint_id(&self) -> i64 {
+        self.joint_id
+    }
+
+    pub fn indices(&self) -> &[i32] {
+        &self.indices
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    pub fn transform(&self) -> &glm::Mat4 {
+        &self.transform
+    }
+
+    pub fn transform_link(&self) -> &glm::Mat4 {
+        &self.transform_link
+    }
+
+    /* Takes mesh space to joint space at bind time, TransformLink⁻¹ · Transform for column vectors. The FBX SDK
+       writes the same product as Transform * TransformLink.Inverse() as it multiplies row vectors. None when the
+       joint matrix can not be inverted. */
+    pub fn inverse_bind_matrix(&self) -> Option<glm::Mat4> {
+        Some(self.transform_link.inverse()? * self.transform)
+    }
+}
+
+/* Clusters deforming a mesh, in the order of its joint list */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Skin {
+    pub(crate) id: i64,
+    pub(crate) clusters: Vec<Cluster>,
+}
+
+impl Skin {
+    pub fn new(id: i64, clusters: Vec<Cluster>) -> Self {
+        Skin {
+            id,
+            clusters,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn clusters(&self) -> &[Cluster] {
+        &self.clusters
+    }
+
+    /* Node ids of the joints, a joint's position in this list is its index in the skinning buffers */
+    pub fn joint_ids(&self) -> Vec<i64> {
+        self.clusters.iter().map(|c| c.joint_id).collect()
+    }
+
+    /* Inverse bind matrix of every joint, identity for joints whose matrix can not be inverted */
+    pub fn inverse_bind_matrices(&self) -> Vec<glm::Mat4> {
+        self.clusters.iter()
+            .map(|cluster| cluster.inverse_bind_matrix().unwrap_or_else(|| {
+                warning!("Joint {} has a bind matrix that can not be inverted, using identity instead.", cluster.joint_id);
+                Cluster::new(0, 0).transform
+            }))
+            .collect()
+    }
+
+    /* Joint index and weight of every influence on each vertex, in joint order */
+    pub(crate) fn vertex_influences(&self, vertex_count: usize) -> Vec<Vec<(usize, f32)>> {
+        let mut influences = vec![Vec::new(); vertex_count];
+        for (joint, cluster) in self.clusters.iter().enumerate() {
+            for (index, weight) in cluster.indices.iter().zip(&cluster.weights) {
+                if let Some(vertex) = influences.get_mut(*index as usize) {
+                    vertex.push((joint, *weight));
+                }
+            }
+        }
+        influences
+    }
+
+    /* Replaces the weights of every cluster with the given per vertex influences */
+    pub(crate) fn set_vertex_influences(&mut self, influences: &[Vec<(usize, f32)>]) {
+        for cluster in &mut self.clusters {
+            cluster.indices.clear();
+            cluster.weights.clear();
+        }
+        for (vertex, vertex_influences) in influences.iter().enumerate() {
+            for (joint, weight) in vertex_influences {
+                self.clusters[*joint].indices.push(vertex as i32);
+                self.clusters[*joint].weights.push(*weight);
+            }
+        }
+    }
+
+    /* For vertices being added or removed: afterwards vertex i has the weights of what was vertex source[i] */
+    pub(crate) fn remap(&mut self, source: &[usize]) {
+        let old_count = source.iter().max().map_or(0, |m| m + 1);
+        let mut targets = vec![Vec::new(); old_count];
+        for (new, old) in source.iter().enumerate() {
+            targets[*old].push(new as i32);
+        }
+
+        for cluster in &mut self.clusters {
+            let mut indices = Vec::with_capacity(cluster.indices.len());
+            let mut weights = Vec::with_capacity(cluster.weights.len());
+            for (index, weight) in cluster.indices.iter().zip(&cluster.weights) {
+                for target in targets.get(*index as usize).into_iter().flatten() {
+                    indices.push(*target);
+                    weights.push(*weight);
+                }
+            }
+            cluster.indices = indices;
+            cluster.weights = weights;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translation(x: f32, y: f32, z: f32) -> glm::Mat4 {
+        glm::ext::translate(&Cluster::new(0, 0).transform, glm::vec3(x, y, z))
+    }
+
+    #[test]
+    fn inverse_bind_matrix_should_keep_vertices_in_place_at_bind_pose() {
+        // Arrange
+        let mut bones = vec![Cluster::new(1, 10), Cluster::new(2, 20)];
+        bones[0].transform_link = translation(0.0, 1.0, 0.0);
+        bones[1].transform_link = translation(0.0, 2.0, 0.0) * glm::ext::rotate(&Cluster::new(0, 0).transform, 1.0, glm::vec3(0.0, 0.0, 1.0));
+        for bone in &mut bones {
+            bone.transform = translation(5.0, 0.0, 0.0);
+        }
+        let skin = Skin::new(3, bones);
+        let vertex = glm::vec4(0.5, 2.5, 0.0, 1.0);
+
+        // Act
+        let matrices = skin.inverse_bind_matrices();
+
+        // Assert
+        for (cluster, inverse_bind) in skin.clusters().iter().zip(&matrices) {
+            // Vertex with weight 1 on the joint, posed by the joint's bind transform
+            let skinned = cluster.transform_link * *inverse_bind * vertex;
+            let expected = cluster.transform * vertex;
+            assert!(glm::length(skinned - expected) < 1e-5);
+        }
+        assert!(glm::length(matrices[0] * glm::vec4(0.0, 1.0, 0.0, 1.0) - glm::vec4(5.0, 0.0, 0.0, 1.0)) < 1e-5);
+    }
+
+    #[test]
+    fn remap_should_follow_copied_and_removed_vertices() {
+        // Arrange
+        let mut cluster = Cluster::new(1, 10);
+        cluster.indices = vec![0, 2];
+        cluster.weights = vec![0.5, 1.0];
+        let mut skin = Skin::new(3, vec![cluster]);
+
+        // Act
+        // Vertex 1 is removed and vertex 2 gets a copy at the end
+        skin.remap(&[0, 2, 2]);
+
+        // Assert
+        assert_eq!(skin.clusters()[0].indices(), &[0, 1, 2]);
+        assert_eq!(skin.clusters()[0].weights(), &[0.5, 1.0, 1.0]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,129 @@
+use crate::scene::Scene;
+use crate::scene::mesh::Mesh;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+// Enough of the vertex and face lists to notice a change without making the snapshots unreadable
+const VERTICES_SHOWN: usize = 4;
+const FACES_SHOWN: usize = 8;
+
+// Set to rewrite the snapshot files from the current output instead of comparing against them
+const UPDATE_VARIABLE: &str = "UPDATE_SNAPSHOTS";
+
+fn vec3(v: &glm::Vec3) -> String {
+    format!("({:.6}, {:.6}, {:.6})", v.x, v.y, v.z)
+}
+
+fn write_vertices(out: &mut String, mesh: &Mesh) {
+    let count = mesh.vertices.len();
+    let shown: Vec<usize> = if count <= VERTICES_SHOWN * 2 {
+        (0..count).collect()
+    } else {
+        (0..VERTICES_SHOWN).chain(count - VERTICES_SHOWN..count).collect()
+    };
+
+    for (n, index) in shown.iter().enumerate() {
+        if n > 0 && shown[n - 1] + 1 != *index {
+            writeln!(out, "    ...").unwrap();
+        }
+        writeln!(out, "    {}: {}", index, vec3(&mesh.vertices[*index])).unwrap();
+    }
+}
+
+fn write_mesh(out: &mut String, mesh: &Mesh) {
+    writeln!(out, "mesh \"{}\" (original \"{}\")", mesh.name, mesh.original_name).unwrap();
+    writeln!(out, "  vertices: {}", mesh.vertices.len()).unwrap();
+    writeln!(out, "  faces: {}", mesh.faces.len()).unwrap();
+    writeln!(out, "  normals: {}", mesh.normals.len()).unwrap();
+    writeln!(out, "  uvs: {}", mesh.uvs.len()).unwrap();
+    writeln!(out, "  degenerate faces: {}", mesh.degenerate_faces.len()).unwrap();
+    writeln!(out, "  smoothing: {:?}", mesh.smoothing).unwrap();
+
+    if let Some((min, max)) = mesh.bounds() {
+        writeln!(out, "  aabb: {} .. {}", vec3(&min), vec3(&max)).unwrap();
+    }
+
+    writeln!(out, "  vertex positions:").unwrap();
+    write_vertices(out, mesh);
+
+    writeln!(out, "  face indices:").unwrap();
+    for (index, face) in mesh.faces.iter().enumerate().take(FACES_SHOWN) {
+        writeln!(out, "    {}: {:?}", index, face.indices).unwrap();
+    }
+    if mesh.faces.len() > FACES_SHOWN {
+        writeln!(out, "    ... {} more", mesh.faces.len() - FACES_SHOWN).unwrap();
+    }
+}
+
+/* Canonical text form of a scene: meshes sorted by name, floats with 6 decimals and long lists cut short, so two
+   imports of the same file always give the same text and a change shows up as a readable diff. */
+pub(crate) fn to_snapshot(scene: &Scene) -> String {
+    let mut out = String::new();
+    let mut meshes: Vec<&Mesh> = scene.meshes.iter().collect();
+    meshes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    writeln!(out, "meshes: {}", meshes.len()).unwrap();
+    for mesh in meshes {
+        write_mesh(&mut out, mesh);
+    }
+
+    writeln!(out, "nodes: {}", scene.nodes.len()).unwrap();
+    for node in &scene.nodes {
+        let parent = node.parent.map_or("root".to_string(), |p| scene.nodes[p].id.to_string());
+        writeln!(out, "  node {} \"{}\" parent {}", node.id, node.name, parent).unwrap();
+    }
+
+    out
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/scene/snapshots").join(format!("{}.snap", name))
+}
+
+/* Compares the scene against src/scene/snapshots/<name>.snap. Run the tests with UPDATE_SNAPSHOTS=1 to write the
+   file after an intended change, and check the diff in. */
+pub(crate) fn assert_snapshot(name: &str, scene: &Scene) {
+    let actual = to_snapshot(scene);
+    let path = snapshot_path(name);
+
+    if std::env::var_os(UPDATE_VARIABLE).is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(_) => panic!("Snapshot {} does not exist, run the tests with {}=1 to create it.", path.display(), UPDATE_VARIABLE),
+    };
+
+    if actual != expected {
+        panic!("Scene does not match snapshot {}, run the tests with {}=1 if the change is intended.\n\
+                --- expected\n{}\n--- actual\n{}", path.display(), UPDATE_VARIABLE, expected, actual);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::mesh::Face;
+
+    #[test]
+    fn to_snapshot_should_sort_meshes_and_cut_long_vertex_lists() {
+        // Arrange
+        let line = (0..10).map(|i| glm::vec3(i as f32, 0.0, 0.0)).collect();
+        let meshes = vec![
+            Mesh::new("b".to_string(), line, vec![Face::new(vec![0, 1, 2])]),
+            Mesh::new("a".to_string(), Vec::new(), Vec::new()),
+        ];
+        let scene = Scene::new(meshes);
+
+        // Act
+        let snapshot = to_snapshot(&scene);
+
+        // Assert
+        assert!(snapshot.find("mesh \"a\"").unwrap() < snapshot.find("mesh \"b\"").unwrap());
+        assert!(snapshot.contains("    3: (3.000000, 0.000000, 0.000000)\n    ...\n    6: (6.000000, 0.000000, 0.000000)\n"));
+        assert!(!snapshot.contains("    4: "));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,289 @@
+use std::path::{Path, PathBuf};
+
+pub mod image_loading;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WrapMode {
+    Repeat,
+    Clamp,
+}
+
+impl WrapMode {
+    /* FBX writes the wrap mode as an enum, 0 for repeat and 1 for clamp */
+    pub fn from_fbx(value: i64) -> Self {
+        match value {
+            1 => WrapMode::Clamp,
+            _ => WrapMode::Repeat,
+        }
+    }
+}
+
+/* Placement of a texture in uv space, applied as scale, then rotation, then translation. Rotation is in degrees. */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UvTransform {
+    pub translation: glm::Vec2,
+    pub scale: glm::Vec2,
+    pub rotation: f32,
+}
+
+impl UvTransform {
+    pub fn identity() -> Self {
+        UvTransform {
+            translation: glm::vec2(0.0, 0.0),
+            scale: glm::vec2(1.0, 1.0),
+            rotation: 0.0,
+        }
+    }
+
+    /* Matrix taking mesh uvs to texture uvs, for homogeneous (u, v, 1) column vectors */
+    pub fn matrix(&self) -> glm::Mat3 {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        glm::mat3(
+            self.scale.x * cos, self.scale.x * sin, 0.0,
+            -self.scale.y * sin, self.scale.y * cos, 0.0,
+            self.translation.x, self.translation.y, 1.0,
+        )
+    }
+}
+
+impl Default for UvTransform {
+    fn default() -> Self {
+        UvTransform::identity()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Texture {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    // Path as written by the exporter, often absolute on the machine the file was made on
+    pub(crate) file_name: String,
+    // Path relative to the FBX file
+    pub(crate) relative_file_name: String,
+    // Name of the uv set the texture is mapped with, empty for the main set
+    pub(crate) uv_set: String,
+    pub(crate) uv_transform: UvTransform,
+    pub(crate) wrap_u: WrapMode,
+    pub(crate) wrap_v: WrapMode,
+    // Directory of the file the texture was imported from, None when imported from memory
+    pub(crate) source_directory: Option<PathBuf>,
+    // Set by Scene::resolve_all_textures
+    pub(crate) resolved_path: Option<PathBuf>,
+    // Image file embedded in the FBX, None when the texture refers to an external file
+    pub(crate) content: Option<Vec<u8>>,
+}
+
+impl Texture {
+    pub fn new(id: i64, name: String) -> Self {
+        Texture {
+            id,
+            name,
+            file_name: String::new(),
+            relative_file_name: String::new(),
+            uv_set: String::new(),
+            uv_transform: UvTransform::identity(),
+            wrap_u: WrapMode::Repeat,
+            wrap_v: WrapMode::Repeat,
+            source_directory: None,
+            resolved_path: None,
+            content: None,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn relative_file_name(&self) -> &str {
+        &self.relative_file_name
+    }
+
+    pub fn uv_set(&self) -> &str {
+        &self.uv_set
+    }
+
+    pub fn uv_transform(&self) -> &UvTransform {
+        &self.uv_transform
+    }
+
+    pub fn wrap_u(&self) -> WrapMode {
+        self.wrap_u
+    }
+
+    pub fn wrap_v(&self) -> WrapMode {
+        self.wrap_v
+    }
+
+    pub fn has_embedded_content(&self) -> bool {
+        self.content.is_some()
+    }
+
+    /* Path found by the last Scene::resolve_all_textures, None if it has not run or found nothing */
+    pub fn resolved_path(&self) -> Option<&Path> {
+        self.resolved_path.as_deref()
+    }
+
+    pub fn resolve_path(&self, search_dirs: &[PathBuf]) -> Option<PathBuf> {
+        self.resolve_path_with_case(search_dirs, false)
+    }
+
+    /* Finds the image file on this machine. Tries RelativeFilename next to the imported file, then the bare file
+       name in each of search_dirs, then FileName as written. Backslashes are read as separators and drive letters
+       are dropped from relative paths, as exporters on Windows write both. */
+    pub fn resolve_path_with_case(&self, search_dirs: &[PathBuf], ignore_case: bool) -> Option<PathBuf> {
+        let relative = normalize_separators(&self.relative_file_name);
+        if let (Some(directory), false) = (&self.source_directory, relative.is_empty()) {
+            let candidate = directory.join(strip_drive(&relative));
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+
+        let file_name = file_name_of(&self.relative_file_name).or_else(|| file_name_of(&self.file_name));
+        if let Some(file_name) = file_name {
+            for directory in search_dirs {
+                if let Some(found) = find_in_directory(directory, file_name, ignore_case) {
+                    return Some(found);
+                }
+            }
+        }
+
+        if self.file_name.is_empty() {
+            return None;
+        }
+        [PathBuf::from(&self.file_name), PathBuf::from(normalize_separators(&self.file_name))]
+            .iter()
+            .find(|candidate| candidate.is_file())
+            .cloned()
+    }
+}
+
+fn normalize_separators(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/* "C:/textures/a.png" becomes "textures/a.png" so it can be joined onto another directory */
+fn strip_drive(path: &str) -> &str {
+    let bytes = path.as_bytes();
+    let path = if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' { &path[2..] } else { path };
+    path.trim_start_matches('/')
+}
+
+fn file_name_of(path: &str) -> Option<&str> {
+    path.rsplit(['/', '\\']).next().filter(|name| !name.is_empty())
+}
+
+fn find_in_directory(directory: &Path, file_name: &str, ignore_case: bool) -> Option<PathBuf> {
+    let candidate = directory.join(file_name);
+    if candidate.is_file() {
+        return Some(candidate);
+    }
+    if !ignore_case {
+        return None;
+    }
+
+    std::fs::read_dir(directory).ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.is_file() && path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.eq_ignore_ascii_case(file_name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(matrix: &glm::Mat3, uv: glm::Vec2) -> glm::Vec2 {
+        let result = *matrix * glm::vec3(uv.x, uv.y, 1.0);
+        glm::vec2(result.x, result.y)
+    }
+
+    /* Empty directory unique to the test, files are created empty as only their existence matters */
+    fn create_layout(test: &str, files: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("fbximport_texture_{}_{}", test, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for file in files {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, b"").unwrap();
+        }
+        root
+    }
+
+    fn create_texture(file_name: &str, relative_file_name: &str, source_directory: Option<PathBuf>) -> Texture {
+        let mut texture = Texture::new(1, "Texture".to_string());
+        texture.file_name = file_name.to_string();
+        texture.relative_file_name = relative_file_name.to_string();
+        texture.source_directory = source_directory;
+        texture
+    }
+
+    #[test]
+    fn resolve_path_should_find_relative_file_name_next_to_the_source_file() {
+        // Arrange
+        let root = create_layout("relative", &["scene/textures/wood.png"]);
+        let texture = create_texture("C:\\work\\textures\\wood.png", "textures\\wood.png", Some(root.join("scene")));
+
+        // Act
+        let path = texture.resolve_path(&[]);
+
+        // Assert
+        assert_eq!(path, Some(root.join("scene/textures/wood.png")));
+    }
+
+    #[test]
+    fn resolve_path_should_find_file_name_in_search_dirs() {
+        // Arrange
+        let root = create_layout("search", &["second/Wood.PNG"]);
+        let texture = create_texture("C:\\work\\wood.png", "..\\work\\wood.png", Some(root.clone()));
+        let search_dirs = vec![root.join("first"), root.join("second")];
+
+        // Act
+        let exact = texture.resolve_path(&search_dirs);
+        let ignoring_case = texture.resolve_path_with_case(&search_dirs, true);
+
+        // Assert
+        assert_eq!(exact, None);
+        assert_eq!(ignoring_case, Some(root.join("second/Wood.PNG")));
+    }
+
+    #[test]
+    fn resolve_path_should_fall_back_to_file_name() {
+        // Arrange
+        let root = create_layout("absolute", &["elsewhere/wood.png"]);
+        let file_name = root.join("elsewhere/wood.png").to_str().unwrap().to_string();
+        let texture = create_texture(&file_name, "", None);
+
+        // Act
+        let path = texture.resolve_path(std::slice::from_ref(&root));
+
+        // Assert
+        assert_eq!(path, Some(PathBuf::from(file_name)));
+    }
+
+    #[test]
+    fn matrix_should_scale_then_rotate_then_translate() {
+        // Arrange
+        let transform = UvTransform {
+            translation: glm::vec2(0.5, 0.0),
+            scale: glm::vec2(2.0, 1.0),
+            rotation: 90.0,
+        };
+
+        // Act
+        let matrix = transform.matrix();
+
+        // Assert
+        let uv = apply(&matrix, glm::vec2(1.0, 0.0));
+        assert!((uv.x - 0.5).abs() < 1e-6);
+        assert!((uv.y - 2.0).abs() < 1e-6);
+        assert_eq!(apply(&UvTransform::identity().matrix(), glm::vec2(0.25, 0.75)), glm::vec2(0.25, 0.75));
+    }
+}
\ No newline at end of file
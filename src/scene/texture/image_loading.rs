@@ -0,0 +1,172 @@
+use crate::scene::texture::Texture;
+use image::{DynamicImage, ImageError, ImageFormat};
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum TextureError {
+    // Not embedded, and the resolver found no file for it
+    NotFound { texture: String },
+    Io { path: PathBuf, error: std::io::Error },
+    // Image in a format the image crate cannot decode, format names it as far as it could be recognized
+    UnsupportedFormat { texture: String, format: String },
+    Decode { texture: String, error: ImageError },
+}
+
+impl std::fmt::Display for TextureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextureError::NotFound { texture } =>
+                write!(f, "Texture '{}' is not embedded and its file could not be found", texture),
+            TextureError::Io { path, error } =>
+                write!(f, "Could not read '{}': {}", path.display(), error),
+            TextureError::UnsupportedFormat { texture, format } =>
+                write!(f, "Texture '{}' is stored as {}, which can not be decoded", texture, format),
+            TextureError::Decode { texture, error } =>
+                write!(f, "Could not decode texture '{}': {}", texture, error),
+        }
+    }
+}
+
+/* File signatures, formats the image crate has no decoder for are listed without one so they can be named */
+const SIGNATURES: [(&[u8], &str, Option<ImageFormat>); 12] = [
+    (b"\x89PNG\r\n\x1a\n", "PNG", Some(ImageFormat::Png)),
+    (b"\xff\xd8\xff", "JPEG", Some(ImageFormat::Jpeg)),
+    (b"GIF8", "GIF", Some(ImageFormat::Gif)),
+    (b"BM", "BMP", Some(ImageFormat::Bmp)),
+    (b"II*\0", "TIFF", Some(ImageFormat::Tiff)),
+    (b"MM\0*", "TIFF", Some(ImageFormat::Tiff)),
+    (b"DDS ", "DDS", Some(ImageFormat::Dds)),
+    (b"#?RADIANCE", "HDR", Some(ImageFormat::Hdr)),
+    (b"8BPS", "PSD", None),
+    (b"\xabKTX", "KTX", None),
+    (b"v/1\x01", "OpenEXR", None),
+    (b"\0\0\0\x0cjP  ", "JPEG 2000", None),
+];
+
+// Only version 2 TGA files end with this, older ones have no signature at all
+const TGA_FOOTER: &[u8] = b"TRUEVISION-XFILE.\0";
+
+/* Format of an image file by its leading bytes. None when nothing matched, which for texture files most likely
+   means a TGA without footer. */
+fn detect_format(bytes: &[u8]) -> Option<(&'static str, Option<ImageFormat>)> {
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some(("WebP", Some(ImageFormat::WebP)));
+    }
+    if bytes.ends_with(TGA_FOOTER) {
+        return Some(("TGA", Some(ImageFormat::Tga)));
+    }
+
+    SIGNATURES.iter()
+        .find(|(signature, _, _)| bytes.starts_with(signature))
+        .map(|(_, name, format)| (*name, *format))
+}
+
+fn decode(texture: &Texture, bytes: &[u8]) -> Result<DynamicImage, TextureError> {
+    let (name, format) = detect_format(bytes).unwrap_or(("TGA", Some(ImageFormat::Tga)));
+    let unsupported = |format: &str| TextureError::UnsupportedFormat { texture: texture.name.clone(), format: format.to_string() };
+
+    let format = format.ok_or_else(|| unsupported(name))?;
+    image::load_from_memory_with_format(bytes, format).map_err(|error| match error {
+        ImageError::Unsupported(_) => unsupported(name),
+        // Garbage decoded as a guessed TGA says more about the data than about TGA
+        _ if detect_format(bytes).is_none() => unsupported("an unrecognized format"),
+        error => TextureError::Decode { texture: texture.name.clone(), error },
+    })
+}
+
+impl Texture {
+    /* Decodes the embedded image, or reads the file resolve returns for textures without one. The format is taken
+       from the data, extensions are often wrong in files passed between tools. */
+    pub fn load_image<F>(&self, resolve: F) -> Result<DynamicImage, TextureError>
+        where F: FnOnce(&Texture) -> Option<PathBuf>
+    {
+        if let Some(content) = &self.content {
+            return decode(self, content);
+        }
+
+        let path = resolve(self).ok_or_else(|| TextureError::NotFound { texture: self.name.clone() })?;
+        let bytes = std::fs::read(&path).map_err(|error| TextureError::Io { path, error })?;
+        decode(self, &bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GenericImageView, ImageOutputFormat};
+
+    fn create_png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        DynamicImage::new_rgb8(width, height).write_to(&mut bytes, ImageOutputFormat::Png).unwrap();
+        bytes
+    }
+
+    fn create_embedded(content: Vec<u8>) -> Texture {
+        let mut texture = Texture::new(1, "Decal".to_string());
+        texture.content = Some(content);
+        texture
+    }
+
+    #[test]
+    fn load_image_should_decode_embedded_content() {
+        // Arrange
+        let texture = create_embedded(create_png(3, 2));
+
+        // Act
+        let image = texture.load_image(|_| panic!("embedded textures need no file")).unwrap();
+
+        // Assert
+        assert_eq!((image.width(), image.height()), (3, 2));
+    }
+
+    #[test]
+    fn load_image_should_detect_format_from_content_not_extension() {
+        // Arrange
+        let path = std::env::temp_dir().join(format!("fbximport_texture_png_as_tga_{}.tga", std::process::id()));
+        std::fs::write(&path, create_png(4, 4)).unwrap();
+        let texture = Texture::new(1, "Decal".to_string());
+
+        // Act
+        let image = texture.load_image(|_| Some(path.clone()));
+
+        // Assert
+        assert_eq!(image.unwrap().width(), 4);
+    }
+
+    #[test]
+    fn load_image_should_name_formats_it_can_not_decode() {
+        // Arrange
+        let psd = create_embedded(b"8BPS\0\x01\0\0\0\0\0\0".to_vec());
+        let mut dds = b"DDS ".to_vec();
+        dds.extend_from_slice(&[0; 124]);
+        dds[4] = 124;
+        // Caps, height, width and pixel format flags, 4x4 pixels
+        dds[8..10].copy_from_slice(&[0x07, 0x10]);
+        dds[12] = 4;
+        dds[16] = 4;
+        dds[76] = 32;
+        dds[80] = 4;
+        dds[84..88].copy_from_slice(b"BC7 ");
+        let dds = create_embedded(dds);
+
+        // Act
+        let errors = [psd.load_image(|_| None), dds.load_image(|_| None)];
+
+        // Assert
+        for (error, expected) in errors.iter().zip(&["PSD", "DDS"]) {
+            match error {
+                Err(TextureError::UnsupportedFormat { format, .. }) => assert_eq!(format, expected),
+                other => panic!("expected an unsupported format error, got {:?}", other.as_ref().map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn load_image_should_fail_without_content_or_file() {
+        // Act
+        let result = Texture::new(1, "Decal".to_string()).load_image(|_| None);
+
+        // Assert
+        assert!(matches!(result, Err(TextureError::NotFound { .. })));
+    }
+}
\ No newline at end of file
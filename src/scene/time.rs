@@ -0,0 +1,180 @@
+// FBX times are counted in ticks of 1/46186158000 of a second
+pub const TICKS_PER_SECOND: i64 = 46_186_158_000;
+
+/* Frame rate the file was authored at, the TimeMode entry of GlobalSettings */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeMode {
+    // The application default, 30 frames per second
+    Default,
+    Frames120,
+    Frames100,
+    Frames60,
+    Frames50,
+    Frames48,
+    Frames30,
+    Frames30Drop,
+    // 29.97 with drop frame timecode
+    NtscDropFrame,
+    // 29.97
+    NtscFullFrame,
+    // 25
+    Pal,
+    Frames24,
+    Frames1000,
+    // 23.976
+    FilmFullFrame,
+    // Rate given by the CustomFrameRate entry
+    Custom(f64),
+    Frames96,
+    Frames72,
+    Frames59_94,
+    Frames119_88,
+}
+
+impl TimeMode {
+    /* Mode from the TimeMode enum value, custom_rate is only used for the custom mode */
+    pub fn from_fbx(value: i64, custom_rate: f64) -> Self {
+        match value {
+            1 => TimeMode::Frames120,
+            2 => TimeMode::Frames100,
+            3 => TimeMode::Frames60,
+            4 => TimeMode::Frames50,
+            5 => TimeMode::Frames48,
+            6 => TimeMode::Frames30,
+            7 => TimeMode::Frames30Drop,
+            8 => TimeMode::NtscDropFrame,
+            9 => TimeMode::NtscFullFrame,
+            10 => TimeMode::Pal,
+            11 => TimeMode::Frames24,
+            12 => TimeMode::Frames1000,
+            13 => TimeMode::FilmFullFrame,
+            14 => TimeMode::Custom(custom_rate),
+            15 => TimeMode::Frames96,
+            16 => TimeMode::Frames72,
+            17 => TimeMode::Frames59_94,
+            18 => TimeMode::Frames119_88,
+            _ => TimeMode::Default,
+        }
+    }
+
+    /* Frames per second as numerator and denominator, so the NTSC rates stay exact */
+    fn rate(&self) -> (f64, f64) {
+        match self {
+            TimeMode::Default | TimeMode::Frames30 | TimeMode::Frames30Drop => (30.0, 1.0),
+            TimeMode::Frames120 => (120.0, 1.0),
+            TimeMode::Frames100 => (100.0, 1.0),
+            TimeMode::Frames60 => (60.0, 1.0),
+            TimeMode::Frames50 => (50.0, 1.0),
+            TimeMode::Frames48 => (48.0, 1.0),
+            TimeMode::NtscDropFrame | TimeMode::NtscFullFrame => (30000.0, 1001.0),
+            TimeMode::Pal => (25.0, 1.0),
+            TimeMode::Frames24 => (24.0, 1.0),
+            TimeMode::Frames1000 => (1000.0, 1.0),
+            TimeMode::FilmFullFrame => (24000.0, 1001.0),
+            TimeMode::Custom(rate) => (*rate, 1.0),
+            TimeMode::Frames96 => (96.0, 1.0),
+            TimeMode::Frames72 => (72.0, 1.0),
+            TimeMode::Frames59_94 => (60000.0, 1001.0),
+            TimeMode::Frames119_88 => (120000.0, 1001.0),
+        }
+    }
+
+    pub fn frames_per_second(&self) -> f64 {
+        let (numerator, denominator) = self.rate();
+        numerator / denominator
+    }
+}
+
+/* Point in time in FBX ticks */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KTime(pub i64);
+
+impl KTime {
+    /* Rounds to the nearest tick */
+    pub fn from_seconds(seconds: f64) -> Self {
+        KTime((seconds * TICKS_PER_SECOND as f64).round() as i64)
+    }
+
+    pub fn seconds(&self) -> f64 {
+        self.0 as f64 / TICKS_PER_SECOND as f64
+    }
+
+    /* Rounds to the nearest tick, frames of the NTSC rates do not fall on whole ticks */
+    pub fn from_frames(frames: f64, mode: TimeMode) -> Self {
+        let (numerator, denominator) = mode.rate();
+        KTime((frames * TICKS_PER_SECOND as f64 * denominator / numerator).round() as i64)
+    }
+
+    pub fn frames(&self, mode: TimeMode) -> f64 {
+        let (numerator, denominator) = mode.rate();
+        self.0 as f64 * numerator / (TICKS_PER_SECOND as f64 * denominator)
+    }
+
+    /* Nearest whole frame */
+    pub fn frame(&self, mode: TimeMode) -> i64 {
+        self.frames(mode).round() as i64
+    }
+
+    pub fn ticks(&self) -> i64 {
+        self.0
+    }
+}
+
+/* Frame rate and time span from GlobalSettings */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeSettings {
+    pub mode: TimeMode,
+    pub start: KTime,
+    pub stop: KTime,
+}
+
+impl Default for TimeSettings {
+    fn default() -> Self {
+        TimeSettings {
+            mode: TimeMode::Default,
+            start: KTime(0),
+            stop: KTime(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seconds_should_convert_both_ways_without_drift() {
+        // Act
+        let time = KTime::from_seconds(1.0);
+
+        // Assert
+        assert_eq!(time, KTime(TICKS_PER_SECOND));
+        assert_eq!(time.seconds(), 1.0);
+    }
+
+    #[test]
+    fn frames_should_convert_both_ways_without_drift() {
+        // Act
+        let frame = KTime::from_frames(1.0, TimeMode::Frames30);
+        let second = KTime::from_frames(30.0, TimeMode::Frames30);
+
+        // Assert
+        assert_eq!(frame, KTime(1_539_538_600));
+        assert_eq!(frame.frames(TimeMode::Frames30), 1.0);
+        assert_eq!(second, KTime(TICKS_PER_SECOND));
+        assert_eq!(KTime(TICKS_PER_SECOND).frames(TimeMode::Frames30), 30.0);
+    }
+
+    #[test]
+    fn frame_should_round_ntsc_frames_back_to_whole_frames() {
+        // Arrange
+        let mode = TimeMode::NtscFullFrame;
+
+        // Act
+        let frames: Vec<i64> = (0..100_000).step_by(997).map(|f| KTime::from_frames(f as f64, mode).frame(mode)).collect();
+
+        // Assert
+        assert_eq!(frames, (0..100_000).step_by(997).collect::<Vec<i64>>());
+        assert!((KTime::from_seconds(1001.0).frames(mode) - 30000.0).abs() < 1e-9);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,69 @@
+use std::fmt;
+use std::time::Duration;
+
+/* Time spent in one mesh processor summed over every mesh of the scene, with the size of the meshes before and after */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProcessorTiming {
+    pub name: String,
+    pub duration: Duration,
+    // Number of meshes the processor ran on
+    pub meshes: usize,
+    pub faces_in: usize,
+    pub faces_out: usize,
+    pub vertices_in: usize,
+    pub vertices_out: usize,
+}
+
+impl ProcessorTiming {
+    pub fn new(name: &str) -> Self {
+        ProcessorTiming {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /* Adds the numbers of another run of the same processor */
+    #[cfg(feature = "parallel")]
+    pub(crate) fn add(&mut self, other: &ProcessorTiming) {
+        self.duration += other.duration;
+        self.meshes += other.meshes;
+        self.faces_in += other.faces_in;
+        self.faces_out += other.faces_out;
+        self.vertices_in += other.vertices_in;
+        self.vertices_out += other.vertices_out;
+    }
+}
+
+/* Wall clock time spent in each phase of an import, see Scene::timings. Durations of processors that ran on several
+   meshes concurrently are summed, so with the parallel feature they can add up to more than the import took. */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportTimings {
+    // Reading the header and the node records
+    pub parse: Duration,
+    // Turning the node records into meshes, nodes, materials and the rest of the scene
+    pub conversion: Duration,
+    // One entry per processor, in the order they ran
+    pub processors: Vec<ProcessorTiming>,
+}
+
+impl ImportTimings {
+    pub fn total(&self) -> Duration {
+        self.parse + self.conversion + self.processors.iter().map(|p| p.duration).sum::<Duration>()
+    }
+}
+
+pub(crate) fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl fmt::Display for ImportTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<32} {:>10.2} ms", "parse", millis(self.parse))?;
+        writeln!(f, "{:<32} {:>10.2} ms", "conversion", millis(self.conversion))?;
+        for processor in &self.processors {
+            writeln!(f, "{:<32} {:>10.2} ms  faces {} -> {}, vertices {} -> {}", processor.name, millis(processor.duration),
+                     processor.faces_in, processor.faces_out, processor.vertices_in, processor.vertices_out)?;
+        }
+        write!(f, "{:<32} {:>10.2} ms", "total", millis(self.total()))
+    }
+}
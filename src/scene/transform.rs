@@ -0,0 +1,324 @@
+use std::ops::Mul;
+
+/* A rotation as a unit quaternion */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quat {
+    pub fn identity() -> Self {
+        Quat { x: 0.0, y: 0.0, z: 0.0, w: 1.0 }
+    }
+
+    /* Rotation around a unit length axis, angle in radians */
+    pub fn from_axis_angle(axis: glm::Vec3, angle: f32) -> Self {
+        let (sin, cos) = (angle * 0.5).sin_cos();
+        Quat { x: axis.x * sin, y: axis.y * sin, z: axis.z * sin, w: cos }
+    }
+
+    /* Euler angles in degrees applied in X, Y, Z order, the default rotation order of FBX */
+    pub fn from_euler_degrees(angles: glm::Vec3) -> Self {
+        let x = Quat::from_axis_angle(glm::vec3(1.0, 0.0, 0.0), angles.x.to_radians());
+        let y = Quat::from_axis_angle(glm::vec3(0.0, 1.0, 0.0), angles.y.to_radians());
+        let z = Quat::from_axis_angle(glm::vec3(0.0, 0.0, 1.0), angles.z.to_radians());
+        z * y * x
+    }
+
+    /* From a rotation matrix without scaling, picking the largest component to divide by for precision */
+    pub fn from_mat4(m: &glm::Mat4) -> Self {
+        let trace = m[0].x + m[1].y + m[2].z;
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quat { x: (m[1].z - m[2].y) / s, y: (m[2].x - m[0].z) / s, z: (m[0].y - m[1].x) / s, w: 0.25 * s }
+        } else if m[0].x > m[1].y && m[0].x > m[2].z {
+            let s = (1.0 + m[0].x - m[1].y - m[2].z).sqrt() * 2.0;
+            Quat { x: 0.25 * s, y: (m[1].x + m[0].y) / s, z: (m[2].x + m[0].z) / s, w: (m[1].z - m[2].y) / s }
+        } else if m[1].y > m[2].z {
+            let s = (1.0 + m[1].y - m[0].x - m[2].z).sqrt() * 2.0;
+            Quat { x: (m[1].x + m[0].y) / s, y: 0.25 * s, z: (m[2].y + m[1].z) / s, w: (m[2].x - m[0].z) / s }
+        } else {
+            let s = (1.0 + m[2].z - m[0].x - m[1].y).sqrt() * 2.0;
+            Quat { x: (m[2].x + m[0].z) / s, y: (m[2].y + m[1].z) / s, z: 0.25 * s, w: (m[0].y - m[1].x) / s }
+        };
+        q.normalize()
+    }
+
+    pub fn to_mat4(&self) -> glm::Mat4 {
+        let Quat { x, y, z, w } = *self;
+        glm::mat4(
+            1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + z * w), 2.0 * (x * z - y * w), 0.0,
+            2.0 * (x * y - z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + x * w), 0.0,
+            2.0 * (x * z + y * w), 2.0 * (y * z - x * w), 1.0 - 2.0 * (x * x + y * y), 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        )
+    }
+
+    pub fn dot(&self, other: &Quat) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn normalize(&self) -> Self {
+        let length = self.dot(self).sqrt();
+        Quat { x: self.x / length, y: self.y / length, z: self.z / length, w: self.w / length }
+    }
+
+    /* The opposite rotation */
+    pub fn conjugate(&self) -> Self {
+        Quat { x: -self.x, y: -self.y, z: -self.z, w: self.w }
+    }
+
+    pub fn rotate(&self, v: glm::Vec3) -> glm::Vec3 {
+        let q = glm::vec3(self.x, self.y, self.z);
+        let t = glm::cross(q, v) * 2.0;
+        v + t * self.w + glm::cross(q, t)
+    }
+
+    /* Interpolates along the shorter arc between the rotations at constant angular speed, t from 0 to 1 */
+    pub fn slerp(&self, other: &Quat, t: f32) -> Self {
+        let mut other = *other;
+        let mut cos = self.dot(&other);
+        if cos < 0.0 {
+            other = Quat { x: -other.x, y: -other.y, z: -other.z, w: -other.w };
+            cos = -cos;
+        }
+
+        // Close rotations divide by a sine near zero, where a straight line is as good
+        let (a, b) = if cos > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+        Quat {
+            x: self.x * a + other.x * b,
+            y: self.y * a + other.y * b,
+            z: self.z * a + other.z * b,
+            w: self.w * a + other.w * b,
+        }.normalize()
+    }
+}
+
+/* Rotates by other first, then by self */
+impl Mul for Quat {
+    type Output = Quat;
+
+    fn mul(self, other: Quat) -> Quat {
+        Quat {
+            x: self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            y: self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            z: self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+            w: self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+        }
+    }
+}
+
+/* Scaling, then rotation, then translation */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: glm::Vec3,
+    pub rotation: Quat,
+    pub scale: glm::Vec3,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform {
+            translation: glm::vec3(0.0, 0.0, 0.0),
+            rotation: Quat::identity(),
+            scale: glm::vec3(1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn new(translation: glm::Vec3, rotation: Quat, scale: glm::Vec3) -> Self {
+        Transform { translation, rotation, scale }
+    }
+
+    /* From the translation, euler rotation in degrees and scaling properties of a Model */
+    pub fn from_fbx(translation: glm::Vec3, rotation: glm::Vec3, scaling: glm::Vec3) -> Self {
+        Transform::new(translation, Quat::from_euler_degrees(rotation), scaling)
+    }
+
+    pub fn to_mat4(&self) -> glm::Mat4 {
+        let mut m = self.rotation.to_mat4();
+        m[0] = m[0] * self.scale.x;
+        m[1] = m[1] * self.scale.y;
+        m[2] = m[2] * self.scale.z;
+        m[3] = glm::vec4(self.translation.x, self.translation.y, self.translation.z, 1.0);
+        m
+    }
+
+    /* Splits a matrix without shear or projection into translation, rotation and scaling. A mirroring matrix could
+       come from negating any odd number of axes, the sign always goes to the x scale and the rotation makes up for
+       the rest, so a transform with a negative y scale comes back with a negative x scale and a half turn. */
+    pub fn from_mat4(m: &glm::Mat4) -> Self {
+        let axis = |i: usize| glm::vec3(m[i].x, m[i].y, m[i].z);
+        let mut scale = glm::vec3(glm::length(axis(0)), glm::length(axis(1)), glm::length(axis(2)));
+        if glm::dot(glm::cross(axis(0), axis(1)), axis(2)) < 0.0 {
+            scale.x = -scale.x;
+        }
+
+        let mut rotation = *m;
+        rotation[0] = rotation[0] / scale.x;
+        rotation[1] = rotation[1] / scale.y;
+        rotation[2] = rotation[2] / scale.z;
+        Transform {
+            translation: axis(3),
+            rotation: Quat::from_mat4(&rotation),
+            scale,
+        }
+    }
+
+    /* Undoes the transform. Exact for uniform scaling, a non-uniform scale under a rotation would need shear to undo
+       and is only inverted per axis. */
+    pub fn inverse(&self) -> Self {
+        let rotation = self.rotation.conjugate();
+        let scale = glm::vec3(1.0 / self.scale.x, 1.0 / self.scale.y, 1.0 / self.scale.z);
+        Transform {
+            translation: rotation.rotate(-self.translation) * scale,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn transform_point(&self, point: glm::Vec3) -> glm::Vec3 {
+        self.rotation.rotate(point * self.scale) + self.translation
+    }
+
+    /* Translation and scaling interpolate linearly, rotation along the shorter arc, t from 0 to 1 */
+    pub fn lerp(&self, other: &Transform, t: f32) -> Self {
+        Transform {
+            translation: glm::mix(self.translation, other.translation, glm::vec3(t, t, t)),
+            rotation: self.rotation.slerp(&other.rotation, t),
+            scale: glm::mix(self.scale, other.scale, glm::vec3(t, t, t)),
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+/* Applies other first, then self, like multiplying their matrices. Exact when the scaling of self is uniform, a
+   non-uniform scale over the rotation of other would shear, which a Transform cannot hold. */
+impl Mul for Transform {
+    type Output = Transform;
+
+    fn mul(self, other: Transform) -> Transform {
+        Transform {
+            translation: self.transform_point(other.translation),
+            rotation: self.rotation * other.rotation,
+            scale: self.scale * other.scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_mat4_near(actual: &glm::Mat4, expected: &glm::Mat4) {
+        for column in 0..4 {
+            let difference = actual[column] - expected[column];
+            assert!(glm::length(difference) < 1e-4, "{:?} is not {:?}", actual, expected);
+        }
+    }
+
+    fn assert_transform_near(actual: &Transform, expected: &Transform) {
+        assert!(glm::length(actual.translation - expected.translation) < 1e-4, "{:?} is not {:?}", actual, expected);
+        assert!(glm::length(actual.scale - expected.scale) < 1e-4, "{:?} is not {:?}", actual, expected);
+        // q and -q are the same rotation
+        assert!(actual.rotation.dot(&expected.rotation).abs() > 1.0 - 1e-4, "{:?} is not {:?}", actual, expected);
+    }
+
+    fn grid() -> Vec<Transform> {
+        let translations = [glm::vec3(0.0, 0.0, 0.0), glm::vec3(1.5, -2.0, 3.0)];
+        let rotations = [glm::vec3(0.0, 0.0, 0.0), glm::vec3(30.0, 0.0, 0.0), glm::vec3(0.0, 90.0, 0.0),
+            glm::vec3(10.0, 20.0, 170.0), glm::vec3(-45.0, 60.0, 180.0)];
+        let scales = [glm::vec3(1.0, 1.0, 1.0), glm::vec3(2.0, 0.5, 3.0), glm::vec3(-1.0, 2.0, 1.0),
+            glm::vec3(1.0, -2.0, 0.5), glm::vec3(-1.0, -1.0, -1.0)];
+
+        let mut transforms = Vec::new();
+        for translation in translations.iter() {
+            for rotation in rotations.iter() {
+                for scale in scales.iter() {
+                    transforms.push(Transform::from_fbx(*translation, *rotation, *scale));
+                }
+            }
+        }
+        transforms
+    }
+
+    #[test]
+    fn from_mat4_should_decompose_the_composed_matrix() {
+        for transform in grid() {
+            // Act
+            let decomposed = Transform::from_mat4(&transform.to_mat4());
+
+            // Assert
+            assert_mat4_near(&decomposed.to_mat4(), &transform.to_mat4());
+            // Only mirroring on x survives as it was, other mirrorings move the sign to x
+            if transform.scale.y > 0.0 && transform.scale.z > 0.0 {
+                assert_transform_near(&decomposed, &transform);
+            }
+        }
+    }
+
+    #[test]
+    fn from_euler_degrees_should_rotate_around_x_then_y_then_z() {
+        // Arrange
+        let rotation = Quat::from_euler_degrees(glm::vec3(90.0, 0.0, 90.0));
+
+        // Act
+        let rotated = rotation.rotate(glm::vec3(0.0, 1.0, 0.0));
+
+        // Assert
+        // x takes y to z, which z leaves alone
+        assert!(glm::length(rotated - glm::vec3(0.0, 0.0, 1.0)) < 1e-5);
+    }
+
+    #[test]
+    fn mul_should_match_the_product_of_the_matrices_for_uniform_scaling() {
+        // Arrange
+        let a = Transform::from_fbx(glm::vec3(1.0, 2.0, 3.0), glm::vec3(0.0, 45.0, 10.0), glm::vec3(2.0, 2.0, 2.0));
+        let b = Transform::from_fbx(glm::vec3(-1.0, 0.5, 0.0), glm::vec3(30.0, 0.0, 0.0), glm::vec3(1.0, 3.0, 0.5));
+
+        // Act
+        let product = a * b;
+
+        // Assert
+        assert_mat4_near(&product.to_mat4(), &(a.to_mat4() * b.to_mat4()));
+    }
+
+    #[test]
+    fn inverse_should_undo_the_transform() {
+        // Arrange
+        let transform = Transform::from_fbx(glm::vec3(1.0, 2.0, 3.0), glm::vec3(20.0, -30.0, 60.0), glm::vec3(2.0, 2.0, 2.0));
+
+        // Act
+        let identity = transform * transform.inverse();
+
+        // Assert
+        assert_transform_near(&identity, &Transform::identity());
+    }
+
+    #[test]
+    fn lerp_should_interpolate_rotation_along_the_arc() {
+        // Arrange
+        let from = Transform::identity();
+        let to = Transform::from_fbx(glm::vec3(2.0, 0.0, 0.0), glm::vec3(0.0, 0.0, 90.0), glm::vec3(3.0, 3.0, 3.0));
+
+        // Act
+        let halfway = from.lerp(&to, 0.5);
+
+        // Assert
+        let expected = Transform::from_fbx(glm::vec3(1.0, 0.0, 0.0), glm::vec3(0.0, 0.0, 45.0), glm::vec3(2.0, 2.0, 2.0));
+        assert_transform_near(&halfway, &expected);
+    }
+}
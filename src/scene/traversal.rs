@@ -0,0 +1,125 @@
+use crate::scene::node::{InheritType, Node};
+
+fn scale(s: glm::Vec3) -> glm::Mat4 {
+    glm::mat4(
+        s.x, 0.0, 0.0, 0.0,
+        0.0, s.y, 0.0, 0.0,
+        0.0, 0.0, s.z, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    )
+}
+
+/* What a node passes down to its children to compose their world transforms: its world matrix, and the rotation and
+   scaling the inherit types other than RSrs need separately */
+#[derive(Clone, Copy)]
+pub(crate) struct Inherited {
+    pub(crate) world: glm::Mat4,
+    rotation: glm::Mat4,
+    scaling: glm::Vec3,
+    // Local scaling of the node, which Rrs children leave out
+    local_scaling: glm::Vec3,
+}
+
+impl Inherited {
+    /* The scene root */
+    pub(crate) fn root() -> Self {
+        let one = glm::vec3(1.0, 1.0, 1.0);
+        Inherited {
+            world: scale(one),
+            rotation: scale(one),
+            scaling: one,
+            local_scaling: one,
+        }
+    }
+
+    /* Composes the local transform of a child of this one as its inherit type says */
+    pub(crate) fn child(&self, node: &Node) -> Self {
+        let local = &node.transform;
+        let local_rotation = local.rotation.to_mat4();
+        let t = self.world * glm::vec4(local.translation.x, local.translation.y, local.translation.z, 1.0);
+        let mut linear = self.world;
+        linear[3] = glm::vec4(0.0, 0.0, 0.0, 1.0);
+
+        let parent_scaling = match node.inherit_type {
+            InheritType::Rrs => self.scaling / self.local_scaling,
+            _ => self.scaling,
+        };
+        let mut world = match node.inherit_type {
+            InheritType::RSrs => linear * local_rotation * scale(local.scale),
+            _ => self.rotation * local_rotation * scale(parent_scaling) * scale(local.scale),
+        };
+        world[3] = glm::vec4(t.x, t.y, t.z, 1.0);
+
+        Inherited {
+            world,
+            rotation: self.rotation * local_rotation,
+            scaling: parent_scaling * local.scale,
+            local_scaling: local.scale,
+        }
+    }
+}
+
+/* Indices of the nodes without a parent, in the order they are stored */
+fn roots(nodes: &[Node]) -> Vec<usize> {
+    (0..nodes.len()).rev().filter(|i| nodes[*i].parent.is_none()).collect()
+}
+
+/* Depth first walk over the nodes of a scene, see Scene::traverse. Every node is visited at most once, so a parent
+   loop in a hand-built scene cuts the walk short instead of repeating it. */
+pub struct Traversal<'a> {
+    nodes: &'a [Node],
+    // Nodes left to visit with their depth and what their parent passes down, the next one last
+    pending: Vec<(usize, usize, Inherited)>,
+    visited: Vec<bool>,
+}
+
+impl<'a> Traversal<'a> {
+    pub(crate) fn new(nodes: &'a [Node]) -> Self {
+        Traversal {
+            nodes,
+            pending: roots(nodes).into_iter().map(|i| (i, 0, Inherited::root())).collect(),
+            visited: vec![false; nodes.len()],
+        }
+    }
+}
+
+impl<'a> Iterator for Traversal<'a> {
+    type Item = (usize, &'a Node, glm::Mat4);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((index, depth, parent)) = self.pending.pop() {
+            if self.visited[index] {
+                continue;
+            }
+            self.visited[index] = true;
+
+            let node = &self.nodes[index];
+            let inherited = parent.child(node);
+            // Reversed so the first child is visited next
+            self.pending.extend(node.children.iter().rev().map(|c| (*c, depth + 1, inherited)));
+            return Some((depth, node, inherited.world));
+        }
+        None
+    }
+}
+
+/* Visits the nodes in the same order as Traversal and lets f change them. The world transform given to f is that of
+   the node before f runs, the transforms of its children are composed from the node as f left it. Brings the cached
+   world transform of every node visited up to date. */
+pub(crate) fn traverse_mut<F: FnMut(usize, &mut Node, glm::Mat4)>(nodes: &mut [Node], mut f: F) {
+    let mut pending: Vec<(usize, usize, Inherited)> = roots(nodes).into_iter().map(|i| (i, 0, Inherited::root())).collect();
+    let mut visited = vec![false; nodes.len()];
+    while let Some((index, depth, parent)) = pending.pop() {
+        if visited[index] {
+            continue;
+        }
+        visited[index] = true;
+
+        let node = &mut nodes[index];
+        f(depth, node, parent.child(node).world);
+        let inherited = parent.child(node);
+        node.world = inherited.world;
+        node.world_dirty = false;
+        pending.extend(node.children.iter().rev().map(|c| (*c, depth + 1, inherited)));
+    }
+}
@@ -0,0 +1,24 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/* Turns the warnings printed while importing and processing files on or off for the whole process. Errors that abort
+   an import are printed either way. */
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/* Same as eprintln, unless warnings have been turned off */
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        if crate::warnings::enabled() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use warning;
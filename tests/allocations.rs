@@ -0,0 +1,83 @@
+use fbximport::fbx::bench::Geometry;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/* Counts allocations made on the current thread, so tests running in parallel do not disturb each other. Lives in
+   its own test binary so the rest of the tests keep the system allocator. */
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static REALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = REALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/* Allocations and reallocations made by f on this thread */
+fn count_allocations<T, F: FnOnce() -> T>(f: F) -> (T, usize, usize) {
+    let allocations_before = ALLOCATIONS.with(|a| a.get());
+    let reallocations_before = REALLOCATIONS.with(|a| a.get());
+    let result = f();
+    let allocations = ALLOCATIONS.with(|a| a.get()) - allocations_before;
+    let reallocations = REALLOCATIONS.with(|a| a.get()) - reallocations_before;
+    (result, allocations, reallocations)
+}
+
+/* Vertices for every index used, three coordinates each */
+fn create_geometry(indices: Vec<i32>) -> Geometry {
+    let vertex_count = indices.iter().map(|i| if *i < 0 { *i ^ -1 } else { *i }).max().map_or(0, |i| i + 1);
+    Geometry::new((0..vertex_count * 3).map(|c| c as f64).collect(), indices)
+}
+
+#[test]
+fn read_faces_should_allocate_little_more_than_the_faces() {
+    // Arrange
+    let face_count = 10000;
+    let indices: Vec<i32> = (0..face_count)
+        .flat_map(|f| vec![f * 4, f * 4 + 1, f * 4 + 2, (f * 4 + 3) ^ -1])
+        .collect();
+    let geometry = create_geometry(indices);
+
+    // Act
+    let (faces, allocations, reallocations) = count_allocations(|| geometry.read_faces());
+
+    // Assert
+    assert_eq!(faces, face_count as usize);
+    // About one allocation for the face list plus one per face. Copying the index array or regrowing the list for
+    // every few faces would go past these.
+    assert!(allocations <= face_count as usize + 8, "{} allocations for {} faces", allocations, face_count);
+    assert!(reallocations <= 4, "{} reallocations for {} faces", reallocations, face_count);
+}
+
+#[test]
+fn read_vertices_should_not_grow_the_vertex_list() {
+    // Arrange
+    let indices: Vec<i32> = (0..3000).map(|i| if i % 3 == 2 { i ^ -1 } else { i }).collect();
+    let geometry = create_geometry(indices);
+
+    // Act
+    let (vertices, allocations, reallocations) = count_allocations(|| geometry.read_vertices());
+
+    // Assert
+    assert_eq!(vertices, 3000);
+    // The list is sized up front, growing it vertex by vertex would reallocate a dozen times
+    assert!(allocations <= 2, "{} allocations", allocations);
+    assert!(reallocations <= 1, "{} reallocations", reallocations);
+}
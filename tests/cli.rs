@@ -0,0 +1,116 @@
+use fbximport::fbx::writer::{FbxNode, FbxWriter};
+use fbximport::fbx::{import_fbx_from_bytes, read_file_info, ArrayProperty, FbxString, ImportOptions, PropertyRecordType};
+use fbximport::mesh_processor::triangulate_processor::TriangulateMeshProcessor;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn geometry(id: i64, name: &str, vertices: Vec<f64>, indices: Vec<i32>) -> FbxNode {
+    FbxNode::new("Geometry")
+        .with_property(PropertyRecordType::SignedInt64(id))
+        .with_property(PropertyRecordType::String(FbxString::new(name).with_class("Geometry")))
+        .with_property(PropertyRecordType::String(FbxString::new("Mesh")))
+        .with_child(FbxNode::new("Vertices").with_property(PropertyRecordType::DoubleArray(ArrayProperty::from_values(vertices))))
+        .with_child(FbxNode::new("PolygonVertexIndex").with_property(PropertyRecordType::SignedInt32Array(ArrayProperty::from_values(indices))))
+}
+
+/* A quad and a triangle as separate meshes */
+fn create_file() -> Vec<u8> {
+    FbxWriter::new(7400)
+        .with_node(FbxNode::new("Objects")
+            .with_child(geometry(1, "quad", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0], vec![0, 1, 2, -4]))
+            .with_child(geometry(2, "triangle", vec![0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 1.0, 1.0], vec![0, 1, -3])))
+        .write()
+}
+
+fn directory(test: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("fbximport_cli_{}_{}", test, std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn fbximport(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_fbximport")).args(args).output().unwrap()
+}
+
+#[test]
+fn convert_should_write_an_obj_with_the_meshes_of_the_file() {
+    // Arrange
+    let directory = directory("convert");
+    let input = directory.join("scene.fbx");
+    let output = directory.join("scene.obj");
+    let bytes = create_file();
+    std::fs::write(&input, &bytes).unwrap();
+    let scene = import_fbx_from_bytes(&bytes, vec![Box::new(TriangulateMeshProcessor::new())], ImportOptions::new()).unwrap();
+    let info = read_file_info(&input).unwrap();
+
+    // Act
+    let result = fbximport(&["convert", input.to_str().unwrap(), "-o", output.to_str().unwrap(), "--quiet"]);
+    let obj = std::fs::read_to_string(&output).unwrap_or_default();
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    // Assert
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    assert!(result.stdout.is_empty());
+    let count = |prefix: &str| obj.lines().filter(|line| line.starts_with(prefix)).count();
+    assert_eq!(count("o "), scene.meshes().len());
+    assert_eq!(count("v "), info.meshes.iter().map(|m| m.vertices).sum::<usize>());
+    assert_eq!(count("f "), scene.meshes().iter().map(|m| m.triangles().count()).sum::<usize>());
+}
+
+#[test]
+fn convert_should_print_warnings_unless_quiet() {
+    // Arrange
+    let directory = directory("quiet");
+    let input = directory.join("scene.fbx");
+    // The quad is missing the negated index ending its polygon
+    let quad = geometry(1, "quad", vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0], vec![0, 1, 2, 3]);
+    std::fs::write(&input, FbxWriter::new(7400).with_node(FbxNode::new("Objects").with_child(quad)).write()).unwrap();
+    let convert = |quiet: &[&str]| fbximport(&[&["convert", input.to_str().unwrap(), "-o", directory.join("scene.obj").to_str().unwrap()], quiet].concat());
+
+    // Act
+    let verbose = convert(&[]);
+    let quiet = convert(&["--quiet"]);
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    // Assert
+    assert!(verbose.status.success());
+    assert!(String::from_utf8_lossy(&verbose.stderr).contains("does not end with a negated index"));
+    assert!(quiet.status.success());
+    assert!(quiet.stderr.is_empty(), "{}", String::from_utf8_lossy(&quiet.stderr));
+}
+
+#[test]
+fn convert_should_fail_readably_on_formats_it_cannot_write() {
+    // Arrange
+    let directory = directory("unsupported");
+    let input = directory.join("scene.fbx");
+    std::fs::write(&input, create_file()).unwrap();
+
+    // Act
+    let result = fbximport(&["convert", input.to_str().unwrap(), "-o", directory.join("scene.stl").to_str().unwrap()]);
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    // Assert
+    assert_eq!(result.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&result.stderr).starts_with("error: writing stl is not supported yet"));
+}
+
+#[test]
+fn info_should_exit_with_an_error_only_when_the_file_cannot_be_parsed() {
+    // Arrange
+    let directory = directory("info");
+    let input = directory.join("scene.fbx");
+    let broken = directory.join("broken.fbx");
+    std::fs::write(&input, create_file()).unwrap();
+    std::fs::write(&broken, b"not an fbx file").unwrap();
+
+    // Act
+    let result = fbximport(&["info", input.to_str().unwrap(), "--json"]);
+    let failed = fbximport(&["info", broken.to_str().unwrap()]);
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    // Assert
+    assert!(result.status.success());
+    assert!(String::from_utf8_lossy(&result.stdout).contains("\"name\":\"triangle\",\"vertices\":3,\"faces\":1"));
+    assert_eq!(failed.status.code(), Some(1));
+}